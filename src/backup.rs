@@ -0,0 +1,172 @@
+// File: src/backup.rs
+// Everything cfait keeps about tasks in one JSON file (`cfait backup` / `cfait restore`),
+// to recover from a lost disk or to move to another machine. Credentials and the rest
+// of the config are left out: the config file can be copied as is.
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::journal::Journal;
+use crate::model::{CalendarListEntry, Task};
+use crate::storage::{LocalStorage, is_local_calendar};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Backup {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    pub local_calendars: Vec<CalendarListEntry>,
+    // Server calendars, as last synced
+    pub calendars: Vec<CalendarListEntry>,
+    // Calendar href (the cache key for server calendars, see `Cache::key`) -> tasks
+    pub tasks: BTreeMap<String, Vec<Task>>,
+    // Changes not uploaded yet
+    #[serde(default)]
+    pub journal: Journal,
+    #[serde(default)]
+    pub tag_aliases: HashMap<String, Vec<String>>,
+}
+
+/// What a restore brought back.
+#[derive(Debug, Default, PartialEq)]
+pub struct RestoreSummary {
+    pub calendars: usize,
+    pub tasks: usize,
+    pub pending: usize,
+    pub aliases: usize,
+}
+
+impl Backup {
+    /// The current state: local calendars, the cache of the server ones, the journal
+    /// and the aliases of `config`.
+    pub fn collect(config: &Config) -> Result<Self> {
+        let local_calendars = LocalStorage::calendars();
+        let calendars: Vec<CalendarListEntry> = Cache::load_calendars()?
+            .into_iter()
+            .filter(|c| !is_local_calendar(&c.href))
+            .collect();
+
+        let mut tasks = BTreeMap::new();
+        for cal in &local_calendars {
+            tasks.insert(cal.href.clone(), LocalStorage::load_calendar(&cal.href)?);
+        }
+        for cal in &calendars {
+            // Never synced: nothing to keep
+            if let Ok((cached, _)) = Cache::load_for(cal) {
+                tasks.insert(Cache::key(cal.account.as_deref(), &cal.href), cached);
+            }
+        }
+
+        Ok(Self {
+            version: VERSION,
+            created: Utc::now(),
+            local_calendars,
+            calendars,
+            tasks,
+            journal: Journal::load()?,
+            tag_aliases: config.tag_aliases.clone(),
+        })
+    }
+
+    /// Encrypted like the data files when `encrypt_data` is on.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        LocalStorage::write_data(path, json).map_err(|e| anyhow!("{}: {}", path.display(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let json =
+            LocalStorage::read_data(path).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        let backup: Self =
+            serde_json::from_str(&json).map_err(|e| anyhow!("Not a cfait backup: {}", e))?;
+        if backup.version > VERSION {
+            return Err(anyhow!(
+                "This backup was made by a newer version of cfait (format {})",
+                backup.version
+            ));
+        }
+        Ok(backup)
+    }
+
+    /// Puts the backup back, on top of what's there: tasks replace the ones with the
+    /// same UID, others are kept, and pending changes join the journal. Server
+    /// calendars come back as their offline copy; the server still has the last word
+    /// on the next sync. The aliases are added to `config`, which the caller saves.
+    pub fn restore(self, config: &mut Config) -> Result<RestoreSummary> {
+        let mut summary = RestoreSummary::default();
+
+        for cal in &self.local_calendars {
+            LocalStorage::restore_calendar(cal)?;
+            summary.calendars += 1;
+        }
+        if !self.calendars.is_empty() {
+            let mut cached = Cache::load_calendars().unwrap_or_default();
+            for cal in &self.calendars {
+                if !cached
+                    .iter()
+                    .any(|c| c.href == cal.href && c.account == cal.account)
+                {
+                    cached.push(cal.clone());
+                }
+            }
+            Cache::save_calendars(&cached)?;
+            summary.calendars += self.calendars.len();
+        }
+
+        for (href, tasks) in &self.tasks {
+            summary.tasks += tasks.len();
+            if is_local_calendar(href) {
+                let mut current = LocalStorage::load_calendar(href)?;
+                merge(&mut current, tasks);
+                LocalStorage::save_calendar(href, &current)?;
+            } else {
+                Cache::modify(href, |current| {
+                    merge(current, tasks);
+                    true
+                })?;
+            }
+        }
+
+        let backup_journal = self.journal;
+        Journal::modify_journal(|journal| {
+            for action in backup_journal.queue {
+                if !journal.queue.contains(&action) {
+                    journal.queue.push(action);
+                    summary.pending += 1;
+                }
+            }
+            for entry in backup_journal.failed {
+                if !journal.failed.contains(&entry) {
+                    journal.failed.push(entry);
+                }
+            }
+            for conflict in backup_journal.conflicts {
+                if !journal.conflicts.contains(&conflict) {
+                    journal.conflicts.push(conflict);
+                }
+            }
+        })?;
+
+        for (alias, tags) in self.tag_aliases {
+            if config.tag_aliases.get(&alias) != Some(&tags) {
+                config.tag_aliases.insert(alias, tags);
+                summary.aliases += 1;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+// Tasks of the backup replace the current ones with the same UID
+fn merge(current: &mut Vec<Task>, restored: &[Task]) {
+    for task in restored {
+        match current.iter_mut().find(|t| t.uid == task.uid) {
+            Some(existing) => *existing = task.clone(),
+            None => current.push(task.clone()),
+        }
+    }
+}
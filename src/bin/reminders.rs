@@ -0,0 +1,22 @@
+// File: src/bin/reminders.rs
+// Standalone reminder daemon: watches the offline cache and fires desktop notifications.
+use cfait::reminders;
+use chrono::Utc;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+fn main() {
+    let mut last_check = Utc::now();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let now = Utc::now();
+        let tasks = reminders::load_cached_tasks();
+        for r in reminders::collect_due(&tasks, last_check, now) {
+            if let Err(e) = reminders::notify(&r) {
+                eprintln!("Notification failed: {}", e);
+            }
+        }
+        last_check = now;
+    }
+}
@@ -1,23 +1,66 @@
 // File: src/cache.rs
+use crate::journal::{Action, Journal, moved_to};
 use crate::model::{CalendarListEntry, Task};
 use crate::paths::AppPaths;
+use crate::search_index::SearchIndex;
 use crate::storage::LocalStorage;
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime};
+
+// Index of every cached task, for `Cache::search`
+static SEARCH_INDEX: LazyLock<Mutex<SearchIndex>> = LazyLock::new(Default::default);
 
 #[derive(Serialize, Deserialize)]
 struct CalendarCache {
     sync_token: Option<String>,
+    // Last-seen getctag, lets us skip unchanged calendars with a single PROPFIND
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ctag: Option<String>,
     tasks: Vec<Task>,
+    // uid -> server version of tasks edited locally, used as 3-way merge base on conflicts
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    bases: HashMap<String, Task>,
+}
+
+// Principal + calendar-home-set of a server, so we don't rediscover them on every start
+#[derive(Serialize, Deserialize)]
+struct DiscoveryCache {
+    principal: String,
+    home_set: String,
+    // Unix timestamp
+    saved_at: i64,
+}
+
+/// Size of the task caches, for the "Clear cache" settings and `cfait cache`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub calendars: usize,
+    pub tasks: usize,
+    pub bytes: u64,
+    // When the least recently synced calendar was last written
+    pub oldest: Option<DateTime<Utc>>,
 }
 
 pub struct Cache;
 
 impl Cache {
+    /// Key of a calendar's cache: its href, after the account label for secondary
+    /// accounts (two servers can use the same paths).
+    pub fn key(account: Option<&str>, calendar_href: &str) -> String {
+        match account {
+            Some(label) => format!("{}\n{}", label, calendar_href),
+            None => calendar_href.to_string(),
+        }
+    }
+
     fn get_calendars_path() -> Option<PathBuf> {
         AppPaths::get_cache_dir()
             .ok()
@@ -33,38 +76,437 @@ impl Cache {
         })
     }
 
+    fn get_discovery_path(key: &str) -> Option<PathBuf> {
+        AppPaths::get_cache_dir().ok().map(|dir| {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            dir.join(format!("discovery_{:x}.json", hasher.finish()))
+        })
+    }
+
+    /// Saves locally edited tasks. Keeps the stored ctag (local edits don't change the
+    /// server state) and remembers the server version of each edited task as merge base.
     pub fn save(key: &str, tasks: &[Task], sync_token: Option<String>) -> Result<()> {
         if let Some(path) = Self::get_path(key) {
             LocalStorage::with_lock(&path, || {
-                let data = CalendarCache {
-                    sync_token: sync_token.clone(),
-                    tasks: tasks.to_vec(),
-                };
-                let json = serde_json::to_string_pretty(&data)?;
-                LocalStorage::atomic_write(&path, json)?;
+                Self::save_locked(&path, tasks, sync_token.clone())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Like `save`, keeping the sync token stored in the file.
+    pub fn save_tasks(key: &str, tasks: &[Task]) -> Result<()> {
+        Self::modify(key, |cached| {
+            *cached = tasks.to_vec();
+            true
+        })
+    }
+
+    /// Edits the cached tasks of a calendar (a local edit, like `save`) while holding
+    /// the lock from read to write, so other processes (TUI and GUI open at once)
+    /// don't lose each other's changes. `edit` returns false to leave the file alone.
+    pub fn modify<F>(key: &str, edit: F) -> Result<()>
+    where
+        F: FnOnce(&mut Vec<Task>) -> bool,
+    {
+        if let Some(path) = Self::get_path(key) {
+            LocalStorage::with_lock(&path, || {
+                let (mut tasks, token) = Self::read_tasks(&path)?;
+                if edit(&mut tasks) {
+                    Self::save_locked(&path, &tasks, token)?;
+                }
                 Ok(())
             })?;
         }
         Ok(())
     }
 
+    // Callers hold the lock
+    fn save_locked(path: &PathBuf, tasks: &[Task], sync_token: Option<String>) -> Result<()> {
+        let (ctag, mut bases) = match Self::read(path) {
+            Some(old) => {
+                let mut bases = old.bases;
+                for prev in old.tasks {
+                    let edited = tasks.iter().any(|t| t.uid == prev.uid && *t != prev);
+                    if edited && !bases.contains_key(&prev.uid) {
+                        bases.insert(prev.uid.clone(), prev);
+                    }
+                }
+                (old.ctag, bases)
+            }
+            None => (None, HashMap::new()),
+        };
+        bases.retain(|uid, _| tasks.iter().any(|t| &t.uid == uid));
+        Self::write(path, tasks, sync_token, ctag, bases)
+    }
+
+    /// Saves tasks as fetched from the server. Merge bases are only kept for tasks
+    /// that still have unsynced changes in the journal.
+    pub fn save_with_ctag(
+        key: &str,
+        tasks: &[Task],
+        sync_token: Option<String>,
+        ctag: Option<String>,
+    ) -> Result<()> {
+        if let Some(path) = Self::get_path(key) {
+            LocalStorage::with_lock(&path, || {
+                let mut bases = Self::read(&path).map(|c| c.bases).unwrap_or_default();
+                // Can't tell which are still needed if the journal can't be read: keep them
+                if !bases.is_empty()
+                    && let Ok(journal) = Journal::load()
+                {
+                    bases.retain(|uid, _| journal.queue.iter().any(|a| &a.task().uid == uid));
+                }
+                Self::write(&path, tasks, sync_token.clone(), ctag.clone(), bases)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Applies a queued change to the cached tasks right away (write-through), so a
+    /// restart shows it even before the next sync. Local edits, like `save`.
+    pub fn apply(action: &Action) -> Result<()> {
+        match action {
+            Action::Create(task) | Action::Update(task) => Self::upsert(task),
+            Action::Delete(task) => Self::remove(task),
+            Action::Move(task, dest) => {
+                Self::remove(task)?;
+                Self::upsert(&moved_to(task, dest))
+            }
+        }
+    }
+
+    fn upsert(task: &Task) -> Result<()> {
+        let key = Self::key(task.account.as_deref(), &task.calendar_href);
+        Self::modify(&key, |tasks| {
+            match tasks.iter_mut().find(|t| t.uid == task.uid) {
+                Some(t) if t == task => return false,
+                Some(t) => *t = task.clone(),
+                None => tasks.push(task.clone()),
+            }
+            true
+        })
+    }
+
+    fn remove(task: &Task) -> Result<()> {
+        let key = Self::key(task.account.as_deref(), &task.calendar_href);
+        Self::modify(&key, |tasks| {
+            let len = tasks.len();
+            tasks.retain(|t| t.uid != task.uid);
+            tasks.len() != len
+        })
+    }
+
+    /// Records the href/ETag the server assigned to a task we uploaded. Unlike `save`,
+    /// this doesn't count as a local edit and keeps the ctag and sync token.
+    pub fn update_etag(key: &str, uid: &str, href: &str, etag: &str) -> Result<()> {
+        if let Some(path) = Self::get_path(key)
+            && path.exists()
+        {
+            LocalStorage::with_lock(&path, || {
+                let Some(mut cache) = Self::read(&path) else {
+                    return Ok(());
+                };
+                let Some(task) = cache.tasks.iter_mut().find(|t| t.uid == uid) else {
+                    return Ok(());
+                };
+                task.href = href.to_string();
+                task.etag = etag.to_string();
+                Self::write(
+                    &path,
+                    &cache.tasks,
+                    cache.sync_token,
+                    cache.ctag,
+                    cache.bases,
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn load_ctag(key: &str) -> Option<String> {
+        let path = Self::get_path(key)?;
+        if !path.exists() {
+            return None;
+        }
+        LocalStorage::with_lock(&path, || Ok(Self::read(&path).and_then(|c| c.ctag)))
+            .ok()
+            .flatten()
+    }
+
+    /// Last known server version of a locally edited task (the 3-way merge base).
+    pub fn load_base(key: &str, uid: &str) -> Option<Task> {
+        let path = Self::get_path(key)?;
+        if !path.exists() {
+            return None;
+        }
+        LocalStorage::with_lock(&path, || {
+            Ok(Self::read(&path).and_then(|mut c| c.bases.remove(uid)))
+        })
+        .ok()
+        .flatten()
+    }
+
+    // Callers hold the lock
+    fn read(path: &PathBuf) -> Option<CalendarCache> {
+        let json = LocalStorage::read_data(path).ok()?;
+        serde_json::from_str::<CalendarCache>(&json).ok()
+    }
+
+    fn write(
+        path: &PathBuf,
+        tasks: &[Task],
+        sync_token: Option<String>,
+        ctag: Option<String>,
+        bases: HashMap<String, Task>,
+    ) -> Result<()> {
+        let data = CalendarCache {
+            sync_token,
+            ctag,
+            tasks: tasks.to_vec(),
+            bases,
+        };
+        let json = serde_json::to_string_pretty(&data)?;
+        LocalStorage::write_data(path, json)?;
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        SEARCH_INDEX
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update(path, tasks, modified);
+        Ok(())
+    }
+
+    /// Cached tasks of all calendars, and the local ones, containing every word of
+    /// `query` (see `SearchIndex::search`). Only files written since the last search
+    /// get read again, so this stays fast with very large caches.
+    pub fn search(query: &str) -> Vec<Task> {
+        // Local calendars are read through LocalStorage, by href
+        let local: Vec<(PathBuf, String)> = LocalStorage::calendars()
+            .into_iter()
+            .filter_map(|c| Some((LocalStorage::get_calendar_path(&c.href)?, c.href)))
+            .filter(|(path, _)| path.exists())
+            .collect();
+        let mut sources: Vec<(PathBuf, Option<SystemTime>)> = Self::calendar_files()
+            .into_iter()
+            .map(|(path, _, modified)| (path, Some(modified)))
+            .collect();
+        for (path, _) in &local {
+            let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+            sources.push((path.clone(), modified));
+        }
+
+        // Files are read without holding the index: `write` takes it under the file lock
+        let stale: Vec<(PathBuf, Option<SystemTime>)> = {
+            let mut index = SEARCH_INDEX.lock().unwrap_or_else(|e| e.into_inner());
+            index.retain(|p| sources.iter().any(|(s, _)| s == p));
+            sources
+                .into_iter()
+                .filter(|(path, modified)| !index.is_fresh(path, *modified))
+                .collect()
+        };
+        let reread: Vec<(PathBuf, Vec<Task>, Option<SystemTime>)> = stale
+            .into_iter()
+            .map(|(path, modified)| {
+                let tasks = if let Some((_, href)) = local.iter().find(|(p, _)| *p == path) {
+                    LocalStorage::load_calendar(href).unwrap_or_default()
+                } else {
+                    LocalStorage::with_lock(&path, || Self::read_tasks(&path))
+                        .map(|(tasks, _)| tasks)
+                        .unwrap_or_default()
+                };
+                (path, tasks, modified)
+            })
+            .collect();
+
+        let mut index = SEARCH_INDEX.lock().unwrap_or_else(|e| e.into_inner());
+        for (path, tasks, modified) in reread {
+            index.update(&path, &tasks, modified);
+        }
+        index.search(query)
+    }
+
+    /// Cached tasks of a listed calendar (see `key`).
+    pub fn load_for(cal: &CalendarListEntry) -> Result<(Vec<Task>, Option<String>)> {
+        Self::load(&Self::key(cal.account.as_deref(), &cal.href))
+    }
+
     pub fn load(key: &str) -> Result<(Vec<Task>, Option<String>)> {
         if let Some(path) = Self::get_path(key)
             && path.exists()
         {
-            return LocalStorage::with_lock(&path, || {
-                let json = fs::read_to_string(&path)?;
-                if let Ok(cache) = serde_json::from_str::<CalendarCache>(&json) {
-                    return Ok((cache.tasks, cache.sync_token));
+            return LocalStorage::with_lock(&path, || Self::read_tasks(&path));
+        }
+        Ok((vec![], None))
+    }
+
+    // Callers hold the lock
+    fn read_tasks(path: &PathBuf) -> Result<(Vec<Task>, Option<String>)> {
+        if !path.exists() {
+            return Ok((vec![], None));
+        }
+        let json = LocalStorage::read_data(path)?;
+        if let Ok(cache) = serde_json::from_str::<CalendarCache>(&json) {
+            return Ok((cache.tasks, cache.sync_token));
+        }
+        // Fallback for older cache format (just array)
+        if let Ok(tasks) = serde_json::from_str::<Vec<Task>>(&json) {
+            return Ok((tasks, None));
+        }
+        Ok((vec![], None))
+    }
+
+    /// Remembers the discovered (principal, calendar-home-set) URLs of a server.
+    pub fn save_discovery(key: &str, principal: &str, home_set: &str) -> Result<()> {
+        if let Some(path) = Self::get_discovery_path(key) {
+            let data = DiscoveryCache {
+                principal: principal.to_string(),
+                home_set: home_set.to_string(),
+                saved_at: Utc::now().timestamp(),
+            };
+            let json = serde_json::to_string_pretty(&data)?;
+            LocalStorage::with_lock(&path, || LocalStorage::atomic_write(&path, json))?;
+        }
+        Ok(())
+    }
+
+    /// (principal, calendar-home-set), unless missing or older than `max_age_secs`.
+    pub fn load_discovery(key: &str, max_age_secs: i64) -> Option<(String, String)> {
+        let path = Self::get_discovery_path(key)?;
+        if !path.exists() {
+            return None;
+        }
+        let data = LocalStorage::with_lock(&path, || {
+            let json = fs::read_to_string(&path)?;
+            Ok(serde_json::from_str::<DiscoveryCache>(&json)?)
+        })
+        .ok()?;
+        let age = Utc::now().timestamp() - data.saved_at;
+        (0..=max_age_secs)
+            .contains(&age)
+            .then_some((data.principal, data.home_set))
+    }
+
+    pub fn clear_discovery(key: &str) {
+        if let Some(path) = Self::get_discovery_path(key) {
+            let _ = LocalStorage::with_lock(&path, || Ok(fs::remove_file(&path)?));
+        }
+    }
+
+    // Per-calendar task caches, with their size and last write
+    fn calendar_files() -> Vec<(PathBuf, u64, SystemTime)> {
+        let Ok(dir) = AppPaths::get_cache_dir() else {
+            return vec![];
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        entries
+            .flatten()
+            .filter(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                name.starts_with("tasks_") && name.ends_with(".json")
+            })
+            .filter_map(|e| {
+                let meta = e.metadata().ok()?;
+                Some((e.path(), meta.len(), meta.modified().ok()?))
+            })
+            .collect()
+    }
+
+    // Caches of calendars with changes still in the journal: they hold the merge bases
+    fn pending_files() -> Result<HashSet<PathBuf>> {
+        let journal = Journal::load()?;
+        // Keyed by the account of the task too (see `key`)
+        let mut keys: Vec<String> = Vec::new();
+        for action in journal
+            .queue
+            .iter()
+            .chain(journal.failed.iter().map(|f| &f.action))
+        {
+            let account = action.task().account.as_deref();
+            keys.push(Self::key(account, action.calendar_href()));
+            if let Action::Move(_, dest) = action {
+                keys.push(Self::key(account, dest));
+            }
+        }
+        keys.extend(
+            journal
+                .conflicts
+                .iter()
+                .map(|c| Self::key(c.local.account.as_deref(), &c.local.calendar_href)),
+        );
+        Ok(keys.iter().filter_map(|k| Self::get_path(k)).collect())
+    }
+
+    pub fn stats() -> Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        for (path, bytes, modified) in Self::calendar_files() {
+            stats.calendars += 1;
+            stats.bytes += bytes;
+            let modified = DateTime::<Utc>::from(modified);
+            stats.oldest = Some(stats.oldest.map_or(modified, |o| o.min(modified)));
+            let tasks = LocalStorage::with_lock(&path, || Ok(Self::read(&path)))?;
+            stats.tasks += tasks.map_or(0, |c| c.tasks.len());
+        }
+        Ok(stats)
+    }
+
+    /// Removes the caches of calendars not synced for `max_age`, then the least
+    /// recently synced ones until the rest fits in `max_size` bytes. Calendars with
+    /// unsynced changes are kept. Returns how many caches were removed.
+    pub fn prune(max_age: Duration, max_size: u64) -> Result<usize> {
+        let pending = Self::pending_files()?;
+        let mut files = Self::calendar_files();
+        files.sort_by_key(|(_, _, modified)| *modified);
+        let mut total: u64 = files.iter().map(|(_, bytes, _)| bytes).sum();
+        let now = SystemTime::now();
+
+        let mut removed = 0;
+        for (path, bytes, modified) in files {
+            let too_old = now.duration_since(modified).unwrap_or_default() > max_age;
+            if (!too_old && total <= max_size) || pending.contains(&path) {
+                continue;
+            }
+            LocalStorage::with_lock(&path, || Ok(fs::remove_file(&path)?))?;
+            total -= bytes;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Drops everything fetched from the servers: the next sync downloads it again.
+    /// Unsynced changes are safe: they live in the journal, and the caches of their
+    /// calendars only forget their sync state (they keep the merge bases).
+    pub fn clear() -> Result<usize> {
+        let pending = Self::pending_files()?;
+        let mut cleared = 0;
+        for (path, _, _) in Self::calendar_files() {
+            LocalStorage::with_lock(&path, || {
+                if !pending.contains(&path) {
+                    return Ok(fs::remove_file(&path)?);
                 }
-                // Fallback for older cache format (just array)
-                if let Ok(tasks) = serde_json::from_str::<Vec<Task>>(&json) {
-                    return Ok((tasks, None));
+                match Self::read(&path) {
+                    Some(cache) => Self::write(&path, &cache.tasks, None, None, cache.bases),
+                    None => Ok(fs::remove_file(&path)?),
                 }
-                Ok((vec![], None))
-            });
+            })?;
+            cleared += 1;
         }
-        Ok((vec![], None))
+        if let Ok(dir) = AppPaths::get_cache_dir()
+            && let Ok(entries) = fs::read_dir(dir)
+        {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("discovery_") && name.ends_with(".json") {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+        if let Some(path) = Self::get_calendars_path() {
+            let _ = LocalStorage::with_lock(&path, || Ok(fs::remove_file(&path)?));
+        }
+        Ok(cleared)
     }
 
     pub fn save_calendars(cals: &[CalendarListEntry]) -> Result<()> {
@@ -0,0 +1,188 @@
+// File: ./src/client/acl.rs
+// RFC 3744 privileges + owner/sharing properties of calendar collections
+use roxmltree::Document;
+use std::collections::HashMap;
+
+const DAV_NS: &str = "DAV:";
+const CS_NS: &str = "http://calendarserver.org/ns/";
+const OC_NS: &str = "http://owncloud.org/ns";
+const NC_NS: &str = "http://nextcloud.com/ns";
+
+// What the server told us about who owns a collection and what we may do with it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CollectionAccess {
+    // None when current-user-privilege-set isn't supported
+    pub read_only: Option<bool>,
+    // Owner principal href (DAV:owner or Nextcloud's owner-principal)
+    pub owner_href: Option<String>,
+    pub owner_name: Option<String>,
+    // cs:shared-url is only set on the sharee's copy of a shared calendar
+    pub shared_url: Option<String>,
+}
+
+// Any of these lets us PUT/DELETE resources in a collection
+const WRITE_PRIVILEGES: [&str; 4] = ["all", "write", "write-content", "bind"];
+
+pub fn build_privilege_body() -> String {
+    r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.com/ns">
+  <d:prop>
+    <d:current-user-privilege-set/>
+    <d:owner/>
+    <cs:shared-url/>
+    <oc:owner-principal/>
+    <oc:owner-displayname/>
+    <nc:owner-displayname/>
+  </d:prop>
+</d:propfind>"#
+        .to_string()
+}
+
+/// Parses a PROPFIND multistatus into href -> access info.
+/// Properties reported with a non-200 propstat are left empty; a missing
+/// privilege set should be treated as writable.
+pub fn parse_collection_access(xml: &str) -> Result<HashMap<String, CollectionAccess>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid PROPFIND XML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name((DAV_NS, "multistatus")) {
+        return Err("PROPFIND: expected multistatus".to_string());
+    }
+
+    let mut result = HashMap::new();
+    for response in root
+        .children()
+        .filter(|n| n.has_tag_name((DAV_NS, "response")))
+    {
+        let Some(href) = response
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "href")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+        else {
+            continue;
+        };
+
+        let mut access = CollectionAccess::default();
+        for propstat in response
+            .children()
+            .filter(|n| n.has_tag_name((DAV_NS, "propstat")))
+        {
+            let ok = propstat
+                .children()
+                .find(|n| n.has_tag_name((DAV_NS, "status")))
+                .and_then(|n| n.text())
+                .is_some_and(|s| s.contains(" 200"));
+            if !ok {
+                continue;
+            }
+            // Text of a property, or of the <href> inside it (DAV:owner wraps one)
+            let text = |ns: &str, name: &str| {
+                propstat
+                    .descendants()
+                    .find(|n| n.has_tag_name((ns, name)))
+                    .and_then(|n| {
+                        n.descendants()
+                            .find(|c| c.has_tag_name((DAV_NS, "href")))
+                            .unwrap_or(n)
+                            .text()
+                    })
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+            };
+
+            if let Some(set) = propstat
+                .descendants()
+                .find(|n| n.has_tag_name((DAV_NS, "current-user-privilege-set")))
+            {
+                let writable = set
+                    .descendants()
+                    .filter(|n| n.has_tag_name((DAV_NS, "privilege")))
+                    .flat_map(|p| p.children().filter(|c| c.is_element()))
+                    .any(|p| {
+                        p.tag_name().namespace() == Some(DAV_NS)
+                            && WRITE_PRIVILEGES.contains(&p.tag_name().name())
+                    });
+                access.read_only = Some(!writable);
+            }
+            access.owner_href = access
+                .owner_href
+                .or_else(|| text(OC_NS, "owner-principal"))
+                .or_else(|| text(DAV_NS, "owner"));
+            access.owner_name = access
+                .owner_name
+                .or_else(|| text(NC_NS, "owner-displayname"))
+                .or_else(|| text(OC_NS, "owner-displayname"));
+            access.shared_url = access.shared_url.or_else(|| text(CS_NS, "shared-url"));
+        }
+        result.insert(href, access);
+    }
+    Ok(result)
+}
+
+/// Display name for an owner principal: the server-provided name, else the
+/// last path segment of the principal href (`/principals/users/alice/` -> `alice`).
+pub fn owner_label(access: &CollectionAccess) -> Option<String> {
+    access.owner_name.clone().or_else(|| {
+        access
+            .owner_href
+            .as_deref()?
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_collection_access() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/cals/shared/</d:href>
+    <d:propstat>
+      <d:prop><d:current-user-privilege-set>
+        <d:privilege><d:read/></d:privilege>
+        <d:privilege><d:read-current-user-privilege-set/></d:privilege>
+      </d:current-user-privilege-set>
+      <d:owner><d:href>/principals/users/alice/</d:href></d:owner></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/cals/mine/</d:href>
+    <d:propstat>
+      <d:prop><d:current-user-privilege-set>
+        <d:privilege><d:read/></d:privilege>
+        <d:privilege><d:write/></d:privilege>
+      </d:current-user-privilege-set></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/cals/old/</d:href>
+    <d:propstat>
+      <d:prop><d:current-user-privilege-set/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        let access = parse_collection_access(xml).unwrap();
+        assert_eq!(access["/cals/shared/"].read_only, Some(true));
+        assert_eq!(access["/cals/mine/"].read_only, Some(false));
+        assert_eq!(access["/cals/old/"].read_only, None);
+        assert_eq!(
+            access["/cals/shared/"].owner_href.as_deref(),
+            Some("/principals/users/alice/")
+        );
+        assert_eq!(
+            owner_label(&access["/cals/shared/"]).as_deref(),
+            Some("alice")
+        );
+    }
+}
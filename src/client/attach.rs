@@ -0,0 +1,70 @@
+// File: ./src/client/attach.rs
+// RFC 8607 managed attachments: helpers for the attachment-add POST
+use std::path::Path;
+
+/// Rough MIME type from the file extension, for FMTTYPE / Content-Type.
+pub fn guess_mime(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "ics" => "text/calendar",
+        "vcf" => "text/vcard",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Whether the DAV header of an OPTIONS response advertises managed attachments.
+pub fn supports_managed(dav_header: &str) -> bool {
+    dav_header.split(',').any(|c| {
+        c.trim()
+            .eq_ignore_ascii_case("calendar-managed-attachments")
+    })
+}
+
+pub fn content_disposition(filename: &str) -> String {
+    // Quoted-string: only the quote and backslash need escaping
+    let escaped = filename.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("attachment;filename=\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_managed_attachment_helpers() {
+        assert!(supports_managed(
+            "1, 3, access-control, calendar-access, calendar-managed-attachments"
+        ));
+        assert!(!supports_managed("1, 2, calendar-access"));
+        assert_eq!(guess_mime(Path::new("notes/Plan.PDF")), "application/pdf");
+        assert_eq!(guess_mime(Path::new("noext")), "application/octet-stream");
+        assert_eq!(
+            content_disposition("my \"file\".txt"),
+            "attachment;filename=\"my \\\"file\\\".txt\""
+        );
+    }
+}
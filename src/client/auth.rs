@@ -0,0 +1,160 @@
+// File: ./src/client/auth.rs
+// Service wrapper adding the Authorization header. OAuth2 access tokens expire, so
+// they're looked up per request: refreshed once they're about to expire, and once
+// more (then the request resent) if the server rejects one anyway.
+use crate::client::core::HttpClient;
+use crate::client::oauth::{self, OAuthToken};
+use crate::config::OAuth2Config;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use futures::future::{BoxFuture, poll_fn};
+use http::{HeaderValue, Request, Response, StatusCode, header};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::Mutex;
+use tower_service::Service;
+
+#[derive(Clone, Debug)]
+pub struct Authorize<S> {
+    inner: S,
+    credentials: Credentials,
+}
+
+#[derive(Clone, Debug)]
+enum Credentials {
+    // None = a token that can't go in a header, the server will say so
+    Static(Option<HeaderValue>),
+    OAuth2(Arc<OAuthSession>),
+}
+
+// Shared between clones so a refresh is done once for all of them
+#[derive(Debug)]
+pub struct OAuthSession {
+    cfg: OAuth2Config,
+    // Plain client for the token endpoint
+    http: HttpClient,
+    token: Mutex<OAuthToken>,
+}
+
+fn header_value(value: &str) -> Option<HeaderValue> {
+    let mut value = HeaderValue::from_str(value).ok()?;
+    value.set_sensitive(true);
+    Some(value)
+}
+
+fn bearer(token: &str) -> Option<HeaderValue> {
+    header_value(&format!("Bearer {}", token))
+}
+
+impl OAuthSession {
+    pub fn new(cfg: OAuth2Config, http: HttpClient, token: OAuthToken) -> Self {
+        Self {
+            cfg,
+            http,
+            token: Mutex::new(token),
+        }
+    }
+
+    async fn header(&self) -> Option<HeaderValue> {
+        let mut token = self.token.lock().await;
+        // On failure keep the old one: the server's 401 says the rest
+        if token.is_expired()
+            && let Ok(fresh) = oauth::get_access_token(&self.cfg, self.http.clone()).await
+        {
+            *token = fresh;
+        }
+        bearer(&token.access_token)
+    }
+
+    // The server turned `rejected` down: get a new token, unless a concurrent
+    // request already did
+    async fn renew(&self, rejected: &HeaderValue) -> Option<HeaderValue> {
+        let mut token = self.token.lock().await;
+        if bearer(&token.access_token).as_ref() != Some(rejected) {
+            return bearer(&token.access_token);
+        }
+        *token = oauth::renew(&self.cfg, self.http.clone()).await.ok()?;
+        bearer(&token.access_token)
+    }
+}
+
+impl<S> Authorize<S> {
+    pub fn basic(inner: S, user: &str, pass: &str) -> Self {
+        let encoded = STANDARD.encode(format!("{}:{}", user, pass));
+        Self {
+            inner,
+            credentials: Credentials::Static(header_value(&format!("Basic {}", encoded))),
+        }
+    }
+
+    pub fn bearer(inner: S, token: &str) -> Self {
+        Self {
+            inner,
+            credentials: Credentials::Static(bearer(token)),
+        }
+    }
+
+    pub fn oauth2(inner: S, session: OAuthSession) -> Self {
+        Self {
+            inner,
+            credentials: Credentials::OAuth2(Arc::new(session)),
+        }
+    }
+}
+
+impl<S, B> Service<Request<String>> for Authorize<S>
+where
+    S: Service<Request<String>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<String>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let credentials = self.credentials.clone();
+
+        Box::pin(async move {
+            let session = match credentials {
+                Credentials::Static(value) => {
+                    if let Some(value) = value {
+                        req.headers_mut().insert(header::AUTHORIZATION, value);
+                    }
+                    return inner.call(req).await;
+                }
+                Credentials::OAuth2(session) => session,
+            };
+            let Some(value) = session.header().await else {
+                return inner.call(req).await;
+            };
+
+            // Kept aside in case the token is rejected and the request sent again
+            let mut again = Request::new(req.body().clone());
+            *again.method_mut() = req.method().clone();
+            *again.uri_mut() = req.uri().clone();
+            *again.version_mut() = req.version();
+            *again.headers_mut() = req.headers().clone();
+
+            req.headers_mut()
+                .insert(header::AUTHORIZATION, value.clone());
+            let resp = inner.call(req).await?;
+            if resp.status() != StatusCode::UNAUTHORIZED {
+                return Ok(resp);
+            }
+            let Some(fresh) = session.renew(&value).await else {
+                return Ok(resp);
+            };
+            poll_fn(|cx| inner.poll_ready(cx)).await?;
+            again.headers_mut().insert(header::AUTHORIZATION, fresh);
+            inner.call(again).await
+        })
+    }
+}
@@ -1,11 +1,40 @@
 // File: src/client/core.rs
 
 use crate::cache::Cache;
-use crate::client::cert::NoVerifier;
-use crate::config::Config;
-use crate::journal::{Action, Journal};
-use crate::model::{CalendarListEntry, Task, TaskStatus};
-use crate::storage::{LOCAL_CALENDAR_HREF, LocalStorage};
+use crate::client::acl::{
+    CollectionAccess, build_privilege_body, owner_label, parse_collection_access,
+};
+use crate::client::attach;
+use crate::client::auth::{Authorize, OAuthSession};
+use crate::client::cert::{
+    NoVerifier, PinnedVerifier, load_ca_certs, load_client_identity, parse_fingerprint,
+};
+use crate::client::discovery;
+use crate::client::error::ClientError;
+use crate::client::oauth;
+use crate::client::proxy::ProxyConnector;
+use crate::client::push;
+use crate::client::query::{
+    build_completed_since_query_body, build_open_todo_query_body, build_todo_query_body,
+    parse_etag_listing,
+};
+use crate::client::quota::{self, Quota};
+use crate::client::retry::Retry;
+use crate::client::sync::{
+    CollectionState, SyncDelta, build_collection_state_body, build_sync_collection_body,
+    parse_collection_states, parse_sync_collection_response,
+};
+use crate::client::throttle::Throttle;
+use crate::color_utils;
+use crate::config::{AuthMode, Config, ConflictPolicy, Hooks, RetryPolicy};
+use crate::history::{ChangeOrigin, History};
+use crate::hooks::{self, Event};
+use crate::journal::{Action, Conflict, Journal, Resolution, compact};
+use crate::model::diff::conflicting_fields;
+use crate::model::{Attachment, CalendarListEntry, FileFormat, Task, TaskStatus};
+use crate::storage::{
+    DEFAULT_TRASH_DAYS, LOCAL_CALENDAR_HREF, LocalStorage, Trash, is_local_calendar,
+};
 
 // Libdav imports
 use libdav::caldav::{FindCalendarHomeSet, FindCalendars, GetCalendarResources};
@@ -13,14 +42,17 @@ use libdav::dav::{Delete, GetProperty, ListResources, PutResource};
 use libdav::dav::{WebDavClient, WebDavError};
 use libdav::{CalDavClient, PropertyName, names};
 
+use chrono::{DateTime, Utc};
 use futures::stream::{self, StreamExt};
 use http::{Request, StatusCode, Uri};
 use hyper_rustls::HttpsConnectorBuilder;
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
 use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
-use tower_http::auth::AddAuthorization;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid::Uuid;
 
 #[cfg(not(target_os = "android"))]
@@ -32,13 +64,268 @@ use rustls_platform_verifier::BuilderVerifierExt;
 pub const GET_CTAG: PropertyName = PropertyName::new("http://calendarserver.org/ns/", "getctag");
 pub const APPLE_COLOR: PropertyName =
     PropertyName::new("http://apple.com/ns/ical/", "calendar-color");
+pub const CALDAV_COLOR: PropertyName =
+    PropertyName::new("urn:ietf:params:xml:ns:caldav", "calendar-color");
+
+pub const DEFAULT_FETCH_BATCH_SIZE: usize = 100;
+// Server errors in a row before a queued change is given up on (see `Journal::park`)
+pub const MAX_REPLAY_ATTEMPTS: u32 = 5;
+// How long discovered principal/home-set URLs are trusted before rediscovering them
+const DISCOVERY_TTL_SECS: i64 = 24 * 60 * 60;
+
+pub(crate) type HttpClient = Client<hyper_rustls::HttpsConnector<ProxyConnector>, String>;
+
+type AuthClient = Authorize<HttpClient>;
+type HttpsClient = Retry<Throttle<AuthClient>>;
+
+/// Transport settings shared by every request of a client.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionOptions {
+    pub insecure: bool,
+    // Mutual TLS identity (PEM paths)
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+    // Explicit proxy URL; falls back to HTTP_PROXY / HTTPS_PROXY
+    pub proxy: Option<String>,
+    pub retry: RetryPolicy,
+    // Extra CA (PEM) for self-hosted servers
+    pub ca_cert_path: Option<String>,
+    // Trust only the server certificate with this SHA-256 fingerprint
+    pub pinned_sha256: Option<String>,
+    pub max_requests_per_second: Option<u32>,
+}
+
+impl ConnectionOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            insecure: config.allow_insecure_certs,
+            client_cert: config.client_cert.clone(),
+            client_key: config.client_key.clone(),
+            proxy: config.proxy.clone(),
+            retry: config.retry.clone(),
+            ca_cert_path: config.ca_cert_path.clone(),
+            pinned_sha256: config.pinned_sha256.clone(),
+            max_requests_per_second: config.max_requests_per_second,
+        }
+    }
+}
+
+pub(crate) fn build_http_client(opts: &ConnectionOptions) -> Result<HttpClient, String> {
+    let tls_config_builder = rustls::ClientConfig::builder();
+
+    let wants_client_cert = if opts.insecure {
+        tls_config_builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerifier))
+    } else if let Some(pin) = &opts.pinned_sha256 {
+        let verifier = PinnedVerifier::new(
+            parse_fingerprint(pin)?,
+            tls_config_builder.crypto_provider().clone(),
+        );
+        tls_config_builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+    } else if let Some(ca_path) = &opts.ca_cert_path {
+        let mut root_store = rustls::RootCertStore::empty();
+        #[cfg(not(target_os = "android"))]
+        root_store.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        for cert in load_ca_certs(ca_path)? {
+            root_store
+                .add(cert)
+                .map_err(|e| format!("CA certificate {}: {}", ca_path, e))?;
+        }
+        tls_config_builder.with_root_certificates(root_store)
+    } else {
+        #[cfg(not(target_os = "android"))]
+        {
+            let mut root_store = rustls::RootCertStore::empty();
+            let result = rustls_native_certs::load_native_certs();
+            root_store.add_parsable_certificates(result.certs);
+            if root_store.is_empty() {
+                return Err("No valid system certificates found.".to_string());
+            }
+            tls_config_builder.with_root_certificates(root_store)
+        }
 
-type HttpsClient = AddAuthorization<
-    Client<
-        hyper_rustls::HttpsConnector<hyper_util::client::legacy::connect::HttpConnector>,
-        String,
-    >,
->;
+        #[cfg(target_os = "android")]
+        {
+            tls_config_builder
+                .with_platform_verifier()
+                .map_err(|e| format!("Failed to init platform verifier: {}", e))? // Handle the Result
+        }
+    };
+
+    let tls_config = match (&opts.client_cert, &opts.client_key) {
+        (Some(cert), Some(key)) => {
+            let (certs, key) = load_client_identity(cert, key)?;
+            wants_client_cert
+                .with_client_auth_cert(certs, key)
+                .map_err(|e| format!("Invalid client certificate: {}", e))?
+        }
+        (None, None) => wants_client_cert.with_no_client_auth(),
+        _ => return Err("client_cert and client_key must be set together".to_string()),
+    };
+
+    let https_connector = HttpsConnectorBuilder::new()
+        .with_tls_config(tls_config)
+        .https_or_http()
+        .enable_http1()
+        .wrap_connector(ProxyConnector::new(opts.proxy.as_deref()));
+
+    Ok(Client::builder(TokioExecutor::new()).build(https_connector))
+}
+
+// Result of sending one journal action to the server
+struct ReplayOutcome {
+    result: Result<(), ClientError>,
+    warnings: Vec<String>,
+    // Replaces the action in the queue (merged update, conflict copy...)
+    resolved: Option<Action>,
+    new_etag: Option<String>,
+    // (old href, new href) after a move
+    new_href: Option<(String, String)>,
+}
+
+impl Default for ReplayOutcome {
+    fn default() -> Self {
+        Self {
+            result: Ok(()),
+            warnings: Vec::new(),
+            resolved: None,
+            new_etag: None,
+            new_href: None,
+        }
+    }
+}
+
+// Removes a replayed action from the queue and propagates the new etag/href
+// to the remaining actions of the same task.
+fn commit_replay(
+    queue: &mut Vec<Action>,
+    idx: usize,
+    action: &Action,
+    resolved: Option<Action>,
+    new_etag: Option<String>,
+    new_href: Option<(String, String)>,
+) {
+    let found = idx < queue.len();
+    if found {
+        queue.remove(idx);
+    }
+
+    if let Some(act) = resolved {
+        queue.insert(idx.min(queue.len()), act);
+    }
+
+    // The action was merged into another one meanwhile: that one is now an edit of
+    // what we just uploaded, not a second create (which would 412 on If-None-Match)
+    if !found
+        && new_etag.is_some()
+        && let Action::Create(created) = action
+    {
+        for item in queue.iter_mut() {
+            if let Action::Create(t) = item
+                && t.uid == created.uid
+            {
+                let mut t = t.clone();
+                if t.href.is_empty() {
+                    t.href = created_href(&t);
+                }
+                *item = Action::Update(t);
+            }
+        }
+    }
+
+    if let Some(etag) = new_etag {
+        let target_uid = match action {
+            Action::Create(t) | Action::Update(t) => t.uid.clone(),
+            Action::Move(t, _) => t.uid.clone(),
+            _ => String::new(),
+        };
+        if !target_uid.is_empty() {
+            for item in queue.iter_mut() {
+                match item {
+                    Action::Update(t) | Action::Delete(t) => {
+                        if t.uid == target_uid {
+                            t.etag = etag.clone();
+                        }
+                    }
+                    Action::Move(t, _) => {
+                        if t.uid == target_uid {
+                            t.etag = etag.clone();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Some((old_href, new_href)) = new_href {
+        let target_uid = match action {
+            Action::Move(t, _) => t.uid.clone(),
+            _ => String::new(),
+        };
+        for item in queue.iter_mut() {
+            match item {
+                Action::Update(t) | Action::Delete(t) => {
+                    if t.uid == target_uid || t.href == old_href {
+                        t.href = new_href.clone();
+                        if let Some(last_slash) = new_href.rfind('/') {
+                            t.calendar_href = new_href[..=last_slash].to_string();
+                        }
+                    }
+                }
+                Action::Move(t, _) => {
+                    if t.uid == target_uid {
+                        t.href = new_href.clone();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Where a Create replay puts the task
+fn created_href(task: &Task) -> String {
+    if task.calendar_href.ends_with('/') {
+        format!("{}{}.ics", task.calendar_href, task.uid)
+    } else {
+        format!("{}/{}.ics", task.calendar_href, task.uid)
+    }
+}
+
+// Stores the ETag the server gave a task we just uploaded, so the next fetch sees
+// it as unchanged instead of downloading it again
+fn cache_uploaded_etag(action: &Action, etag: &str, new_href: Option<&(String, String)>) {
+    let (calendar_href, href) = match action {
+        Action::Create(t) => (t.calendar_href.as_str(), created_href(t)),
+        Action::Update(t) => (t.calendar_href.as_str(), t.href.clone()),
+        Action::Move(_, new_cal) => match new_href {
+            Some((_, h)) => (new_cal.as_str(), h.clone()),
+            None => return,
+        },
+        Action::Delete(_) => return,
+    };
+    let key = Cache::key(action.task().account.as_deref(), calendar_href);
+    let _ = Cache::update_etag(&key, &action.task().uid, &strip_host(&href), etag);
+}
+
+// New task carrying the local side of a conflict, uploaded next to the server version
+fn conflict_copy(task: &Task) -> Task {
+    let mut copy = task.clone();
+    copy.uid = Uuid::new_v4().to_string();
+    copy.summary = format!("{} (Conflict Copy)", task.summary);
+    copy.href = String::new();
+    copy.etag = String::new();
+    copy
+}
+
+// Path without host or trailing slash, to match hrefs from different responses
+fn collection_key(href: &str) -> String {
+    strip_host(href).trim_end_matches('/').to_string()
+}
 
 fn strip_host(href: &str) -> String {
     if let Ok(uri) = href.parse::<Uri>()
@@ -52,177 +339,362 @@ fn strip_host(href: &str) -> String {
     href.to_string()
 }
 
+// Known tasks that came back from the server with a new etag go to the history
+fn record_remote_changes(cached: &[Task], fetched: &[Task]) {
+    let by_uid: HashMap<&str, &Task> = cached.iter().map(|t| (t.uid.as_str(), t)).collect();
+    let changed: Vec<(Option<&Task>, &Task)> = fetched
+        .iter()
+        .filter_map(|t| {
+            let old = by_uid.get(t.uid.as_str())?;
+            (old.etag != t.etag).then_some((Some(*old), t))
+        })
+        .collect();
+    let _ = History::record_all(&changed, ChangeOrigin::Remote);
+}
+
 #[derive(Clone, Debug)]
 pub struct RustyClient {
     pub client: Option<CalDavClient<HttpsClient>>,
+    // Account label for secondary accounts (None = primary account)
+    pub account: Option<String>,
+    // Journal actions replayed at once (1 = strictly sequential)
+    pub sync_concurrency: usize,
+    // What to do with an offline change that conflicts with the server version
+    pub conflict_policy: ConflictPolicy,
+    // User commands run on sync, task creation and completion
+    pub hooks: Hooks,
+    // Resources per calendar-multiget REPORT
+    pub fetch_batch_size: usize,
+    // Called with the tasks known so far after each multiget batch
+    pub progress: Option<FetchProgress>,
+    // Days a deleted task stays in the trash (0 = no trash)
+    pub trash_days: u32,
+    // Tasks completed longer ago than this are neither downloaded nor cached
+    pub completed_max_age_days: Option<u32>,
+    // Offline mode: mutations only go to the journal until `sync_journal` is called.
+    // Shared between clones so toggling it at runtime affects every copy.
+    offline: Arc<AtomicBool>,
+    // The last journal sync couldn't reach the server (shared the same way)
+    unreachable: Arc<AtomicBool>,
+    // Only a domain was configured: `discover_calendar` looks up the server first
+    bootstrap: Option<Bootstrap>,
 }
 
-impl RustyClient {
-    pub fn new(url: &str, user: &str, pass: &str, insecure: bool) -> Result<Self, String> {
-        if url.is_empty() {
-            return Ok(Self { client: None });
-        }
-        let uri: Uri = url
-            .parse()
-            .map_err(|e: http::uri::InvalidUri| e.to_string())?;
+// What it takes to point the client at the server found by RFC 6764 discovery
+#[derive(Clone, Debug)]
+struct Bootstrap {
+    domain: String,
+    opts: ConnectionOptions,
+    http: HttpsClient,
+}
 
-        let tls_config_builder = rustls::ClientConfig::builder();
+/// Callback receiving (calendar href, tasks fetched so far) while a large calendar
+/// is downloaded in batches, so UIs can show tasks before the sync finishes.
+#[derive(Clone)]
+pub struct FetchProgress(pub Arc<dyn Fn(&str, &[Task]) + Send + Sync>);
 
-        let tls_config = if insecure {
-            tls_config_builder
-                .dangerous()
-                .with_custom_certificate_verifier(Arc::new(NoVerifier))
-                .with_no_client_auth()
-        } else {
-            #[cfg(not(target_os = "android"))]
-            {
-                let mut root_store = rustls::RootCertStore::empty();
-                let result = rustls_native_certs::load_native_certs();
-                root_store.add_parsable_certificates(result.certs);
-                if root_store.is_empty() {
-                    return Err("No valid system certificates found.".to_string());
-                }
-                tls_config_builder
-                    .with_root_certificates(root_store)
-                    .with_no_client_auth()
-            }
+impl std::fmt::Debug for FetchProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FetchProgress")
+    }
+}
 
-            #[cfg(target_os = "android")]
-            {
-                tls_config_builder
-                    .with_platform_verifier()
-                    .map_err(|e| format!("Failed to init platform verifier: {}", e))? // Handle the Result
-                    .with_no_client_auth()
-            }
+impl RustyClient {
+    pub fn new(url: &str, user: &str, pass: &str, insecure: bool) -> Result<Self, ClientError> {
+        let opts = ConnectionOptions {
+            insecure,
+            ..Default::default()
         };
+        Self::build(url, &opts, |http| Authorize::basic(http, user, pass))
+    }
 
-        let https_connector = HttpsConnectorBuilder::new()
-            .with_tls_config(tls_config)
-            .https_or_http()
-            .enable_http1()
-            .build();
+    pub fn new_with_bearer(url: &str, token: &str, insecure: bool) -> Result<Self, ClientError> {
+        let opts = ConnectionOptions {
+            insecure,
+            ..Default::default()
+        };
+        Self::build(url, &opts, |http| Authorize::bearer(http, token))
+    }
 
-        let http_client = Client::builder(TokioExecutor::new()).build(https_connector);
-        let auth_client = AddAuthorization::basic(http_client.clone(), user, pass);
+    /// Builds a client using the auth mode from the config.
+    /// For OAuth2 this may hit the token endpoint to refresh the access token.
+    pub async fn from_config(config: &Config) -> Result<Self, ClientError> {
+        let opts = ConnectionOptions::from_config(config);
+        let url = &config.url;
+        let client = match config.auth {
+            AuthMode::Basic => Self::build(url, &opts, |http| {
+                Authorize::basic(http, &config.username, &config.password)
+            }),
+            AuthMode::Bearer => {
+                let token = config.bearer_token.as_deref().ok_or_else(|| {
+                    ClientError::Config(
+                        "auth = \"bearer\" requires bearer_token to be set".to_string(),
+                    )
+                })?;
+                Self::build(url, &opts, |http| Authorize::bearer(http, token))
+            }
+            AuthMode::OAuth2 => {
+                if config.url.is_empty() {
+                    return Ok(Self::with_client(None));
+                }
+                let oauth = config.oauth2.as_ref().ok_or_else(|| {
+                    ClientError::Config(
+                        "auth = \"oauth2\" requires an [oauth2] section".to_string(),
+                    )
+                })?;
+                let http = build_http_client(&opts).map_err(ClientError::Tls)?;
+                // A refresh token that no longer works means logging in again
+                let token = oauth::get_access_token(oauth, http.clone())
+                    .await
+                    .map_err(ClientError::Unauthorized)?;
+                // Refreshed again by the auth layer whenever it expires
+                let session = OAuthSession::new(oauth.clone(), http, token);
+                Self::build(url, &opts, |http| Authorize::oauth2(http, session))
+            }
+        }?;
+        let client = client
+            .with_sync_concurrency(config.sync_concurrency)
+            .with_conflict_policy(config.conflict_policy)
+            .with_hooks(config.hooks.clone())
+            .with_fetch_batch_size(config.fetch_batch_size)
+            .with_trash_days(config.trash_days)
+            .with_completed_max_age_days(config.completed_max_age_days);
+        client.set_offline(config.offline);
+        Ok(client)
+    }
+
+    fn build<F>(url: &str, opts: &ConnectionOptions, authorize: F) -> Result<Self, ClientError>
+    where
+        F: FnOnce(HttpClient) -> AuthClient,
+    {
+        if url.is_empty() {
+            return Ok(Self::with_client(None));
+        }
+        let full_url = discovery::with_scheme(url);
+        let uri: Uri = full_url.parse().map_err(|e: http::uri::InvalidUri| {
+            ClientError::Config(format!("Invalid URL: {}", e))
+        })?;
+
+        let throttled = Throttle::new(
+            authorize(build_http_client(opts).map_err(ClientError::Tls)?),
+            opts.max_requests_per_second,
+        );
+        let auth_client = Retry::new(throttled, opts.retry.clone());
         let webdav = WebDavClient::new(uri, auth_client.clone());
         let caldav = CalDavClient::new(webdav);
-        Ok(Self {
-            client: Some(caldav),
-        })
+        let mut built = Self::with_client(Some(caldav));
+        if discovery::needs_bootstrap(url) {
+            built.bootstrap = Some(Bootstrap {
+                domain: url.to_string(),
+                opts: opts.clone(),
+                http: auth_client,
+            });
+        }
+        Ok(built)
+    }
+
+    fn with_client(client: Option<CalDavClient<HttpsClient>>) -> Self {
+        Self {
+            client,
+            account: None,
+            sync_concurrency: 1,
+            conflict_policy: ConflictPolicy::Copy,
+            hooks: Hooks::default(),
+            fetch_batch_size: DEFAULT_FETCH_BATCH_SIZE,
+            progress: None,
+            trash_days: DEFAULT_TRASH_DAYS,
+            completed_max_age_days: None,
+            offline: Arc::new(AtomicBool::new(false)),
+            unreachable: Arc::new(AtomicBool::new(false)),
+            bootstrap: None,
+        }
+    }
+
+    pub fn with_account(mut self, label: &str) -> Self {
+        self.account = Some(label.to_string());
+        self
+    }
+
+    pub fn with_sync_concurrency(mut self, n: usize) -> Self {
+        self.sync_concurrency = n.max(1);
+        self
+    }
+
+    pub fn with_conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    pub fn with_fetch_batch_size(mut self, n: usize) -> Self {
+        self.fetch_batch_size = n.max(1);
+        self
+    }
+
+    pub fn with_trash_days(mut self, days: u32) -> Self {
+        self.trash_days = days;
+        self
+    }
+
+    pub fn with_completed_max_age_days(mut self, days: Option<u32>) -> Self {
+        self.completed_max_age_days = days;
+        self
+    }
+
+    pub fn with_progress(mut self, progress: FetchProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// True when the last `sync_journal` with something to send failed for lack of
+    /// a connection, until one goes through.
+    pub fn is_unreachable(&self) -> bool {
+        self.unreachable.load(Ordering::Relaxed)
+    }
+
+    // The server connection, unless we're in offline mode
+    fn online_client(&self) -> Option<&CalDavClient<HttpsClient>> {
+        self.client.as_ref().filter(|_| !self.is_offline())
+    }
+
+    // Opportunistic journal flush after a change; a no-op in offline mode
+    async fn sync_if_online(&self) -> Result<Vec<String>, ClientError> {
+        if self.is_offline() {
+            return Ok(vec![]);
+        }
+        self.sync_journal().await
     }
 
     // --- DISCOVERY & CONNECTION ---
 
-    pub async fn discover_calendar(&self) -> Result<String, String> {
-        if let Some(client) = &self.client {
+    /// True until `discover_calendar` has looked up the server of a bare domain.
+    pub fn needs_bootstrap(&self) -> bool {
+        self.bootstrap.is_some()
+    }
+
+    /// Finds a calendar to start with. A client configured with a bare domain first
+    /// looks up its server via SRV records and /.well-known/caldav (RFC 6764), and
+    /// sends everything there from then on.
+    pub async fn discover_calendar(&mut self) -> Result<String, ClientError> {
+        // If nothing is found, stay at the domain's root: there may be a server there.
+        // The lookup is kept for next time until it works (e.g. started offline).
+        if self.online_client().is_some()
+            && let Some(boot) = &self.bootstrap
+            && let Ok(url) = discovery::bootstrap_url(&boot.domain, &boot.opts).await
+            && let Ok(uri) = url.parse::<Uri>()
+            && let Some(boot) = self.bootstrap.take()
+        {
+            self.client = Some(CalDavClient::new(WebDavClient::new(uri, boot.http)));
+        }
+        if let Some(client) = self.online_client() {
             let base_path = client.base_url().path().to_string();
             if let Ok(response) = client.request(ListResources::new(&base_path)).await
                 && response.resources.iter().any(|r| r.href.ends_with(".ics"))
             {
                 return Ok(base_path);
             }
-            if let Ok(Some(principal)) = client.find_current_user_principal().await
-                && let Ok(response) = client.request(FindCalendarHomeSet::new(&principal)).await
-                && let Some(home_url) = response.home_sets.first()
-                && let Ok(cals_resp) = client.request(FindCalendars::new(home_url)).await
+            if let Ok((_, home_url)) = self.calendar_home(client).await
+                && let Ok(cals_resp) = client.request(FindCalendars::new(&home_url)).await
                 && let Some(first) = cals_resp.calendars.first()
             {
                 return Ok(first.href.clone());
             }
             Ok(base_path)
         } else {
-            Err("Offline".to_string())
-        }
-    }
-
-    pub async fn connect_with_fallback(
-        config: Config,
-    ) -> Result<
-        (
-            Self,
-            Vec<CalendarListEntry>,
-            Vec<Task>,
-            Option<String>,
-            Option<String>,
-        ),
-        String,
-    > {
-        let client = Self::new(
-            &config.url,
-            &config.username,
-            &config.password,
-            config.allow_insecure_certs,
-        )
-        .map_err(|e| e.to_string())?;
+            Err(ClientError::Offline)
+        }
+    }
 
-        let _ = client.sync_journal().await;
+    /// Logs in with `config` and lists its calendars, without falling back to the cache:
+    /// lets the setup wizards check the settings before saving them.
+    pub async fn check_connection(config: &Config) -> Result<Vec<CalendarListEntry>, ClientError> {
+        Self::from_config(config).await?.get_calendars().await
+    }
 
-        let (calendars, warning) = match client.get_calendars().await {
-            Ok(c) => {
-                let _ = Cache::save_calendars(&c);
-                (c, None)
-            }
-            Err(e) => {
-                if e.contains("InvalidCertificate") {
-                    return Err(format!("Connection failed: {}", e));
-                }
-                (
-                    Cache::load_calendars().unwrap_or_default(),
-                    Some("Offline Mode".to_string()),
-                )
-            }
-        };
+    // One discovery cache entry per server and account
+    fn discovery_key(&self, client: &CalDavClient<HttpsClient>) -> String {
+        format!(
+            "{}#{}",
+            client.base_url(),
+            self.account.as_deref().unwrap_or_default()
+        )
+    }
 
-        let mut active_href = None;
-        if let Some(def_cal) = &config.default_calendar
-            && let Some(found) = calendars
-                .iter()
-                .find(|c| c.name == *def_cal || c.href == *def_cal)
-        {
-            active_href = Some(found.href.clone());
-        }
+    fn cached_home(&self, client: &CalDavClient<HttpsClient>) -> Option<(Uri, Uri)> {
+        let (principal, home) =
+            Cache::load_discovery(&self.discovery_key(client), DISCOVERY_TTL_SECS)?;
+        Some((principal.parse().ok()?, home.parse().ok()?))
+    }
 
-        if active_href.is_none()
-            && warning.is_none()
-            && let Ok(href) = client.discover_calendar().await
-        {
-            active_href = Some(href);
+    /// Principal and calendar-home-set URLs. Discovery takes two round trips, so the
+    /// result is cached for `DISCOVERY_TTL_SECS`.
+    async fn calendar_home(
+        &self,
+        client: &CalDavClient<HttpsClient>,
+    ) -> Result<(Uri, Uri), ClientError> {
+        if let Some(found) = self.cached_home(client) {
+            return Ok(found);
         }
+        let principal = client
+            .find_current_user_principal()
+            .await
+            .map_err(ClientError::from_debug)?
+            .ok_or_else(|| ClientError::NotFound("No principal".to_string()))?;
 
-        let tasks = if warning.is_none() {
-            if let Some(ref h) = active_href {
-                client.get_tasks(h).await.unwrap_or_default()
-            } else {
-                vec![]
-            }
-        } else if let Some(ref h) = active_href {
-            Cache::load(h).map(|res| res.0).unwrap_or_default()
-        } else {
-            vec![]
-        };
-
-        Ok((client, calendars, tasks, active_href, warning))
+        let home_set_resp = client
+            .request(FindCalendarHomeSet::new(&principal))
+            .await
+            .map_err(ClientError::from_debug)?;
+        let home_url = home_set_resp
+            .home_sets
+            .into_iter()
+            .next()
+            .ok_or_else(|| ClientError::NotFound("No home set".to_string()))?;
+
+        let _ = Cache::save_discovery(
+            &self.discovery_key(client),
+            &principal.to_string(),
+            &home_url.to_string(),
+        );
+        Ok((principal, home_url))
     }
 
-    pub async fn get_calendars(&self) -> Result<Vec<CalendarListEntry>, String> {
+    pub async fn get_calendars(&self) -> Result<Vec<CalendarListEntry>, ClientError> {
+        if self.is_offline() {
+            return Err(ClientError::Offline);
+        }
         if let Some(client) = &self.client {
-            let principal = client
-                .find_current_user_principal()
-                .await
-                .map_err(|e| format!("{:?}", e))?
-                .ok_or("No principal")?;
-
-            let home_set_resp = client
-                .request(FindCalendarHomeSet::new(&principal))
-                .await
-                .map_err(|e| format!("{:?}", e))?;
-
-            let home_url = home_set_resp.home_sets.first().ok_or("No home set")?;
+            let from_cache = self.cached_home(client).is_some();
+            let (principal, home_url) = self.calendar_home(client).await?;
+
+            let cals_resp = match client.request(FindCalendars::new(&home_url)).await {
+                Ok(resp) => resp,
+                // The cached home set may be stale (account moved?): forget it and retry
+                Err(_) if from_cache => {
+                    Cache::clear_discovery(&self.discovery_key(client));
+                    return Box::pin(self.get_calendars()).await;
+                }
+                Err(e) => return Err(ClientError::from_debug(e)),
+            };
 
-            let cals_resp = client
-                .request(FindCalendars::new(home_url))
+            // One PROPFIND for the whole home set; servers without ACL support are writable
+            let access = self
+                .collection_access(home_url.path())
                 .await
-                .map_err(|e| format!("{:?}", e))?;
+                .unwrap_or_default();
+            let me = collection_key(&principal.to_string());
 
             let mut calendars = Vec::new();
             for col in cals_resp.calendars {
@@ -233,17 +705,37 @@ impl RustyClient {
                     .and_then(|r| r.value)
                     .unwrap_or_else(|| col.href.clone());
 
-                // Fetch Color
-                let color = client
-                    .request(GetProperty::new(&col.href, &APPLE_COLOR))
-                    .await
-                    .ok()
-                    .and_then(|r| r.value);
+                // Fetch Color (Apple namespace first, then the CalDAV one some servers use)
+                let mut color = None;
+                for prop in [&APPLE_COLOR, &CALDAV_COLOR] {
+                    color = client
+                        .request(GetProperty::new(&col.href, prop))
+                        .await
+                        .ok()
+                        .and_then(|r| r.value)
+                        .and_then(|v| color_utils::normalize_hex_color(&v));
+                    if color.is_some() {
+                        break;
+                    }
+                }
 
+                let acc = access
+                    .get(&collection_key(&col.href))
+                    .cloned()
+                    .unwrap_or_default();
+                let shared = acc.shared_url.is_some()
+                    || acc
+                        .owner_href
+                        .as_deref()
+                        .is_some_and(|o| collection_key(o) != me);
                 calendars.push(CalendarListEntry {
                     name,
                     href: col.href,
                     color, // Store it
+                    read_only: acc.read_only.unwrap_or(false),
+                    owner: owner_label(&acc),
+                    shared,
+                    account: self.account.clone(),
                 });
             }
             Ok(calendars)
@@ -252,76 +744,265 @@ impl RustyClient {
         }
     }
 
+    /// Storage quota of the calendar home (RFC 4331).
+    /// `None` when the server doesn't report one.
+    pub async fn quota(&self) -> Result<Option<Quota>, ClientError> {
+        let client = self.online_client().ok_or(ClientError::Offline)?;
+        let (_, home_url) = self.calendar_home(client).await?;
+        let uri = client
+            .webdav_client
+            .relative_uri(home_url.path())
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+
+        let req = Request::builder()
+            .method("PROPFIND")
+            .uri(uri)
+            .header("Depth", "0")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(quota::build_quota_body())?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if parts.status != StatusCode::MULTI_STATUS {
+            return Err(ClientError::from_status(parts.status, "PROPFIND"));
+        }
+        let quota =
+            quota::parse_quota(&String::from_utf8_lossy(&body)).map_err(ClientError::Parse)?;
+        Ok(Some(quota).filter(|q| !q.is_empty()))
+    }
+
+    /// WebDAV-Push: registers `push_resource` on every calendar that supports it.
+    /// Returns topic -> calendar href, to resolve incoming `push::ChangeEvent`s, and a
+    /// warning per calendar that couldn't subscribe. Calendars without push support
+    /// are skipped (they still sync on refresh).
+    pub async fn subscribe_push(
+        &self,
+        calendars: &[CalendarListEntry],
+        push_resource: &str,
+    ) -> Result<(HashMap<String, String>, Vec<String>), ClientError> {
+        let client = self.online_client().ok_or(ClientError::Offline)?;
+        let expires = chrono::Utc::now() + chrono::Duration::days(push::REGISTRATION_DAYS);
+        let mut topics = HashMap::new();
+        let mut warnings = Vec::new();
+
+        for cal in calendars.iter().filter(|c| !is_local_calendar(&c.href)) {
+            let uri = client
+                .webdav_client
+                .relative_uri(&cal.href)
+                .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+
+            let req = Request::builder()
+                .method("PROPFIND")
+                .uri(uri.clone())
+                .header("Depth", "0")
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(push::build_push_support_body())?;
+            let Ok((parts, body)) = client.webdav_client.request_raw(req).await else {
+                continue;
+            };
+            if parts.status != StatusCode::MULTI_STATUS {
+                continue;
+            }
+            let support = match push::parse_push_support(&String::from_utf8_lossy(&body)) {
+                Ok(s) => s,
+                Err(e) => {
+                    warnings.push(format!("Push support of {}: {}", cal.name, e));
+                    continue;
+                }
+            };
+            let Some(topic) = support.topic.filter(|_| support.web_push) else {
+                continue;
+            };
+
+            let req = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("Content-Type", "application/xml; charset=utf-8")
+                .body(push::build_register_body(push_resource, expires))?;
+            let (parts, _) = client
+                .webdav_client
+                .request_raw(req)
+                .await
+                .map_err(ClientError::from_debug)?;
+            // 201 = new registration, 204 = existing one refreshed. The other
+            // calendars may still subscribe.
+            if !parts.status.is_success() {
+                warnings.push(
+                    ClientError::from_status(
+                        parts.status,
+                        &format!("Push registration for {}", cal.name),
+                    )
+                    .to_string(),
+                );
+                continue;
+            }
+            topics.insert(topic, cal.href.clone());
+        }
+        Ok((topics, warnings))
+    }
+
     // --- TASK FETCHING ---
 
     async fn fetch_calendar_tasks_internal(
         &self,
         calendar_href: &str,
-    ) -> Result<Vec<Task>, String> {
-        if calendar_href == LOCAL_CALENDAR_HREF {
-            return LocalStorage::load().map_err(|e| e.to_string());
+    ) -> Result<Vec<Task>, ClientError> {
+        self.fetch_calendar_tasks_with_state(calendar_href, None)
+            .await
+    }
+
+    // `known_state` comes from a batched PROPFIND (see get_all_tasks), saving a request
+    async fn fetch_calendar_tasks_with_state(
+        &self,
+        calendar_href: &str,
+        known_state: Option<CollectionState>,
+    ) -> Result<Vec<Task>, ClientError> {
+        if is_local_calendar(calendar_href) {
+            return LocalStorage::load_calendar(calendar_href).map_err(ClientError::from);
         }
+        // The cache may predate the setting (or a smaller value of it)
+        let mut tasks = self.fetch_server_tasks(calendar_href, known_state).await?;
+        self.drop_old_completed(&mut tasks);
+        Ok(tasks)
+    }
+
+    // Start of the window of completed tasks worth keeping
+    fn completed_cutoff(&self) -> Option<DateTime<Utc>> {
+        self.completed_max_age_days
+            .map(|days| Utc::now() - chrono::Duration::days(days as i64))
+    }
 
-        let (cached_tasks, cached_token) = Cache::load(calendar_href).unwrap_or((vec![], None));
+    fn drop_old_completed(&self, tasks: &mut Vec<Task>) {
+        if let Some(cutoff) = self.completed_cutoff() {
+            tasks.retain(|t| t.completed_at.is_none_or(|done| done >= cutoff));
+        }
+    }
+
+    async fn fetch_server_tasks(
+        &self,
+        calendar_href: &str,
+        known_state: Option<CollectionState>,
+    ) -> Result<Vec<Task>, ClientError> {
+        let cache_key = self.cache_key(calendar_href);
+        let (cached_tasks, cached_token) = Cache::load(&cache_key).unwrap_or((vec![], None));
+        if self.is_offline() {
+            return Ok(cached_tasks);
+        }
 
         if let Some(client) = &self.client {
             let path_href = strip_host(calendar_href);
 
-            let remote_token = if let Ok(resp) = client
-                .request(GetProperty::new(&path_href, &GET_CTAG))
-                .await
-                && let Some(val) = resp.value
-            {
-                Some(val)
-            } else if let Ok(resp) = client
-                .request(GetProperty::new(&path_href, &names::SYNC_TOKEN))
-                .await
-                && let Some(val) = resp.value
-            {
-                Some(val)
-            } else {
-                None
+            let state = match known_state {
+                Some(s) => s,
+                None => self.collection_state(&path_href).await,
             };
 
-            if let Some(r_tok) = &remote_token
+            // Unchanged ctag: nothing to do, the cache is up to date
+            if let Some(r_ctag) = &state.ctag
+                && Cache::load_ctag(&cache_key).as_ref() == Some(r_ctag)
+            {
+                return Ok(cached_tasks);
+            }
+
+            // Prefer the RFC 6578 sync-token: it enables incremental sync below.
+            let remote_sync_token = state.sync_token.clone();
+            let remote_ctag = state.ctag.clone();
+
+            if let Some(r_tok) = &remote_sync_token
                 && let Some(c_tok) = &cached_token
                 && r_tok == c_tok
             {
                 return Ok(cached_tasks);
             }
 
-            let list_resp = client
-                .request(ListResources::new(&path_href))
-                .await
-                .map_err(|e| format!("PROPFIND: {:?}", e))?;
+            // --- INCREMENTAL SYNC (sync-collection REPORT) ---
+            // Falls back to the full PROPFIND listing if the server rejects the token
+            // (e.g. it expired, or the cached value is an old CTAG) or truncates the result.
+            if remote_sync_token.is_some()
+                && let Some(c_tok) = &cached_token
+                && let Ok(delta) = self.sync_collection(&path_href, c_tok).await
+                && !delta.truncated
+            {
+                let mut cache_map: HashMap<String, Task> = HashMap::new();
+                for t in &cached_tasks {
+                    cache_map.insert(strip_host(&t.href), t.clone());
+                }
+
+                for href in &delta.deleted {
+                    cache_map.remove(&strip_host(href));
+                }
+
+                let mut to_fetch = Vec::new();
+                for (href, etag) in &delta.changed {
+                    if !href.ends_with(".ics") {
+                        continue;
+                    }
+                    let key = strip_host(href);
+                    let unchanged = matches!(
+                        (cache_map.get(&key), etag),
+                        (Some(local), Some(r_etag)) if *r_etag == local.etag
+                    );
+                    if !unchanged {
+                        cache_map.remove(&key);
+                        to_fetch.push(key);
+                    }
+                }
+
+                let mut final_tasks = self
+                    .fetch_resources_batched(
+                        &path_href,
+                        calendar_href,
+                        to_fetch,
+                        cache_map.into_values().collect(),
+                    )
+                    .await?;
+                self.drop_old_completed(&mut final_tasks);
+                record_remote_changes(&cached_tasks, &final_tasks);
+
+                let new_token = delta.new_token.or(remote_sync_token);
+                let _ = Cache::save_with_ctag(&cache_key, &final_tasks, new_token, remote_ctag);
+                return Ok(final_tasks);
+            }
+
+            // Only VTODOs; servers without calendar-query get the plain PROPFIND listing
+            let listing = match self.list_todos(&path_href).await {
+                Ok(listing) => listing,
+                Err(_) => client
+                    .request(ListResources::new(&path_href))
+                    .await
+                    .map_err(ClientError::from_debug)?
+                    .resources
+                    .into_iter()
+                    .filter(|r| r.href.ends_with(".ics"))
+                    .map(|r| (r.href, r.etag))
+                    .collect(),
+            };
 
             let mut cache_map: HashMap<String, Task> = HashMap::new();
-            for t in cached_tasks {
-                cache_map.insert(t.href.clone(), t);
+            for t in &cached_tasks {
+                cache_map.insert(t.href.clone(), t.clone());
             }
 
             let mut final_tasks = Vec::new();
             let mut to_fetch = Vec::new();
             let mut server_hrefs = HashSet::new();
 
-            for resource in list_resp.resources {
-                if !resource.href.ends_with(".ics") {
-                    continue;
-                }
-                server_hrefs.insert(resource.href.clone());
-                let remote_etag = resource.etag;
+            for (href, remote_etag) in listing {
+                server_hrefs.insert(href.clone());
 
-                if let Some(local_task) = cache_map.remove(&resource.href) {
+                if let Some(local_task) = cache_map.remove(&href) {
                     if let Some(r_etag) = &remote_etag
                         && !r_etag.is_empty()
                         && *r_etag == local_task.etag
                     {
                         final_tasks.push(local_task);
                     } else {
-                        to_fetch.push(strip_host(&resource.href));
+                        to_fetch.push(strip_host(&href));
                     }
                 } else {
-                    to_fetch.push(strip_host(&resource.href));
+                    to_fetch.push(strip_host(&href));
                 }
             }
 
@@ -331,51 +1012,258 @@ impl RustyClient {
                 }
             }
 
-            if !to_fetch.is_empty() {
-                let fetched_resp = client
-                    .request(GetCalendarResources::new(&path_href).with_hrefs(to_fetch))
-                    .await
-                    .map_err(|e| format!("MULTIGET: {:?}", e))?;
-
-                for item in fetched_resp.resources {
-                    if let Ok(content) = item.content
-                        && let Ok(task) = Task::from_ics(
-                            &content.data,
-                            content.etag,
-                            item.href,
-                            calendar_href.to_string(),
-                        )
-                    {
-                        final_tasks.push(task);
-                    }
-                }
-            }
+            // Servers without calendar-query sent us everything: trim before caching
+            let mut final_tasks = self
+                .fetch_resources_batched(&path_href, calendar_href, to_fetch, final_tasks)
+                .await?;
+            self.drop_old_completed(&mut final_tasks);
+            record_remote_changes(&cached_tasks, &final_tasks);
 
-            let _ = Cache::save(calendar_href, &final_tasks, remote_token);
+            let _ = Cache::save_with_ctag(&cache_key, &final_tasks, remote_sync_token, remote_ctag);
             Ok(final_tasks)
         } else {
-            Err("Offline".to_string())
+            Err(ClientError::Offline)
+        }
+    }
+
+    // Multiget in batches of `fetch_batch_size`, appending to `known` and reporting
+    // progress after each batch. Keeps each response (and its parse) small.
+    async fn fetch_resources_batched(
+        &self,
+        path_href: &str,
+        calendar_href: &str,
+        hrefs: Vec<String>,
+        mut known: Vec<Task>,
+    ) -> Result<Vec<Task>, ClientError> {
+        let batches: Vec<Vec<String>> = hrefs
+            .chunks(self.fetch_batch_size.max(1))
+            .map(|c| c.to_vec())
+            .collect();
+        let report = batches.len() > 1;
+        for batch in batches {
+            known.extend(
+                self.fetch_resources(path_href, calendar_href, batch)
+                    .await?,
+            );
+            if report && let Some(progress) = &self.progress {
+                (progress.0)(calendar_href, &known);
+            }
+        }
+        Ok(known)
+    }
+
+    // Calendar-multiget of the given resource paths
+    async fn fetch_resources(
+        &self,
+        path_href: &str,
+        calendar_href: &str,
+        hrefs: Vec<String>,
+    ) -> Result<Vec<Task>, ClientError> {
+        let client = self.client.as_ref().ok_or(ClientError::Offline)?;
+        let mut tasks = Vec::new();
+        if hrefs.is_empty() {
+            return Ok(tasks);
+        }
+
+        let fetched_resp = client
+            .request(GetCalendarResources::new(path_href).with_hrefs(hrefs))
+            .await
+            .map_err(ClientError::from_debug)?;
+
+        for item in fetched_resp.resources {
+            if let Ok(content) = item.content
+                && let Ok(mut task) = Task::from_ics(
+                    &content.data,
+                    content.etag,
+                    item.href,
+                    calendar_href.to_string(),
+                )
+            {
+                task.account = self.account.clone();
+                tasks.push(task);
+            }
         }
+        Ok(tasks)
     }
 
-    pub async fn get_tasks(&self, calendar_href: &str) -> Result<Vec<Task>, String> {
-        let _ = self.sync_journal().await;
+    // getctag + sync-token of a collection (Depth 0) or of its children (Depth 1)
+    async fn collection_states(
+        &self,
+        path_href: &str,
+        depth: &str,
+    ) -> Result<HashMap<String, CollectionState>, ClientError> {
+        let client = self.online_client().ok_or(ClientError::Offline)?;
+        let uri = client
+            .webdav_client
+            .relative_uri(path_href)
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("PROPFIND")
+            .uri(uri)
+            .header("Depth", depth)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(build_collection_state_body())?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if parts.status != StatusCode::MULTI_STATUS {
+            return Err(ClientError::from_status(parts.status, "PROPFIND"));
+        }
+        Ok(parse_collection_states(&String::from_utf8_lossy(&body))
+            .map_err(ClientError::Parse)?
+            .into_iter()
+            .map(|(href, state)| (collection_key(&href), state))
+            .collect())
+    }
+
+    async fn collection_access(
+        &self,
+        home_href: &str,
+    ) -> Result<HashMap<String, CollectionAccess>, ClientError> {
+        let client = self.client.as_ref().ok_or(ClientError::Offline)?;
+        let uri = client
+            .webdav_client
+            .relative_uri(home_href)
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("PROPFIND")
+            .uri(uri)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(build_privilege_body())?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if parts.status != StatusCode::MULTI_STATUS {
+            return Err(ClientError::from_status(parts.status, "PROPFIND"));
+        }
+        Ok(parse_collection_access(&String::from_utf8_lossy(&body))
+            .map_err(ClientError::Parse)?
+            .into_iter()
+            .map(|(href, access)| (collection_key(&href), access))
+            .collect())
+    }
+
+    async fn collection_state(&self, path_href: &str) -> CollectionState {
+        self.collection_states(path_href, "0")
+            .await
+            .ok()
+            .and_then(|mut states| states.remove(&collection_key(path_href)))
+            .unwrap_or_default()
+    }
+
+    // RFC 6578 sync-collection REPORT against a previously stored token
+    async fn sync_collection(
+        &self,
+        path_href: &str,
+        token: &str,
+    ) -> Result<SyncDelta, ClientError> {
+        let client = self.client.as_ref().ok_or(ClientError::Offline)?;
+        let uri = client
+            .webdav_client
+            .relative_uri(path_href)
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("REPORT")
+            .uri(uri)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(build_sync_collection_body(token))?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if parts.status != StatusCode::MULTI_STATUS {
+            return Err(ClientError::from_status(parts.status, "sync-collection"));
+        }
+        parse_sync_collection_response(&String::from_utf8_lossy(&body)).map_err(ClientError::Parse)
+    }
+
+    // RFC 4791 calendar-query: (href, etag) of the VTODO resources only,
+    // minus the old completed ones when `completed_max_age_days` is set
+    async fn list_todos(
+        &self,
+        path_href: &str,
+    ) -> Result<Vec<(String, Option<String>)>, ClientError> {
+        let Some(cutoff) = self.completed_cutoff() else {
+            return self.query_todos(path_href, build_todo_query_body()).await;
+        };
+        let mut listing = self
+            .query_todos(path_href, build_open_todo_query_body())
+            .await?;
+        let recent = self
+            .query_todos(path_href, build_completed_since_query_body(cutoff))
+            .await?;
+        let seen: HashSet<String> = listing.iter().map(|(h, _)| h.clone()).collect();
+        listing.extend(recent.into_iter().filter(|(h, _)| !seen.contains(h)));
+        Ok(listing)
+    }
+
+    async fn query_todos(
+        &self,
+        path_href: &str,
+        body: String,
+    ) -> Result<Vec<(String, Option<String>)>, ClientError> {
+        let client = self.client.as_ref().ok_or(ClientError::Offline)?;
+        let uri = client
+            .webdav_client
+            .relative_uri(path_href)
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("REPORT")
+            .uri(uri)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body)?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if parts.status != StatusCode::MULTI_STATUS {
+            return Err(ClientError::from_status(parts.status, "calendar-query"));
+        }
+        parse_etag_listing(&String::from_utf8_lossy(&body)).map_err(ClientError::Parse)
+    }
+
+    pub async fn get_tasks(&self, calendar_href: &str) -> Result<Vec<Task>, ClientError> {
+        let _ = self.sync_if_online().await;
         self.fetch_calendar_tasks_internal(calendar_href).await
     }
 
     pub async fn get_all_tasks(
         &self,
         calendars: &[CalendarListEntry],
-    ) -> Result<Vec<(String, Vec<Task>)>, String> {
-        let _ = self.sync_journal().await;
+    ) -> Result<Vec<(String, Vec<Task>)>, ClientError> {
+        let _ = self.sync_if_online().await;
+
+        // One Depth:1 PROPFIND per parent collection tells us which calendars changed
+        let mut parents: HashSet<String> = HashSet::new();
+        for cal in calendars.iter().filter(|c| !is_local_calendar(&c.href)) {
+            let key = collection_key(&cal.href);
+            if let Some((parent, _)) = key.rsplit_once('/') {
+                parents.insert(format!("{}/", parent));
+            }
+        }
+        let mut states: HashMap<String, CollectionState> = HashMap::new();
+        for parent in parents {
+            if let Ok(found) = self.collection_states(&parent, "1").await {
+                states.extend(found);
+            }
+        }
 
-        let hrefs: Vec<String> = calendars.iter().map(|c| c.href.clone()).collect();
-        let futures = hrefs.into_iter().map(|href| {
+        let futures = calendars.iter().map(|cal| {
             let client = self.clone();
+            let href = cal.href.clone();
+            let state = states.get(&collection_key(&href)).cloned();
             async move {
                 (
                     href.clone(),
-                    client.fetch_calendar_tasks_internal(&href).await,
+                    client.fetch_calendar_tasks_with_state(&href, state).await,
                 )
             }
         });
@@ -394,14 +1282,66 @@ impl RustyClient {
 
     // --- TASK OPERATIONS ---
 
-    pub async fn create_task(&self, task: &mut Task) -> Result<Vec<String>, String> {
-        if task.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut all = LocalStorage::load().map_err(|e| e.to_string())?;
-            all.push(task.clone());
-            LocalStorage::save(&all).map_err(|e| e.to_string())?;
+    pub async fn create_task(&self, task: &mut Task) -> Result<Vec<String>, ClientError> {
+        self.queue_create(task)?;
+        hooks::fire(&self.hooks, Event::TaskCreated, task);
+        if is_local_calendar(&task.calendar_href) {
             return Ok(vec![]);
         }
+        self.sync_if_online().await
+    }
+
+    // Queues a change for the server, and shows it in the cache right away so an
+    // offline restart doesn't go back to the last fetched version
+    fn queue(&self, mut action: Action) -> Result<(), ClientError> {
+        action.task_mut().account = self.account.clone();
+        Journal::push_for(self.account.as_deref(), action.clone())?;
+        let _ = Cache::apply(&action);
+        Ok(())
+    }
+
+    // Cache of one of our calendars (see `Cache::key`)
+    fn cache_key(&self, calendar_href: &str) -> String {
+        Cache::key(self.account.as_deref(), calendar_href)
+    }
+
+    // Refuse to queue writes against a collection the server reported as read-only,
+    // instead of letting the journal retry a 403 forever
+    fn ensure_writable(&self, calendar_href: &str) -> Result<(), ClientError> {
+        let key = collection_key(calendar_href);
+        match Cache::load_calendars()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|c| c.read_only && c.account == self.account && collection_key(&c.href) == key)
+        {
+            Some(cal) => Err(ClientError::Forbidden(format!(
+                "Calendar '{}' is read-only",
+                cal.name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    // Stores a new task in the Local calendar, or queues it for the server
+    fn queue_create(&self, task: &mut Task) -> Result<(), ClientError> {
+        // Starting point for the history of later edits
+        let _ = History::record(None, task, ChangeOrigin::Local);
+        if is_local_calendar(&task.calendar_href) {
+            let mut all = LocalStorage::load_calendar(&task.calendar_href)?;
+            all.push(task.clone());
+            LocalStorage::save_calendar(&task.calendar_href, &all)?;
+            return Ok(());
+        }
+
+        self.ensure_writable(&task.calendar_href)?;
+        self.prepare_create(task);
+        self.queue(Action::Create(task.clone()))?;
+        Ok(())
+    }
 
+    // Account and resource href of a new server task
+    fn prepare_create(&self, task: &mut Task) {
+        task.account = self.account.clone();
         let cal_path = task.calendar_href.clone();
         let filename = format!("{}.ics", task.uid);
         let full_href = if cal_path.ends_with('/') {
@@ -410,56 +1350,184 @@ impl RustyClient {
             format!("{}/{}", cal_path, filename)
         };
         task.href = full_href;
+    }
+
+    /// Restores a task from the trash into the calendar it was deleted from.
+    /// Server calendars get it back like a new task, through the journal.
+    pub async fn undelete(&self, uid: &str) -> Result<(Task, Vec<String>), ClientError> {
+        let entry = Trash::load()?
+            .into_iter()
+            .find(|e| e.task.uid == uid)
+            .ok_or_else(|| ClientError::NotFound(format!("{} is not in the trash", uid)))?;
+        let mut task = entry.task;
+        task.calendar_href = entry.calendar_href;
+        task.etag.clear();
+        // Its local calendar may have been deleted since
+        if is_local_calendar(&task.calendar_href)
+            && !LocalStorage::calendar_exists(&task.calendar_href)
+        {
+            task.calendar_href = LOCAL_CALENDAR_HREF.to_string();
+        }
+
+        self.queue_create(&mut task)?;
+        Trash::take(uid)?;
+        if is_local_calendar(&task.calendar_href) {
+            return Ok((task, vec![]));
+        }
+        let msgs = self.sync_if_online().await?;
+        Ok((task, msgs))
+    }
+
+    /// Undoes the last change that hasn't reached the server yet, by queueing its
+    /// inverse (see `Journal::revert_last`). Returns the inverse, to apply to the
+    /// task list, or None when there is nothing left to undo.
+    pub async fn undo_last(&self) -> Result<Option<Action>, ClientError> {
+        let Some(inverse) = Journal::revert_last()? else {
+            return Ok(None);
+        };
+        if let Action::Create(t) = &inverse {
+            let _ = Trash::take(&t.uid);
+        }
+        let _ = Cache::apply(&inverse);
+        self.sync_if_online().await?;
+        Ok(Some(inverse))
+    }
+
+    /// Settles a conflict set aside by the interactive policy (see `Journal::conflicts`).
+    /// Returns the tasks to show in place of the local version.
+    pub async fn resolve_conflict(
+        &self,
+        uid: &str,
+        keep: Resolution,
+    ) -> Result<(Vec<Task>, Vec<String>), ClientError> {
+        let Conflict { local, server, .. } = Journal::take_conflict(uid)?
+            .ok_or_else(|| ClientError::NotFound(format!("No conflict on {}", uid)))?;
+        let calendar_href = local.calendar_href.clone();
+        let tasks = match keep {
+            Resolution::Local => {
+                let mut forced = local;
+                forced.etag = server.etag;
+                self.queue(Action::Update(forced.clone()))?;
+                vec![forced]
+            }
+            Resolution::Server => vec![server],
+            Resolution::Both => {
+                let mut copy = conflict_copy(&local);
+                self.queue_create(&mut copy)?;
+                vec![server, copy]
+            }
+        };
+        // Show the kept versions right away, even offline
+        let _ = Cache::modify(&self.cache_key(&calendar_href), |cached| {
+            for task in &tasks {
+                match cached.iter_mut().find(|t| t.uid == task.uid) {
+                    Some(t) => *t = task.clone(),
+                    None => cached.push(task.clone()),
+                }
+            }
+            true
+        });
+        let msgs = self.sync_if_online().await?;
+        Ok((tasks, msgs))
+    }
+
+    pub async fn update_task(&self, task: &mut Task) -> Result<Vec<String>, ClientError> {
+        self.queue_update(task)?;
+        if is_local_calendar(&task.calendar_href) {
+            return Ok(vec![]);
+        }
+        self.sync_if_online().await
+    }
+
+    // Saves a Local task, or queues the edit for the server
+    fn queue_update(&self, task: &mut Task) -> Result<(), ClientError> {
+        task.sequence += 1;
+        if is_local_calendar(&task.calendar_href) {
+            let mut all = LocalStorage::load_calendar(&task.calendar_href)?;
+            if let Some(idx) = all.iter().position(|t| t.uid == task.uid) {
+                let _ = History::record(Some(&all[idx]), task, ChangeOrigin::Local);
+                self.fire_if_completed(Some(&all[idx]), task);
+                all[idx] = task.clone();
+                LocalStorage::save_calendar(&task.calendar_href, &all)?;
+            }
+            return Ok(());
+        }
+
+        self.ensure_writable(&task.calendar_href)?;
+        let cached = self.prepare_update(task);
+        self.queue(Action::Update(task.clone()))?;
+        self.fire_if_completed(cached.as_ref(), task);
+        Ok(())
+    }
+
+    // Records the edit of a server task in the history. Returns the version before it.
+    fn prepare_update(&self, task: &mut Task) -> Option<Task> {
+        task.account = self.account.clone();
+        // The cached copy is the version before this edit (unless the history knows better)
+        let cached = Cache::load(&self.cache_key(&task.calendar_href))
+            .ok()
+            .and_then(|(tasks, _)| tasks.into_iter().find(|t| t.uid == task.uid));
+        let _ = History::record(cached.as_ref(), task, ChangeOrigin::Local);
+        cached
+    }
 
-        Journal::push(Action::Create(task.clone())).map_err(|e| e.to_string())?;
-        self.sync_journal().await
+    // Runs the on_task_completed hook when this edit is what completed the task
+    fn fire_if_completed(&self, before: Option<&Task>, task: &Task) {
+        let was_completed = before.is_some_and(|t| t.status == TaskStatus::Completed);
+        if task.status == TaskStatus::Completed && !was_completed {
+            hooks::fire(&self.hooks, Event::TaskCompleted, task);
+        }
     }
 
-    pub async fn update_task(&self, task: &mut Task) -> Result<Vec<String>, String> {
-        if task.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut all = LocalStorage::load().map_err(|e| e.to_string())?;
-            if let Some(idx) = all.iter().position(|t| t.uid == task.uid) {
-                all[idx] = task.clone();
-                LocalStorage::save(&all).map_err(|e| e.to_string())?;
-            }
+    /// Deletes a task, keeping a copy in the trash (see `undelete`).
+    pub async fn delete_task(&self, task: &Task) -> Result<Vec<String>, ClientError> {
+        self.ensure_writable(&task.calendar_href)?;
+        Trash::add(task, self.trash_days)?;
+        self.remove_task(task).await
+    }
+
+    // Deletes without going through the trash (the task lives on elsewhere after a move)
+    pub(crate) async fn remove_task(&self, task: &Task) -> Result<Vec<String>, ClientError> {
+        self.queue_remove(task)?;
+        if is_local_calendar(&task.calendar_href) {
             return Ok(vec![]);
         }
-
-        Journal::push(Action::Update(task.clone())).map_err(|e| e.to_string())?;
-        self.sync_journal().await
+        self.sync_if_online().await
     }
 
-    pub async fn delete_task(&self, task: &Task) -> Result<Vec<String>, String> {
-        if task.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut all = LocalStorage::load().map_err(|e| e.to_string())?;
+    fn queue_remove(&self, task: &Task) -> Result<(), ClientError> {
+        if is_local_calendar(&task.calendar_href) {
+            let mut all = LocalStorage::load_calendar(&task.calendar_href)?;
             all.retain(|t| t.uid != task.uid);
-            LocalStorage::save(&all).map_err(|e| e.to_string())?;
-            return Ok(vec![]);
+            LocalStorage::save_calendar(&task.calendar_href, &all)?;
+            return Ok(());
         }
 
-        Journal::push(Action::Delete(task.clone())).map_err(|e| e.to_string())?;
-        self.sync_journal().await
+        self.ensure_writable(&task.calendar_href)?;
+        self.queue(Action::Delete(task.clone()))
     }
 
     pub async fn toggle_task(
         &self,
         task: &mut Task,
-    ) -> Result<(Task, Option<Task>, Vec<String>), String> {
+    ) -> Result<(Task, Option<Task>, Vec<String>), ClientError> {
         let next_task = if task.status == TaskStatus::Completed {
             task.respawn()
         } else {
             None
         };
 
-        if task.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut all = LocalStorage::load().map_err(|e| e.to_string())?;
+        if is_local_calendar(&task.calendar_href) {
+            let mut all = LocalStorage::load_calendar(&task.calendar_href)?;
             if let Some(idx) = all.iter().position(|t| t.uid == task.uid) {
+                self.fire_if_completed(Some(&all[idx]), task);
                 all[idx] = task.clone();
             }
             if let Some(new_t) = &next_task {
                 all.push(new_t.clone());
+                hooks::fire(&self.hooks, Event::TaskCreated, new_t);
             }
-            LocalStorage::save(&all).map_err(|e| e.to_string())?;
+            LocalStorage::save_calendar(&task.calendar_href, &all)?;
             return Ok((task.clone(), next_task, vec![]));
         }
 
@@ -478,31 +1546,131 @@ impl RustyClient {
         &self,
         task: &Task,
         new_calendar_href: &str,
-    ) -> Result<(Task, Vec<String>), String> {
-        if task.calendar_href == LOCAL_CALENDAR_HREF {
+    ) -> Result<(Task, Vec<String>), ClientError> {
+        let moved = self.queue_move(task, new_calendar_href)?;
+        if is_local_calendar(&task.calendar_href) && is_local_calendar(new_calendar_href) {
+            return Ok((moved, vec![]));
+        }
+        let logs = self.sync_if_online().await?;
+        Ok((moved, logs))
+    }
+
+    // Returns the task as it is in its new calendar
+    fn queue_move(&self, task: &Task, new_calendar_href: &str) -> Result<Task, ClientError> {
+        // Nothing to move on the server: a copy is created on the other side
+        if is_local_calendar(&task.calendar_href) || is_local_calendar(new_calendar_href) {
             let mut new_task = task.clone();
             new_task.calendar_href = new_calendar_href.to_string();
             new_task.href = String::new();
             new_task.etag = String::new();
-            self.create_task(&mut new_task).await?;
-            self.delete_task(task).await?;
-            return Ok((new_task, vec![]));
+            self.queue_create(&mut new_task)?;
+            hooks::fire(&self.hooks, Event::TaskCreated, &new_task);
+            self.queue_remove(task)?;
+            return Ok(new_task);
         }
 
-        Journal::push(Action::Move(task.clone(), new_calendar_href.to_string()))
-            .map_err(|e| e.to_string())?;
+        self.ensure_writable(&task.calendar_href)?;
+        self.ensure_writable(new_calendar_href)?;
+        self.queue(Action::Move(task.clone(), new_calendar_href.to_string()))?;
 
         let mut t = task.clone();
         t.calendar_href = new_calendar_href.to_string();
-        let logs = self.sync_journal().await?;
-        Ok((t, logs))
+        Ok(t)
+    }
+
+    /// Bulk changes: everything goes to the journal first, then a single sync sends it.
+    /// Deletions keep a copy in the trash like `delete_task`. The whole batch is checked
+    /// before anything is queued (e.g. for read-only calendars), and the server changes
+    /// are queued in one journal write: all of them or none.
+    pub async fn apply_batch(&self, actions: Vec<Action>) -> Result<Vec<String>, ClientError> {
+        for action in &actions {
+            self.ensure_writable(action.calendar_href())?;
+            if let Action::Move(_, new_calendar_href) = action {
+                self.ensure_writable(new_calendar_href)?;
+            }
+        }
+
+        // Local calendars have no journal, moves in or out of them are a create and a delete
+        let (local, server): (Vec<Action>, Vec<Action>) = actions.into_iter().partition(|a| {
+            is_local_calendar(a.calendar_href())
+                || matches!(a, Action::Move(_, new_href) if is_local_calendar(new_href))
+        });
+        let remote = !server.is_empty()
+            || local
+                .iter()
+                .any(|a| matches!(a, Action::Move(_, new_href) if !is_local_calendar(new_href)));
+
+        let mut queued = Vec::with_capacity(server.len());
+        let mut before = HashMap::new();
+        for mut action in server {
+            match &mut action {
+                Action::Create(task) => {
+                    let _ = History::record(None, task, ChangeOrigin::Local);
+                    self.prepare_create(task);
+                }
+                Action::Update(task) => {
+                    task.sequence += 1;
+                    if let Some(cached) = self.prepare_update(task) {
+                        before.insert(task.uid.clone(), cached);
+                    }
+                }
+                Action::Delete(_) | Action::Move(..) => {}
+            }
+            action.task_mut().account = self.account.clone();
+            queued.push(action);
+        }
+        if !queued.is_empty() {
+            let mut trashed = Ok(());
+            Journal::modify_journal(|journal| {
+                // A deletion that can't keep its copy in the trash cancels the batch
+                trashed = queued.iter().try_for_each(|action| match action {
+                    Action::Delete(task) => Trash::add(task, self.trash_days),
+                    _ => Ok(()),
+                });
+                if trashed.is_ok() {
+                    journal.queue.extend(queued.iter().cloned());
+                    for action in &queued {
+                        let _ = Cache::apply(action);
+                    }
+                }
+            })?;
+            trashed?;
+        }
+        for action in &queued {
+            match action {
+                Action::Create(task) => hooks::fire(&self.hooks, Event::TaskCreated, task),
+                Action::Update(task) => self.fire_if_completed(before.get(&task.uid), task),
+                Action::Delete(_) | Action::Move(..) => {}
+            }
+        }
+
+        for action in local {
+            match action {
+                Action::Create(mut task) => {
+                    self.queue_create(&mut task)?;
+                    hooks::fire(&self.hooks, Event::TaskCreated, &task);
+                }
+                Action::Update(mut task) => self.queue_update(&mut task)?,
+                Action::Delete(task) => {
+                    Trash::add(&task, self.trash_days)?;
+                    self.queue_remove(&task)?;
+                }
+                Action::Move(task, new_calendar_href) => {
+                    self.queue_move(&task, &new_calendar_href)?;
+                }
+            }
+        }
+        if !remote {
+            return Ok(vec![]);
+        }
+        self.sync_if_online().await
     }
 
     pub async fn migrate_tasks(
         &self,
         tasks: Vec<Task>,
         target_calendar_href: &str,
-    ) -> Result<usize, String> {
+    ) -> Result<usize, ClientError> {
         let futures = tasks.into_iter().map(|task| {
             let client = self.clone();
             let target = target_calendar_href.to_string();
@@ -519,6 +1687,265 @@ impl RustyClient {
         Ok(count)
     }
 
+    /// Imports every task of a file (ICS, Taskwarrior JSON) into a calendar. Uploads go
+    /// through the journal like any other create, so this also works offline. Tasks
+    /// whose UID already exists in the calendar are skipped. Returns the number imported.
+    pub async fn import_file(
+        &self,
+        path: &Path,
+        calendar_href: &str,
+        format: FileFormat,
+    ) -> Result<usize, ClientError> {
+        let raw = fs::read_to_string(path)
+            .map_err(|e| ClientError::Storage(format!("{}: {}", path.display(), e)))?;
+
+        let existing = if is_local_calendar(calendar_href) {
+            LocalStorage::load_calendar(calendar_href)?
+        } else {
+            self.ensure_writable(calendar_href)?;
+            Cache::load(&self.cache_key(calendar_href))
+                .map(|(t, _)| t)
+                .unwrap_or_default()
+        };
+        let mut seen: HashSet<String> = existing.iter().map(|t| t.uid.clone()).collect();
+
+        let mut imported: Vec<Task> = format
+            .read(&raw, calendar_href)
+            .map_err(ClientError::Parse)?
+            .into_iter()
+            .filter(|t| seen.insert(t.uid.clone()))
+            .collect();
+        if imported.is_empty() {
+            return Ok(0);
+        }
+
+        if is_local_calendar(calendar_href) {
+            let mut all = existing;
+            all.extend(imported.iter().cloned());
+            LocalStorage::save_calendar(calendar_href, &all)?;
+            return Ok(imported.len());
+        }
+
+        for task in &mut imported {
+            let filename = format!("{}.ics", task.uid);
+            task.href = if calendar_href.ends_with('/') {
+                format!("{}{}", calendar_href, filename)
+            } else {
+                format!("{}/{}", calendar_href, filename)
+            };
+            task.account = self.account.clone();
+            Journal::push_for(self.account.as_deref(), Action::Create(task.clone()))?;
+        }
+        // Show them right away, even before (or without) the upload
+        let _ = Cache::modify(&self.cache_key(calendar_href), |cached| {
+            cached.extend(imported.iter().cloned());
+            true
+        });
+
+        let _ = self.sync_if_online().await;
+        Ok(imported.len())
+    }
+
+    /// Writes every task of a calendar to one file (backup, or to open it in another
+    /// app). Falls back to the cache when the server can't be reached.
+    /// Returns the number of tasks written.
+    pub async fn export_file(
+        &self,
+        calendar_href: &str,
+        path: &Path,
+        format: FileFormat,
+    ) -> Result<usize, ClientError> {
+        let tasks = if is_local_calendar(calendar_href) {
+            LocalStorage::load_calendar(calendar_href)?
+        } else {
+            match self.get_tasks(calendar_href).await {
+                Ok(tasks) => tasks,
+                Err(e) => Cache::load(&self.cache_key(calendar_href))
+                    .map(|(t, _)| t)
+                    .map_err(|_| e)?,
+            }
+        };
+        let mut calendars = Cache::load_calendars().unwrap_or_default();
+        LocalStorage::add_calendars(&mut calendars);
+        let title = calendars
+            .iter()
+            .find(|c| c.href == calendar_href)
+            .map_or(calendar_href, |c| c.name.as_str());
+        fs::write(path, format.write(&tasks, title, &calendars))
+            .map_err(|e| ClientError::Storage(format!("{}: {}", path.display(), e)))?;
+        Ok(tasks.len())
+    }
+
+    // --- ATTACHMENTS ---
+
+    /// Attaches a file to a task. Goes through a managed attachment (RFC 8607) when
+    /// the server supports it, so the file isn't copied into every sync of the task.
+    /// Otherwise (Local calendar, offline, unsupported) it is embedded inline as base64.
+    pub async fn add_attachment(
+        &self,
+        task: &mut Task,
+        path: &Path,
+    ) -> Result<Vec<String>, ClientError> {
+        let data = fs::read(path)
+            .map_err(|e| ClientError::Storage(format!("{}: {}", path.display(), e)))?;
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "attachment".to_string());
+        let mime = attach::guess_mime(path);
+
+        // Request bodies are strings, so only text files can be uploaded as-is
+        if !is_local_calendar(&task.calendar_href)
+            && !task.etag.is_empty()
+            && let Some(client) = self.online_client()
+            && let Ok(text) = std::str::from_utf8(&data)
+            && self.supports_managed_attachments(client, &task.href).await
+        {
+            self.ensure_writable(&task.calendar_href)?;
+            match self
+                .upload_managed(client, task, text.to_string(), &filename, mime)
+                .await
+            {
+                Ok(updated) => {
+                    *task = updated;
+                    return Ok(vec![]);
+                }
+                // Lost the connection meanwhile: the inline copy goes through the journal
+                Err(e) if e.is_connectivity() => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        task.attachments
+            .push(Attachment::inline(&data, &filename, mime));
+        self.update_task(task).await
+    }
+
+    async fn supports_managed_attachments(
+        &self,
+        client: &CalDavClient<HttpsClient>,
+        href: &str,
+    ) -> bool {
+        let Ok(uri) = client.webdav_client.relative_uri(strip_host(href)) else {
+            return false;
+        };
+        let Ok(req) = Request::builder()
+            .method("OPTIONS")
+            .uri(uri)
+            .body(String::new())
+        else {
+            return false;
+        };
+        match client.webdav_client.request_raw(req).await {
+            Ok((parts, _)) => parts
+                .headers
+                .get_all("DAV")
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .any(attach::supports_managed),
+            Err(_) => false,
+        }
+    }
+
+    // POST ?action=attachment-add, then fetch the task back: the server adds the
+    // ATTACH property (with its MANAGED-ID) and changes the ETag
+    async fn upload_managed(
+        &self,
+        client: &CalDavClient<HttpsClient>,
+        task: &Task,
+        body: String,
+        filename: &str,
+        mime: &str,
+    ) -> Result<Task, ClientError> {
+        let path = strip_host(&task.href);
+        let uri = client
+            .webdav_client
+            .relative_uri(format!("{}?action=attachment-add", path))
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("Content-Type", mime)
+            .header("Content-Disposition", attach::content_disposition(filename))
+            .header("If-Match", &task.etag)
+            .body(body)?;
+        let (parts, _) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if !parts.status.is_success() {
+            return Err(ClientError::from_status(
+                parts.status,
+                "POST attachment-add",
+            ));
+        }
+
+        self.fetch_resources(
+            &strip_host(&task.calendar_href),
+            &task.calendar_href,
+            vec![path],
+        )
+        .await?
+        .into_iter()
+        .find(|t| t.uid == task.uid)
+        .map(|mut t| {
+            // The server copy knows nothing about the local tree
+            t.depth = task.depth;
+            t
+        })
+        .ok_or_else(|| ClientError::NotFound(format!("{} after upload", task.href)))
+    }
+
+    /// Whether an attachment is stored on our server (managed, or a relative URI),
+    /// so downloading it needs our credentials. Other URLs can go to a browser.
+    pub fn is_server_attachment(&self, att: &Attachment) -> bool {
+        if att.inline {
+            return false;
+        }
+        let Ok(uri) = att.value.parse::<Uri>() else {
+            return false;
+        };
+        match (uri.authority(), &self.client) {
+            (None, _) => true,
+            (Some(auth), Some(client)) => client.base_url().authority() == Some(auth),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Content of an inline attachment, or of one stored on our server.
+    pub async fn fetch_attachment(&self, att: &Attachment) -> Result<Vec<u8>, ClientError> {
+        if att.inline {
+            return att
+                .decode_inline()
+                .ok_or_else(|| ClientError::Parse("Invalid base64 attachment".to_string()));
+        }
+        if !self.is_server_attachment(att) {
+            return Err(ClientError::Other(format!(
+                "{} is not stored on the server",
+                att.value
+            )));
+        }
+        let client = self.online_client().ok_or(ClientError::Offline)?;
+        let uri = client
+            .webdav_client
+            .relative_uri(strip_host(&att.value))
+            .map_err(|e| ClientError::Other(format!("Invalid URI: {}", e)))?;
+        let req = Request::builder()
+            .method("GET")
+            .uri(uri)
+            .body(String::new())?;
+        let (parts, body) = client
+            .webdav_client
+            .request_raw(req)
+            .await
+            .map_err(ClientError::from_debug)?;
+        if !parts.status.is_success() {
+            return Err(ClientError::from_status(parts.status, "GET attachment"));
+        }
+        Ok(body.to_vec())
+    }
+
     // --- JOURNAL SYNC ---
 
     // NEW HELPER: Fetch ETag explicitly if missing in PUT response
@@ -533,258 +1960,441 @@ impl RustyClient {
         None
     }
 
-    pub async fn sync_journal(&self) -> Result<Vec<String>, String> {
-        let client = self.client.as_ref().ok_or("Offline")?;
+    pub async fn sync_journal(&self) -> Result<Vec<String>, ClientError> {
+        if self.client.is_none() {
+            return Err(ClientError::Offline);
+        }
+        let queued: Vec<Task> = Journal::load()?
+            .queue_for(self.account.as_deref())
+            .map(|a| a.task().clone())
+            .collect();
+        let result = if self.sync_concurrency > 1 {
+            self.sync_journal_parallel(self.sync_concurrency).await
+        } else {
+            self.replay_journal().await
+        };
+        // Nothing sent, nothing learned about the connection
+        if !queued.is_empty() {
+            let unreachable = result.as_ref().is_err_and(|e| e.is_connectivity());
+            self.unreachable.store(unreachable, Ordering::Relaxed);
+        }
+        let warnings = result?;
+        self.fire_sync_hook(queued);
+        Ok(warnings)
+    }
+
+    // Runs the on_sync_success hook with the latest version of each task that left
+    // the queue (parked and interactive-conflict ones didn't reach the server)
+    fn fire_sync_hook(&self, queued: Vec<Task>) {
+        if self.hooks.on_sync_success.is_none() {
+            return;
+        }
+        let Ok(journal) = Journal::load() else {
+            return;
+        };
+        let pending = |uid: &str| {
+            journal.queue.iter().any(|a| a.task().uid == uid)
+                || journal.failed.iter().any(|f| f.action.task().uid == uid)
+                || journal.conflicts.iter().any(|c| c.local.uid == uid)
+        };
+        let mut synced: Vec<Task> = Vec::new();
+        for task in queued.into_iter().rev() {
+            if !pending(&task.uid) && !synced.iter().any(|t| t.uid == task.uid) {
+                synced.push(task);
+            }
+        }
+        if !synced.is_empty() {
+            synced.reverse();
+            hooks::fire(&self.hooks, Event::SyncSuccess, &synced);
+        }
+    }
+
+    async fn replay_journal(&self) -> Result<Vec<String>, ClientError> {
+        Journal::modify(compact)?;
+
+        let mut warnings = Vec::new();
+        loop {
+            // Only replay actions queued for this client's account
+            let next_action = {
+                let j = Journal::load()?;
+                match j.queue_for(self.account.as_deref()).next() {
+                    Some(a) => a.clone(),
+                    None => return Ok(warnings),
+                }
+            };
+
+            let outcome = self.replay_action(&next_action).await;
+            warnings.extend(outcome.warnings);
+            if let Err(e) = &outcome.result
+                && self.park_if_hopeless(&next_action, e, &mut warnings)
+            {
+                continue;
+            }
+            outcome.result?;
+            Journal::modify_journal(|journal| {
+                journal.attempts.remove(&next_action.task().uid);
+                // A sync started meanwhile may have merged it into a later action
+                let queue = &mut journal.queue;
+                let idx = queue
+                    .iter()
+                    .position(|a| *a == next_action)
+                    .unwrap_or(queue.len());
+                commit_replay(
+                    queue,
+                    idx,
+                    &next_action,
+                    outcome.resolved,
+                    outcome.new_etag,
+                    outcome.new_href,
+                )
+            })?;
+        }
+    }
+
+    /// Replays independent tasks concurrently. Actions of the same task UID still run
+    /// in queue order; a failure only stops the actions of that task.
+    pub async fn sync_journal_parallel(
+        &self,
+        concurrency: usize,
+    ) -> Result<Vec<String>, ClientError> {
+        if self.client.is_none() {
+            return Err(ClientError::Offline);
+        }
+        Journal::modify(compact)?;
         let mut warnings = Vec::new();
+        loop {
+            let uids: Vec<String> = {
+                let j = Journal::load()?;
+                let mut seen = HashSet::new();
+                j.queue_for(self.account.as_deref())
+                    .map(|a| a.task().uid.clone())
+                    .filter(|uid| seen.insert(uid.clone()))
+                    .collect()
+            };
+            if uids.is_empty() {
+                return Ok(warnings);
+            }
+
+            let results: Vec<Result<Vec<String>, ClientError>> = stream::iter(uids)
+                .map(|uid| async move { self.replay_uid(&uid).await })
+                .buffer_unordered(concurrency.max(1))
+                .collect()
+                .await;
+
+            let mut first_error = None;
+            for res in results {
+                match res {
+                    Ok(w) => warnings.extend(w),
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+            // Conflict copies get a new UID: another round picks them up
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+    }
+
+    // Records a failed replay. Changes the server rejects for good, or after too many
+    // server errors, are parked so the replay can go on: returns true then.
+    // Connectivity and credential problems stop the replay instead.
+    fn park_if_hopeless(
+        &self,
+        action: &Action,
+        e: &ClientError,
+        warnings: &mut Vec<String>,
+    ) -> bool {
+        let counted = matches!(e, ClientError::Server(_));
+        let attempts =
+            Journal::record_failure(self.account.as_deref(), action, &e.to_string(), counted)
+                .unwrap_or(0);
+        if !e.is_permanent() && attempts < MAX_REPLAY_ATTEMPTS {
+            return false;
+        }
+        if Journal::park(action, &e.to_string()).is_err() {
+            return false;
+        }
+        warnings.push(format!(
+            "Gave up on '{}' ({}), see the pending changes.",
+            action.task().summary,
+            e
+        ));
+        true
+    }
 
+    // Drains the queued actions of one task, in order
+    async fn replay_uid(&self, uid: &str) -> Result<Vec<String>, ClientError> {
+        let mut warnings = Vec::new();
         loop {
+            // Re-read every time: the previous commit may have updated etag/href
             let next_action = {
-                let j = Journal::load();
-                if j.queue.is_empty() {
-                    return Ok(warnings);
+                let j = Journal::load()?;
+                let next = j
+                    .queue
+                    .iter()
+                    .find(|a| a.task().uid == uid && j.account_of(a) == self.account.as_deref());
+                match next {
+                    Some(a) => a.clone(),
+                    None => return Ok(warnings),
                 }
-                j.queue[0].clone()
             };
 
-            let mut conflict_resolved_action = None;
-            let mut new_etag_to_propagate: Option<String> = None;
-            let mut new_href_to_propagate: Option<(String, String)> = None;
-            let mut path_for_refresh: Option<String> = None;
+            let outcome = self.replay_action(&next_action).await;
+            warnings.extend(outcome.warnings);
+            if let Err(e) = &outcome.result
+                && self.park_if_hopeless(&next_action, e, &mut warnings)
+            {
+                // The other changes of the task were parked with it
+                return Ok(warnings);
+            }
+            outcome.result?;
+            Journal::modify_journal(|journal| {
+                journal.attempts.remove(&next_action.task().uid);
+                // Other tasks may have been committed meanwhile: locate the action again.
+                // Gone (merged by another sync): nothing to remove
+                let queue = &mut journal.queue;
+                let idx = queue
+                    .iter()
+                    .position(|a| *a == next_action)
+                    .unwrap_or(queue.len());
+                commit_replay(
+                    queue,
+                    idx,
+                    &next_action,
+                    outcome.resolved,
+                    outcome.new_etag,
+                    outcome.new_href,
+                )
+            })?;
+        }
+    }
 
-            let result = match &next_action {
-                Action::Create(task) => {
-                    let filename = format!("{}.ics", task.uid);
-                    let full_href = if task.calendar_href.ends_with('/') {
-                        format!("{}{}", task.calendar_href, filename)
-                    } else {
-                        format!("{}/{}", task.calendar_href, filename)
-                    };
-                    let path = strip_host(&full_href);
-                    let ics_string = task.to_ics();
-                    match client
-                        .request(PutResource::new(&path).create(ics_string, "text/calendar"))
-                        .await
-                    {
-                        Ok(resp) => {
-                            if let Some(etag) = resp.etag {
-                                new_etag_to_propagate = Some(etag);
-                            } else {
-                                path_for_refresh = Some(path.clone());
-                            }
-                            Ok(())
+    // Sends one journal action to the server
+    async fn replay_action(&self, action: &Action) -> ReplayOutcome {
+        let mut outcome = ReplayOutcome::default();
+        let Some(client) = self.client.as_ref() else {
+            outcome.result = Err(ClientError::Offline);
+            return outcome;
+        };
+        let mut path_for_refresh: Option<String> = None;
+
+        outcome.result = match action {
+            Action::Create(task) => {
+                let path = strip_host(&created_href(task));
+                let ics_string = task.to_ics();
+                match client
+                    .request(PutResource::new(&path).create(ics_string, "text/calendar"))
+                    .await
+                {
+                    Ok(resp) => {
+                        if let Some(etag) = resp.etag {
+                            outcome.new_etag = Some(etag);
+                        } else {
+                            path_for_refresh = Some(path.clone());
                         }
-                        Err(e) => Err(format!("{:?}", e)),
+                        Ok(())
                     }
+                    Err(e) => Err(e.into()),
                 }
-                Action::Update(task) => {
-                    let path = strip_host(&task.href);
-                    let ics_string = task.to_ics();
-                    match client
-                        .request(PutResource::new(&path).update(
-                            ics_string,
-                            "text/calendar; charset=utf-8; component=VTODO",
-                            &task.etag,
-                        ))
-                        .await
-                    {
-                        Ok(resp) => {
-                            if let Some(etag) = resp.etag {
-                                new_etag_to_propagate = Some(etag);
-                            } else {
-                                path_for_refresh = Some(path.clone());
-                            }
-                            Ok(())
-                        }
-                        Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED))
-                        | Err(WebDavError::PreconditionFailed(_)) => {
-                            if let Some((resolution, msg)) =
-                                self.attempt_conflict_resolution(task).await
-                            {
-                                warnings.push(msg);
-                                conflict_resolved_action = Some(resolution);
-                                Ok(())
-                            } else {
-                                let msg = format!(
-                                    "Conflict (412) on task '{}'. Merge failed. Creating copy.",
-                                    task.summary
-                                );
-                                warnings.push(msg);
-
-                                let mut conflict_copy = task.clone();
-                                conflict_copy.uid = Uuid::new_v4().to_string();
-                                conflict_copy.summary = format!("{} (Conflict Copy)", task.summary);
-                                conflict_copy.href = String::new();
-                                conflict_copy.etag = String::new();
-                                conflict_resolved_action = Some(Action::Create(conflict_copy));
-                                Ok(())
-                            }
-                        }
-                        Err(WebDavError::BadStatusCode(StatusCode::NOT_FOUND)) => {
-                            conflict_resolved_action = Some(Action::Create(task.clone()));
-                            Ok(())
-                        }
-                        Err(e) => {
-                            let msg = format!("{:?}", e);
-                            if msg.contains("412") || msg.contains("PreconditionFailed") {
-                                let w = format!(
-                                    "Conflict (412-Fallback) on task '{}'. Creating copy.",
-                                    task.summary
-                                );
-                                warnings.push(w);
-
-                                let mut conflict_copy = task.clone();
-                                conflict_copy.uid = Uuid::new_v4().to_string();
-                                conflict_copy.summary = format!("{} (Conflict Copy)", task.summary);
-                                conflict_copy.href = String::new();
-                                conflict_copy.etag = String::new();
-                                conflict_resolved_action = Some(Action::Create(conflict_copy));
-                                Ok(())
-                            } else {
-                                Err(msg)
-                            }
+            }
+            Action::Update(task) => {
+                let path = strip_host(&task.href);
+                let ics_string = task.to_ics();
+                match client
+                    .request(PutResource::new(&path).update(
+                        ics_string,
+                        "text/calendar; charset=utf-8; component=VTODO",
+                        &task.etag,
+                    ))
+                    .await
+                {
+                    Ok(resp) => {
+                        if let Some(etag) = resp.etag {
+                            outcome.new_etag = Some(etag);
+                        } else {
+                            path_for_refresh = Some(path.clone());
                         }
+                        Ok(())
                     }
-                }
-                Action::Delete(task) => {
-                    let path = strip_host(&task.href);
-                    match client
-                        .request(Delete::new(&path).with_etag(&task.etag))
-                        .await
-                    {
-                        Ok(_) => Ok(()),
-                        Err(WebDavError::BadStatusCode(StatusCode::NOT_FOUND)) => Ok(()),
-                        Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED)) => {
-                            warnings.push(format!(
-                                "Conflict on delete task '{}'. Already modified/deleted.",
-                                task.summary
-                            ));
+                    Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED))
+                    | Err(WebDavError::PreconditionFailed(_)) => {
+                        self.handle_conflict(task, &mut outcome).await;
+                        Ok(())
+                    }
+                    Err(WebDavError::BadStatusCode(StatusCode::NOT_FOUND)) => {
+                        outcome.resolved = Some(Action::Create(task.clone()));
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let msg = format!("{:?}", e);
+                        if msg.contains("412") || msg.contains("PreconditionFailed") {
+                            self.handle_conflict(task, &mut outcome).await;
                             Ok(())
+                        } else {
+                            Err(e.into())
                         }
-                        Err(e) => Err(format!("{:?}", e)),
                     }
                 }
-                Action::Move(task, new_cal) => match self.execute_move(task, new_cal).await {
-                    Ok(_) => {
-                        let filename = format!("{}.ics", task.uid);
-                        let new_href = if new_cal.ends_with('/') {
-                            format!("{}{}", new_cal, filename)
-                        } else {
-                            format!("{}/{}", new_cal, filename)
-                        };
-                        new_href_to_propagate = Some((task.href.clone(), new_href.clone()));
-
-                        // Mark for refresh because MOVE does not return new ETag
-                        path_for_refresh = Some(strip_host(&new_href));
+            }
+            Action::Delete(task) => {
+                let path = strip_host(&task.href);
+                match client
+                    .request(Delete::new(&path).with_etag(&task.etag))
+                    .await
+                {
+                    Ok(_) => Ok(()),
+                    Err(WebDavError::BadStatusCode(StatusCode::NOT_FOUND)) => Ok(()),
+                    Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED)) => {
+                        outcome.warnings.push(format!(
+                            "Conflict on delete task '{}'. Already modified/deleted.",
+                            task.summary
+                        ));
                         Ok(())
                     }
-                    Err(e) => Err(e),
-                },
-            };
+                    Err(e) => Err(e.into()),
+                }
+            }
+            Action::Move(task, new_cal) => match self.execute_move(task, new_cal).await {
+                Ok(warnings) => {
+                    outcome.warnings.extend(warnings);
+                    let filename = format!("{}.ics", task.uid);
+                    let new_href = if new_cal.ends_with('/') {
+                        format!("{}{}", new_cal, filename)
+                    } else {
+                        format!("{}/{}", new_cal, filename)
+                    };
+                    outcome.new_href = Some((task.href.clone(), new_href.clone()));
 
-            match result {
-                Ok(_) => {
-                    // --- FIX: Fetch ETag if needed ---
-                    if new_etag_to_propagate.is_none()
-                        && let Some(path) = path_for_refresh
-                            && let Some(fetched) = self.fetch_etag(&path).await {
-                                new_etag_to_propagate = Some(fetched);
-                            }
-
-                    let commit_res = Journal::modify(|queue| {
-                        if !queue.is_empty() {
-                            queue.remove(0);
-                        }
+                    // Mark for refresh because MOVE does not return new ETag
+                    path_for_refresh = Some(strip_host(&new_href));
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            },
+        };
 
-                        if let Some(act) = conflict_resolved_action {
-                            queue.insert(0, act);
-                        }
+        // --- FIX: Fetch ETag if needed ---
+        if outcome.result.is_ok()
+            && outcome.new_etag.is_none()
+            && let Some(path) = path_for_refresh
+            && let Some(fetched) = self.fetch_etag(&path).await
+        {
+            outcome.new_etag = Some(fetched);
+        }
 
-                        if let Some(etag) = new_etag_to_propagate {
-                            let target_uid = match &next_action {
-                                Action::Create(t) | Action::Update(t) => t.uid.clone(),
-                                Action::Move(t, _) => t.uid.clone(),
-                                _ => String::new(),
-                            };
-                            if !target_uid.is_empty() {
-                                for item in queue.iter_mut() {
-                                    match item {
-                                        Action::Update(t) | Action::Delete(t) => {
-                                            if t.uid == target_uid {
-                                                t.etag = etag.clone();
-                                            }
-                                        }
-                                        Action::Move(t, _) => {
-                                            if t.uid == target_uid {
-                                                t.etag = etag.clone();
-                                            }
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        }
+        if outcome.result.is_ok()
+            && let Some(etag) = &outcome.new_etag
+        {
+            cache_uploaded_etag(action, etag, outcome.new_href.as_ref());
+        }
+        outcome
+    }
 
-                        if let Some((old_href, new_href)) = new_href_to_propagate {
-                            let target_uid = match &next_action {
-                                Action::Move(t, _) => t.uid.clone(),
-                                _ => String::new(),
-                            };
-                            for item in queue.iter_mut() {
-                                match item {
-                                    Action::Update(t) | Action::Delete(t) => {
-                                        if t.uid == target_uid || t.href == old_href {
-                                            t.href = new_href.clone();
-                                            if let Some(last_slash) = new_href.rfind('/') {
-                                                t.calendar_href =
-                                                    new_href[..=last_slash].to_string();
-                                            }
-                                        }
-                                    }
-                                    Action::Move(t, _) => {
-                                        if t.uid == target_uid {
-                                            t.href = new_href.clone();
-                                        }
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
-                    });
+    // A queued update hit a newer server version (412): merge the two if possible,
+    // else apply the conflict policy
+    async fn handle_conflict(&self, task: &Task, outcome: &mut ReplayOutcome) {
+        let (fields, server) = match self.attempt_conflict_resolution(task).await {
+            Ok((resolution, msg)) => {
+                outcome.warnings.push(msg);
+                outcome.resolved = Some(resolution);
+                return;
+            }
+            Err(e) => e,
+        };
+        let detail = if fields.is_empty() {
+            String::new()
+        } else {
+            format!(" (both sides changed {})", fields.join(", "))
+        };
 
-                    if let Err(e) = commit_res {
-                        return Err(e.to_string());
-                    }
-                }
-                Err(e) => {
-                    return Err(e);
+        // The other policies need the server version
+        let Some(server) = server.filter(|_| self.conflict_policy != ConflictPolicy::Copy) else {
+            outcome.warnings.push(format!(
+                "Conflict (412) on task '{}'. Merge failed{}. Creating copy.",
+                task.summary, detail
+            ));
+            outcome.resolved = Some(Action::Create(conflict_copy(task)));
+            return;
+        };
+        match self.conflict_policy {
+            ConflictPolicy::LocalWins => {
+                outcome.warnings.push(format!(
+                    "Conflict (412) on task '{}'{}. Overwriting the server version.",
+                    task.summary, detail
+                ));
+                // Same change, aimed at the version the server has now
+                let mut forced = task.clone();
+                forced.etag = server.etag;
+                outcome.resolved = Some(Action::Update(forced));
+            }
+            ConflictPolicy::RemoteWins => {
+                outcome.warnings.push(format!(
+                    "Conflict (412) on task '{}'{}. Kept the server version.",
+                    task.summary, detail
+                ));
+            }
+            _ => {
+                let conflict = Conflict {
+                    local: task.clone(),
+                    server,
+                    fields: fields.iter().map(|f| f.to_string()).collect(),
+                };
+                match Journal::add_conflict(conflict) {
+                    Ok(()) => outcome.warnings.push(format!(
+                        "Conflict (412) on task '{}'{}. Pick the version to keep.",
+                        task.summary, detail
+                    )),
+                    // Don't lose the change
+                    Err(_) => outcome.resolved = Some(Action::Create(conflict_copy(task))),
                 }
             }
         }
     }
 
-    async fn attempt_conflict_resolution(&self, local_task: &Task) -> Option<(Action, String)> {
-        let (cached_tasks, _) = Cache::load(&local_task.calendar_href).ok()?;
-        let base_task = cached_tasks.iter().find(|t| t.uid == local_task.uid)?;
-
-        let server_tasks = self
-            .fetch_calendar_tasks_internal(&local_task.calendar_href)
+    // Err lists the fields both sides changed (empty when the merge couldn't be tried),
+    // with the server version when it could be fetched
+    async fn attempt_conflict_resolution(
+        &self,
+        local_task: &Task,
+    ) -> Result<(Action, String), (Vec<&'static str>, Option<Task>)> {
+        let server_task = self
+            .fetch_resources(
+                &strip_host(&local_task.calendar_href),
+                &local_task.calendar_href,
+                vec![strip_host(&local_task.href)],
+            )
             .await
-            .ok()?;
-        let server_task = server_tasks.iter().find(|t| t.uid == local_task.uid)?;
+            .ok()
+            .and_then(|tasks| tasks.into_iter().find(|t| t.uid == local_task.uid))
+            .ok_or((Vec::new(), None))?;
+
+        // The cache already holds the optimistic local edit: the server version it
+        // replaced is kept aside as merge base.
+        let key = self.cache_key(&local_task.calendar_href);
+        let Some(base_task) = Cache::load_base(&key, &local_task.uid) else {
+            return Err((Vec::new(), Some(server_task)));
+        };
 
-        if let Some(merged) = three_way_merge(base_task, local_task, server_task) {
+        if let Some(merged) = three_way_merge(&base_task, local_task, &server_task) {
             let msg = format!(
                 "Conflict (412) on '{}' resolved via 3-way merge.",
                 local_task.summary
             );
-            return Some((Action::Update(merged), msg));
+            return Ok((Action::Update(merged), msg));
         }
 
-        None
+        let fields = conflicting_fields(&base_task, local_task, &server_task);
+        Err((fields, Some(server_task)))
     }
 
-    async fn execute_move(&self, task: &Task, new_calendar_href: &str) -> Result<(), String> {
-        let client = self.client.as_ref().ok_or("Offline")?;
+    async fn execute_move(
+        &self,
+        task: &Task,
+        new_calendar_href: &str,
+    ) -> Result<Vec<String>, ClientError> {
+        let client = self.client.as_ref().ok_or(ClientError::Offline)?;
         let destination = if new_calendar_href.ends_with('/') {
             format!("{}{}.ics", new_calendar_href, task.uid)
         } else {
@@ -794,32 +2404,84 @@ impl RustyClient {
         let source_uri = client
             .webdav_client
             .relative_uri(&source_path)
-            .map_err(|e| format!("Invalid source URI: {}", e))?;
+            .map_err(|e| ClientError::Other(format!("Invalid source URI: {}", e)))?;
         let dest_path = strip_host(&destination);
         let dest_uri = client
             .webdav_client
             .relative_uri(&dest_path)
-            .map_err(|e| format!("Invalid dest URI: {}", e))?;
+            .map_err(|e| ClientError::Other(format!("Invalid dest URI: {}", e)))?;
         let req = Request::builder()
             .method("MOVE")
             .uri(source_uri)
             .header("Destination", dest_uri.to_string())
             .header("Overwrite", "F")
-            .body(String::new())
-            .map_err(|e| e.to_string())?;
+            .body(String::new())?;
         let (parts, _) = client
             .webdav_client
             .request_raw(req)
             .await
-            .map_err(|e| format!("{:?}", e))?;
-        if parts.status.is_success() {
-            Ok(())
-        } else {
-            Err(format!("MOVE failed: {}", parts.status))
+            .map_err(ClientError::from_debug)?;
+        match parts.status {
+            s if s.is_success() => Ok(vec![]),
+            // Server without MOVE support: same result with two requests
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => {
+                self.copy_then_delete(client, task, &source_path, &dest_path)
+                    .await
+            }
+            s => Err(ClientError::from_status(s, "MOVE")),
+        }
+    }
+
+    // MOVE fallback: PUT at the destination, then DELETE the source if it's unchanged
+    async fn copy_then_delete(
+        &self,
+        client: &CalDavClient<HttpsClient>,
+        task: &Task,
+        source_path: &str,
+        dest_path: &str,
+    ) -> Result<Vec<String>, ClientError> {
+        match client
+            .request(PutResource::new(dest_path).create(task.to_ics(), "text/calendar"))
+            .await
+        {
+            Ok(_) => {}
+            // Already copied by an earlier attempt whose DELETE failed
+            Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED))
+            | Err(WebDavError::PreconditionFailed(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        match client
+            .request(Delete::new(source_path).with_etag(&task.etag))
+            .await
+        {
+            Ok(_) | Err(WebDavError::BadStatusCode(StatusCode::NOT_FOUND)) => Ok(vec![]),
+            Err(WebDavError::BadStatusCode(StatusCode::PRECONDITION_FAILED))
+            | Err(WebDavError::PreconditionFailed(_)) => Ok(vec![format!(
+                "Task '{}' changed on the server while moving it: the original was kept.",
+                task.summary
+            )]),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+// Set-like fields merge element-wise: additions and removals from both sides are kept
+fn merge_set(base: &[String], local: &[String], server: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = server
+        .iter()
+        .filter(|s| local.contains(s) || !base.contains(s))
+        .cloned()
+        .collect();
+    for s in local {
+        if !base.contains(s) && !out.contains(s) {
+            out.push(s.clone());
         }
     }
+    out
 }
 
+/// Field-level 3-way merge. Returns None only if a field changed differently on both sides.
 fn three_way_merge(base: &Task, local: &Task, server: &Task) -> Option<Task> {
     let mut merged = server.clone();
 
@@ -838,16 +2500,39 @@ fn three_way_merge(base: &Task, local: &Task, server: &Task) -> Option<Task> {
     merge_field!(summary);
     merge_field!(description);
     merge_field!(status);
+    merge_field!(custom_status);
     merge_field!(priority);
+    merge_field!(completed_at);
     merge_field!(due);
     merge_field!(dtstart);
+    merge_field!(due_tz);
+    merge_field!(dtstart_tz);
+    merge_field!(is_all_day);
     merge_field!(estimated_duration);
+    merge_field!(flagged);
+    merge_field!(time_spent);
+    merge_field!(time_sessions);
     merge_field!(rrule);
-    merge_field!(categories);
-    merge_field!(dependencies);
+    merge_field!(repeat_after_completion);
+    merge_field!(exdates);
+    merge_field!(overrides);
     merge_field!(parent_uid);
     merge_field!(unmapped_properties);
     merge_field!(raw_components);
+    merge_field!(alarms);
+    merge_field!(attachments);
+
+    // Both sides bumped SEQUENCE from the base: the merge is a newer revision still
+    merged.sequence = local.sequence.max(server.sequence + 1);
+
+    merged.categories = merge_set(&base.categories, &local.categories, &server.categories);
+    merged.categories.sort();
+    merged.contexts = merge_set(&base.contexts, &local.contexts, &server.contexts);
+    merged.dependencies = merge_set(
+        &base.dependencies,
+        &local.dependencies,
+        &server.dependencies,
+    );
 
     Some(merged)
 }
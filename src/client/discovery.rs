@@ -0,0 +1,151 @@
+// File: ./src/client/discovery.rs
+// RFC 6764 bootstrapping: turn a bare domain ("example.com") into a CalDAV context URL
+// using SRV/TXT records, then /.well-known/caldav.
+use crate::client::core::{ConnectionOptions, build_http_client};
+use hickory_resolver::TokioAsyncResolver;
+use http::{Request, Uri};
+use libdav::dav::WebDavClient;
+
+const WELL_KNOWN: &str = "/.well-known/caldav";
+
+/// True if the user only gave us a domain: no scheme and no path. A full URL, even
+/// of the server's root, is taken as is.
+pub fn needs_bootstrap(url: &str) -> bool {
+    !url.is_empty() && !url.contains("://") && !url.trim_end_matches('/').contains('/')
+}
+
+/// The URL to connect to before any discovery: https unless told otherwise.
+pub fn with_scheme(url: &str) -> String {
+    if url.is_empty() || url.contains("://") {
+        url.to_string()
+    } else if needs_bootstrap(url) {
+        format!("https://{}/", url.trim_end_matches('/'))
+    } else {
+        format!("https://{}", url)
+    }
+}
+
+/// Resolves the CalDAV context URL for a bare domain.
+pub async fn bootstrap_url(url: &str, opts: &ConnectionOptions) -> Result<String, String> {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    // SRV records and .well-known are per host: drop any path
+    let host = rest.split('/').next().unwrap_or_default();
+    if host.is_empty() {
+        return Err("No host to discover".to_string());
+    }
+
+    if let Some((scheme, target, port, path)) = srv_lookup(host).await {
+        let path = path.unwrap_or_else(|| WELL_KNOWN.to_string());
+        let url = format!("{}://{}:{}{}", scheme, target, port, path);
+        if url.ends_with(WELL_KNOWN) {
+            return Ok(follow_well_known(&url, opts).await.unwrap_or(url));
+        }
+        return Ok(url);
+    }
+
+    let well_known = format!("{}://{}{}", scheme, host, WELL_KNOWN);
+    Ok(follow_well_known(&well_known, opts)
+        .await
+        // No redirect: the server probably lives at the root
+        .unwrap_or_else(|| format!("{}://{}/", scheme, host)))
+}
+
+// (scheme, target, port, context path from TXT)
+async fn srv_lookup(domain: &str) -> Option<(&'static str, String, u16, Option<String>)> {
+    // Strip an explicit port, SRV is keyed by domain only
+    let domain = domain.split(':').next().unwrap_or(domain);
+    let resolver = TokioAsyncResolver::tokio_from_system_conf().ok()?;
+
+    // Prefer TLS, as RFC 6764 recommends
+    for (scheme, service) in [("https", "_caldavs._tcp"), ("http", "_caldav._tcp")] {
+        let name = format!("{}.{}.", service, domain);
+        let Ok(lookup) = resolver.srv_lookup(name.as_str()).await else {
+            continue;
+        };
+        let mut records: Vec<_> = lookup.iter().collect();
+        // Lowest priority first, then highest weight
+        records.sort_by(|a, b| {
+            a.priority()
+                .cmp(&b.priority())
+                .then(b.weight().cmp(&a.weight()))
+        });
+        // A target of "." means the service is explicitly unavailable
+        let Some(rec) = records.into_iter().find(|r| !r.target().is_root()) else {
+            continue;
+        };
+        let target = rec.target().to_utf8().trim_end_matches('.').to_string();
+
+        let path = resolver
+            .txt_lookup(name.as_str())
+            .await
+            .ok()
+            .and_then(|txt| {
+                txt.iter().find_map(|r| {
+                    r.txt_data().iter().find_map(|d| {
+                        String::from_utf8_lossy(d)
+                            .strip_prefix("path=")
+                            .map(|p| p.to_string())
+                    })
+                })
+            });
+
+        return Some((scheme, target, rec.port(), path));
+    }
+    None
+}
+
+// The well-known URL normally redirects to the real context path
+async fn follow_well_known(url: &str, opts: &ConnectionOptions) -> Option<String> {
+    let uri: Uri = url.parse().ok()?;
+    let req = Request::builder()
+        .method("PROPFIND")
+        .uri(uri.clone())
+        .header("Depth", "0")
+        .body(String::new())
+        .ok()?;
+
+    let http = build_http_client(opts).ok()?;
+    let (parts, _) = WebDavClient::new(uri.clone(), http)
+        .request_raw(req)
+        .await
+        .ok()?;
+    if !parts.status.is_redirection() {
+        return None;
+    }
+
+    let location = parts.headers.get("Location")?.to_str().ok()?;
+    if location.contains("://") {
+        Some(location.to_string())
+    } else {
+        let scheme = uri.scheme_str().unwrap_or("https");
+        let authority = uri.authority()?;
+        Some(format!("{}://{}{}", scheme, authority, location))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_bootstrap() {
+        assert!(needs_bootstrap("example.com"));
+        assert!(needs_bootstrap("example.com:8443/"));
+        assert!(!needs_bootstrap("example.com/dav/"));
+        assert!(!needs_bootstrap("https://example.com"));
+        assert!(!needs_bootstrap("https://example.com/"));
+        assert!(!needs_bootstrap("https://example.com/dav/"));
+        assert!(!needs_bootstrap(""));
+    }
+
+    #[test]
+    fn test_with_scheme() {
+        assert_eq!(with_scheme("example.com"), "https://example.com/");
+        assert_eq!(with_scheme("example.com/dav/"), "https://example.com/dav/");
+        assert_eq!(
+            with_scheme("http://localhost:5232/"),
+            "http://localhost:5232/"
+        );
+        assert_eq!(with_scheme(""), "");
+    }
+}
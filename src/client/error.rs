@@ -0,0 +1,169 @@
+// File: ./src/client/error.rs
+// Errors returned by the client, so callers can react per kind of failure
+// (ask for credentials again, stay on the cache, offer a retry...).
+use http::StatusCode;
+use libdav::dav::WebDavError;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ClientError {
+    // 401, or no token could be obtained
+    #[error("Authentication failed: {0}")]
+    Unauthorized(String),
+    // 403, or a calendar the server reported as read-only
+    #[error("Permission denied: {0}")]
+    Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    // 409 / 412: the resource changed on the server
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("TLS error: {0}")]
+    Tls(String),
+    // No server configured, or offline mode is on
+    #[error("Offline")]
+    Offline,
+    // Server unreachable (DNS, refused connection, timeout...)
+    #[error("Network error: {0}")]
+    Network(String),
+    // Any other unexpected status code
+    #[error("Server error: {0}")]
+    Server(String),
+    // Malformed XML / ICS from the server or a file
+    #[error("Parse error: {0}")]
+    Parse(String),
+    // Local files: cache, journal, Local calendar, import/export
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("{0}")]
+    Config(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl ClientError {
+    /// Maps a non-success status of `what` (e.g. "PROPFIND") to an error.
+    pub fn from_status(status: StatusCode, what: &str) -> Self {
+        let msg = format!("{} returned {}", what, status);
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(msg),
+            StatusCode::FORBIDDEN => Self::Forbidden(msg),
+            StatusCode::NOT_FOUND => Self::NotFound(msg),
+            StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => Self::Conflict(msg),
+            _ => Self::Server(msg),
+        }
+    }
+
+    /// For libdav errors we only know through `Debug` (transport errors, request
+    /// specific error types...): recovers the status code or a TLS failure from it.
+    pub fn from_debug(e: impl std::fmt::Debug) -> Self {
+        let msg = format!("{:?}", e);
+        let status = msg
+            .split_once("BadStatusCode(")
+            .and_then(|(_, rest)| rest.get(..3))
+            .and_then(|code| code.parse::<u16>().ok())
+            .and_then(|code| StatusCode::from_u16(code).ok());
+        if let Some(status) = status {
+            Self::from_status(status, "Request")
+        } else if msg.contains("Certificate") || msg.contains("rustls") {
+            // rustls errors only show up in the Debug chain of hyper's error
+            Self::Tls(msg)
+        } else {
+            Self::Network(msg)
+        }
+    }
+
+    /// Prefixes the message with `ctx` (e.g. an account label), keeping the kind.
+    pub fn context(self, ctx: &str) -> Self {
+        let wrap = |m: String| format!("{}: {}", ctx, m);
+        match self {
+            Self::Unauthorized(m) => Self::Unauthorized(wrap(m)),
+            Self::Forbidden(m) => Self::Forbidden(wrap(m)),
+            Self::NotFound(m) => Self::NotFound(wrap(m)),
+            Self::Conflict(m) => Self::Conflict(wrap(m)),
+            Self::Tls(m) => Self::Tls(wrap(m)),
+            Self::Offline => Self::Offline,
+            Self::Network(m) => Self::Network(wrap(m)),
+            Self::Server(m) => Self::Server(wrap(m)),
+            Self::Parse(m) => Self::Parse(wrap(m)),
+            Self::Storage(m) => Self::Storage(wrap(m)),
+            Self::Config(m) => Self::Config(wrap(m)),
+            Self::Other(m) => Self::Other(wrap(m)),
+        }
+    }
+
+    /// Failures that will go away once the server can be reached again.
+    /// Anything else needs the user's attention (credentials, certificates...).
+    pub fn is_connectivity(&self) -> bool {
+        matches!(self, Self::Offline | Self::Network(_))
+    }
+
+    /// Failures of one request that retrying won't fix (403 on a read-only
+    /// calendar, a resource the server refuses to parse...).
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::Forbidden(_) | Self::NotFound(_) | Self::Conflict(_) | Self::Parse(_)
+        )
+    }
+}
+
+impl From<WebDavError> for ClientError {
+    fn from(e: WebDavError) -> Self {
+        match e {
+            WebDavError::BadStatusCode(status) => Self::from_status(status, "Request"),
+            WebDavError::PreconditionFailed(_) => Self::Conflict("precondition failed".to_string()),
+            e => Self::from_debug(e),
+        }
+    }
+}
+
+impl From<http::Error> for ClientError {
+    fn from(e: http::Error) -> Self {
+        Self::Other(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ClientError {
+    fn from(e: anyhow::Error) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
+impl From<String> for ClientError {
+    fn from(e: String) -> Self {
+        Self::Other(e)
+    }
+}
+
+impl From<&str> for ClientError {
+    fn from(e: &str) -> Self {
+        Self::Other(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status() {
+        assert!(matches!(
+            ClientError::from_status(StatusCode::UNAUTHORIZED, "PROPFIND"),
+            ClientError::Unauthorized(_)
+        ));
+        assert!(matches!(
+            ClientError::from_status(StatusCode::PRECONDITION_FAILED, "PUT"),
+            ClientError::Conflict(_)
+        ));
+        assert_eq!(
+            ClientError::from_status(StatusCode::BAD_GATEWAY, "MOVE").to_string(),
+            "Server error: MOVE returned 502 Bad Gateway"
+        );
+        assert!(matches!(
+            ClientError::from_debug("Request(BadStatusCode(401))"),
+            ClientError::Unauthorized(_)
+        ));
+        assert!(ClientError::from_debug("ConnectError(\"tcp connect error\")").is_connectivity());
+    }
+}
@@ -0,0 +1,390 @@
+// File: ./src/client/manager.rs
+// One RustyClient per configured account, routed by the account label of tasks
+use crate::cache::Cache;
+use crate::client::core::{FetchProgress, RustyClient};
+use crate::client::error::ClientError;
+use crate::config::Config;
+use crate::journal::{Action, Journal, Resolution};
+use crate::model::{CalendarListEntry, Task};
+use crate::storage::Trash;
+use futures::future::join_all;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct AccountManager {
+    // clients[0] is always the primary account
+    pub clients: Vec<RustyClient>,
+    // Calendar href -> index in `clients`, as listed (primary account first). Only
+    // for callers that know nothing but the href: tasks carry their account.
+    routes: Vec<(String, usize)>,
+    // Secondary accounts that couldn't be set up (bad config, keyring...), one
+    // message each. They're left out rather than taking the others down with them.
+    pub skipped: Vec<String>,
+}
+
+impl AccountManager {
+    pub async fn from_config(config: &Config) -> Result<Self, ClientError> {
+        let mut clients = vec![RustyClient::from_config(config).await?];
+        let mut skipped = Vec::new();
+        for acc in &config.accounts {
+            match RustyClient::from_config(&config.for_account(acc)).await {
+                Ok(client) => clients.push(client.with_account(&acc.label)),
+                Err(e) => skipped.push(e.context(&acc.label).to_string()),
+            }
+        }
+        Ok(Self {
+            clients,
+            routes: Vec::new(),
+            skipped,
+        })
+    }
+
+    /// Connects every account, flushes the queued changes and lists the calendars,
+    /// falling back to the cache when the servers can't be reached. Also returns the
+    /// tasks of the default (or discovered) calendar, and a warning when offline.
+    pub async fn connect_with_fallback(
+        config: Config,
+    ) -> Result<
+        (
+            Self,
+            Vec<CalendarListEntry>,
+            Vec<Task>,
+            Option<String>,
+            Option<String>,
+        ),
+        ClientError,
+    > {
+        let mut manager = Self::from_config(&config).await?;
+
+        if !manager.is_offline() {
+            manager.bootstrap().await;
+            let _ = manager.sync_journal().await;
+        }
+
+        let (calendars, warning) = match manager.get_calendars().await {
+            Ok(c) => {
+                let _ = Cache::save_calendars(&c);
+                (c, None)
+            }
+            // Bad credentials or certificates won't fix themselves: don't hide them
+            // behind the cache like a plain network failure
+            Err(e @ (ClientError::Unauthorized(_) | ClientError::Tls(_))) => return Err(e),
+            Err(_) => (
+                Cache::load_calendars().unwrap_or_default(),
+                Some("Offline Mode".to_string()),
+            ),
+        };
+
+        let mut active_href = None;
+        if let Some(def_cal) = &config.default_calendar
+            && let Some(found) = calendars
+                .iter()
+                .find(|c| c.name == *def_cal || c.href == *def_cal)
+        {
+            active_href = Some(found.href.clone());
+        }
+
+        if active_href.is_none()
+            && warning.is_none()
+            && let Ok(href) = manager.clients[0].discover_calendar().await
+        {
+            active_href = Some(href);
+        }
+
+        let tasks = match &active_href {
+            Some(h) if warning.is_none() => manager
+                .client_of_calendar(h)
+                .get_tasks(h)
+                .await
+                .unwrap_or_default(),
+            Some(h) => {
+                let account = manager.client_of_calendar(h).account.as_deref();
+                Cache::load(&Cache::key(account, h))
+                    .map(|res| res.0)
+                    .unwrap_or_default()
+            }
+            None => vec![],
+        };
+
+        // Skipped accounts go in front of whatever else went wrong
+        let warning = manager
+            .skipped
+            .iter()
+            .cloned()
+            .chain(warning)
+            .reduce(|a, b| format!("{}; {}", a, b));
+
+        Ok((manager, calendars, tasks, active_href, warning))
+    }
+
+    /// Accounts configured with a bare domain look up their server (see
+    /// `RustyClient::discover_calendar`) before anything else is asked from them.
+    pub async fn bootstrap(&mut self) {
+        for client in self.clients.iter_mut().filter(|c| c.needs_bootstrap()) {
+            let _ = client.discover_calendar().await;
+        }
+    }
+
+    pub fn with_progress(mut self, progress: FetchProgress) -> Self {
+        self.clients = self
+            .clients
+            .into_iter()
+            .map(|c| c.with_progress(progress.clone()))
+            .collect();
+        self
+    }
+
+    /// Switches every account in or out of offline mode (see `RustyClient::set_offline`).
+    pub fn set_offline(&self, offline: bool) {
+        for client in &self.clients {
+            client.set_offline(offline);
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.primary().is_offline()
+    }
+
+    /// Some account couldn't send its queued changes on the last try.
+    pub fn is_unreachable(&self) -> bool {
+        self.clients.iter().any(|c| c.is_unreachable())
+    }
+
+    pub fn primary(&self) -> &RustyClient {
+        &self.clients[0]
+    }
+
+    /// Client of an account (`None` and unknown labels = primary account).
+    pub fn client_for(&self, account: Option<&str>) -> &RustyClient {
+        self.clients
+            .iter()
+            .find(|c| c.account.as_deref() == account)
+            .unwrap_or(&self.clients[0])
+    }
+
+    /// Client owning a task (see `Task::account`). Local tasks go to the primary account.
+    pub fn client_of(&self, task: &Task) -> &RustyClient {
+        self.client_for(task.account.as_deref())
+    }
+
+    /// Client listing a calendar, for callers that only have its href. When two
+    /// accounts use the same path, the primary account (then the first one) wins.
+    pub fn client_of_calendar(&self, calendar_href: &str) -> &RustyClient {
+        self.routes
+            .iter()
+            .find(|(href, _)| href == calendar_href)
+            .and_then(|(_, i)| self.clients.get(*i))
+            .unwrap_or(&self.clients[0])
+    }
+
+    // Client of the calendar a task moves to: its own account if it has that calendar
+    fn destination(&self, task: &Task, calendar_href: &str) -> &RustyClient {
+        let src = self.client_of(task);
+        let own = self
+            .routes
+            .iter()
+            .any(|(href, i)| href == calendar_href && src.account == self.clients[*i].account);
+        if own {
+            src
+        } else {
+            self.client_of_calendar(calendar_href)
+        }
+    }
+
+    /// Aggregates calendars across accounts. Only fails if every account failed,
+    /// with the error of the primary account.
+    /// Calendars of secondary accounts are prefixed with the account label.
+    pub async fn get_calendars(&mut self) -> Result<Vec<CalendarListEntry>, ClientError> {
+        let mut all = Vec::new();
+        let mut errors = Vec::new();
+        self.routes.clear();
+
+        for (i, client) in self.clients.iter().enumerate() {
+            match client.get_calendars().await {
+                Ok(cals) => {
+                    for mut cal in cals {
+                        if let Some(label) = &client.account {
+                            cal.name = format!("{}: {}", label, cal.name);
+                        }
+                        self.routes.push((cal.href.clone(), i));
+                        all.push(cal);
+                    }
+                }
+                Err(e) => errors.push(match &client.account {
+                    Some(label) => e.context(label),
+                    None => e,
+                }),
+            }
+        }
+
+        if all.is_empty() && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+        Ok(all)
+    }
+
+    pub async fn get_all_tasks(
+        &self,
+        calendars: &[CalendarListEntry],
+    ) -> Result<Vec<(String, Vec<Task>)>, ClientError> {
+        let mut results = Vec::new();
+        for client in &self.clients {
+            let owned: Vec<CalendarListEntry> = calendars
+                .iter()
+                .filter(|c| c.account == client.account)
+                .cloned()
+                .collect();
+            if owned.is_empty() {
+                continue;
+            }
+            if let Ok(res) = client.get_all_tasks(&owned).await {
+                results.extend(res);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Registers the push endpoint with every account. Push is best-effort: an
+    /// account that fails is reported but doesn't prevent the others from subscribing.
+    pub async fn subscribe_push(
+        &self,
+        calendars: &[CalendarListEntry],
+        push_resource: &str,
+    ) -> (HashMap<String, String>, Vec<String>) {
+        let mut topics = HashMap::new();
+        let mut errors = Vec::new();
+        for client in &self.clients {
+            let owned: Vec<CalendarListEntry> = calendars
+                .iter()
+                .filter(|c| c.account == client.account)
+                .cloned()
+                .collect();
+            if owned.is_empty() || client.client.is_none() || client.is_offline() {
+                continue;
+            }
+            match client.subscribe_push(&owned, push_resource).await {
+                Ok((t, warnings)) => {
+                    topics.extend(t);
+                    errors.extend(warnings);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        (topics, errors)
+    }
+
+    /// Replays the queue of every account concurrently. An account that fails
+    /// doesn't hold back the others: its error is reported as a warning, and the
+    /// sync only fails if every account with queued changes failed.
+    pub async fn sync_journal(&self) -> Result<Vec<String>, ClientError> {
+        let journal = Journal::load()?;
+        let busy: Vec<&RustyClient> = self
+            .clients
+            .iter()
+            .filter(|c| c.client.is_some())
+            .filter(|c| journal.queue_for(c.account.as_deref()).next().is_some())
+            .collect();
+        let results = join_all(busy.iter().map(|c| c.sync_journal())).await;
+
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+        for (client, res) in busy.iter().zip(results) {
+            match res {
+                Ok(w) => warnings.extend(w),
+                Err(e) => errors.push(match &client.account {
+                    Some(label) => e.context(label),
+                    None => e,
+                }),
+            }
+        }
+        if !errors.is_empty() && errors.len() == busy.len() {
+            return Err(errors.swap_remove(0));
+        }
+        warnings.extend(errors.iter().map(|e| format!("Sync failed: {}", e)));
+        Ok(warnings)
+    }
+
+    /// Moves a task, falling back to create + delete when the destination
+    /// calendar lives on another account (WebDAV MOVE can't cross servers).
+    pub async fn move_task(
+        &self,
+        task: &Task,
+        new_calendar_href: &str,
+    ) -> Result<(Task, Vec<String>), ClientError> {
+        let src = self.client_of(task);
+        let dst = self.destination(task, new_calendar_href);
+        if src.account == dst.account {
+            return src.move_task(task, new_calendar_href).await;
+        }
+
+        let mut new_task = task.clone();
+        new_task.calendar_href = new_calendar_href.to_string();
+        new_task.account = dst.account.clone();
+        new_task.href = String::new();
+        new_task.etag = String::new();
+        let mut logs = dst.create_task(&mut new_task).await?;
+        logs.extend(src.remove_task(task).await?);
+        Ok((new_task, logs))
+    }
+
+    /// Undo (see `RustyClient::undo_last`), through the account of the last change.
+    pub async fn undo_last(&self) -> Result<Option<Action>, ClientError> {
+        let Some(last) = Journal::load()?.next_undo().cloned() else {
+            return Ok(None);
+        };
+        self.client_of(last.task()).undo_last().await
+    }
+
+    /// Restores a task from the trash, through the account of its calendar.
+    pub async fn undelete(&self, uid: &str) -> Result<(Task, Vec<String>), ClientError> {
+        let account = Trash::load()?
+            .into_iter()
+            .find(|e| e.task.uid == uid)
+            .and_then(|e| e.task.account);
+        self.client_for(account.as_deref()).undelete(uid).await
+    }
+
+    /// Settles a conflict (see `RustyClient::resolve_conflict`) on the task's account.
+    pub async fn resolve_conflict(
+        &self,
+        uid: &str,
+        keep: Resolution,
+    ) -> Result<(Vec<Task>, Vec<String>), ClientError> {
+        let account = Journal::load()?
+            .conflicts
+            .iter()
+            .find(|c| c.local.uid == uid)
+            .and_then(|c| c.local.account.clone());
+        self.client_for(account.as_deref())
+            .resolve_conflict(uid, keep)
+            .await
+    }
+
+    /// Bulk changes (see `RustyClient::apply_batch`), one batch per account. Moves to
+    /// another account are a create and a delete on two servers, they go one by one.
+    pub async fn apply_batch(&self, actions: Vec<Action>) -> Result<Vec<String>, ClientError> {
+        let mut logs = Vec::new();
+        let mut batches: Vec<Vec<Action>> = vec![Vec::new(); self.clients.len()];
+        for action in actions {
+            if let Action::Move(task, dest) = &action
+                && self.client_of(task).account != self.destination(task, dest).account
+            {
+                logs.extend(self.move_task(task, dest).await?.1);
+                continue;
+            }
+            let account = action.task().account.as_deref();
+            let idx = self
+                .clients
+                .iter()
+                .position(|c| c.account.as_deref() == account)
+                .unwrap_or(0);
+            batches[idx].push(action);
+        }
+        for (client, batch) in self.clients.iter().zip(batches) {
+            if !batch.is_empty() {
+                logs.extend(client.apply_batch(batch).await?);
+            }
+        }
+        Ok(logs)
+    }
+}
@@ -1,6 +1,22 @@
 // File: ./src/client/mod.rs
 // re-exports the cleaned up client modules
+pub mod acl;
+pub mod attach;
+pub mod auth;
 pub mod cert;
 pub mod core;
+pub mod discovery;
+pub mod error;
+pub mod manager;
+pub mod oauth;
+pub mod proxy;
+pub mod push;
+pub mod query;
+pub mod quota;
+pub mod retry;
+pub mod sync;
+pub mod throttle;
 
 pub use self::core::{GET_CTAG, RustyClient};
+pub use self::error::ClientError;
+pub use self::manager::AccountManager;
@@ -0,0 +1,166 @@
+// File: ./src/client/oauth.rs
+// OAuth2 refresh-token flow. The rotated token is persisted in the data dir.
+use crate::client::core::HttpClient;
+use crate::config::OAuth2Config;
+use crate::paths::AppPaths;
+use crate::storage::LocalStorage;
+use chrono::Utc;
+use http::{Request, Uri};
+use libdav::dav::WebDavClient;
+use serde::{Deserialize, Serialize};
+
+// Refresh a bit early so a request never starts with an about-to-expire token
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OAuthToken {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    // Unix timestamp (seconds)
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+impl OAuthToken {
+    pub fn load() -> Option<Self> {
+        let path = AppPaths::get_oauth_token_path()?;
+        if !path.exists() {
+            return None;
+        }
+        LocalStorage::with_lock(&path, || {
+            let json = LocalStorage::read_data(&path)?;
+            Ok(serde_json::from_str::<Self>(&json)?)
+        })
+        .ok()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(path) = AppPaths::get_oauth_token_path() {
+            LocalStorage::with_lock(&path, || {
+                let json = serde_json::to_string_pretty(self)?;
+                // Holds the refresh token: encrypted like the other data files
+                LocalStorage::write_data(&path, json)?;
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(ts) => Utc::now().timestamp() + EXPIRY_MARGIN_SECS >= ts,
+            None => false,
+        }
+    }
+}
+
+/// Returns a valid access token, refreshing (and persisting) it if needed.
+pub async fn get_access_token(cfg: &OAuth2Config, http: HttpClient) -> Result<OAuthToken, String> {
+    // Another process may have refreshed it already
+    if let Some(tok) = OAuthToken::load()
+        && !tok.access_token.is_empty()
+        && !tok.is_expired()
+    {
+        return Ok(tok);
+    }
+    renew(cfg, http).await
+}
+
+/// Trades the refresh token for a new access token, whatever the expiry of the
+/// current one (for when the server rejects a token that should still be valid).
+pub async fn renew(cfg: &OAuth2Config, http: HttpClient) -> Result<OAuthToken, String> {
+    let refresh_token = OAuthToken::load()
+        .and_then(|t| t.refresh_token)
+        .or_else(|| cfg.refresh_token.clone())
+        .ok_or("OAuth2: no refresh token available")?;
+
+    let fresh = refresh(cfg, &refresh_token, http).await?;
+    fresh.save().map_err(|e| e.to_string())?;
+    Ok(fresh)
+}
+
+async fn refresh(
+    cfg: &OAuth2Config,
+    refresh_token: &str,
+    http: HttpClient,
+) -> Result<OAuthToken, String> {
+    let uri: Uri = cfg
+        .token_url
+        .parse()
+        .map_err(|e: http::uri::InvalidUri| e.to_string())?;
+
+    let mut params = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", cfg.client_id.as_str()),
+    ];
+    if let Some(secret) = &cfg.client_secret {
+        params.push(("client_secret", secret.as_str()));
+    }
+    if let Some(scope) = &cfg.scope {
+        params.push(("scope", scope.as_str()));
+    }
+
+    let req = Request::builder()
+        .method("POST")
+        .uri(uri.clone())
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(form_encode(&params))
+        .map_err(|e| e.to_string())?;
+
+    // The token endpoint needs no auth header, so reuse the plain HTTP client
+    let (parts, body) = WebDavClient::new(uri, http)
+        .request_raw(req)
+        .await
+        .map_err(|e| format!("OAuth2 token request: {:?}", e))?;
+    if !parts.status.is_success() {
+        return Err(format!(
+            "OAuth2 token refresh failed: {} {}",
+            parts.status,
+            String::from_utf8_lossy(&body)
+        ));
+    }
+
+    let resp: TokenResponse =
+        serde_json::from_slice(&body).map_err(|e| format!("OAuth2 token response: {}", e))?;
+    Ok(OAuthToken {
+        access_token: resp.access_token,
+        // Servers that don't rotate refresh tokens omit it: keep the one we used
+        refresh_token: resp
+            .refresh_token
+            .or_else(|| Some(refresh_token.to_string())),
+        expires_at: resp.expires_in.map(|s| Utc::now().timestamp() + s),
+    })
+}
+
+fn form_encode(params: &[(&str, &str)]) -> String {
+    fn encode(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for b in s.bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    out.push(b as char)
+                }
+                b' ' => out.push('+'),
+                _ => out.push_str(&format!("%{:02X}", b)),
+            }
+        }
+        out
+    }
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode(k), encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
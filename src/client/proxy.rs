@@ -0,0 +1,84 @@
+// File: ./src/client/proxy.rs
+// Connector routing requests through an HTTP proxy (HTTP_PROXY / HTTPS_PROXY / `proxy` config).
+// Every proxied connection is a CONNECT tunnel, so TLS stays end-to-end with the CalDAV server.
+use futures::future::{BoxFuture, poll_fn};
+use http::Uri;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::connect::proxy::Tunnel;
+use hyper_util::client::proxy::matcher::Matcher;
+use hyper_util::rt::TokioIo;
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Clone)]
+pub struct ProxyConnector {
+    http: HttpConnector,
+    matcher: Arc<Matcher>,
+}
+
+impl ProxyConnector {
+    /// An explicit proxy URL overrides the environment. NO_PROXY is honored either way.
+    pub fn new(proxy: Option<&str>) -> Self {
+        let mut http = HttpConnector::new();
+        // The TLS layer on top of us handles https:// URIs
+        http.enforce_http(false);
+
+        let matcher = match proxy {
+            Some(url) => {
+                let no_proxy = std::env::var("NO_PROXY")
+                    .or_else(|_| std::env::var("no_proxy"))
+                    .unwrap_or_default();
+                Matcher::builder().all(url.to_string()).no(no_proxy).build()
+            }
+            None => Matcher::from_env(),
+        };
+
+        Self {
+            http,
+            matcher: Arc::new(matcher),
+        }
+    }
+}
+
+impl fmt::Debug for ProxyConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConnector").finish_non_exhaustive()
+    }
+}
+
+impl Service<Uri> for ProxyConnector {
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, dst: Uri) -> Self::Future {
+        let mut http = self.http.clone();
+        let intercept = self.matcher.intercept(&dst);
+
+        Box::pin(async move {
+            match intercept {
+                None => http.call(dst).await.map_err(Into::into),
+                Some(proxy) => {
+                    let mut tunnel = Tunnel::new(proxy.uri().clone(), http);
+                    if let Some(auth) = proxy.basic_auth() {
+                        tunnel = tunnel.with_auth(auth.clone());
+                    }
+                    poll_fn(|cx| tunnel.poll_ready(cx)).await?;
+                    tunnel
+                        .call(dst)
+                        .await
+                        .map_err(|e| format!("Proxy tunnel: {}", e).into())
+                }
+            }
+        })
+    }
+}
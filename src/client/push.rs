@@ -0,0 +1,258 @@
+// File: ./src/client/push.rs
+// WebDAV-Push (draft-bitfire-webdav-push): the CalDAV server POSTs a small
+// <push-message> to a push resource whenever a subscribed collection changes.
+// We use an ntfy/UnifiedPush topic URL as push resource and read it back as a
+// JSON stream, so desktop clients need no Web Push service of their own.
+use crate::client::core::{ConnectionOptions, HttpClient, build_http_client};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use http::Uri;
+use http_body_util::BodyExt;
+use roxmltree::Document;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DAV_NS: &str = "DAV:";
+pub const PUSH_NS: &str = "https://bitfire.at/webdav-push";
+
+// How long a registration lasts before the server may drop it. We re-register
+// on every connect, so this only matters for long-running sessions.
+pub const REGISTRATION_DAYS: i64 = 7;
+
+/// Push capabilities of a collection.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PushSupport {
+    pub topic: Option<String>,
+    pub web_push: bool,
+}
+
+/// A collection changed on the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub topic: String,
+    // Resolved from the topics returned by `RustyClient::subscribe_push`
+    pub calendar_href: Option<String>,
+    pub sync_token: Option<String>,
+}
+
+pub fn build_push_support_body() -> String {
+    r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:p="https://bitfire.at/webdav-push">
+  <d:prop>
+    <p:transports/>
+    <p:topic/>
+  </d:prop>
+</d:propfind>"#
+        .to_string()
+}
+
+/// Parses the Depth 0 PROPFIND response of `build_push_support_body`.
+pub fn parse_push_support(xml: &str) -> Result<PushSupport, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid PROPFIND XML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name((DAV_NS, "multistatus")) {
+        return Err("PROPFIND: expected multistatus".to_string());
+    }
+
+    let mut support = PushSupport::default();
+    for propstat in root
+        .descendants()
+        .filter(|n| n.has_tag_name((DAV_NS, "propstat")))
+    {
+        let ok = propstat
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "status")))
+            .and_then(|n| n.text())
+            .is_some_and(|s| s.contains(" 200"));
+        if !ok {
+            continue;
+        }
+        support.topic = support.topic.or_else(|| {
+            propstat
+                .descendants()
+                .find(|n| n.has_tag_name((PUSH_NS, "topic")))
+                .and_then(|n| n.text())
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        });
+        support.web_push |= propstat
+            .descendants()
+            .any(|n| n.has_tag_name((PUSH_NS, "web-push")));
+    }
+    Ok(support)
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn build_register_body(push_resource: &str, expires: DateTime<Utc>) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<p:push-register xmlns:p="https://bitfire.at/webdav-push">
+  <p:subscription>
+    <p:web-push-subscription>
+      <p:push-resource>{}</p:push-resource>
+    </p:web-push-subscription>
+  </p:subscription>
+  <p:expires>{}</p:expires>
+</p:push-register>"#,
+        escape_xml(push_resource),
+        // HTTP-date (RFC 9110)
+        expires.format("%a, %d %b %Y %H:%M:%S GMT")
+    )
+}
+
+/// Extracts (topic, sync-token) from a <push-message>.
+pub fn parse_push_message(xml: &str) -> Option<(String, Option<String>)> {
+    let doc = Document::parse(xml).ok()?;
+    let root = doc.root_element();
+    if !root.has_tag_name((PUSH_NS, "push-message")) {
+        return None;
+    }
+    let topic = root
+        .descendants()
+        .find(|n| n.has_tag_name((PUSH_NS, "topic")))
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())?;
+    let sync_token = root
+        .descendants()
+        .find(|n| n.has_tag_name((DAV_NS, "sync-token")))
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    Some((topic, sync_token))
+}
+
+// One line of ntfy's `/json` stream; only "message" events carry a push
+fn parse_stream_line(line: &str) -> Option<(String, Option<String>)> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    if value.get("event")?.as_str()? != "message" {
+        return None;
+    }
+    parse_push_message(value.get("message")?.as_str()?)
+}
+
+struct ListenState {
+    http: HttpClient,
+    url: Uri,
+    body: Option<BoxStream<'static, Result<Vec<u8>, String>>>,
+    buf: Vec<u8>,
+    failures: u32,
+    topics: HashMap<String, String>,
+}
+
+/// Stream of change events read from the push endpoint. Reconnects with a
+/// capped backoff when the connection drops; never ends on its own.
+/// `topics` maps push topics to calendar hrefs (see `RustyClient::subscribe_push`).
+pub fn listen(
+    endpoint: &str,
+    opts: &ConnectionOptions,
+    topics: HashMap<String, String>,
+) -> Result<impl Stream<Item = ChangeEvent> + Send + 'static, String> {
+    let url = format!("{}/json", endpoint.trim_end_matches('/'))
+        .parse::<Uri>()
+        .map_err(|e| format!("Invalid push endpoint: {}", e))?;
+    let state = ListenState {
+        http: build_http_client(opts)?,
+        url,
+        body: None,
+        buf: Vec::new(),
+        failures: 0,
+        topics,
+    };
+
+    Ok(stream::unfold(state, |mut st| async move {
+        loop {
+            while let Some(pos) = st.buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = st.buf.drain(..=pos).collect();
+                if let Some((topic, sync_token)) =
+                    parse_stream_line(&String::from_utf8_lossy(&line))
+                {
+                    let calendar_href = st.topics.get(&topic).cloned();
+                    let event = ChangeEvent {
+                        topic,
+                        calendar_href,
+                        sync_token,
+                    };
+                    return Some((event, st));
+                }
+            }
+
+            match st.body.as_mut() {
+                Some(body) => match body.next().await {
+                    Some(Ok(chunk)) => {
+                        st.buf.extend_from_slice(&chunk);
+                        st.failures = 0;
+                    }
+                    // Dropped or closed: reconnect after a short pause
+                    _ => {
+                        st.body = None;
+                        st.failures += 1;
+                    }
+                },
+                None => {
+                    if st.failures > 0 {
+                        let secs = 2u64.saturating_pow(st.failures.min(6));
+                        tokio::time::sleep(Duration::from_secs(secs)).await;
+                    }
+                    match st.http.get(st.url.clone()).await {
+                        Ok(resp) if resp.status().is_success() => {
+                            st.buf.clear();
+                            st.body = Some(
+                                resp.into_body()
+                                    .into_data_stream()
+                                    .map(|r| r.map(|b| b.to_vec()).map_err(|e| e.to_string()))
+                                    .boxed(),
+                            );
+                        }
+                        _ => st.failures += 1,
+                    }
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_push_support() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:p="https://bitfire.at/webdav-push">
+  <d:response>
+    <d:href>/cals/work/</d:href>
+    <d:propstat>
+      <d:prop>
+        <p:transports><p:web-push/></p:transports>
+        <p:topic>O7M1nQ7cKkKTKsoS_j6Z3w</p:topic>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        assert_eq!(
+            parse_push_support(xml).unwrap(),
+            PushSupport {
+                topic: Some("O7M1nQ7cKkKTKsoS_j6Z3w".to_string()),
+                web_push: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let msg = r#"<push-message xmlns="https://bitfire.at/webdav-push" xmlns:D="DAV:"><topic>abc</topic><content-update><D:sync-token>tok-2</D:sync-token></content-update></push-message>"#;
+        let line = serde_json::json!({"id": "x", "event": "message", "message": msg}).to_string();
+        assert_eq!(
+            parse_stream_line(&line),
+            Some(("abc".to_string(), Some("tok-2".to_string())))
+        );
+        assert_eq!(parse_stream_line(r#"{"event":"keepalive"}"#), None);
+    }
+}
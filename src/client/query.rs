@@ -0,0 +1,126 @@
+// File: ./src/client/query.rs
+// RFC 4791 calendar-query REPORT: lists the VTODOs of a calendar, skipping the
+// events of mixed calendars before they're ever downloaded
+use chrono::{DateTime, Utc};
+use roxmltree::Document;
+
+const DAV_NS: &str = "DAV:";
+
+fn query_body(vtodo_filter: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      {}
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        vtodo_filter
+    )
+}
+
+pub fn build_todo_query_body() -> String {
+    query_body(r#"<c:comp-filter name="VTODO"/>"#)
+}
+
+// Filters can't be OR'ed in RFC 4791, so skipping old completed tasks takes two
+// queries: the open tasks, then the ones completed recently.
+
+/// VTODOs without a COMPLETED date.
+pub fn build_open_todo_query_body() -> String {
+    query_body(
+        r#"<c:comp-filter name="VTODO">
+        <c:prop-filter name="COMPLETED"><c:is-not-defined/></c:prop-filter>
+      </c:comp-filter>"#,
+    )
+}
+
+/// VTODOs completed at or after `since`.
+pub fn build_completed_since_query_body(since: DateTime<Utc>) -> String {
+    query_body(&format!(
+        r#"<c:comp-filter name="VTODO">
+        <c:prop-filter name="COMPLETED"><c:time-range start="{}"/></c:prop-filter>
+      </c:comp-filter>"#,
+        since.format("%Y%m%dT%H%M%SZ")
+    ))
+}
+
+/// Parses a calendar-query multistatus into (href, etag) pairs.
+pub fn parse_etag_listing(xml: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid calendar-query XML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name((DAV_NS, "multistatus")) {
+        return Err("calendar-query: expected multistatus".to_string());
+    }
+
+    let mut listing = Vec::new();
+    for response in root
+        .children()
+        .filter(|n| n.has_tag_name((DAV_NS, "response")))
+    {
+        let Some(href) = response
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "href")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+        else {
+            continue;
+        };
+        let etag = response
+            .descendants()
+            .find(|n| n.has_tag_name((DAV_NS, "getetag")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+        listing.push((href, etag));
+    }
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_etag_listing() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/cal/todo-1.ics</d:href>
+    <d:propstat>
+      <d:prop><d:getetag>"e1"</d:getetag></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/cal/todo-2.ics</d:href>
+    <d:propstat>
+      <d:prop><d:getetag/></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        assert_eq!(
+            parse_etag_listing(xml).unwrap(),
+            vec![
+                ("/cal/todo-1.ics".to_string(), Some("\"e1\"".to_string())),
+                ("/cal/todo-2.ics".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completed_since_query() {
+        let since = Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+        let body = build_completed_since_query_body(since);
+        assert!(body.contains(r#"<c:time-range start="20250301T120000Z"/>"#));
+        assert!(Document::parse(&body).is_ok());
+        assert!(Document::parse(&build_open_todo_query_body()).is_ok());
+    }
+}
@@ -0,0 +1,103 @@
+// File: ./src/client/quota.rs
+// RFC 4331 quota properties, read on the calendar home
+use roxmltree::Document;
+
+const DAV_NS: &str = "DAV:";
+
+/// Storage usage reported by the server. Either value may be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Quota {
+    pub used_bytes: Option<u64>,
+    pub available_bytes: Option<u64>,
+}
+
+impl Quota {
+    pub fn is_empty(&self) -> bool {
+        self.used_bytes.is_none() && self.available_bytes.is_none()
+    }
+
+    /// e.g. "12.5 MB used of 1.0 GB"
+    pub fn summary(&self) -> String {
+        match (self.used_bytes, self.available_bytes) {
+            (Some(used), Some(avail)) => format!(
+                "{} used of {}",
+                format_bytes(used),
+                format_bytes(used.saturating_add(avail))
+            ),
+            (Some(used), None) => format!("{} used", format_bytes(used)),
+            (None, Some(avail)) => format!("{} available", format_bytes(avail)),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+}
+
+pub fn build_quota_body() -> String {
+    r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:quota-used-bytes/>
+    <d:quota-available-bytes/>
+  </d:prop>
+</d:propfind>"#
+        .to_string()
+}
+
+pub fn parse_quota(xml: &str) -> Result<Quota, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid PROPFIND XML: {}", e))?;
+    let number = |name: &str| {
+        doc.descendants()
+            .find(|n| n.has_tag_name((DAV_NS, name)))
+            .and_then(|n| n.text())
+            .and_then(|t| t.trim().parse::<u64>().ok())
+    };
+    Ok(Quota {
+        used_bytes: number("quota-used-bytes"),
+        available_bytes: number("quota-available-bytes"),
+    })
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quota() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/calendars/user/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:quota-used-bytes>1572864</d:quota-used-bytes>
+        <d:quota-available-bytes>1072168960</d:quota-available-bytes>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        let quota = parse_quota(xml).unwrap();
+        assert_eq!(quota.used_bytes, Some(1_572_864));
+        assert_eq!(quota.summary(), "1.5 MB used of 1.0 GB");
+
+        // Unsupported properties come back empty in a 404 propstat
+        let xml = r#"<d:multistatus xmlns:d="DAV:"><d:response><d:href>/</d:href>
+<d:propstat><d:prop><d:quota-used-bytes/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat>
+</d:response></d:multistatus>"#;
+        assert!(parse_quota(xml).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+// File: ./src/client/retry.rs
+// Service wrapper retrying transient failures with exponential backoff.
+// Permanent errors (403, 404, 412...) are returned as-is on the first attempt.
+// A 429/503 with Retry-After is waited out instead, without using up an attempt.
+use crate::config::RetryPolicy;
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, poll_fn};
+use http::{HeaderMap, Method, Request, Response, StatusCode, header};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower_service::Service;
+
+#[derive(Clone, Debug)]
+pub struct Retry<S> {
+    inner: S,
+    policy: RetryPolicy,
+}
+
+impl<S> Retry<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+// MOVE/COPY/POST may have been applied before the failure: replaying them isn't safe
+fn is_idempotent(method: &Method) -> bool {
+    !matches!(
+        method.as_str(),
+        "POST" | "MOVE" | "COPY" | "LOCK" | "UNLOCK"
+    )
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Delay requested by a `Retry-After` header, either in seconds or as an HTTP-date.
+pub fn parse_retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = DateTime::parse_from_rfc2822(value)
+        .ok()?
+        .with_timezone(&Utc);
+    Some((at - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(16));
+        let capped = exp.min(self.max_delay_ms);
+        let ms = if self.jitter && capped > 0 {
+            // Full jitter in [capped/2, capped], no need for a RNG crate here
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.subsec_nanos() as u64)
+                .unwrap_or(0);
+            capped / 2 + nanos % (capped / 2 + 1)
+        } else {
+            capped
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+impl<S, B> Service<Request<String>> for Retry<S>
+where
+    S: Service<Request<String>, Response = Response<B>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    B: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<String>) -> Self::Future {
+        // The ready clone goes into the future, a fresh clone stays behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let policy = self.policy.clone();
+
+        Box::pin(async move {
+            let idempotent = is_idempotent(req.method());
+            if (policy.max_attempts <= 1 || !idempotent) && policy.max_retry_after_secs == 0 {
+                return inner.call(req).await;
+            }
+
+            let (parts, body) = req.into_parts();
+            let max_wait = Duration::from_secs(policy.max_retry_after_secs);
+            let mut waited = Duration::ZERO;
+            let mut attempt = 1;
+            loop {
+                let mut next = Request::new(body.clone());
+                *next.method_mut() = parts.method.clone();
+                *next.uri_mut() = parts.uri.clone();
+                *next.version_mut() = parts.version;
+                *next.headers_mut() = parts.headers.clone();
+
+                let res = inner.call(next).await;
+
+                // The server refused the request without processing it, so even
+                // non-idempotent methods are safe to resend once the delay is over
+                if let Ok(resp) = &res
+                    && matches!(
+                        resp.status(),
+                        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+                    )
+                    && let Some(delay) = parse_retry_after(resp.headers(), Utc::now())
+                    && waited + delay <= max_wait
+                {
+                    tokio::time::sleep(delay).await;
+                    waited += delay;
+                    poll_fn(|cx| inner.poll_ready(cx)).await?;
+                    continue;
+                }
+
+                let retryable = idempotent
+                    && match &res {
+                        Ok(resp) => is_retryable_status(resp.status()),
+                        // Transport errors (refused, reset, timeout)
+                        Err(_) => true,
+                    };
+                if !retryable || attempt >= policy.max_attempts {
+                    return res;
+                }
+
+                tokio::time::sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+                poll_fn(|cx| inner.poll_ready(cx)).await?;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(
+            parse_retry_after(&headers, now),
+            Some(Duration::from_secs(120))
+        );
+
+        headers.insert(
+            header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:30 GMT".parse().unwrap(),
+        );
+        assert_eq!(
+            parse_retry_after(&headers, now),
+            Some(Duration::from_secs(30))
+        );
+
+        headers.insert(header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers, now), None);
+    }
+}
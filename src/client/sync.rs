@@ -0,0 +1,226 @@
+// File: ./src/client/sync.rs
+// RFC 6578 sync-collection REPORT: request body + multistatus parsing
+use roxmltree::Document;
+use std::collections::HashMap;
+
+const DAV_NS: &str = "DAV:";
+const CS_NS: &str = "http://calendarserver.org/ns/";
+
+// Change markers of a collection, fetched in one PROPFIND
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CollectionState {
+    pub ctag: Option<String>,
+    pub sync_token: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SyncDelta {
+    pub new_token: Option<String>,
+    // (href, etag) of members that were added or modified since the old token
+    pub changed: Vec<(String, Option<String>)>,
+    pub deleted: Vec<String>,
+    // Server returned 507 on the collection itself: more changes are pending
+    pub truncated: bool,
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn build_sync_collection_body(token: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:">
+  <d:sync-token>{}</d:sync-token>
+  <d:sync-level>1</d:sync-level>
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+</d:sync-collection>"#,
+        escape_xml(token)
+    )
+}
+
+pub fn parse_sync_collection_response(xml: &str) -> Result<SyncDelta, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid sync-collection XML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name((DAV_NS, "multistatus")) {
+        return Err("sync-collection: expected multistatus".to_string());
+    }
+
+    let mut delta = SyncDelta {
+        new_token: root
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "sync-token")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty()),
+        ..Default::default()
+    };
+
+    for response in root
+        .children()
+        .filter(|n| n.has_tag_name((DAV_NS, "response")))
+    {
+        let Some(href) = response
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "href")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+        else {
+            continue;
+        };
+
+        // A status directly under <response> (no propstat) describes the member itself
+        if let Some(status) = response
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "status")))
+            .and_then(|n| n.text())
+        {
+            if status.contains(" 404") {
+                delta.deleted.push(href);
+            } else if status.contains(" 507") {
+                delta.truncated = true;
+            }
+            continue;
+        }
+
+        let etag = response
+            .descendants()
+            .find(|n| n.has_tag_name((DAV_NS, "getetag")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+        delta.changed.push((href, etag));
+    }
+
+    Ok(delta)
+}
+
+pub fn build_collection_state_body() -> String {
+    r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <d:prop>
+    <cs:getctag/>
+    <d:sync-token/>
+  </d:prop>
+</d:propfind>"#
+        .to_string()
+}
+
+/// Parses a PROPFIND multistatus into href -> (ctag, sync-token).
+/// Properties reported with a non-200 propstat (unsupported) are left empty.
+pub fn parse_collection_states(xml: &str) -> Result<HashMap<String, CollectionState>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid PROPFIND XML: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name((DAV_NS, "multistatus")) {
+        return Err("PROPFIND: expected multistatus".to_string());
+    }
+
+    let mut states = HashMap::new();
+    for response in root
+        .children()
+        .filter(|n| n.has_tag_name((DAV_NS, "response")))
+    {
+        let Some(href) = response
+            .children()
+            .find(|n| n.has_tag_name((DAV_NS, "href")))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+        else {
+            continue;
+        };
+
+        let mut state = CollectionState::default();
+        for propstat in response
+            .children()
+            .filter(|n| n.has_tag_name((DAV_NS, "propstat")))
+        {
+            let ok = propstat
+                .children()
+                .find(|n| n.has_tag_name((DAV_NS, "status")))
+                .and_then(|n| n.text())
+                .is_some_and(|s| s.contains(" 200"));
+            if !ok {
+                continue;
+            }
+            let text = |ns: &str, name: &str| {
+                propstat
+                    .descendants()
+                    .find(|n| n.has_tag_name((ns, name)))
+                    .and_then(|n| n.text())
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+            };
+            state.ctag = state.ctag.or_else(|| text(CS_NS, "getctag"));
+            state.sync_token = state.sync_token.or_else(|| text(DAV_NS, "sync-token"));
+        }
+        states.insert(href, state);
+    }
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sync_collection_delta() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/cal/a.ics</d:href>
+    <d:propstat>
+      <d:prop><d:getetag>"etag-a2"</d:getetag></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/cal/b.ics</d:href>
+    <d:status>HTTP/1.1 404 Not Found</d:status>
+  </d:response>
+  <d:sync-token>http://example.com/sync/42</d:sync-token>
+</d:multistatus>"#;
+
+        let delta = parse_sync_collection_response(xml).unwrap();
+        assert_eq!(
+            delta.changed,
+            vec![("/cal/a.ics".to_string(), Some("\"etag-a2\"".to_string()))]
+        );
+        assert_eq!(delta.deleted, vec!["/cal/b.ics".to_string()]);
+        assert_eq!(
+            delta.new_token.as_deref(),
+            Some("http://example.com/sync/42")
+        );
+        assert!(!delta.truncated);
+    }
+
+    #[test]
+    fn test_parse_collection_states() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+  <d:response>
+    <d:href>/cals/work/</d:href>
+    <d:propstat>
+      <d:prop><cs:getctag>ctag-1</cs:getctag></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop><d:sync-token/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        let states = parse_collection_states(xml).unwrap();
+        assert_eq!(
+            states.get("/cals/work/"),
+            Some(&CollectionState {
+                ctag: Some("ctag-1".to_string()),
+                sync_token: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,67 @@
+// File: ./src/client/throttle.rs
+// Service wrapper spacing requests out so a client never exceeds N requests/second.
+// Clones share the same schedule, so concurrent replays are throttled together.
+use futures::future::BoxFuture;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower_service::Service;
+
+#[derive(Clone, Debug)]
+pub struct Throttle<S> {
+    inner: S,
+    // None = unlimited
+    interval: Option<Duration>,
+    next_slot: Arc<Mutex<Instant>>,
+}
+
+impl<S> Throttle<S> {
+    pub fn new(inner: S, max_per_second: Option<u32>) -> Self {
+        Self {
+            inner,
+            interval: max_per_second
+                .filter(|n| *n > 0)
+                .map(|n| Duration::from_secs(1) / n),
+            next_slot: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    // Reserves the next free slot and returns how long to wait for it
+    fn reserve(&self) -> Duration {
+        let Some(interval) = self.interval else {
+            return Duration::ZERO;
+        };
+        let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let start = (*next).max(now);
+        *next = start + interval;
+        start - now
+    }
+}
+
+impl<S, Req> Service<Req> for Throttle<S>
+where
+    S: Service<Req> + Clone + Send + 'static,
+    S::Future: Send,
+    Req: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let wait = self.reserve();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+            inner.call(req).await
+        })
+    }
+}
@@ -56,6 +56,23 @@ pub fn is_dark(r: f32, g: f32, b: f32) -> bool {
     brightness < 0.5
 }
 
+/// Normalizes a server-provided calendar color to `#RRGGBB`.
+/// Handles Apple's `#RRGGBBAA` (alpha is dropped), short `#RGB` and missing `#`.
+pub fn normalize_hex_color(raw: &str) -> Option<String> {
+    let hex = raw.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        3 => {
+            let expanded: String = hex.chars().flat_map(|c| [c, c]).collect();
+            Some(format!("#{}", expanded.to_uppercase()))
+        }
+        6 | 8 => Some(format!("#{}", hex[0..6].to_uppercase())),
+        _ => None,
+    }
+}
+
 pub fn parse_hex_to_floats(hex: &str) -> Option<(f32, f32, f32)> {
     let hex = hex.trim_start_matches('#');
     if hex.len() < 6 {
@@ -79,3 +96,29 @@ pub fn parse_hex_to_u8(hex: &str) -> Option<(u8, u8, u8)> {
 
     Some((r, g, b))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_hex_color() {
+        assert_eq!(normalize_hex_color("#1a2B3c"), Some("#1A2B3C".to_string()));
+        // Short form, Apple's alpha, no '#', stray spaces
+        assert_eq!(normalize_hex_color("#f80"), Some("#FF8800".to_string()));
+        assert_eq!(
+            normalize_hex_color("#FF8800CC"),
+            Some("#FF8800".to_string())
+        );
+        assert_eq!(normalize_hex_color("ff8800"), Some("#FF8800".to_string()));
+        assert_eq!(
+            normalize_hex_color(" #ff8800 "),
+            Some("#FF8800".to_string())
+        );
+        // Not a color
+        assert_eq!(normalize_hex_color("orange"), None);
+        assert_eq!(normalize_hex_color("#ff88"), None);
+        assert_eq!(normalize_hex_color("#gg8800"), None);
+        assert_eq!(normalize_hex_color(""), None);
+    }
+}
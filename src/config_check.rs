@@ -0,0 +1,385 @@
+// File: src/config_check.rs
+// Checks of config.toml beyond what serde does, so `Config::load` can list every problem
+// at once, each with the key it's about and how to fix it, instead of the first parse error.
+use crate::config::{
+    AccountConfig, CalendarPrefs, Config, Hooks, OAuth2Config, RetryPolicy, ThemeConfig,
+};
+use crate::model::CustomStatus;
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    // Dotted path of the key, e.g. "accounts[1].url" (empty: the whole file)
+    pub key: String,
+    pub message: String,
+    pub hint: String,
+    // Warnings (unknown or ignored keys) don't stop cfait from starting
+    pub warning: bool,
+}
+
+impl ConfigProblem {
+    fn error(key: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.into(),
+            hint: hint.into(),
+            warning: false,
+        }
+    }
+
+    fn warning(key: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            warning: true,
+            ..Self::error(key, message, hint)
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        write!(f, "{}", self.message)?;
+        if !self.hint.is_empty() {
+            write!(f, " ({})", self.hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// What `Config::load` returns when the file has problems that keep it from loading.
+#[derive(Debug)]
+pub struct ConfigErrors {
+    pub path: String,
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Problems in {}:", self.path)?;
+        for p in &self.problems {
+            write!(f, "\n- {}{}", if p.warning { "(warning) " } else { "" }, p)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Everything wrong with the contents of a config file, errors and warnings.
+pub fn check(contents: &str) -> Vec<ConfigProblem> {
+    let table: toml::Table = match toml::from_str(contents) {
+        Ok(t) => t,
+        Err(e) => {
+            return vec![ConfigProblem::error(
+                "",
+                e.message().trim(),
+                line_hint(contents, e.span()),
+            )];
+        }
+    };
+
+    let mut problems = Vec::new();
+    unknown_keys::<Config>(&table, "", &mut problems);
+    section::<RetryPolicy>(&table, "retry", &mut problems);
+    section::<ThemeConfig>(&table, "theme", &mut problems);
+    section::<Hooks>(&table, "hooks", &mut problems);
+    section::<OAuth2Config>(&table, "oauth2", &mut problems);
+    if let Some(toml::Value::Table(cals)) = table.get("calendars") {
+        for (name, value) in cals {
+            if let toml::Value::Table(t) = value {
+                unknown_keys::<CalendarPrefs>(t, &format!("calendars.\"{}\"", name), &mut problems);
+            }
+        }
+    }
+    for (i, t) in tables(&table, "custom_statuses") {
+        unknown_keys::<CustomStatus>(t, &format!("custom_statuses[{}]", i), &mut problems);
+    }
+
+    required(&table, "", &["url", "username"], &mut problems);
+    if let Some(toml::Value::Table(t)) = table.get("oauth2") {
+        required(t, "oauth2", &["token_url", "client_id"], &mut problems);
+    }
+    check_url(&table, "url", "", false, &mut problems);
+    check_connection(&table, "", &mut problems);
+    check_auth(&table, &mut problems);
+
+    for (i, t) in tables(&table, "accounts") {
+        let prefix = format!("accounts[{}]", i);
+        unknown_keys::<AccountConfig>(t, &prefix, &mut problems);
+        required(t, &prefix, &["label", "url"], &mut problems);
+        check_url(t, "url", &prefix, false, &mut problems);
+        check_connection(t, &prefix, &mut problems);
+    }
+
+    // Proxies default to http://, a push topic is a full URL
+    check_url(&table, "proxy", "", false, &mut problems);
+    check_url(&table, "push_endpoint", "", true, &mut problems);
+
+    // Wrong types and values: serde's message, with the line
+    if !problems.iter().any(|p| !p.warning)
+        && let Err(e) = toml::from_str::<Config>(contents)
+    {
+        problems.push(ConfigProblem::error(
+            "",
+            e.message().trim(),
+            line_hint(contents, e.span()),
+        ));
+    }
+    problems
+}
+
+fn path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+fn line_hint(contents: &str, span: Option<std::ops::Range<usize>>) -> String {
+    match span {
+        Some(span) => {
+            let before = &contents[..span.start.min(contents.len())];
+            format!("line {}", before.matches('\n').count() + 1)
+        }
+        None => String::new(),
+    }
+}
+
+// The array of tables under `key`, with their index
+fn tables<'a>(table: &'a toml::Table, key: &str) -> Vec<(usize, &'a toml::Table)> {
+    match table.get(key) {
+        Some(toml::Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.as_table().map(|t| (i, t)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn str_value<'a>(table: &'a toml::Table, key: &str) -> Option<&'a str> {
+    table.get(key).and_then(|v| v.as_str())
+}
+
+// Unknown keys of the `[key]` table, when there is one
+fn section<T: DeserializeOwned>(table: &toml::Table, key: &str, problems: &mut Vec<ConfigProblem>) {
+    if let Some(toml::Value::Table(t)) = table.get(key) {
+        unknown_keys::<T>(t, key, problems);
+    }
+}
+
+fn unknown_keys<T: DeserializeOwned>(
+    table: &toml::Table,
+    prefix: &str,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let known = field_names::<T>();
+    for key in table.keys().filter(|k| !known.contains(&k.as_str())) {
+        let hint = match closest(key, known) {
+            Some(name) => format!("did you mean `{}`?", name),
+            None => "it is ignored".to_string(),
+        };
+        problems.push(ConfigProblem::warning(
+            &path(prefix, key),
+            "unknown setting",
+            hint,
+        ));
+    }
+}
+
+fn required(table: &toml::Table, prefix: &str, keys: &[&str], problems: &mut Vec<ConfigProblem>) {
+    for key in keys.iter().filter(|k| !table.contains_key(**k)) {
+        let hint = match *key {
+            "url" if prefix.is_empty() => "use url = \"\" to keep tasks on this device only",
+            "username" => "use username = \"\" if the server doesn't need one",
+            _ => "",
+        };
+        problems.push(ConfigProblem::error(&path(prefix, key), "missing", hint));
+    }
+}
+
+// `needs_scheme`: a bare host isn't enough (the server URL can be a domain, for discovery)
+fn check_url(
+    table: &toml::Table,
+    key: &str,
+    prefix: &str,
+    needs_scheme: bool,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let Some(url) = str_value(table, key).map(str::trim) else {
+        return;
+    };
+    if url.is_empty() {
+        return;
+    }
+    let key = path(prefix, key);
+    match url.split_once("://") {
+        Some((scheme, _)) if !matches!(scheme, "http" | "https") => {
+            problems.push(ConfigProblem::error(
+                &key,
+                format!("unsupported scheme \"{}\"", scheme),
+                "use an https:// (or http://) URL",
+            ));
+        }
+        Some((_, rest)) if rest.is_empty() || rest.starts_with('/') => {
+            problems.push(ConfigProblem::error(&key, "no host in the URL", ""));
+        }
+        None if needs_scheme || url.contains('/') => {
+            problems.push(ConfigProblem::error(
+                &key,
+                "no scheme in the URL",
+                format!("try \"https://{}\"", url),
+            ));
+        }
+        _ => {}
+    }
+}
+
+// TLS options that only work together, or cancel each other out
+fn check_connection(table: &toml::Table, prefix: &str, problems: &mut Vec<ConfigProblem>) {
+    match (
+        table.contains_key("client_cert"),
+        table.contains_key("client_key"),
+    ) {
+        (true, false) => problems.push(ConfigProblem::error(
+            &path(prefix, "client_key"),
+            "missing",
+            "client_cert and client_key must be set together",
+        )),
+        (false, true) => problems.push(ConfigProblem::error(
+            &path(prefix, "client_cert"),
+            "missing",
+            "client_cert and client_key must be set together",
+        )),
+        _ => {}
+    }
+    let insecure = table
+        .get("allow_insecure_certs")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    for key in ["ca_cert_path", "pinned_sha256"] {
+        if insecure && table.contains_key(key) {
+            problems.push(ConfigProblem::warning(
+                &path(prefix, key),
+                "has no effect with allow_insecure_certs = true",
+                "remove allow_insecure_certs to check the certificate",
+            ));
+        }
+    }
+}
+
+// `auth` picks one way to log in: the settings of the other ones are mistakes
+fn check_auth(table: &toml::Table, problems: &mut Vec<ConfigProblem>) {
+    let auth = str_value(table, "auth").unwrap_or("basic");
+    // Anything else is serde's to report
+    if !matches!(auth, "basic" | "bearer" | "oauth2") {
+        return;
+    }
+    let has_token = table.contains_key("bearer_token");
+    let has_oauth2 = table.contains_key("oauth2");
+    let has_password = str_value(table, "password").is_some_and(|p| !p.is_empty());
+
+    if has_token && has_oauth2 {
+        problems.push(ConfigProblem::error(
+            "bearer_token",
+            "set together with [oauth2]",
+            "keep one of them, and set auth to \"bearer\" or \"oauth2\" to match",
+        ));
+    }
+    match auth {
+        "bearer" if !has_token => problems.push(ConfigProblem::error(
+            "bearer_token",
+            "missing",
+            "auth = \"bearer\" needs a token",
+        )),
+        "oauth2" if !has_oauth2 => problems.push(ConfigProblem::error(
+            "oauth2",
+            "missing",
+            "auth = \"oauth2\" needs an [oauth2] section with token_url and client_id",
+        )),
+        _ => {}
+    }
+    if auth != "bearer" && has_token && !has_oauth2 {
+        problems.push(ConfigProblem::warning(
+            "bearer_token",
+            format!("ignored with auth = \"{}\"", auth),
+            "add auth = \"bearer\" to use it",
+        ));
+    }
+    if auth != "oauth2" && has_oauth2 && !has_token {
+        problems.push(ConfigProblem::warning(
+            "oauth2",
+            format!("ignored with auth = \"{}\"", auth),
+            "add auth = \"oauth2\" to use it",
+        ));
+    }
+    if auth != "basic" && has_password {
+        problems.push(ConfigProblem::warning(
+            "password",
+            format!("ignored with auth = \"{}\"", auth),
+            "",
+        ));
+    }
+}
+
+// The known name nearest to a typo, if it's near enough
+fn closest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|name| (edit_distance(key, name), *name))
+        .filter(|(d, _)| *d <= (key.len() / 3).clamp(1, 3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, name)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+// The field names of a struct, read off its Deserialize impl: serde hands them to the
+// deserializer, which stops there. Keeps the known keys in step with the structs.
+fn field_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("done"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
@@ -0,0 +1,68 @@
+// File: src/credentials.rs
+// OS keyring storage for the CalDAV secret (secret-service / keychain / wincred)
+use anyhow::Result;
+
+const SERVICE: &str = "cfait";
+// Key of the encrypted data files (see `encryption`), shared by all accounts
+const STORAGE_KEY_ENTRY: &str = "storage-key";
+
+// One entry per account so changing the server doesn't reuse a stale secret
+fn account_key(url: &str, username: &str) -> String {
+    format!("{}@{}", username, url)
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn get_password(url: &str, username: &str) -> Result<Option<String>> {
+    let entry = keyring::Entry::new(SERVICE, &account_key(url, username))?;
+    match entry.get_password() {
+        Ok(p) => Ok(Some(p)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn set_password(url: &str, username: &str, password: &str) -> Result<()> {
+    let entry = keyring::Entry::new(SERVICE, &account_key(url, username))?;
+    entry.set_password(password)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn get_storage_key() -> Result<Option<String>> {
+    let entry = keyring::Entry::new(SERVICE, STORAGE_KEY_ENTRY)?;
+    match entry.get_password() {
+        Ok(k) => Ok(Some(k)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+pub fn set_storage_key(key: &str) -> Result<()> {
+    keyring::Entry::new(SERVICE, STORAGE_KEY_ENTRY)?.set_password(key)?;
+    Ok(())
+}
+
+#[cfg(target_os = "android")]
+pub fn get_password(url: &str, username: &str) -> Result<Option<String>> {
+    let _ = account_key(url, username);
+    Err(anyhow::anyhow!("Keyring is not supported on this platform"))
+}
+
+#[cfg(target_os = "android")]
+pub fn set_password(url: &str, username: &str, _password: &str) -> Result<()> {
+    let _ = account_key(url, username);
+    Err(anyhow::anyhow!("Keyring is not supported on this platform"))
+}
+
+#[cfg(target_os = "android")]
+pub fn get_storage_key() -> Result<Option<String>> {
+    let _ = STORAGE_KEY_ENTRY;
+    Err(anyhow::anyhow!("Keyring is not supported on this platform"))
+}
+
+#[cfg(target_os = "android")]
+pub fn set_storage_key(_key: &str) -> Result<()> {
+    Err(anyhow::anyhow!("Keyring is not supported on this platform"))
+}
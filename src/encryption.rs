@@ -0,0 +1,124 @@
+// File: src/encryption.rs
+// Optional encryption of the data files (journal, caches, local tasks, trash,
+// history, OAuth2 token) with XChaCha20-Poly1305. The key lives in the OS keyring.
+use crate::credentials;
+use anyhow::{Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::sync::RwLock;
+
+// Start of an encrypted file. Anything else is read as plain JSON, so turning
+// encryption on or off converts the files as they get saved.
+const MAGIC: &str = "cfait-encrypted-v1:";
+const NONCE_LEN: usize = 24;
+
+struct State {
+    enabled: bool,
+    // Loaded when encryption is enabled, or when an encrypted file is read
+    cipher: Option<XChaCha20Poly1305>,
+}
+
+static STATE: RwLock<State> = RwLock::new(State {
+    enabled: false,
+    cipher: None,
+});
+
+/// Turns encryption of the files written from now on on or off (from the config).
+/// Enabling it fetches the key from the keyring, or creates one.
+pub fn init(enabled: bool) -> Result<()> {
+    let mut state = STATE.write().unwrap_or_else(|e| e.into_inner());
+    if enabled && state.cipher.is_none() {
+        let key = match credentials::get_storage_key()? {
+            Some(key) => key,
+            None => {
+                let key = STANDARD.encode(XChaCha20Poly1305::generate_key(&mut OsRng));
+                credentials::set_storage_key(&key)?;
+                key
+            }
+        };
+        state.cipher = Some(cipher_from(&key)?);
+    }
+    state.enabled = enabled;
+    Ok(())
+}
+
+fn cipher_from(key: &str) -> Result<XChaCha20Poly1305> {
+    let bytes = STANDARD.decode(key.trim())?;
+    XChaCha20Poly1305::new_from_slice(&bytes).map_err(|_| anyhow!("Invalid encryption key"))
+}
+
+/// What to write to disk for `plain`: itself, or its encrypted form.
+pub fn seal(plain: String) -> Result<String> {
+    let state = STATE.read().unwrap_or_else(|e| e.into_inner());
+    match &state.cipher {
+        Some(cipher) if state.enabled => seal_with(cipher, &plain),
+        _ => Ok(plain),
+    }
+}
+
+/// Reverse of `seal`. Plain files are returned as they are.
+pub fn open(content: String) -> Result<String> {
+    if !content.starts_with(MAGIC) {
+        return Ok(content);
+    }
+    {
+        let state = STATE.read().unwrap_or_else(|e| e.into_inner());
+        if let Some(cipher) = &state.cipher {
+            return open_with(cipher, &content);
+        }
+    }
+    // Encryption was turned off since: the key is still needed to read old files
+    let key = credentials::get_storage_key()?.ok_or_else(|| {
+        anyhow!("Data file is encrypted, but the key is missing from the keyring")
+    })?;
+    let cipher = cipher_from(&key)?;
+    let plain = open_with(&cipher, &content);
+    STATE.write().unwrap_or_else(|e| e.into_inner()).cipher = Some(cipher);
+    plain
+}
+
+fn seal_with(cipher: &XChaCha20Poly1305, plain: &str) -> Result<String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut data = nonce.to_vec();
+    data.extend(
+        cipher
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| anyhow!("Encryption failed"))?,
+    );
+    Ok(format!("{}{}", MAGIC, STANDARD.encode(data)))
+}
+
+fn open_with(cipher: &XChaCha20Poly1305, content: &str) -> Result<String> {
+    let data = STANDARD.decode(content[MAGIC.len()..].trim())?;
+    if data.len() < NONCE_LEN {
+        return Err(anyhow!("Encrypted data file is truncated"));
+    }
+    let (nonce, sealed) = data.split_at(NONCE_LEN);
+    let plain = cipher
+        .decrypt(XNonce::from_slice(nonce), sealed)
+        .map_err(|_| anyhow!("Can't decrypt data file (wrong key or corrupted file)"))?;
+    Ok(String::from_utf8(plain)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let cipher = XChaCha20Poly1305::new(&XChaCha20Poly1305::generate_key(&mut OsRng));
+        let json = r#"[{"summary":"Renew passport"}]"#;
+
+        let sealed = seal_with(&cipher, json).unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        assert!(!sealed.contains("passport"));
+        assert_eq!(open_with(&cipher, &sealed).unwrap(), json);
+
+        // Another key can't read it, and plain files pass through
+        let other = XChaCha20Poly1305::new(&XChaCha20Poly1305::generate_key(&mut OsRng));
+        assert!(open_with(&other, &sealed).is_err());
+        assert_eq!(open(json.to_string()).unwrap(), json);
+    }
+}
@@ -1,8 +1,15 @@
 // File: ./src/gui/async_ops.rs
-use crate::client::RustyClient;
+use crate::client::core::FetchProgress;
+use crate::client::quota::Quota;
+use crate::client::{AccountManager, ClientError, RustyClient};
 use crate::config::Config;
-use crate::model::{CalendarListEntry, Task as TodoTask};
-use std::sync::OnceLock;
+use crate::gui::message::Message;
+use crate::journal::{Action, Resolution};
+use crate::model::{Attachment, CalendarListEntry, FileFormat, Task as TodoTask};
+use futures::Stream;
+use futures::channel::mpsc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
 use tokio::runtime::Runtime;
 
 // Global runtime instance for bridging Iced (sync) and Client (async)
@@ -23,92 +30,306 @@ pub fn get_runtime() -> &'static Runtime {
 
 // --- WRAPPERS ---
 
+// A runtime task that panicked or was cancelled
+fn join_error(e: tokio::task::JoinError) -> ClientError {
+    ClientError::Other(e.to_string())
+}
+
 pub async fn connect_and_fetch_wrapper(
     config: Config,
 ) -> Result<
     (
-        RustyClient,
+        AccountManager,
         Vec<CalendarListEntry>,
         Vec<TodoTask>,
         Option<String>,
         Option<String>,
     ),
-    String,
+    ClientError,
 > {
     let rt = get_runtime();
-    rt.spawn(async { RustyClient::connect_with_fallback(config).await })
+    rt.spawn(async { AccountManager::connect_with_fallback(config).await })
+        .await
+        .map_err(join_error)?
+}
+
+pub async fn async_check_connection_wrapper(
+    config: Config,
+) -> Result<Vec<CalendarListEntry>, ClientError> {
+    let rt = get_runtime();
+    rt.spawn(async move { RustyClient::check_connection(&config).await })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(join_error)?
 }
 
 pub async fn async_fetch_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     href: String,
-) -> Result<(String, Vec<TodoTask>), String> {
+) -> Result<(String, Vec<TodoTask>), ClientError> {
     let rt = get_runtime();
     rt.spawn(async move {
-        let tasks = client.get_tasks(&href).await.map_err(|e| e.to_string())?;
+        let tasks = manager.client_of_calendar(&href).get_tasks(&href).await?;
         Ok((href, tasks))
     })
     .await
-    .map_err(|e| e.to_string())?
+    .map_err(join_error)?
 }
 
 pub async fn async_fetch_all_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     cals: Vec<CalendarListEntry>,
-) -> Result<Vec<(String, Vec<TodoTask>)>, String> {
+) -> Result<Vec<(String, Vec<TodoTask>)>, ClientError> {
     let rt = get_runtime();
-    rt.spawn(async move { client.get_all_tasks(&cals).await })
+    rt.spawn(async move { manager.get_all_tasks(&cals).await })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(join_error)?
+}
+
+pub async fn async_sync_wrapper(manager: AccountManager) -> Result<Vec<String>, ClientError> {
+    let rt = get_runtime();
+    rt.spawn(async move { manager.sync_journal().await })
+        .await
+        .map_err(join_error)?
+}
+
+/// Like `async_fetch_all_wrapper`, but large calendars also report each downloaded
+/// batch as `TasksRefreshed` before the final `RefreshedAll`.
+pub fn fetch_all_progressive(
+    manager: AccountManager,
+    cals: Vec<CalendarListEntry>,
+) -> impl Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    let partial = tx.clone();
+    let manager = manager.with_progress(FetchProgress(Arc::new(move |href, tasks| {
+        let _ = partial.unbounded_send(Message::TasksRefreshed(Ok((
+            href.to_string(),
+            tasks.to_vec(),
+        ))));
+    })));
+    // The stream ends once the clients (holding `partial`) and `tx` are dropped
+    get_runtime().spawn(async move {
+        let res = manager.get_all_tasks(&cals).await;
+        let _ = tx.unbounded_send(Message::RefreshedAll(res));
+    });
+    rx
+}
+
+pub async fn async_subscribe_push_wrapper(
+    manager: AccountManager,
+    cals: Vec<CalendarListEntry>,
+    endpoint: String,
+) -> Result<(String, BTreeMap<String, String>), ClientError> {
+    let rt = get_runtime();
+    rt.spawn(async move {
+        let (topics, errors) = manager.subscribe_push(&cals, &endpoint).await;
+        // Fine as long as one account subscribed
+        if topics.is_empty() && !errors.is_empty() {
+            return Err(ClientError::Other(errors.join("; ")));
+        }
+        Ok((endpoint, topics.into_iter().collect()))
+    })
+    .await
+    .map_err(join_error)?
+}
+
+pub async fn async_quota_wrapper(manager: AccountManager) -> Result<Option<Quota>, ClientError> {
+    let rt = get_runtime();
+    rt.spawn(async move { manager.primary().quota().await })
+        .await
+        .map_err(join_error)?
 }
 
 pub async fn async_create_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     mut task: TodoTask,
-) -> Result<TodoTask, String> {
+) -> Result<TodoTask, ClientError> {
+    let client = manager.client_of(&task).clone();
     let _ = client.create_task(&mut task).await?;
     Ok(task)
 }
 
 pub async fn async_update_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     mut task: TodoTask,
-) -> Result<TodoTask, String> {
+) -> Result<TodoTask, ClientError> {
+    let client = manager.client_of(&task).clone();
     let _ = client.update_task(&mut task).await?;
     Ok(task)
 }
 
-pub async fn async_delete_wrapper(client: RustyClient, task: TodoTask) -> Result<(), String> {
-    let _ = client.delete_task(&task).await?;
+pub async fn async_delete_wrapper(
+    manager: AccountManager,
+    task: TodoTask,
+) -> Result<(), ClientError> {
+    let _ = manager.client_of(&task).delete_task(&task).await?;
     Ok(())
 }
 
+pub async fn async_undelete_wrapper(
+    manager: AccountManager,
+    uid: String,
+) -> Result<TodoTask, ClientError> {
+    let (task, _) = manager.undelete(&uid).await?;
+    Ok(task)
+}
+
+pub async fn async_undo_wrapper(manager: AccountManager) -> Result<Option<Action>, ClientError> {
+    manager.undo_last().await
+}
+
+pub async fn async_resolve_conflict_wrapper(
+    manager: AccountManager,
+    uid: String,
+    keep: Resolution,
+) -> Result<(Vec<TodoTask>, Vec<String>), ClientError> {
+    manager.resolve_conflict(&uid, keep).await
+}
+
 pub async fn async_toggle_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     mut task: TodoTask,
-) -> Result<(TodoTask, Option<TodoTask>), String> {
+) -> Result<(TodoTask, Option<TodoTask>), ClientError> {
+    let client = manager.client_of(&task).clone();
     let (_, next, _) = client.toggle_task(&mut task).await?;
     Ok((task, next))
 }
 
 pub async fn async_move_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     task: TodoTask,
     new_href: String,
-) -> Result<TodoTask, String> {
-    let (t, _) = client.move_task(&task, &new_href).await?;
+) -> Result<TodoTask, ClientError> {
+    let (t, _) = manager.move_task(&task, &new_href).await?;
     Ok(t)
 }
 
+/// Asks for an .ics file (or a Taskwarrior export) and imports it. `Ok(0)` when the
+/// dialog is cancelled.
+pub async fn async_import_wrapper(
+    manager: AccountManager,
+    calendar_href: String,
+) -> Result<usize, ClientError> {
+    let client = manager.client_of_calendar(&calendar_href).clone();
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .add_filter("iCalendar", &["ics"])
+        .add_filter("Taskwarrior", &["json"])
+        .pick_file()
+        .await
+    else {
+        return Ok(0);
+    };
+    let path = file.path().to_path_buf();
+    let rt = get_runtime();
+    let format = FileFormat::from_path(&path);
+    rt.spawn(async move { client.import_file(&path, &calendar_href, format).await })
+        .await
+        .map_err(join_error)?
+}
+
+/// Asks where to save and exports the calendar. `Ok(0)` when the dialog is cancelled.
+pub async fn async_export_wrapper(
+    manager: AccountManager,
+    calendar_href: String,
+    name: String,
+    format: FileFormat,
+) -> Result<usize, ClientError> {
+    let client = manager.client_of_calendar(&calendar_href).clone();
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .add_filter(format.label(), &[format.extension()])
+        .set_file_name(format!("{}.{}", name, format.extension()))
+        .save_file()
+        .await
+    else {
+        return Ok(0);
+    };
+    let path = file.path().to_path_buf();
+    let rt = get_runtime();
+    rt.spawn(async move { client.export_file(&calendar_href, &path, format).await })
+        .await
+        .map_err(join_error)?
+}
+
+/// Asks where to save and writes `tasks` (what the list shows), for report formats.
+/// `Ok(0)` when the dialog is cancelled.
+pub async fn async_export_list_wrapper(
+    tasks: Vec<TodoTask>,
+    calendars: Vec<CalendarListEntry>,
+    name: String,
+    format: FileFormat,
+) -> Result<usize, ClientError> {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .add_filter(format.label(), &[format.extension()])
+        .set_file_name(format!("{}.{}", name, format.extension()))
+        .save_file()
+        .await
+    else {
+        return Ok(0);
+    };
+    let path = file.path().to_path_buf();
+    std::fs::write(&path, format.write(&tasks, &name, &calendars))
+        .map_err(|e| ClientError::Storage(format!("{}: {}", path.display(), e)))?;
+    Ok(tasks.len())
+}
+
+/// Asks for a file and attaches it. Returns the task unchanged when cancelled.
+pub async fn async_attach_wrapper(
+    manager: AccountManager,
+    mut task: TodoTask,
+) -> Result<TodoTask, ClientError> {
+    let client = manager.client_of(&task).clone();
+    let Some(file) = rfd::AsyncFileDialog::new().pick_file().await else {
+        return Ok(task);
+    };
+    let path = file.path().to_path_buf();
+    let rt = get_runtime();
+    rt.spawn(async move { client.add_attachment(&mut task, &path).await.map(|_| task) })
+        .await
+        .map_err(join_error)?
+}
+
+/// Opens an attachment with the default application. Files stored on the server
+/// (or inline) are downloaded to a temporary folder first.
+pub async fn async_open_attachment_wrapper(
+    manager: AccountManager,
+    attachment: Attachment,
+) -> Result<(), ClientError> {
+    // The account whose server stores the file
+    let server = manager
+        .clients
+        .iter()
+        .find(|c| c.is_server_attachment(&attachment))
+        .cloned();
+    let client = match server {
+        Some(c) => c,
+        None if attachment.inline => manager.primary().clone(),
+        None => {
+            return open::that(&attachment.value).map_err(|e| ClientError::Other(e.to_string()));
+        }
+    };
+    // No path separators from the server in the file name
+    let name = attachment.display_name().replace(['/', '\\'], "_");
+    let rt = get_runtime();
+    let data = rt
+        .spawn(async move { client.fetch_attachment(&attachment).await })
+        .await
+        .map_err(join_error)??;
+
+    let dir = std::env::temp_dir().join("cfait");
+    let path = dir.join(name);
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&path, data))
+        .map_err(|e| ClientError::Storage(format!("{}: {}", path.display(), e)))?;
+    open::that(&path).map_err(|e| ClientError::Other(e.to_string()))
+}
+
 pub async fn async_migrate_wrapper(
-    client: RustyClient,
+    manager: AccountManager,
     tasks: Vec<TodoTask>,
     target: String,
-) -> Result<usize, String> {
+) -> Result<usize, ClientError> {
+    let client = manager.client_of_calendar(&target).clone();
     let rt = get_runtime();
     rt.spawn(async move { client.migrate_tasks(tasks, &target).await })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(join_error)?
 }
@@ -18,9 +18,11 @@ pub const TAG: char = '\u{f02b}'; // 
 pub const SETTINGS: char = '\u{f013}'; // 
 pub const REFRESH: char = '\u{f0450}'; // nf-md-refresh
 pub const UNSYNCED: char = '\u{f0c2}'; //  (Cloud)
+pub const CLOUD_OFF: char = '\u{f0164}'; // nf-md-cloud_off_outline
 pub const PLUS: char = '\u{f0603}'; // nf-md-priority_high
 pub const MINUS: char = '\u{f0604}'; // nf-md-priority_low
 pub const TRASH: char = '\u{f1f8}'; // 
+pub const CHART: char = '\u{f080}'; // nf-fa-bar_chart
 pub const CHECK: char = '\u{f00c}'; // 
 pub const CROSS: char = '\u{f00d}'; // 
 pub const EDIT: char = '\u{f040}'; // 
@@ -28,6 +30,12 @@ pub const PLAY: char = '\u{eb2c}'; // nf-cod-play
 pub const PLAY_FA: char = '\u{f04b}'; // nf-fa-play
 pub const PAUSE: char = '\u{f04c}'; // 
 pub const STOP: char = '\u{f04d}'; // 
+pub const TIMER: char = '\u{f017}'; // nf-fa-clock_o
+pub const TODAY: char = '\u{f274}'; // nf-fa-calendar_check_o
+pub const UPCOMING: char = '\u{f133}'; // nf-fa-calendar_o
+pub const OVERDUE: char = '\u{f071}'; // nf-fa-warning
+pub const FLAG: char = '\u{f024}'; // nf-fa-flag
+pub const FLAG_OUTLINE: char = '\u{f11d}'; // nf-fa-flag_o
 pub const LOCK: char = '\u{f023}'; // 
 pub const LINK: char = '\u{f0c1}'; // 
 pub const UNLINK: char = '\u{f127}'; // 
@@ -35,15 +43,21 @@ pub const SHIELD: char = '\u{f32a}'; // 
 pub const CHILD_ARROW: char = '\u{f149}'; // 
 pub const INFO: char = '\u{f129}'; // 
 pub const REPEAT: char = '\u{f0b6}'; // 
+pub const ARROW_UP: char = '\u{f062}'; // nf-fa-arrow_up
+pub const ARROW_DOWN: char = '\u{f063}'; // nf-fa-arrow_down
 pub const ARROW_RIGHT: char = '\u{f061}'; // 
 pub const CHECK_SQUARE: char = '\u{f14a}'; // 
 pub const SQUARE: char = '\u{f096}'; // 
 pub const EXPORT: char = '\u{f56e}'; // 
+pub const IMPORT: char = '\u{f56f}'; // nf-fa-file_import
+pub const SAVE: char = '\u{f0c7}'; // nf-fa-save
+pub const UNDO: char = '\u{f0e2}'; // nf-fa-undo
 pub const BLOCKED: char = '\u{f479}'; // nf-oct-blocked
 pub const CHILD: char = '\u{f0a89}'; // nf-md-account_child
 pub const CREATE_CHILD: char = '\u{f0014}'; // nf-md-account_plus
 pub const CLEAR_ALL: char = '\u{eabf}'; // nf-cod-clear_all
 pub const ELEVATOR_UP: char = '\u{f12c1}'; // nf-md-elevator_up
+pub const PAPERCLIP: char = '\u{f0c6}'; // nf-fa-paperclip
 
 // New Icons
 pub const SETTINGS_GEAR: char = '\u{e690}'; // nf-seti-settings
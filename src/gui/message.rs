@@ -1,19 +1,23 @@
 // File: src/gui/message.rs
-use crate::client::RustyClient;
+use crate::client::quota::Quota;
+use crate::client::{AccountManager, ClientError};
 use crate::config::Config;
 use crate::gui::state::{ResizeDirection, SidebarMode};
-use crate::model::{CalendarListEntry, Task as TodoTask};
+use crate::journal::{Action, Resolution};
+use crate::model::{CalendarListEntry, FileFormat, Freq, GroupBy, SortOrder, Task as TodoTask};
+use crate::store::SmartList;
 use iced::widget::text_editor;
+use std::collections::BTreeMap;
 
 pub type LoadedResult = Result<
     (
-        RustyClient,
+        AccountManager,
         Vec<CalendarListEntry>,
         Vec<TodoTask>,
         Option<String>,
         Option<String>,
     ),
-    String,
+    ClientError,
 >;
 
 #[derive(Debug, Clone)]
@@ -25,11 +29,41 @@ pub enum Message {
     ToggleCalendarVisibility(String, bool),
     ToggleCalendarDisabled(String, bool),
     ObDefaultCalChanged(String),
+    // Log in and list the calendars, to pick the default one before saving
+    ObTestConnection,
+    ObTested(Result<Vec<CalendarListEntry>, ClientError>),
     ObSubmit,
     OpenSettings,
+    QuotaLoaded(Result<Option<Quota>, ClientError>),
+    // Drop the task caches (not the pending changes): the next sync downloads everything
+    ClearCache,
+    LocalCalendarInput(String),
+    AddLocalCalendar,
+    // Its tasks go to the trash
+    DeleteLocalCalendar(String),
     CancelSettings,
     OpenHelp,
     CloseHelp,
+    OpenTrash,
+    CloseTrash,
+    OpenStats,
+    CloseStats,
+    OpenPending,
+    ClosePending,
+    // Index in the pending list: drop it, or move it up (true) / down
+    DropPending(usize),
+    MovePending(usize, bool),
+    // Index in the failed list: queue it again, or give up on it
+    RetryFailed(usize),
+    DropFailed(usize),
+    Undelete(String),
+    // Revert the last change that hasn't been synced yet
+    Undo,
+    Undone(Result<Option<Action>, ClientError>),
+    // Settle the first conflict, or put it off
+    ResolveConflict(Resolution),
+    ConflictResolved(Result<(Vec<TodoTask>, Vec<String>), ClientError>),
+    CloseConflict,
     InputChanged(String),
 
     DescriptionChanged(text_editor::Action),
@@ -41,22 +75,33 @@ pub enum Message {
     EditTaskStart(usize),
     CancelEdit,
     ChangePriority(usize, i8),
+    // Task UID, index of the item in its checklist
+    ToggleChecklistItem(String, usize),
+    // Start/stop the time tracking session of a task
+    ToggleTracking(String),
+    // Next status of the configured cycle
+    CycleStatus(String),
+    ToggleFlag(String),
+    // Manual order: one step up (true) or down
+    MoveTaskInOrder(String, bool),
+    MoveSelectedInOrder(bool),
     SetTaskStatus(usize, crate::model::TaskStatus),
     SetMinDuration(Option<u32>),
     SetMaxDuration(Option<u32>),
     ToggleIncludeUnsetDuration(bool),
     ToggleDetails(String),
+    ToggleHistory(String),
     ConfigLoaded(Result<Config, String>),
     ObSortMonthsChanged(String),
 
     Loaded(LoadedResult),
     Refresh,
 
-    SyncSaved(Result<TodoTask, String>),
-    SyncToggleComplete(Box<Result<(TodoTask, Option<TodoTask>), String>>),
+    SyncSaved(Result<TodoTask, ClientError>),
+    SyncToggleComplete(Box<Result<(TodoTask, Option<TodoTask>), ClientError>>),
 
-    TasksRefreshed(Result<(String, Vec<TodoTask>), String>),
-    DeleteComplete(#[allow(dead_code)] Result<(), String>),
+    TasksRefreshed(Result<(String, Vec<TodoTask>), ClientError>),
+    DeleteComplete(#[allow(dead_code)] Result<(), ClientError>),
 
     SidebarModeChanged(SidebarMode),
     SelectCalendar(String),
@@ -64,10 +109,16 @@ pub enum Message {
     CategoryToggled(String),
     ClearAllTags,
     CategoryMatchModeChanged(bool),
-    RefreshedAll(Result<Vec<(String, Vec<TodoTask>)>, String>),
+    RefreshedAll(Result<Vec<(String, Vec<TodoTask>)>, ClientError>),
 
     ToggleHideCompleted(bool),
     ToggleHideFullyCompletedTags(bool),
+    SortOrderChanged(SortOrder),
+    GroupByChanged(GroupBy),
+    // "@@home", or "Any context" to drop the filter
+    ContextFilterChanged(String),
+    // Switch to a smart list (Today, Flagged, ...), None to leave it
+    ShowSmartList(Option<SmartList>),
 
     YankTask(String),
     ClearYank,
@@ -84,12 +135,13 @@ pub enum Message {
     MoveTask(String, String),
 
     JumpToTag(String),
+    JumpToTask(String),
 
-    TaskMoved(Result<TodoTask, String>),
+    TaskMoved(Result<TodoTask, ClientError>),
     ObSubmitOffline,
     MigrateLocalTo(String),
 
-    MigrationComplete(Result<usize, String>),
+    MigrationComplete(Result<usize, ClientError>),
     FontLoaded(Result<(), String>),
     DismissError,
     ToggleAllCalendars(bool),
@@ -104,4 +156,37 @@ pub enum Message {
 
     // Resize
     ResizeStart(ResizeDirection),
+
+    // Reminders (VALARM)
+    CheckReminders,
+
+    // WebDAV-Push: topic -> calendar href, then change notifications
+    PushSubscribed(Result<(String, BTreeMap<String, String>), ClientError>),
+    PushChange(Option<String>),
+
+    // Offline mode + manual journal flush
+    SyncNow,
+    SyncNowDone(Result<Vec<String>, ClientError>),
+    ToggleOffline,
+
+    // Import an .ics file into the active calendar
+    ImportIcs,
+    ImportComplete(Result<usize, ClientError>),
+    // Save the active calendar to a file (the Export menu)
+    Export(FileFormat),
+    ExportComplete(Result<usize, ClientError>),
+
+    // Attachments (ATTACH) of a task: pick a file to attach, open one by index
+    AttachFile(String),
+    OpenAttachment(String, usize),
+    AttachmentOpened(Result<(), ClientError>),
+
+    // Recurrence builder of the task being edited
+    ToggleRecurrenceEditor,
+    RecurrenceFreqChanged(Option<Freq>),
+    RecurrenceIntervalChanged(String),
+    RecurrenceDayToggled(chrono::Weekday),
+    RecurrenceUntilChanged(String),
+    RecurrenceCountChanged(String),
+    RecurrenceAfterCompletionToggled(bool),
 }
@@ -6,7 +6,7 @@ pub mod subscription;
 pub mod update;
 pub mod view;
 
-use crate::config::Config;
+use crate::config::{Config, ThemeBase};
 use crate::gui::message::Message;
 use crate::gui::state::GuiApp;
 use iced::{Element, Subscription, Task, Theme, font, window};
@@ -57,7 +57,10 @@ impl GuiApp {
     }
 
     fn theme(&self) -> Theme {
-        Theme::Dark
+        match self.theme.base {
+            ThemeBase::Dark => Theme::Dark,
+            ThemeBase::Light => Theme::Light,
+        }
     }
 
     fn subscription(&self) -> Subscription<Message> {
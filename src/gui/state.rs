@@ -1,9 +1,20 @@
 // File: src/gui/state.rs
-use crate::client::RustyClient;
-use crate::model::{CalendarListEntry, Task as TodoTask};
-use crate::store::TaskStore;
+use crate::cache::CacheStats;
+use crate::client::AccountManager;
+use crate::client::quota::Quota;
+use crate::config::{CalendarPrefs, ThemeConfig};
+use crate::history::HistoryEntry;
+use crate::journal::{Action, Conflict, FailedAction};
+use crate::model::recurrence::Until;
+use crate::model::status::status_cycle;
+use crate::model::{
+    CalendarListEntry, CustomStatus, Freq, GroupBy, Recurrence, SortOrder, Task as TodoTask,
+};
+use crate::storage::TrashEntry;
+use crate::store::{EventQueue, SmartList, Stats, TaskStore};
+use chrono::{DateTime, NaiveDate, Utc};
 use iced::widget::text_editor;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Default, PartialEq, Clone, Copy, Debug)]
 pub enum AppState {
@@ -13,6 +24,10 @@ pub enum AppState {
     Active,
     Settings,
     Help,
+    Trash,
+    Stats,
+    Pending,
+    Conflict,
 }
 
 #[derive(Default, PartialEq, Clone, Copy, Debug)]
@@ -34,13 +49,99 @@ pub enum ResizeDirection {
     SouthWest,
 }
 
+/// Recurrence being edited. The numbers stay text until the task is saved.
+#[derive(Debug, Clone, Default)]
+pub struct RecurrenceDraft {
+    // None: the task doesn't repeat
+    pub rule: Option<Recurrence>,
+    pub interval: String,
+    pub until: String,
+    pub count: String,
+    // Next occurrence counted from the completion instead of the fixed dates
+    pub after_completion: bool,
+}
+
+impl RecurrenceDraft {
+    pub fn from_task(task: &TodoTask) -> Self {
+        let rule = task.recurrence();
+        Self {
+            interval: rule
+                .as_ref()
+                .map(|r| r.interval.to_string())
+                .unwrap_or_else(|| "1".to_string()),
+            until: rule
+                .as_ref()
+                .and_then(|r| r.until)
+                .map(|u| u.date().format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            count: rule
+                .as_ref()
+                .and_then(|r| r.count)
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+            after_completion: task.repeat_after_completion,
+            rule,
+        }
+    }
+
+    pub fn set_freq(&mut self, freq: Option<Freq>) {
+        self.rule = freq.map(|f| match self.rule.take() {
+            Some(r) => Recurrence { freq: f, ..r },
+            None => Recurrence::new(f),
+        });
+    }
+
+    pub fn build(&self) -> Result<Option<Recurrence>, String> {
+        let Some(mut rule) = self.rule.clone() else {
+            return Ok(None);
+        };
+        rule.interval = match self.interval.trim() {
+            "" => 1,
+            s => s
+                .parse()
+                .ok()
+                .filter(|i| *i > 0)
+                .ok_or_else(|| format!("Invalid interval '{}'", s))?,
+        };
+        rule.until = match self.until.trim() {
+            "" => None,
+            s => Some(Until::Date(
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .map_err(|_| format!("Invalid end date '{}' (use YYYY-MM-DD)", s))?,
+            )),
+        };
+        rule.count = match self.count.trim() {
+            "" => None,
+            s => Some(
+                s.parse()
+                    .ok()
+                    .filter(|c| *c > 0)
+                    .ok_or_else(|| format!("Invalid count '{}'", s))?,
+            ),
+        };
+        if rule.until.is_some() && rule.count.is_some() {
+            return Err("Set either an end date or a count, not both".to_string());
+        }
+        Ok(Some(rule))
+    }
+}
+
 pub struct GuiApp {
     pub state: AppState,
     pub store: TaskStore,
+    // Changes to the store since the last refresh
+    pub store_events: EventQueue,
+    // Filter options and grouping `tasks` was built with
+    pub view_fingerprint: Option<(u64, GroupBy)>,
     pub tasks: Vec<TodoTask>,
     pub calendars: Vec<CalendarListEntry>,
-    pub client: Option<RustyClient>,
+    pub manager: Option<AccountManager>,
     pub tag_aliases: HashMap<String, Vec<String>>,
+    // `[calendars."..."]` of the config, by href or name
+    pub calendar_prefs: HashMap<String, CalendarPrefs>,
+    pub theme: ThemeConfig,
+    // Non-fatal problems `Config::load` found (unknown keys, ignored settings)
+    pub config_warnings: Vec<String>,
 
     // UI State
     pub sidebar_mode: SidebarMode,
@@ -58,6 +159,17 @@ pub struct GuiApp {
     pub hide_completed: bool,
     pub hide_fully_completed_tags: bool,
     pub sort_cutoff_months: Option<u32>,
+    pub sort_order: SortOrder,
+    pub group_by: GroupBy,
+    // Where each section of `tasks` starts, and its title
+    pub group_headers: Vec<(usize, String)>,
+    pub selected_context: Option<String>,
+    // Showing a smart list (Today, Flagged, ...) instead of the calendars
+    pub smart_list: Option<SmartList>,
+    pub named_priorities: bool,
+    pub custom_statuses: Vec<CustomStatus>,
+    // What the status chip cycles through
+    pub status_cycle: Vec<CustomStatus>,
 
     // Filter State
     pub filter_min_duration: Option<u32>,
@@ -70,16 +182,25 @@ pub struct GuiApp {
     pub search_value: String,
     pub editing_uid: Option<String>,
     pub creating_child_of: Option<String>,
+    // Recurrence builder, open below the edit box
+    pub recurrence_draft: Option<RecurrenceDraft>,
     pub expanded_tasks: HashSet<String>,
     pub unsynced_changes: bool,
+    // Set when the journal is over its limits (entries, days)
+    pub journal_warning: Option<String>,
+    pub journal_limits: (usize, u32),
 
     // Inputs - Settings (Aliases)
     pub alias_input_key: String,
     pub alias_input_values: String,
+    // Name of a new local calendar
+    pub local_cal_input: String,
 
     // System
     pub loading: bool,
     pub error_msg: Option<String>,
+    // Input that got a warning: submitting it again unchanged saves it anyway
+    pub warned_input: Option<String>,
 
     // Onboarding / Config
     pub ob_url: String,
@@ -94,17 +215,50 @@ pub struct GuiApp {
     // Window Resizing State
     pub resize_direction: Option<ResizeDirection>,
     pub current_window_size: iced::Size,
+
+    // Alarms firing after this instant haven't been shown yet
+    pub last_reminder_check: DateTime<Utc>,
+
+    // Active WebDAV-Push subscription (endpoint, topic -> calendar href)
+    pub push: Option<(String, BTreeMap<String, String>)>,
+    // Offline mode: changes stay in the journal until SyncNow
+    pub offline: bool,
+    // Deleted tasks, loaded when the trash view opens
+    pub trash: Vec<TrashEntry>,
+    // Computed when the statistics view opens
+    pub stats: Stats,
+    // Journal entries, loaded when the pending changes view opens
+    pub pending: Vec<Action>,
+    // Journal entries given up on, loaded with `pending`
+    pub failed: Vec<FailedAction>,
+    // Left by the interactive conflict policy, the first one is shown
+    pub conflicts: Vec<Conflict>,
+    // Change history shown in the details of one task (UID, newest first)
+    pub history: Option<(String, Vec<HistoryEntry>)>,
+    // Server storage usage, fetched when the settings open
+    pub quota: Option<Quota>,
+    // Size of the task caches, read when the settings open
+    pub cache_stats: Option<CacheStats>,
 }
 
 impl Default for GuiApp {
     fn default() -> Self {
+        let store_events = EventQueue::default();
+        let mut store = TaskStore::new();
+        store.subscribe(store_events.listener());
+
         Self {
             state: AppState::Loading,
-            store: TaskStore::new(),
+            store,
+            store_events,
+            view_fingerprint: None,
             tasks: vec![],
             calendars: vec![],
-            client: None,
+            manager: None,
             tag_aliases: HashMap::new(),
+            calendar_prefs: HashMap::new(),
+            theme: ThemeConfig::default(),
+            config_warnings: Vec::new(),
 
             sidebar_mode: SidebarMode::Calendars,
             active_cal_href: None,
@@ -118,6 +272,14 @@ impl Default for GuiApp {
             hide_completed: false,
             hide_fully_completed_tags: true,
             sort_cutoff_months: Some(6),
+            sort_order: SortOrder::Smart,
+            group_by: GroupBy::None,
+            group_headers: Vec::new(),
+            selected_context: None,
+            smart_list: None,
+            named_priorities: false,
+            custom_statuses: Vec::new(),
+            status_cycle: status_cycle(&[], &[]),
             ob_sort_months_input: "6".to_string(),
 
             filter_min_duration: None,
@@ -129,14 +291,19 @@ impl Default for GuiApp {
             search_value: String::new(),
             editing_uid: None,
             creating_child_of: None,
+            recurrence_draft: None,
             expanded_tasks: HashSet::new(),
             unsynced_changes: false,
+            journal_warning: None,
+            journal_limits: (100, 7),
 
             alias_input_key: String::new(),
             alias_input_values: String::new(),
+            local_cal_input: String::new(),
 
             loading: true,
             error_msg: None,
+            warned_input: None,
             ob_url: String::new(),
             ob_user: String::new(),
             ob_pass: String::new(),
@@ -147,6 +314,18 @@ impl Default for GuiApp {
 
             resize_direction: None,
             current_window_size: iced::Size::new(800.0, 600.0),
+
+            last_reminder_check: Utc::now(),
+            push: None,
+            offline: false,
+            trash: Vec::new(),
+            stats: Stats::default(),
+            pending: Vec::new(),
+            failed: Vec::new(),
+            conflicts: Vec::new(),
+            history: None,
+            quota: None,
+            cache_stats: None,
         }
     }
 }
@@ -1,7 +1,13 @@
 // File: src/gui/subscription.rs
+use crate::client::core::ConnectionOptions;
+use crate::client::push;
+use crate::config::Config;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp};
+use futures::stream::{self, Stream, StreamExt};
 use iced::{Subscription, event, keyboard, window};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 pub fn subscription(app: &GuiApp) -> Subscription<Message> {
     use iced::keyboard::key;
@@ -19,11 +25,49 @@ pub fn subscription(app: &GuiApp) -> Subscription<Message> {
         }));
     }
 
+    // Alt+Up/Down moves the selected task in the manual order
+    if app.state == AppState::Active {
+        subs.push(keyboard::listen().filter_map(|event| {
+            if let keyboard::Event::KeyPressed { key, modifiers, .. } = event
+                && modifiers.alt()
+            {
+                return match key {
+                    key::Key::Named(key::Named::ArrowUp) => {
+                        Some(Message::MoveSelectedInOrder(true))
+                    }
+                    key::Key::Named(key::Named::ArrowDown) => {
+                        Some(Message::MoveSelectedInOrder(false))
+                    }
+                    _ => None,
+                };
+            }
+            None
+        }));
+    }
+
     // Track window metrics (Size)
     subs.push(event::listen_with(|evt, _status, _window_id| match evt {
         iced::Event::Window(window::Event::Resized(size)) => Some(Message::WindowResized(size)),
         _ => None,
     }));
 
+    // Poll for VALARMs that became due
+    subs.push(iced::time::every(Duration::from_secs(30)).map(|_| Message::CheckReminders));
+
+    // Server-side changes (WebDAV-Push); restarted whenever the topics change
+    if let Some(push) = &app.push {
+        subs.push(Subscription::run_with(push.clone(), push_stream));
+    }
+
     Subscription::batch(subs)
 }
+
+fn push_stream(
+    (endpoint, topics): &(String, BTreeMap<String, String>),
+) -> impl Stream<Item = Message> + use<> {
+    let opts = ConnectionOptions::from_config(&Config::load().unwrap_or_default());
+    let topics = topics.clone().into_iter().collect();
+    stream::iter(push::listen(endpoint, &opts, topics).ok())
+        .flatten()
+        .map(|event| Message::PushChange(event.calendar_href))
+}
@@ -1,9 +1,11 @@
 // File: src/gui/update/common.rs
-use crate::config::Config;
+use crate::client::ClientError;
+use crate::config::{CalendarPrefs, Config};
 use crate::gui::async_ops::*;
 use crate::gui::message::Message;
-use crate::gui::state::GuiApp;
-use crate::store::FilterOptions;
+use crate::gui::state::{AppState, GuiApp};
+use crate::journal::Journal;
+use crate::store::{FilterOptions, flatten_groups, group_tasks, patch_view};
 use chrono::{Duration, Utc};
 use iced::Task;
 
@@ -18,18 +20,84 @@ pub fn refresh_filtered_tasks(app: &mut GuiApp) {
         None
     };
 
-    app.tasks = app.store.filter(FilterOptions {
+    let hide_completed_in =
+        CalendarPrefs::hide_completed_by_href(&app.calendar_prefs, &app.calendars);
+    let sort_order = app
+        .active_cal_href
+        .as_deref()
+        .and_then(|href| CalendarPrefs::find(&app.calendar_prefs, &app.calendars, href))
+        .and_then(|p| p.sort_order)
+        .unwrap_or(app.sort_order);
+
+    let options = FilterOptions {
         active_cal_href: cal_filter,
         hidden_calendars: &app.hidden_calendars,
         selected_categories: &app.selected_categories,
         match_all_categories: app.match_all_categories,
         search_term: &app.search_value,
+        calendars: &app.calendars,
         hide_completed_global: app.hide_completed,
+        hide_completed_in: &hide_completed_in,
         cutoff_date,
         min_duration: app.filter_min_duration,
         max_duration: app.filter_max_duration,
         include_unset_duration: app.filter_include_unset_duration,
-    });
+        sort_order,
+        selected_context: app.selected_context.as_deref(),
+        smart_list: app.smart_list,
+    };
+
+    // Edits that can't move a task only patch its row
+    let fingerprint = (options.fingerprint(), app.group_by);
+    let events = app.store_events.drain();
+    if app.view_fingerprint == Some(fingerprint)
+        && !events.is_empty()
+        && patch_view(&mut app.tasks, &events)
+    {
+        return;
+    }
+    app.view_fingerprint = Some(fingerprint);
+    let tasks = app.store.filter(options);
+    (app.tasks, app.group_headers) =
+        flatten_groups(group_tasks(tasks, app.group_by, &app.calendars));
+}
+
+/// Reads the journal for the unsynced badge and the health warning.
+pub fn refresh_journal_status(app: &mut GuiApp) {
+    let journal = match Journal::load() {
+        Ok(j) => j,
+        // Not "nothing pending": there may be changes in there
+        Err(e) => {
+            app.unsynced_changes = true;
+            app.journal_warning = Some(format!("Can't read the offline queue: {}", e));
+            return;
+        }
+    };
+    let (max_entries, max_days) = app.journal_limits;
+    app.unsynced_changes = !journal.is_empty() || !journal.failed.is_empty();
+    app.journal_warning = journal.health_warning(max_entries, max_days, Utc::now());
+    // Ask about conflicts left by the last sync (not again about those put off)
+    if !journal.conflicts.is_empty()
+        && journal.conflicts != app.conflicts
+        && app.state == AppState::Active
+    {
+        app.state = AppState::Conflict;
+    }
+    app.conflicts = journal.conflicts;
+}
+
+/// What to tell the user when logging in to the server fails.
+pub fn login_error(e: &ClientError, insecure: bool) -> String {
+    match e {
+        ClientError::Unauthorized(_) => {
+            "Login failed: please check your username and password.".to_string()
+        }
+        ClientError::Tls(_) if !insecure => format!(
+            "Connection failed: {}\nFor a self-hosted server, try allowing insecure certificates.",
+            e
+        ),
+        _ => format!("Connection Failed: {}", e),
+    }
 }
 
 pub fn save_config(app: &GuiApp) {
@@ -45,6 +113,10 @@ pub fn save_config(app: &GuiApp) {
         disabled_calendars: app.disabled_calendars.iter().cloned().collect(),
         tag_aliases: app.tag_aliases.clone(),
         sort_cutoff_months: app.sort_cutoff_months,
+        sort_order: app.sort_order,
+        group_by: app.group_by,
+        // Keep settings the GUI doesn't edit (auth mode, tokens, ...)
+        ..Config::load().unwrap_or_default()
     }
     .save();
 }
@@ -68,11 +140,11 @@ pub fn apply_alias_retroactively(
     // Sync Phase
     // This dispatches individual updates which go through the Journal.
     // The Journal allows appending actions safely even if other instances are writing.
-    if let Some(client) = &app.client {
+    if let Some(manager) = &app.manager {
         let mut commands = Vec::new();
         for t in modified_tasks {
             commands.push(Task::perform(
-                async_update_wrapper(client.clone(), t),
+                async_update_wrapper(manager.clone(), t),
                 Message::SyncSaved,
             ));
         }
@@ -21,7 +21,14 @@ pub fn update(app: &mut GuiApp, message: Message) -> Task<Message> {
         | Message::ObDefaultCalChanged(_)
         | Message::ObInsecureToggled(_)
         | Message::ObSubmit
+        | Message::ObTestConnection
+        | Message::ObTested(_)
         | Message::OpenSettings
+        | Message::QuotaLoaded(_)
+        | Message::ClearCache
+        | Message::LocalCalendarInput(_)
+        | Message::AddLocalCalendar
+        | Message::DeleteLocalCalendar(_)
         | Message::CancelSettings
         | Message::ObSubmitOffline
         | Message::AliasKeyInput(_)
@@ -39,6 +46,12 @@ pub fn update(app: &mut GuiApp, message: Message) -> Task<Message> {
         | Message::CancelEdit
         | Message::DeleteTask(_)
         | Message::ChangePriority(_, _)
+        | Message::ToggleChecklistItem(_, _)
+        | Message::ToggleTracking(_)
+        | Message::CycleStatus(_)
+        | Message::ToggleFlag(_)
+        | Message::MoveTaskInOrder(_, _)
+        | Message::MoveSelectedInOrder(_)
         | Message::SetTaskStatus(_, _)
         | Message::YankTask(_)
         | Message::ClearYank
@@ -47,7 +60,21 @@ pub fn update(app: &mut GuiApp, message: Message) -> Task<Message> {
         | Message::RemoveDependency(_, _)
         | Message::AddDependency(_)
         | Message::MoveTask(_, _)
-        | Message::MigrateLocalTo(_) => tasks::handle(app, message),
+        | Message::MigrateLocalTo(_)
+        | Message::ImportIcs
+        | Message::Export(_)
+        | Message::AttachFile(_)
+        | Message::OpenAttachment(_, _)
+        | Message::ToggleRecurrenceEditor
+        | Message::RecurrenceFreqChanged(_)
+        | Message::RecurrenceIntervalChanged(_)
+        | Message::RecurrenceDayToggled(_)
+        | Message::RecurrenceUntilChanged(_)
+        | Message::RecurrenceCountChanged(_)
+        | Message::RecurrenceAfterCompletionToggled(_)
+        | Message::Undelete(_)
+        | Message::Undo
+        | Message::ResolveConflict(_) => tasks::handle(app, message),
 
         Message::TabPressed(_)
         | Message::DismissError
@@ -60,6 +87,10 @@ pub fn update(app: &mut GuiApp, message: Message) -> Task<Message> {
         | Message::CategoryMatchModeChanged(_)
         | Message::ToggleHideCompleted(_)
         | Message::ToggleHideFullyCompletedTags(_)
+        | Message::SortOrderChanged(_)
+        | Message::GroupByChanged(_)
+        | Message::ContextFilterChanged(_)
+        | Message::ShowSmartList(_)
         | Message::SelectCalendar(_)
         | Message::ToggleCalendarDisabled(_, _)
         | Message::SearchChanged(_)
@@ -67,22 +98,46 @@ pub fn update(app: &mut GuiApp, message: Message) -> Task<Message> {
         | Message::SetMaxDuration(_)
         | Message::ToggleIncludeUnsetDuration(_)
         | Message::ToggleDetails(_)
+        | Message::ToggleHistory(_)
         | Message::OpenHelp
         | Message::CloseHelp
+        | Message::OpenTrash
+        | Message::CloseTrash
+        | Message::OpenStats
+        | Message::CloseStats
+        | Message::OpenPending
+        | Message::ClosePending
+        | Message::DropPending(_)
+        | Message::MovePending(_, _)
+        | Message::RetryFailed(_)
+        | Message::DropFailed(_)
+        | Message::CloseConflict
         | Message::WindowDragged
         | Message::MinimizeWindow
         | Message::CloseWindow
         | Message::ResizeStart(_)
         | Message::WindowResized(_)
-        | Message::JumpToTag(_) => view::handle(app, message),
+        | Message::JumpToTag(_)
+        | Message::JumpToTask(_)
+        | Message::CheckReminders => view::handle(app, message),
 
         Message::Refresh
         | Message::Loaded(_)
         | Message::RefreshedAll(_)
         | Message::TasksRefreshed(_)
         | Message::SyncSaved(_)
+        | Message::Undone(_)
+        | Message::ConflictResolved(_)
         | Message::SyncToggleComplete(_)
         | Message::TaskMoved(_)
-        | Message::MigrationComplete(_) => network::handle(app, message),
+        | Message::MigrationComplete(_)
+        | Message::PushSubscribed(_)
+        | Message::PushChange(_)
+        | Message::SyncNow
+        | Message::SyncNowDone(_)
+        | Message::ToggleOffline
+        | Message::ImportComplete(_)
+        | Message::ExportComplete(_)
+        | Message::AttachmentOpened(_) => network::handle(app, message),
     }
 }
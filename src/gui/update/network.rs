@@ -1,63 +1,74 @@
 // File: src/gui/update/network.rs
 use crate::cache::Cache;
-use crate::config::Config;
+use crate::client::ClientError;
+use crate::config::{CalendarPrefs, Config};
 use crate::gui::async_ops::*;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp};
-use crate::gui::update::common::{refresh_filtered_tasks, save_config};
-use crate::journal::Journal;
-use crate::model::CalendarListEntry;
-use crate::storage::{LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME};
+use crate::gui::update::common::{
+    login_error, refresh_filtered_tasks, refresh_journal_status, save_config,
+};
+use crate::storage::{LOCAL_CALENDAR_HREF, LocalStorage, is_local_calendar};
 use iced::Task;
 
+// Bad credentials send the user back to the settings screen, network failures
+// only mean that changes wait in the journal. Anything else is shown as is.
+fn report_error(app: &mut GuiApp, context: &str, e: &ClientError) {
+    app.loading = false;
+    refresh_journal_status(app);
+    match e {
+        ClientError::Unauthorized(_) => {
+            app.state = AppState::Settings;
+            app.error_msg =
+                Some("Authentication failed: please check your username and password.".to_string());
+        }
+        e if e.is_connectivity() && app.unsynced_changes => {
+            app.error_msg = Some("Offline: Changes queued.".to_string());
+        }
+        e => app.error_msg = Some(format!("{}: {}", context, e)),
+    }
+}
+
 pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
     match message {
         Message::Refresh => {
             app.loading = true;
             app.error_msg = None;
 
-            if app.client.is_some()
-                && let Ok(cfg) = Config::load()
+            if app.manager.is_some()
+                && let Ok(mut cfg) = Config::load()
             {
+                // The runtime toggle wins over the config file
+                cfg.offline = app.offline;
                 return Task::perform(connect_and_fetch_wrapper(cfg), Message::Loaded);
             }
             Task::none()
         }
-        Message::Loaded(Ok((client, mut cals, tasks, mut active, warning))) => {
-            app.client = Some(client.clone());
+        Message::Loaded(Ok((manager, mut cals, tasks, mut active, warning))) => {
+            app.manager = Some(manager.clone());
 
-            if let Some(w) = warning {
-                app.error_msg = Some(w);
-            } else {
-                app.error_msg = None;
-            }
+            app.error_msg = warning.or_else(|| {
+                (!app.config_warnings.is_empty())
+                    .then(|| format!("Config: {}", app.config_warnings.join("; ")))
+            });
 
-            app.unsynced_changes = !Journal::load().is_empty();
+            refresh_journal_status(app);
 
-            let local_entry = CalendarListEntry {
-                name: LOCAL_CALENDAR_NAME.to_string(),
-                href: LOCAL_CALENDAR_HREF.to_string(),
-                color: None,
-            };
-
-            if !cals.iter().any(|c| c.href == LOCAL_CALENDAR_HREF) {
-                cals.push(local_entry);
-            }
+            LocalStorage::add_calendars(&mut cals);
+            CalendarPrefs::apply_colors(&app.calendar_prefs, &mut cals);
 
             app.calendars = cals.clone();
             app.store.clear();
 
-            if let Ok(local_t) = crate::gui::async_ops::get_runtime()
-                .block_on(async { client.get_tasks(LOCAL_CALENDAR_HREF).await })
-            {
-                app.store.insert(LOCAL_CALENDAR_HREF.to_string(), local_t);
+            for (href, local_t) in LocalStorage::load_all() {
+                app.store.insert(href, local_t);
             }
 
             for cal in &app.calendars {
-                if cal.href == LOCAL_CALENDAR_HREF {
+                if is_local_calendar(&cal.href) {
                     continue;
                 }
-                if let Ok((cached_tasks, _)) = Cache::load(&cal.href) {
+                if let Ok((cached_tasks, _)) = Cache::load_for(cal) {
                     app.store.insert(cal.href.clone(), cached_tasks);
                 }
             }
@@ -85,7 +96,7 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.active_cal_href = active.clone();
 
             if let Some(href) = &active
-                && href != LOCAL_CALENDAR_HREF
+                && !is_local_calendar(href)
                 && app.error_msg.is_none()
             {
                 app.store.insert(href.clone(), tasks);
@@ -94,6 +105,8 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             if let Ok(cfg) = Config::load() {
                 app.hide_completed = cfg.hide_completed;
                 app.hide_fully_completed_tags = cfg.hide_fully_completed_tags;
+                app.sort_order = cfg.sort_order;
+                app.group_by = cfg.group_by;
                 app.tag_aliases = cfg.tag_aliases;
                 app.disabled_calendars = cfg.disabled_calendars.into_iter().collect();
             }
@@ -108,13 +121,86 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
 
             if app.error_msg.is_none() {
                 app.loading = true;
-                Task::perform(async_fetch_all_wrapper(client, cals), Message::RefreshedAll)
+                let fetch = Task::run(fetch_all_progressive(manager.clone(), cals.clone()), |m| m);
+                match Config::load().ok().and_then(|c| c.push_endpoint) {
+                    Some(endpoint) => Task::batch([
+                        fetch,
+                        Task::perform(
+                            async_subscribe_push_wrapper(manager, cals, endpoint),
+                            Message::PushSubscribed,
+                        ),
+                    ]),
+                    None => fetch,
+                }
             } else {
                 Task::none()
             }
         }
+        Message::PushSubscribed(Ok((endpoint, topics))) => {
+            app.push = (!topics.is_empty()).then_some((endpoint, topics));
+            Task::none()
+        }
+        Message::PushSubscribed(Err(e)) => {
+            app.error_msg = Some(format!("Push: {}", e));
+            Task::none()
+        }
+        Message::PushChange(href) => {
+            let Some(manager) = app.manager.clone() else {
+                return Task::none();
+            };
+            match href.filter(|h| app.calendars.iter().any(|c| c.href == *h)) {
+                Some(href) => {
+                    Task::perform(async_fetch_wrapper(manager, href), Message::TasksRefreshed)
+                }
+                None => Task::perform(
+                    async_fetch_all_wrapper(manager, app.calendars.clone()),
+                    Message::RefreshedAll,
+                ),
+            }
+        }
+        Message::SyncNow => {
+            let Some(manager) = app.manager.clone() else {
+                return Task::none();
+            };
+            app.loading = true;
+            Task::perform(async_sync_wrapper(manager), Message::SyncNowDone)
+        }
+        Message::SyncNowDone(Ok(warnings)) => {
+            refresh_journal_status(app);
+            app.error_msg = (!warnings.is_empty()).then(|| warnings.join("; "));
+            // Reload so the ETags/hrefs assigned by the server are picked up
+            match app.manager.clone() {
+                Some(manager) => Task::perform(
+                    async_fetch_all_wrapper(manager, app.calendars.clone()),
+                    Message::RefreshedAll,
+                ),
+                None => {
+                    app.loading = false;
+                    Task::none()
+                }
+            }
+        }
+        Message::SyncNowDone(Err(e)) => {
+            report_error(app, "Sync failed", &e);
+            Task::none()
+        }
+        Message::ToggleOffline => {
+            app.offline = !app.offline;
+            if let Some(manager) = &app.manager {
+                manager.set_offline(app.offline);
+            }
+            if app.offline {
+                app.error_msg =
+                    Some("Offline mode: changes are queued until you sync.".to_string());
+                Task::none()
+            } else {
+                // Back online: push what was queued right away
+                app.error_msg = None;
+                handle(app, Message::SyncNow)
+            }
+        }
         Message::Loaded(Err(e)) => {
-            app.error_msg = Some(format!("Connection Failed: {}", e));
+            app.error_msg = Some(login_error(&e, app.ob_insecure));
             app.state = AppState::Onboarding;
             app.loading = false;
             Task::none()
@@ -128,8 +214,7 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::RefreshedAll(Err(e)) => {
-            app.error_msg = Some(format!("Sync warning: {}", e));
-            app.loading = false;
+            report_error(app, "Sync warning", &e);
             Task::none()
         }
         Message::TasksRefreshed(Ok((href, tasks))) => {
@@ -143,15 +228,14 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::TasksRefreshed(Err(e)) => {
-            app.error_msg = Some(format!("Fetch: {}", e));
-            app.loading = false;
+            report_error(app, "Fetch", &e);
             Task::none()
         }
         Message::SyncSaved(Ok(updated)) => {
             // Fix: Use update_or_add_task to ensure index is updated
             app.store.update_or_add_task(updated);
 
-            app.unsynced_changes = !Journal::load().is_empty();
+            refresh_journal_status(app);
             if app.unsynced_changes {
                 app.error_msg = Some("Offline: Changes queued.".to_string());
             }
@@ -159,7 +243,35 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::SyncSaved(Err(e)) => {
-            app.error_msg = Some(format!("Sync Error: {}", e));
+            report_error(app, "Sync Error", &e);
+            Task::none()
+        }
+        Message::Undone(Ok(inverse)) => {
+            match inverse {
+                Some(action) => app.store.apply_action(&action),
+                None => app.error_msg = Some("Nothing to undo.".to_string()),
+            }
+            refresh_journal_status(app);
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
+        Message::Undone(Err(e)) => {
+            report_error(app, "Undo failed", &e);
+            Task::none()
+        }
+        Message::ConflictResolved(Ok((tasks, warnings))) => {
+            for task in tasks {
+                app.store.update_or_add_task(task);
+            }
+            if !warnings.is_empty() {
+                app.error_msg = Some(warnings.join("; "));
+            }
+            refresh_journal_status(app);
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
+        Message::ConflictResolved(Err(e)) => {
+            report_error(app, "Resolve failed", &e);
             Task::none()
         }
         Message::SyncToggleComplete(boxed_res) => match *boxed_res {
@@ -174,42 +286,63 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 Task::none()
             }
             Err(e) => {
-                app.error_msg = Some(format!("Toggle Error: {}", e));
+                report_error(app, "Toggle Error", &e);
                 Task::none()
             }
         },
         Message::TaskMoved(Ok(new_task)) => {
-            if let Some(list) = app.store.calendars.get_mut(&new_task.calendar_href) {
-                if let Some(idx) = list.iter().position(|t| t.uid == new_task.uid) {
-                    list[idx] = new_task.clone();
-                } else {
-                    list.push(new_task.clone());
-                }
-                let (_, token) = Cache::load(&new_task.calendar_href).unwrap_or((vec![], None));
-                let _ = Cache::save(&new_task.calendar_href, list, token);
-            }
+            app.store.update_or_add_task(new_task);
             refresh_filtered_tasks(app);
             Task::none()
         }
         Message::TaskMoved(Err(e)) => {
-            app.error_msg = Some(format!("Move failed: {}", e));
+            report_error(app, "Move failed", &e);
             Task::none()
         }
         Message::MigrationComplete(Ok(count)) => {
             app.loading = false;
             app.error_msg = Some(format!("Exported {} tasks successfully.", count));
-            if let Some(client) = &app.client {
+            if let Some(manager) = &app.manager {
                 app.loading = true;
                 return Task::perform(
-                    async_fetch_all_wrapper(client.clone(), app.calendars.clone()),
+                    async_fetch_all_wrapper(manager.clone(), app.calendars.clone()),
                     Message::RefreshedAll,
                 );
             }
             Task::none()
         }
+        Message::ImportComplete(Ok(0)) => Task::none(),
+        Message::ImportComplete(Ok(count)) => {
+            refresh_journal_status(app);
+            app.error_msg = Some(format!("Imported {} tasks.", count));
+            match (&app.manager, &app.active_cal_href) {
+                (Some(manager), Some(href)) => Task::perform(
+                    async_fetch_wrapper(manager.clone(), href.clone()),
+                    Message::TasksRefreshed,
+                ),
+                _ => Task::none(),
+            }
+        }
+        Message::ImportComplete(Err(e)) => {
+            report_error(app, "Import failed", &e);
+            Task::none()
+        }
+        Message::ExportComplete(Ok(0)) => Task::none(),
+        Message::ExportComplete(Ok(count)) => {
+            app.error_msg = Some(format!("Exported {} tasks.", count));
+            Task::none()
+        }
+        Message::ExportComplete(Err(e)) => {
+            report_error(app, "Export failed", &e);
+            Task::none()
+        }
+        Message::AttachmentOpened(Ok(())) => Task::none(),
+        Message::AttachmentOpened(Err(e)) => {
+            report_error(app, "Could not open attachment", &e);
+            Task::none()
+        }
         Message::MigrationComplete(Err(e)) => {
-            app.loading = false;
-            app.error_msg = Some(format!("Export failed: {}", e));
+            report_error(app, "Export failed", &e);
             Task::none()
         }
         _ => Task::none(),
@@ -1,11 +1,17 @@
 // File: src/gui/update/settings.rs
 use crate::cache::Cache;
-use crate::config::Config;
+use crate::config::{CalendarPrefs, Config};
 use crate::gui::async_ops::*;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp};
-use crate::gui::update::common::{apply_alias_retroactively, refresh_filtered_tasks, save_config};
-use crate::storage::{LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME, LocalStorage};
+use crate::gui::update::common::{
+    apply_alias_retroactively, login_error, refresh_filtered_tasks, save_config,
+};
+use crate::model::insert_alias;
+use crate::model::status::status_cycle;
+use crate::storage::{
+    DEFAULT_TRASH_DAYS, LOCAL_CALENDAR_HREF, LocalStorage, Trash, is_local_calendar,
+};
 use iced::Task;
 
 pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
@@ -20,8 +26,18 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             };
             app.ob_insecure = config.allow_insecure_certs;
             app.tag_aliases = config.tag_aliases.clone();
+            app.calendar_prefs = config.calendars.clone();
+            app.theme = config.theme.clone();
+            app.config_warnings = config.warnings.iter().map(|p| p.to_string()).collect();
             app.hide_completed = config.hide_completed;
             app.hide_fully_completed_tags = config.hide_fully_completed_tags;
+            app.named_priorities = config.named_priorities;
+            app.status_cycle = status_cycle(&config.custom_statuses, &config.status_cycle);
+            app.custom_statuses = config.custom_statuses.clone();
+            app.sort_order = config.sort_order;
+            app.group_by = config.group_by;
+            app.offline = config.offline;
+            app.journal_limits = (config.journal_max_entries, config.journal_max_days);
 
             app.ob_url = config.url.clone();
             app.ob_user = config.username.clone();
@@ -29,26 +45,19 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.ob_default_cal = config.default_calendar.clone();
 
             let mut cached_cals = Cache::load_calendars().unwrap_or_default();
-
-            if !cached_cals.iter().any(|c| c.href == LOCAL_CALENDAR_HREF) {
-                cached_cals.push(crate::model::CalendarListEntry {
-                    name: LOCAL_CALENDAR_NAME.to_string(),
-                    href: LOCAL_CALENDAR_HREF.to_string(),
-                    color: None,
-                });
-            }
+            LocalStorage::add_calendars(&mut cached_cals);
+            CalendarPrefs::apply_colors(&app.calendar_prefs, &mut cached_cals);
             app.calendars = cached_cals;
 
             app.store.clear();
 
-            if let Ok(local_tasks) = LocalStorage::load() {
-                app.store
-                    .insert(LOCAL_CALENDAR_HREF.to_string(), local_tasks);
+            for (href, local_tasks) in LocalStorage::load_all() {
+                app.store.insert(href, local_tasks);
             }
 
             for cal in &app.calendars {
-                if cal.href != LOCAL_CALENDAR_HREF
-                    && let Ok((tasks, _)) = Cache::load(&cal.href)
+                if !is_local_calendar(&cal.href)
+                    && let Ok((tasks, _)) = Cache::load_for(cal)
                 {
                     app.store.insert(cal.href.clone(), tasks);
                 }
@@ -79,7 +88,11 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.loading = true;
             Task::perform(connect_and_fetch_wrapper(config), Message::Loaded)
         }
-        Message::ConfigLoaded(Err(_)) => {
+        Message::ConfigLoaded(Err(e)) => {
+            // A broken file (not a missing one): say what to fix, key by key
+            if Config::file_exists() {
+                app.error_msg = Some(e);
+            }
             app.state = AppState::Onboarding;
             Task::none()
         }
@@ -103,6 +116,35 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.ob_insecure = val;
             Task::none()
         }
+        Message::ObTestConnection => {
+            let config = Config {
+                url: app.ob_url.clone(),
+                username: app.ob_user.clone(),
+                password: app.ob_pass.clone(),
+                allow_insecure_certs: app.ob_insecure,
+                ..Config::load().unwrap_or_default()
+            };
+            app.error_msg = Some("Connecting...".to_string());
+            Task::perform(async_check_connection_wrapper(config), Message::ObTested)
+        }
+        Message::ObTested(Ok(mut calendars)) => {
+            app.error_msg = Some(format!("Connected: {} calendar(s) found.", calendars.len()));
+            LocalStorage::add_calendars(&mut calendars);
+            CalendarPrefs::apply_colors(&app.calendar_prefs, &mut calendars);
+            // The first server calendar, unless one was already picked
+            if app.ob_default_cal.is_none() {
+                app.ob_default_cal = calendars
+                    .iter()
+                    .find(|c| !is_local_calendar(&c.href))
+                    .map(|c| c.name.clone());
+            }
+            app.calendars = calendars;
+            Task::none()
+        }
+        Message::ObTested(Err(e)) => {
+            app.error_msg = Some(login_error(&e, app.ob_insecure));
+            Task::none()
+        }
         Message::ObSubmit => {
             if app.ob_sort_months_input.trim().is_empty() {
                 app.sort_cutoff_months = None;
@@ -122,6 +164,7 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 hide_fully_completed_tags: app.hide_fully_completed_tags,
                 tag_aliases: app.tag_aliases.clone(),
                 sort_cutoff_months: Some(6),
+                ..Config::default()
             });
 
             config_to_save.url = app.ob_url.clone();
@@ -161,6 +204,61 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 };
             }
             app.state = AppState::Settings;
+            app.cache_stats = Cache::stats().ok();
+            match &app.manager {
+                Some(manager) => {
+                    Task::perform(async_quota_wrapper(manager.clone()), Message::QuotaLoaded)
+                }
+                None => Task::none(),
+            }
+        }
+        Message::QuotaLoaded(res) => {
+            // Not worth an error message: the line is just left out
+            app.quota = res.ok().flatten();
+            Task::none()
+        }
+        Message::ClearCache => {
+            if let Err(e) = Cache::clear() {
+                app.error_msg = Some(format!("Could not clear the cache: {}", e));
+            }
+            app.cache_stats = Cache::stats().ok();
+            Task::none()
+        }
+        Message::LocalCalendarInput(v) => {
+            app.local_cal_input = v;
+            Task::none()
+        }
+        Message::AddLocalCalendar => {
+            match LocalStorage::create_calendar(&app.local_cal_input) {
+                Ok(cal) => {
+                    app.local_cal_input.clear();
+                    app.error_msg = None;
+                    app.store.insert(cal.href.clone(), vec![]);
+                    app.calendars.push(cal);
+                }
+                Err(e) => app.error_msg = Some(e.to_string()),
+            }
+            Task::none()
+        }
+        Message::DeleteLocalCalendar(href) => {
+            let trash_days = Config::load().map_or(DEFAULT_TRASH_DAYS, |c| c.trash_days);
+            match LocalStorage::delete_calendar(&href) {
+                Ok(tasks) => {
+                    for task in &tasks {
+                        if let Err(e) = Trash::add(task, trash_days) {
+                            app.error_msg = Some(format!("Could not move to trash: {}", e));
+                        }
+                        app.store.delete_task(&task.uid);
+                    }
+                    app.calendars.retain(|c| c.href != href);
+                    app.store.calendars.remove(&href);
+                    if app.active_cal_href.as_ref() == Some(&href) {
+                        app.active_cal_href = Some(LOCAL_CALENDAR_HREF.to_string());
+                    }
+                    refresh_filtered_tasks(app);
+                }
+                Err(e) => app.error_msg = Some(e.to_string()),
+            }
             Task::none()
         }
         Message::CancelSettings => {
@@ -184,6 +282,10 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 hide_fully_completed_tags: app.hide_fully_completed_tags,
                 tag_aliases: app.tag_aliases.clone(),
                 sort_cutoff_months: app.sort_cutoff_months,
+                named_priorities: app.named_priorities,
+                sort_order: app.sort_order,
+                group_by: app.group_by,
+                ..Config::default()
             };
 
             let _ = config_to_save.save();
@@ -200,23 +302,17 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::AddAlias => {
-            if !app.alias_input_key.is_empty() && !app.alias_input_values.is_empty() {
-                let tags: Vec<String> = app
-                    .alias_input_values
-                    .split(',')
-                    .map(|s| s.trim().trim_start_matches('#').to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                if !tags.is_empty() {
-                    let key = app
-                        .alias_input_key
-                        .trim()
-                        .trim_start_matches('#')
-                        .to_string();
-                    app.tag_aliases.insert(key.clone(), tags.clone());
+            let tags: Vec<String> = app
+                .alias_input_values
+                .split(',')
+                .map(|s| s.to_string())
+                .collect();
+            match insert_alias(&mut app.tag_aliases, &app.alias_input_key, &tags) {
+                Ok(key) => {
+                    let tags = app.tag_aliases[&key].clone();
                     app.alias_input_key.clear();
                     app.alias_input_values.clear();
+                    app.error_msg = None;
                     save_config(app);
 
                     // Use common helper
@@ -224,6 +320,7 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                         return task;
                     }
                 }
+                Err(e) => app.error_msg = Some(e),
             }
             Task::none()
         }
@@ -1,9 +1,10 @@
 // File: src/gui/update/tasks.rs
+use crate::config::CalendarPrefs;
 use crate::gui::async_ops::*;
 use crate::gui::message::Message;
-use crate::gui::state::{GuiApp, SidebarMode};
+use crate::gui::state::{AppState, GuiApp, RecurrenceDraft, SidebarMode};
 use crate::gui::update::common::{apply_alias_retroactively, refresh_filtered_tasks, save_config};
-use crate::model::{Task as TodoTask, extract_inline_aliases};
+use crate::model::{SortOrder, Task as TodoTask, TaskStatus, extract_inline_aliases, insert_alias};
 use iced::Task;
 use iced::widget::operation;
 use iced::widget::scrollable::RelativeOffset;
@@ -39,11 +40,12 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
 
         Message::EditTaskStart(index) => {
             if let Some(task) = app.tasks.get(index) {
-                app.input_value = task.to_smart_string();
+                app.input_value = task.to_smart_string_with(app.named_priorities);
                 app.description_value =
                     iced::widget::text_editor::Content::with_text(&task.description);
                 app.editing_uid = Some(task.uid.clone());
                 app.selected_uid = Some(task.uid.clone());
+                app.recurrence_draft = None;
             }
             Task::none()
         }
@@ -52,6 +54,56 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.description_value = iced::widget::text_editor::Content::new();
             app.editing_uid = None;
             app.creating_child_of = None;
+            app.recurrence_draft = None;
+            Task::none()
+        }
+
+        Message::ToggleRecurrenceEditor => {
+            if app.recurrence_draft.is_some() {
+                app.recurrence_draft = None;
+            } else if let Some(uid) = &app.editing_uid
+                && let Some(task) = app.tasks.iter().find(|t| t.uid == *uid)
+            {
+                app.recurrence_draft = Some(RecurrenceDraft::from_task(task));
+            }
+            Task::none()
+        }
+        Message::RecurrenceFreqChanged(freq) => {
+            if let Some(draft) = &mut app.recurrence_draft {
+                draft.set_freq(freq);
+            }
+            Task::none()
+        }
+        Message::RecurrenceIntervalChanged(value) => {
+            if let Some(draft) = &mut app.recurrence_draft {
+                draft.interval = value;
+            }
+            Task::none()
+        }
+        Message::RecurrenceDayToggled(day) => {
+            if let Some(draft) = &mut app.recurrence_draft
+                && let Some(rule) = &mut draft.rule
+            {
+                rule.toggle_day(day);
+            }
+            Task::none()
+        }
+        Message::RecurrenceUntilChanged(value) => {
+            if let Some(draft) = &mut app.recurrence_draft {
+                draft.until = value;
+            }
+            Task::none()
+        }
+        Message::RecurrenceCountChanged(value) => {
+            if let Some(draft) = &mut app.recurrence_draft {
+                draft.count = value;
+            }
+            Task::none()
+        }
+        Message::RecurrenceAfterCompletionToggled(value) => {
+            if let Some(draft) = &mut app.recurrence_draft {
+                draft.after_completion = value;
+            }
             Task::none()
         }
 
@@ -61,9 +113,9 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 app.selected_uid = Some(uid.clone());
                 if let Some(updated) = app.store.toggle_task(&uid) {
                     refresh_filtered_tasks(app);
-                    if let Some(client) = &app.client {
+                    if let Some(manager) = &app.manager {
                         return Task::perform(
-                            async_toggle_wrapper(client.clone(), updated),
+                            async_toggle_wrapper(manager.clone(), updated),
                             |res| Message::SyncToggleComplete(Box::new(res)),
                         );
                     }
@@ -76,23 +128,54 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
                 && let Some(deleted) = app.store.delete_task(&view_task.uid)
             {
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_delete_wrapper(client.clone(), deleted),
+                        async_delete_wrapper(manager.clone(), deleted),
                         Message::DeleteComplete,
                     );
                 }
             }
             Task::none()
         }
+        Message::Undelete(uid) => {
+            app.trash.retain(|e| e.task.uid != uid);
+            if let Some(manager) = &app.manager {
+                return Task::perform(
+                    async_undelete_wrapper(manager.clone(), uid),
+                    Message::SyncSaved,
+                );
+            }
+            Task::none()
+        }
+        Message::Undo => {
+            if let Some(manager) = &app.manager {
+                return Task::perform(async_undo_wrapper(manager.clone()), Message::Undone);
+            }
+            Task::none()
+        }
+        Message::ResolveConflict(keep) => {
+            if let Some(manager) = &app.manager
+                && !app.conflicts.is_empty()
+            {
+                let conflict = app.conflicts.remove(0);
+                if app.conflicts.is_empty() {
+                    app.state = AppState::Active;
+                }
+                return Task::perform(
+                    async_resolve_conflict_wrapper(manager.clone(), conflict.local.uid, keep),
+                    Message::ConflictResolved,
+                );
+            }
+            Task::none()
+        }
         Message::ChangePriority(index, delta) => {
             if let Some(view_task) = app.tasks.get(index) {
                 app.selected_uid = Some(view_task.uid.clone());
                 if let Some(updated) = app.store.change_priority(&view_task.uid, delta) {
                     refresh_filtered_tasks(app);
-                    if let Some(client) = &app.client {
+                    if let Some(manager) = &app.manager {
                         return Task::perform(
-                            async_update_wrapper(client.clone(), updated),
+                            async_update_wrapper(manager.clone(), updated),
                             Message::SyncSaved,
                         );
                     }
@@ -100,14 +183,95 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             }
             Task::none()
         }
+        Message::ToggleChecklistItem(uid, index) => {
+            if let Some(updated) = app.store.toggle_checklist_item(&uid, index) {
+                app.selected_uid = Some(uid);
+                refresh_filtered_tasks(app);
+                if let Some(manager) = &app.manager {
+                    return Task::perform(
+                        async_update_wrapper(manager.clone(), updated),
+                        Message::SyncSaved,
+                    );
+                }
+            }
+            Task::none()
+        }
+        Message::ToggleFlag(uid) => {
+            if let Some(updated) = app.store.toggle_flag(&uid) {
+                app.selected_uid = Some(uid);
+                refresh_filtered_tasks(app);
+                if let Some(manager) = &app.manager {
+                    return Task::perform(
+                        async_update_wrapper(manager.clone(), updated),
+                        Message::SyncSaved,
+                    );
+                }
+            }
+            Task::none()
+        }
+        Message::ToggleTracking(uid) => {
+            if let Some(updated) = app.store.toggle_tracking(&uid) {
+                app.selected_uid = Some(uid);
+                refresh_filtered_tasks(app);
+                if let Some(manager) = &app.manager {
+                    return Task::perform(
+                        async_update_wrapper(manager.clone(), updated),
+                        Message::SyncSaved,
+                    );
+                }
+            }
+            Task::none()
+        }
+        Message::CycleStatus(uid) => {
+            let index = app.tasks.iter().position(|t| t.uid == uid);
+            // Done the usual way, so recurring tasks respawn
+            if let Some(index) = index
+                && let Some(step) = app.tasks[index].next_in_cycle(&app.status_cycle)
+                && step.is_standard()
+                && step.base == TaskStatus::Completed
+            {
+                return handle(app, Message::ToggleTask(index, true));
+            }
+            if let Some(updated) = app.store.cycle_status(&uid, &app.status_cycle) {
+                app.selected_uid = Some(uid);
+                refresh_filtered_tasks(app);
+                if let Some(manager) = &app.manager {
+                    return Task::perform(
+                        async_update_wrapper(manager.clone(), updated),
+                        Message::SyncSaved,
+                    );
+                }
+            }
+            Task::none()
+        }
+        Message::MoveSelectedInOrder(up) => match app.selected_uid.clone() {
+            Some(uid) => handle(app, Message::MoveTaskInOrder(uid, up)),
+            None => Task::none(),
+        },
+        Message::MoveTaskInOrder(uid, up) => {
+            app.selected_uid = Some(uid.clone());
+            let updated = app.store.move_in_manual_order(&app.tasks, &uid, up);
+            // Reordering only makes sense while looking at the manual order
+            if app.sort_order != SortOrder::Manual {
+                app.sort_order = SortOrder::Manual;
+                save_config(app);
+            }
+            refresh_filtered_tasks(app);
+            if let Some(manager) = &app.manager {
+                return Task::batch(updated.into_iter().map(|t| {
+                    Task::perform(async_update_wrapper(manager.clone(), t), Message::SyncSaved)
+                }));
+            }
+            Task::none()
+        }
         Message::SetTaskStatus(index, new_status) => {
             if let Some(view_task) = app.tasks.get(index) {
                 app.selected_uid = Some(view_task.uid.clone());
                 if let Some(updated) = app.store.set_status(&view_task.uid, new_status) {
                     refresh_filtered_tasks(app);
-                    if let Some(client) = &app.client {
+                    if let Some(manager) = &app.manager {
                         return Task::perform(
-                            async_update_wrapper(client.clone(), updated),
+                            async_update_wrapper(manager.clone(), updated),
                             Message::SyncSaved,
                         );
                     }
@@ -128,15 +292,21 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             // Clone first to avoid borrow conflicts when clearing later
             let parent_opt = app.yanked_uid.clone();
 
+            if let Some(parent_uid) = &parent_opt
+                && let Err(e) = app.store.check_parent(&target_uid, parent_uid)
+            {
+                app.error_msg = Some(e);
+                return Task::none();
+            }
             if let Some(parent_uid) = parent_opt
                 && let Some(updated) = app.store.set_parent(&target_uid, Some(parent_uid.clone()))
             {
                 app.selected_uid = Some(target_uid);
                 app.yanked_uid = None; // Clear yank state
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_update_wrapper(client.clone(), updated),
+                        async_update_wrapper(manager.clone(), updated),
                         Message::SyncSaved,
                     );
                 }
@@ -147,9 +317,9 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             if let Some(updated) = app.store.set_parent(&child_uid, None) {
                 app.selected_uid = Some(child_uid);
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_update_wrapper(client.clone(), updated),
+                        async_update_wrapper(manager.clone(), updated),
                         Message::SyncSaved,
                     );
                 }
@@ -160,9 +330,9 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             if let Some(updated) = app.store.remove_dependency(&task_uid, &dep_uid) {
                 app.selected_uid = Some(task_uid);
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_update_wrapper(client.clone(), updated),
+                        async_update_wrapper(manager.clone(), updated),
                         Message::SyncSaved,
                     );
                 }
@@ -173,15 +343,21 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             // Clone first to avoid borrow conflicts
             let blocker_opt = app.yanked_uid.clone();
 
+            if let Some(blocker_uid) = &blocker_opt
+                && let Err(e) = app.store.check_dependency(&target_uid, blocker_uid)
+            {
+                app.error_msg = Some(e);
+                return Task::none();
+            }
             if let Some(blocker_uid) = blocker_opt
                 && let Some(updated) = app.store.add_dependency(&target_uid, blocker_uid.clone())
             {
                 app.selected_uid = Some(target_uid);
                 app.yanked_uid = None; // Clear yank state
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_update_wrapper(client.clone(), updated),
+                        async_update_wrapper(manager.clone(), updated),
                         Message::SyncSaved,
                     );
                 }
@@ -192,9 +368,9 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             if let Some(updated) = app.store.move_task(&task_uid, target_href.clone()) {
                 app.selected_uid = Some(task_uid);
                 refresh_filtered_tasks(app);
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_move_wrapper(client.clone(), updated, target_href),
+                        async_move_wrapper(manager.clone(), updated, target_href),
                         Message::TaskMoved,
                     );
                 }
@@ -202,22 +378,92 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::MigrateLocalTo(target_href) => {
-            if let Some(local_tasks) = app.store.calendars.get(crate::storage::LOCAL_CALENDAR_HREF)
+            if let Some(source) = app
+                .active_cal_href
+                .as_deref()
+                .filter(|h| crate::storage::is_local_calendar(h))
+                && let Some(local_tasks) = app.store.calendars.get(source)
             {
                 let tasks_to_move = local_tasks.clone();
                 if tasks_to_move.is_empty() {
                     return Task::none();
                 }
                 app.loading = true;
-                if let Some(client) = &app.client {
+                if let Some(manager) = &app.manager {
                     return Task::perform(
-                        async_migrate_wrapper(client.clone(), tasks_to_move, target_href),
+                        async_migrate_wrapper(manager.clone(), tasks_to_move, target_href),
                         Message::MigrationComplete,
                     );
                 }
             }
             Task::none()
         }
+        Message::ImportIcs => {
+            let (Some(manager), Some(href)) = (&app.manager, &app.active_cal_href) else {
+                return Task::none();
+            };
+            Task::perform(
+                async_import_wrapper(manager.clone(), href.clone()),
+                Message::ImportComplete,
+            )
+        }
+        Message::AttachFile(uid) => {
+            let Some(manager) = &app.manager else {
+                return Task::none();
+            };
+            let Some((task, _)) = app.store.get_task_mut(&uid) else {
+                return Task::none();
+            };
+            Task::perform(
+                async_attach_wrapper(manager.clone(), task.clone()),
+                Message::SyncSaved,
+            )
+        }
+        Message::OpenAttachment(uid, idx) => {
+            let Some(manager) = app.manager.clone() else {
+                return Task::none();
+            };
+            let Some(attachment) = app
+                .store
+                .get_task_mut(&uid)
+                .and_then(|(t, _)| t.attachments.get(idx).cloned())
+            else {
+                return Task::none();
+            };
+            Task::perform(
+                async_open_attachment_wrapper(manager, attachment),
+                Message::AttachmentOpened,
+            )
+        }
+        Message::Export(format) => {
+            let Some(href) = &app.active_cal_href else {
+                return Task::none();
+            };
+            let name = app
+                .calendars
+                .iter()
+                .find(|c| &c.href == href)
+                .map(|c| c.name.clone())
+                .unwrap_or_else(|| "tasks".to_string());
+            if format.is_report() {
+                return Task::perform(
+                    async_export_list_wrapper(
+                        app.tasks.clone(),
+                        app.calendars.clone(),
+                        name,
+                        format,
+                    ),
+                    Message::ExportComplete,
+                );
+            }
+            let Some(manager) = &app.manager else {
+                return Task::none();
+            };
+            Task::perform(
+                async_export_wrapper(manager.clone(), href.clone(), name, format),
+                Message::ExportComplete,
+            )
+        }
         _ => Task::none(),
     }
 }
@@ -235,7 +481,14 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
     if !new_aliases.is_empty() {
         // Register new aliases
         for (key, tags) in new_aliases {
-            app.tag_aliases.insert(key.clone(), tags.clone());
+            let key = match insert_alias(&mut app.tag_aliases, &key, &tags) {
+                Ok(key) => key,
+                Err(e) => {
+                    app.error_msg = Some(e);
+                    continue;
+                }
+            };
+            let tags = app.tag_aliases[&key].clone();
 
             // Queue retroactive application
             if let Some(task_cmd) = apply_alias_retroactively(app, &key, &tags) {
@@ -279,20 +532,43 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
     }
 
     if let Some(edit_uid) = &app.editing_uid {
-        if let Some((task, _)) = app.store.get_task_mut(edit_uid) {
-            task.apply_smart_input(&clean_input, &app.tag_aliases);
-            task.description = app.description_value.text();
-            let task_copy = task.clone();
+        // An open recurrence builder wins over @daily & co. in the title
+        let recurrence = match app
+            .recurrence_draft
+            .as_ref()
+            .map(|d| d.build().map(|rule| (rule, d.after_completion)))
+        {
+            Some(Ok(rule)) => Some(rule),
+            Some(Err(e)) => {
+                app.error_msg = Some(e);
+                return Task::none();
+            }
+            None => None,
+        };
+        if let Some(mut task_copy) = app.store.get_task_mut(edit_uid).map(|(t, _)| t.clone()) {
+            task_copy.apply_smart_input(&clean_input, &app.tag_aliases);
+            task_copy.description = app.description_value.text();
+            if let Some((rule, after_completion)) = recurrence {
+                task_copy.repeat_after_completion = rule.is_some() && after_completion;
+                task_copy.set_recurrence(rule);
+            }
+            if !confirm_warning(app, task_copy.smart_input_warning(&clean_input)) {
+                return Task::batch(retroactive_sync_batch);
+            }
+            if let Some((task, _)) = app.store.get_task_mut(edit_uid) {
+                *task = task_copy.clone();
+            }
 
             app.input_value.clear();
             app.description_value = iced::widget::text_editor::Content::new();
             app.editing_uid = None;
+            app.recurrence_draft = None;
             app.selected_uid = Some(task_copy.uid.clone());
 
             refresh_filtered_tasks(app);
-            if let Some(client) = &app.client {
+            if let Some(manager) = &app.manager {
                 let save_cmd = Task::perform(
-                    async_update_wrapper(client.clone(), task_copy),
+                    async_update_wrapper(manager.clone(), task_copy),
                     Message::SyncSaved,
                 );
                 retroactive_sync_batch.push(save_cmd);
@@ -300,9 +576,19 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
             }
         }
     } else if !clean_input.is_empty() {
-        let mut new_task = TodoTask::new(&clean_input, &app.tag_aliases);
+        // "Parent > child > child" creates the whole family at once
+        let mut new_tasks = TodoTask::new_with_subtasks(&clean_input, &app.tag_aliases);
+        if new_tasks.is_empty() {
+            return Task::batch(retroactive_sync_batch);
+        }
+        let warning = new_tasks
+            .iter()
+            .find_map(|t| t.smart_input_warning(&clean_input));
+        if !confirm_warning(app, warning) {
+            return Task::batch(retroactive_sync_batch);
+        }
         if let Some(parent) = &app.creating_child_of {
-            new_task.parent_uid = Some(parent.clone());
+            new_tasks[0].parent_uid = Some(parent.clone());
             app.creating_child_of = None;
         }
 
@@ -312,22 +598,39 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
             .or_else(|| app.calendars.first().map(|c| c.href.clone()))
             .unwrap_or_default();
 
-        if !target_href.is_empty() {
-            new_task.calendar_href = target_href.clone();
+        if let Some(cal) = app
+            .calendars
+            .iter()
+            .find(|c| c.href == target_href && c.read_only)
+        {
+            app.error_msg = Some(format!("Calendar '{}' is read-only", cal.name));
+            return Task::none();
+        }
 
-            // Fix: Use add_task to maintain index
-            app.store.add_task(new_task.clone());
+        if !target_href.is_empty() {
+            let prefs = CalendarPrefs::find(&app.calendar_prefs, &app.calendars, &target_href);
+            let account = app
+                .calendars
+                .iter()
+                .find(|c| c.href == target_href)
+                .and_then(|c| c.account.clone());
+            for t in &mut new_tasks {
+                t.calendar_href = target_href.clone();
+                t.account = account.clone();
+                if let Some(prefs) = prefs {
+                    prefs.tag_new_task(t, &app.tag_aliases);
+                }
+                // Fix: Use add_task to maintain index
+                app.store.add_task(t.clone());
+            }
+            let new_uid = new_tasks[0].uid.clone();
 
-            app.selected_uid = Some(new_task.uid.clone());
+            app.selected_uid = Some(new_uid.clone());
             refresh_filtered_tasks(app);
             app.input_value.clear();
 
             let len = app.tasks.len().max(1) as f32;
-            let idx = app
-                .tasks
-                .iter()
-                .position(|t| t.uid == new_task.uid)
-                .unwrap_or(0) as f32;
+            let idx = app.tasks.iter().position(|t| t.uid == new_uid).unwrap_or(0) as f32;
             let scroll_cmd = operation::snap_to(
                 app.scrollable_id.clone(),
                 RelativeOffset {
@@ -336,13 +639,13 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
                 },
             );
 
-            if let Some(client) = &app.client {
-                let create_cmd = Task::perform(
-                    async_create_wrapper(client.clone(), new_task),
-                    Message::SyncSaved,
-                );
-
-                retroactive_sync_batch.push(create_cmd);
+            if let Some(manager) = &app.manager {
+                for t in new_tasks {
+                    retroactive_sync_batch.push(Task::perform(
+                        async_create_wrapper(manager.clone(), t),
+                        Message::SyncSaved,
+                    ));
+                }
                 retroactive_sync_batch.push(scroll_cmd);
 
                 return Task::batch(retroactive_sync_batch);
@@ -355,3 +658,18 @@ fn handle_submit(app: &mut GuiApp) -> Task<Message> {
     }
     Task::none()
 }
+
+// A warning blocks the first submit only: the same input again is saved as is
+fn confirm_warning(app: &mut GuiApp, warning: Option<String>) -> bool {
+    let Some(warning) = warning else {
+        app.warned_input = None;
+        return true;
+    };
+    if app.warned_input.as_deref() == Some(app.input_value.as_str()) {
+        app.warned_input = None;
+        return true;
+    }
+    app.error_msg = Some(format!("{} (press Enter again to save anyway)", warning));
+    app.warned_input = Some(app.input_value.clone());
+    false
+}
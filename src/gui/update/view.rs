@@ -2,10 +2,27 @@
 use crate::gui::async_ops::*;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp, ResizeDirection, SidebarMode};
-use crate::gui::update::common::{refresh_filtered_tasks, save_config};
+use crate::gui::update::common::{refresh_filtered_tasks, refresh_journal_status, save_config};
+use crate::history::History;
+use crate::journal::Journal;
+use crate::storage::Trash;
 use iced::widget::operation;
 use iced::{Task, window};
 
+// Weeks shown in the statistics view
+const STATS_WEEKS: usize = 12;
+
+// Queued and failed journal entries, for the pending changes view
+fn reload_pending(app: &mut GuiApp) {
+    match Journal::load() {
+        Ok(journal) => {
+            app.pending = journal.queue;
+            app.failed = journal.failed;
+        }
+        Err(e) => app.error_msg = Some(format!("Can't read the offline queue: {}", e)),
+    }
+}
+
 pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
     match message {
         Message::TabPressed(shift_held) => {
@@ -50,12 +67,12 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             save_config(app);
             refresh_filtered_tasks(app);
 
-            if let Some(client) = &app.client {
+            if let Some(manager) = &app.manager {
                 if !app.store.calendars.contains_key(&href) {
                     app.loading = true;
                 }
                 return Task::perform(
-                    async_fetch_wrapper(client.clone(), href),
+                    async_fetch_wrapper(manager.clone(), href),
                     Message::TasksRefreshed,
                 );
             }
@@ -97,22 +114,45 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             refresh_filtered_tasks(app);
             Task::none()
         }
+        Message::SortOrderChanged(order) => {
+            app.sort_order = order;
+            save_config(app);
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
+        Message::GroupByChanged(group_by) => {
+            app.group_by = group_by;
+            save_config(app);
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
+        Message::ContextFilterChanged(choice) => {
+            app.selected_context = choice.strip_prefix("@@").map(str::to_string);
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
+        Message::ShowSmartList(list) => {
+            app.smart_list = list;
+            refresh_filtered_tasks(app);
+            Task::none()
+        }
         Message::SelectCalendar(href) => {
             if app.sidebar_mode == SidebarMode::Categories {
                 app.sidebar_mode = SidebarMode::Calendars;
             }
+            app.smart_list = None;
             app.active_cal_href = Some(href.clone());
             if app.hidden_calendars.contains(&href) {
                 app.hidden_calendars.remove(&href);
                 save_config(app);
             }
             refresh_filtered_tasks(app);
-            if let Some(client) = &app.client {
+            if let Some(manager) = &app.manager {
                 if !app.store.calendars.contains_key(&href) {
                     app.loading = true;
                 }
                 return Task::perform(
-                    async_fetch_wrapper(client.clone(), href),
+                    async_fetch_wrapper(manager.clone(), href),
                     Message::TasksRefreshed,
                 );
             }
@@ -173,6 +213,15 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.selected_uid = Some(uid);
             Task::none()
         }
+        Message::ToggleHistory(uid) => {
+            if app.history.as_ref().is_some_and(|(shown, _)| *shown == uid) {
+                app.history = None;
+            } else {
+                let entries = History::load(&uid).unwrap_or_default();
+                app.history = Some((uid, entries));
+            }
+            Task::none()
+        }
         Message::OpenHelp => {
             app.state = AppState::Help;
             Task::none()
@@ -181,6 +230,78 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.state = AppState::Active;
             Task::none()
         }
+        Message::OpenTrash => {
+            app.trash = Trash::load().unwrap_or_default();
+            app.state = AppState::Trash;
+            Task::none()
+        }
+        Message::CloseTrash => {
+            app.state = AppState::Active;
+            Task::none()
+        }
+        Message::OpenStats => {
+            app.stats = app
+                .store
+                .stats(&app.hidden_calendars, &app.calendars, STATS_WEEKS);
+            app.state = AppState::Stats;
+            Task::none()
+        }
+        Message::CloseStats => {
+            app.state = AppState::Active;
+            Task::none()
+        }
+        Message::OpenPending => {
+            reload_pending(app);
+            app.state = AppState::Pending;
+            Task::none()
+        }
+        Message::ClosePending => {
+            app.state = AppState::Active;
+            Task::none()
+        }
+        Message::CloseConflict => {
+            app.state = AppState::Active;
+            Task::none()
+        }
+        Message::DropPending(idx) => {
+            if let Some(action) = app.pending.get(idx) {
+                if let Err(e) = Journal::remove(action) {
+                    app.error_msg = Some(format!("Failed to drop the change: {}", e));
+                }
+                reload_pending(app);
+                refresh_journal_status(app);
+            }
+            Task::none()
+        }
+        Message::MovePending(idx, up) => {
+            if let Some(action) = app.pending.get(idx) {
+                if let Err(e) = Journal::move_entry(action, up) {
+                    app.error_msg = Some(format!("Failed to move the change: {}", e));
+                }
+                reload_pending(app);
+            }
+            Task::none()
+        }
+        Message::RetryFailed(idx) => {
+            if let Some(entry) = app.failed.get(idx) {
+                if let Err(e) = Journal::retry_failed(entry) {
+                    app.error_msg = Some(format!("Failed to queue the change: {}", e));
+                }
+                reload_pending(app);
+                refresh_journal_status(app);
+            }
+            Task::none()
+        }
+        Message::DropFailed(idx) => {
+            if let Some(entry) = app.failed.get(idx) {
+                if let Err(e) = Journal::drop_failed(entry) {
+                    app.error_msg = Some(format!("Failed to drop the change: {}", e));
+                }
+                reload_pending(app);
+                refresh_journal_status(app);
+            }
+            Task::none()
+        }
         Message::WindowDragged => window::latest().then(|id| {
             if let Some(id) = id {
                 window::drag(id)
@@ -225,6 +346,29 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
             app.current_window_size = size;
             Task::none()
         }
+        Message::JumpToTask(uid) => {
+            // Drop search/tag filters if they hide the target
+            if !app.tasks.iter().any(|t| t.uid == uid) {
+                app.search_value.clear();
+                app.selected_categories.clear();
+                refresh_filtered_tasks(app);
+            }
+            let Some(idx) = app.tasks.iter().position(|t| t.uid == uid) else {
+                app.error_msg = Some("That task is hidden in the current view".to_string());
+                return Task::none();
+            };
+            app.selected_uid = Some(uid.clone());
+            app.expanded_tasks.insert(uid);
+
+            let len = app.tasks.len().max(1) as f32;
+            operation::snap_to(
+                app.scrollable_id.clone(),
+                iced::widget::scrollable::RelativeOffset {
+                    x: 0.0,
+                    y: idx as f32 / len,
+                },
+            )
+        }
         Message::JumpToTag(tag) => {
             app.sidebar_mode = SidebarMode::Categories;
             app.selected_categories.clear();
@@ -257,6 +401,17 @@ pub fn handle(app: &mut GuiApp, message: Message) -> Task<Message> {
 
             Task::none()
         }
+        Message::CheckReminders => {
+            let now = chrono::Utc::now();
+            let tasks: Vec<_> = app.store.calendars.values().flatten().cloned().collect();
+            for r in crate::reminders::collect_due(&tasks, app.last_reminder_check, now) {
+                if let Err(e) = crate::reminders::notify(&r) {
+                    app.error_msg = Some(format!("Notification failed: {}", e));
+                }
+            }
+            app.last_reminder_check = now;
+            Task::none()
+        }
         _ => Task::none(),
     }
 }
@@ -0,0 +1,115 @@
+// File: src/gui/view/conflict.rs
+use crate::gui::icon;
+use crate::gui::message::Message;
+use crate::gui::state::GuiApp;
+use crate::journal::Resolution;
+
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Color, Element, Length};
+
+const COL_MUTED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const COL_SERVER: Color = Color::from_rgb(0.4, 0.7, 0.9);
+const COL_LOCAL: Color = Color::from_rgb(0.9, 0.75, 0.3);
+
+pub fn view_conflict(app: &GuiApp) -> Element<'_, Message> {
+    let Some(conflict) = app.conflicts.first() else {
+        return container(text("No conflicts left."))
+            .width(Length::Fill)
+            .center_x(Length::Fill)
+            .padding(20)
+            .into();
+    };
+
+    let mut heading = "Conflict".to_string();
+    if app.conflicts.len() > 1 {
+        heading = format!("Conflict (1 of {})", app.conflicts.len());
+    }
+    let title = row![icon::icon(icon::OVERDUE).size(28), text(heading).size(28)]
+        .spacing(15)
+        .align_y(iced::Alignment::Center);
+
+    let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+    let mut fields = column![
+        row![
+            text("").width(140),
+            text("Server").size(12).color(COL_MUTED).width(Length::Fill),
+            text("Mine").size(12).color(COL_MUTED).width(Length::Fill),
+        ]
+        .spacing(10)
+    ]
+    .spacing(6);
+    for change in conflict.server.diff(&conflict.local) {
+        fields = fields.push(
+            row![
+                text(change.field).size(14).width(140),
+                text(show(&change.old))
+                    .size(14)
+                    .color(COL_SERVER)
+                    .width(Length::Fill),
+                text(show(&change.new))
+                    .size(14)
+                    .color(COL_LOCAL)
+                    .width(Length::Fill),
+            ]
+            .spacing(10),
+        );
+    }
+
+    let choice = |label: &'static str, keep: Resolution| {
+        button(
+            text(label)
+                .size(16)
+                .width(Length::Fill)
+                .align_x(iced::alignment::Horizontal::Center),
+        )
+        .padding(12)
+        .width(Length::Fixed(160.0))
+        .style(button::primary)
+        .on_press(Message::ResolveConflict(keep))
+    };
+    let later_btn = button(
+        text("Later")
+            .size(16)
+            .width(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Center),
+    )
+    .padding(12)
+    .width(Length::Fixed(160.0))
+    .style(button::secondary)
+    .on_press(Message::CloseConflict);
+
+    let content = column![
+        title,
+        text(&conflict.local.summary).size(18),
+        text(
+            "This task changed on the server while your change was waiting to be \
+             synced, and the two couldn't be merged. Keeping both uploads yours as a copy."
+        )
+        .size(12)
+        .color(COL_MUTED),
+        fields,
+        container(
+            row![
+                choice("Keep mine", Resolution::Local),
+                choice("Keep server", Resolution::Server),
+                choice("Keep both", Resolution::Both),
+                later_btn,
+            ]
+            .spacing(10)
+        )
+        .width(Length::Fill)
+        .center_x(Length::Fill)
+        .padding(20),
+    ]
+    .spacing(20)
+    .padding(20)
+    .max_width(800);
+
+    scrollable(
+        container(content)
+            .width(Length::Fill)
+            .center_x(Length::Fill),
+    )
+    .height(Length::Fill)
+    .into()
+}
@@ -25,9 +25,11 @@ pub fn view_help() -> Element<'static, Message> {
             "Organization", 
             crate::gui::icon::TAG,
             vec![
-                entry("!1", "Priority High (1) to Low (9)", "!1, !5, !9"),
-                entry("#tag", "Add category. Use ':' for sub-tags.", "#work, #dev:backend"),
+                entry("!1", "Priority High (1) to Low (9)", "!1, !5, !9, !high, !med, !low, !none"),
+                entry("#tag", "Add category. Use ':' for sub-tags, quotes for spaces.", "#work, #dev:backend, #\"home improvement\""),
                 entry("#a=#b,#c", "Define/update alias inline.", "#groceries=#home,#shopping"),
+                entry("@@context", "Add a context (where/with what), filtered on its own.", "@@home, @@errands"),
+                entry(">", "Add subtasks along with the new task.", "Plan trip > book hotel > pack"),
                 entry("~30m", "Estimated Duration (m/h/d/w).", "~30m, ~1.5h, ~2d"),
             ]
         ),
@@ -40,7 +42,8 @@ pub fn view_help() -> Element<'static, Message> {
                 entry("@date", "Due Date. Deadline for the task.", "@tomorrow, @2025-12-31"),
                 entry("^date", "Start Date. Hides/sorts lower until date.", "^next week, ^2025-01-01"),
                 entry("Offsets", "Add time from today.", "1d (1 day), 2w (2 weeks), 3mo (3 months), 4y (4 years)"),
-                entry("Keywords", "Relative dates supported.", "today, tomorrow, next week, next year"),
+                entry("Keywords", "Relative dates supported.", "today, tomorrow, friday, @\"next week\", @\"next friday\""),
+                entry("Time", "Optional time after the date.", "@tomorrow 17:00, @today 5pm, @2025-07-01T09:00"),
 
 
             ]
@@ -53,6 +56,7 @@ pub fn view_help() -> Element<'static, Message> {
             vec![
                 entry("@daily", "Quick presets.", "@daily, @weekly, @monthly, @yearly"),
                 entry("@every X", "Custom intervals.", "@every 3 days, @every 2 weeks"),
+                entry("Repeat", "Weekdays, end date or count: Repeat button while editing.", ""),
                 entry("Note", "Recurrence calculates next date based on Start Date if present, else Due Date.", ""),
             ]
         ),
@@ -62,9 +66,11 @@ pub fn view_help() -> Element<'static, Message> {
             "Search & Filtering", 
             crate::gui::icon::SHIELD, 
             vec![
-                entry("text", "Matches summary or description.", "buy cat food"),
+                entry("text", "Matches summary, description, tags, contexts or calendar name.", "buy cat food"),
                 entry("#tag", "Filter by specific tag.", "#gardening"),
-                entry("is:status", "Filter by state.", "is:done, is:ongoing, is:active"),
+                entry("@@context", "Filter by context.", "@@home"),
+                entry("is:status", "Filter by state.", "is:done, is:ongoing, is:active, is:flagged"),
+                entry(":filter", "Filter expression, with AND/OR/NOT and parentheses.", ":due<7d AND prio<=4 AND #work AND status!=done"),
                 entry("Operators", "Compare values (<, >, <=, >=).", "~<20m (less than 20 minutes), <!4 (urgent tasks)"),
                 entry("  Dates", "Filter by timeframe.", "@<today (Overdue), ^>tomorrow"),
                 entry("  Priority", "Filter by priority range.", "!<3 (High prio), !>=5"),
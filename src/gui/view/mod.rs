@@ -1,18 +1,30 @@
 // File: src/gui/view/mod.rs
 use std::time::Duration;
+pub mod conflict;
 pub mod help;
+pub mod pending;
+pub mod recurrence;
 pub mod settings;
 pub mod sidebar;
+pub mod stats;
 pub mod task_row;
+pub mod trash;
 
 use crate::gui::icon;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp, ResizeDirection, SidebarMode};
+use crate::gui::view::conflict::view_conflict;
 use crate::gui::view::help::view_help;
+use crate::gui::view::pending::view_pending;
+use crate::gui::view::recurrence::view_recurrence_builder;
 use crate::gui::view::settings::view_settings;
 use crate::gui::view::sidebar::{view_sidebar_calendars, view_sidebar_categories};
+use crate::gui::view::stats::view_stats;
 use crate::gui::view::task_row::view_task_row;
-use crate::storage::LOCAL_CALENDAR_HREF;
+use crate::gui::view::trash::view_trash;
+use crate::model::{FileFormat, GroupBy, SortOrder};
+use crate::storage::is_local_calendar;
+use crate::store::{SmartList, query_error};
 
 use iced::widget::scrollable::{Direction, Scrollbar};
 use iced::widget::{
@@ -20,6 +32,9 @@ use iced::widget::{
 };
 use iced::{Color, Element, Length, Theme, mouse};
 
+// First entry of the context picker: no context filter
+const ANY_CONTEXT: &str = "Any context";
+
 /// Shared style for tooltips with slight transparency
 pub fn tooltip_style(theme: &Theme) -> container::Style {
     let palette = theme.extended_palette();
@@ -52,10 +67,15 @@ pub fn root_view(app: &GuiApp) -> Element<'_, Message> {
             .into(),
         AppState::Onboarding | AppState::Settings => view_settings(app),
         AppState::Help => view_help(),
+        AppState::Trash => view_trash(app),
+        AppState::Stats => view_stats(app),
+        AppState::Pending => view_pending(app),
+        AppState::Conflict => view_conflict(app),
         AppState::Active => {
             // ... [Layout logic: No Change] ...
             const ITEM_HEIGHT_CAL: f32 = 44.0;
             const ITEM_HEIGHT_TAG: f32 = 34.0;
+            const ITEM_HEIGHT_SMART: f32 = 38.0;
             const SIDEBAR_CHROME: f32 = 110.0;
             const LOGO_SPACE_REQUIRED: f32 = 140.0;
 
@@ -66,6 +86,7 @@ pub fn root_view(app: &GuiApp) -> Element<'_, Message> {
                         .filter(|c| !app.disabled_calendars.contains(&c.href))
                         .count() as f32
                         * ITEM_HEIGHT_CAL
+                        + SmartList::ALL.len() as f32 * ITEM_HEIGHT_SMART
                 }
                 SidebarMode::Categories => {
                     app.store
@@ -287,6 +308,28 @@ fn view_sidebar(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
     .style(iced::widget::button::secondary)
     .on_press(Message::OpenHelp);
 
+    let trash_btn = iced::widget::button(
+        container(icon::icon(icon::TRASH).size(20))
+            .center_x(Length::Fill)
+            .center_y(Length::Fill),
+    )
+    .padding(0)
+    .height(Length::Fixed(40.0))
+    .width(Length::Fixed(50.0))
+    .style(iced::widget::button::secondary)
+    .on_press(Message::OpenTrash);
+
+    let stats_btn = iced::widget::button(
+        container(icon::icon(icon::CHART).size(20))
+            .center_x(Length::Fill)
+            .center_y(Length::Fill),
+    )
+    .padding(0)
+    .height(Length::Fixed(40.0))
+    .width(Length::Fixed(50.0))
+    .style(iced::widget::button::secondary)
+    .on_press(Message::OpenStats);
+
     // Apply tooltip_style
     let footer = row![
         tooltip(
@@ -296,6 +339,16 @@ fn view_sidebar(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
         )
         .style(tooltip_style)
         .delay(Duration::from_millis(700)),
+        tooltip(trash_btn, text("Trash").size(12), tooltip::Position::Top)
+            .style(tooltip_style)
+            .delay(Duration::from_millis(700)),
+        tooltip(
+            stats_btn,
+            text("Statistics").size(12),
+            tooltip::Position::Top
+        )
+        .style(tooltip_style)
+        .delay(Duration::from_millis(700)),
         tooltip(help_btn, text("Help").size(12), tooltip::Position::Top)
             .style(tooltip_style)
             .delay(Duration::from_millis(700))
@@ -345,6 +398,8 @@ fn view_sidebar(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
 fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
     let title_text = if app.loading {
         "Loading...".to_string()
+    } else if let Some(list) = app.smart_list {
+        list.label().to_string()
     } else if app.active_cal_href.is_none() {
         if app.selected_categories.is_empty() {
             "All Tasks".to_string()
@@ -362,7 +417,9 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
     let task_count = app.tasks.len();
     let mut subtitle = format!("{} Tasks", task_count);
 
-    if !app.search_value.is_empty() {
+    if let Some(err) = query_error(&app.search_value) {
+        subtitle.push_str(&format!(" | Filter: {}", err));
+    } else if !app.search_value.is_empty() {
         subtitle.push_str(&format!(" | Search: '{}'", app.search_value));
     } else if !app.selected_categories.is_empty() {
         let tag_count = app.selected_categories.len();
@@ -393,7 +450,8 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
         .align_y(iced::Alignment::Center);
 
     if app.unsynced_changes {
-        left_section = left_section.push(
+        // Click to see (and manage) what is waiting to be synced
+        let badge = MouseArea::new(
             container(text("Unsynced").size(10).color(Color::WHITE))
                 .style(|_| container::Style {
                     background: Some(Color::from_rgb(0.8, 0.5, 0.0).into()),
@@ -404,6 +462,81 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
                     ..Default::default()
                 })
                 .padding(3),
+        )
+        .on_press(Message::OpenPending)
+        .interaction(mouse::Interaction::Pointer);
+        left_section = left_section.push(
+            tooltip(
+                badge,
+                text("Pending changes").size(12),
+                tooltip::Position::Bottom,
+            )
+            .style(tooltip_style)
+            .delay(Duration::from_millis(700)),
+        );
+
+        // The queue looks stuck (too long or too old)
+        if let Some(warning) = &app.journal_warning {
+            let alert = MouseArea::new(
+                container(
+                    row![
+                        icon::icon(icon::OVERDUE).size(10).color(Color::WHITE),
+                        text(warning.clone()).size(10).color(Color::WHITE)
+                    ]
+                    .spacing(4)
+                    .align_y(iced::Alignment::Center),
+                )
+                .style(|_| container::Style {
+                    background: Some(Color::from_rgb(0.8, 0.2, 0.2).into()),
+                    border: iced::Border {
+                        radius: 4.0.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .padding(3),
+            )
+            .on_press(Message::OpenPending)
+            .interaction(mouse::Interaction::Pointer);
+            left_section = left_section.push(
+                tooltip(
+                    alert,
+                    text("Run `cfait doctor` to see what blocks the sync").size(12),
+                    tooltip::Position::Bottom,
+                )
+                .style(tooltip_style)
+                .delay(Duration::from_millis(700)),
+            );
+        }
+
+        // Only what hasn't reached the server can be undone
+        let undo_btn = iced::widget::button(icon::icon(icon::UNDO).size(14))
+            .style(iced::widget::button::text)
+            .padding(4)
+            .on_press(Message::Undo);
+        left_section = left_section.push(
+            tooltip(
+                undo_btn,
+                text("Undo last unsynced change").size(12),
+                tooltip::Position::Bottom,
+            )
+            .style(tooltip_style)
+            .delay(Duration::from_millis(700)),
+        );
+    }
+
+    if app.offline {
+        left_section = left_section.push(
+            container(text("Offline").size(10).color(Color::WHITE))
+                .style(|_| container::Style {
+                    background: Some(Color::from_rgb(0.4, 0.4, 0.4).into()),
+                    border: iced::Border {
+                        radius: 4.0.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .padding(3),
         );
     }
 
@@ -423,6 +556,74 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
         .delay(Duration::from_millis(700)),
     );
 
+    // Uploads queued changes, also in offline mode
+    let sync_btn = iced::widget::button(icon::icon(icon::UNSYNCED).size(16))
+        .style(iced::widget::button::text)
+        .padding(4)
+        .on_press(Message::SyncNow);
+    left_section = left_section.push(
+        tooltip(
+            sync_btn,
+            text("Sync now").size(12),
+            tooltip::Position::Bottom,
+        )
+        .style(tooltip_style)
+        .delay(Duration::from_millis(700)),
+    );
+
+    let offline_btn = iced::widget::button(icon::icon(icon::CLOUD_OFF).size(16))
+        .style(if app.offline {
+            iced::widget::button::primary
+        } else {
+            iced::widget::button::text
+        })
+        .padding(4)
+        .on_press(Message::ToggleOffline);
+    left_section = left_section.push(
+        tooltip(
+            offline_btn,
+            text(if app.offline {
+                "Go back online"
+            } else {
+                "Work offline"
+            })
+            .size(12),
+            tooltip::Position::Bottom,
+        )
+        .style(tooltip_style)
+        .delay(Duration::from_millis(700)),
+    );
+
+    if app.active_cal_href.is_some() {
+        let import_btn = iced::widget::button(icon::icon(icon::IMPORT).size(16))
+            .style(iced::widget::button::text)
+            .padding(4)
+            .on_press(Message::ImportIcs);
+        left_section = left_section.push(
+            tooltip(
+                import_btn,
+                text("Import .ics or a Taskwarrior export into this calendar").size(12),
+                tooltip::Position::Bottom,
+            )
+            .style(tooltip_style)
+            .delay(Duration::from_millis(700)),
+        );
+
+        let export_menu = iced::widget::pick_list(FileFormat::EXPORT, None, Message::Export)
+            .placeholder("Export")
+            .text_size(13)
+            .padding(4);
+        left_section = left_section.push(
+            tooltip(
+                export_menu,
+                text("Export this calendar to a file (CSV, Markdown: the tasks shown)").size(12),
+                tooltip::Position::Bottom,
+            )
+            .style(tooltip_style)
+            .delay(Duration::from_millis(700)),
+        );
+    }
+
     let subtitle_text = text(subtitle)
         .size(14)
         .color(Color::from_rgb(0.6, 0.6, 0.6));
@@ -432,7 +633,8 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
         .center_x(Length::Fill)
         .center_y(Length::Shrink);
 
-    let search_input = iced::widget::text_input("Search...", &app.search_value)
+    // ":" starts a filter expression, e.g. ":due<7d AND #work"
+    let search_input = iced::widget::text_input("Search or :filter...", &app.search_value)
         .on_input(Message::SearchChanged)
         .padding(5)
         .size(14)
@@ -450,9 +652,42 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
     ]
     .spacing(0);
 
-    let right_section = row![search_input, window_controls]
-        .spacing(10)
-        .align_y(iced::Alignment::Center);
+    let sort_picker = iced::widget::pick_list(
+        SortOrder::ALL,
+        Some(app.sort_order),
+        Message::SortOrderChanged,
+    )
+    .text_size(13)
+    .padding(5);
+
+    let group_picker =
+        iced::widget::pick_list(GroupBy::ALL, Some(app.group_by), Message::GroupByChanged)
+            .text_size(13)
+            .padding(5);
+
+    let mut right_section = row![].spacing(10).align_y(iced::Alignment::Center);
+
+    // Context filter, only once some task has a context
+    let contexts = app.store.get_all_contexts(&app.hidden_calendars);
+    if !contexts.is_empty() || app.selected_context.is_some() {
+        let mut options = vec![ANY_CONTEXT.to_string()];
+        options.extend(contexts.into_iter().map(|(ctx, _)| format!("@@{}", ctx)));
+        let selected = app
+            .selected_context
+            .as_ref()
+            .map(|c| format!("@@{}", c))
+            .unwrap_or_else(|| ANY_CONTEXT.to_string());
+        right_section = right_section.push(
+            iced::widget::pick_list(options, Some(selected), Message::ContextFilterChanged)
+                .text_size(13)
+                .padding(5),
+        );
+    }
+    let right_section = right_section
+        .push(group_picker)
+        .push(sort_picker)
+        .push(search_input)
+        .push(window_controls);
 
     let header_row = row![left_section, middle_container, right_section]
         .spacing(10)
@@ -467,11 +702,15 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
     let header_drag_area = MouseArea::new(header_row).on_press(Message::WindowDragged);
 
     let mut export_ui: Element<'_, Message> = row![].into();
-    if app.active_cal_href.as_deref() == Some(LOCAL_CALENDAR_HREF) {
+    if app
+        .active_cal_href
+        .as_deref()
+        .is_some_and(is_local_calendar)
+    {
         let targets: Vec<_> = app
             .calendars
             .iter()
-            .filter(|c| c.href != LOCAL_CALENDAR_HREF && !app.disabled_calendars.contains(&c.href))
+            .filter(|c| !is_local_calendar(&c.href) && !app.disabled_calendars.contains(&c.href))
             .collect();
         if !targets.is_empty() {
             let mut row = row![
@@ -550,14 +789,29 @@ fn view_main_content(app: &GuiApp, show_logo: bool) -> Element<'_, Message> {
         );
     }
 
-    let tasks_view = column(
-        app.tasks
-            .iter()
-            .enumerate()
-            .map(|(real_index, task)| view_task_row(app, real_index, task))
-            .collect::<Vec<_>>(),
-    )
-    .spacing(1);
+    let mut rows: Vec<Element<'_, Message>> = Vec::with_capacity(app.tasks.len());
+    let mut headers = app.group_headers.iter().peekable();
+    for (real_index, task) in app.tasks.iter().enumerate() {
+        // Section title of a grouped view, before its first task
+        if let Some((_, group)) = headers.next_if(|(start, _)| *start == real_index) {
+            rows.push(
+                container(
+                    text(group.as_str())
+                        .size(13)
+                        .color(Color::from_rgb(0.5, 0.6, 0.8)),
+                )
+                .padding(iced::Padding {
+                    top: if real_index == 0 { 2.0 } else { 10.0 },
+                    bottom: 2.0,
+                    left: 6.0,
+                    right: 0.0,
+                })
+                .into(),
+            );
+        }
+        rows.push(view_task_row(app, real_index, task));
+    }
+    let tasks_view = column(rows).spacing(1);
     main_col = main_col.push(
         scrollable(tasks_view)
             .height(Length::Fill)
@@ -617,11 +871,23 @@ fn view_input_area(app: &GuiApp) -> Element<'_, Message> {
         let save_btn = iced::widget::button(text("Save").size(16))
             .style(iced::widget::button::primary)
             .on_press(Message::SubmitTask);
+        let repeat_btn = iced::widget::button(
+            row![icon::icon(icon::REPEAT).size(14), text("Repeat").size(16)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .style(if app.recurrence_draft.is_some() {
+            iced::widget::button::primary
+        } else {
+            iced::widget::button::secondary
+        })
+        .on_press(Message::ToggleRecurrenceEditor);
         let top_bar = row![
             text("Editing")
                 .size(14)
                 .color(Color::from_rgb(0.7, 0.7, 1.0)),
             Space::new().width(Length::Fill),
+            repeat_btn,
             cancel_btn,
             save_btn
         ]
@@ -658,9 +924,19 @@ fn view_input_area(app: &GuiApp) -> Element<'_, Message> {
                     .into();
             }
         }
-        column![top_bar, input_title, input_desc, move_element]
-            .spacing(10)
-            .into()
+        let recurrence_element: Element<'_, Message> = match &app.recurrence_draft {
+            Some(draft) => view_recurrence_builder(draft),
+            None => row![].into(),
+        };
+        column![
+            top_bar,
+            input_title,
+            input_desc,
+            recurrence_element,
+            move_element
+        ]
+        .spacing(10)
+        .into()
     } else {
         column![input_title].spacing(5).into()
     };
@@ -0,0 +1,158 @@
+// File: src/gui/view/pending.rs
+use crate::gui::icon;
+use crate::gui::message::Message;
+use crate::gui::state::GuiApp;
+use crate::journal::Action;
+
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Color, Element, Length};
+
+const COL_MUTED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const COL_FAILED: Color = Color::from_rgb(0.9, 0.4, 0.4);
+
+pub fn view_pending(app: &GuiApp) -> Element<'_, Message> {
+    let title = row![
+        icon::icon(icon::UNSYNCED).size(28),
+        text("Pending changes").size(28)
+    ]
+    .spacing(15)
+    .align_y(iced::Alignment::Center);
+
+    // The calendar may have been removed since
+    let cal_name = |href: &str| -> String {
+        app.calendars
+            .iter()
+            .find(|c| c.href == href)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| href.to_string())
+    };
+
+    let mut list = column![].spacing(8);
+    if app.pending.is_empty() && app.failed.is_empty() {
+        list = list.push(text("Everything is synced.").color(COL_MUTED));
+    }
+    for (idx, action) in app.pending.iter().enumerate() {
+        let mut details = format!(
+            "{} \u{2022} {}",
+            action.kind(),
+            cal_name(action.calendar_href())
+        );
+        if let Action::Move(_, dest) = action {
+            details.push_str(&format!(" \u{2192} {}", cal_name(dest)));
+        }
+
+        let arrow = |glyph: char, up: bool, enabled: bool| {
+            button(icon::icon(glyph).size(14))
+                .padding([4, 8])
+                .style(button::text)
+                .on_press_maybe(enabled.then_some(Message::MovePending(idx, up)))
+        };
+        let drop_btn = button(
+            row![icon::icon(icon::CROSS).size(14), text("Drop").size(14)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .padding([4, 10])
+        .style(button::danger)
+        .on_press(Message::DropPending(idx));
+
+        list = list.push(
+            row![
+                column![
+                    text(&action.task().summary).size(16),
+                    text(details).size(12).color(COL_MUTED),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                arrow(icon::ARROW_UP, true, idx > 0),
+                arrow(icon::ARROW_DOWN, false, idx + 1 < app.pending.len()),
+                drop_btn,
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    // Given up on by the sync: only sent again when retried
+    if !app.failed.is_empty() {
+        list = list.push(text("Failed").size(18));
+    }
+    for (idx, entry) in app.failed.iter().enumerate() {
+        let action = &entry.action;
+        let retry_btn = button(
+            row![icon::icon(icon::REFRESH).size(14), text("Retry").size(14)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .padding([4, 10])
+        .style(button::secondary)
+        .on_press(Message::RetryFailed(idx));
+        let drop_btn = button(
+            row![icon::icon(icon::CROSS).size(14), text("Drop").size(14)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .padding([4, 10])
+        .style(button::danger)
+        .on_press(Message::DropFailed(idx));
+
+        list = list.push(
+            row![
+                column![
+                    text(&action.task().summary).size(16),
+                    text(format!(
+                        "{} \u{2022} {}",
+                        action.kind(),
+                        cal_name(action.calendar_href())
+                    ))
+                    .size(12)
+                    .color(COL_MUTED),
+                    text(&entry.error).size(12).color(COL_FAILED),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                retry_btn,
+                drop_btn,
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let close_btn = button(
+        text("Close")
+            .size(16)
+            .width(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Center),
+    )
+    .padding(12)
+    .width(Length::Fixed(200.0))
+    .style(button::primary)
+    .on_press(Message::ClosePending);
+
+    let content = column![
+        title,
+        text(
+            "Changes waiting to be sent, oldest first. Drop one the server keeps \
+             rejecting: the task goes back to the server version on the next sync."
+        )
+        .size(12)
+        .color(COL_MUTED),
+        list,
+        container(close_btn)
+            .width(Length::Fill)
+            .center_x(Length::Fill)
+            .padding(20),
+    ]
+    .spacing(20)
+    .padding(20)
+    .max_width(800);
+
+    scrollable(
+        container(content)
+            .width(Length::Fill)
+            .center_x(Length::Fill),
+    )
+    .height(Length::Fill)
+    .into()
+}
@@ -0,0 +1,143 @@
+// File: src/gui/view/recurrence.rs
+use crate::gui::icon;
+use crate::gui::message::Message;
+use crate::gui::state::RecurrenceDraft;
+use crate::model::Freq;
+
+use chrono::Weekday;
+use iced::widget::{button, checkbox, column, row, text, text_input};
+use iced::{Color, Element, Length};
+
+const COL_MUTED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+fn choice<'a>(label: &'a str, selected: bool, msg: Message) -> Element<'a, Message> {
+    button(text(label).size(12))
+        .padding([4, 8])
+        .style(if selected {
+            button::primary
+        } else {
+            button::secondary
+        })
+        .on_press(msg)
+        .into()
+}
+
+pub fn view_recurrence_builder(draft: &RecurrenceDraft) -> Element<'_, Message> {
+    let current = draft.rule.as_ref().map(|r| r.freq);
+
+    let mut freq_row = row![choice(
+        "Never",
+        current.is_none(),
+        Message::RecurrenceFreqChanged(None)
+    )]
+    .spacing(5);
+    for freq in Freq::ALL {
+        freq_row = freq_row.push(choice(
+            freq.label(),
+            current == Some(freq),
+            Message::RecurrenceFreqChanged(Some(freq)),
+        ));
+    }
+
+    let mut content = column![
+        row![
+            icon::icon(icon::REPEAT).size(14),
+            text("Repeat").size(14),
+            freq_row
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center)
+    ]
+    .spacing(8);
+
+    if let Some(rule) = &draft.rule {
+        let interval = row![
+            text("Every").size(12),
+            text_input("1", &draft.interval)
+                .on_input(Message::RecurrenceIntervalChanged)
+                .size(12)
+                .padding(4)
+                .width(Length::Fixed(50.0)),
+            text(format!("{}(s)", rule.freq.unit())).size(12),
+        ]
+        .spacing(5)
+        .align_y(iced::Alignment::Center);
+
+        let mut days = row![text("On").size(12)]
+            .spacing(5)
+            .align_y(iced::Alignment::Center);
+        for day in WEEKDAYS {
+            days = days.push(choice(
+                weekday_label(day),
+                rule.has_day(day),
+                Message::RecurrenceDayToggled(day),
+            ));
+        }
+
+        let end = row![
+            text("Ends on").size(12),
+            text_input("YYYY-MM-DD", &draft.until)
+                .on_input(Message::RecurrenceUntilChanged)
+                .size(12)
+                .padding(4)
+                .width(Length::Fixed(110.0)),
+            text("or after").size(12),
+            text_input("", &draft.count)
+                .on_input(Message::RecurrenceCountChanged)
+                .size(12)
+                .padding(4)
+                .width(Length::Fixed(50.0)),
+            text("times").size(12),
+        ]
+        .spacing(5)
+        .align_y(iced::Alignment::Center);
+
+        let after_completion = checkbox(draft.after_completion)
+            .label("Count from the completion instead of the due/start date")
+            .text_size(12)
+            .size(16)
+            .on_toggle(Message::RecurrenceAfterCompletionToggled);
+
+        let preview = match draft.build() {
+            Ok(Some(r)) if draft.after_completion => {
+                text(format!("{}, after completion", r.summary()))
+                    .size(12)
+                    .color(COL_MUTED)
+            }
+            Ok(Some(r)) => text(r.summary()).size(12).color(COL_MUTED),
+            Ok(None) => text("").size(12),
+            Err(e) => text(e).size(12).color(Color::from_rgb(0.9, 0.4, 0.4)),
+        };
+
+        content = content
+            .push(interval)
+            .push(days)
+            .push(end)
+            .push(after_completion)
+            .push(preview);
+    }
+
+    content.into()
+}
+
+fn weekday_label(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Mo",
+        Weekday::Tue => "Tu",
+        Weekday::Wed => "We",
+        Weekday::Thu => "Th",
+        Weekday::Fri => "Fr",
+        Weekday::Sat => "Sa",
+        Weekday::Sun => "Su",
+    }
+}
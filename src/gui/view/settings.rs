@@ -1,6 +1,8 @@
+use crate::client::quota::format_bytes;
 use crate::gui::icon;
 use crate::gui::message::Message;
 use crate::gui::state::{AppState, GuiApp};
+use crate::storage::{LOCAL_CALENDAR_HREF, is_local_calendar};
 
 use iced::widget::{Space, button, checkbox, column, container, row, scrollable, text, text_input};
 use iced::{Color, Element, Length};
@@ -19,8 +21,22 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
         text("")
     };
 
+    let config_warnings: Element<_> = if is_settings && !app.config_warnings.is_empty() {
+        column(app.config_warnings.iter().map(|w| {
+            text(format!("Config: {}", w))
+                .size(14)
+                .color(Color::from_rgb(0.9, 0.6, 0.2))
+                .into()
+        }))
+        .spacing(5)
+        .into()
+    } else {
+        Space::new().width(0).into()
+    };
+
     let cal_names: Vec<String> = app.calendars.iter().map(|c| c.name.clone()).collect();
-    let picker: Element<_> = if !cal_names.is_empty() && is_settings {
+    // On the welcome screen, filled by "Test connection"
+    let picker: Element<_> = if !cal_names.is_empty() {
         column![
             text("Default calendar:"),
             iced::widget::pick_list(
@@ -139,17 +155,37 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
             // Logic inverted: Checkbox checked = Enabled (!Disabled)
             let is_enabled = !app.disabled_calendars.contains(&cal.href);
 
-            let row_content = row![
+            let mut row_content = row![
                 checkbox(is_enabled)
                     .label(&cal.name)
                     // When toggled, we send !v because the msg is "ToggleDisabled"
                     .on_toggle(move |v| Message::ToggleCalendarDisabled(cal.href.clone(), !v))
                     .width(Length::Fill)
             ];
+            if is_local_calendar(&cal.href) && cal.href != LOCAL_CALENDAR_HREF {
+                row_content = row_content.push(
+                    button(icon::icon(icon::CROSS).size(12))
+                        .style(button::danger)
+                        .padding(5)
+                        .on_press(Message::DeleteLocalCalendar(cal.href.clone())),
+                );
+            }
 
             col = col.push(row_content.spacing(10).align_y(iced::Alignment::Center));
         }
 
+        col = col.push(
+            row![
+                text_input("New local calendar", &app.local_cal_input)
+                    .on_input(Message::LocalCalendarInput)
+                    .on_submit(Message::AddLocalCalendar)
+                    .padding(5)
+                    .width(Length::Fill),
+                button("Add").padding(5).on_press(Message::AddLocalCalendar)
+            ]
+            .spacing(10),
+        );
+
         container(col)
             .padding(10)
             .style(|_| container::Style {
@@ -176,6 +212,14 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
                 .style(button::secondary)
                 .on_press(Message::ObSubmitOffline),
         );
+        buttons = buttons.push(
+            button("Test connection")
+                .padding(10)
+                .style(button::secondary)
+                .on_press_maybe(
+                    (!app.ob_url.trim().is_empty()).then_some(Message::ObTestConnection),
+                ),
+        );
     }
 
     if is_settings {
@@ -198,6 +242,36 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
         .padding(10)
         .on_press(Message::ObSubmit),
     );
+    let quota_ui: Element<_> = match &app.quota {
+        Some(quota) if is_settings => text(format!("Server storage: {}", quota.summary()))
+            .size(14)
+            .color(Color::from_rgb(0.6, 0.6, 0.6))
+            .into(),
+        _ => Space::new().width(0).into(),
+    };
+
+    let cache_ui: Element<_> = match &app.cache_stats {
+        Some(stats) if is_settings => row![
+            text(format!(
+                "Cache: {} calendar(s), {} task(s), {}",
+                stats.calendars,
+                stats.tasks,
+                format_bytes(stats.bytes)
+            ))
+            .size(14)
+            .color(Color::from_rgb(0.6, 0.6, 0.6))
+            .width(Length::Fill),
+            button(text("Clear cache").size(14))
+                .padding([4, 10])
+                .style(button::secondary)
+                .on_press_maybe((stats.calendars > 0).then_some(Message::ClearCache)),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center)
+        .into(),
+        _ => Space::new().width(0).into(),
+    };
+
     let insecure_check = checkbox(app.ob_insecure)
         .label("Allow Insecure SSL (e.g. self-signed)")
         .on_toggle(Message::ObInsecureToggled)
@@ -219,6 +293,8 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
             .secure(true)
             .padding(10),
         insecure_check,
+        quota_ui,
+        cache_ui,
         picker,
         prefs,
         sorting_ui,
@@ -229,7 +305,7 @@ pub fn view_settings(app: &GuiApp) -> Element<'_, Message> {
     .spacing(15)
     .max_width(500);
 
-    let content = column![title, error, form]
+    let content = column![title, error, config_warnings, form]
         .spacing(20)
         .align_x(iced::Alignment::Center);
 
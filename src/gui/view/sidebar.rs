@@ -4,7 +4,7 @@ use crate::color_utils;
 use crate::gui::icon;
 use crate::gui::message::Message;
 use crate::gui::state::GuiApp;
-use crate::store::UNCATEGORIZED_ID;
+use crate::store::{SmartList, UNCATEGORIZED_ID};
 use iced::never;
 use iced::widget::{Space, button, checkbox, column, container, row, text, toggler, tooltip};
 use iced::{Color, Element, Length, Theme};
@@ -41,6 +41,42 @@ pub fn view_sidebar_calendars(app: &GuiApp) -> Element<'_, Message> {
         .style(toggler_style);
     let toggle_container = container(toggle_all).padding(5);
 
+    // Smart lists, across the visible calendars
+    let mut smart_lists = column![].spacing(0);
+    for list in SmartList::ALL {
+        let (list_icon, color) = match list {
+            SmartList::Today => (icon::TODAY, Color::from_rgb(0.3, 0.6, 1.0)),
+            SmartList::Upcoming => (icon::UPCOMING, Color::from_rgb(0.6, 0.4, 0.9)),
+            SmartList::Overdue => (icon::OVERDUE, Color::from_rgb(0.9, 0.3, 0.3)),
+            SmartList::Flagged => (icon::FLAG, Color::from_rgb(1.0, 0.6, 0.0)),
+        };
+        let is_active = app.smart_list == Some(list);
+        let list_row = row![
+            icon::icon(list_icon).size(14).color(color),
+            text(format!(
+                "{} ({})",
+                list.label(),
+                app.store.count_smart_list(list, &app.hidden_calendars)
+            ))
+            .size(16),
+        ]
+        .spacing(10)
+        .align_y(iced::Alignment::Center);
+        let list_btn = button(list_row)
+            .width(Length::Fill)
+            .padding(8)
+            .on_press(Message::ShowSmartList((!is_active).then_some(list)));
+        smart_lists = smart_lists.push(if is_active {
+            list_btn.style(move |_theme: &Theme, _status| button::Style {
+                text_color: color,
+                background: Some(Color { a: 0.05, ..color }.into()),
+                ..button::Style::default()
+            })
+        } else {
+            list_btn.style(button::text)
+        });
+    }
+
     let list = column(
         app.calendars
             .iter()
@@ -92,7 +128,33 @@ pub fn view_sidebar_calendars(app: &GuiApp) -> Element<'_, Message> {
                 .style(tooltip_style)
                 .delay(Duration::from_millis(700));
 
-                let mut label = button(text(&cal.name).size(16))
+                let mut name_row = row![text(&cal.name).size(16)]
+                    .spacing(6)
+                    .align_y(iced::Alignment::Center);
+                if cal.shared {
+                    let badge = match &cal.owner {
+                        Some(owner) => format!("shared by {}", owner),
+                        None => "shared".to_string(),
+                    };
+                    let badge =
+                        container(text(badge).size(10))
+                            .padding([1, 5])
+                            .style(|theme: &Theme| container::Style {
+                                background: Some(
+                                    theme.extended_palette().background.weak.color.into(),
+                                ),
+                                border: iced::Border {
+                                    radius: 4.0.into(),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            });
+                    name_row = name_row.push(badge);
+                }
+                if cal.read_only {
+                    name_row = name_row.push(icon::icon(icon::LOCK).size(12));
+                }
+                let mut label = button(name_row)
                     .width(Length::Fill)
                     .padding(10)
                     .on_press(Message::SelectCalendar(cal.href.clone()));
@@ -135,7 +197,9 @@ pub fn view_sidebar_calendars(app: &GuiApp) -> Element<'_, Message> {
     .spacing(2)
     .width(Length::Fill);
 
-    column![toggle_container, list].spacing(5).into()
+    column![toggle_container, smart_lists, list]
+        .spacing(5)
+        .into()
 }
 
 // ... DurationOpt (unchanged) ...
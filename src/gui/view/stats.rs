@@ -0,0 +1,183 @@
+// File: src/gui/view/stats.rs
+use crate::gui::icon;
+use crate::gui::message::Message;
+use crate::gui::state::GuiApp;
+
+use iced::widget::{Space, button, column, container, row, scrollable, text};
+use iced::{Color, Element, Length};
+
+const COL_MUTED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+const COL_BAR: Color = Color::from_rgb(0.3, 0.6, 0.4);
+const COL_OVERDUE: Color = Color::from_rgb(0.9, 0.4, 0.4);
+const BAR_WIDTH: f32 = 200.0;
+const TOP_TAGS: usize = 15;
+
+pub fn view_stats(app: &GuiApp) -> Element<'_, Message> {
+    let stats = &app.stats;
+    let title = row![
+        icon::icon(icon::CHART).size(28),
+        text("Statistics").size(28)
+    ]
+    .spacing(15)
+    .align_y(iced::Alignment::Center);
+
+    let mut summary = row![
+        figure(stats.total, "tasks", Color::WHITE),
+        figure(stats.open, "open", Color::WHITE),
+        figure(stats.total - stats.open, "done", COL_BAR),
+    ]
+    .spacing(30);
+    if stats.overdue > 0 {
+        summary = summary.push(figure(stats.overdue, "overdue", COL_OVERDUE));
+    }
+
+    // Weekly burndown: completion bar, then created/completed/open
+    let mut weeks = column![
+        row![
+            text("Week of").size(12).color(COL_MUTED).width(90),
+            text("Completed")
+                .size(12)
+                .color(COL_MUTED)
+                .width(BAR_WIDTH + 50.0),
+            text("Created / done / open").size(12).color(COL_MUTED),
+        ]
+        .spacing(10)
+    ]
+    .spacing(4);
+    for week in &stats.weeks {
+        let rate = week.completion_rate() as f32;
+        weeks = weeks.push(
+            row![
+                text(week.start.format("%Y-%m-%d").to_string())
+                    .size(13)
+                    .width(90),
+                bar(rate),
+                text(format!("{:.0}%", rate * 100.0)).size(13).width(40),
+                text(format!(
+                    "{} / {} / {}",
+                    week.created, week.completed, week.open
+                ))
+                .size(13),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let max = stats.by_calendar.iter().map(|(_, o, d)| o + d).max();
+    let mut calendars = column![].spacing(4);
+    for (name, open, done) in &stats.by_calendar {
+        let share = max.map_or(0.0, |m| (open + done) as f32 / m.max(1) as f32);
+        calendars = calendars.push(count_row(
+            name,
+            format!("{} open, {} done", open, done),
+            share,
+        ));
+    }
+
+    let max = stats.by_status.values().max().copied().unwrap_or(1);
+    let mut statuses = column![].spacing(4);
+    for (status, count) in &stats.by_status {
+        statuses = statuses.push(count_row(
+            status,
+            count.to_string(),
+            *count as f32 / max as f32,
+        ));
+    }
+
+    let mut tags: Vec<_> = stats.by_tag.iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let max = tags.first().map_or(1, |(_, c)| **c);
+    let mut tag_list = column![].spacing(4);
+    for (tag, count) in tags.into_iter().take(TOP_TAGS) {
+        tag_list = tag_list.push(count_row(
+            &format!("#{}", tag),
+            count.to_string(),
+            *count as f32 / max as f32,
+        ));
+    }
+
+    let close_btn = button(
+        text("Close statistics")
+            .size(16)
+            .width(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Center),
+    )
+    .padding(12)
+    .width(Length::Fixed(200.0))
+    .style(button::primary)
+    .on_press(Message::CloseStats);
+
+    let content = column![
+        title,
+        text("Visible calendars, as of the last sync.")
+            .size(12)
+            .color(COL_MUTED),
+        summary,
+        section("Last weeks"),
+        weeks,
+        section("By status"),
+        statuses,
+        section("By calendar"),
+        calendars,
+        section("Open tasks by tag"),
+        tag_list,
+        container(close_btn)
+            .width(Length::Fill)
+            .center_x(Length::Fill)
+            .padding(20),
+    ]
+    .spacing(15)
+    .padding(20)
+    .max_width(800);
+
+    scrollable(
+        container(content)
+            .width(Length::Fill)
+            .center_x(Length::Fill),
+    )
+    .height(Length::Fill)
+    .into()
+}
+
+fn figure(value: usize, label: &str, color: Color) -> Element<'static, Message> {
+    column![
+        text(value.to_string()).size(26).color(color),
+        text(label.to_string()).size(12).color(COL_MUTED),
+    ]
+    .align_x(iced::Alignment::Center)
+    .into()
+}
+
+fn section(title: &str) -> Element<'static, Message> {
+    text(title.to_string()).size(18).into()
+}
+
+fn count_row(label: &str, value: String, share: f32) -> Element<'static, Message> {
+    row![
+        text(label.to_string()).size(13).width(160),
+        bar(share),
+        text(value).size(13),
+    ]
+    .spacing(10)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+// Horizontal bar, `share` of BAR_WIDTH (0.0-1.0)
+fn bar(share: f32) -> Element<'static, Message> {
+    let filled = BAR_WIDTH * share.clamp(0.0, 1.0);
+    row![
+        container(Space::new().width(filled).height(10)).style(|_| container::Style {
+            background: Some(COL_BAR.into()),
+            ..Default::default()
+        }),
+        container(Space::new().width(BAR_WIDTH - filled).height(10)).style(|_| {
+            container::Style {
+                background: Some(Color::from_rgba(1.0, 1.0, 1.0, 0.08).into()),
+                ..Default::default()
+            }
+        }),
+    ]
+    .into()
+}
@@ -4,14 +4,16 @@ use crate::color_utils;
 use crate::gui::icon;
 use crate::gui::message::Message;
 use crate::gui::state::GuiApp;
-use crate::model::Task as TodoTask;
+use crate::model::matcher::{highlight_ranges, search_text_terms};
+use crate::model::tracking::format_minutes;
+use crate::model::{CustomStatus, SortOrder, Task as TodoTask};
 use std::collections::HashSet;
 use std::time::Duration;
 
 use super::tooltip_style;
-use iced::widget::{Space, button, column, container, row, scrollable, text, tooltip};
+use iced::widget::{Space, button, checkbox, column, container, row, scrollable, text, tooltip};
 pub use iced::widget::{rich_text, span};
-use iced::{Border, Color, Element, Length, Theme};
+use iced::{Border, Color, Element, Length, Theme, never};
 
 pub fn view_task_row<'a>(
     app: &'a GuiApp,
@@ -23,31 +25,15 @@ pub fn view_task_row<'a>(
     let color = if is_blocked {
         Color::from_rgb(0.5, 0.5, 0.5)
     } else {
-        // Priority Gradient: Red (Hot) -> Yellow (Normal) -> Purple/Slate (Cold)
-        match task.priority {
-            // 1: Red
-            1 => Color::from_rgb(1.0, 0.2, 0.2),
-            // 2: Orange-Red
-            2 => Color::from_rgb(1.0, 0.4, 0.2),
-            // 3: Orange
-            3 => Color::from_rgb(1.0, 0.6, 0.2),
-            // 4: Amber
-            4 => Color::from_rgb(1.0, 0.8, 0.2),
-            // 5: Yellow
-            5 => Color::from_rgb(1.0, 1.0, 0.2),
-            // 6: Pale Khaki (Desaturating)
-            6 => Color::from_rgb(0.85, 0.85, 0.55),
-            // 7: Light Steel Blue (Cooling)
-            7 => Color::from_rgb(0.7, 0.75, 0.85),
-            // 8: Slate / Muted Purple
-            8 => Color::from_rgb(0.65, 0.6, 0.8),
-            // 9: Greyish Lavender
-            9 => Color::from_rgb(0.6, 0.55, 0.65),
-            // 0: White
-            _ => Color::WHITE,
+        // Priority gradient from the theme: red (hot) -> yellow (normal) -> purple (cold)
+        match app.theme.priority_rgb(task.priority) {
+            Some((r, g, b)) => Color::from_rgb8(r, g, b),
+            // No priority: the theme's text color
+            None => app.theme().palette().text,
         }
     };
-    let show_indent = app.active_cal_href.is_some() && app.search_value.is_empty();
+    let show_indent =
+        app.active_cal_href.is_some() && app.search_value.is_empty() && app.smart_list.is_none();
     let indent_size = if show_indent { task.depth * 12 } else { 0 };
     let indent = Space::new().width(Length::Fixed(indent_size as f32));
 
@@ -124,6 +110,17 @@ pub fn view_task_row<'a>(
     let build_tags = || -> Element<'a, Message> {
         let mut tags_row: iced::widget::Row<'_, Message> = row![].spacing(3);
 
+        if task.flagged {
+            tags_row = tags_row.push(
+                container(
+                    icon::icon(icon::FLAG)
+                        .size(14)
+                        .color(Color::from_rgb(1.0, 0.6, 0.0)),
+                )
+                .padding(3),
+            );
+        }
+
         if is_blocked {
             tags_row = tags_row.push(
                 container(text("[Blocked]").size(12).color(Color::WHITE))
@@ -139,6 +136,30 @@ pub fn view_task_row<'a>(
             );
         }
 
+        if let Some(name) = &task.custom_status {
+            let (r, g, b) = CustomStatus::find(&app.custom_statuses, name)
+                .and_then(|s| s.color.as_deref())
+                .and_then(color_utils::parse_hex_to_floats)
+                .unwrap_or_else(|| color_utils::generate_color(name));
+            let text_color = if color_utils::is_dark(r, g, b) {
+                Color::WHITE
+            } else {
+                Color::BLACK
+            };
+            tags_row = tags_row.push(
+                container(text(format!("[{}]", name)).size(12).color(text_color))
+                    .style(move |_| container::Style {
+                        background: Some(Color::from_rgb(r, g, b).into()),
+                        border: iced::Border {
+                            radius: 4.0.into(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .padding(3),
+            );
+        }
+
         // --- FIXED: Consolidated hiding logic ---
         // 1. Calculate tags to hide because they are inherited from the parent task.
         let mut tags_to_hide: HashSet<String> = if show_indent && let Some(p_uid) = &task.parent_uid
@@ -214,6 +235,14 @@ pub fn view_task_row<'a>(
                     .on_press(Message::JumpToTag(cat.clone())),
             );
         }
+        for ctx in &task.contexts {
+            tags_row = tags_row.push(
+                button(text(format!("@@{}", ctx)).size(12))
+                    .style(button::secondary)
+                    .padding(3)
+                    .on_press(Message::ContextFilterChanged(format!("@@{}", ctx))),
+            );
+        }
         if let Some(mins) = task.estimated_duration {
             let label = if mins >= 525600 {
                 format!("{}y", mins / 525600)
@@ -247,24 +276,43 @@ pub fn view_task_row<'a>(
         tags_row.into()
     };
 
-    let date_text: Element<'a, Message> = match task.due {
-        Some(d) => container(
-            text(d.format("%Y-%m-%d").to_string())
-                .size(14)
-                .color(Color::from_rgb(0.5, 0.5, 0.5)),
-        )
-        .width(Length::Fixed(80.0))
-        .into(),
+    let date_text: Element<'a, Message> = match task.format_due("%Y-%m-%d") {
+        Some(d) => {
+            // Room for the time, when there is one
+            let width = if d.len() > 10 { 120.0 } else { 80.0 };
+            let due_color = match app.theme.due_soon_rgb() {
+                Some((r, g, b)) if app.theme.is_due_soon(task, chrono::Utc::now()) => {
+                    Color::from_rgb8(r, g, b)
+                }
+                _ => Color::from_rgb(0.5, 0.5, 0.5),
+            };
+            container(text(d).size(14).color(due_color))
+                .width(Length::Fixed(width))
+                .into()
+        }
         None => Space::new().width(Length::Fixed(0.0)).into(),
     };
 
     let has_desc = !task.description.is_empty();
-    let has_deps = !task.dependencies.is_empty();
+    let has_deps = !task.dependencies.is_empty() || !app.store.blocked_tasks(&task.uid).is_empty();
+    let has_attachments = !task.attachments.is_empty();
     let is_expanded = app.expanded_tasks.contains(&task.uid);
+    let read_only = app
+        .calendars
+        .iter()
+        .any(|c| c.href == task.calendar_href && c.read_only);
 
     let mut actions = row![].spacing(3);
 
-    if has_desc || has_deps {
+    // Writable tasks always get details, that's where files are attached
+    if has_desc
+        || has_deps
+        || has_attachments
+        || task.completed_at.is_some()
+        || task.rrule.is_some()
+        || !task.time_sessions.is_empty()
+        || !read_only
+    {
         let info_btn = button(icon::icon(icon::INFO).size(12))
             .style(if is_expanded {
                 button::primary
@@ -288,68 +336,170 @@ pub fn view_task_row<'a>(
         actions = actions.push(Space::new().width(Length::Fixed(25.0)));
     }
 
-    if let Some(yanked) = &app.yanked_uid {
-        if *yanked != task.uid {
-            let block_btn = button(icon::icon(icon::BLOCKED).size(14))
+    // Shared read-only calendars: nothing to do but look
+    if !read_only {
+        if let Some(yanked) = &app.yanked_uid {
+            if *yanked != task.uid {
+                let block_btn = button(icon::icon(icon::BLOCKED).size(14))
+                    .style(action_style)
+                    .padding(4)
+                    .on_press(Message::AddDependency(task.uid.clone()));
+                actions = actions.push(
+                    tooltip(
+                        block_btn,
+                        text("Block (depends on)").size(12),
+                        tooltip::Position::Top,
+                    )
+                    .style(tooltip_style)
+                    .delay(Duration::from_millis(700)),
+                );
+                let child_btn = button(icon::icon(icon::CHILD).size(14))
+                    .style(action_style)
+                    .padding(4)
+                    .on_press(Message::MakeChild(task.uid.clone()));
+                actions = actions.push(
+                    tooltip(
+                        child_btn,
+                        text("Make child").size(12),
+                        tooltip::Position::Top,
+                    )
+                    .style(tooltip_style)
+                    .delay(Duration::from_millis(700)),
+                );
+            } else {
+                let unlink_btn = button(icon::icon(icon::UNLINK).size(14))
+                    .style(button::primary)
+                    .padding(4)
+                    .on_press(Message::ClearYank);
+                actions = actions.push(
+                    tooltip(unlink_btn, text("Unlink").size(12), tooltip::Position::Top)
+                        .style(tooltip_style)
+                        .delay(Duration::from_millis(700)),
+                );
+                let create_child_btn = button(icon::icon(icon::CREATE_CHILD).size(14))
+                    .style(button::primary)
+                    .padding(4)
+                    .on_press(Message::StartCreateChild(task.uid.clone()));
+                actions = actions.push(
+                    tooltip(
+                        create_child_btn,
+                        text("Create subtask").size(12),
+                        tooltip::Position::Top,
+                    )
+                    .style(tooltip_style)
+                    .delay(Duration::from_millis(700)),
+                );
+
+                // ELEVATOR UP (Moved here as requested)
+                if task.parent_uid.is_some() {
+                    let lift_btn = button(icon::icon(icon::ELEVATOR_UP).size(14))
+                        .style(action_style)
+                        .padding(4)
+                        .on_press(Message::RemoveParent(task.uid.clone()));
+                    actions = actions.push(
+                        tooltip(
+                            lift_btn,
+                            text("Promote (remove parent)").size(12),
+                            tooltip::Position::Top,
+                        )
+                        .style(tooltip_style)
+                        .delay(Duration::from_millis(700)),
+                    );
+                }
+            }
+        } else {
+            let link_btn = button(icon::icon(icon::LINK).size(14))
                 .style(action_style)
                 .padding(4)
-                .on_press(Message::AddDependency(task.uid.clone()));
+                .on_press(Message::YankTask(task.uid.clone()));
             actions = actions.push(
                 tooltip(
-                    block_btn,
-                    text("Block (depends on)").size(12),
+                    link_btn,
+                    text("Yank (copy ID)").size(12),
                     tooltip::Position::Top,
                 )
                 .style(tooltip_style)
                 .delay(Duration::from_millis(700)),
             );
-            let child_btn = button(icon::icon(icon::CHILD).size(14))
+        }
+
+        if task.status != crate::model::TaskStatus::Completed
+            && task.status != crate::model::TaskStatus::Cancelled
+        {
+            let (action_icon, msg_status, tooltip_text) =
+                if task.status == crate::model::TaskStatus::InProcess {
+                    (
+                        icon::PAUSE,
+                        crate::model::TaskStatus::NeedsAction,
+                        "Pause Task",
+                    )
+                } else {
+                    (
+                        icon::PLAY,
+                        crate::model::TaskStatus::InProcess,
+                        "Start Task",
+                    )
+                };
+            let status_toggle_btn = button(icon::icon(action_icon).size(14))
                 .style(action_style)
                 .padding(4)
-                .on_press(Message::MakeChild(task.uid.clone()));
+                .on_press(Message::SetTaskStatus(index, msg_status));
             actions = actions.push(
                 tooltip(
-                    child_btn,
-                    text("Make child").size(12),
+                    status_toggle_btn,
+                    text(tooltip_text).size(12),
                     tooltip::Position::Top,
                 )
                 .style(tooltip_style)
                 .delay(Duration::from_millis(700)),
             );
-        } else {
-            let unlink_btn = button(icon::icon(icon::UNLINK).size(14))
-                .style(button::primary)
+
+            let (flag_icon, flag_tip) = if task.flagged {
+                (icon::FLAG, "Unflag")
+            } else {
+                (icon::FLAG_OUTLINE, "Flag")
+            };
+            let flag_btn = button(icon::icon(flag_icon).size(14))
+                .style(action_style)
                 .padding(4)
-                .on_press(Message::ClearYank);
+                .on_press(Message::ToggleFlag(task.uid.clone()));
             actions = actions.push(
-                tooltip(unlink_btn, text("Unlink").size(12), tooltip::Position::Top)
+                tooltip(flag_btn, text(flag_tip).size(12), tooltip::Position::Top)
                     .style(tooltip_style)
                     .delay(Duration::from_millis(700)),
             );
-            let create_child_btn = button(icon::icon(icon::CREATE_CHILD).size(14))
-                .style(button::primary)
+
+            let tracking = task.is_tracking();
+            let (track_icon, track_tip) = if tracking {
+                (icon::STOP, "Stop tracking")
+            } else {
+                (icon::TIMER, "Track time")
+            };
+            let track_btn = button(icon::icon(track_icon).size(14))
+                .style(if tracking {
+                    button::primary
+                } else {
+                    action_style
+                })
                 .padding(4)
-                .on_press(Message::StartCreateChild(task.uid.clone()));
+                .on_press(Message::ToggleTracking(task.uid.clone()));
             actions = actions.push(
-                tooltip(
-                    create_child_btn,
-                    text("Create subtask").size(12),
-                    tooltip::Position::Top,
-                )
-                .style(tooltip_style)
-                .delay(Duration::from_millis(700)),
+                tooltip(track_btn, text(track_tip).size(12), tooltip::Position::Top)
+                    .style(tooltip_style)
+                    .delay(Duration::from_millis(700)),
             );
 
-            // ELEVATOR UP (Moved here as requested)
-            if task.parent_uid.is_some() {
-                let lift_btn = button(icon::icon(icon::ELEVATOR_UP).size(14))
+            if !app.custom_statuses.is_empty()
+                && let Some(next) = task.next_in_cycle(&app.status_cycle)
+            {
+                let cycle_btn = button(icon::icon(icon::ARROW_RIGHT).size(14))
                     .style(action_style)
                     .padding(4)
-                    .on_press(Message::RemoveParent(task.uid.clone()));
+                    .on_press(Message::CycleStatus(task.uid.clone()));
                 actions = actions.push(
                     tooltip(
-                        lift_btn,
-                        text("Promote (remove parent)").size(12),
+                        cycle_btn,
+                        text(format!("Status: {}", next.name)).size(12),
                         tooltip::Position::Top,
                     )
                     .style(tooltip_style)
@@ -357,117 +507,88 @@ pub fn view_task_row<'a>(
                 );
             }
         }
-    } else {
-        let link_btn = button(icon::icon(icon::LINK).size(14))
+
+        if app.sort_order == SortOrder::Manual {
+            for (glyph, up, tip) in [
+                (icon::ARROW_UP, true, "Move up (Alt+Up)"),
+                (icon::ARROW_DOWN, false, "Move down (Alt+Down)"),
+            ] {
+                let move_btn = button(icon::icon(glyph).size(14))
+                    .style(action_style)
+                    .padding(4)
+                    .on_press(Message::MoveTaskInOrder(task.uid.clone(), up));
+                actions = actions.push(
+                    tooltip(move_btn, text(tip).size(12), tooltip::Position::Top)
+                        .style(tooltip_style)
+                        .delay(Duration::from_millis(700)),
+                );
+            }
+        }
+
+        let plus_btn = button(icon::icon(icon::PLUS).size(14))
             .style(action_style)
             .padding(4)
-            .on_press(Message::YankTask(task.uid.clone()));
+            .on_press(Message::ChangePriority(index, 1));
         actions = actions.push(
             tooltip(
-                link_btn,
-                text("Yank (copy ID)").size(12),
+                plus_btn,
+                text("Increase priority").size(12),
                 tooltip::Position::Top,
             )
             .style(tooltip_style)
             .delay(Duration::from_millis(700)),
         );
-    }
 
-    if task.status != crate::model::TaskStatus::Completed
-        && task.status != crate::model::TaskStatus::Cancelled
-    {
-        let (action_icon, msg_status, tooltip_text) =
-            if task.status == crate::model::TaskStatus::InProcess {
-                (
-                    icon::PAUSE,
-                    crate::model::TaskStatus::NeedsAction,
-                    "Pause Task",
-                )
-            } else {
-                (
-                    icon::PLAY,
-                    crate::model::TaskStatus::InProcess,
-                    "Start Task",
-                )
-            };
-        let status_toggle_btn = button(icon::icon(action_icon).size(14))
+        let minus_btn = button(icon::icon(icon::MINUS).size(14))
             .style(action_style)
             .padding(4)
-            .on_press(Message::SetTaskStatus(index, msg_status));
+            .on_press(Message::ChangePriority(index, -1));
         actions = actions.push(
             tooltip(
-                status_toggle_btn,
-                text(tooltip_text).size(12),
+                minus_btn,
+                text("Decrease priority").size(12),
                 tooltip::Position::Top,
             )
             .style(tooltip_style)
             .delay(Duration::from_millis(700)),
         );
-    }
 
-    let plus_btn = button(icon::icon(icon::PLUS).size(14))
-        .style(action_style)
-        .padding(4)
-        .on_press(Message::ChangePriority(index, 1));
-    actions = actions.push(
-        tooltip(
-            plus_btn,
-            text("Increase priority").size(12),
-            tooltip::Position::Top,
-        )
-        .style(tooltip_style)
-        .delay(Duration::from_millis(700)),
-    );
-
-    let minus_btn = button(icon::icon(icon::MINUS).size(14))
-        .style(action_style)
-        .padding(4)
-        .on_press(Message::ChangePriority(index, -1));
-    actions = actions.push(
-        tooltip(
-            minus_btn,
-            text("Decrease priority").size(12),
-            tooltip::Position::Top,
-        )
-        .style(tooltip_style)
-        .delay(Duration::from_millis(700)),
-    );
-
-    let edit_btn = button(icon::icon(icon::EDIT).size(14))
-        .style(action_style)
-        .padding(4)
-        .on_press(Message::EditTaskStart(index));
-    actions = actions.push(
-        tooltip(edit_btn, text("Edit").size(12), tooltip::Position::Top)
-            .style(tooltip_style)
-            .delay(Duration::from_millis(700)),
-    );
-
-    let delete_btn = button(icon::icon(icon::TRASH).size(14))
-        .style(danger_style)
-        .padding(4)
-        .on_press(Message::DeleteTask(index));
-    actions = actions.push(
-        tooltip(delete_btn, text("Delete").size(12), tooltip::Position::Top)
-            .style(tooltip_style)
-            .delay(Duration::from_millis(700)),
-    );
+        let edit_btn = button(icon::icon(icon::EDIT).size(14))
+            .style(action_style)
+            .padding(4)
+            .on_press(Message::EditTaskStart(index));
+        actions = actions.push(
+            tooltip(edit_btn, text("Edit").size(12), tooltip::Position::Top)
+                .style(tooltip_style)
+                .delay(Duration::from_millis(700)),
+        );
 
-    if task.status != crate::model::TaskStatus::Completed
-        && task.status != crate::model::TaskStatus::Cancelled
-    {
-        let cancel_btn = button(icon::icon(icon::CROSS).size(14))
+        let delete_btn = button(icon::icon(icon::TRASH).size(14))
             .style(danger_style)
             .padding(4)
-            .on_press(Message::SetTaskStatus(
-                index,
-                crate::model::TaskStatus::Cancelled,
-            ));
+            .on_press(Message::DeleteTask(index));
         actions = actions.push(
-            tooltip(cancel_btn, text("Cancel").size(12), tooltip::Position::Top)
+            tooltip(delete_btn, text("Delete").size(12), tooltip::Position::Top)
                 .style(tooltip_style)
                 .delay(Duration::from_millis(700)),
         );
+
+        if task.status != crate::model::TaskStatus::Completed
+            && task.status != crate::model::TaskStatus::Cancelled
+        {
+            let cancel_btn = button(icon::icon(icon::CROSS).size(14))
+                .style(danger_style)
+                .padding(4)
+                .on_press(Message::SetTaskStatus(
+                    index,
+                    crate::model::TaskStatus::Cancelled,
+                ));
+            actions = actions.push(
+                tooltip(cancel_btn, text("Cancel").size(12), tooltip::Position::Top)
+                    .style(tooltip_style)
+                    .delay(Duration::from_millis(700)),
+            );
+        }
     }
 
     let (icon_char, bg_color, default_border_color) = match task.status {
@@ -516,7 +637,7 @@ pub fn view_task_row<'a>(
     .width(Length::Fixed(24.0))
     .height(Length::Fixed(24.0))
     .padding(0)
-    .on_press(Message::ToggleTask(index, true))
+    .on_press_maybe((!read_only).then_some(Message::ToggleTask(index, true)))
     .style(move |_theme, status| {
         let base_active = button::Style {
             background: Some(bg_color.into()),
@@ -548,26 +669,62 @@ pub fn view_task_row<'a>(
     });
 
     let title_chars = task.summary.chars().count();
-    let est_tags_len = task.categories.len() * 4
+    let est_tags_len = (task.categories.len() + task.contexts.len()) * 4
         + if task.estimated_duration.is_some() {
             3
         } else {
             0
         }
         + if task.rrule.is_some() { 1 } else { 0 }
-        + if is_blocked { 9 } else { 0 };
+        + if is_blocked { 9 } else { 0 }
+        + if task.flagged { 2 } else { 0 }
+        + task.custom_status.as_ref().map_or(0, |s| s.len() + 2);
     let place_inline = (title_chars + est_tags_len) <= 60;
     let has_metadata = !task.categories.is_empty()
+        || !task.contexts.is_empty()
         || task.rrule.is_some()
         || is_blocked
+        || task.custom_status.is_some()
+        || task.flagged
         || task.estimated_duration.is_some();
 
-    let title_row = if place_inline {
-        row![
-            text(&task.summary)
+    // Search matches highlighted in the title
+    let search_terms = search_text_terms(&app.search_value);
+    let summary_text = || -> Element<'a, Message> {
+        let ranges = highlight_ranges(&task.summary, &search_terms);
+        if ranges.is_empty() {
+            return text(&task.summary)
                 .size(20)
                 .color(color)
-                .width(Length::Fill),
+                .width(Length::Fill)
+                .into();
+        }
+        let mut spans = Vec::new();
+        let mut pos = 0;
+        for r in ranges {
+            if r.start > pos {
+                spans.push(span(&task.summary[pos..r.start]).color(color));
+            }
+            spans.push(
+                span(&task.summary[r.clone()])
+                    .color(Color::BLACK)
+                    .background(Color::from_rgb(1.0, 0.85, 0.3)),
+            );
+            pos = r.end;
+        }
+        if pos < task.summary.len() {
+            spans.push(span(&task.summary[pos..]).color(color));
+        }
+        rich_text(spans)
+            .size(20)
+            .width(Length::Fill)
+            .on_link_click(never)
+            .into()
+    };
+
+    let title_row = if place_inline {
+        row![
+            summary_text(),
             if has_metadata {
                 build_tags()
             } else {
@@ -577,14 +734,9 @@ pub fn view_task_row<'a>(
         .spacing(6)
         .align_y(iced::Alignment::Center)
     } else {
-        row![
-            text(&task.summary)
-                .size(20)
-                .color(color)
-                .width(Length::Fill)
-        ]
-        .spacing(6)
-        .align_y(iced::Alignment::Center)
+        row![summary_text()]
+            .spacing(6)
+            .align_y(iced::Alignment::Center)
     };
     let main_text_col = column![
         title_row,
@@ -607,21 +759,16 @@ pub fn view_task_row<'a>(
         left: 6.0,
     });
     if is_selected {
-        padded_row = padded_row.style(|theme: &Theme| {
-            let palette = theme.extended_palette();
+        let selection = app.theme.selection_rgb();
+        padded_row = padded_row.style(move |theme: &Theme| {
+            let accent = selection
+                .map_or(theme.extended_palette().warning.base.color, |(r, g, b)| {
+                    Color::from_rgb8(r, g, b)
+                });
             container::Style {
-                background: Some(
-                    Color {
-                        a: 0.05,
-                        ..palette.warning.base.color
-                    }
-                    .into(),
-                ),
+                background: Some(Color { a: 0.05, ..accent }.into()),
                 border: iced::Border {
-                    color: Color {
-                        a: 0.5,
-                        ..palette.warning.base.color
-                    },
+                    color: Color { a: 0.5, ..accent },
                     width: 1.0,
                     radius: 4.0.into(),
                 },
@@ -634,13 +781,66 @@ pub fn view_task_row<'a>(
 
     if is_expanded {
         let mut details_col = column![].spacing(5);
-        if !task.description.is_empty() {
+        let description = task.description_text();
+        if !description.is_empty() {
             details_col = details_col.push(
-                text(&task.description)
+                text(description)
                     .size(14)
                     .color(Color::from_rgb(0.7, 0.7, 0.7)),
             );
         }
+        for (i, item) in task.checklist().into_iter().enumerate() {
+            let mut check = checkbox(item.done).label(item.text).size(14).text_size(14);
+            if !read_only {
+                let uid = task.uid.clone();
+                check = check.on_toggle(move |_| Message::ToggleChecklistItem(uid.clone(), i));
+            }
+            details_col = details_col.push(check);
+        }
+        if let Some(done) = task.completed_at {
+            details_col = details_col.push(
+                text(format!(
+                    "Completed: {}",
+                    done.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                ))
+                .size(12)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            );
+        }
+        if !task.time_sessions.is_empty() {
+            let spent = format_minutes(task.tracked_minutes(chrono::Utc::now()));
+            let estimate = task
+                .estimated_duration
+                .map(|m| format!(" of ~{}", format_minutes(m)))
+                .unwrap_or_default();
+            let running = if task.is_tracking() { ", running" } else { "" };
+            details_col = details_col.push(
+                text(format!(
+                    "Time spent: {}{} ({} sessions{})",
+                    spent,
+                    estimate,
+                    task.time_sessions.len(),
+                    running
+                ))
+                .size(12)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            );
+        }
+        if let Some(next) = task.occurrence_summary() {
+            details_col =
+                details_col.push(text(next).size(12).color(Color::from_rgb(0.5, 0.5, 0.5)));
+            for o in &task.overrides {
+                details_col = details_col.push(
+                    text(format!(
+                        "  {}: {}",
+                        task.format_occurrence(o.recurrence_id),
+                        o.status.label()
+                    ))
+                    .size(12)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                );
+            }
+        }
         if let Some(p_uid) = &task.parent_uid {
             let p_name = app
                 .store
@@ -703,6 +903,50 @@ pub fn view_task_row<'a>(
                 details_col = details_col.push(dep_row);
             }
         }
+        let blocked = app.store.blocked_tasks(&task.uid);
+        if !blocked.is_empty() {
+            details_col = details_col.push(
+                text(format!("[Blocks: {}]:", blocked.len()))
+                    .size(12)
+                    .color(Color::from_rgb(0.8, 0.6, 0.3)),
+            );
+            for uid in blocked {
+                let name = app
+                    .store
+                    .get_summary(uid)
+                    .unwrap_or_else(|| "Unknown Task".to_string());
+                let jump_btn = button(text(name).size(12))
+                    .style(button::text)
+                    .padding(0)
+                    .on_press(Message::JumpToTask(uid.clone()));
+                details_col = details_col.push(jump_btn);
+            }
+        }
+        if has_attachments || !read_only {
+            let mut attach_row = row![
+                icon::icon(icon::PAPERCLIP)
+                    .size(12)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5))
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center);
+            for (i, att) in task.attachments.iter().enumerate() {
+                attach_row = attach_row.push(
+                    button(text(att.display_name()).size(10))
+                        .style(button::secondary)
+                        .padding(3)
+                        .on_press(Message::OpenAttachment(task.uid.clone(), i)),
+                );
+            }
+            if !read_only {
+                let attach_btn = button(text("Attach file...").size(10))
+                    .style(action_style)
+                    .padding(3)
+                    .on_press(Message::AttachFile(task.uid.clone()));
+                attach_row = attach_row.push(attach_btn);
+            }
+            details_col = details_col.push(attach_row);
+        }
         if app.calendars.len() > 1 {
             let current_cal_href = task.calendar_href.clone();
             let targets: Vec<_> = app
@@ -728,6 +972,48 @@ pub fn view_task_row<'a>(
                     .align_y(iced::Alignment::Center),
             );
         }
+        let history = app
+            .history
+            .as_ref()
+            .filter(|(uid, _)| *uid == task.uid)
+            .map(|(_, entries)| entries);
+        let history_btn = button(
+            text(if history.is_some() {
+                "Hide history"
+            } else {
+                "History"
+            })
+            .size(10),
+        )
+        .style(button::secondary)
+        .padding(3)
+        .on_press(Message::ToggleHistory(task.uid.clone()));
+        details_col = details_col.push(history_btn);
+        if let Some(entries) = history {
+            if entries.is_empty() {
+                details_col = details_col.push(
+                    text("No changes recorded on this device yet")
+                        .size(12)
+                        .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                );
+            }
+            for entry in entries {
+                let mut lines = vec![format!(
+                    "{} ({})",
+                    entry
+                        .at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M"),
+                    entry.origin
+                )];
+                lines.extend(entry.changes.iter().map(|c| format!("  {}", c)));
+                details_col = details_col.push(
+                    text(lines.join("\n"))
+                        .size(12)
+                        .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                );
+            }
+        }
         let desc_row = row![
             Space::new().width(Length::Fixed(indent_size as f32 + 30.0)),
             details_col
@@ -0,0 +1,94 @@
+// File: src/gui/view/trash.rs
+use crate::gui::icon;
+use crate::gui::message::Message;
+use crate::gui::state::GuiApp;
+
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Color, Element, Length};
+
+const COL_MUTED: Color = Color::from_rgb(0.6, 0.6, 0.6);
+
+pub fn view_trash(app: &GuiApp) -> Element<'_, Message> {
+    let title = row![icon::icon(icon::TRASH).size(28), text("Trash").size(28)]
+        .spacing(15)
+        .align_y(iced::Alignment::Center);
+
+    let mut list = column![].spacing(8);
+    if app.trash.is_empty() {
+        list = list.push(text("The trash is empty.").color(COL_MUTED));
+    }
+    for entry in &app.trash {
+        // The calendar may have been removed since
+        let cal_name = app
+            .calendars
+            .iter()
+            .find(|c| c.href == entry.calendar_href)
+            .map(|c| c.name.as_str())
+            .unwrap_or(entry.calendar_href.as_str());
+        let details = format!(
+            "{} \u{2022} deleted {}",
+            cal_name,
+            entry
+                .deleted_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        );
+
+        let restore_btn = button(
+            row![icon::icon(icon::REPEAT).size(14), text("Restore").size(14)]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+        )
+        .padding([4, 10])
+        .style(button::secondary)
+        .on_press(Message::Undelete(entry.task.uid.clone()));
+
+        list = list.push(
+            row![
+                column![
+                    text(&entry.task.summary).size(16),
+                    text(details).size(12).color(COL_MUTED),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                restore_btn,
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let close_btn = button(
+        text("Close trash")
+            .size(16)
+            .width(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Center),
+    )
+    .padding(12)
+    .width(Length::Fixed(200.0))
+    .style(button::primary)
+    .on_press(Message::CloseTrash);
+
+    let content = column![
+        title,
+        text("Deleted tasks can be restored into the calendar they came from.")
+            .size(12)
+            .color(COL_MUTED),
+        list,
+        container(close_btn)
+            .width(Length::Fill)
+            .center_x(Length::Fill)
+            .padding(20),
+    ]
+    .spacing(20)
+    .padding(20)
+    .max_width(800);
+
+    scrollable(
+        container(content)
+            .width(Length::Fill)
+            .center_x(Length::Fill),
+    )
+    .height(Length::Fill)
+    .into()
+}
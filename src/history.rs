@@ -0,0 +1,209 @@
+// File: src/history.rs
+// Local log of what changed in each task and when (history.json), so a moved
+// due date or priority can be traced back. Never synced.
+use crate::model::{FieldChange, Task};
+use crate::paths::AppPaths;
+use crate::storage::LocalStorage;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+// Entries kept per task, and tasks kept overall (least recently changed go first)
+const MAX_ENTRIES_PER_TASK: usize = 50;
+const MAX_TASKS: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOrigin {
+    // Edited here
+    Local,
+    // Seen on the server during a sync (another client, or the web UI)
+    Remote,
+}
+
+impl fmt::Display for ChangeOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeOrigin::Local => "this device",
+            ChangeOrigin::Remote => "server",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl From<FieldChange> for HistoryChange {
+    fn from(c: FieldChange) -> Self {
+        Self {
+            field: c.field.to_string(),
+            old: c.old,
+            new: c.new,
+        }
+    }
+}
+
+impl fmt::Display for HistoryChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            show(&self.old),
+            show(&self.new)
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub at: DateTime<Utc>,
+    pub origin: ChangeOrigin,
+    pub changes: Vec<HistoryChange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct TaskHistory {
+    // Field values last seen, what the next version is compared to
+    snapshot: BTreeMap<String, String>,
+    // Newest first
+    entries: Vec<HistoryEntry>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl TaskHistory {
+    // `previous` only counts until there is a snapshot (the first time a task is seen)
+    fn apply(
+        &mut self,
+        previous: Option<&Task>,
+        task: &Task,
+        origin: ChangeOrigin,
+        now: DateTime<Utc>,
+    ) {
+        let changes = match (self.updated_at, previous) {
+            (Some(_), _) => task.diff_from_values(&self.snapshot),
+            (None, Some(prev)) => prev.diff(task),
+            (None, None) => Vec::new(),
+        };
+        self.snapshot = task.field_values();
+        self.updated_at = Some(now);
+        if changes.is_empty() {
+            return;
+        }
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                at: now,
+                origin,
+                changes: changes.into_iter().map(HistoryChange::from).collect(),
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES_PER_TASK);
+    }
+}
+
+pub struct History;
+
+impl History {
+    pub fn get_path() -> Option<PathBuf> {
+        AppPaths::get_history_path()
+    }
+
+    /// Recorded changes of a task, newest first.
+    pub fn load(uid: &str) -> Result<Vec<HistoryEntry>> {
+        let Some(path) = Self::get_path() else {
+            return Ok(vec![]);
+        };
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let mut all = LocalStorage::with_lock(&path, || Self::load_internal(&path))?;
+        Ok(all.remove(uid).map(|h| h.entries).unwrap_or_default())
+    }
+
+    fn load_internal(path: &Path) -> Result<HashMap<String, TaskHistory>> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let json = LocalStorage::read_data(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Records what changed in `task` since it was last seen. `previous` is the
+    /// version it replaces, used for tasks the history doesn't know yet.
+    pub fn record(previous: Option<&Task>, task: &Task, origin: ChangeOrigin) -> Result<()> {
+        Self::record_all(&[(previous, task)], origin)
+    }
+
+    /// Same as `record` for several tasks, in a single write.
+    pub fn record_all(versions: &[(Option<&Task>, &Task)], origin: ChangeOrigin) -> Result<()> {
+        let Some(path) = Self::get_path() else {
+            return Ok(());
+        };
+        if versions.is_empty() {
+            return Ok(());
+        }
+        let now = Utc::now();
+        LocalStorage::with_lock(&path, || {
+            let mut all = Self::load_internal(&path)?;
+            for (previous, task) in versions {
+                all.entry(task.uid.clone())
+                    .or_default()
+                    .apply(*previous, task, origin, now);
+            }
+            if all.len() > MAX_TASKS {
+                let mut by_age: Vec<_> = all
+                    .iter()
+                    .map(|(uid, h)| (h.updated_at, uid.clone()))
+                    .collect();
+                by_age.sort();
+                for (_, uid) in by_age.into_iter().take(all.len() - MAX_TASKS) {
+                    all.remove(&uid);
+                }
+            }
+            let json = serde_json::to_string(&all)?;
+            LocalStorage::write_data(&path, json)?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_apply() {
+        let aliases = Default::default();
+        let old = Task::new("Report !5 @2030-01-01", &aliases);
+        let mut task = old.clone();
+        task.priority = 1;
+        let now = Utc::now();
+
+        let mut history = TaskHistory::default();
+        history.apply(Some(&old), &task, ChangeOrigin::Local, now);
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(
+            history.entries[0].changes[0].to_string(),
+            "Priority: 5 -> 1"
+        );
+
+        // Coming back from the server unchanged: nothing new
+        history.apply(Some(&old), &task, ChangeOrigin::Remote, now);
+        assert_eq!(history.entries.len(), 1);
+
+        task.due = None;
+        history.apply(None, &task, ChangeOrigin::Remote, now);
+        assert_eq!(history.entries[0].origin, ChangeOrigin::Remote);
+        assert_eq!(history.entries[0].changes[0].field, "Due");
+        assert_eq!(history.entries[0].changes[0].new, None);
+    }
+}
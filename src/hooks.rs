@@ -0,0 +1,90 @@
+// File: src/hooks.rs
+// User commands run on sync/task events (see `Hooks` in the config), for scripts,
+// notifications or time trackers. They get the task(s) as JSON on stdin.
+use crate::config::Hooks;
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    SyncSuccess,
+    TaskCompleted,
+    TaskCreated,
+}
+
+impl Event {
+    // Also passed to the command as CFAIT_HOOK, for scripts handling several events
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::SyncSuccess => "on_sync_success",
+            Event::TaskCompleted => "on_task_completed",
+            Event::TaskCreated => "on_task_created",
+        }
+    }
+}
+
+/// Runs the command configured for `event`, if any, in the background.
+/// Hooks never fail or slow down the change that triggered them.
+pub fn fire<T: Serialize + ?Sized>(hooks: &Hooks, event: Event, payload: &T) {
+    let command = match event {
+        Event::SyncSuccess => &hooks.on_sync_success,
+        Event::TaskCompleted => &hooks.on_task_completed,
+        Event::TaskCreated => &hooks.on_task_created,
+    };
+    let Some(command) = command.clone().filter(|c| !c.trim().is_empty()) else {
+        return;
+    };
+    let Ok(json) = serde_json::to_string(payload) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let _ = run(&command, event, &json);
+    });
+}
+
+fn run(command: &str, event: Event, input: &str) -> std::io::Result<()> {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut c = Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        let mut c = Command::new("sh");
+        c.args(["-c", command]);
+        c
+    };
+    let mut child = cmd
+        .env("CFAIT_HOOK", event.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that doesn't read its input closes the pipe early: not an error
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_gets_json_on_stdin() {
+        let out = std::env::temp_dir().join(format!("cfait_hook_{}.json", std::process::id()));
+        let command = format!(
+            "printf \"$CFAIT_HOOK \" > '{0}'; cat >> '{0}'",
+            out.display()
+        );
+
+        run(&command, Event::TaskCreated, r#"{"summary":"Buy milk"}"#).unwrap();
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(written, r#"on_task_created {"summary":"Buy milk"}"#);
+        let _ = std::fs::remove_file(out);
+    }
+}
@@ -1,13 +1,16 @@
 // File: src/journal.rs
+use crate::cache::Cache;
 use crate::model::Task;
 use crate::paths::AppPaths;
 use crate::storage::LocalStorage;
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Action {
     Create(Task),
     Update(Task),
@@ -15,9 +18,196 @@ pub enum Action {
     Move(Task, String),
 }
 
+impl Action {
+    pub fn task(&self) -> &Task {
+        match self {
+            Action::Create(t) | Action::Update(t) | Action::Delete(t) | Action::Move(t, _) => t,
+        }
+    }
+
+    pub fn task_mut(&mut self) -> &mut Task {
+        match self {
+            Action::Create(t) | Action::Update(t) | Action::Delete(t) | Action::Move(t, _) => t,
+        }
+    }
+
+    /// Calendar the action applies to (the source calendar for moves).
+    pub fn calendar_href(&self) -> &str {
+        match self {
+            Action::Create(t) | Action::Update(t) | Action::Delete(t) | Action::Move(t, _) => {
+                &t.calendar_href
+            }
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Action::Create(_) => "New",
+            Action::Update(_) => "Edit",
+            Action::Delete(_) => "Delete",
+            Action::Move(..) => "Move",
+        }
+    }
+
+    /// The action that cancels this one. `previous` is the task before an update;
+    /// without it an update can't be reverted.
+    pub fn inverse(&self, previous: Option<&Task>) -> Option<Action> {
+        match self {
+            Action::Create(t) => Some(Action::Delete(t.clone())),
+            Action::Delete(t) => {
+                let mut t = t.clone();
+                t.etag.clear();
+                Some(Action::Create(t))
+            }
+            Action::Update(t) => {
+                // Same resource, one version later
+                let mut restored = previous?.clone();
+                restored.account = t.account.clone();
+                restored.href = t.href.clone();
+                restored.etag = t.etag.clone();
+                restored.sequence = t.sequence + 1;
+                Some(Action::Update(restored))
+            }
+            Action::Move(t, dest) => Some(Action::Move(moved_to(t, dest), t.calendar_href.clone())),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.task().summary)?;
+        if let Action::Move(_, dest) = self {
+            write!(f, " -> {}", dest)?;
+        }
+        Ok(())
+    }
+}
+
+// `task` as it ends up after a move to `calendar_href`
+pub(crate) fn moved_to(task: &Task, calendar_href: &str) -> Task {
+    let mut t = task.clone();
+    t.calendar_href = calendar_href.to_string();
+    t.href = if calendar_href.ends_with('/') {
+        format!("{}{}.ics", calendar_href, t.uid)
+    } else {
+        format!("{}/{}.ics", calendar_href, t.uid)
+    };
+    t
+}
+
+/// Merges actions that make each other redundant, so edits made offline cost one
+/// request per task: Create+Update -> Create, Update+Update -> the last one,
+/// Update+Delete -> Delete, Create+Delete -> nothing. Nothing is merged across a
+/// move (the task changes href). Merged actions keep the place of the first one.
+pub fn compact(queue: &mut Vec<Action>) {
+    let mut out: Vec<Action> = Vec::with_capacity(queue.len());
+    for action in queue.drain(..) {
+        let Some(i) = out.iter().rposition(|a| a.task().uid == action.task().uid) else {
+            out.push(action);
+            continue;
+        };
+        match (&out[i], action) {
+            (Action::Create(first), Action::Update(t)) => {
+                out[i] = Action::Create(same_resource(first, t));
+            }
+            (Action::Update(first), Action::Update(t)) => {
+                out[i] = Action::Update(same_resource(first, t));
+            }
+            (Action::Update(first), Action::Delete(t)) => {
+                out[i] = Action::Delete(same_resource(first, t));
+            }
+            (Action::Create(_), Action::Delete(_)) => {
+                out.remove(i);
+            }
+            (_, action) => out.push(action),
+        }
+    }
+    *queue = out;
+}
+
+// `task`, at the href/ETag of the action it replaces
+fn same_resource(first: &Task, mut task: Task) -> Task {
+    task.href = first.href.clone();
+    task.etag = first.etag.clone();
+    task
+}
+
+// The task as it was before `task` got queued: an earlier queued version, else
+// the server copy from the cache
+fn previous_version(earlier: &[Action], task: &Task) -> Option<Task> {
+    if let Some(action) = earlier.iter().rev().find(|a| a.task().uid == task.uid) {
+        return match action {
+            Action::Create(t) | Action::Update(t) => Some(t.clone()),
+            Action::Move(t, dest) => Some(moved_to(t, dest)),
+            Action::Delete(_) => None,
+        };
+    }
+    let key = Cache::key(task.account.as_deref(), &task.calendar_href);
+    Cache::load_base(&key, &task.uid).or_else(|| {
+        let (tasks, _) = Cache::load(&key).ok()?;
+        tasks.into_iter().find(|t| t.uid == task.uid && t != task)
+    })
+}
+
+/// Last replay of an account that failed, to explain a stuck queue (`cfait doctor`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayFailure {
+    pub uid: String,
+    pub error: String,
+    pub at: DateTime<Utc>,
+}
+
+/// A change given up on: rejected for good (403 on a read-only calendar...) or
+/// failing too many times in a row. Kept out of the queue until retried or dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FailedAction {
+    pub action: Action,
+    pub error: String,
+    pub at: DateTime<Utc>,
+}
+
+/// A change the server rejected because the task changed there too, left for the
+/// user to settle (`conflict_policy = "interactive"`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub local: Task,
+    pub server: Task,
+    // Fields both sides changed
+    pub fields: Vec<String>,
+}
+
+/// Which side of a `Conflict` to keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Local,
+    Server,
+    Both,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Journal {
+    // Each action's task carries the label of its account (see `push_for`)
     pub queue: Vec<Action>,
+    // Task UID -> when its oldest pending change was queued
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub queued_at: HashMap<String, DateTime<Utc>>,
+    // Account label ("" = primary account) -> last failed replay. Each account
+    // replays its own actions, so one server being down doesn't hold back the others.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub failures: HashMap<String, ReplayFailure>,
+    // Task UID -> server errors in a row while replaying its changes
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub attempts: HashMap<String, u32>,
+    // Parked, see `FailedAction`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedAction>,
+    // Waiting for the user, see `Conflict`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<Conflict>,
+    // Changes reverted by `revert_last` and the inverses it queued, skipped by the
+    // next undo (until the sync replays or merges them)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub undone: Vec<Action>,
 }
 
 impl Journal {
@@ -25,40 +215,47 @@ impl Journal {
         AppPaths::get_journal_path()
     }
 
-    /// Internal load helper (no locking)
-    fn load_internal(path: &PathBuf) -> Self {
-        if path.exists()
-            && let Ok(content) = fs::read_to_string(path)
-            && let Ok(journal) = serde_json::from_str(&content)
-        {
-            return journal;
+    /// Internal load helper (no locking). Fails if the file can't be read, decrypted
+    /// or parsed, so that a write doesn't replace it with an empty queue.
+    fn load_internal(path: &PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
         }
-        Self::default()
+        let content = LocalStorage::read_data(path)?;
+        Ok(serde_json::from_str(&content)?)
     }
 
-    /// Public load with locking
-    pub fn load() -> Self {
-        if let Some(path) = Self::get_path() {
-            if !path.exists() {
-                return Self::default();
+    /// Public load with locking. An unreadable journal is an error, not an empty
+    /// queue: the changes in it haven't reached the server.
+    pub fn load() -> Result<Self> {
+        match Self::get_path() {
+            Some(path) if path.exists() => {
+                LocalStorage::with_lock(&path, || Self::load_internal(&path))
             }
-            return LocalStorage::with_lock(&path, || Ok(Self::load_internal(&path)))
-                .unwrap_or_default();
+            _ => Ok(Self::default()),
         }
-        Self::default()
     }
 
     /// Transactional modification of the journal queue.
     pub fn modify<F>(f: F) -> Result<()>
     where
         F: FnOnce(&mut Vec<Action>),
+    {
+        Self::modify_journal(|journal| f(&mut journal.queue))
+    }
+
+    /// Transactional modification of the whole journal (queue, failures, conflicts...).
+    pub fn modify_journal<F>(f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Journal),
     {
         if let Some(path) = Self::get_path() {
             LocalStorage::with_lock(&path, || {
-                let mut journal = Self::load_internal(&path);
-                f(&mut journal.queue);
+                let mut journal = Self::load_internal(&path)?;
+                f(&mut journal);
+                journal.track_ages(Utc::now());
                 let json = serde_json::to_string_pretty(&journal)?;
-                LocalStorage::atomic_write(&path, json)?;
+                LocalStorage::write_data(&path, json)?;
                 Ok(())
             })?;
         }
@@ -69,7 +266,252 @@ impl Journal {
         Self::modify(|queue| queue.push(action))
     }
 
+    /// Queues an action on behalf of an account (`None` = primary account). The
+    /// label goes on the action's task, a move stays on the same account.
+    pub fn push_for(account: Option<&str>, mut action: Action) -> Result<()> {
+        action.task_mut().account = account.map(str::to_string);
+        Self::push(action)
+    }
+
+    /// Queued actions, oldest (next to be replayed) first.
+    pub fn list() -> Result<Vec<Action>> {
+        Ok(Self::load()?.queue)
+    }
+
+    /// Drops a queued action, e.g. one the server keeps rejecting. The local copy
+    /// of the task goes back to the server version on the next full sync.
+    /// Returns false if the action isn't queued anymore (replayed meanwhile).
+    pub fn remove(action: &Action) -> Result<bool> {
+        let mut found = false;
+        Self::modify(|queue| {
+            if let Some(idx) = queue.iter().position(|a| a == action) {
+                queue.remove(idx);
+                found = true;
+            }
+        })?;
+        Ok(found)
+    }
+
+    /// Moves a queued action one step earlier (`up`) or later in the queue.
+    /// Returns false if it isn't queued anymore or is already at that end.
+    pub fn move_entry(action: &Action, up: bool) -> Result<bool> {
+        let mut moved = false;
+        Self::modify(|queue| {
+            let Some(idx) = queue.iter().position(|a| a == action) else {
+                return;
+            };
+            let other = if up {
+                idx.checked_sub(1)
+            } else {
+                Some(idx + 1)
+            };
+            if let Some(other) = other.filter(|o| *o < queue.len()) {
+                queue.swap(idx, other);
+                moved = true;
+            }
+        })?;
+        Ok(moved)
+    }
+
+    /// Undo: queues the inverse of the last queued action, so only changes that
+    /// haven't been replayed yet can be undone. Returns the queued inverse, or None
+    /// when there is nothing to revert. The sync then merges the two (see `compact`).
+    /// Undoing again reverts the change before, and so on.
+    pub fn revert_last() -> Result<Option<Action>> {
+        let mut inverse = None;
+        Self::modify_journal(|journal| {
+            let queue = &journal.queue;
+            journal.undone.retain(|a| queue.contains(a));
+            let Some(idx) = journal.undo_index() else {
+                return;
+            };
+            let last = &queue[idx];
+            let previous = match last {
+                Action::Update(t) => previous_version(&queue[..idx], t),
+                _ => None,
+            };
+            inverse = last.inverse(previous.as_ref());
+            // A copy of the original's task, so same account
+            if let Some(action) = &inverse {
+                journal.undone.push(last.clone());
+                journal.undone.push(action.clone());
+                journal.queue.push(action.clone());
+            }
+        })?;
+        Ok(inverse)
+    }
+
+    /// The change `revert_last` would undo next.
+    pub fn next_undo(&self) -> Option<&Action> {
+        self.undo_index().map(|i| &self.queue[i])
+    }
+
+    fn undo_index(&self) -> Option<usize> {
+        self.queue.iter().rposition(|a| !self.undone.contains(a))
+    }
+
+    /// Account label an action belongs to (`None` = primary account).
+    pub fn account_of(&self, action: &Action) -> Option<&str> {
+        action.task().account.as_deref()
+    }
+
+    /// Queued actions of one account, in replay order.
+    pub fn queue_for<'a>(&'a self, account: Option<&'a str>) -> impl Iterator<Item = &'a Action> {
+        self.queue
+            .iter()
+            .filter(move |a| self.account_of(a) == account)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty()
     }
+
+    // Dates new tasks of the queue, forgets the ones that are gone
+    fn track_ages(&mut self, now: DateTime<Utc>) {
+        for action in &self.queue {
+            self.queued_at
+                .entry(action.task().uid.clone())
+                .or_insert(now);
+        }
+        let queue = &self.queue;
+        self.queued_at
+            .retain(|uid, _| queue.iter().any(|a| a.task().uid == *uid));
+        let queued_at = &self.queued_at;
+        self.failures.retain(|_, f| queued_at.contains_key(&f.uid));
+        self.attempts.retain(|uid, _| queued_at.contains_key(uid));
+    }
+
+    /// Remembers why the replay of `action` by `account` failed. With `counted`,
+    /// adds one to the attempts of its task and returns them.
+    pub fn record_failure(
+        account: Option<&str>,
+        action: &Action,
+        error: &str,
+        counted: bool,
+    ) -> Result<u32> {
+        let uid = action.task().uid.clone();
+        let failure = ReplayFailure {
+            uid: uid.clone(),
+            error: error.to_string(),
+            at: Utc::now(),
+        };
+        let mut attempts = 0;
+        Self::modify_journal(|journal| {
+            journal
+                .failures
+                .insert(account.unwrap_or_default().to_string(), failure);
+            let count = journal.attempts.entry(uid).or_default();
+            if counted {
+                *count += 1;
+            }
+            attempts = *count;
+        })?;
+        Ok(attempts)
+    }
+
+    /// Moves the queued changes of `action`'s task to `failed`, so the rest of
+    /// the queue can go on (its later changes would fail the same way).
+    pub fn park(action: &Action, error: &str) -> Result<()> {
+        let uid = action.task().uid.clone();
+        let at = Utc::now();
+        Self::modify_journal(|journal| {
+            let (parked, kept): (Vec<Action>, Vec<Action>) = std::mem::take(&mut journal.queue)
+                .into_iter()
+                .partition(|a| a.task().uid == uid);
+            journal.queue = kept;
+            journal
+                .failed
+                .extend(parked.into_iter().map(|action| FailedAction {
+                    action,
+                    error: error.to_string(),
+                    at,
+                }));
+        })
+    }
+
+    /// Puts a parked action back at the end of the queue, for the next sync.
+    /// Returns false if it isn't parked anymore.
+    pub fn retry_failed(entry: &FailedAction) -> Result<bool> {
+        let mut found = false;
+        Self::modify_journal(|journal| {
+            if let Some(idx) = journal.failed.iter().position(|f| f == entry) {
+                journal.queue.push(journal.failed.remove(idx).action);
+                found = true;
+            }
+        })?;
+        Ok(found)
+    }
+
+    /// Gives up on a parked action for good. Returns false if it isn't parked anymore.
+    pub fn drop_failed(entry: &FailedAction) -> Result<bool> {
+        let mut found = false;
+        Self::modify_journal(|journal| {
+            if let Some(idx) = journal.failed.iter().position(|f| f == entry) {
+                journal.failed.remove(idx);
+                found = true;
+            }
+        })?;
+        Ok(found)
+    }
+
+    /// Sets a conflict aside until the user settles it (replaces an older one
+    /// about the same task).
+    pub fn add_conflict(conflict: Conflict) -> Result<()> {
+        Self::modify_journal(|journal| {
+            journal
+                .conflicts
+                .retain(|c| c.local.uid != conflict.local.uid);
+            journal.conflicts.push(conflict);
+        })
+    }
+
+    /// Removes and returns the conflict about a task.
+    pub fn take_conflict(uid: &str) -> Result<Option<Conflict>> {
+        let mut taken = None;
+        Self::modify_journal(|journal| {
+            if let Some(idx) = journal.conflicts.iter().position(|c| c.local.uid == uid) {
+                taken = Some(journal.conflicts.remove(idx));
+            }
+        })?;
+        Ok(taken)
+    }
+
+    /// Last failed replay of an account.
+    pub fn failure_of(&self, account: Option<&str>) -> Option<&ReplayFailure> {
+        self.failures.get(account.unwrap_or_default())
+    }
+
+    /// When the oldest pending change was queued.
+    pub fn oldest(&self) -> Option<DateTime<Utc>> {
+        self.queued_at.values().min().copied()
+    }
+
+    /// Warning when changes were given up on, or the queue looks stuck: more than
+    /// `max_entries` changes, or one waiting for more than `max_days`. 0 disables a limit.
+    pub fn health_warning(
+        &self,
+        max_entries: usize,
+        max_days: u32,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        if !self.failed.is_empty() {
+            return Some(format!("{} changes failed to sync", self.failed.len()));
+        }
+        if max_entries > 0 && self.queue.len() > max_entries {
+            return Some(format!("{} changes not synced", self.queue.len()));
+        }
+        let oldest = self.oldest()?;
+        (max_days > 0 && now - oldest > Duration::days(max_days as i64))
+            .then(|| format!("Not synced for {} days", (now - oldest).num_days()))
+    }
+
+    /// What the replay of an account is stuck on: its first action. The last
+    /// failure of the account is returned with it if it's about the same task.
+    pub fn blocking(&self, account: Option<&str>) -> Option<(&Action, Option<&ReplayFailure>)> {
+        let first = self.queue_for(account).next()?;
+        let failure = self
+            .failure_of(account)
+            .filter(|f| f.uid == first.task().uid);
+        Some((first, failure))
+    }
 }
@@ -1,13 +1,22 @@
 // File: src/lib.rs
+pub mod backup;
 pub mod cache;
 pub mod client;
 pub mod color_utils;
 pub mod config;
+pub mod config_check;
+pub mod credentials;
+pub mod encryption;
+pub mod history;
+pub mod hooks;
 pub mod journal;
 pub mod model;
 pub mod paths;
+pub mod reminders;
+pub mod search_index;
 pub mod storage;
 pub mod store;
+pub mod vdir;
 
 #[cfg(feature = "tui")]
 pub mod tui;
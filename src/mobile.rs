@@ -1,10 +1,10 @@
 // File: ./src/mobile.rs
 use crate::cache::Cache;
-use crate::client::RustyClient;
-use crate::config::Config;
-use crate::model::Task;
+use crate::client::{AccountManager, ClientError};
+use crate::config::{CalendarPrefs, Config};
+use crate::model::{Task, extract_inline_aliases};
 use crate::paths::AppPaths;
-use crate::storage::{LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME, LocalStorage};
+use crate::storage::{LOCAL_CALENDAR_HREF, LocalStorage, Trash, is_local_calendar};
 use crate::store::{FilterOptions, TaskStore, UNCATEGORIZED_ID};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -30,6 +30,11 @@ impl From<&str> for MobileError {
         Self::Generic(e.to_string())
     }
 }
+impl From<ClientError> for MobileError {
+    fn from(e: ClientError) -> Self {
+        Self::Generic(e.to_string())
+    }
+}
 impl From<anyhow::Error> for MobileError {
     fn from(e: anyhow::Error) -> Self {
         Self::Generic(e.to_string())
@@ -59,10 +64,17 @@ pub struct MobileTask {
     pub priority: u8,
     pub due_date_iso: Option<String>,
     pub start_date_iso: Option<String>,
+    pub is_all_day: bool,
     pub duration_mins: Option<u32>,
+    pub time_spent_mins: u32,
+    pub is_tracking: bool,
+    // "Waiting", "Delegated"... None for the standard statuses
+    pub custom_status: Option<String>,
+    pub flagged: bool,
     pub calendar_href: String,
     pub categories: Vec<String>,
     pub is_recurring: bool,
+    pub next_occurrence_iso: Option<String>,
     pub parent_uid: Option<String>,
     pub smart_string: String,
     pub depth: u32,
@@ -80,6 +92,9 @@ pub struct MobileCalendar {
     pub is_visible: bool,
     pub is_local: bool,
     pub is_disabled: bool,
+    pub is_read_only: bool,
+    pub owner: Option<String>,
+    pub is_shared: bool,
 }
 
 #[derive(uniffi::Record)]
@@ -118,10 +133,18 @@ fn task_to_mobile(t: &Task, store: &TaskStore) -> MobileTask {
         priority: t.priority,
         due_date_iso: t.due.map(|d| d.to_rfc3339()),
         start_date_iso: t.dtstart.map(|d| d.to_rfc3339()),
+        is_all_day: t.is_all_day,
         duration_mins: t.estimated_duration,
+        time_spent_mins: t.tracked_minutes(chrono::Utc::now()),
+        is_tracking: t.is_tracking(),
+        custom_status: t.custom_status.clone(),
+        flagged: t.flagged,
         calendar_href: t.calendar_href.clone(),
         categories: t.categories.clone(),
         is_recurring: t.rrule.is_some(),
+        next_occurrence_iso: t
+            .next_occurrence(chrono::Utc::now())
+            .map(|o| o.start.to_rfc3339()),
         parent_uid: t.parent_uid.clone(),
         smart_string: smart,
         depth: t.depth as u32,
@@ -136,7 +159,7 @@ fn task_to_mobile(t: &Task, store: &TaskStore) -> MobileTask {
 
 #[derive(uniffi::Object)]
 pub struct CfaitMobile {
-    client: Arc<Mutex<Option<RustyClient>>>,
+    manager: Arc<Mutex<Option<AccountManager>>>,
     store: Arc<Mutex<TaskStore>>,
 }
 
@@ -156,7 +179,7 @@ impl CfaitMobile {
         );
         AppPaths::init_android_path(android_files_dir);
         Self {
-            client: Arc::new(Mutex::new(None)),
+            manager: Arc::new(Mutex::new(None)),
             store: Arc::new(Mutex::new(TaskStore::new())),
         }
     }
@@ -202,9 +225,11 @@ impl CfaitMobile {
         task_uid: String,
         blocker_uid: String,
     ) -> Result<(), MobileError> {
-        if task_uid == blocker_uid {
-            return Err(MobileError::from("Cannot depend on self"));
-        }
+        self.store
+            .lock()
+            .await
+            .check_dependency(&task_uid, &blocker_uid)
+            .map_err(MobileError::from)?;
         self.modify_task_and_sync(task_uid, |t| {
             if !t.dependencies.contains(&blocker_uid) {
                 t.dependencies.push(blocker_uid.clone());
@@ -213,6 +238,23 @@ impl CfaitMobile {
         .await
     }
 
+    pub async fn toggle_checklist_item(&self, uid: String, index: u32) -> Result<(), MobileError> {
+        self.modify_task_and_sync(uid, |t| {
+            t.toggle_checklist_item(index as usize);
+        })
+        .await
+    }
+
+    pub async fn toggle_flag(&self, uid: String) -> Result<(), MobileError> {
+        self.modify_task_and_sync(uid, |t| t.flagged = !t.flagged)
+            .await
+    }
+
+    pub async fn toggle_tracking(&self, uid: String) -> Result<(), MobileError> {
+        self.modify_task_and_sync(uid, |t| t.toggle_tracking(chrono::Utc::now()))
+            .await
+    }
+
     pub async fn remove_dependency(
         &self,
         task_uid: String,
@@ -232,9 +274,11 @@ impl CfaitMobile {
         parent_uid: Option<String>,
     ) -> Result<(), MobileError> {
         if let Some(p) = &parent_uid {
-            if *p == child_uid {
-                return Err(MobileError::from("Cannot be child of self"));
-            }
+            self.store
+                .lock()
+                .await
+                .check_parent(&child_uid, p)
+                .map_err(MobileError::from)?;
         }
         self.modify_task_and_sync(child_uid, |t| {
             t.parent_uid = parent_uid.clone();
@@ -262,12 +306,12 @@ impl CfaitMobile {
 
     pub fn add_alias(&self, key: String, tags: Vec<String>) -> Result<(), MobileError> {
         let mut c = Config::load().unwrap_or_default();
-        c.tag_aliases.insert(key, tags);
+        c.set_alias(&key, &tags).map_err(MobileError::from)?;
         c.save().map_err(MobileError::from)
     }
     pub fn remove_alias(&self, key: String) -> Result<(), MobileError> {
         let mut c = Config::load().unwrap_or_default();
-        c.tag_aliases.remove(&key);
+        c.remove_alias(&key);
         c.save().map_err(MobileError::from)
     }
     pub fn set_default_calendar(&self, href: String) -> Result<(), MobileError> {
@@ -284,18 +328,38 @@ impl CfaitMobile {
         }
         config.save().map_err(MobileError::from)
     }
+    pub fn create_local_calendar(&self, name: String) -> Result<String, MobileError> {
+        let cal = LocalStorage::create_calendar(&name).map_err(MobileError::from)?;
+        self.store.blocking_lock().insert(cal.href.clone(), vec![]);
+        Ok(cal.href)
+    }
+    pub fn rename_local_calendar(&self, href: String, name: String) -> Result<(), MobileError> {
+        LocalStorage::rename_calendar(&href, &name).map_err(MobileError::from)
+    }
+    // Its tasks go to the trash
+    pub fn delete_local_calendar(&self, href: String) -> Result<(), MobileError> {
+        let trash_days = Config::load().unwrap_or_default().trash_days;
+        let tasks = LocalStorage::delete_calendar(&href).map_err(MobileError::from)?;
+        let mut store = self.store.blocking_lock();
+        for task in &tasks {
+            Trash::add(task, trash_days).map_err(MobileError::from)?;
+            store.delete_task(&task.uid);
+        }
+        store.calendars.remove(&href);
+        Ok(())
+    }
     pub fn load_from_cache(&self) {
         let mut store = self.store.blocking_lock();
         store.clear();
-        if let Ok(local) = LocalStorage::load() {
-            store.insert(LOCAL_CALENDAR_HREF.to_string(), local);
+        for (href, local) in LocalStorage::load_all() {
+            store.insert(href, local);
         }
         if let Ok(cals) = Cache::load_calendars() {
             for cal in cals {
-                if cal.href == LOCAL_CALENDAR_HREF {
+                if is_local_calendar(&cal.href) {
                     continue;
                 }
-                if let Ok((tasks, _)) = Cache::load(&cal.href) {
+                if let Ok((tasks, _)) = Cache::load_for(&cal) {
                     store.insert(cal.href, tasks);
                 }
             }
@@ -327,19 +391,23 @@ impl CfaitMobile {
     pub fn get_calendars(&self) -> Vec<MobileCalendar> {
         let config = Config::load().unwrap_or_default();
         let disabled_set: HashSet<String> = config.disabled_calendars.iter().cloned().collect();
-        let mut result = Vec::new();
-        let local_href = LOCAL_CALENDAR_HREF.to_string();
-        result.push(MobileCalendar {
-            name: LOCAL_CALENDAR_NAME.to_string(),
-            href: local_href.clone(),
-            color: None,
-            is_visible: !config.hidden_calendars.contains(&local_href),
-            is_local: true,
-            is_disabled: false,
-        });
+        let mut result: Vec<MobileCalendar> = LocalStorage::calendars()
+            .into_iter()
+            .map(|c| MobileCalendar {
+                name: c.name,
+                is_visible: !config.hidden_calendars.contains(&c.href),
+                href: c.href,
+                color: None,
+                is_local: true,
+                is_disabled: false,
+                is_read_only: false,
+                owner: None,
+                is_shared: false,
+            })
+            .collect();
         if let Ok(cals) = crate::cache::Cache::load_calendars() {
             for c in cals {
-                if c.href == LOCAL_CALENDAR_HREF {
+                if is_local_calendar(&c.href) {
                     continue;
                 }
                 result.push(MobileCalendar {
@@ -349,6 +417,9 @@ impl CfaitMobile {
                     is_visible: !config.hidden_calendars.contains(&c.href),
                     is_local: false,
                     is_disabled: disabled_set.contains(&c.href),
+                    is_read_only: c.read_only,
+                    owner: c.owner,
+                    is_shared: c.shared,
                 });
             }
         }
@@ -395,17 +466,25 @@ impl CfaitMobile {
         } else {
             None
         };
+        let calendars = Cache::load_calendars().unwrap_or_default();
+        let hide_completed_in =
+            CalendarPrefs::hide_completed_by_href(&config.calendars, &calendars);
         let filtered = store.filter(FilterOptions {
             active_cal_href: None,
             hidden_calendars: &hidden,
             selected_categories: &selected_categories,
             match_all_categories: false,
             search_term: &search_query,
+            calendars: &calendars,
             hide_completed_global: config.hide_completed,
+            hide_completed_in: &hide_completed_in,
             cutoff_date,
             min_duration: None,
             max_duration: None,
             include_unset_duration: true,
+            sort_order: config.sort_order,
+            selected_context: None,
+            smart_list: None,
         });
         filtered
             .into_iter()
@@ -420,25 +499,64 @@ impl CfaitMobile {
     }
 
     pub async fn add_task_smart(&self, input: String) -> Result<(), MobileError> {
+        // Inline definitions (#alias=#tag1,#tag2) are saved and applied like in the TUI/GUI
+        let (input, new_aliases) = extract_inline_aliases(&input);
+        let only_definitions = !new_aliases.is_empty() && !input.trim().contains(' ');
+        if !new_aliases.is_empty() {
+            let mut c = Config::load().unwrap_or_default();
+            let mut retro = Vec::new();
+            for (key, tags) in new_aliases {
+                let key = c.set_alias(&key, &tags).map_err(MobileError::from)?;
+                let tags = c.tag_aliases[&key].clone();
+                retro.extend(
+                    self.store
+                        .lock()
+                        .await
+                        .apply_alias_retroactively(&key, &tags),
+                );
+            }
+            c.save().map_err(MobileError::from)?;
+            for t in retro {
+                self.modify_task_and_sync(t.uid, |_| {}).await?;
+            }
+        }
+        if input.trim().is_empty() || only_definitions {
+            return Ok(());
+        }
+
         let aliases = Config::load().unwrap_or_default().tag_aliases;
         let mut task = Task::new(&input, &aliases);
-        let guard = self.client.lock().await;
+        let guard = self.manager.lock().await;
         let config = Config::load().unwrap_or_default();
         let target_href = config
             .default_calendar
             .clone()
             .unwrap_or(LOCAL_CALENDAR_HREF.to_string());
         task.calendar_href = target_href.clone();
-        if let Some(client) = &*guard {
-            client
+        let calendars = Cache::load_calendars().unwrap_or_default();
+        if let Some(prefs) = CalendarPrefs::find(&config.calendars, &calendars, &target_href) {
+            prefs.tag_new_task(&mut task, &aliases);
+        }
+        task.account = calendars
+            .iter()
+            .find(|c| c.href == target_href)
+            .and_then(|c| c.account.clone());
+        if let Some(manager) = &*guard {
+            manager
+                .client_of(&task)
                 .create_task(&mut task)
                 .await
                 .map(|_| ())
                 .map_err(MobileError::from)?;
         } else {
-            let mut all = LocalStorage::load().unwrap_or_default();
+            let local_href = if is_local_calendar(&target_href) {
+                target_href.as_str()
+            } else {
+                LOCAL_CALENDAR_HREF
+            };
+            let mut all = LocalStorage::load_calendar(local_href).unwrap_or_default();
             all.push(task.clone());
-            LocalStorage::save(&all).map_err(MobileError::from)?;
+            LocalStorage::save_calendar(local_href, &all).map_err(MobileError::from)?;
         }
         self.store.lock().await.add_task(task);
         Ok(())
@@ -468,21 +586,21 @@ impl CfaitMobile {
     }
     pub async fn set_status_process(&self, uid: String) -> Result<(), MobileError> {
         self.modify_task_and_sync(uid, |t| {
-            t.status = if t.status == crate::model::TaskStatus::InProcess {
+            t.set_status(if t.status == crate::model::TaskStatus::InProcess {
                 crate::model::TaskStatus::NeedsAction
             } else {
                 crate::model::TaskStatus::InProcess
-            };
+            });
         })
         .await
     }
     pub async fn set_status_cancelled(&self, uid: String) -> Result<(), MobileError> {
         self.modify_task_and_sync(uid, |t| {
-            t.status = if t.status == crate::model::TaskStatus::Cancelled {
+            t.set_status(if t.status == crate::model::TaskStatus::Cancelled {
                 crate::model::TaskStatus::NeedsAction
             } else {
                 crate::model::TaskStatus::Cancelled
-            };
+            });
         })
         .await
     }
@@ -510,9 +628,9 @@ impl CfaitMobile {
     pub async fn toggle_task(&self, uid: String) -> Result<(), MobileError> {
         self.modify_task_and_sync(uid, |t| {
             if t.status.is_done() {
-                t.status = crate::model::TaskStatus::NeedsAction;
+                t.set_status(crate::model::TaskStatus::NeedsAction);
             } else {
-                t.status = crate::model::TaskStatus::Completed;
+                t.set_status(crate::model::TaskStatus::Completed);
             }
         })
         .await
@@ -522,9 +640,9 @@ impl CfaitMobile {
         let updated_task = store
             .move_task(&uid, new_cal_href.clone())
             .ok_or(MobileError::from("Task not found"))?;
-        let client_guard = self.client.lock().await;
-        if let Some(client) = &*client_guard {
-            client
+        let guard = self.manager.lock().await;
+        if let Some(manager) = &*guard {
+            manager
                 .move_task(&updated_task, &new_cal_href)
                 .await
                 .map_err(MobileError::from)?;
@@ -538,14 +656,19 @@ impl CfaitMobile {
         let task = store
             .delete_task(&uid)
             .ok_or(MobileError::from("Task not found"))?;
-        let client_guard = self.client.lock().await;
-        if let Some(client) = &*client_guard {
-            client.delete_task(&task).await.map_err(MobileError::from)?;
-        } else if task.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut local = LocalStorage::load().unwrap_or_default();
+        let guard = self.manager.lock().await;
+        if let Some(manager) = &*guard {
+            manager
+                .client_of(&task)
+                .delete_task(&task)
+                .await
+                .map_err(MobileError::from)?;
+        } else if is_local_calendar(&task.calendar_href) {
+            let mut local = LocalStorage::load_calendar(&task.calendar_href).unwrap_or_default();
             if let Some(pos) = local.iter().position(|t| t.uid == uid) {
                 local.remove(pos);
-                LocalStorage::save(&local).map_err(MobileError::from)?;
+                LocalStorage::save_calendar(&task.calendar_href, &local)
+                    .map_err(MobileError::from)?;
             }
         }
         Ok(())
@@ -558,17 +681,17 @@ impl CfaitMobile {
 
 impl CfaitMobile {
     async fn apply_connection(&self, config: Config) -> Result<String, MobileError> {
-        let (client, cals, _, _, warning) = RustyClient::connect_with_fallback(config)
+        let (manager, cals, _, _, warning) = AccountManager::connect_with_fallback(config)
             .await
             .map_err(MobileError::from)?;
-        *self.client.lock().await = Some(client.clone());
+        *self.manager.lock().await = Some(manager.clone());
         let mut store = self.store.lock().await;
         store.clear();
-        if let Ok(local) = LocalStorage::load() {
-            store.insert(LOCAL_CALENDAR_HREF.to_string(), local);
+        for (href, local) in LocalStorage::load_all() {
+            store.insert(href, local);
         }
 
-        match client.get_all_tasks(&cals).await {
+        match manager.get_all_tasks(&cals).await {
             Ok(results) => {
                 for (href, tasks) in results {
                     store.insert(href, tasks);
@@ -576,8 +699,8 @@ impl CfaitMobile {
             }
             Err(e) => {
                 for cal in &cals {
-                    if cal.href != LOCAL_CALENDAR_HREF && !store.calendars.contains_key(&cal.href) {
-                        if let Ok((cached, _)) = crate::cache::Cache::load(&cal.href) {
+                    if !is_local_calendar(&cal.href) && !store.calendars.contains_key(&cal.href) {
+                        if let Ok((cached, _)) = crate::cache::Cache::load_for(cal) {
                             store.insert(cal.href.clone(), cached);
                         }
                     }
@@ -601,17 +724,19 @@ impl CfaitMobile {
         modifier(task);
         let task_copy = task.clone();
         drop(store);
-        let client_guard = self.client.lock().await;
-        if let Some(client) = &*client_guard {
-            client
+        let guard = self.manager.lock().await;
+        if let Some(manager) = &*guard {
+            manager
+                .client_of(&task_copy)
                 .update_task(&mut task_copy.clone())
                 .await
                 .map_err(MobileError::from)?;
-        } else if task_copy.calendar_href == LOCAL_CALENDAR_HREF {
-            let mut local = LocalStorage::load().unwrap_or_default();
+        } else if is_local_calendar(&task_copy.calendar_href) {
+            let href = task_copy.calendar_href.clone();
+            let mut local = LocalStorage::load_calendar(&href).unwrap_or_default();
             if let Some(idx) = local.iter().position(|t| t.uid == uid) {
                 local[idx] = task_copy;
-                LocalStorage::save(&local).map_err(MobileError::from)?;
+                LocalStorage::save_calendar(&href, &local).map_err(MobileError::from)?;
             }
         }
         Ok(())
@@ -1,9 +1,12 @@
 // File: src/model/adapter.rs
-use crate::model::item::{RawProperty, Task, TaskStatus};
-use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
-use icalendar::{Calendar, CalendarComponent, Component, Todo, TodoStatus};
-use rrule::RRuleSet;
-use std::str::FromStr;
+use crate::model::item::{
+    Alarm, AlarmTrigger, Attachment, DateTz, RawProperty, RecurrenceOverride, Task, TaskStatus,
+};
+use crate::model::status::CUSTOM_STATUS_KEY;
+use crate::model::tracking::TimeSession;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use icalendar::{Calendar, CalendarComponent, Component, Todo};
+use std::collections::HashSet;
 use uuid::Uuid;
 
 const HANDLED_KEYS: &[&str] = &[
@@ -11,14 +14,23 @@ const HANDLED_KEYS: &[&str] = &[
     "SUMMARY",
     "DESCRIPTION",
     "STATUS",
+    "X-STATUS",
     "PRIORITY",
+    "X-APPLE-FLAGGED",
     "DUE",
     "DTSTART",
     "RRULE",
+    "EXDATE",
     "DURATION",
     "X-ESTIMATED-DURATION",
+    "X-TIME-SPENT",
+    "X-TIME-SESSION",
+    "X-CFAIT-REPEAT-FROM",
     "CATEGORIES",
+    "X-CONTEXT",
     "RELATED-TO",
+    "ATTACH",
+    "COMPLETED",
     "DTSTAMP",
     "CREATED",
     "LAST-MODIFIED",
@@ -30,57 +42,71 @@ const HANDLED_KEYS: &[&str] = &[
 
 impl Task {
     pub fn respawn(&self) -> Option<Task> {
-        let rule_str = self.rrule.as_ref()?;
+        self.rrule.as_ref()?;
         let seed_date = self.dtstart.or(self.due)?;
+        let next_start = if self.repeat_after_completion {
+            // Same series, moved to the day it was completed (keeping the time of day)
+            let done = self
+                .completed_at
+                .unwrap_or_else(Utc::now)
+                .with_timezone(&Local);
+            let time = seed_date.with_timezone(&Local).time();
+            let anchor = done
+                .date_naive()
+                .and_time(time)
+                .and_local_timezone(Local)
+                .earliest()?
+                .with_timezone(&Utc);
+            let offset = anchor - seed_date;
+            let mut basis = self.clone();
+            basis.dtstart = self.dtstart.map(|d| d + offset);
+            basis.due = self.due.map(|d| d + offset);
+            basis.exdates.clear();
+            basis.overrides.clear();
+            basis.next_occurrence(anchor)?.start
+        } else {
+            // Skips EXDATEs and occurrences already completed on their own
+            self.next_occurrence(seed_date)?.start
+        };
 
-        let dtstart_str = seed_date.format("%Y%m%dT%H%M%SZ").to_string();
-        let rrule_string = format!("DTSTART:{}\nRRULE:{}", dtstart_str, rule_str);
-
-        if let Ok(rrule_set) = RRuleSet::from_str(&rrule_string) {
-            let result = rrule_set.all(2);
-            let dates = result.dates;
-            if dates.len() > 1 {
-                let next_occurrence = dates[1];
-                let next_start = Utc.from_utc_datetime(&next_occurrence.naive_utc());
-
-                let mut next_task = self.clone();
-                next_task.uid = Uuid::new_v4().to_string();
-                next_task.href = String::new();
-                next_task.etag = String::new();
-                next_task.status = TaskStatus::NeedsAction;
-                next_task.dependencies.clear();
-
-                if self.dtstart.is_some() {
-                    next_task.dtstart = Some(next_start);
-                }
+        let mut next_task = self.clone();
+        next_task.uid = Uuid::new_v4().to_string();
+        next_task.href = String::new();
+        next_task.etag = String::new();
+        next_task.set_status(TaskStatus::NeedsAction);
+        next_task.sequence = 0;
+        next_task.created = Some(Utc::now());
+        next_task.dependencies.clear();
+        next_task.time_spent = 0;
+        next_task.time_sessions.clear();
 
-                if let Some(old_due) = self.due {
-                    let duration = old_due - seed_date;
-                    next_task.due = Some(next_start + duration);
-                }
+        // Exceptions still ahead move to the new series. A series counted from
+        // the completion has no fixed dates to except.
+        if self.repeat_after_completion {
+            next_task.exdates.clear();
+            next_task.overrides.clear();
+        }
+        next_task.exdates.retain(|d| *d > next_start);
+        next_task
+            .overrides
+            .retain(|o| o.recurrence_id >= next_start);
+        for o in &mut next_task.overrides {
+            o.set_uid(&next_task.uid);
+        }
 
-                return Some(next_task);
-            }
+        if self.dtstart.is_some() {
+            next_task.dtstart = Some(next_start);
         }
-        None
-    }
 
-    pub fn to_ics(&self) -> String {
-        let mut todo = Todo::new();
-        todo.uid(&self.uid);
-        todo.summary(&self.summary);
-        if !self.description.is_empty() {
-            todo.description(&self.description);
+        if let Some(old_due) = self.due {
+            let duration = old_due - seed_date;
+            next_task.due = Some(next_start + duration);
         }
-        todo.timestamp(Utc::now());
 
-        match self.status {
-            TaskStatus::NeedsAction => todo.status(TodoStatus::NeedsAction),
-            TaskStatus::InProcess => todo.status(TodoStatus::InProcess),
-            TaskStatus::Completed => todo.status(TodoStatus::Completed),
-            TaskStatus::Cancelled => todo.status(TodoStatus::Cancelled),
-        };
+        Some(next_task)
+    }
 
+    pub fn to_ics(&self) -> String {
         fn format_iso_duration(mins: u32) -> String {
             if mins.is_multiple_of(24 * 60) {
                 format!("P{}D", mins / (24 * 60))
@@ -91,96 +117,136 @@ impl Task {
             }
         }
 
+        let utc = |dt: DateTime<Utc>| dt.format("%Y%m%dT%H%M%SZ").to_string();
+        let mut w = IcsWriter::default();
+        w.begin("VCALENDAR");
+        w.value("VERSION", &[], "2.0");
+        w.value("PRODID", &[], "-//cfait//EN");
+
+        w.begin("VTODO");
+        w.value("UID", &[], &self.uid);
+        w.value("DTSTAMP", &[], &utc(Utc::now()));
+        w.text("SUMMARY", &self.summary);
+        if !self.description.is_empty() {
+            w.text("DESCRIPTION", &self.description);
+        }
+        let status = match self.status {
+            TaskStatus::NeedsAction => "NEEDS-ACTION",
+            TaskStatus::InProcess => "IN-PROCESS",
+            TaskStatus::Completed => "COMPLETED",
+            TaskStatus::Cancelled => "CANCELLED",
+        };
+        w.value("STATUS", &[], status);
+        if let Some(name) = &self.custom_status {
+            w.text(CUSTOM_STATUS_KEY, name);
+        }
+
         if let Some(dt) = self.dtstart {
-            let formatted = dt.format("%Y%m%dT%H%M%SZ").to_string();
-            todo.add_property("DTSTART", &formatted);
+            w.property(&date_property(
+                "DTSTART",
+                dt,
+                &self.dtstart_tz,
+                self.is_all_day,
+            ));
         }
 
         if let Some(dt) = self.due {
-            let formatted = dt.format("%Y%m%dT%H%M%SZ").to_string();
-            todo.add_property("DUE", &formatted);
+            w.property(&date_property("DUE", dt, &self.due_tz, self.is_all_day));
             if let Some(mins) = self.estimated_duration {
-                let val = format_iso_duration(mins);
-                todo.add_property("X-ESTIMATED-DURATION", &val);
+                w.value("X-ESTIMATED-DURATION", &[], &format_iso_duration(mins));
             }
         } else if let Some(mins) = self.estimated_duration {
-            let val = format_iso_duration(mins);
-            todo.add_property("DURATION", &val);
+            w.value("DURATION", &[], &format_iso_duration(mins));
+        }
+        if self.time_spent > 0 {
+            w.value("X-TIME-SPENT", &[], &self.time_spent.to_string());
+        }
+        for session in &self.time_sessions {
+            w.value("X-TIME-SESSION", &[], &session.to_ics_value());
+        }
+        if let Some(done) = self.completed_at
+            && self.status == TaskStatus::Completed
+        {
+            // Always UTC (RFC 5545 3.8.2.1)
+            w.value("COMPLETED", &[], &utc(done));
+        }
+        if let Some(created) = self.created {
+            w.value("CREATED", &[], &utc(created));
+        }
+        if self.sequence > 0 {
+            w.value("SEQUENCE", &[], &self.sequence.to_string());
         }
         if self.priority > 0 {
-            todo.priority(self.priority.into());
+            w.value("PRIORITY", &[], &self.priority.to_string());
+        }
+        if self.flagged {
+            w.value("X-APPLE-FLAGGED", &[], "TRUE");
         }
         if let Some(rrule) = &self.rrule {
-            todo.add_property("RRULE", rrule.as_str());
+            w.value("RRULE", &[], rrule);
+            if self.repeat_after_completion {
+                w.value("X-CFAIT-REPEAT-FROM", &[], "COMPLETION");
+            }
+        }
+        // Same value type as the date the series is keyed on
+        let series_tz = if self.dtstart.is_some() {
+            &self.dtstart_tz
+        } else {
+            &self.due_tz
+        };
+        for exdate in &self.exdates {
+            w.property(&date_property(
+                "EXDATE",
+                *exdate,
+                series_tz,
+                self.is_all_day,
+            ));
         }
 
         // --- HIERARCHY & DEPENDENCIES ---
         if let Some(p_uid) = &self.parent_uid {
-            let prop = icalendar::Property::new("RELATED-TO", p_uid.as_str());
-            todo.append_multi_property(prop);
+            w.value("RELATED-TO", &[], p_uid);
         }
-
         for dep_uid in &self.dependencies {
-            let mut prop = icalendar::Property::new("RELATED-TO", dep_uid);
-            prop.add_parameter("RELTYPE", "DEPENDS-ON");
-            todo.append_multi_property(prop);
+            w.value("RELATED-TO", &[("RELTYPE", "DEPENDS-ON")], dep_uid);
         }
 
         // --- WRITE BACK UNMAPPED PROPERTIES ---
+        // Kept in the form they were read in, already escaped
         for raw in &self.unmapped_properties {
-            let mut prop = icalendar::Property::new(&raw.key, &raw.value);
-            for (k, v) in &raw.params {
-                prop.add_parameter(k, v);
-            }
-            todo.append_multi_property(prop);
-        }
-
-        let mut calendar = Calendar::new();
-        calendar.push(todo);
-        let mut ics = calendar.to_string();
-
-        // 1. Manual injection of CATEGORIES
-        if !self.categories.is_empty() {
-            let escaped_cats: Vec<String> = self
-                .categories
+            let params: Vec<(&str, &str)> = raw
+                .params
                 .iter()
-                .map(|c| c.replace(',', "\\,"))
+                .map(|(k, v)| (k.as_str(), v.as_str()))
                 .collect();
-            let cat_line = format!("CATEGORIES:{}", escaped_cats.join(","));
-
-            if let Some(idx) = ics.rfind("END:VTODO") {
-                let (start, end) = ics.split_at(idx);
-                let mut buffer = String::with_capacity(ics.len() + cat_line.len() + 2);
-                buffer.push_str(start);
-                buffer.push_str(&cat_line);
-                buffer.push_str("\r\n");
-                buffer.push_str(end);
-                ics = buffer;
-            }
+            w.value(&raw.key, &params, &raw.value);
         }
 
-        // 2. Inject Raw Components (Exceptions, Timezones, etc.)
-        if !self.raw_components.is_empty() {
-            let trimmed = ics.trim_end();
-            if let Some(idx) = trimmed.rfind("END:VCALENDAR") {
-                let (start, end) = trimmed.split_at(idx);
-
-                let extra_len: usize = self.raw_components.iter().map(|s| s.len() + 2).sum();
-                let mut buffer = String::with_capacity(trimmed.len() + extra_len);
-
-                buffer.push_str(start);
-                for raw in &self.raw_components {
-                    buffer.push_str(raw);
-                    if !raw.ends_with("\r\n") && !raw.ends_with('\n') {
-                        buffer.push_str("\r\n");
-                    }
-                }
-                buffer.push_str(end);
-                ics = buffer;
-            }
+        if !self.categories.is_empty() {
+            w.list("CATEGORIES", &self.categories);
+        }
+        if !self.contexts.is_empty() {
+            w.list("X-CONTEXT", &self.contexts);
         }
+        for attachment in &self.attachments {
+            write_attachment(&mut w, attachment);
+        }
+        for alarm in &self.alarms {
+            write_alarm(&mut w, alarm);
+        }
+        w.end("VTODO");
 
-        ics
+        // Overrides and raw components (exceptions, timezones, events) are already serialized
+        for raw in self
+            .overrides
+            .iter()
+            .map(|o| &o.raw)
+            .chain(&self.raw_components)
+        {
+            w.raw(raw);
+        }
+        w.end("VCALENDAR");
+        w.finish()
     }
 
     pub fn from_ics(
@@ -192,6 +258,7 @@ impl Task {
         let calendar: Calendar = raw_ics.parse().map_err(|e| format!("Parse: {}", e))?;
 
         let mut master_todo: Option<&Todo> = None;
+        let mut exceptions: Vec<&Todo> = Vec::new();
         let mut raw_components: Vec<String> = Vec::with_capacity(calendar.components.len());
 
         for component in &calendar.components {
@@ -200,7 +267,7 @@ impl Task {
                     let is_exception = t.properties().contains_key("RECURRENCE-ID");
 
                     if is_exception {
-                        raw_components.push(t.to_string());
+                        exceptions.push(t);
                     } else if master_todo.is_none() {
                         master_todo = Some(t);
                     } else {
@@ -218,68 +285,81 @@ impl Task {
             None => return Err("No Master VTODO found in ICS".to_string()),
         };
 
-        let summary = todo.get_summary().unwrap_or("No Title").to_string();
-        let description = todo.get_description().unwrap_or("").to_string();
+        // TEXT values are unescaped here, from the raw lines
+        let summary =
+            parse_text_manually(raw_ics, "SUMMARY").unwrap_or_else(|| "No Title".to_string());
+        let description = parse_text_manually(raw_ics, "DESCRIPTION").unwrap_or_default();
         let uid = todo.get_uid().unwrap_or_default().to_string();
 
-        let status = if let Some(prop) = todo.properties().get("STATUS") {
-            match prop.value().trim().to_uppercase().as_str() {
-                "COMPLETED" => TaskStatus::Completed,
-                "IN-PROCESS" => TaskStatus::InProcess,
-                "CANCELLED" => TaskStatus::Cancelled,
-                _ => TaskStatus::NeedsAction,
-            }
-        } else {
-            TaskStatus::NeedsAction
-        };
+        let status = parse_status(todo);
+        // Only meaningful while open: another client may have completed it since
+        let custom_status = parse_text_manually(raw_ics, CUSTOM_STATUS_KEY)
+            .filter(|s| !s.trim().is_empty() && !status.is_done());
         let priority = todo
             .properties()
             .get("PRIORITY")
             .and_then(|p| p.value().parse::<u8>().ok())
             .unwrap_or(0);
+        let flagged = todo
+            .properties()
+            .get("X-APPLE-FLAGGED")
+            .is_some_and(|p| is_true(p.value()));
 
-        let parse_date_prop = |val: &str| -> Option<DateTime<Utc>> {
-            if val.len() == 8 {
-                NaiveDate::parse_from_str(val, "%Y%m%d")
-                    .ok()
-                    .and_then(|d| d.and_hms_opt(0, 0, 0))
-                    .map(|d| d.and_utc())
-            } else {
-                NaiveDateTime::parse_from_str(
-                    val,
-                    if val.ends_with('Z') {
-                        "%Y%m%dT%H%M%SZ"
-                    } else {
-                        "%Y%m%dT%H%M%S"
-                    },
-                )
-                .ok()
-                .map(|d| Utc.from_utc_datetime(&d))
-            }
-        };
-
-        let due = todo.properties().get("DUE").and_then(|p| {
-            let val = p.value();
-            if val.len() == 8 {
-                NaiveDate::parse_from_str(val, "%Y%m%d")
-                    .ok()
-                    .and_then(|d| d.and_hms_opt(23, 59, 59))
-                    .map(|d| d.and_utc())
-            } else {
-                parse_date_prop(val)
-            }
-        });
-
-        let dtstart = todo
+        let (due, due_tz) = todo
+            .properties()
+            .get("DUE")
+            .and_then(|p| parse_date_prop(p, true))
+            .unzip();
+        let (dtstart, dtstart_tz) = todo
             .properties()
             .get("DTSTART")
-            .and_then(|p| parse_date_prop(p.value()));
+            .and_then(|p| parse_date_prop(p, false))
+            .unzip();
+        let sequence = todo
+            .properties()
+            .get("SEQUENCE")
+            .and_then(|p| p.value().trim().parse::<u32>().ok())
+            .unwrap_or(0);
+        let completed_at = todo
+            .properties()
+            .get("COMPLETED")
+            .and_then(|p| parse_date_prop(p, false))
+            .map(|(dt, _)| dt);
+        let created = todo
+            .properties()
+            .get("CREATED")
+            .and_then(|p| parse_date_prop(p, false))
+            .map(|(dt, _)| dt);
+        // DTSTART and DUE must share their value type (RFC 5545 3.8.2.3)
+        let is_all_day = ["DUE", "DTSTART"].iter().any(|key| {
+            todo.properties()
+                .get(*key)
+                .is_some_and(|p| p.value().trim().len() == 8)
+        });
 
         let rrule = todo
             .properties()
             .get("RRULE")
             .map(|p| p.value().to_string());
 
+        let repeat_after_completion = todo
+            .properties()
+            .get("X-CFAIT-REPEAT-FROM")
+            .is_some_and(|p| p.value().trim().eq_ignore_ascii_case("COMPLETION"));
+
+        // Occurrences are keyed like the series: by DTSTART, else by DUE (end of day)
+        let keyed_by_due = dtstart.is_none();
+        let exdates = parse_exdates_manually(raw_ics, keyed_by_due);
+        let mut overrides = Vec::new();
+        for t in exceptions {
+            match parse_override(t, &uid, keyed_by_due) {
+                Some(o) => overrides.push(o),
+                // Other UIDs, or unreadable RECURRENCE-IDs: kept untouched
+                None => raw_components.push(t.to_string()),
+            }
+        }
+        overrides.sort_by_key(|o| o.recurrence_id);
+
         let parse_dur = |val: &str| -> Option<u32> {
             let mut minutes = 0;
             let mut num_buf = String::new();
@@ -324,34 +404,49 @@ impl Task {
                 .and_then(|p| parse_dur(p.value()));
         }
 
+        let time_spent = todo
+            .properties()
+            .get("X-TIME-SPENT")
+            .and_then(|p| p.value().trim().parse().ok())
+            .unwrap_or(0);
+        let time_sessions = parse_time_sessions_manually(raw_ics);
+
         let mut categories = Vec::new();
         if let Some(multi_props) = todo.multi_properties().get("CATEGORIES") {
             for prop in multi_props {
-                let parts: Vec<String> = prop
-                    .value()
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                categories.extend(parts);
+                categories.extend(split_categories(prop.value()));
             }
         }
         if let Some(prop) = todo.properties().get("CATEGORIES") {
-            let parts: Vec<String> = prop
-                .value()
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
-            categories.extend(parts);
+            categories.extend(split_categories(prop.value()));
         }
         categories.sort();
         categories.dedup();
 
+        // Same list format as CATEGORIES, kept in the order typed
+        let mut contexts: Vec<String> = Vec::new();
+        let context_props = todo
+            .multi_properties()
+            .get("X-CONTEXT")
+            .into_iter()
+            .flatten();
+        for prop in context_props.chain(todo.properties().get("X-CONTEXT")) {
+            for ctx in split_categories(prop.value()) {
+                if !contexts.contains(&ctx) {
+                    contexts.push(ctx);
+                }
+            }
+        }
+
         // --- OPTIMIZED RELATION EXTRACTION (MANUAL PARSE) ---
         // Use manual parsing to avoid issues where icalendar library overwrites duplicate keys
         // (e.g. RELATED-TO) when they are not explicitly handled as multi-properties.
         let (parent_uid, dependencies) = parse_related_to_manually(raw_ics);
+        // VALARM is a nested component, also parsed by hand
+        let alarms = parse_alarms_manually(raw_ics);
+        // ATTACH params (FILENAME, MANAGED-ID...) and inline data are too easy to lose
+        // through the icalendar crate, same treatment
+        let attachments = parse_attachments_manually(raw_ics);
 
         // --- CAPTURE UNMAPPED PROPERTIES ---
         let mut unmapped_properties = Vec::new();
@@ -396,24 +491,710 @@ impl Task {
             summary,
             description,
             status,
+            custom_status,
             estimated_duration,
+            time_spent,
+            time_sessions,
             due,
             dtstart,
+            due_tz: due_tz.unwrap_or_default(),
+            dtstart_tz: dtstart_tz.unwrap_or_default(),
+            is_all_day,
+            sequence,
+            completed_at,
+            created,
             priority,
+            flagged,
             parent_uid,
             dependencies,
             etag,
             href,
             calendar_href,
+            account: None,
             categories,
+            contexts,
             depth: 0,
             rrule,
+            repeat_after_completion,
+            exdates,
+            overrides,
             unmapped_properties,
             raw_components,
+            alarms,
+            attachments,
         })
     }
 }
 
+impl RecurrenceOverride {
+    /// A new override for the occurrence of `task` starting at `recurrence_id`,
+    /// copied from the master with its dates moved onto that occurrence.
+    pub fn new(task: &Task, recurrence_id: DateTime<Utc>) -> Self {
+        let mut instance = task.clone();
+        instance.rrule = None;
+        instance.exdates.clear();
+        instance.overrides.clear();
+        instance.raw_components.clear();
+        if let Some(seed) = task.dtstart.or(task.due) {
+            let offset = recurrence_id - seed;
+            instance.dtstart = task.dtstart.map(|d| d + offset);
+            instance.due = task.due.map(|d| d + offset);
+        }
+        let series_tz = if task.dtstart.is_some() {
+            &task.dtstart_tz
+        } else {
+            &task.due_tz
+        };
+        let rid = date_property("RECURRENCE-ID", recurrence_id, series_tz, task.is_all_day);
+        instance.unmapped_properties.push(RawProperty {
+            key: rid.key().to_string(),
+            value: rid.value().to_string(),
+            params: rid
+                .params()
+                .iter()
+                .map(|(k, p)| (k.clone(), p.value().to_string()))
+                .collect(),
+        });
+
+        let ics = instance.to_ics();
+        let raw = match (ics.find("BEGIN:VTODO"), ics.rfind("END:VTODO")) {
+            (Some(start), Some(end)) => format!("{}END:VTODO\r\n", &ics[start..end]),
+            _ => String::new(),
+        };
+        Self {
+            recurrence_id,
+            status: instance.status,
+            completed_at: instance.completed_at,
+            summary: instance.summary,
+            due: instance.due,
+            dtstart: instance.dtstart,
+            raw,
+        }
+    }
+
+    pub fn set_status(&mut self, status: TaskStatus) {
+        if status != TaskStatus::Completed {
+            self.completed_at = None;
+        } else if self.status != status || self.completed_at.is_none() {
+            self.completed_at = Some(Utc::now());
+        }
+        self.status = status;
+        let value = match status {
+            TaskStatus::NeedsAction => "NEEDS-ACTION",
+            TaskStatus::InProcess => "IN-PROCESS",
+            TaskStatus::Completed => "COMPLETED",
+            TaskStatus::Cancelled => "CANCELLED",
+        };
+        set_raw_property(&mut self.raw, "STATUS", Some(value));
+        let completed = self
+            .completed_at
+            .map(|d| d.format("%Y%m%dT%H%M%SZ").to_string());
+        set_raw_property(&mut self.raw, "COMPLETED", completed.as_deref());
+    }
+
+    pub fn set_summary(&mut self, summary: &str) {
+        self.summary = summary.to_string();
+        set_raw_property(&mut self.raw, "SUMMARY", Some(&escape_text(summary)));
+    }
+
+    // After a respawn, the override follows the new series
+    pub(crate) fn set_uid(&mut self, uid: &str) {
+        set_raw_property(&mut self.raw, "UID", Some(uid));
+    }
+}
+
+/// Replaces (or removes, with None) a property of a raw VTODO, leaving its VALARMs alone.
+fn set_raw_property(raw: &mut String, key: &str, value: Option<&str>) {
+    let mut out = String::with_capacity(raw.len() + 32);
+    let mut depth = 0;
+    let mut skipping = false;
+    for line in raw.split_inclusive('\n') {
+        if skipping && (line.starts_with(' ') || line.starts_with('\t')) {
+            continue;
+        }
+        skipping = false;
+        let upper = line.trim_end().to_uppercase();
+        if upper.starts_with("BEGIN:") {
+            depth += 1;
+        } else if upper == "END:VTODO" && depth == 1 {
+            if let Some(v) = value {
+                out.push_str(&fold_line(&format!("{}:{}", key, v)));
+            }
+        } else if upper.starts_with("END:") {
+            depth -= 1;
+        } else if depth == 1
+            && upper
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.starts_with([':', ';']))
+        {
+            skipping = true;
+            continue;
+        }
+        out.push_str(line);
+    }
+    *raw = out;
+}
+
+fn parse_status(todo: &Todo) -> TaskStatus {
+    match todo.properties().get("STATUS") {
+        Some(prop) => match prop.value().trim().to_uppercase().as_str() {
+            "COMPLETED" => TaskStatus::Completed,
+            "IN-PROCESS" => TaskStatus::InProcess,
+            "CANCELLED" => TaskStatus::Cancelled,
+            _ => TaskStatus::NeedsAction,
+        },
+        None => TaskStatus::NeedsAction,
+    }
+}
+
+// Boolean X- properties, however the writing client spelled "true"
+fn is_true(value: &str) -> bool {
+    matches!(value.trim().to_uppercase().as_str(), "TRUE" | "1" | "YES")
+}
+
+fn parse_override(todo: &Todo, uid: &str, keyed_by_due: bool) -> Option<RecurrenceOverride> {
+    if todo.get_uid() != Some(uid) {
+        return None;
+    }
+    let props = todo.properties();
+    let (recurrence_id, _) = parse_date_prop(props.get("RECURRENCE-ID")?, keyed_by_due)?;
+    let date = |key: &str, end_of_day: bool| {
+        props
+            .get(key)
+            .and_then(|p| parse_date_prop(p, end_of_day))
+            .map(|(dt, _)| dt)
+    };
+    Some(RecurrenceOverride {
+        recurrence_id,
+        status: parse_status(todo),
+        completed_at: date("COMPLETED", false),
+        summary: todo.get_summary().unwrap_or_default().to_string(),
+        due: date("DUE", true),
+        dtstart: date("DTSTART", false),
+        raw: todo.to_string(),
+    })
+}
+
+// Keeps how the date was written (UTC, floating, TZID) for the write-back
+fn parse_date_prop(
+    prop: &icalendar::Property,
+    end_of_day: bool,
+) -> Option<(DateTime<Utc>, DateTz)> {
+    let val = prop.value().trim();
+    if val.len() == 8 {
+        // A day, wherever the user is
+        let d = NaiveDate::parse_from_str(val, "%Y%m%d").ok()?;
+        let t = if end_of_day {
+            d.and_hms_opt(23, 59, 59)?
+        } else {
+            d.and_hms_opt(0, 0, 0)?
+        };
+        return Some((DateTz::Floating.to_utc(t)?, DateTz::Floating));
+    }
+    if let Some(utc) = val.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((naive.and_utc(), DateTz::Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(val, "%Y%m%dT%H%M%S").ok()?;
+    let tz = match prop.params().get("TZID") {
+        Some(p) => DateTz::Tzid(p.value().trim_matches('"').to_string()),
+        None => DateTz::Floating,
+    };
+    Some((tz.to_utc(naive)?, tz))
+}
+
+fn date_property(name: &str, dt: DateTime<Utc>, tz: &DateTz, all_day: bool) -> icalendar::Property {
+    let naive = tz.to_naive(dt);
+    if all_day {
+        let mut prop = icalendar::Property::new(name, naive.format("%Y%m%d").to_string());
+        prop.add_parameter("VALUE", "DATE");
+        return prop;
+    }
+    match tz {
+        DateTz::Utc => icalendar::Property::new(name, naive.format("%Y%m%dT%H%M%SZ").to_string()),
+        DateTz::Floating => {
+            icalendar::Property::new(name, naive.format("%Y%m%dT%H%M%S").to_string())
+        }
+        DateTz::Tzid(id) => {
+            let mut prop =
+                icalendar::Property::new(name, naive.format("%Y%m%dT%H%M%S").to_string());
+            prop.add_parameter("TZID", id);
+            prop
+        }
+    }
+}
+
+fn write_attachment(w: &mut IcsWriter, att: &Attachment) {
+    let size = att.size.map(|s| s.to_string());
+    let mut params: Vec<(&str, &str)> = Vec::new();
+    if let Some(fmt) = &att.fmttype {
+        params.push(("FMTTYPE", fmt));
+    }
+    if let Some(name) = &att.filename {
+        params.push(("FILENAME", name));
+    }
+    if let Some(id) = &att.managed_id {
+        params.push(("MANAGED-ID", id));
+    }
+    if let Some(size) = &size {
+        params.push(("SIZE", size));
+    }
+    if att.inline {
+        params.push(("ENCODING", "BASE64"));
+        params.push(("VALUE", "BINARY"));
+    }
+    w.value("ATTACH", &params, &att.value);
+}
+
+/// Writes a calendar property by property, so escaping, parameter quoting and
+/// folding (RFC 5545 3.1, 3.3.11) are done once here instead of on the finished text.
+#[derive(Default)]
+struct IcsWriter {
+    out: String,
+}
+
+impl IcsWriter {
+    fn begin(&mut self, component: &str) {
+        self.line(&format!("BEGIN:{}", component));
+    }
+
+    fn end(&mut self, component: &str) {
+        self.line(&format!("END:{}", component));
+    }
+
+    /// A property whose value is already in its wire format (dates, durations, RRULE...).
+    fn value(&mut self, name: &str, params: &[(&str, &str)], value: &str) {
+        let mut line = name.to_string();
+        for (key, param) in params {
+            line.push_str(&format!(";{}={}", key, quote_param(param)));
+        }
+        line.push(':');
+        line.push_str(value);
+        self.line(&line);
+    }
+
+    /// A TEXT property, escaped.
+    fn text(&mut self, name: &str, text: &str) {
+        self.value(name, &[], &escape_text(text));
+    }
+
+    /// A comma separated list of TEXT values (CATEGORIES).
+    fn list(&mut self, name: &str, items: &[String]) {
+        let escaped: Vec<String> = items.iter().map(|i| escape_text(i)).collect();
+        self.value(name, &[], &escaped.join(","));
+    }
+
+    fn property(&mut self, prop: &icalendar::Property) {
+        let params: Vec<(&str, &str)> = prop
+            .params()
+            .iter()
+            .map(|(k, p)| (k.as_str(), p.value()))
+            .collect();
+        self.value(prop.key(), &params, prop.value());
+    }
+
+    /// A component serialized elsewhere, copied with CRLF line endings.
+    fn raw(&mut self, block: &str) {
+        for line in block.lines().filter(|l| !l.trim().is_empty()) {
+            self.out.push_str(line.trim_end_matches('\r'));
+            self.out.push_str("\r\n");
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.out.push_str(&fold_line(line));
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+// Parameter values with separators are quoted; they can't contain quotes or line breaks
+fn quote_param(value: &str) -> String {
+    let clean = value
+        .trim_matches('"')
+        .replace('"', "'")
+        .replace(['\r', '\n'], " ");
+    if clean.contains([';', ':', ',']) {
+        format!("\"{}\"", clean)
+    } else {
+        clean
+    }
+}
+
+/// Folds a content line at 75 octets (RFC 5545 3.1), without splitting a UTF-8 char.
+fn fold_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + line.len() / 74 * 3 + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// Splits "NAME;P1=a;P2=\"b;c\":value" into its parameters and value,
+/// honoring quoted parameter values.
+fn split_content_line(line: &str) -> Option<(Vec<(String, String)>, &str)> {
+    let mut in_quotes = false;
+    let mut params = Vec::new();
+    let mut start = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' | ':' if !in_quotes => {
+                let part = &line[start..i];
+                if start > 0 {
+                    let (k, v) = part.split_once('=').unwrap_or((part, ""));
+                    params.push((k.to_uppercase(), v.trim_matches('"').to_string()));
+                }
+                start = i + 1;
+                if c == ':' {
+                    return Some((params, &line[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Unfolded content lines of the master VTODO, without its VALARMs.
+fn master_todo_lines(raw_ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw_line in raw_ics.lines() {
+        if (raw_line.starts_with(' ') || raw_line.starts_with('\t'))
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(raw_line[1..].trim_end_matches('\r'));
+        } else {
+            lines.push(raw_line.trim_end_matches('\r').to_string());
+        }
+    }
+
+    let mut in_todo = false;
+    let mut in_alarm = false;
+    let mut is_exception = false;
+    let mut todo_lines = Vec::new();
+
+    for line in lines {
+        let upper = line.to_uppercase();
+        if upper == "BEGIN:VTODO" {
+            in_todo = true;
+            is_exception = false;
+            todo_lines.clear();
+        } else if upper == "END:VTODO" {
+            in_todo = false;
+            if !is_exception {
+                return todo_lines;
+            }
+        } else if !in_todo {
+            continue;
+        } else if upper == "BEGIN:VALARM" {
+            in_alarm = true;
+        } else if upper == "END:VALARM" {
+            in_alarm = false;
+        } else if in_alarm {
+            // AUDIO alarms carry their own ATTACH
+            continue;
+        } else {
+            if upper.starts_with("RECURRENCE-ID") {
+                is_exception = true;
+            }
+            todo_lines.push(line);
+        }
+    }
+    Vec::new()
+}
+
+/// Helper: Manually parse ATTACH properties of the master VTODO.
+fn parse_attachments_manually(raw_ics: &str) -> Vec<Attachment> {
+    let mut attachments = Vec::new();
+    for line in master_todo_lines(raw_ics) {
+        let upper = line.to_uppercase();
+        if (upper.starts_with("ATTACH;") || upper.starts_with("ATTACH:"))
+            && let Some((params, value)) = split_content_line(&line)
+        {
+            let param = |name: &str| {
+                params
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.clone())
+            };
+            attachments.push(Attachment {
+                value: value.trim().to_string(),
+                inline: param("ENCODING").is_some_and(|e| e.eq_ignore_ascii_case("BASE64"))
+                    || param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("BINARY")),
+                fmttype: param("FMTTYPE"),
+                filename: param("FILENAME"),
+                managed_id: param("MANAGED-ID"),
+                size: param("SIZE").and_then(|s| s.parse().ok()),
+            });
+        }
+    }
+    attachments
+}
+
+/// EXDATE lines can repeat and hold several comma-separated values.
+fn parse_exdates_manually(raw_ics: &str, end_of_day: bool) -> Vec<DateTime<Utc>> {
+    let mut exdates = Vec::new();
+    for line in master_todo_lines(raw_ics) {
+        let upper = line.to_uppercase();
+        if !(upper.starts_with("EXDATE;") || upper.starts_with("EXDATE:")) {
+            continue;
+        }
+        let Some((params, values)) = split_content_line(&line) else {
+            continue;
+        };
+        for value in values.split(',') {
+            let mut prop = icalendar::Property::new("EXDATE", value.trim());
+            for (k, v) in &params {
+                prop.add_parameter(k, v);
+            }
+            if let Some((dt, _)) = parse_date_prop(&prop, end_of_day) {
+                exdates.push(dt);
+            }
+        }
+    }
+    exdates.sort();
+    exdates.dedup();
+    exdates
+}
+
+fn parse_text_manually(raw_ics: &str, key: &str) -> Option<String> {
+    master_todo_lines(raw_ics).iter().find_map(|line| {
+        let rest = line.get(key.len()..)?;
+        if !line[..key.len()].eq_ignore_ascii_case(key) || !rest.starts_with([':', ';']) {
+            return None;
+        }
+        Some(unescape_text(split_content_line(line)?.1))
+    })
+}
+
+// Repeated X-TIME-SESSION lines, read from the raw text like EXDATE
+fn parse_time_sessions_manually(raw_ics: &str) -> Vec<TimeSession> {
+    master_todo_lines(raw_ics)
+        .iter()
+        .filter(|line| {
+            let upper = line.to_uppercase();
+            upper.starts_with("X-TIME-SESSION;") || upper.starts_with("X-TIME-SESSION:")
+        })
+        .filter_map(|line| TimeSession::parse(split_content_line(line)?.1))
+        .collect()
+}
+
+fn write_alarm(w: &mut IcsWriter, alarm: &Alarm) {
+    w.begin("VALARM");
+    w.value("ACTION", &[], &alarm.action);
+    match alarm.trigger {
+        AlarmTrigger::Relative {
+            minutes,
+            related_end,
+        } => {
+            let params: &[(&str, &str)] = if related_end {
+                &[("RELATED", "END")]
+            } else {
+                &[]
+            };
+            w.value("TRIGGER", params, &format_signed_duration(minutes));
+        }
+        AlarmTrigger::Absolute(dt) => {
+            let value = dt.format("%Y%m%dT%H%M%SZ").to_string();
+            w.value("TRIGGER", &[("VALUE", "DATE-TIME")], &value);
+        }
+    }
+    // DISPLAY alarms require a DESCRIPTION (RFC 5545 3.6.6)
+    w.text(
+        "DESCRIPTION",
+        alarm.description.as_deref().unwrap_or("Reminder"),
+    );
+    w.end("VALARM");
+}
+
+fn escape_text(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+// In one pass, so an escaped backslash followed by "n" stays text
+fn unescape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            // \\, \; and \, (anything else is kept as is, without the backslash)
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// CATEGORIES is a list: only unescaped commas separate the values
+fn split_categories(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ',' => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+        .iter()
+        .map(|s| unescape_text(s.trim()))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn format_signed_duration(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let m = minutes.unsigned_abs();
+    if m == 0 {
+        "PT0S".to_string()
+    } else if m.is_multiple_of(24 * 60) {
+        format!("{}P{}D", sign, m / (24 * 60))
+    } else if m.is_multiple_of(60) {
+        format!("{}PT{}H", sign, m / 60)
+    } else {
+        format!("{}PT{}M", sign, m)
+    }
+}
+
+/// Parses a signed ISO 8601 duration (e.g. "-PT15M", "P1DT2H") into minutes.
+fn parse_signed_duration(val: &str) -> Option<i64> {
+    let val = val.trim();
+    let (neg, rest) = match val.strip_prefix('-') {
+        Some(r) => (true, r),
+        None => (false, val.strip_prefix('+').unwrap_or(val)),
+    };
+    let rest = rest.strip_prefix('P')?;
+
+    let mut minutes: i64 = 0;
+    let mut num_buf = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c {
+            'T' => in_time = true,
+            '0'..='9' => num_buf.push(c),
+            _ => {
+                let n: i64 = num_buf.parse().ok()?;
+                num_buf.clear();
+                match c {
+                    'W' => minutes += n * 7 * 24 * 60,
+                    'D' => minutes += n * 24 * 60,
+                    'H' if in_time => minutes += n * 60,
+                    'M' if in_time => minutes += n,
+                    // Seconds are below our resolution
+                    'S' if in_time => {}
+                    _ => return None,
+                }
+            }
+        }
+    }
+    Some(if neg { -minutes } else { minutes })
+}
+
+/// Helper: Manually parse VALARM blocks of the master VTODO.
+/// Alarms of RECURRENCE-ID exceptions stay in their raw component.
+fn parse_alarms_manually(raw_ics: &str) -> Vec<Alarm> {
+    // Unfold first
+    let mut lines: Vec<String> = Vec::new();
+    for raw_line in raw_ics.lines() {
+        if (raw_line.starts_with(' ') || raw_line.starts_with('\t'))
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(&raw_line[1..]);
+        } else {
+            lines.push(raw_line.trim_end_matches('\r').to_string());
+        }
+    }
+
+    let mut result: Option<Vec<Alarm>> = None;
+    let mut in_todo = false;
+    let mut is_exception = false;
+    let mut todo_alarms = Vec::new();
+    let mut current: Option<(Option<AlarmTrigger>, String, Option<String>)> = None;
+
+    for line in &lines {
+        let upper = line.to_uppercase();
+        if upper == "BEGIN:VTODO" {
+            in_todo = true;
+            is_exception = false;
+            todo_alarms.clear();
+        } else if upper == "END:VTODO" {
+            in_todo = false;
+            if !is_exception && result.is_none() {
+                result = Some(std::mem::take(&mut todo_alarms));
+            }
+        } else if !in_todo {
+            continue;
+        } else if upper == "BEGIN:VALARM" {
+            current = Some((None, "DISPLAY".to_string(), None));
+        } else if upper == "END:VALARM" {
+            if let Some((Some(trigger), action, description)) = current.take() {
+                todo_alarms.push(Alarm {
+                    trigger,
+                    action,
+                    description,
+                });
+            }
+        } else if let Some((trigger, action, description)) = current.as_mut() {
+            let Some((params_part, value)) = line.split_once(':') else {
+                continue;
+            };
+            let params_upper = params_part.to_uppercase();
+            let name = params_upper.split(';').next().unwrap_or("");
+            match name {
+                "TRIGGER" => {
+                    *trigger = if params_upper.contains("VALUE=DATE-TIME") {
+                        NaiveDateTime::parse_from_str(
+                            value.trim().trim_end_matches('Z'),
+                            "%Y%m%dT%H%M%S",
+                        )
+                        .ok()
+                        .map(|dt| AlarmTrigger::Absolute(Utc.from_utc_datetime(&dt)))
+                    } else {
+                        parse_signed_duration(value).map(|minutes| AlarmTrigger::Relative {
+                            minutes,
+                            related_end: params_upper.contains("RELATED=END"),
+                        })
+                    };
+                }
+                "ACTION" => *action = value.trim().to_uppercase(),
+                "DESCRIPTION" => *description = Some(unescape_text(value)),
+                _ => {}
+            }
+        } else if upper.starts_with("RECURRENCE-ID") {
+            is_exception = true;
+        }
+    }
+
+    result.unwrap_or_default()
+}
+
 /// Helper: Manually parse RELATED-TO from raw ICS string.
 /// This handles unfolding lines and ensures we catch ALL occurrences,
 /// bypassing potential overwrites in the icalendar parser.
@@ -457,10 +1238,144 @@ fn parse_related_to_manually(raw_ics: &str) -> (Option<String>, Vec<String>) {
     (parent, deps)
 }
 
+/// Splits an ICS file holding many tasks (e.g. a calendar export) into one VCALENDAR
+/// per UID, so each can go through `Task::from_ics`. Recurrence exceptions stay
+/// with their master task.
+pub fn split_ics(raw_ics: &str) -> Vec<String> {
+    // UID -> VTODO blocks, in file order
+    let mut groups: Vec<(String, String)> = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in raw_ics.lines() {
+        let line = line.trim_end_matches('\r');
+        let upper = line.to_ascii_uppercase();
+        if current.is_none() && upper == "BEGIN:VTODO" {
+            current = Some(String::new());
+        }
+        let Some(block) = current.as_mut() else {
+            continue;
+        };
+        block.push_str(line);
+        block.push_str("\r\n");
+        if upper != "END:VTODO" {
+            continue;
+        }
+
+        let block = current.take().unwrap_or_default();
+        let uid = block
+            .lines()
+            .find_map(|l| l.strip_prefix("UID:"))
+            .map(|u| u.trim().to_string())
+            .unwrap_or_default();
+        match groups
+            .iter_mut()
+            .find(|(u, _)| !uid.is_empty() && *u == uid)
+        {
+            Some((_, blocks)) => blocks.push_str(&block),
+            None => groups.push((uid, block)),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, blocks)| {
+            format!(
+                "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+                blocks
+            )
+        })
+        .collect()
+}
+
+/// Inverse of `split_ics`: serializes tasks into a single VCALENDAR stream.
+/// Raw components (exceptions, timezones) are kept; a VTIMEZONE shared by
+/// several tasks is only written once.
+pub fn join_ics(tasks: &[Task]) -> String {
+    let mut body = String::new();
+    let mut timezones: HashSet<String> = HashSet::new();
+
+    for task in tasks {
+        let ics = task.to_ics();
+        let mut depth = 0;
+        let mut block = String::new();
+        let mut tzid: Option<String> = None;
+        for line in ics.lines() {
+            let line = line.trim_end_matches('\r');
+            let upper = line.to_ascii_uppercase();
+            if upper.starts_with("BEGIN:") {
+                depth += 1;
+            }
+            // Everything at depth 1 belongs to the per-task VCALENDAR wrapper
+            if depth >= 2 {
+                if depth == 2 && upper.starts_with("TZID:") {
+                    tzid = Some(line[5..].trim().to_string());
+                }
+                block.push_str(line);
+                block.push_str("\r\n");
+            }
+            if upper.starts_with("END:") {
+                depth -= 1;
+                if depth == 1 {
+                    let duplicate = upper == "END:VTIMEZONE"
+                        && tzid.take().is_some_and(|id| !timezones.insert(id));
+                    if !duplicate {
+                        body.push_str(&block);
+                    }
+                    block.clear();
+                }
+            }
+        }
+    }
+
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//cfait//EN\r\n{}END:VCALENDAR\r\n",
+        body
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_ics_groups_by_uid() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VTODO\r\nUID:a\r\nSUMMARY:A\r\nRRULE:FREQ=DAILY\r\nEND:VTODO\r\n\
+BEGIN:VEVENT\r\nUID:e\r\nSUMMARY:Event\r\nEND:VEVENT\r\n\
+BEGIN:VTODO\r\nUID:b\r\nSUMMARY:B\r\nEND:VTODO\r\n\
+BEGIN:VTODO\r\nUID:a\r\nRECURRENCE-ID:20250102T000000Z\r\nSUMMARY:A moved\r\nEND:VTODO\r\n\
+END:VCALENDAR\r\n";
+
+        let chunks = split_ics(ics);
+        assert_eq!(chunks.len(), 2);
+
+        let a = Task::from_ics(&chunks[0], String::new(), String::new(), String::new()).unwrap();
+        assert_eq!(a.uid, "a");
+        assert_eq!(a.overrides.len(), 1);
+        assert!(a.raw_components.is_empty());
+        let b = Task::from_ics(&chunks[1], String::new(), String::new(), String::new()).unwrap();
+        assert_eq!(b.summary, "B");
+    }
+
+    #[test]
+    fn test_join_ics_round_trip() {
+        let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VTODO\r\nUID:a\r\nSUMMARY:A\r\nRRULE:FREQ=DAILY\r\nEND:VTODO\r\n\
+BEGIN:VTODO\r\nUID:a\r\nRECURRENCE-ID:20250102T000000Z\r\nSUMMARY:A moved\r\nEND:VTODO\r\n\
+BEGIN:VTODO\r\nUID:b\r\nSUMMARY:B\r\nEND:VTODO\r\n\
+END:VCALENDAR\r\n";
+        let tasks: Vec<Task> = split_ics(ics)
+            .iter()
+            .map(|c| Task::from_ics(c, String::new(), String::new(), String::new()).unwrap())
+            .collect();
+
+        let joined = join_ics(&tasks);
+        assert_eq!(joined.matches("BEGIN:VCALENDAR").count(), 1);
+        assert_eq!(joined.matches("BEGIN:VTODO").count(), 3);
+        assert!(joined.contains("SUMMARY:A moved"));
+        assert_eq!(split_ics(&joined).len(), 2);
+    }
+
     #[test]
     fn test_relationships_parsing_duplicate_protection() {
         // This validates the bug fix where having a dependency caused the parent relationship
@@ -553,4 +1468,357 @@ END:VCALENDAR";
             "Folded lines should be unwrapped correctly"
         );
     }
+
+    #[test]
+    fn test_valarm_roundtrip() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:alarm
+SUMMARY:With alarm
+DUE:20250101T120000Z
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER;RELATED=END:-PT15M
+DESCRIPTION:Soon\\, really
+END:VALARM
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER;VALUE=DATE-TIME:20241231T090000Z
+DESCRIPTION:Reminder
+END:VALARM
+END:VTODO
+END:VCALENDAR";
+
+        let task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(task.alarms.len(), 2);
+        assert_eq!(
+            task.alarms[0].trigger,
+            AlarmTrigger::Relative {
+                minutes: -15,
+                related_end: true
+            }
+        );
+        assert_eq!(task.alarms[0].description.as_deref(), Some("Soon, really"));
+        assert_eq!(
+            task.alarms[0].fire_time(&task),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 11, 45, 0).unwrap())
+        );
+        assert!(matches!(task.alarms[1].trigger, AlarmTrigger::Absolute(_)));
+
+        let reparsed =
+            Task::from_ics(&task.to_ics(), "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.alarms, task.alarms);
+    }
+
+    #[test]
+    fn test_attachments_round_trip() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:attach-test
+SUMMARY:Attach
+ATTACH;FMTTYPE=application/pdf;FILENAME=\"report; final.pdf\";MANAGED-ID=97S
+ ;SIZE=1024:https://dav.example.com/attachments/97S
+ATTACH:https://example.com/spec.html
+BEGIN:VALARM
+ACTION:AUDIO
+TRIGGER:-PT5M
+ATTACH:ftp://example.com/sound.wav
+END:VALARM
+END:VTODO
+END:VCALENDAR";
+
+        let mut task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(task.attachments.len(), 2);
+        assert_eq!(
+            task.attachments[0].filename.as_deref(),
+            Some("report; final.pdf")
+        );
+        assert_eq!(task.attachments[0].managed_id.as_deref(), Some("97S"));
+        assert_eq!(task.attachments[0].size, Some(1024));
+        assert_eq!(task.attachments[1].display_name(), "spec.html");
+        assert!(task.unmapped_properties.is_empty());
+
+        // Long enough to need folding
+        let data = vec![7u8; 200];
+        task.attachments.push(Attachment::inline(
+            &data,
+            "blob.bin",
+            "application/octet-stream",
+        ));
+        let out = task.to_ics();
+        assert!(out.lines().all(|l| l.len() <= 75));
+
+        let reparsed = Task::from_ics(&out, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.attachments, task.attachments);
+        assert_eq!(reparsed.attachments[2].decode_inline(), Some(data));
+    }
+
+    #[test]
+    fn test_tzid_and_floating_dates_round_trip() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:tz-test
+SUMMARY:Timezones
+DTSTART:20250115T080000
+DUE;TZID=Europe/Brussels:20250115T100000
+END:VTODO
+END:VCALENDAR";
+
+        let task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(task.due_tz, DateTz::Tzid("Europe/Brussels".to_string()));
+        // CET is UTC+1 in January
+        assert_eq!(
+            task.due,
+            Some(Utc.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap())
+        );
+        assert_eq!(task.dtstart_tz, DateTz::Floating);
+
+        let out = task.to_ics();
+        assert!(out.contains("DUE;TZID=Europe/Brussels:20250115T100000"));
+        assert!(out.contains("DTSTART:20250115T080000\r\n"));
+    }
+
+    #[test]
+    fn test_all_day_due_stays_a_date() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:all-day
+SUMMARY:All day
+DUE;VALUE=DATE:20240102
+END:VTODO
+END:VCALENDAR";
+
+        let task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert!(task.is_all_day);
+        assert_eq!(task.format_due("%Y-%m-%d").as_deref(), Some("2024-01-02"));
+        assert!(task.to_ics().contains("DUE;VALUE=DATE:20240102\r\n"));
+
+        let typed = Task::new("Pay rent @2024-01-02", &Default::default());
+        assert!(typed.is_all_day);
+        assert!(typed.to_ics().contains("DUE;VALUE=DATE:20240102\r\n"));
+    }
+
+    #[test]
+    fn test_categories_escaping() {
+        let mut task = Task::new(r#"Paint #"home improvement""#, &Default::default());
+        task.categories.push("a,b;c".to_string());
+        let ics = task.to_ics();
+        assert!(ics.contains("CATEGORIES:home improvement,a\\,b\\;c\r\n"));
+
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.categories, vec!["a,b;c", "home improvement"]);
+    }
+
+    #[test]
+    fn test_text_escaping_and_folding() {
+        let mut task = Task::new("Party", &Default::default());
+        task.summary = "Call Zoë; bring cake, plates \\ forks: 🎂".to_string();
+        task.description = format!(
+            "Line one\nLine two; with, commas\nliteral \\n stays\n{}\n",
+            "é".repeat(100)
+        );
+        task.alarms.push(Alarm {
+            action: "DISPLAY".to_string(),
+            trigger: AlarmTrigger::Relative {
+                minutes: -15,
+                related_end: true,
+            },
+            description: Some("Don't forget:\nthe cake".to_string()),
+        });
+        let ics = task.to_ics();
+
+        assert!(ics.contains("SUMMARY:Call Zoë\\; bring cake\\, plates \\\\ forks: 🎂\r\n"));
+        assert!(ics.contains("DESCRIPTION:Don't forget:\\nthe cake\r\n"));
+        // Folded at 75 octets, and no bare line breaks left in values
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75, "{:?}", line);
+            assert!(!line.contains('\n'));
+        }
+
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.summary, task.summary);
+        assert_eq!(reparsed.description, task.description);
+        assert_eq!(reparsed.alarms, task.alarms);
+        assert!(reparsed.unmapped_properties.is_empty());
+
+        // CRLF in the input comes back as a plain newline
+        task.description = "a\r\nb".to_string();
+        let reparsed =
+            Task::from_ics(&task.to_ics(), "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.description, "a\nb");
+    }
+
+    #[test]
+    fn test_contexts_round_trip() {
+        let task = Task::new(
+            "Buy nails #diy @@errands @@\"hardware store\"",
+            &Default::default(),
+        );
+        assert_eq!(task.summary, "Buy nails");
+        assert_eq!(task.categories, vec!["diy"]);
+        assert_eq!(task.contexts, vec!["errands", "hardware store"]);
+        assert_eq!(
+            task.to_smart_string(),
+            "Buy nails #diy @@errands @@\"hardware store\""
+        );
+
+        let ics = task.to_ics();
+        assert!(ics.contains("X-CONTEXT:errands,hardware store\r\n"));
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.contexts, task.contexts);
+        assert_eq!(reparsed.categories, vec!["diy"]);
+        assert!(reparsed.unmapped_properties.is_empty());
+        assert!(reparsed.matches_search_term("@@hardware"));
+        assert!(!reparsed.matches_search_term("@@home"));
+    }
+
+    #[test]
+    fn test_flagged_round_trip() {
+        let mut task = Task::new("Call the bank", &Default::default());
+        assert!(!task.to_ics().contains("X-APPLE-FLAGGED"));
+        task.flagged = true;
+
+        let ics = task.to_ics();
+        assert!(ics.contains("X-APPLE-FLAGGED:TRUE\r\n"));
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert!(reparsed.flagged);
+        assert!(reparsed.unmapped_properties.is_empty());
+        assert!(reparsed.matches_search_term("is:flagged"));
+
+        let lowercase = ics.replace("X-APPLE-FLAGGED:TRUE", "X-APPLE-FLAGGED:true");
+        let reparsed =
+            Task::from_ics(&lowercase, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert!(reparsed.flagged);
+    }
+
+    #[test]
+    fn test_completed_timestamp() {
+        let mut task = Task::new("Water plants", &Default::default());
+        task.set_status(TaskStatus::Completed);
+        let done = task.completed_at.expect("stamped on completion");
+
+        let reparsed =
+            Task::from_ics(&task.to_ics(), "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(
+            reparsed.completed_at.map(|d| d.timestamp()),
+            Some(done.timestamp())
+        );
+        assert!(reparsed.unmapped_properties.is_empty());
+
+        task.set_status(TaskStatus::NeedsAction);
+        assert!(task.completed_at.is_none());
+        assert!(!task.to_ics().contains("COMPLETED:"));
+    }
+
+    #[test]
+    fn test_sequence_round_trip() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:seq
+SUMMARY:Revised
+SEQUENCE:4
+END:VTODO
+END:VCALENDAR";
+
+        let mut task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(task.sequence, 4);
+        task.sequence += 1;
+        assert!(task.to_ics().contains("SEQUENCE:5\r\n"));
+        let fresh = Task::new("New", &Default::default());
+        assert!(!fresh.to_ics().contains("SEQUENCE"));
+    }
+
+    #[test]
+    fn test_recurrence_exceptions() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:series
+SUMMARY:Water plants
+DTSTART:20250101T090000Z
+RRULE:FREQ=DAILY
+EXDATE:20250103T090000Z,20250104T090000Z
+EXDATE:20250106T090000Z
+END:VTODO
+BEGIN:VTODO
+UID:series
+RECURRENCE-ID:20250102T090000Z
+SUMMARY:Water plants (moved)
+STATUS:COMPLETED
+X-OTHER-CLIENT:keep-me
+END:VTODO
+END:VCALENDAR";
+
+        let mut task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(task.exdates.len(), 3);
+        assert_eq!(task.overrides.len(), 1);
+        assert_eq!(task.overrides[0].status, TaskStatus::Completed);
+
+        let seed = task.dtstart.unwrap();
+        let day = |dt: DateTime<Utc>| dt.format("%d").to_string();
+        let next: Vec<_> = task
+            .occurrences_after(seed, 3)
+            .iter()
+            .map(|o| day(o.start))
+            .collect();
+        assert_eq!(next, ["02", "05", "07"]);
+        // The 2nd is already done on its own
+        assert_eq!(day(task.next_occurrence(seed).unwrap().start), "05");
+
+        // Completing one occurrence adds an override, the series stays open
+        let fifth = Utc.with_ymd_and_hms(2025, 1, 5, 9, 0, 0).unwrap();
+        task.set_occurrence_status(fifth, TaskStatus::Completed);
+        assert_eq!(task.status, TaskStatus::NeedsAction);
+        let ics = task.to_ics();
+        assert!(ics.contains("RECURRENCE-ID:20250105T090000Z"));
+        assert!(ics.contains("X-OTHER-CLIENT:keep-me"));
+        assert_eq!(ics.matches("EXDATE").count(), 3);
+
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.overrides.len(), 2);
+        assert!(reparsed.override_for(fifth).unwrap().completed_at.is_some());
+        assert!(reparsed.raw_components.is_empty());
+
+        // Completing the series moves on to the next open occurrence
+        task.set_status(TaskStatus::Completed);
+        let next = task.respawn().unwrap();
+        assert_eq!(day(next.dtstart.unwrap()), "07");
+        assert!(next.overrides.is_empty());
+        assert!(next.exdates.is_empty());
+    }
+
+    #[test]
+    fn test_repeat_after_completion() {
+        let ics = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:relative
+SUMMARY:Change the filter
+DUE:20250101T090000Z
+RRULE:FREQ=WEEKLY
+X-CFAIT-REPEAT-FROM:COMPLETION
+END:VTODO
+END:VCALENDAR";
+
+        let mut task = Task::from_ics(ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert!(task.repeat_after_completion);
+        assert!(task.to_ics().contains("X-CFAIT-REPEAT-FROM:COMPLETION"));
+
+        // Done three weeks late: the next one is a week after that, not on Jan 8
+        let done = Utc.with_ymd_and_hms(2025, 1, 22, 18, 0, 0).unwrap();
+        task.set_status(TaskStatus::Completed);
+        task.completed_at = Some(done);
+        let next = task.respawn().unwrap().due.unwrap();
+        assert!(next > done + chrono::Duration::days(6));
+        assert!(next < done + chrono::Duration::days(8));
+
+        task.repeat_after_completion = false;
+        let fixed = task.respawn().unwrap().due.unwrap();
+        assert_eq!(fixed, Utc.with_ymd_and_hms(2025, 1, 8, 9, 0, 0).unwrap());
+    }
 }
@@ -0,0 +1,117 @@
+// File: src/model/checklist.rs
+// Checklists: "- [ ] item" / "- [x] item" lines in the description, the same
+// markdown other clients (Tasks.org, Nextcloud) render
+use crate::model::item::Task;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    // Line of the description it comes from
+    pub line: usize,
+    pub text: String,
+    pub done: bool,
+}
+
+impl Task {
+    pub fn checklist(&self) -> Vec<ChecklistItem> {
+        self.description
+            .lines()
+            .enumerate()
+            .filter_map(|(line, l)| {
+                let (done, text) = parse_item(l)?;
+                Some(ChecklistItem {
+                    line,
+                    text: text.to_string(),
+                    done,
+                })
+            })
+            .collect()
+    }
+
+    /// (done, total), None without a checklist.
+    pub fn checklist_progress(&self) -> Option<(usize, usize)> {
+        let items = self.checklist();
+        if items.is_empty() {
+            return None;
+        }
+        Some((items.iter().filter(|i| i.done).count(), items.len()))
+    }
+
+    /// The description minus its checklist lines.
+    pub fn description_text(&self) -> String {
+        self.description
+            .lines()
+            .filter(|l| parse_item(l).is_none())
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+
+    /// Checks or unchecks the `index`-th checklist item. False if there is no such item.
+    pub fn toggle_checklist_item(&mut self, index: usize) -> bool {
+        let Some(item) = self.checklist().into_iter().nth(index) else {
+            return false;
+        };
+        let lines: Vec<String> = self
+            .description
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                if i != item.line {
+                    return l.to_string();
+                }
+                // Keep the indent and bullet, only flip the box
+                let open = l.find('[').unwrap_or(0);
+                let mark = if item.done { ' ' } else { 'x' };
+                format!("{}[{}]{}", &l[..open], mark, &l[open + 3..])
+            })
+            .collect();
+        let trailing_newline = self.description.ends_with('\n');
+        self.description = lines.join("\n");
+        if trailing_newline {
+            self.description.push('\n');
+        }
+        true
+    }
+}
+
+// "- [ ] text", "* [x] text" (any indent) -> (done, text)
+fn parse_item(line: &str) -> Option<(bool, &str)> {
+    let rest = line.trim_start();
+    let rest = rest
+        .strip_prefix("- ")
+        .or_else(|| rest.strip_prefix("* "))?;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    Some((done, rest[3..].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checklist_toggle() {
+        let mut t = Task::new("Pack", &Default::default());
+        t.description =
+            "For the trip:\n- [ ] passport\n  * [X] charger\n- [link] not an item\n".into();
+
+        let items = t.checklist();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].text, "charger");
+        assert!(items[1].done);
+        assert_eq!(t.checklist_progress(), Some((1, 2)));
+        assert_eq!(t.description_text(), "For the trip:\n- [link] not an item");
+
+        assert!(t.toggle_checklist_item(0));
+        assert!(t.toggle_checklist_item(1));
+        assert_eq!(
+            t.description,
+            "For the trip:\n- [x] passport\n  * [ ] charger\n- [link] not an item\n"
+        );
+        assert!(!t.toggle_checklist_item(2));
+    }
+}
@@ -0,0 +1,73 @@
+// File: src/model/csv.rs
+// CSV export for spreadsheets: one row per task, durations in minutes so they can be
+// summed. Export only, there's nothing in it a task could be rebuilt from.
+use crate::model::item::{CalendarListEntry, Task};
+
+const HEADER: &str = "Summary,Status,Priority,Due,Tags,Calendar,Estimated (min),Spent (min)";
+
+// RFC 4180: quoted when it holds a comma, a quote or a line break; quotes doubled
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The tasks in the order given, one row each. Calendars are named from `calendars`.
+pub fn to_csv(tasks: &[Task], calendars: &[CalendarListEntry]) -> String {
+    let mut out = format!("{}\r\n", HEADER);
+    for task in tasks {
+        let calendar = calendars
+            .iter()
+            .find(|c| c.href == task.calendar_href)
+            .map_or(task.calendar_href.as_str(), |c| c.name.as_str());
+        let row = [
+            task.summary.clone(),
+            task.status.label().to_string(),
+            if task.priority > 0 {
+                task.priority.to_string()
+            } else {
+                String::new()
+            },
+            task.format_due("%Y-%m-%d").unwrap_or_default(),
+            task.categories.join(", "),
+            calendar.to_string(),
+            task.estimated_duration
+                .map(|m| m.to_string())
+                .unwrap_or_default(),
+            if task.time_spent > 0 {
+                task.time_spent.to_string()
+            } else {
+                String::new()
+            },
+        ];
+        let row: Vec<String> = row.iter().map(|v| field(v)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let mut task = Task::new("Call \"Bob\", then Alice", &HashMap::new());
+        task.categories = vec!["work".to_string(), "phone".to_string()];
+        task.estimated_duration = Some(30);
+        task.time_spent = 45;
+        task.calendar_href = "/cal/work/".to_string();
+
+        let csv = to_csv(&[task], &[]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], HEADER);
+        assert_eq!(
+            lines[1],
+            "\"Call \"\"Bob\"\", then Alice\",To do,,,\"work, phone\",/cal/work/,30,45"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+// File: src/model/diff.rs
+// Field-level differences between two versions of a task, in readable form
+use crate::model::item::Task;
+use crate::model::tracking::format_minutes;
+use chrono::{DateTime, Local, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    // None when the field is unset on that side
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            show(&self.old),
+            show(&self.new)
+        )
+    }
+}
+
+type Getter = fn(&Task) -> Option<String>;
+
+// Only what a user would recognize; etag, href, sequence and depth are bookkeeping
+const FIELDS: &[(&str, Getter)] = &[
+    ("Summary", |t| non_empty(&t.summary)),
+    ("Description", |t| non_empty(&t.description)),
+    ("Status", |t| Some(t.status_name().to_string())),
+    ("Priority", |t| {
+        (t.priority > 0).then(|| t.priority.to_string())
+    }),
+    ("Flagged", |t| t.flagged.then(|| "yes".to_string())),
+    ("Start", |t| t.dtstart.map(|d| format_date(d, t.is_all_day))),
+    ("Due", |t| t.due.map(|d| format_date(d, t.is_all_day))),
+    ("Duration", |t| {
+        t.estimated_duration.map(|m| format!("{}m", m))
+    }),
+    ("Time spent", |t| {
+        (t.time_spent > 0).then(|| format_minutes(t.time_spent))
+    }),
+    ("Recurrence", |t| t.rrule.clone()),
+    ("Repeat from completion", |t| {
+        t.repeat_after_completion.then(|| "yes".to_string())
+    }),
+    ("Skipped dates", |t| {
+        join(t.exdates.iter().map(|d| format_date(*d, t.is_all_day)))
+    }),
+    ("Completed", |t| {
+        t.completed_at.map(|d| format_date(d, false))
+    }),
+    ("Tags", |t| {
+        let mut tags: Vec<_> = t.categories.iter().map(|c| format!("#{}", c)).collect();
+        tags.sort();
+        join(tags.into_iter())
+    }),
+    ("Contexts", |t| {
+        join(t.contexts.iter().map(|c| format!("@@{}", c)))
+    }),
+    ("Parent", |t| t.parent_uid.clone()),
+    ("Blocked by", |t| join(t.dependencies.iter().cloned())),
+    ("Reminders", |t| {
+        (!t.alarms.is_empty()).then(|| t.alarms.len().to_string())
+    }),
+    ("Attachments", |t| {
+        join(t.attachments.iter().map(|a| a.display_name()))
+    }),
+    ("Calendar", |t| non_empty(&t.calendar_href)),
+];
+
+impl Task {
+    /// What changed from `self` to `other`, one entry per differing field.
+    pub fn diff(&self, other: &Task) -> Vec<FieldChange> {
+        FIELDS
+            .iter()
+            .filter_map(|(field, get)| {
+                let (old, new) = (get(self), get(other));
+                (old != new).then_some(FieldChange {
+                    field: *field,
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+
+    /// Readable value of every set field, a snapshot to diff against later.
+    pub fn field_values(&self) -> BTreeMap<String, String> {
+        FIELDS
+            .iter()
+            .filter_map(|(field, get)| Some((field.to_string(), get(self)?)))
+            .collect()
+    }
+
+    /// Like `diff`, from a snapshot taken with `field_values`.
+    pub fn diff_from_values(&self, old: &BTreeMap<String, String>) -> Vec<FieldChange> {
+        FIELDS
+            .iter()
+            .filter_map(|(field, get)| {
+                let (old, new) = (old.get(*field).cloned(), get(self));
+                (old != new).then_some(FieldChange {
+                    field: *field,
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Fields both sides changed from `base`, to different values (what blocks a merge).
+pub fn conflicting_fields(base: &Task, local: &Task, server: &Task) -> Vec<&'static str> {
+    let server_changes = base.diff(server);
+    base.diff(local)
+        .into_iter()
+        .filter(|l| {
+            server_changes
+                .iter()
+                .any(|s| s.field == l.field && s.new != l.new)
+        })
+        .map(|l| l.field)
+        .collect()
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+fn join(items: impl Iterator<Item = String>) -> Option<String> {
+    let list: Vec<String> = items.collect();
+    (!list.is_empty()).then(|| list.join(", "))
+}
+
+fn format_date(dt: DateTime<Utc>, all_day: bool) -> String {
+    if all_day {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_and_conflicts() {
+        let aliases = Default::default();
+        let base = Task::new("Report #work !5 @2030-01-01", &aliases);
+        assert!(base.diff(&base.clone()).is_empty());
+
+        let mut local = base.clone();
+        local.summary = "Final report".to_string();
+        local.priority = 0;
+        local.categories.push("urgent".to_string());
+        let changes = base.diff(&local);
+        assert_eq!(
+            changes.iter().map(|c| c.field).collect::<Vec<_>>(),
+            ["Summary", "Priority", "Tags"]
+        );
+        assert_eq!(changes[1].to_string(), "Priority: 5 -> (none)");
+        assert_eq!(changes[2].new.as_deref(), Some("#urgent, #work"));
+        assert_eq!(local.diff_from_values(&base.field_values()), changes);
+
+        let mut server = base.clone();
+        server.summary = "Quarterly report".to_string();
+        server.priority = 0;
+        server.description = "Numbers".to_string();
+        // Same new priority on both sides isn't a conflict
+        assert_eq!(conflicting_fields(&base, &local, &server), ["Summary"]);
+    }
+}
@@ -0,0 +1,202 @@
+// File: src/model/field.rs
+// One task field at a time in the smart input syntax, for editors that go field by
+// field (the TUI details pane) instead of rewriting the whole line.
+use crate::model::item::Task;
+use crate::model::parser::tokenize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskField {
+    Due,
+    Start,
+    Priority,
+    Tags,
+    Duration,
+    Recurrence,
+}
+
+impl TaskField {
+    pub const ALL: [TaskField; 6] = [
+        TaskField::Due,
+        TaskField::Start,
+        TaskField::Priority,
+        TaskField::Tags,
+        TaskField::Duration,
+        TaskField::Recurrence,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TaskField::Due => "Due",
+            TaskField::Start => "Start",
+            TaskField::Priority => "Priority",
+            TaskField::Tags => "Tags",
+            TaskField::Duration => "Duration",
+            TaskField::Recurrence => "Repeat",
+        }
+    }
+
+    /// What to type, shown next to the input.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            TaskField::Due | TaskField::Start => "e.g. tomorrow 17:00, friday, 2025-03-01, 2w",
+            TaskField::Priority => "1-9, high, med or low",
+            TaskField::Tags => "space separated, e.g. work #home:garden",
+            TaskField::Duration => "e.g. 30m, 2h, 1d",
+            TaskField::Recurrence => "e.g. daily, weekly, every 2 weeks",
+        }
+    }
+
+    /// The next field in `ALL`, wrapping around.
+    pub fn next(&self) -> TaskField {
+        let idx = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> TaskField {
+        let idx = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    // Put in front of a bare value ("tomorrow" -> "@tomorrow")
+    fn marker(&self) -> &'static str {
+        match self {
+            TaskField::Due | TaskField::Recurrence => "@",
+            TaskField::Start => "^",
+            TaskField::Priority => "!",
+            TaskField::Tags => "#",
+            TaskField::Duration => "~",
+        }
+    }
+
+    // Values already starting with one of these are left alone
+    fn is_marked(&self, word: &str) -> bool {
+        let prefixes: &[&str] = match self {
+            TaskField::Due => &["@", "due:"],
+            TaskField::Start => &["^", "start:"],
+            TaskField::Priority => &["!"],
+            TaskField::Tags => &["#"],
+            TaskField::Duration => &["~", "est:"],
+            TaskField::Recurrence => &["@", "rec:", "rrule:", "RRULE:"],
+        };
+        prefixes.iter().any(|p| word.starts_with(p))
+    }
+}
+
+impl Task {
+    /// The field as it appears in `to_smart_string` ("@2025-03-01 17:00", "#work #home"),
+    /// empty when unset.
+    pub fn field_smart_string(&self, field: TaskField, named_priorities: bool) -> String {
+        let mut only = self.clone();
+        only.summary.clear();
+        only.contexts.clear();
+        if field != TaskField::Priority {
+            only.priority = 0;
+        }
+        if field != TaskField::Due {
+            only.due = None;
+        }
+        if field != TaskField::Start {
+            only.dtstart = None;
+        }
+        if field != TaskField::Tags {
+            only.categories.clear();
+        }
+        if field != TaskField::Duration {
+            only.estimated_duration = None;
+        }
+        if field != TaskField::Recurrence {
+            only.rrule = None;
+        }
+        only.to_smart_string_with(named_priorities)
+            .trim()
+            .to_string()
+    }
+
+    /// Sets one field from smart input, the marker being optional ("tomorrow" works as
+    /// well as "@tomorrow"). Empty input clears the field, the others are left alone.
+    pub fn apply_field_input(
+        &mut self,
+        field: TaskField,
+        input: &str,
+        aliases: &HashMap<String, Vec<String>>,
+    ) -> Result<(), String> {
+        // Every tag needs its '#', dates and rules only the first word ("@tomorrow 17:00")
+        let each_word = field == TaskField::Tags;
+        let mut words: Vec<String> = Vec::new();
+        for (i, word) in tokenize(input.trim()).into_iter().enumerate() {
+            if (i == 0 || each_word) && !field.is_marked(word) {
+                words.push(format!("{}{}", field.marker(), word));
+            } else {
+                words.push(word.to_string());
+            }
+        }
+        // The other date goes along so both keep the same all-day flag
+        match field {
+            TaskField::Due => words.push(self.field_smart_string(TaskField::Start, false)),
+            TaskField::Start => words.push(self.field_smart_string(TaskField::Due, false)),
+            _ => {}
+        }
+
+        let mut parsed = self.clone();
+        parsed.apply_smart_input(&words.join(" "), aliases);
+        if !parsed.summary.is_empty() {
+            return Err(format!("Not understood: {}", parsed.summary));
+        }
+        match field {
+            TaskField::Due | TaskField::Start => {
+                self.due = parsed.due;
+                self.dtstart = parsed.dtstart;
+                self.is_all_day = parsed.is_all_day;
+                if field == TaskField::Due {
+                    self.due_tz = parsed.due_tz;
+                } else {
+                    self.dtstart_tz = parsed.dtstart_tz;
+                }
+            }
+            TaskField::Priority => self.priority = parsed.priority,
+            TaskField::Tags => self.categories = parsed.categories,
+            TaskField::Duration => self.estimated_duration = parsed.estimated_duration,
+            TaskField::Recurrence => self.rrule = parsed.rrule,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_round_trip() {
+        let aliases = HashMap::new();
+        let mut task = Task::new("Water plants !3 ~15m #home @weekly", &aliases);
+        assert_eq!(task.field_smart_string(TaskField::Priority, false), "!3");
+        assert_eq!(task.field_smart_string(TaskField::Due, false), "");
+
+        task.apply_field_input(TaskField::Tags, "home:garden chores", &aliases)
+            .unwrap();
+        assert_eq!(task.categories, ["home:garden", "chores"]);
+        task.apply_field_input(TaskField::Priority, "high", &aliases)
+            .unwrap();
+        assert_eq!(task.priority, 1);
+        task.apply_field_input(TaskField::Duration, "", &aliases)
+            .unwrap();
+        assert_eq!(task.estimated_duration, None);
+        task.apply_field_input(TaskField::Due, "2030-05-01 17:00", &aliases)
+            .unwrap();
+        assert_eq!(
+            task.field_smart_string(TaskField::Due, false),
+            "@2030-05-01 17:00"
+        );
+        // The rest is untouched
+        assert_eq!(task.summary, "Water plants");
+        assert_eq!(task.rrule.as_deref(), Some("FREQ=WEEKLY"));
+
+        assert!(
+            task.apply_field_input(TaskField::Duration, "a while", &aliases)
+                .is_err()
+        );
+        assert_eq!(task.estimated_duration, None);
+    }
+}
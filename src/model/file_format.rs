@@ -0,0 +1,126 @@
+// File: src/model/file_format.rs
+// Files tasks are imported from and exported to (`cfait import/export`, GUI buttons).
+use crate::model::adapter::{join_ics, split_ics};
+use crate::model::item::{CalendarListEntry, Task};
+use crate::model::{csv, markdown, org, taskwarrior};
+use std::fmt;
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FileFormat {
+    #[default]
+    Ics,
+    // `task export` / `task import` JSON
+    Taskwarrior,
+    // Export only
+    Org,
+    // Export only, usually of a filter's result rather than a whole calendar
+    Csv,
+    // Export only, a checklist
+    Markdown,
+}
+
+impl FileFormat {
+    /// What a calendar can be exported to (the GUI "Export" menu).
+    pub const EXPORT: [FileFormat; 5] = [
+        FileFormat::Ics,
+        FileFormat::Taskwarrior,
+        FileFormat::Org,
+        FileFormat::Csv,
+        FileFormat::Markdown,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileFormat::Ics => "iCalendar (.ics)",
+            FileFormat::Taskwarrior => "Taskwarrior (.json)",
+            FileFormat::Org => "Org (.org)",
+            FileFormat::Csv => "Spreadsheet (.csv)",
+            FileFormat::Markdown => "Markdown checklist (.md)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Ics => "ics",
+            FileFormat::Taskwarrior => "json",
+            FileFormat::Org => "org",
+            FileFormat::Csv => "csv",
+            FileFormat::Markdown => "md",
+        }
+    }
+
+    /// Formats for reports: the GUI writes the list as filtered on screen rather than
+    /// the whole calendar.
+    pub fn is_report(&self) -> bool {
+        matches!(self, FileFormat::Csv | FileFormat::Markdown)
+    }
+
+    /// Format named on the command line (`--format`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ics" | "ical" | "icalendar" => Some(FileFormat::Ics),
+            "taskwarrior" | "tw" => Some(FileFormat::Taskwarrior),
+            "org" => Some(FileFormat::Org),
+            "csv" => Some(FileFormat::Csv),
+            "markdown" | "md" => Some(FileFormat::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Guessed from the extension, iCalendar when it's none of ours.
+    pub fn from_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        Self::EXPORT
+            .into_iter()
+            .find(|f| f.extension().eq_ignore_ascii_case(ext))
+            .unwrap_or_default()
+    }
+
+    /// Tasks in `raw`, for `calendar_href`. Tasks without a UID get a new one.
+    pub fn read(self, raw: &str, calendar_href: &str) -> Result<Vec<Task>, String> {
+        let mut tasks = match self {
+            FileFormat::Ics => split_ics(raw)
+                .iter()
+                .map(|chunk| {
+                    Task::from_ics(
+                        chunk,
+                        String::new(),
+                        String::new(),
+                        calendar_href.to_string(),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            FileFormat::Taskwarrior => taskwarrior::parse(raw, calendar_href)?,
+            FileFormat::Org | FileFormat::Csv | FileFormat::Markdown => {
+                return Err(format!("{} files can't be imported", self.label()));
+            }
+        };
+        for task in tasks.iter_mut().filter(|t| t.uid.is_empty()) {
+            task.uid = Uuid::new_v4().to_string();
+        }
+        Ok(tasks)
+    }
+
+    /// `tasks` in this format. `title` names the calendar where the format has room for it,
+    /// `calendars` name the calendar of each task (CSV).
+    pub fn write(self, tasks: &[Task], title: &str, calendars: &[CalendarListEntry]) -> String {
+        match self {
+            FileFormat::Ics => join_ics(tasks),
+            FileFormat::Taskwarrior => taskwarrior::to_json(tasks),
+            FileFormat::Org => org::to_org(tasks, title),
+            FileFormat::Csv => csv::to_csv(tasks, calendars),
+            FileFormat::Markdown => markdown::to_markdown(tasks, title),
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
@@ -0,0 +1,135 @@
+// File: src/model/group.rs
+// Sections of the task list ("Today / Tomorrow / Later / No date"), picked in
+// the config or switched at runtime. The grouping itself is in `store`.
+use crate::model::item::{CalendarListEntry, Task};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    None,
+    Due,
+    Tag,
+    Priority,
+    Calendar,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 5] = [
+        GroupBy::None,
+        GroupBy::Due,
+        GroupBy::Tag,
+        GroupBy::Priority,
+        GroupBy::Calendar,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GroupBy::None => "No groups",
+            GroupBy::Due => "By due date",
+            GroupBy::Tag => "By tag",
+            GroupBy::Priority => "By priority",
+            GroupBy::Calendar => "By calendar",
+        }
+    }
+
+    /// The next grouping in `ALL`, wrapping around (for a cycling key/button).
+    pub fn next(&self) -> GroupBy {
+        let idx = Self::ALL.iter().position(|g| g == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Section of `task`: a rank to order the sections, and its title.
+    /// Finished tasks all go to a last "Done" section.
+    pub fn key(
+        &self,
+        task: &Task,
+        calendars: &[CalendarListEntry],
+        today: NaiveDate,
+    ) -> (usize, String) {
+        if task.status.is_done() && *self != GroupBy::None {
+            return (usize::MAX, "Done".to_string());
+        }
+        let (rank, title) = match self {
+            GroupBy::None => (0, ""),
+            GroupBy::Due => match task.due.map(|d| d.with_timezone(&Local).date_naive()) {
+                None => (5, "No date"),
+                Some(d) if d < today => (0, "Overdue"),
+                Some(d) if d == today => (1, "Today"),
+                Some(d) if d == today.succ_opt().unwrap_or(today) => (2, "Tomorrow"),
+                Some(d) if (d - today).num_days() < 7 => (3, "This week"),
+                Some(_) => (4, "Later"),
+            },
+            GroupBy::Priority => match task.priority {
+                1..=4 => (0, "High"),
+                5 => (1, "Medium"),
+                6..=9 => (2, "Low"),
+                _ => (3, "No priority"),
+            },
+            GroupBy::Tag => {
+                // A task with several tags goes under the first one alphabetically
+                return match task.categories.iter().min() {
+                    Some(tag) => (0, format!("#{}", tag)),
+                    None => (1, "No tag".to_string()),
+                };
+            }
+            GroupBy::Calendar => {
+                let pos = calendars.iter().position(|c| c.href == task.calendar_href);
+                return match pos {
+                    Some(i) => (i, calendars[i].name.clone()),
+                    None => (calendars.len(), task.calendar_href.clone()),
+                };
+            }
+        };
+        (rank, title.to_string())
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn test_group_keys() {
+        let aliases = Default::default();
+        let today = Local::now().date_naive();
+        let at_noon = |days: i64| {
+            let d = today + Duration::days(days);
+            Local
+                .from_local_datetime(&d.and_hms_opt(12, 0, 0).unwrap())
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let mut t = Task::new("Report #work #admin !5", &aliases);
+        let title = |by: GroupBy, t: &Task| by.key(t, &[], today).1;
+
+        for (days, expected) in [
+            (-1, "Overdue"),
+            (0, "Today"),
+            (1, "Tomorrow"),
+            (3, "This week"),
+            (30, "Later"),
+        ] {
+            t.due = Some(at_noon(days));
+            assert_eq!(title(GroupBy::Due, &t), expected);
+        }
+        t.due = None;
+        assert_eq!(title(GroupBy::Due, &t), "No date");
+        assert_eq!(title(GroupBy::Priority, &t), "Medium");
+        assert_eq!(title(GroupBy::Tag, &t), "#admin");
+
+        t.set_status(crate::model::TaskStatus::Completed);
+        assert_eq!(title(GroupBy::Priority, &t), "Done");
+        assert_eq!(GroupBy::Calendar.next(), GroupBy::None);
+    }
+}
@@ -1,5 +1,7 @@
 // File: src/model/item.rs
-use chrono::{DateTime, Utc};
+use crate::model::sort::SortOrder;
+use crate::model::tracking::TimeSession;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
@@ -10,13 +12,30 @@ pub struct CalendarListEntry {
     pub name: String,
     pub href: String,
     pub color: Option<String>,
+    // current-user-privilege-set lacks write access (e.g. a read-only share)
+    #[serde(default)]
+    pub read_only: bool,
+    // Owner principal's display name, when the server reports one
+    #[serde(default)]
+    pub owner: Option<String>,
+    // Someone else's calendar shared with us
+    #[serde(default)]
+    pub shared: bool,
+    // Label of the secondary account listing it (None = primary account)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub enum TaskStatus {
+    // The aliases are the RFC 5545 names, easier to type in the config
+    #[serde(alias = "needs-action")]
     NeedsAction,
+    #[serde(alias = "in-process")]
     InProcess,
+    #[serde(alias = "completed")]
     Completed,
+    #[serde(alias = "cancelled")]
     Cancelled,
 }
 
@@ -24,6 +43,75 @@ impl TaskStatus {
     pub fn is_done(&self) -> bool {
         matches!(self, Self::Completed | Self::Cancelled)
     }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NeedsAction => "To do",
+            Self::InProcess => "In progress",
+            Self::Completed => "Done",
+            Self::Cancelled => "Cancelled",
+        }
+    }
+}
+
+/// How a DUE / DTSTART is written in the ICS. The instant itself is always kept in UTC.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DateTz {
+    #[default]
+    Utc,
+    // No zone: the same wall-clock time wherever the user is
+    Floating,
+    // TZID parameter, e.g. "Europe/Brussels"
+    Tzid(String),
+}
+
+impl DateTz {
+    pub fn is_utc(&self) -> bool {
+        *self == Self::Utc
+    }
+
+    // Unknown TZIDs (Windows names, custom VTIMEZONEs) are treated as floating
+    fn zone(id: &str) -> Option<chrono_tz::Tz> {
+        if let Ok(tz) = id.parse() {
+            return Some(tz);
+        }
+        // Prefixed IDs like "/mozilla.org/20050126_1/Europe/Berlin"
+        id.match_indices('/')
+            .find_map(|(i, _)| id[i + 1..].parse().ok())
+    }
+
+    /// Instant of a wall-clock time in this zone.
+    pub fn to_utc(&self, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let tz = match self {
+            Self::Utc => return Some(naive.and_utc()),
+            Self::Tzid(id) => Self::zone(id),
+            Self::Floating => None,
+        };
+        // Skipped by a DST change: take the first valid time
+        match tz {
+            Some(tz) => tz
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc)),
+            None => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc)),
+        }
+    }
+
+    /// Wall-clock time of `dt` in this zone, to write it back.
+    pub fn to_naive(&self, dt: DateTime<Utc>) -> NaiveDateTime {
+        let tz = match self {
+            Self::Utc => return dt.naive_utc(),
+            Self::Tzid(id) => Self::zone(id),
+            Self::Floating => None,
+        };
+        match tz {
+            Some(tz) => dt.with_timezone(&tz).naive_local(),
+            None => dt.with_timezone(&Local).naive_local(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -33,27 +121,177 @@ pub struct RawProperty {
     pub params: Vec<(String, String)>,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AlarmTrigger {
+    // Offset in minutes (negative = before). Relative to DTSTART, or DUE when
+    // `related_end` is set (or when the task has no start date).
+    Relative { minutes: i64, related_end: bool },
+    Absolute(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Alarm {
+    pub trigger: AlarmTrigger,
+    // DISPLAY, AUDIO, EMAIL...
+    pub action: String,
+    pub description: Option<String>,
+}
+
+impl Alarm {
+    pub fn fire_time(&self, task: &Task) -> Option<DateTime<Utc>> {
+        match self.trigger {
+            AlarmTrigger::Absolute(dt) => Some(dt),
+            AlarmTrigger::Relative {
+                minutes,
+                related_end,
+            } => {
+                let anchor = if related_end {
+                    task.due
+                } else {
+                    task.dtstart.or(task.due)
+                }?;
+                Some(anchor + chrono::Duration::minutes(minutes))
+            }
+        }
+    }
+}
+
+/// A RECURRENCE-ID instance: one occurrence of a recurring task changed on its own.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RecurrenceOverride {
+    // Original start of the occurrence it replaces
+    pub recurrence_id: DateTime<Utc>,
+    pub status: TaskStatus,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub summary: String,
+    pub due: Option<DateTime<Utc>>,
+    pub dtstart: Option<DateTime<Utc>>,
+    // The whole VTODO, written back as-is apart from the fields we change
+    pub raw: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Attachment {
+    // A URI, or the base64 data itself when `inline` is set
+    pub value: String,
+    pub inline: bool,
+    pub fmttype: Option<String>,
+    pub filename: Option<String>,
+    // RFC 8607 MANAGED-ID, set by the server for managed attachments
+    pub managed_id: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl Attachment {
+    pub fn inline(data: &[u8], filename: &str, fmttype: &str) -> Self {
+        use base64::Engine;
+        Self {
+            value: base64::engine::general_purpose::STANDARD.encode(data),
+            inline: true,
+            fmttype: Some(fmttype.to_string()),
+            filename: Some(filename.to_string()),
+            managed_id: None,
+            size: Some(data.len() as u64),
+        }
+    }
+
+    pub fn decode_inline(&self) -> Option<Vec<u8>> {
+        use base64::Engine;
+        if !self.inline {
+            return None;
+        }
+        // Some clients fold the data with whitespace in it
+        let data: String = self.value.split_whitespace().collect();
+        base64::engine::general_purpose::STANDARD.decode(data).ok()
+    }
+
+    pub fn display_name(&self) -> String {
+        if let Some(name) = &self.filename {
+            return name.clone();
+        }
+        if self.inline {
+            return "attachment".to_string();
+        }
+        self.value
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|s| !s.is_empty())
+            .unwrap_or(&self.value)
+            .to_string()
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Task {
     pub uid: String,
     pub summary: String,
     pub description: String,
     pub status: TaskStatus,
+    // X-STATUS: user-defined status on top of `status` (see model::status)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_status: Option<String>,
     pub estimated_duration: Option<u32>,
+    // X-TIME-SPENT: minutes actually tracked (finished sessions)
+    #[serde(default)]
+    pub time_spent: u32,
+    // X-TIME-SESSION log, the last one may still be running
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_sessions: Vec<TimeSession>,
     pub due: Option<DateTime<Utc>>,
     pub dtstart: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "DateTz::is_utc")]
+    pub due_tz: DateTz,
+    #[serde(default, skip_serializing_if = "DateTz::is_utc")]
+    pub dtstart_tz: DateTz,
+    // Due/start are plain days (VALUE=DATE), not times
+    #[serde(default)]
+    pub is_all_day: bool,
+    // SEQUENCE: revision number, bumped on each local change
+    #[serde(default)]
+    pub sequence: u32,
+    // COMPLETED: when the task was marked done (only set while it is Completed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+    // CREATED: when the task was first added (unknown for some other clients' tasks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
     pub priority: u8,
+    // X-APPLE-FLAGGED, the flag of Apple Reminders
+    #[serde(default)]
+    pub flagged: bool,
     pub parent_uid: Option<String>,
     pub dependencies: Vec<String>,
     pub etag: String,
     pub href: String,
     pub calendar_href: String,
+    // Label of the secondary account of the calendar (None = primary account). Two
+    // servers can use the same calendar paths, the href alone doesn't tell them apart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
     pub categories: Vec<String>,
+    // GTD contexts (@@home, @@errands): where/with what the task can be done.
+    // X-CONTEXT, kept apart from the CATEGORIES tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contexts: Vec<String>,
     pub depth: usize,
     pub rrule: Option<String>,
+    // X-CFAIT-REPEAT-FROM:COMPLETION: the next occurrence counts from when it was done
+    #[serde(default)]
+    pub repeat_after_completion: bool,
+    // EXDATE: occurrences removed from the series
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exdates: Vec<DateTime<Utc>>,
+    // Exception VTODOs sharing our UID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<RecurrenceOverride>,
     pub unmapped_properties: Vec<RawProperty>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub raw_components: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alarms: Vec<Alarm>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
 }
 
 impl Task {
@@ -63,20 +301,37 @@ impl Task {
             summary: String::new(),
             description: String::new(),
             status: TaskStatus::NeedsAction,
+            custom_status: None,
             estimated_duration: None,
+            time_spent: 0,
+            time_sessions: Vec::new(),
             due: None,
             dtstart: None,
+            due_tz: DateTz::Utc,
+            dtstart_tz: DateTz::Utc,
+            is_all_day: false,
+            sequence: 0,
+            completed_at: None,
+            created: Some(Utc::now()),
             priority: 0,
+            flagged: false,
             parent_uid: None,
             dependencies: Vec::new(),
             etag: String::new(),
             href: String::new(),
             calendar_href: String::new(),
+            account: None,
             categories: Vec::new(),
+            contexts: Vec::new(),
             depth: 0,
             rrule: None,
+            repeat_after_completion: false,
+            exdates: Vec::new(),
+            overrides: Vec::new(),
             unmapped_properties: Vec::new(),
             raw_components: Vec::new(),
+            alarms: Vec::new(),
+            attachments: Vec::new(),
         };
         task.apply_smart_input(input, aliases);
         task
@@ -104,6 +359,17 @@ impl Task {
         }
     }
 
+    /// Due date in the user's zone, with the time unless it's an all-day task.
+    pub fn format_due(&self, date_fmt: &str) -> Option<String> {
+        let local = self.due?.with_timezone(&Local);
+        // 23:59: end-of-day dates stored before all-day support
+        if self.is_all_day || local.format("%H:%M").to_string() == "23:59" {
+            Some(local.format(date_fmt).to_string())
+        } else {
+            Some(local.format(&format!("{} %H:%M", date_fmt)).to_string())
+        }
+    }
+
     pub fn checkbox_symbol(&self) -> &'static str {
         match self.status {
             TaskStatus::Completed => "[x]",
@@ -115,6 +381,23 @@ impl Task {
 
     // --- Logic ---
 
+    /// Changes the status, stamping or clearing the completion time.
+    /// Drops the custom status (see `apply_status` to set one).
+    pub fn set_status(&mut self, status: TaskStatus) {
+        self.custom_status = None;
+        if status.is_done() {
+            self.stop_tracking(Utc::now());
+        }
+        if status == TaskStatus::Completed {
+            if self.status != TaskStatus::Completed || self.completed_at.is_none() {
+                self.completed_at = Some(Utc::now());
+            }
+        } else {
+            self.completed_at = None;
+        }
+        self.status = status;
+    }
+
     pub fn compare_with_cutoff(&self, other: &Self, cutoff: Option<DateTime<Utc>>) -> Ordering {
         fn status_prio(s: TaskStatus) -> u8 {
             match s {
@@ -188,14 +471,18 @@ impl Task {
         self.summary.cmp(&other.summary)
     }
 
-    pub fn organize_hierarchy(mut tasks: Vec<Task>, cutoff: Option<DateTime<Utc>>) -> Vec<Task> {
+    pub fn organize_hierarchy(
+        mut tasks: Vec<Task>,
+        cutoff: Option<DateTime<Utc>>,
+        order: SortOrder,
+    ) -> Vec<Task> {
         let present_uids: HashSet<String> = tasks.iter().map(|t| t.uid.clone()).collect();
         let mut children_map: HashMap<String, Vec<Task>> = HashMap::new();
         let mut roots: Vec<Task> = Vec::new();
 
-        tasks.sort_by(|a, b| a.compare_with_cutoff(b, cutoff));
+        tasks.sort_by(|a, b| order.compare(a, b, cutoff));
 
-        for mut task in tasks {
+        for mut task in tasks.iter().cloned() {
             let is_orphan = match &task.parent_uid {
                 Some(p_uid) => !present_uids.contains(p_uid),
                 None => true,
@@ -213,8 +500,15 @@ impl Task {
         }
 
         let mut result = Vec::new();
+        let mut emitted = HashSet::new();
         for root in roots {
-            Self::append_task_and_children(&root, &mut result, &children_map, 0);
+            Self::append_task_and_children(&root, &mut result, &children_map, 0, &mut emitted);
+        }
+        // Parent loops (e.g. synced from another client) have no root: show them flat
+        for task in &tasks {
+            if !emitted.contains(&task.uid) {
+                Self::append_task_and_children(task, &mut result, &children_map, 0, &mut emitted);
+            }
         }
         result
     }
@@ -224,13 +518,17 @@ impl Task {
         result: &mut Vec<Task>,
         map: &HashMap<String, Vec<Task>>,
         depth: usize,
+        emitted: &mut HashSet<String>,
     ) {
+        if !emitted.insert(task.uid.clone()) {
+            return;
+        }
         let mut t = task.clone();
         t.depth = depth;
         result.push(t);
         if let Some(children) = map.get(&task.uid) {
             for child in children {
-                Self::append_task_and_children(child, result, map, depth + 1);
+                Self::append_task_and_children(child, result, map, depth + 1, emitted);
             }
         }
     }
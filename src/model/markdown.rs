@@ -0,0 +1,51 @@
+// File: src/model/markdown.rs
+// Markdown checklist export, to paste a list into a wiki, an issue or a chat.
+use crate::model::item::{Task, TaskStatus};
+use crate::model::sort::SortOrder;
+
+/// The tasks as a "- [ ]" checklist under a `title` heading, subtasks indented under
+/// their parent.
+pub fn to_markdown(tasks: &[Task], title: &str) -> String {
+    let mut out = format!("# {}\n\n", title);
+    for task in Task::organize_hierarchy(tasks.to_vec(), None, SortOrder::Smart) {
+        let check = if task.status.is_done() { 'x' } else { ' ' };
+        let mut line = format!("{}- [{}] ", "  ".repeat(task.depth), check);
+        match task.status {
+            TaskStatus::Cancelled => line.push_str(&format!("~~{}~~", task.summary)),
+            TaskStatus::InProcess => line.push_str(&format!("{} _(in progress)_", task.summary)),
+            _ => line.push_str(&task.summary),
+        }
+        if let Some(due) = task.format_due("%Y-%m-%d") {
+            line.push_str(&format!(" (due {})", due));
+        }
+        for tag in &task.categories {
+            line.push_str(&format!(" #{}", tag));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_markdown_checklist() {
+        let mut parent = Task::new("Release 1.2", &HashMap::new());
+        parent.categories = vec!["work".to_string()];
+        let mut done = Task::new("Write changelog", &HashMap::new());
+        done.parent_uid = Some(parent.uid.clone());
+        done.set_status(TaskStatus::Completed);
+        let mut dropped = Task::new("Blog post", &HashMap::new());
+        dropped.parent_uid = Some(parent.uid.clone());
+        dropped.status = TaskStatus::Cancelled;
+
+        let md = to_markdown(&[done, parent, dropped], "Work");
+        assert!(md.starts_with("# Work\n\n- [ ] Release 1.2 #work\n"));
+        assert!(md.contains("\n  - [x] Write changelog\n"));
+        assert!(md.contains("\n  - [x] ~~Blog post~~\n"));
+    }
+}
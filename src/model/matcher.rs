@@ -1,10 +1,71 @@
 // File: ./src/model/matcher.rs
 // Handles logic for checking if a task matches a search query
 use crate::model::item::{Task, TaskStatus};
-use chrono::Utc;
+use chrono::Local;
+use std::ops::Range;
+
+// Prefixes of the filter parts of a query; anything else is free text
+const FILTER_PREFIXES: &[&str] = &["~", "!", "^", "@", "#", "start:", "done:", "is:"];
+
+/// The free-text words of a search query (lowercase), what results highlight.
+/// None for filter expressions (":due<7d AND #work").
+pub fn search_text_terms(term: &str) -> Vec<String> {
+    if term.starts_with(':') {
+        return Vec::new();
+    }
+    term.to_lowercase()
+        .split_whitespace()
+        .filter(|part| !FILTER_PREFIXES.iter().any(|p| part.starts_with(p)))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Byte ranges of `text` matching any of `terms` (case-insensitive), sorted and merged.
+pub fn highlight_ranges(text: &str, terms: &[String]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for term in terms.iter().filter(|t| !t.is_empty()) {
+        for (start, _) in text.char_indices() {
+            if let Some(len) = match_len(&text[start..], term) {
+                ranges.push(start..start + len);
+            }
+        }
+    }
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for r in ranges {
+        match merged.last_mut() {
+            Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+            _ => merged.push(r),
+        }
+    }
+    merged
+}
+
+// Bytes of `text` matching lowercase `term` at its start. Char by char, as
+// lowercasing can change byte lengths
+fn match_len(text: &str, term: &str) -> Option<usize> {
+    let mut wanted = term.chars().peekable();
+    for (i, c) in text.char_indices() {
+        for lc in c.to_lowercase() {
+            if wanted.next() != Some(lc) {
+                return None;
+            }
+        }
+        if wanted.peek().is_none() {
+            return Some(i + c.len_utf8());
+        }
+    }
+    None
+}
 
 impl Task {
     pub fn matches_search_term(&self, term: &str) -> bool {
+        self.matches_search_in(term, None)
+    }
+
+    /// Like `matches_search_term`, free text also matching the name of the
+    /// task's calendar.
+    pub fn matches_search_in(&self, term: &str, calendar_name: Option<&str>) -> bool {
         if term.is_empty() {
             return true;
         }
@@ -148,7 +209,7 @@ impl Task {
                     ("=", val_str)
                 };
 
-                let now = Utc::now().date_naive();
+                let now = Local::now().date_naive();
                 // Reuse logic from 'parse_smart_date' conceptual equivalents or simple parsing
                 let target_date = if date_str == "today" {
                     Some(now)
@@ -161,7 +222,7 @@ impl Task {
                 if let Some(target) = target_date {
                     match self.dtstart {
                         Some(dt) => {
-                            let t_date = dt.naive_utc().date();
+                            let t_date = dt.with_timezone(&Local).date_naive();
                             match op {
                                 "<" => {
                                     if t_date >= target {
@@ -196,6 +257,18 @@ impl Task {
                 }
             }
 
+            // Context Filter (@@home)
+            if let Some(ctx_query) = part.strip_prefix("@@") {
+                if !self
+                    .contexts
+                    .iter()
+                    .any(|c| c.to_lowercase().contains(ctx_query))
+                {
+                    return false;
+                }
+                continue;
+            }
+
             // 3. Due Date Filter (@<2025-01-01, @>today)
             if part.starts_with('@') {
                 let (op, val_str) = if let Some(stripped) = part.strip_prefix("@<=") {
@@ -213,7 +286,7 @@ impl Task {
                 };
 
                 // Parse Target Date
-                let now = Utc::now().date_naive();
+                let now = Local::now().date_naive();
                 let target_date = if val_str == "today" {
                     Some(now)
                 } else if val_str == "tomorrow" {
@@ -240,7 +313,7 @@ impl Task {
                 if let Some(target) = target_date {
                     match self.due {
                         Some(dt) => {
-                            let t_date = dt.naive_utc().date();
+                            let t_date = dt.with_timezone(&Local).date_naive();
                             match op {
                                 "<" => {
                                     if t_date >= target {
@@ -275,6 +348,63 @@ impl Task {
                 }
             }
 
+            // COMPLETION DATE FILTER (done:today, done:>=1w = completed in the last week)
+            if let Some(val_str) = part.strip_prefix("done:") {
+                let (op, date_str) = if let Some(s) = val_str.strip_prefix("<=") {
+                    ("<=", s)
+                } else if let Some(s) = val_str.strip_prefix(">=") {
+                    (">=", s)
+                } else if let Some(s) = val_str.strip_prefix('<') {
+                    ("<", s)
+                } else if let Some(s) = val_str.strip_prefix('>') {
+                    (">", s)
+                } else {
+                    ("=", val_str)
+                };
+
+                // Relative offsets count backwards: completion is in the past
+                let now = Local::now().date_naive();
+                let target_date = if date_str == "today" {
+                    Some(now)
+                } else if date_str == "yesterday" {
+                    Some(now - chrono::Duration::days(1))
+                } else if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
+                    Some(date)
+                } else if let Some(n) = date_str.strip_suffix('d') {
+                    n.parse::<i64>()
+                        .ok()
+                        .map(|d| now - chrono::Duration::days(d))
+                } else if let Some(n) = date_str.strip_suffix('w') {
+                    n.parse::<i64>()
+                        .ok()
+                        .map(|w| now - chrono::Duration::days(w * 7))
+                } else if let Some(n) = date_str.strip_suffix("mo") {
+                    n.parse::<i64>()
+                        .ok()
+                        .map(|m| now - chrono::Duration::days(m * 30))
+                } else {
+                    None
+                };
+
+                if let Some(target) = target_date {
+                    let Some(done) = self.completed_at else {
+                        return false;
+                    };
+                    let d = done.with_timezone(&Local).date_naive();
+                    let keep = match op {
+                        "<" => d < target,
+                        ">" => d > target,
+                        "<=" => d <= target,
+                        ">=" => d >= target,
+                        _ => d == target,
+                    };
+                    if !keep {
+                        return false;
+                    }
+                    continue;
+                }
+            }
+
             // 2. Tag Filter (#work)
             if let Some(tag_query) = part.strip_prefix('#') {
                 if !self
@@ -306,6 +436,23 @@ impl Task {
                 }
                 continue;
             }
+            if part == "is:flagged" {
+                if !self.flagged {
+                    return false;
+                }
+                continue;
+            }
+            // Custom statuses, spaces typed as dashes (is:waiting, is:on-hold)
+            if let Some(name) = part.strip_prefix("is:") {
+                let matches = self
+                    .custom_status
+                    .as_ref()
+                    .is_some_and(|s| s.to_lowercase().replace(' ', "-") == name);
+                if !matches {
+                    return false;
+                }
+                continue;
+            }
 
             // Standard Text Search
             // Explicitly search categories for matches even without # prefix
@@ -315,6 +462,11 @@ impl Task {
                     .categories
                     .iter()
                     .any(|c| c.to_lowercase().contains(part))
+                && !self
+                    .contexts
+                    .iter()
+                    .any(|c| c.to_lowercase().contains(part))
+                && !calendar_name.is_some_and(|n| n.to_lowercase().contains(part))
             {
                 return false;
             }
@@ -322,3 +474,30 @@ impl Task {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_text_search() {
+        let mut t = Task::new("Renew passport #admin @@town-hall", &Default::default());
+        t.description = "Bring the old one".to_string();
+
+        assert!(t.matches_search_term("old"));
+        assert!(t.matches_search_term("town"));
+        assert!(!t.matches_search_term("personal"));
+        assert!(t.matches_search_in("personal", Some("Personal")));
+        assert!(!t.matches_search_in("personal #home", Some("Personal")));
+
+        let terms = search_text_terms("Pass #admin is:active ORT ~<1h");
+        assert_eq!(terms, ["pass", "ort"]);
+        assert_eq!(highlight_ranges(&t.summary, &terms), [6..10, 11..14]);
+        assert_eq!(
+            highlight_ranges("Passport", &["ss".into(), "sp".into()]),
+            [2..5]
+        );
+        // Byte ranges stay on char boundaries
+        assert_eq!(highlight_ranges("Ärger über", &["über".into()]), [7..12]);
+    }
+}
@@ -1,8 +1,35 @@
 // File: src/model/mod.rs
 pub mod adapter;
+pub mod checklist;
+pub mod csv;
+pub mod diff;
+pub mod field;
+pub mod file_format;
+pub mod group;
 pub mod item;
+pub mod markdown;
 pub mod matcher;
+pub mod org;
 pub mod parser;
+pub mod query;
+pub mod recurrence;
+pub mod sort;
+pub mod status;
+pub mod taskwarrior;
+pub mod tracking;
 
-pub use item::{CalendarListEntry, Task, TaskStatus};
-pub use parser::extract_inline_aliases;
+pub use checklist::ChecklistItem;
+pub use diff::FieldChange;
+pub use field::TaskField;
+pub use file_format::FileFormat;
+pub use group::GroupBy;
+pub use item::{
+    Alarm, AlarmTrigger, Attachment, CalendarListEntry, DateTz, RecurrenceOverride, Task,
+    TaskStatus,
+};
+pub use parser::{extract_inline_aliases, insert_alias};
+pub use query::Query;
+pub use recurrence::{Freq, Recurrence};
+pub use sort::SortOrder;
+pub use status::CustomStatus;
+pub use tracking::TimeSession;
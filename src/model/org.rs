@@ -0,0 +1,115 @@
+// File: src/model/org.rs
+// Org-mode export: one heading per task, subtasks nested under their parent.
+use crate::model::item::{Task, TaskStatus};
+use crate::model::sort::SortOrder;
+use chrono::{DateTime, Local, Utc};
+
+// Keywords beyond Org's default TODO/DONE, declared at the top of the file
+const TODO_KEYWORDS: &str = "#+TODO: TODO STARTED | DONE CANCELLED";
+
+fn keyword(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::NeedsAction => "TODO",
+        TaskStatus::InProcess => "STARTED",
+        TaskStatus::Completed => "DONE",
+        TaskStatus::Cancelled => "CANCELLED",
+    }
+}
+
+// <2025-03-01 Sat 14:00>, without the time for all-day dates
+fn timestamp(dt: DateTime<Utc>, all_day: bool, open: char, close: char) -> String {
+    let local = dt.with_timezone(&Local);
+    let format = if all_day {
+        "%Y-%m-%d %a"
+    } else {
+        "%Y-%m-%d %a %H:%M"
+    };
+    format!("{}{}{}", open, local.format(format), close)
+}
+
+// Org tags are letters, digits, _ @ # %: "home:admin" becomes "home_admin"
+fn org_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "_@#%".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The tasks as an Org file, in the smart order with subtasks under their parent.
+pub fn to_org(tasks: &[Task], title: &str) -> String {
+    let mut out = format!("#+TITLE: {}\n{}\n\n", title, TODO_KEYWORDS);
+    for task in Task::organize_hierarchy(tasks.to_vec(), None, SortOrder::Smart) {
+        let stars = "*".repeat(task.depth + 1);
+        let mut heading = format!("{} {}", stars, keyword(task.status));
+        match task.priority {
+            1..=4 => heading.push_str(" [#A]"),
+            5 => heading.push_str(" [#B]"),
+            6..=9 => heading.push_str(" [#C]"),
+            _ => {}
+        }
+        heading.push(' ');
+        heading.push_str(&task.summary);
+        if !task.categories.is_empty() {
+            let tags: Vec<String> = task.categories.iter().map(|t| org_tag(t)).collect();
+            heading.push_str(&format!(" :{}:", tags.join(":")));
+        }
+        out.push_str(&heading);
+        out.push('\n');
+
+        let mut planning = Vec::new();
+        if let Some(closed) = task.completed_at.filter(|_| task.status.is_done()) {
+            planning.push(format!("CLOSED: {}", timestamp(closed, false, '[', ']')));
+        }
+        if let Some(start) = task.dtstart {
+            let start = timestamp(start, task.is_all_day, '<', '>');
+            planning.push(format!("SCHEDULED: {}", start));
+        }
+        if let Some(due) = task.due {
+            let due = timestamp(due, task.is_all_day, '<', '>');
+            planning.push(format!("DEADLINE: {}", due));
+        }
+        if !planning.is_empty() {
+            out.push_str(&format!("{}\n", planning.join(" ")));
+        }
+        out.push_str(&format!(":PROPERTIES:\n:ID: {}\n:END:\n", task.uid));
+
+        // Indented, so a line starting with "*" doesn't turn into a heading
+        for line in task.description.lines() {
+            if !line.is_empty() {
+                out.push_str("  ");
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_org_nests_subtasks() {
+        let mut parent = Task::new("Plan trip", &HashMap::new());
+        parent.priority = 1;
+        parent.categories = vec!["travel:europe".to_string()];
+        parent.description = "* not a heading".to_string();
+        let mut child = Task::new("Book hotel", &HashMap::new());
+        child.parent_uid = Some(parent.uid.clone());
+        child.set_status(TaskStatus::Completed);
+
+        let org = to_org(&[child, parent], "Trips");
+        let lines: Vec<&str> = org.lines().collect();
+        assert_eq!(lines[0], "#+TITLE: Trips");
+        assert_eq!(lines[3], "* TODO [#A] Plan trip :travel_europe:");
+        assert!(org.contains("\n  * not a heading\n"));
+        assert!(org.contains("\n** DONE Book hotel\nCLOSED: ["));
+    }
+}
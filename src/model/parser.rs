@@ -1,7 +1,8 @@
 // File: src/model/parser.rs
 // Handles smart text input parsing
-use crate::model::item::Task;
-use chrono::{DateTime, Local, NaiveDate, Utc};
+use crate::model::item::{DateTz, Task};
+use crate::model::recurrence::parse_weekday;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, NaiveTime, Utc};
 use std::collections::HashMap;
 
 impl Task {
@@ -14,17 +15,18 @@ impl Task {
         self.rrule = None;
         self.estimated_duration = None;
         self.categories.clear();
+        self.contexts.clear();
 
-        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let tokens = tokenize(input);
         let mut i = 0;
+        let mut has_time = false;
 
         while i < tokens.len() {
             let word = tokens[i];
 
             // 1. Priority (!1 - !9)
-            if word.starts_with('!')
-                && let Ok(p) = word[1..].parse::<u8>()
-                && (1..=9).contains(&p)
+            if let Some(val) = word.strip_prefix('!')
+                && let Some(p) = parse_priority(val)
             {
                 self.priority = p;
                 i += 1;
@@ -40,32 +42,23 @@ impl Task {
                 continue;
             }
 
-            // 3. Tags (#tag)
+            // 3. Tags (#tag, #"multi word tag")
             if let Some(stripped) = word.strip_prefix('#') {
-                let cat = stripped.to_string();
+                let cat = unquote(stripped);
                 if !cat.is_empty() {
-                    if !self.categories.contains(&cat) {
-                        self.categories.push(cat.clone());
-                    }
+                    self.add_tag(cat, aliases);
+                    i += 1;
+                    continue;
+                }
+            }
 
-                    // Apply aliases recursively (e.g. #a:b -> check alias for #a:b, then #a)
-                    let mut search = cat.as_str();
-                    loop {
-                        if let Some(expanded_tags) = aliases.get(search) {
-                            for extra_tag in expanded_tags {
-                                if !self.categories.contains(extra_tag) {
-                                    self.categories.push(extra_tag.clone());
-                                }
-                            }
-                        }
-                        // Move up hierarchy
-                        if let Some(idx) = search.rfind(':') {
-                            search = &search[..idx];
-                        } else {
-                            break;
-                        }
+            // Context (@@home, @@"at the office"), before '@' dates
+            if let Some(stripped) = word.strip_prefix("@@") {
+                let ctx = unquote(stripped).to_string();
+                if !ctx.is_empty() {
+                    if !self.contexts.contains(&ctx) {
+                        self.contexts.push(ctx);
                     }
-
                     i += 1;
                     continue;
                 }
@@ -79,6 +72,17 @@ impl Task {
                 i += 1;
                 continue;
             }
+
+            // Raw rule (rrule:FREQ=WEEKLY;BYDAY=MO,WE), as written back by to_smart_string
+            if let Some(val) = word
+                .strip_prefix("rrule:")
+                .or_else(|| word.strip_prefix("RRULE:"))
+                && let Some(rrule) = parse_raw_rrule(val)
+            {
+                self.rrule = Some(rrule);
+                i += 1;
+                continue;
+            }
             // If not a recurrence keyword, it might be a date using '@' synonym, allow fallthrough
 
             // 5. Explicit Recurrence with interval (rec:every 2 days)
@@ -96,25 +100,31 @@ impl Task {
                 }
             }
 
-            // 6. Due Date (due:2025-01-01, @2025-01-01)
+            // 6. Due Date (due:2025-01-01, @2025-01-01, @tomorrow 17:00, @2025-01-01T09:00)
             if let Some(val) = word.strip_prefix("due:").or_else(|| word.strip_prefix('@'))
-                && let Some(dt) = parse_smart_date(val, true)
+                && let Some((dt, timed, used)) =
+                    parse_smart_datetime(unquote(val), tokens.get(i + 1).copied(), true)
             {
-                // true = end of day
+                // true = end of day when no time is given
                 self.due = Some(dt);
-                i += 1;
+                self.due_tz = DateTz::Floating;
+                has_time |= timed;
+                i += used;
                 continue;
             }
 
-            // 7. Start Date (start:2025-01-01, ^2025-01-01)
+            // 7. Start Date (start:2025-01-01, ^2025-01-01, ^today 9am)
             if let Some(val) = word
                 .strip_prefix("start:")
                 .or_else(|| word.strip_prefix('^'))
-                && let Some(dt) = parse_smart_date(val, false)
+                && let Some((dt, timed, used)) =
+                    parse_smart_datetime(unquote(val), tokens.get(i + 1).copied(), false)
             {
-                // false = start of day
+                // false = start of day when no time is given
                 self.dtstart = Some(dt);
-                i += 1;
+                self.dtstart_tz = DateTz::Floating;
+                has_time |= timed;
+                i += used;
                 continue;
             }
 
@@ -123,24 +133,87 @@ impl Task {
             i += 1;
         }
         self.summary = summary_words.join(" ");
+        // A typed time makes both dates timed (DUE and DTSTART share a value type)
+        self.is_all_day = (self.due.is_some() || self.dtstart.is_some()) && !has_time;
+    }
+
+    /// Adds a tag and the tags its aliases stand for, skipping those already there.
+    pub fn add_tag(&mut self, cat: &str, aliases: &HashMap<String, Vec<String>>) {
+        if !self.categories.iter().any(|c| c == cat) {
+            self.categories.push(cat.to_string());
+        }
+
+        // Apply aliases recursively (e.g. #a:b -> check alias for #a:b, then #a)
+        let mut search = cat;
+        loop {
+            if let Some(expanded_tags) = aliases.get(search) {
+                for extra_tag in expanded_tags {
+                    if !self.categories.contains(extra_tag) {
+                        self.categories.push(extra_tag.clone());
+                    }
+                }
+            }
+            // Move up hierarchy
+            if let Some(idx) = search.rfind(':') {
+                search = &search[..idx];
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Creates the task for a smart input plus any inline subtasks
+    /// (see `split_inline_subtasks`). The parent comes first, children point to it.
+    pub fn new_with_subtasks(input: &str, aliases: &HashMap<String, Vec<String>>) -> Vec<Task> {
+        let mut parts = split_inline_subtasks(input).into_iter();
+        let Some(first) = parts.next() else {
+            return Vec::new();
+        };
+        let parent = Task::new(&first, aliases);
+        let mut tasks = vec![parent];
+        for part in parts {
+            let mut child = Task::new(&part, aliases);
+            child.parent_uid = Some(tasks[0].uid.clone());
+            tasks.push(child);
+        }
+        tasks
     }
 
     pub fn to_smart_string(&self) -> String {
+        self.to_smart_string_with(false)
+    }
+
+    /// Same as `to_smart_string`, writing !high/!med/!low instead of !1/!5/!9
+    /// when `named_priorities` is set.
+    pub fn to_smart_string_with(&self, named_priorities: bool) -> String {
         let mut s = self.summary.clone();
 
-        // Priority: !1
+        // Priority: !1 (or !high)
         if self.priority > 0 {
-            s.push_str(&format!(" !{}", self.priority));
+            match priority_name(self.priority) {
+                Some(name) if named_priorities => s.push_str(&format!(" !{}", name)),
+                _ => s.push_str(&format!(" !{}", self.priority)),
+            }
         }
 
-        // Start: ^YYYY-MM-DD
+        // Start: ^YYYY-MM-DD [HH:MM]
         if let Some(start) = self.dtstart {
+            let start = start.with_timezone(&Local);
             s.push_str(&format!(" ^{}", start.format("%Y-%m-%d")));
+            // Midnight is what a bare day parses to anyway
+            if !self.is_all_day && start.time() != NaiveTime::MIN {
+                s.push_str(&format!(" {}", start.format("%H:%M")));
+            }
         }
 
-        // Due: @YYYY-MM-DD
+        // Due: @YYYY-MM-DD [HH:MM]
         if let Some(d) = self.due {
+            let d = d.with_timezone(&Local);
             s.push_str(&format!(" @{}", d.format("%Y-%m-%d")));
+            // 23:59 is the implicit end of day, same as format_due
+            if !self.is_all_day && d.format("%H:%M").to_string() != "23:59" {
+                s.push_str(&format!(" {}", d.format("%H:%M")));
+            }
         }
 
         // Duration: ~30m
@@ -174,16 +247,87 @@ impl Task {
             } else if let Some(simple) = reconstruct_simple_rrule(r) {
                 s.push_str(&format!(" {}", simple));
             } else {
-                s.push_str(" rec:custom"); // Fallback for complex RRULEs
+                // Anything else (BYDAY, COUNT, UNTIL, ...) goes through as is
+                s.push_str(&format!(" rrule:{}", r));
             }
         }
 
-        // Tags: #tag
+        // Tags: #tag, or #"multi word tag"
         for cat in &self.categories {
-            s.push_str(&format!(" #{}", cat));
+            if cat.contains(char::is_whitespace) {
+                s.push_str(&format!(" #\"{}\"", cat));
+            } else {
+                s.push_str(&format!(" #{}", cat));
+            }
+        }
+        for ctx in &self.contexts {
+            if ctx.contains(char::is_whitespace) {
+                s.push_str(&format!(" @@\"{}\"", ctx));
+            } else {
+                s.push_str(&format!(" @@{}", ctx));
+            }
         }
         s
     }
+
+    /// Warning for recurrence input that won't work: tokens that weren't understood
+    /// (they end up in the title) or a rule that never repeats.
+    pub fn smart_input_warning(&self, input: &str) -> Option<String> {
+        unparsed_recurrence(input).or_else(|| self.recurrence_warning())
+    }
+}
+
+/// Splits "Project plan > draft outline > send for review" into the parent's
+/// input followed by each child's. A lone `>` or `:sub` token separates them,
+/// so `>` inside a word (e.g. a filter) is left alone.
+pub fn split_inline_subtasks(input: &str) -> Vec<String> {
+    let mut parts = vec![Vec::new()];
+    for token in tokenize(input) {
+        if token == ">" || token == ":sub" {
+            parts.push(Vec::new());
+        } else if let Some(last) = parts.last_mut() {
+            last.push(token);
+        }
+    }
+    parts
+        .into_iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| p.join(" "))
+        .collect()
+}
+
+/// Splits on whitespace, except inside double quotes: `#"home improvement"` and
+/// `@"next friday"` are one token each. Quotes are kept (see `unquote`), so a
+/// quoted title word stays as typed. An unbalanced quote disables the grouping.
+pub fn tokenize(input: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (idx, c) in input.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if let Some(s) = start.take() {
+                tokens.push(&input[s..idx]);
+            }
+        } else if start.is_none() {
+            start = Some(idx);
+        }
+    }
+    if quoted {
+        return input.split_whitespace().collect();
+    }
+    if let Some(s) = start {
+        tokens.push(&input[s..]);
+    }
+    tokens
+}
+
+fn unquote(val: &str) -> &str {
+    val.strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(val)
 }
 
 /// Helper to extract inline alias definitions from an input string.
@@ -222,11 +366,43 @@ pub fn extract_inline_aliases(input: &str) -> (String, HashMap<String, Vec<Strin
     (cleaned_words.join(" "), new_aliases)
 }
 
+/// Adds or replaces the alias `#key` -> `tags` after cleaning it up
+/// (no leading '#', no empty or duplicate tags). Returns the key as stored.
+pub fn insert_alias(
+    aliases: &mut HashMap<String, Vec<String>>,
+    key: &str,
+    tags: &[String],
+) -> Result<String, String> {
+    let key = key.trim().trim_start_matches('#').to_string();
+    if key.is_empty() {
+        return Err("Alias name is empty".to_string());
+    }
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    if cleaned.is_empty() {
+        return Err(format!("Alias '#{}' needs at least one tag", key));
+    }
+    if cleaned.contains(&key) {
+        return Err(format!("Alias '#{}' can't expand to itself", key));
+    }
+    aliases.insert(key.clone(), cleaned);
+    Ok(key)
+}
+
 // --- Helpers ---
 
 fn reconstruct_simple_rrule(rrule: &str) -> Option<String> {
     // Basic parser to handle FREQ=X;INTERVAL=Y -> @every Y X(s)
     let parts: HashMap<&str, &str> = rrule.split(';').filter_map(|s| s.split_once('=')).collect();
+    // Any other part would be lost, leave those to the rrule: form
+    if parts.keys().any(|k| *k != "FREQ" && *k != "INTERVAL") {
+        return None;
+    }
 
     let freq = parts.get("FREQ")?;
     let interval = parts.get("INTERVAL").unwrap_or(&"1");
@@ -242,7 +418,72 @@ fn reconstruct_simple_rrule(rrule: &str) -> Option<String> {
     Some(format!("@every {} {}", interval, unit))
 }
 
-fn parse_duration(val: &str) -> Option<u32> {
+// Only needs to look like a rule, other clients may use parts we don't know
+fn parse_raw_rrule(val: &str) -> Option<String> {
+    let rrule = val.trim_matches(';').to_ascii_uppercase();
+    let valid = rrule.split(';').all(|p| p.split_once('=').is_some())
+        && rrule.split(';').any(|p| p.starts_with("FREQ="));
+    valid.then_some(rrule)
+}
+
+// e.g. "@every 2 wekz": silently kept as words of the summary otherwise
+fn unparsed_recurrence(input: &str) -> Option<String> {
+    let tokens = tokenize(input);
+    for (i, word) in tokens.iter().enumerate() {
+        if *word == "rec:every" || *word == "@every" {
+            let valid = tokens.get(i + 1).is_some_and(|n| n.parse::<u32>().is_ok())
+                && tokens
+                    .get(i + 2)
+                    .is_some_and(|u| !parse_freq_unit(u).is_empty());
+            if !valid {
+                let end = (i + 3).min(tokens.len());
+                return Some(format!(
+                    "Unknown repeat '{}' (e.g. '@every 2 weeks')",
+                    tokens[i..end].join(" ")
+                ));
+            }
+        } else if let Some(val) = word.strip_prefix("rec:")
+            && parse_recurrence(val).is_none()
+        {
+            return Some(format!(
+                "Unknown repeat '{}' (use daily, weekly, monthly or yearly)",
+                word
+            ));
+        } else if let Some(val) = word
+            .strip_prefix("rrule:")
+            .or_else(|| word.strip_prefix("RRULE:"))
+            && parse_raw_rrule(val).is_none()
+        {
+            return Some(format!(
+                "Invalid rule '{}' (e.g. 'rrule:FREQ=WEEKLY;BYDAY=MO')",
+                word
+            ));
+        }
+    }
+    None
+}
+
+// !1..!9, or !high/!med/!low/!none (1/5/9/0)
+fn parse_priority(val: &str) -> Option<u8> {
+    match val.to_lowercase().as_str() {
+        "high" => Some(1),
+        "med" | "medium" => Some(5),
+        "low" => Some(9),
+        "none" => Some(0),
+        n => n.parse::<u8>().ok().filter(|p| (1..=9).contains(p)),
+    }
+}
+
+fn priority_name(priority: u8) -> Option<&'static str> {
+    match priority {
+        1 => Some("high"),
+        5 => Some("med"),
+        9 => Some("low"),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_duration(val: &str) -> Option<u32> {
     let lower = val.to_lowercase();
     if let Some(n) = lower.strip_suffix("min") {
         return n.parse::<u32>().ok();
@@ -288,44 +529,278 @@ fn parse_freq_unit(unit: &str) -> &'static str {
     }
 }
 
-fn parse_smart_date(val: &str, end_of_day: bool) -> Option<DateTime<Utc>> {
+/// Date token with an optional time, either inline (2025-01-01T09:00) or as the
+/// next token (17:00, 5pm, 5:30pm).
+/// Returns the instant, whether a time was given and how many tokens were used.
+fn parse_smart_datetime(
+    val: &str,
+    next: Option<&str>,
+    end_of_day: bool,
+) -> Option<(DateTime<Utc>, bool, usize)> {
+    if let Some((day, time)) = val.split_once('T')
+        && let Some(date) = parse_smart_day(day)
+        && let Some(time) = parse_time(time)
+    {
+        return Some((DateTz::Floating.to_utc(date.and_time(time))?, true, 1));
+    }
+
+    let date = parse_smart_day(val)?;
+    if let Some(time) = next.and_then(parse_time) {
+        return Some((DateTz::Floating.to_utc(date.and_time(time))?, true, 2));
+    }
+    Some((finalize_date(date, end_of_day)?, false, 1))
+}
+
+// 17:00, 9:30, 5pm, 5:30pm, 12am
+fn parse_time(val: &str) -> Option<NaiveTime> {
+    let lower = val.to_lowercase();
+    let (clock, pm) = if let Some(c) = lower.strip_suffix("pm") {
+        (c, Some(true))
+    } else if let Some(c) = lower.strip_suffix("am") {
+        (c, Some(false))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let (h, m) = match clock.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm ("5" alone stays in the summary)
+        None if pm.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+
+    let h = match pm {
+        Some(_) if !(1..=12).contains(&h) => return None,
+        Some(true) => h % 12 + 12,
+        Some(false) => h % 12,
+        None => h,
+    };
+    NaiveTime::from_hms_opt(h, m, 0)
+}
+
+fn parse_smart_day(val: &str) -> Option<NaiveDate> {
     // 1. Specific Date YYYY-MM-DD
     if let Ok(date) = NaiveDate::parse_from_str(val, "%Y-%m-%d") {
-        return finalize_date(date, end_of_day);
+        return Some(date);
     }
 
     let now = Local::now().date_naive();
 
     // 2. Relative Keywords
     if val == "today" {
-        return finalize_date(now, end_of_day);
+        return Some(now);
     }
     if val == "tomorrow" {
-        return finalize_date(now + chrono::Duration::days(1), end_of_day);
+        return Some(now + chrono::Duration::days(1));
     }
 
     // 3. "1w", "2d" offsets (from now)
     if let Some(n) = val.strip_suffix('d').and_then(|s| s.parse::<i64>().ok()) {
-        return finalize_date(now + chrono::Duration::days(n), end_of_day);
+        return Some(now + chrono::Duration::days(n));
     }
     if let Some(n) = val.strip_suffix('w').and_then(|s| s.parse::<i64>().ok()) {
-        return finalize_date(now + chrono::Duration::days(n * 7), end_of_day);
+        return Some(now + chrono::Duration::days(n * 7));
     }
     if let Some(n) = val.strip_suffix("mo").and_then(|s| s.parse::<i64>().ok()) {
-        return finalize_date(now + chrono::Duration::days(n * 30), end_of_day);
+        return Some(now + chrono::Duration::days(n * 30));
     }
     if let Some(n) = val.strip_suffix('y').and_then(|s| s.parse::<i64>().ok()) {
-        return finalize_date(now + chrono::Duration::days(n * 365), end_of_day);
+        return Some(now + chrono::Duration::days(n * 365));
+    }
+
+    // 4. "next week" & co. (quoted, as in @"next week")
+    match val {
+        "next week" => return Some(now + chrono::Duration::days(7)),
+        "next month" => return now.checked_add_months(Months::new(1)),
+        "next year" => return now.checked_add_months(Months::new(12)),
+        _ => {}
+    }
+
+    // 5. Weekdays: "friday", "next fri" (the coming one, never today)
+    let day = val.strip_prefix("next ").unwrap_or(val);
+    if day.len() >= 3
+        && let Some(wd) = parse_weekday(day)
+    {
+        let ahead = (wd.num_days_from_monday() + 6 - now.weekday().num_days_from_monday()) % 7 + 1;
+        return Some(now + chrono::Duration::days(ahead as i64));
     }
 
     None
 }
 
+// Dates typed by the user are days in their own zone
 fn finalize_date(d: NaiveDate, end_of_day: bool) -> Option<DateTime<Utc>> {
     let t = if end_of_day {
         d.and_hms_opt(23, 59, 59)?
     } else {
         d.and_hms_opt(0, 0, 0)?
     };
-    Some(t.and_utc())
+    DateTz::Floating.to_utc(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn local(dt: DateTime<Utc>) -> NaiveDateTime {
+        dt.with_timezone(&Local).naive_local()
+    }
+
+    #[test]
+    fn test_smart_due_with_time() {
+        let aliases = HashMap::new();
+        let mut t = Task::new("x", &aliases);
+        let tomorrow = Local::now().date_naive() + chrono::Duration::days(1);
+
+        t.apply_smart_input("Call Bob @tomorrow 17:00", &aliases);
+        assert_eq!(t.summary, "Call Bob");
+        assert!(!t.is_all_day);
+        assert_eq!(
+            local(t.due.unwrap()),
+            tomorrow.and_hms_opt(17, 0, 0).unwrap()
+        );
+        assert_eq!(
+            t.to_smart_string(),
+            format!("Call Bob @{} 17:00", tomorrow.format("%Y-%m-%d"))
+        );
+
+        t.apply_smart_input("Standup @2024-07-01T09:00", &aliases);
+        assert_eq!(local(t.due.unwrap()).to_string(), "2024-07-01 09:00:00");
+        assert_eq!(t.to_smart_string(), "Standup @2024-07-01 09:00");
+
+        t.apply_smart_input("Gym @today 5pm", &aliases);
+        assert_eq!(local(t.due.unwrap()).time().to_string(), "17:00:00");
+
+        // Without a time it stays a whole day, and bare numbers stay in the summary
+        t.apply_smart_input("Buy 5 apples @2024-07-01 5", &aliases);
+        assert_eq!(t.summary, "Buy 5 apples 5");
+        assert!(t.is_all_day);
+        assert_eq!(t.to_smart_string(), "Buy 5 apples 5 @2024-07-01");
+    }
+
+    #[test]
+    fn test_inline_subtasks() {
+        let aliases = HashMap::new();
+        let tasks = Task::new_with_subtasks(
+            "Project plan !1 > draft outline > send for review",
+            &aliases,
+        );
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].summary, "Project plan");
+        assert_eq!(tasks[0].priority, 1);
+        assert!(tasks[0].parent_uid.is_none());
+        assert_eq!(tasks[1].summary, "draft outline");
+        assert_eq!(tasks[2].parent_uid.as_deref(), Some(tasks[0].uid.as_str()));
+
+        let tasks = Task::new_with_subtasks("Trip :sub book hotel > ", &aliases);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].summary, "book hotel");
+
+        // Only a standalone '>' splits
+        assert_eq!(split_inline_subtasks("a->b"), vec!["a->b"]);
+    }
+
+    #[test]
+    fn test_quoted_tags_and_values() {
+        let aliases = HashMap::new();
+        assert_eq!(
+            tokenize(r#"Fix "the door" #"home improvement" !2"#),
+            vec!["Fix", "\"the door\"", "#\"home improvement\"", "!2"]
+        );
+        // Unbalanced quotes fall back to plain words
+        assert_eq!(tokenize(r#"5" screen"#), vec!["5\"", "screen"]);
+
+        let mut t = Task::new(r#"Paint #"home improvement" #diy @"next friday""#, &aliases);
+        assert_eq!(t.summary, "Paint");
+        assert_eq!(t.categories, vec!["home improvement", "diy"]);
+        let due = t.due.unwrap().with_timezone(&Local).date_naive();
+        let today = Local::now().date_naive();
+        assert_eq!(due.weekday(), chrono::Weekday::Fri);
+        assert!(due > today && due <= today + chrono::Duration::days(7));
+
+        t.due = None;
+        assert_eq!(t.to_smart_string(), r#"Paint #"home improvement" #diy"#);
+    }
+
+    #[test]
+    fn test_recurrence_round_trip() {
+        let aliases = HashMap::new();
+        let mut t = Task::new("Backup @every 3 weeks", &aliases);
+        assert_eq!(t.rrule.as_deref(), Some("FREQ=WEEKLY;INTERVAL=3"));
+        assert_eq!(t.to_smart_string(), "Backup @every 3 weeks");
+
+        // Parts the shorthand can't express survive an edit
+        t.rrule = Some("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10".to_string());
+        let smart = t.to_smart_string();
+        assert_eq!(smart, "Backup rrule:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10");
+        t.apply_smart_input(&smart, &aliases);
+        assert_eq!(t.rrule.as_deref(), Some("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10"));
+        assert_eq!(t.summary, "Backup");
+
+        t.apply_smart_input("Backup rrule:nonsense", &aliases);
+        assert!(t.rrule.is_none());
+        assert_eq!(t.summary, "Backup rrule:nonsense");
+    }
+
+    #[test]
+    fn test_recurrence_warnings() {
+        let aliases = HashMap::new();
+        let warning = |input: &str| Task::new(input, &aliases).smart_input_warning(input);
+
+        assert!(warning("Backup @every 2 weeks @tomorrow").is_none());
+        assert!(warning("Backup @weekly @tomorrow").is_none());
+        assert!(warning("Buy milk").is_none());
+
+        let w = warning("Backup @every 2 wekz @tomorrow").unwrap();
+        assert!(w.contains("@every 2 wekz"));
+        assert!(warning("Backup rec:fortnightly @tomorrow").is_some());
+        assert!(warning("Backup rrule:nonsense @tomorrow").is_some());
+        // Understood, but it can't repeat
+        assert!(warning("Backup @weekly").unwrap().contains("date"));
+        assert!(warning("Backup rrule:FREQ=DAILY;COUNT=1 @tomorrow").is_some());
+    }
+
+    #[test]
+    fn test_insert_alias() {
+        let mut aliases = HashMap::new();
+        let tags = [
+            "#home".to_string(),
+            " shopping ".to_string(),
+            "home".to_string(),
+        ];
+        assert_eq!(
+            insert_alias(&mut aliases, "#groceries", &tags).as_deref(),
+            Ok("groceries")
+        );
+        assert_eq!(aliases["groceries"], vec!["home", "shopping"]);
+
+        assert!(insert_alias(&mut aliases, "#", &tags).is_err());
+        assert!(insert_alias(&mut aliases, "x", &["#".to_string()]).is_err());
+        assert!(insert_alias(&mut aliases, "a", &["a".to_string()]).is_err());
+        assert_eq!(aliases.len(), 1);
+    }
+
+    #[test]
+    fn test_named_priorities() {
+        let aliases = HashMap::new();
+        let mut t = Task::new("Taxes !high", &aliases);
+        assert_eq!(t.priority, 1);
+        assert_eq!(t.to_smart_string(), "Taxes !1");
+        assert_eq!(t.to_smart_string_with(true), "Taxes !high");
+
+        t.apply_smart_input("Taxes !MED", &aliases);
+        assert_eq!(t.priority, 5);
+        t.apply_smart_input("Taxes !low", &aliases);
+        assert_eq!(t.to_smart_string_with(true), "Taxes !low");
+        t.apply_smart_input("Taxes !3", &aliases);
+        assert_eq!(t.to_smart_string_with(true), "Taxes !3");
+        t.apply_smart_input("Taxes !none", &aliases);
+        assert_eq!(t.priority, 0);
+        assert_eq!(t.to_smart_string_with(true), "Taxes");
+
+        // Unknown names stay in the summary
+        t.apply_smart_input("Wow !cool", &aliases);
+        assert_eq!(t.summary, "Wow !cool");
+    }
 }
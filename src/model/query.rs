@@ -0,0 +1,459 @@
+// File: src/model/query.rs
+// Filter expressions, a step up from the search syntax:
+//   due<7d AND prio<=4 AND #work AND status!=done
+//   (#home OR @@errands) AND NOT status=ongoing
+// Adjacent terms without an operator are ANDed, words alone match text.
+use crate::model::item::{Task, TaskStatus};
+use crate::model::parser::parse_duration;
+use chrono::{Duration, Local, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn test<T: PartialOrd>(self, left: T, right: T) -> bool {
+        match self {
+            CmpOp::Lt => left < right,
+            CmpOp::Le => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Ge => left >= right,
+            CmpOp::Eq => left == right,
+            CmpOp::Ne => left != right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pred {
+    // None: the date isn't set ("due=none")
+    Due(CmpOp, Option<NaiveDate>),
+    Start(CmpOp, Option<NaiveDate>),
+    Priority(CmpOp, Option<u8>),
+    Duration(CmpOp, Option<u32>),
+    // Equal (true) or not to: done, active, todo, ongoing, cancelled, or a custom status
+    Status(bool, String),
+    Tag(bool, String),
+    Context(bool, String),
+    Calendar(bool, String),
+    Flagged(bool),
+    Text(bool, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Pred(Pred),
+}
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Op(CmpOp),
+    Word(String),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Query(expr)),
+            Some(Token::Close) => Err("Unmatched ')'".to_string()),
+            Some(t) => Err(format!("Unexpected {}", describe(t))),
+        }
+    }
+
+    /// Whether the expression filters on status, so completed tasks shouldn't be hidden.
+    pub fn mentions_status(&self) -> bool {
+        self.0.mentions_status()
+    }
+
+    pub fn matches(&self, task: &Task, calendar_name: Option<&str>) -> bool {
+        self.0.matches(task, calendar_name)
+    }
+}
+
+impl Expr {
+    fn mentions_status(&self) -> bool {
+        match self {
+            Expr::And(es) | Expr::Or(es) => es.iter().any(Expr::mentions_status),
+            Expr::Not(e) => e.mentions_status(),
+            Expr::Pred(p) => matches!(p, Pred::Status(..)),
+        }
+    }
+
+    fn matches(&self, task: &Task, calendar_name: Option<&str>) -> bool {
+        match self {
+            Expr::And(es) => es.iter().all(|e| e.matches(task, calendar_name)),
+            Expr::Or(es) => es.iter().any(|e| e.matches(task, calendar_name)),
+            Expr::Not(e) => !e.matches(task, calendar_name),
+            Expr::Pred(p) => p.matches(task, calendar_name),
+        }
+    }
+}
+
+impl Pred {
+    fn matches(&self, task: &Task, calendar_name: Option<&str>) -> bool {
+        let contains = |s: &str, v: &str| s.to_lowercase().contains(v);
+        match self {
+            Pred::Due(op, date) => compare_opt(*op, task.due.map(local_date), *date),
+            Pred::Start(op, date) => compare_opt(*op, task.dtstart.map(local_date), *date),
+            Pred::Priority(op, p) => {
+                let own = (task.priority > 0).then_some(task.priority);
+                compare_opt(*op, own, *p)
+            }
+            Pred::Duration(op, mins) => compare_opt(*op, task.estimated_duration, *mins),
+            Pred::Status(eq, name) => *eq == status_is(task, name),
+            Pred::Tag(eq, tag) => {
+                // Sub-tags count: #work matches #work:meetings
+                let has = task.categories.iter().any(|c| {
+                    let c = c.to_lowercase();
+                    c == *tag || c.starts_with(&format!("{}:", tag))
+                });
+                *eq == has
+            }
+            Pred::Context(eq, ctx) => {
+                *eq == task.contexts.iter().any(|c| c.eq_ignore_ascii_case(ctx))
+            }
+            Pred::Calendar(eq, name) => *eq == calendar_name.is_some_and(|n| contains(n, name)),
+            Pred::Flagged(flagged) => task.flagged == *flagged,
+            Pred::Text(eq, word) => {
+                let found = contains(&task.summary, word)
+                    || contains(&task.description, word)
+                    || task.categories.iter().any(|c| contains(c, word));
+                *eq == found
+            }
+        }
+    }
+}
+
+fn local_date(dt: chrono::DateTime<chrono::Utc>) -> NaiveDate {
+    dt.with_timezone(&Local).date_naive()
+}
+
+// Unset values only compare with "none", for = and !=
+fn compare_opt<T: PartialOrd>(op: CmpOp, own: Option<T>, wanted: Option<T>) -> bool {
+    match (own, wanted) {
+        (Some(a), Some(b)) => op.test(a, b),
+        (None, None) => op == CmpOp::Eq,
+        _ => op == CmpOp::Ne,
+    }
+}
+
+fn status_is(task: &Task, name: &str) -> bool {
+    match name {
+        "done" => task.status.is_done(),
+        "active" | "open" => !task.status.is_done(),
+        "todo" | "needs-action" => task.status == TaskStatus::NeedsAction,
+        "ongoing" | "started" | "in-process" => task.status == TaskStatus::InProcess,
+        "completed" => task.status == TaskStatus::Completed,
+        "cancelled" | "canceled" => task.status == TaskStatus::Cancelled,
+        custom => task
+            .custom_status
+            .as_ref()
+            .is_some_and(|s| s.to_lowercase().replace(' ', "-") == custom),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.and()?];
+        while self.peek_keyword("OR") {
+            self.pos += 1;
+            terms.push(self.and()?);
+        }
+        if terms.len() == 1 {
+            return Ok(terms.remove(0));
+        }
+        if terms.iter().any(|t| *t == Expr::And(Vec::new())) {
+            return Err("OR needs a term on both sides".to_string());
+        }
+        Ok(Expr::Or(terms))
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut terms = Vec::new();
+        loop {
+            match self.tokens.get(self.pos) {
+                None | Some(Token::Close) => break,
+                _ if self.peek_keyword("OR") => break,
+                _ if self.peek_keyword("AND") => {
+                    self.pos += 1;
+                    let right = self.tokens.get(self.pos);
+                    if terms.is_empty()
+                        || right.is_none()
+                        || right == Some(&Token::Close)
+                        || self.peek_keyword("OR")
+                    {
+                        return Err("AND needs a term on both sides".to_string());
+                    }
+                }
+                _ => terms.push(self.unary()?),
+            }
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            // Empty: matches everything
+            Expr::And(terms)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek_keyword("NOT") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Open) => {
+                self.pos += 1;
+                let inner = self.or()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    return Err("Missing ')'".to_string());
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Some(Token::Word(word)) => {
+                self.pos += 1;
+                if let Some(Token::Op(op)) = self.tokens.get(self.pos).cloned() {
+                    self.pos += 1;
+                    let Some(Token::Word(value)) = self.tokens.get(self.pos).cloned() else {
+                        return Err(format!("Missing value after '{}'", word));
+                    };
+                    self.pos += 1;
+                    return comparison(&word, op, &value).map(Expr::Pred);
+                }
+                Ok(Expr::Pred(bare_word(&word)))
+            }
+            Some(t) => Err(format!("Unexpected {}", describe(&t))),
+            None => Err("Unexpected end of query".to_string()),
+        }
+    }
+}
+
+fn bare_word(word: &str) -> Pred {
+    let lower = word.to_lowercase();
+    if let Some(tag) = lower.strip_prefix('#') {
+        Pred::Tag(true, tag.to_string())
+    } else if let Some(ctx) = lower.strip_prefix("@@") {
+        Pred::Context(true, ctx.to_string())
+    } else {
+        Pred::Text(true, lower)
+    }
+}
+
+fn comparison(field: &str, op: CmpOp, value: &str) -> Result<Pred, String> {
+    let value = value.to_lowercase();
+    let none = value == "none";
+    let equality = |make: fn(bool, String) -> Pred| match op {
+        CmpOp::Eq => Ok(make(true, value.clone())),
+        CmpOp::Ne => Ok(make(false, value.clone())),
+        _ => Err(format!("'{}' only supports = and !=", field)),
+    };
+    match field.to_lowercase().as_str() {
+        "due" => Ok(Pred::Due(op, parse_date(&value, none)?)),
+        "start" => Ok(Pred::Start(op, parse_date(&value, none)?)),
+        "prio" | "priority" => {
+            let p = match value.as_str() {
+                "none" | "0" => None,
+                "high" => Some(1),
+                "med" | "medium" => Some(5),
+                "low" => Some(9),
+                v => Some(
+                    v.parse::<u8>()
+                        .ok()
+                        .filter(|p| (1..=9).contains(p))
+                        .ok_or_else(|| format!("Bad priority '{}' (1-9)", v))?,
+                ),
+            };
+            Ok(Pred::Priority(op, p))
+        }
+        "dur" | "duration" => {
+            let mins = if none {
+                None
+            } else {
+                Some(parse_duration(&value).ok_or_else(|| format!("Bad duration '{}'", value))?)
+            };
+            Ok(Pred::Duration(op, mins))
+        }
+        "status" | "is" => equality(Pred::Status),
+        "tag" => equality(|eq, v| Pred::Tag(eq, v.trim_start_matches('#').to_string())),
+        "context" | "ctx" => {
+            equality(|eq, v| Pred::Context(eq, v.trim_start_matches("@@").to_string()))
+        }
+        "cal" | "calendar" => equality(Pred::Calendar),
+        "flagged" => {
+            let flagged = match value.as_str() {
+                "yes" | "true" => true,
+                "no" | "false" => false,
+                v => return Err(format!("Bad flag '{}' (yes/no)", v)),
+            };
+            match op {
+                CmpOp::Eq => Ok(Pred::Flagged(flagged)),
+                CmpOp::Ne => Ok(Pred::Flagged(!flagged)),
+                _ => Err("'flagged' only supports = and !=".to_string()),
+            }
+        }
+        "text" => equality(Pred::Text),
+        other => Err(format!("Unknown field '{}'", other)),
+    }
+}
+
+// today, tomorrow, yesterday, 2025-01-31, or days from today: 7d, 2w, 1mo, 1y, -3d
+fn parse_date(value: &str, none: bool) -> Result<Option<NaiveDate>, String> {
+    if none {
+        return Ok(None);
+    }
+    let today = Local::now().date_naive();
+    let date = match value {
+        "today" => Some(today),
+        "tomorrow" => Some(today + Duration::days(1)),
+        "yesterday" => Some(today - Duration::days(1)),
+        v => NaiveDate::parse_from_str(v, "%Y-%m-%d").ok().or_else(|| {
+            let days = if let Some(n) = v.strip_suffix("mo") {
+                n.parse::<i64>().ok().map(|m| m * 30)
+            } else if let Some(n) = v.strip_suffix('d') {
+                n.parse::<i64>().ok()
+            } else if let Some(n) = v.strip_suffix('w') {
+                n.parse::<i64>().ok().map(|w| w * 7)
+            } else if let Some(n) = v.strip_suffix('y') {
+                n.parse::<i64>().ok().map(|y| y * 365)
+            } else {
+                None
+            };
+            days.map(|d| today + Duration::days(d))
+        }),
+    };
+    date.map(Some)
+        .ok_or_else(|| format!("Bad date '{}'", value))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '<' | '>' | '=' | '!' => {
+                chars.next();
+                let eq = chars.next_if_eq(&'=').is_some();
+                let op = match (c, eq) {
+                    ('<', false) => CmpOp::Lt,
+                    ('<', true) => CmpOp::Le,
+                    ('>', false) => CmpOp::Gt,
+                    ('>', true) => CmpOp::Ge,
+                    ('=', _) => CmpOp::Eq,
+                    ('!', true) => CmpOp::Ne,
+                    _ => return Err("'!' must be followed by '=' (use NOT to negate)".to_string()),
+                };
+                tokens.push(Token::Op(op));
+            }
+            '"' => {
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unclosed quote".to_string()),
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()<>=!\"".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+        Token::Op(_) => "comparison".to_string(),
+        Token::Word(w) => format!("'{}'", w),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_language() {
+        let aliases = Default::default();
+        let today = Local::now().date_naive().format("%Y-%m-%d");
+        let work = Task::new(&format!("Report #work:reports !2 @{}", today), &aliases);
+        let mut home = Task::new("Fix sink #home @@home ~1h", &aliases);
+        home.set_status(TaskStatus::Completed);
+
+        let q = Query::parse("due<7d AND prio<=4 AND #work AND status!=done").unwrap();
+        assert!(q.matches(&work, None));
+        assert!(!q.matches(&home, None));
+
+        let q = Query::parse("(tag=home OR @@errands) NOT status=active dur>=30m").unwrap();
+        assert!(q.matches(&home, None));
+        assert!(q.mentions_status());
+        assert!(
+            Query::parse("due=none prio=none")
+                .unwrap()
+                .matches(&home, None)
+        );
+        assert!(
+            Query::parse("cal=\"my work\" report")
+                .unwrap()
+                .matches(&work, Some("My Work"))
+        );
+        assert!(Query::parse("").unwrap().matches(&home, None));
+
+        assert!(Query::parse("due<soon").is_err());
+        assert!(Query::parse("(#work").is_err());
+        assert!(Query::parse("colour=red").is_err());
+        assert!(Query::parse("!#work").is_err());
+    }
+}
@@ -0,0 +1,529 @@
+// File: src/model/recurrence.rs
+// Typed view of an RRULE, for the recurrence editors
+use crate::model::item::{RecurrenceOverride, Task, TaskStatus};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use rrule::RRuleSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Freq {
+    #[default]
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Freq {
+    pub const ALL: [Freq; 4] = [Freq::Daily, Freq::Weekly, Freq::Monthly, Freq::Yearly];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+            Freq::Yearly => "YEARLY",
+        }
+    }
+
+    // Unit used in summaries, e.g. "every 2 weeks"
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Freq::Daily => "day",
+            Freq::Weekly => "week",
+            Freq::Monthly => "month",
+            Freq::Yearly => "year",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Freq::Daily => "Daily",
+            Freq::Weekly => "Weekly",
+            Freq::Monthly => "Monthly",
+            Freq::Yearly => "Yearly",
+        }
+    }
+}
+
+/// One BYDAY entry: "MO", or "1MO" / "-1FR" for the nth weekday of the month/year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub nth: Option<i8>,
+    pub weekday: Weekday,
+}
+
+impl ByDay {
+    pub fn every(weekday: Weekday) -> Self {
+        Self { nth: None, weekday }
+    }
+
+    /// "MO", "-1FR", or a plain day name like "monday"
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if let Some(weekday) = parse_weekday(s) {
+            return Ok(Self::every(weekday));
+        }
+        let err = || format!("Invalid day '{}'", s);
+        if !s.is_ascii() {
+            return Err(err());
+        }
+        let (num, code) = s.split_at(s.len().saturating_sub(2));
+        let weekday = parse_weekday(code).ok_or_else(err)?;
+        let nth: i8 = num.trim_start_matches('+').parse().map_err(|_| err())?;
+        if nth == 0 || !(-53..=53).contains(&nth) {
+            return Err(err());
+        }
+        Ok(Self {
+            nth: Some(nth),
+            weekday,
+        })
+    }
+}
+
+impl fmt::Display for ByDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(n) = self.nth {
+            write!(f, "{}", n)?;
+        }
+        f.write_str(weekday_code(self.weekday))
+    }
+}
+
+/// UNTIL keeps the value type it was written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    Date(NaiveDate),
+    Utc(DateTime<Utc>),
+    Floating(NaiveDateTime),
+}
+
+impl Until {
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Until::Date(d) => *d,
+            Until::Utc(dt) => dt.with_timezone(&Local).date_naive(),
+            Until::Floating(dt) => dt.date(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub freq: Freq,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    pub until: Option<Until>,
+    pub count: Option<u32>,
+    // Parts we don't model (BYMONTHDAY, WKST...), written back as they were
+    pub extra: Vec<(String, String)>,
+}
+
+impl Default for Recurrence {
+    fn default() -> Self {
+        Self::new(Freq::Daily)
+    }
+}
+
+impl Recurrence {
+    pub fn new(freq: Freq) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            by_day: Vec::new(),
+            until: None,
+            count: None,
+            extra: Vec::new(),
+        }
+    }
+
+    pub fn parse(rrule: &str) -> Result<Self, String> {
+        let rrule = rrule.trim();
+        let rrule = rrule.strip_prefix("RRULE:").unwrap_or(rrule);
+        let mut freq = None;
+        let mut rec = Self::default();
+
+        for part in rrule.split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Malformed RRULE part '{}'", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        other => return Err(format!("Unsupported frequency '{}'", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rec.interval = val
+                        .parse()
+                        .ok()
+                        .filter(|i| *i > 0)
+                        .ok_or_else(|| format!("Invalid INTERVAL '{}'", val))?
+                }
+                "COUNT" => {
+                    rec.count = Some(
+                        val.parse()
+                            .map_err(|_| format!("Invalid COUNT '{}'", val))?,
+                    )
+                }
+                "UNTIL" => rec.until = Some(parse_until(val)?),
+                "BYDAY" => {
+                    rec.by_day = val.split(',').map(ByDay::parse).collect::<Result<_, _>>()?
+                }
+                _ => rec.extra.push((key.to_string(), val.to_string())),
+            }
+        }
+
+        rec.freq = freq.ok_or("RRULE has no FREQ")?;
+        if rec.until.is_some() && rec.count.is_some() {
+            return Err("UNTIL and COUNT can't both be set".to_string());
+        }
+        Ok(rec)
+    }
+
+    pub fn to_rrule(&self) -> String {
+        let mut parts = vec![format!("FREQ={}", self.freq.as_str())];
+        if self.interval > 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter().map(|d| d.to_string()).collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        match self.until {
+            Some(Until::Date(d)) => parts.push(format!("UNTIL={}", d.format("%Y%m%d"))),
+            Some(Until::Utc(dt)) => parts.push(format!("UNTIL={}", dt.format("%Y%m%dT%H%M%SZ"))),
+            Some(Until::Floating(dt)) => {
+                parts.push(format!("UNTIL={}", dt.format("%Y%m%dT%H%M%S")))
+            }
+            None => {}
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        for (key, val) in &self.extra {
+            parts.push(format!("{}={}", key, val));
+        }
+        parts.join(";")
+    }
+
+    /// e.g. "Every 2 weeks on Mon, Fri until 2025-06-01"
+    pub fn summary(&self) -> String {
+        let mut s = if self.interval > 1 {
+            format!("Every {} {}s", self.interval, self.freq.unit())
+        } else {
+            self.freq.label().to_string()
+        };
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|d| match d.nth {
+                    Some(n) => format!("{}{}", n, d.weekday),
+                    None => d.weekday.to_string(),
+                })
+                .collect();
+            s.push_str(&format!(" on {}", days.join(", ")));
+        }
+        if let Some(until) = &self.until {
+            s.push_str(&format!(" until {}", until.date().format("%Y-%m-%d")));
+        }
+        if let Some(count) = self.count {
+            s.push_str(&format!(", {} times", count));
+        }
+        s
+    }
+
+    pub fn has_day(&self, weekday: Weekday) -> bool {
+        self.by_day
+            .iter()
+            .any(|d| d.nth.is_none() && d.weekday == weekday)
+    }
+
+    pub fn toggle_day(&mut self, weekday: Weekday) {
+        if self.has_day(weekday) {
+            self.by_day
+                .retain(|d| !(d.nth.is_none() && d.weekday == weekday));
+        } else {
+            self.by_day.push(ByDay::every(weekday));
+            self.by_day
+                .sort_by_key(|d| (d.weekday.num_days_from_monday(), d.nth));
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_rrule())
+    }
+}
+
+impl Task {
+    /// The RRULE as a typed value. None if there is none or it can't be parsed.
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.rrule
+            .as_deref()
+            .and_then(|r| Recurrence::parse(r).ok())
+    }
+
+    /// Human summary of the RRULE, e.g. "Weekly, after completion".
+    pub fn recurrence_summary(&self) -> Option<String> {
+        let mut s = self.recurrence()?.summary();
+        if self.repeat_after_completion {
+            s.push_str(", after completion");
+        }
+        Some(s)
+    }
+
+    pub fn set_recurrence(&mut self, rec: Option<Recurrence>) {
+        self.rrule = rec.map(|mut r| {
+            // UNTIL has to match DTSTART's value type: a timed task ends at
+            // the end of that local day, written in UTC
+            let timed = !self.is_all_day && (self.dtstart.is_some() || self.due.is_some());
+            if timed && let Some(Until::Date(d)) = r.until {
+                let end = d.and_hms_opt(23, 59, 59).unwrap();
+                r.until = Local
+                    .from_local_datetime(&end)
+                    .earliest()
+                    .map(|dt| Until::Utc(dt.with_timezone(&Utc)));
+            }
+            r.to_rrule()
+        });
+    }
+
+    /// Why the RRULE would never give a next occurrence (so the task wouldn't respawn).
+    pub fn recurrence_warning(&self) -> Option<String> {
+        let rule = self.rrule.as_deref()?;
+        let Some(seed) = self.dtstart.or(self.due) else {
+            return Some("Repeating task needs a due or start date".to_string());
+        };
+        if let Err(e) = rule_set(rule, seed) {
+            return Some(format!("Invalid recurrence '{}': {}", rule, e));
+        }
+        if self.occurrences_after(seed, 1).is_empty() {
+            return Some(format!("Recurrence '{}' never repeats", rule));
+        }
+        None
+    }
+}
+
+/// One occurrence of a recurring task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    // Start as generated by the RRULE, i.e. its RECURRENCE-ID
+    pub start: DateTime<Utc>,
+    pub status: TaskStatus,
+    // Changed on its own through a RECURRENCE-ID override
+    pub overridden: bool,
+}
+
+impl Task {
+    // All-day series match occurrences by day, the stored times are only a convention
+    fn same_occurrence(&self, a: DateTime<Utc>, b: DateTime<Utc>) -> bool {
+        a == b
+            || (self.is_all_day
+                && a.with_timezone(&Local).date_naive() == b.with_timezone(&Local).date_naive())
+    }
+
+    /// Occurrences starting after `after`, without the EXDATEs.
+    pub fn occurrences_after(&self, after: DateTime<Utc>, limit: usize) -> Vec<Occurrence> {
+        let (Some(rule), Some(seed)) = (&self.rrule, self.dtstart.or(self.due)) else {
+            return Vec::new();
+        };
+        let Ok(set) = rule_set(rule, seed) else {
+            return Vec::new();
+        };
+        let wanted = (limit + self.exdates.len() + 1).min(u16::MAX as usize) as u16;
+        set.after(after.with_timezone(&rrule::Tz::UTC))
+            .all(wanted)
+            .dates
+            .into_iter()
+            .map(|d| Utc.from_utc_datetime(&d.naive_utc()))
+            .filter(|d| *d > after && !self.exdates.iter().any(|x| self.same_occurrence(*x, *d)))
+            .take(limit)
+            .map(|start| match self.override_for(start) {
+                Some(o) => Occurrence {
+                    start,
+                    status: o.status,
+                    overridden: true,
+                },
+                None => Occurrence {
+                    start,
+                    status: TaskStatus::NeedsAction,
+                    overridden: false,
+                },
+            })
+            .collect()
+    }
+
+    /// First occurrence after `after` that isn't done yet.
+    pub fn next_occurrence(&self, after: DateTime<Utc>) -> Option<Occurrence> {
+        self.occurrences_after(after, self.overrides.len() + 1)
+            .into_iter()
+            .find(|o| !o.status.is_done())
+    }
+
+    pub fn override_for(&self, recurrence_id: DateTime<Utc>) -> Option<&RecurrenceOverride> {
+        self.overrides
+            .iter()
+            .find(|o| self.same_occurrence(o.recurrence_id, recurrence_id))
+    }
+
+    /// Completes (or cancels...) a single occurrence, through its override.
+    pub fn set_occurrence_status(&mut self, recurrence_id: DateTime<Utc>, status: TaskStatus) {
+        match self
+            .overrides
+            .iter()
+            .position(|o| self.same_occurrence(o.recurrence_id, recurrence_id))
+        {
+            Some(idx) => self.overrides[idx].set_status(status),
+            None => {
+                let mut o = RecurrenceOverride::new(self, recurrence_id);
+                o.set_status(status);
+                self.overrides.push(o);
+                self.overrides.sort_by_key(|o| o.recurrence_id);
+            }
+        }
+    }
+
+    /// e.g. "Next: 2025-01-07 (2 changed, 3 skipped)". None if the task doesn't repeat.
+    pub fn occurrence_summary(&self) -> Option<String> {
+        self.rrule.as_ref()?;
+        let mut s = match self.next_occurrence(Utc::now()) {
+            Some(o) => format!("Next: {}", self.format_occurrence(o.start)),
+            None => "No more occurrences".to_string(),
+        };
+        let mut counts = Vec::new();
+        if !self.overrides.is_empty() {
+            counts.push(format!("{} changed", self.overrides.len()));
+        }
+        if !self.exdates.is_empty() {
+            counts.push(format!("{} skipped", self.exdates.len()));
+        }
+        if !counts.is_empty() {
+            s.push_str(&format!(" ({})", counts.join(", ")));
+        }
+        Some(s)
+    }
+
+    /// Local date of an occurrence, with the time unless the series is all-day.
+    pub fn format_occurrence(&self, start: DateTime<Utc>) -> String {
+        let local = start.with_timezone(&Local);
+        if self.is_all_day {
+            local.format("%Y-%m-%d").to_string()
+        } else {
+            local.format("%Y-%m-%d %H:%M").to_string()
+        }
+    }
+
+    /// Drops one occurrence from the series (EXDATE), with its override if any.
+    pub fn skip_occurrence(&mut self, recurrence_id: DateTime<Utc>) {
+        let overrides = std::mem::take(&mut self.overrides);
+        self.overrides = overrides
+            .into_iter()
+            .filter(|o| !self.same_occurrence(o.recurrence_id, recurrence_id))
+            .collect();
+        if !self.exdates.contains(&recurrence_id) {
+            self.exdates.push(recurrence_id);
+            self.exdates.sort();
+        }
+    }
+}
+
+// The rule as the rrule crate expands it, starting at `seed`
+fn rule_set(rule: &str, seed: DateTime<Utc>) -> Result<RRuleSet, rrule::RRuleError> {
+    format!("DTSTART:{}\nRRULE:{}", seed.format("%Y%m%dT%H%M%SZ"), rule).parse()
+}
+
+pub fn parse_weekday(s: &str) -> Option<Weekday> {
+    match s.to_ascii_uppercase().as_str() {
+        "MO" | "MON" | "MONDAY" => Some(Weekday::Mon),
+        "TU" | "TUE" | "TUESDAY" => Some(Weekday::Tue),
+        "WE" | "WED" | "WEDNESDAY" => Some(Weekday::Wed),
+        "TH" | "THU" | "THURSDAY" => Some(Weekday::Thu),
+        "FR" | "FRI" | "FRIDAY" => Some(Weekday::Fri),
+        "SA" | "SAT" | "SATURDAY" => Some(Weekday::Sat),
+        "SU" | "SUN" | "SUNDAY" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_until(val: &str) -> Result<Until, String> {
+    let err = || format!("Invalid UNTIL '{}'", val);
+    if let Some(utc) = val.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| err())?;
+        Ok(Until::Utc(naive.and_utc()))
+    } else if val.len() == 8 {
+        NaiveDate::parse_from_str(val, "%Y%m%d")
+            .map(Until::Date)
+            .map_err(|_| err())
+    } else {
+        NaiveDateTime::parse_from_str(val, "%Y%m%dT%H%M%S")
+            .map(Until::Floating)
+            .map_err(|_| err())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recurrence_round_trip() {
+        let rec = Recurrence::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20250601").unwrap();
+        assert_eq!(rec.freq, Freq::Weekly);
+        assert_eq!(rec.interval, 2);
+        assert!(rec.has_day(Weekday::Fri));
+        assert_eq!(
+            rec.until,
+            Some(Until::Date(NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()))
+        );
+        assert_eq!(
+            rec.to_rrule(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;UNTIL=20250601"
+        );
+        assert_eq!(rec.summary(), "Every 2 weeks on Mon, Fri until 2025-06-01");
+
+        // Unknown parts and ordinals survive
+        let raw = "FREQ=MONTHLY;BYDAY=-1FR;COUNT=3;WKST=SU";
+        let rec = Recurrence::parse(raw).unwrap();
+        assert_eq!(rec.by_day[0].nth, Some(-1));
+        assert_eq!(rec.to_rrule(), raw);
+
+        assert!(Recurrence::parse("INTERVAL=2").is_err());
+        assert!(Recurrence::parse("FREQ=DAILY;COUNT=2;UNTIL=20250101").is_err());
+        assert!(Recurrence::parse("FREQ=HOURLY").is_err());
+    }
+
+    #[test]
+    fn test_set_recurrence_until_on_timed_task() {
+        let mut task = Task::new("Standup", &Default::default());
+        task.due = Some(Utc::now());
+        let mut rec = Recurrence::new(Freq::Daily);
+        rec.until = Some(Until::Date(NaiveDate::from_ymd_opt(2030, 1, 31).unwrap()));
+        task.set_recurrence(Some(rec));
+        let rrule = task.rrule.clone().unwrap();
+        assert!(rrule.starts_with("FREQ=DAILY;UNTIL=2030"));
+        assert!(rrule.ends_with('Z'));
+        assert!(task.recurrence().is_some());
+
+        task.set_recurrence(None);
+        assert!(task.rrule.is_none());
+    }
+}
@@ -0,0 +1,245 @@
+// File: src/model/sort.rs
+// Task list orderings, picked in the config or switched at runtime
+use crate::model::item::{RawProperty, Task, TaskStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+// Unmapped property used by Apple Reminders and Tasks.org for the manual order
+pub const SORT_ORDER_KEY: &str = "X-APPLE-SORT-ORDER";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    // Status, scheduled, due within the cutoff, priority (the historical order)
+    #[default]
+    Smart,
+    Due,
+    Priority,
+    Created,
+    Alphabetical,
+    Manual,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 6] = [
+        SortOrder::Smart,
+        SortOrder::Due,
+        SortOrder::Priority,
+        SortOrder::Created,
+        SortOrder::Alphabetical,
+        SortOrder::Manual,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Smart => "Smart",
+            SortOrder::Due => "Due date",
+            SortOrder::Priority => "Priority",
+            SortOrder::Created => "Newest",
+            SortOrder::Alphabetical => "A-Z",
+            SortOrder::Manual => "Manual",
+        }
+    }
+
+    /// The next order in `ALL`, wrapping around (for a cycling key/button).
+    pub fn next(&self) -> SortOrder {
+        let idx = Self::ALL.iter().position(|o| o == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn compare(&self, a: &Task, b: &Task, cutoff: Option<DateTime<Utc>>) -> Ordering {
+        // Open tasks always come before done ones
+        let by_status = status_rank(a.status).cmp(&status_rank(b.status));
+        if by_status != Ordering::Equal {
+            return by_status;
+        }
+
+        let primary = match self {
+            SortOrder::Smart => Ordering::Equal,
+            SortOrder::Due => none_last(a.due, b.due),
+            SortOrder::Priority => priority_rank(a.priority).cmp(&priority_rank(b.priority)),
+            SortOrder::Created => none_last(b.created, a.created),
+            SortOrder::Alphabetical => a.summary.to_lowercase().cmp(&b.summary.to_lowercase()),
+            SortOrder::Manual => none_last(a.sort_order(), b.sort_order()),
+        };
+        primary.then_with(|| a.compare_with_cutoff(b, cutoff))
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl Task {
+    /// Position in the manual order (X-APPLE-SORT-ORDER), if any.
+    pub fn sort_order(&self) -> Option<i64> {
+        self.unmapped_properties
+            .iter()
+            .find(|p| p.key.eq_ignore_ascii_case(SORT_ORDER_KEY))
+            .and_then(|p| p.value.trim().parse().ok())
+    }
+
+    pub fn set_sort_order(&mut self, position: Option<i64>) {
+        self.unmapped_properties
+            .retain(|p| !p.key.eq_ignore_ascii_case(SORT_ORDER_KEY));
+        if let Some(position) = position {
+            self.unmapped_properties.push(RawProperty {
+                key: SORT_ORDER_KEY.to_string(),
+                value: position.to_string(),
+                params: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Manual positions after moving `uid` one step up or down among its siblings,
+/// as listed in `view` (the tasks on screen, in their current order).
+/// The siblings are renumbered from 1, only the positions that change are returned.
+pub fn manual_order_moves(view: &[Task], uid: &str, up: bool) -> Vec<(String, i64)> {
+    let Some(task) = view.iter().find(|t| t.uid == uid) else {
+        return Vec::new();
+    };
+    // Top-level rows may be orphans pointing to a parent that isn't shown
+    let mut siblings: Vec<&Task> = view
+        .iter()
+        .filter(|t| t.depth == task.depth && (task.depth == 0 || t.parent_uid == task.parent_uid))
+        .collect();
+
+    let Some(idx) = siblings.iter().position(|t| t.uid == uid) else {
+        return Vec::new();
+    };
+    let target = if up {
+        idx.checked_sub(1)
+    } else {
+        Some(idx + 1).filter(|i| *i < siblings.len())
+    };
+    let Some(target) = target else {
+        return Vec::new();
+    };
+    siblings.swap(idx, target);
+
+    siblings
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (t, i as i64 + 1))
+        .filter(|(t, pos)| t.sort_order() != Some(*pos))
+        .map(|(t, pos)| (t.uid.clone(), pos))
+        .collect()
+}
+
+fn status_rank(s: TaskStatus) -> u8 {
+    match s {
+        TaskStatus::InProcess | TaskStatus::NeedsAction => 0,
+        TaskStatus::Completed => 1,
+        TaskStatus::Cancelled => 2,
+    }
+}
+
+// 0 means unset, which RFC 5545 puts in the middle
+fn priority_rank(p: u8) -> u8 {
+    if p == 0 { 5 } else { p }
+}
+
+fn none_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_orders() {
+        let aliases = Default::default();
+        let mut a = Task::new("banana !9 @2030-01-01", &aliases);
+        let mut b = Task::new("Apple !1", &aliases);
+        let mut done = Task::new("aardvark !1 @2020-01-01", &aliases);
+        done.set_status(TaskStatus::Completed);
+        a.created = Some(Utc::now() - chrono::Duration::days(1));
+        b.unmapped_properties.push(RawProperty {
+            key: SORT_ORDER_KEY.to_string(),
+            value: "10".to_string(),
+            params: Vec::new(),
+        });
+        a.unmapped_properties.push(RawProperty {
+            key: SORT_ORDER_KEY.to_string(),
+            value: "20".to_string(),
+            params: Vec::new(),
+        });
+
+        let sorted = |order: SortOrder| {
+            let mut tasks = vec![done.clone(), a.clone(), b.clone()];
+            tasks.sort_by(|x, y| order.compare(x, y, None));
+            tasks.into_iter().map(|t| t.summary).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(SortOrder::Due), ["banana", "Apple", "aardvark"]);
+        assert_eq!(sorted(SortOrder::Priority), ["Apple", "banana", "aardvark"]);
+        assert_eq!(sorted(SortOrder::Created), ["Apple", "banana", "aardvark"]);
+        assert_eq!(
+            sorted(SortOrder::Alphabetical),
+            ["Apple", "banana", "aardvark"]
+        );
+        assert_eq!(sorted(SortOrder::Manual), ["Apple", "banana", "aardvark"]);
+        assert_eq!(SortOrder::Manual.next(), SortOrder::Smart);
+    }
+
+    #[test]
+    fn test_manual_order_moves() {
+        let aliases = Default::default();
+        let mut view: Vec<Task> = ["a", "b", "c"]
+            .iter()
+            .map(|s| Task::new(s, &aliases))
+            .collect();
+        let mut child = Task::new("a1", &aliases);
+        child.parent_uid = Some(view[0].uid.clone());
+        child.depth = 1;
+        view.insert(1, child);
+
+        // No positions yet: the visible order is numbered, with c moved above b
+        let moves = manual_order_moves(&view, &view[3].uid, true);
+        let expected = [
+            (view[0].uid.clone(), 1),
+            (view[3].uid.clone(), 2),
+            (view[2].uid.clone(), 3),
+        ];
+        assert_eq!(moves, expected);
+
+        for (uid, pos) in moves {
+            let t = view.iter_mut().find(|t| t.uid == uid).unwrap();
+            t.set_sort_order(Some(pos));
+        }
+        view.sort_by(|a, b| SortOrder::Manual.compare(a, b, None));
+        assert_eq!(view[0].summary, "a");
+
+        // Only the swapped pair changes afterwards; the edges don't move
+        let c = view.iter().find(|t| t.summary == "c").unwrap().uid.clone();
+        assert_eq!(manual_order_moves(&view, &c, true).len(), 2);
+        assert!(manual_order_moves(&view, &view[0].uid.clone(), true).is_empty());
+        let lone_child = view.iter().find(|t| t.summary == "a1").unwrap();
+        assert!(manual_order_moves(&view, &lone_child.uid, false).is_empty());
+    }
+
+    #[test]
+    fn test_hierarchy_with_parent_loop() {
+        let aliases = Default::default();
+        let mut a = Task::new("a", &aliases);
+        let mut b = Task::new("b", &aliases);
+        a.parent_uid = Some(b.uid.clone());
+        b.parent_uid = Some(a.uid.clone());
+
+        // Neither is a root, but both must still be listed (once)
+        let view = Task::organize_hierarchy(vec![a, b], None, SortOrder::Alphabetical);
+        let names: Vec<_> = view.iter().map(|t| (t.summary.as_str(), t.depth)).collect();
+        assert_eq!(names, [("a", 0), ("b", 1)]);
+    }
+}
@@ -0,0 +1,156 @@
+// File: src/model/status.rs
+// User-defined statuses ("Waiting", "Delegated") on top of the four of RFC 5545.
+// The name goes in X-STATUS; STATUS keeps a standard value for other clients.
+use crate::model::item::{Task, TaskStatus};
+use serde::{Deserialize, Serialize};
+
+pub const CUSTOM_STATUS_KEY: &str = "X-STATUS";
+
+fn default_base() -> TaskStatus {
+    TaskStatus::NeedsAction
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct CustomStatus {
+    pub name: String,
+    // What STATUS says meanwhile
+    #[serde(default = "default_base")]
+    pub base: TaskStatus,
+    // "#RRGGBB"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl CustomStatus {
+    /// One of the standard statuses, as a step of the cycle.
+    pub fn standard(status: TaskStatus) -> Self {
+        Self {
+            name: status.label().to_string(),
+            base: status,
+            color: None,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        self.name == self.base.label()
+    }
+
+    pub fn find<'a>(statuses: &'a [CustomStatus], name: &str) -> Option<&'a CustomStatus> {
+        statuses.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The statuses to cycle through, in `order` (names of standard or custom
+/// statuses). Without an order: to do, in progress, then the custom ones.
+pub fn status_cycle(custom: &[CustomStatus], order: &[String]) -> Vec<CustomStatus> {
+    let standard: Vec<CustomStatus> = [
+        TaskStatus::NeedsAction,
+        TaskStatus::InProcess,
+        TaskStatus::Completed,
+        TaskStatus::Cancelled,
+    ]
+    .into_iter()
+    .map(CustomStatus::standard)
+    .collect();
+    // A custom status can't shadow a standard one
+    let custom: Vec<CustomStatus> = custom
+        .iter()
+        .filter(|c| CustomStatus::find(&standard, &c.name).is_none())
+        .cloned()
+        .collect();
+
+    if order.is_empty() {
+        return standard[..2].iter().chain(&custom).cloned().collect();
+    }
+    let mut cycle: Vec<CustomStatus> = Vec::new();
+    for name in order {
+        let step =
+            CustomStatus::find(&standard, name).or_else(|| CustomStatus::find(&custom, name));
+        if let Some(step) = step
+            && !cycle.contains(step)
+        {
+            cycle.push(step.clone());
+        }
+    }
+    cycle
+}
+
+impl Task {
+    /// The custom status if there is one, else the standard label.
+    pub fn status_name(&self) -> &str {
+        self.custom_status
+            .as_deref()
+            .unwrap_or_else(|| self.status.label())
+    }
+
+    /// Moves to a step of the cycle, standard or custom.
+    pub fn apply_status(&mut self, step: &CustomStatus) {
+        self.set_status(step.base);
+        if !step.is_standard() {
+            self.custom_status = Some(step.name.clone());
+        }
+    }
+
+    /// The step after the current status in `cycle`, or the first one.
+    pub fn next_in_cycle<'a>(&self, cycle: &'a [CustomStatus]) -> Option<&'a CustomStatus> {
+        let current = cycle.iter().position(|s| match &self.custom_status {
+            Some(name) => s.name.eq_ignore_ascii_case(name),
+            None => s.is_standard() && s.base == self.status,
+        });
+        let next = current.map_or(0, |i| (i + 1) % cycle.len());
+        cycle.get(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_cycle() {
+        let waiting = CustomStatus {
+            name: "Waiting".to_string(),
+            base: TaskStatus::InProcess,
+            color: Some("#D08770".to_string()),
+        };
+        let custom = vec![
+            waiting.clone(),
+            CustomStatus::standard(TaskStatus::Completed),
+        ];
+
+        let cycle = status_cycle(&custom, &[]);
+        let names: Vec<_> = cycle.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["To do", "In progress", "Waiting"]);
+
+        let order = vec![
+            "waiting".to_string(),
+            "Done".to_string(),
+            "Nope".to_string(),
+        ];
+        let names: Vec<_> = status_cycle(&custom, &order)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["Waiting", "Done"]);
+
+        let mut t = Task::new("Ask for the invoice", &Default::default());
+        for expected in ["In progress", "Waiting", "To do"] {
+            let next = t.next_in_cycle(&cycle).unwrap().clone();
+            t.apply_status(&next);
+            assert_eq!(t.status_name(), expected);
+        }
+
+        t.apply_status(&waiting);
+        assert_eq!(t.status, TaskStatus::InProcess);
+        let ics = t.to_ics();
+        assert!(ics.contains("STATUS:IN-PROCESS\r\n"));
+        assert!(ics.contains("X-STATUS:Waiting\r\n"));
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.custom_status.as_deref(), Some("Waiting"));
+        assert!(reparsed.matches_search_term("is:waiting"));
+
+        // Finishing it drops the custom status
+        t.set_status(TaskStatus::Completed);
+        assert_eq!(t.custom_status, None);
+    }
+}
@@ -0,0 +1,262 @@
+// File: src/model/taskwarrior.rs
+// Taskwarrior's JSON (`task export` / `task import`), to move tasks between the two.
+// Projects become tags under "project:" (Home.Garden -> #project:Home:Garden) so they
+// survive a round trip; annotations become lines of the description.
+use crate::model::item::{Task, TaskStatus};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const PROJECT_TAG: &str = "project:";
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Serialize, Deserialize)]
+struct TwTask {
+    #[serde(default)]
+    uuid: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Depends::is_empty")]
+    depends: Depends,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    // Set on the instances of a recurring task (the template's UUID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+}
+
+// A list since Taskwarrior 2.6, a comma-separated string before
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Depends {
+    List(Vec<String>),
+    Joined(String),
+}
+
+impl Default for Depends {
+    fn default() -> Self {
+        Depends::List(Vec::new())
+    }
+}
+
+impl Depends {
+    fn is_empty(&self) -> bool {
+        self.uuids().is_empty()
+    }
+
+    fn uuids(&self) -> Vec<String> {
+        match self {
+            Depends::List(list) => list.clone(),
+            Depends::Joined(s) => s
+                .split(',')
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Annotation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    description: String,
+}
+
+fn parse_date(s: &Option<String>) -> Option<DateTime<Utc>> {
+    let s = s.as_deref()?;
+    NaiveDateTime::parse_from_str(s, DATE_FORMAT)
+        .map(|d| d.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(s).map(|d| d.with_timezone(&Utc)))
+        .ok()
+}
+
+fn format_date(d: Option<DateTime<Utc>>) -> Option<String> {
+    d.map(|d| d.format(DATE_FORMAT).to_string())
+}
+
+/// Tasks of a `task export` file, for `calendar_href`. Deleted tasks come in cancelled;
+/// the generated instances of recurring tasks are skipped, their template carries the
+/// recurrence.
+pub fn parse(json: &str, calendar_href: &str) -> Result<Vec<Task>, String> {
+    let items: Vec<TwTask> =
+        serde_json::from_str(json).map_err(|e| format!("Not a Taskwarrior export: {}", e))?;
+
+    let mut tasks = Vec::new();
+    for tw in items {
+        if tw.parent.is_some() && tw.recur.is_some() {
+            continue;
+        }
+        let mut task = Task::new("", &HashMap::new());
+        if !tw.uuid.is_empty() {
+            task.uid = tw.uuid;
+        }
+        task.summary = tw.description;
+        task.calendar_href = calendar_href.to_string();
+        task.status = match tw.status.as_str() {
+            "completed" => TaskStatus::Completed,
+            "deleted" => TaskStatus::Cancelled,
+            _ => TaskStatus::NeedsAction,
+        };
+        if task.status == TaskStatus::Completed {
+            task.completed_at = parse_date(&tw.end);
+        }
+        task.created = parse_date(&tw.entry).or(task.created);
+        task.due = parse_date(&tw.due);
+        task.dtstart = parse_date(&tw.scheduled);
+        task.priority = match tw.priority.as_deref() {
+            Some("H") => 1,
+            Some("M") => 5,
+            Some("L") => 9,
+            _ => 0,
+        };
+        if let Some(project) = tw.project.filter(|p| !p.is_empty()) {
+            task.categories
+                .push(format!("{}{}", PROJECT_TAG, project.replace('.', ":")));
+        }
+        task.categories.extend(tw.tags);
+        task.dependencies = tw.depends.uuids();
+        task.description = tw
+            .annotations
+            .into_iter()
+            .map(|a| a.description)
+            .collect::<Vec<_>>()
+            .join("\n");
+        task.rrule = tw.recur.as_deref().and_then(recur_to_rrule);
+        tasks.push(task);
+    }
+    Ok(tasks)
+}
+
+fn recur_to_rrule(recur: &str) -> Option<String> {
+    let freq = match recur {
+        "daily" | "day" => "DAILY",
+        "weekly" | "week" => "WEEKLY",
+        "monthly" | "month" => "MONTHLY",
+        "yearly" | "year" | "annual" => "YEARLY",
+        _ => return None,
+    };
+    Some(format!("FREQ={}", freq))
+}
+
+fn rrule_to_recur(rrule: &str) -> Option<String> {
+    let recur = match rrule {
+        "FREQ=DAILY" => "daily",
+        "FREQ=WEEKLY" => "weekly",
+        "FREQ=MONTHLY" => "monthly",
+        "FREQ=YEARLY" => "yearly",
+        _ => return None,
+    };
+    Some(recur.to_string())
+}
+
+/// `task import` input for `tasks`. Each line of a description becomes an annotation.
+pub fn to_json(tasks: &[Task]) -> String {
+    let items: Vec<TwTask> = tasks
+        .iter()
+        .map(|task| {
+            let (project, tags): (Vec<&String>, Vec<&String>) = task
+                .categories
+                .iter()
+                .partition(|c| c.starts_with(PROJECT_TAG));
+            let recur = task.rrule.as_deref().and_then(rrule_to_recur);
+            let status = match task.status {
+                TaskStatus::Completed => "completed",
+                TaskStatus::Cancelled => "deleted",
+                // Taskwarrior wants a due date on recurring tasks
+                _ if recur.is_some() && task.due.is_some() => "recurring",
+                _ => "pending",
+            };
+            let entry = format_date(task.created);
+            TwTask {
+                uuid: task.uid.clone(),
+                description: task.summary.clone(),
+                status: status.to_string(),
+                entry: entry.clone(),
+                end: format_date(task.completed_at),
+                due: format_date(task.due),
+                scheduled: format_date(task.dtstart),
+                project: project
+                    .first()
+                    .and_then(|p| p.strip_prefix(PROJECT_TAG))
+                    .map(|p| p.replace(':', ".")),
+                tags: tags.into_iter().cloned().collect(),
+                priority: match task.priority {
+                    1..=4 => Some("H".to_string()),
+                    5 => Some("M".to_string()),
+                    6..=9 => Some("L".to_string()),
+                    _ => None,
+                },
+                depends: Depends::List(task.dependencies.clone()),
+                annotations: task
+                    .description
+                    .lines()
+                    .filter(|l| !l.trim().is_empty())
+                    .map(|l| Annotation {
+                        entry: entry.clone(),
+                        description: l.to_string(),
+                    })
+                    .collect(),
+                recur: if status == "recurring" { recur } else { None },
+                parent: None,
+            }
+        })
+        .collect();
+    serde_json::to_string_pretty(&items).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taskwarrior_round_trip() {
+        let json = r#"[
+            {"id":1,"description":"Plant tomatoes","status":"pending",
+             "uuid":"a1","project":"Home.Garden","tags":["spring"],
+             "due":"20250405T120000Z","priority":"H","depends":"b2,c3",
+             "annotations":[{"entry":"20250101T000000Z","description":"Buy seeds first"}],
+             "urgency":8.2},
+            {"description":"Buy seeds","status":"completed","uuid":"b2",
+             "end":"20250301T080000Z"},
+            {"description":"Water","status":"pending","uuid":"d4","recur":"daily",
+             "parent":"e5"}
+        ]"#;
+        let tasks = parse(json, "/cal/").unwrap();
+        assert_eq!(tasks.len(), 2);
+        let plant = &tasks[0];
+        assert_eq!(plant.uid, "a1");
+        assert_eq!(plant.categories, vec!["project:Home:Garden", "spring"]);
+        assert_eq!(plant.dependencies, vec!["b2", "c3"]);
+        assert_eq!(plant.priority, 1);
+        assert_eq!(plant.description, "Buy seeds first");
+        assert_eq!(format_date(plant.due).as_deref(), Some("20250405T120000Z"));
+        assert_eq!(tasks[1].status, TaskStatus::Completed);
+        assert!(tasks[1].completed_at.is_some());
+
+        let back = parse(&to_json(&tasks), "/cal/").unwrap();
+        assert_eq!(back[0].categories, plant.categories);
+        assert_eq!(back[0].dependencies, plant.dependencies);
+        assert_eq!(back[0].due, plant.due);
+        assert_eq!(back[0].description, plant.description);
+        assert_eq!(back[1].status, TaskStatus::Completed);
+    }
+}
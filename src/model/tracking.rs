@@ -0,0 +1,147 @@
+// File: src/model/tracking.rs
+// Actual time spent, next to the estimate: start/stop sessions summed in
+// X-TIME-SPENT (minutes), each one logged as X-TIME-SESSION:start/end
+use crate::model::item::Task;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const ICS_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TimeSession {
+    pub start: DateTime<Utc>,
+    // None while it is running
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeSession {
+    pub fn minutes(&self, now: DateTime<Utc>) -> u32 {
+        let end = self.end.unwrap_or(now);
+        // Rounded, so a 40s session still counts as a minute
+        ((end - self.start).num_seconds().max(0) as u32 + 30) / 60
+    }
+
+    /// RFC 5545 PERIOD style "start/end", only the start while running.
+    pub fn to_ics_value(&self) -> String {
+        match self.end {
+            Some(end) => format!(
+                "{}/{}",
+                self.start.format(ICS_FORMAT),
+                end.format(ICS_FORMAT)
+            ),
+            None => self.start.format(ICS_FORMAT).to_string(),
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let parse = |s: &str| {
+            NaiveDateTime::parse_from_str(s.trim(), ICS_FORMAT)
+                .ok()
+                .map(|dt| dt.and_utc())
+        };
+        match value.split_once('/') {
+            Some((start, end)) => Some(Self {
+                start: parse(start)?,
+                end: Some(parse(end)?),
+            }),
+            None => Some(Self {
+                start: parse(value)?,
+                end: None,
+            }),
+        }
+    }
+}
+
+impl Task {
+    pub fn is_tracking(&self) -> bool {
+        self.time_sessions.iter().any(|s| s.end.is_none())
+    }
+
+    /// Recorded minutes plus the running session, if any.
+    pub fn tracked_minutes(&self, now: DateTime<Utc>) -> u32 {
+        let running: u32 = self
+            .time_sessions
+            .iter()
+            .filter(|s| s.end.is_none())
+            .map(|s| s.minutes(now))
+            .sum();
+        self.time_spent + running
+    }
+
+    /// False if a session is already running.
+    pub fn start_tracking(&mut self, now: DateTime<Utc>) -> bool {
+        if self.is_tracking() {
+            return false;
+        }
+        self.time_sessions.push(TimeSession {
+            start: now,
+            end: None,
+        });
+        true
+    }
+
+    /// Ends the running session and adds it to the time spent. Returns its minutes.
+    pub fn stop_tracking(&mut self, now: DateTime<Utc>) -> Option<u32> {
+        let session = self.time_sessions.iter_mut().find(|s| s.end.is_none())?;
+        session.end = Some(now.max(session.start));
+        let minutes = session.minutes(now);
+        self.time_spent += minutes;
+        Some(minutes)
+    }
+
+    pub fn toggle_tracking(&mut self, now: DateTime<Utc>) {
+        if self.stop_tracking(now).is_none() {
+            self.start_tracking(now);
+        }
+    }
+}
+
+/// "45m", "1h 05m"
+pub fn format_minutes(minutes: u32) -> String {
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TaskStatus;
+    use chrono::Duration;
+
+    #[test]
+    fn test_time_tracking() {
+        let mut t = Task::new("Write report ~2h", &Default::default());
+        let start = Utc::now() - Duration::hours(2);
+
+        assert!(t.start_tracking(start));
+        assert!(!t.start_tracking(start));
+        assert_eq!(t.tracked_minutes(start + Duration::minutes(10)), 10);
+        assert_eq!(t.stop_tracking(start + Duration::minutes(25)), Some(25));
+        assert!(t.stop_tracking(start).is_none());
+
+        t.toggle_tracking(start + Duration::minutes(60));
+        assert!(t.is_tracking());
+        // Completing the task closes the running session
+        t.set_status(TaskStatus::Completed);
+        assert!(!t.is_tracking());
+        assert!(t.time_spent >= 25 + 59);
+
+        let ics = t.to_ics();
+        assert!(ics.contains(&format!("X-TIME-SPENT:{}", t.time_spent)));
+        assert_eq!(ics.matches("X-TIME-SESSION:").count(), 2);
+        let reparsed = Task::from_ics(&ics, "".into(), "/href".into(), "/cal/".into()).unwrap();
+        assert_eq!(reparsed.time_spent, t.time_spent);
+        assert_eq!(reparsed.time_sessions.len(), 2);
+        assert_eq!(
+            reparsed.time_sessions[0].start.timestamp(),
+            start.timestamp()
+        );
+        assert!(reparsed.unmapped_properties.is_empty());
+
+        assert_eq!(format_minutes(65), "1h 05m");
+        assert_eq!(format_minutes(45), "45m");
+    }
+}
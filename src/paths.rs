@@ -84,7 +84,21 @@ impl AppPaths {
         Self::get_data_dir().ok().map(|p| p.join("journal.json"))
     }
 
+    pub fn get_oauth_token_path() -> Option<PathBuf> {
+        Self::get_data_dir()
+            .ok()
+            .map(|p| p.join("oauth_token.json"))
+    }
+
     pub fn get_local_task_path() -> Option<PathBuf> {
         Self::get_data_dir().ok().map(|p| p.join("local.json"))
     }
+
+    pub fn get_trash_path() -> Option<PathBuf> {
+        Self::get_data_dir().ok().map(|p| p.join("trash.json"))
+    }
+
+    pub fn get_history_path() -> Option<PathBuf> {
+        Self::get_data_dir().ok().map(|p| p.join("history.json"))
+    }
 }
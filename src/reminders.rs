@@ -0,0 +1,65 @@
+// File: src/reminders.rs
+// VALARM scheduling: find alarms firing in a time window and show them
+use crate::cache::Cache;
+use crate::model::Task;
+use crate::storage::LocalStorage;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    pub task_uid: String,
+    pub summary: String,
+    pub message: String,
+    pub fire_at: DateTime<Utc>,
+}
+
+/// Alarms of open tasks firing in (after, until].
+pub fn collect_due(tasks: &[Task], after: DateTime<Utc>, until: DateTime<Utc>) -> Vec<Reminder> {
+    let mut out = Vec::new();
+    for task in tasks.iter().filter(|t| !t.status.is_done()) {
+        for alarm in &task.alarms {
+            if let Some(at) = alarm.fire_time(task)
+                && at > after
+                && at <= until
+            {
+                out.push(Reminder {
+                    task_uid: task.uid.clone(),
+                    summary: task.summary.clone(),
+                    message: alarm
+                        .description
+                        .clone()
+                        .filter(|d| !d.is_empty() && d != "Reminder")
+                        .unwrap_or_else(|| task.summary.clone()),
+                    fire_at: at,
+                });
+            }
+        }
+    }
+    out.sort_by_key(|r| r.fire_at);
+    out
+}
+
+/// Every task we know about offline (server caches + local calendars).
+pub fn load_cached_tasks() -> Vec<Task> {
+    let mut tasks: Vec<Task> = LocalStorage::load_all()
+        .into_iter()
+        .flat_map(|(_, t)| t)
+        .collect();
+    for cal in Cache::load_calendars().unwrap_or_default() {
+        if let Ok((cached, _)) = Cache::load_for(&cal) {
+            tasks.extend(cached);
+        }
+    }
+    tasks
+}
+
+#[cfg(feature = "notifications")]
+pub fn notify(reminder: &Reminder) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("cfait")
+        .summary(&reminder.summary)
+        .body(&reminder.message)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,125 @@
+// File: src/search_index.rs
+// In-memory inverted index over the cached tasks (summary, description, tags), behind
+// `Cache::search`. Kept per cache file and rebuilt when the file changed on disk, so
+// writes from other processes are picked up. Never written to disk: with encrypt_data
+// on, a plain-text index would leak what the caches hide.
+use crate::model::Task;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+struct Indexed {
+    // Last write of the file when it was indexed
+    modified: Option<SystemTime>,
+    tasks: Vec<Task>,
+    // word -> positions in `tasks`
+    words: BTreeMap<String, Vec<usize>>,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    files: HashMap<PathBuf, Indexed>,
+}
+
+/// Lowercased words of `text`, split on anything that isn't a letter or digit.
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+impl SearchIndex {
+    pub fn is_fresh(&self, path: &Path, modified: Option<SystemTime>) -> bool {
+        self.files
+            .get(path)
+            .is_some_and(|f| f.modified.is_some() && f.modified == modified)
+    }
+
+    pub fn update(&mut self, path: &Path, tasks: &[Task], modified: Option<SystemTime>) {
+        let mut words: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, task) in tasks.iter().enumerate() {
+            let text = [&task.summary, &task.description]
+                .into_iter()
+                .chain(&task.categories);
+            let unique: HashSet<String> = text.flat_map(|t| words(t.as_str())).collect();
+            for word in unique {
+                words.entry(word).or_default().push(i);
+            }
+        }
+        self.files.insert(
+            path.to_path_buf(),
+            Indexed {
+                modified,
+                tasks: tasks.to_vec(),
+                words,
+            },
+        );
+    }
+
+    /// Forgets the files `keep` says are gone.
+    pub fn retain(&mut self, keep: impl Fn(&Path) -> bool) {
+        self.files.retain(|path, _| keep(path));
+    }
+
+    /// Tasks containing every word of `query`, each as a word prefix ("pay" finds
+    /// "payment"). An empty query finds nothing.
+    pub fn search(&self, query: &str) -> Vec<Task> {
+        let query: Vec<String> = words(query).collect();
+        if query.is_empty() {
+            return vec![];
+        }
+        let mut found = Vec::new();
+        for file in self.files.values() {
+            let mut hits: Option<HashSet<usize>> = None;
+            for word in &query {
+                let matching: HashSet<usize> = file
+                    .words
+                    .range(word.clone()..)
+                    .take_while(|(w, _)| w.starts_with(word.as_str()))
+                    .flat_map(|(_, positions)| positions.iter().copied())
+                    .collect();
+                hits = Some(match hits {
+                    Some(h) => h.intersection(&matching).copied().collect(),
+                    None => matching,
+                });
+            }
+            let mut hits: Vec<usize> = hits.unwrap_or_default().into_iter().collect();
+            hits.sort_unstable();
+            found.extend(hits.into_iter().map(|i| file.tasks[i].clone()));
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_word_prefixes() {
+        let mut pay = Task::new("Pay the electricity bill", &HashMap::new());
+        pay.categories = vec!["home:admin".to_string()];
+        let mut call = Task::new("Call the plumber", &HashMap::new());
+        call.description = "About the Bill from March".to_string();
+
+        let mut index = SearchIndex::default();
+        index.update(Path::new("a.json"), std::slice::from_ref(&pay), None);
+        index.update(Path::new("b.json"), std::slice::from_ref(&call), None);
+
+        let uids = |q: &str| -> Vec<String> {
+            let mut u: Vec<String> = index.search(q).into_iter().map(|t| t.uid).collect();
+            u.sort();
+            u
+        };
+        let mut both = vec![pay.uid.clone(), call.uid.clone()];
+        both.sort();
+        assert_eq!(uids("bill"), both);
+        assert_eq!(uids("ELEC bill"), vec![pay.uid.clone()]);
+        assert_eq!(uids("#admin"), vec![pay.uid.clone()]);
+        assert!(uids("plumber admin").is_empty());
+        assert!(uids("  ").is_empty());
+
+        // Files without a known write time are always re-read
+        assert!(!index.is_fresh(Path::new("a.json"), None));
+    }
+}
@@ -1,9 +1,14 @@
 // File: src/storage.rs
-use crate::model::Task;
+use crate::encryption;
+use crate::model::{CalendarListEntry, Task};
 use crate::paths::AppPaths;
-use anyhow::Result;
+use crate::vdir;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
 #[cfg(not(target_os = "android"))]
 use fs2::FileExt;
@@ -11,6 +16,14 @@ use fs2::FileExt;
 // Constants for identification
 pub const LOCAL_CALENDAR_HREF: &str = "local://default";
 pub const LOCAL_CALENDAR_NAME: &str = "Local";
+// Local calendars created by the user get "local://<uuid>", stored in local_<uuid>.json
+pub const LOCAL_CALENDAR_PREFIX: &str = "local://";
+pub const DEFAULT_TRASH_DAYS: u32 = 30;
+
+/// True for calendars kept on this device only (never synced).
+pub fn is_local_calendar(href: &str) -> bool {
+    href.starts_with(LOCAL_CALENDAR_PREFIX)
+}
 
 pub struct LocalStorage;
 
@@ -19,6 +32,31 @@ impl LocalStorage {
         AppPaths::get_local_task_path()
     }
 
+    /// File of a local calendar, or its directory when they are kept in a vdir.
+    pub fn get_calendar_path(href: &str) -> Option<PathBuf> {
+        if vdir::root().is_some() {
+            return vdir::collection_path(href);
+        }
+        if href == LOCAL_CALENDAR_HREF {
+            return Self::get_path();
+        }
+        // Ids are generated, but hrefs can come from a hand-edited config
+        let id = href.strip_prefix(LOCAL_CALENDAR_PREFIX)?;
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return None;
+        }
+        AppPaths::get_data_dir()
+            .ok()
+            .map(|dir| dir.join(format!("local_{}.json", id)))
+    }
+
+    // Names of the local calendars (local_calendars.json)
+    fn get_registry_path() -> Option<PathBuf> {
+        AppPaths::get_data_dir()
+            .ok()
+            .map(|dir| dir.join("local_calendars.json"))
+    }
+
     /// Helper to get a sidecar lock file path (e.g., "local.json.lock")
     #[cfg(not(target_os = "android"))]
     fn get_lock_path(file_path: &Path) -> PathBuf {
@@ -71,24 +109,50 @@ impl LocalStorage {
         Ok(())
     }
 
+    /// Atomic write of a data file, encrypted when enabled in the config.
+    pub fn write_data(path: &Path, json: String) -> Result<()> {
+        Self::atomic_write(path, encryption::seal(json)?)
+    }
+
+    /// Reads a data file written with `write_data`.
+    pub fn read_data(path: &Path) -> Result<String> {
+        encryption::open(fs::read_to_string(path)?)
+    }
+
+    /// Saves the default local calendar.
     pub fn save(tasks: &[Task]) -> Result<()> {
-        if let Some(path) = Self::get_path() {
+        Self::save_calendar(LOCAL_CALENDAR_HREF, tasks)
+    }
+
+    /// Loads the default local calendar.
+    pub fn load() -> Result<Vec<Task>> {
+        Self::load_calendar(LOCAL_CALENDAR_HREF)
+    }
+
+    pub fn save_calendar(href: &str, tasks: &[Task]) -> Result<()> {
+        if vdir::root().is_some() {
+            return vdir::save(href, tasks);
+        }
+        if let Some(path) = Self::get_calendar_path(href) {
             Self::with_lock(&path, || {
                 let json = serde_json::to_string_pretty(tasks)?;
-                Self::atomic_write(&path, json)?;
+                Self::write_data(&path, json)?;
                 Ok(())
             })?;
         }
         Ok(())
     }
 
-    pub fn load() -> Result<Vec<Task>> {
-        if let Some(path) = Self::get_path() {
+    pub fn load_calendar(href: &str) -> Result<Vec<Task>> {
+        if vdir::root().is_some() {
+            return vdir::load(href);
+        }
+        if let Some(path) = Self::get_calendar_path(href) {
             if !path.exists() {
                 return Ok(vec![]);
             }
             return Self::with_lock(&path, || {
-                let json = fs::read_to_string(&path)?;
+                let json = Self::read_data(&path)?;
                 // CHANGE: Propagate error instead of checking `if let Ok`
                 let tasks = serde_json::from_str::<Vec<Task>>(&json)?;
                 Ok(tasks)
@@ -96,4 +160,256 @@ impl LocalStorage {
         }
         Ok(vec![])
     }
+
+    /// Tasks of every local calendar, by href.
+    pub fn load_all() -> Vec<(String, Vec<Task>)> {
+        Self::calendars()
+            .into_iter()
+            .map(|c| {
+                let tasks = Self::load_calendar(&c.href).unwrap_or_default();
+                (c.href, tasks)
+            })
+            .collect()
+    }
+
+    /// Every local calendar, the default one first.
+    pub fn calendars() -> Vec<CalendarListEntry> {
+        if vdir::root().is_some() {
+            return vdir::calendars();
+        }
+        let mut cals: Vec<CalendarListEntry> = Self::get_registry_path()
+            .filter(|p| p.exists())
+            .and_then(|p| Self::with_lock(&p, || Self::read_registry(&p)).ok())
+            .unwrap_or_default();
+        Self::ensure_default(&mut cals);
+        cals
+    }
+
+    /// Puts the current local calendars at the end of a (server) calendar list,
+    /// dropping any stale copies of them.
+    pub fn add_calendars(cals: &mut Vec<CalendarListEntry>) {
+        cals.retain(|c| !is_local_calendar(&c.href));
+        cals.extend(Self::calendars());
+    }
+
+    pub fn calendar_exists(href: &str) -> bool {
+        Self::calendars().iter().any(|c| c.href == href)
+    }
+
+    // The default calendar exists even before the registry does
+    fn ensure_default(cals: &mut Vec<CalendarListEntry>) {
+        if !cals.iter().any(|c| c.href == LOCAL_CALENDAR_HREF) {
+            let entry = Self::local_entry(LOCAL_CALENDAR_NAME, LOCAL_CALENDAR_HREF);
+            cals.insert(0, entry);
+        }
+    }
+
+    fn local_entry(name: &str, href: &str) -> CalendarListEntry {
+        CalendarListEntry {
+            name: name.to_string(),
+            href: href.to_string(),
+            color: None,
+            read_only: false,
+            owner: None,
+            shared: false,
+            account: None,
+        }
+    }
+
+    fn read_registry(path: &Path) -> Result<Vec<CalendarListEntry>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    // Transactional change of the list of local calendars
+    fn modify_registry<F, T>(f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Vec<CalendarListEntry>) -> Result<T>,
+    {
+        let path = Self::get_registry_path()
+            .ok_or_else(|| anyhow!("Could not determine data directory"))?;
+        Self::with_lock(&path, || {
+            let mut cals = Self::read_registry(&path)?;
+            Self::ensure_default(&mut cals);
+            let result = f(&mut cals)?;
+            Self::atomic_write(&path, serde_json::to_string_pretty(&cals)?)?;
+            Ok(result)
+        })
+    }
+
+    /// Adds an empty local calendar.
+    pub fn create_calendar(name: &str) -> Result<CalendarListEntry> {
+        let name = name.trim();
+        if vdir::root().is_some() {
+            Self::check_name(&Self::calendars(), name, None)?;
+            return vdir::create(name);
+        }
+        Self::modify_registry(|cals| {
+            Self::check_name(cals, name, None)?;
+            let entry = Self::local_entry(
+                name,
+                &format!("{}{}", LOCAL_CALENDAR_PREFIX, Uuid::new_v4()),
+            );
+            cals.push(entry.clone());
+            Ok(entry)
+        })
+    }
+
+    pub fn rename_calendar(href: &str, name: &str) -> Result<()> {
+        let name = name.trim();
+        if vdir::root().is_some() {
+            Self::check_name(&Self::calendars(), name, Some(href))?;
+            return vdir::rename(href, name);
+        }
+        Self::modify_registry(|cals| {
+            Self::check_name(cals, name, Some(href))?;
+            let cal = cals
+                .iter_mut()
+                .find(|c| c.href == href)
+                .ok_or_else(|| anyhow!("No local calendar {}", href))?;
+            cal.name = name.to_string();
+            Ok(())
+        })
+    }
+
+    /// Brings back a local calendar under its old href (backup restore). One that
+    /// still exists keeps its current name.
+    pub fn restore_calendar(cal: &CalendarListEntry) -> Result<()> {
+        if Self::calendar_exists(&cal.href) {
+            return Ok(());
+        }
+        if vdir::root().is_some() {
+            return vdir::rename(&cal.href, &cal.name);
+        }
+        Self::modify_registry(|cals| {
+            cals.push(cal.clone());
+            Ok(())
+        })
+    }
+
+    /// Removes a local calendar and its file, returning the tasks it held.
+    /// The default one can be renamed, not deleted.
+    pub fn delete_calendar(href: &str) -> Result<Vec<Task>> {
+        if href == LOCAL_CALENDAR_HREF {
+            return Err(anyhow!("The default local calendar can't be deleted"));
+        }
+        if vdir::root().is_some() {
+            return vdir::delete(href);
+        }
+        let tasks = Self::load_calendar(href)?;
+        Self::modify_registry(|cals| {
+            let before = cals.len();
+            cals.retain(|c| c.href != href);
+            if cals.len() == before {
+                return Err(anyhow!("No local calendar {}", href));
+            }
+            Ok(())
+        })?;
+        if let Some(path) = Self::get_calendar_path(href) {
+            Self::with_lock(&path, || {
+                if path.exists() {
+                    fs::remove_file(&path)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(tasks)
+    }
+
+    fn check_name(cals: &[CalendarListEntry], name: &str, renaming: Option<&str>) -> Result<()> {
+        if name.is_empty() {
+            return Err(anyhow!("Calendar name can't be empty"));
+        }
+        let taken = cals
+            .iter()
+            .any(|c| c.name.eq_ignore_ascii_case(name) && Some(c.href.as_str()) != renaming);
+        if taken {
+            return Err(anyhow!("There is already a local calendar named {}", name));
+        }
+        Ok(())
+    }
+}
+
+/// A deleted task, kept around so it can be restored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub task: Task,
+    // Calendar the task was deleted from
+    pub calendar_href: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Deleted tasks from every calendar (trash.json), newest first.
+pub struct Trash;
+
+impl Trash {
+    pub fn get_path() -> Option<PathBuf> {
+        AppPaths::get_trash_path()
+    }
+
+    pub fn load() -> Result<Vec<TrashEntry>> {
+        let Some(path) = Self::get_path() else {
+            return Ok(vec![]);
+        };
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        LocalStorage::with_lock(&path, || Self::load_internal(&path))
+    }
+
+    fn load_internal(path: &Path) -> Result<Vec<TrashEntry>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let json = LocalStorage::read_data(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Transactional modification of the trash.
+    fn modify<F, T>(f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Vec<TrashEntry>) -> T,
+    {
+        let Some(path) = Self::get_path() else {
+            return Ok(f(&mut Vec::new()));
+        };
+        LocalStorage::with_lock(&path, || {
+            let mut entries = Self::load_internal(&path)?;
+            let result = f(&mut entries);
+            let json = serde_json::to_string_pretty(&entries)?;
+            LocalStorage::write_data(&path, json)?;
+            Ok(result)
+        })
+    }
+
+    /// Keeps a copy of a deleted task, dropping entries older than `days`.
+    /// `days == 0` disables the trash.
+    pub fn add(task: &Task, days: u32) -> Result<()> {
+        if days == 0 {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let cutoff = now - Duration::days(days as i64);
+        Self::modify(|entries| {
+            entries.retain(|e| e.task.uid != task.uid && e.deleted_at > cutoff);
+            entries.insert(
+                0,
+                TrashEntry {
+                    task: task.clone(),
+                    calendar_href: task.calendar_href.clone(),
+                    deleted_at: now,
+                },
+            );
+        })
+    }
+
+    /// Removes a task from the trash, returning it.
+    pub fn take(uid: &str) -> Result<Option<TrashEntry>> {
+        Self::modify(|entries| {
+            let pos = entries.iter().position(|e| e.task.uid == uid)?;
+            Some(entries.remove(pos))
+        })
+    }
 }
@@ -1,8 +1,14 @@
 // File: src/store.rs
 use crate::cache::Cache;
-use crate::model::{Task, TaskStatus};
-use chrono::{DateTime, Utc};
-use std::collections::{HashMap, HashSet};
+use crate::journal::Action;
+use crate::model::sort::manual_order_moves;
+use crate::model::{CalendarListEntry, CustomStatus, GroupBy, Query, SortOrder, Task, TaskStatus};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 
 pub const UNCATEGORIZED_ID: &str = ":::uncategorized:::";
 
@@ -11,6 +17,279 @@ pub struct TaskStore {
     pub calendars: HashMap<String, Vec<Task>>,
     /// Reverse index: Maps Task UID -> Calendar HREF for O(1) lookups
     pub index: HashMap<String, String>,
+    /// Reverse dependencies: Maps blocker UID -> UIDs of the tasks waiting on it
+    pub blocks_index: HashMap<String, Vec<String>>,
+    listeners: Listeners,
+}
+
+/// What changed in the store, sent to the subscribers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreEvent {
+    Added(Task),
+    Updated { old: Task, new: Task },
+    Removed(Task),
+    // Handed out with `get_task_mut`: the new value isn't known
+    Touched(String),
+    // A whole calendar was replaced, or everything (None) was cleared
+    Reloaded(Option<String>),
+}
+
+pub type StoreListener = Arc<dyn Fn(&StoreEvent) + Send + Sync>;
+
+#[derive(Default)]
+struct Listeners {
+    next_id: usize,
+    list: Vec<(usize, StoreListener)>,
+}
+
+// A copy of the store isn't observed: its changes would be reported twice
+impl Clone for Listeners {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} listener(s)", self.list.len())
+    }
+}
+
+/// Collects store events for the UIs, which look at them on their next refresh.
+#[derive(Clone, Default)]
+pub struct EventQueue(Arc<Mutex<Vec<StoreEvent>>>);
+
+impl EventQueue {
+    pub fn listener(&self) -> impl Fn(&StoreEvent) + Send + Sync + 'static {
+        let queue = self.0.clone();
+        move |event| {
+            if let Ok(mut q) = queue.lock() {
+                q.push(event.clone());
+            }
+        }
+    }
+
+    pub fn drain(&self) -> Vec<StoreEvent> {
+        self.0
+            .lock()
+            .map(|mut q| std::mem::take(&mut *q))
+            .unwrap_or_default()
+    }
+}
+
+/// Applies store events to a list built by `filter`, when none of them can
+/// change which tasks it shows or where. False when it has to be filtered again.
+pub fn patch_view(view: &mut [Task], events: &[StoreEvent]) -> bool {
+    let in_place = events.iter().all(|e| match e {
+        StoreEvent::Updated { old, new } => same_placement(old, new),
+        _ => false,
+    });
+    if !in_place {
+        return false;
+    }
+    for event in events {
+        if let StoreEvent::Updated { new, .. } = event
+            && let Some(shown) = view.iter_mut().find(|t| t.uid == new.uid)
+        {
+            let depth = shown.depth;
+            *shown = new.clone();
+            shown.depth = depth;
+        }
+    }
+    true
+}
+
+// Everything filtering, searching, sorting or grouping looks at
+fn same_placement(a: &Task, b: &Task) -> bool {
+    a.summary == b.summary
+        && a.description == b.description
+        && a.status == b.status
+        && a.custom_status == b.custom_status
+        && a.priority == b.priority
+        && a.due == b.due
+        && a.dtstart == b.dtstart
+        && a.created == b.created
+        && a.completed_at == b.completed_at
+        && a.estimated_duration == b.estimated_duration
+        && a.categories == b.categories
+        && a.contexts == b.contexts
+        && a.parent_uid == b.parent_uid
+        && a.dependencies == b.dependencies
+        && a.flagged == b.flagged
+        && a.calendar_href == b.calendar_href
+        && a.sort_order() == b.sort_order()
+}
+
+/// Search text starting with this is a filter expression (see model::query).
+pub const QUERY_PREFIX: char = ':';
+
+/// Why the search text isn't a valid filter expression, if it is one.
+pub fn query_error(search_term: &str) -> Option<String> {
+    Query::parse(search_term.strip_prefix(QUERY_PREFIX)?).err()
+}
+
+/// A section of a grouped list ("Today", "#work", ...).
+#[derive(Debug, Clone)]
+pub struct TaskGroup {
+    pub title: String,
+    pub tasks: Vec<Task>,
+}
+
+/// Splits a filtered list (see `TaskStore::filter`) into sections, keeping the
+/// order within each one. Subtasks stay under their top-level task.
+pub fn group_tasks(
+    tasks: Vec<Task>,
+    by: GroupBy,
+    calendars: &[CalendarListEntry],
+) -> Vec<TaskGroup> {
+    if by == GroupBy::None {
+        return vec![TaskGroup {
+            title: String::new(),
+            tasks,
+        }];
+    }
+    let today = Local::now().date_naive();
+    let mut groups: BTreeMap<(usize, String), Vec<Task>> = BTreeMap::new();
+    let mut current = None;
+    for task in tasks {
+        if task.depth == 0 || current.is_none() {
+            current = Some(by.key(&task, calendars, today));
+        }
+        if let Some(key) = &current {
+            groups.entry(key.clone()).or_default().push(task);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|((_, title), tasks)| TaskGroup { title, tasks })
+        .collect()
+}
+
+/// Title of the agenda's first section.
+pub const AGENDA_OVERDUE: &str = "Overdue";
+
+/// Agenda of the `days` days from `today`: open overdue tasks first, then a section
+/// per day. Up to a week every day is listed, longer spans only list the busy ones.
+/// Tasks without a due date, or due later, are left out.
+pub fn agenda_groups(tasks: Vec<Task>, today: NaiveDate, days: i64) -> Vec<TaskGroup> {
+    let mut overdue = Vec::new();
+    let mut by_day: BTreeMap<i64, Vec<Task>> = BTreeMap::new();
+    for mut task in tasks {
+        let Some(due) = task.due.map(|d| d.with_timezone(&Local).date_naive()) else {
+            continue;
+        };
+        // Days, not a tree
+        task.depth = 0;
+        let offset = (due - today).num_days();
+        if offset < 0 {
+            if !task.status.is_done() {
+                overdue.push(task);
+            }
+        } else if offset < days {
+            by_day.entry(offset).or_default().push(task);
+        }
+    }
+
+    let mut groups = Vec::new();
+    if !overdue.is_empty() {
+        groups.push(TaskGroup {
+            title: AGENDA_OVERDUE.to_string(),
+            tasks: overdue,
+        });
+    }
+    for offset in 0..days {
+        let tasks = by_day.remove(&offset).unwrap_or_default();
+        if tasks.is_empty() && offset > 0 && days > 7 {
+            continue;
+        }
+        let day = (today + Duration::days(offset)).format("%a %d %b");
+        let title = if offset == 0 {
+            format!("Today, {}", day)
+        } else {
+            day.to_string()
+        };
+        groups.push(TaskGroup { title, tasks });
+    }
+    groups
+}
+
+/// Columns of the kanban board, left to right.
+pub const BOARD_COLUMNS: [TaskStatus; 4] = [
+    TaskStatus::NeedsAction,
+    TaskStatus::InProcess,
+    TaskStatus::Completed,
+    TaskStatus::Cancelled,
+];
+
+/// One section per board column, empty ones included so the columns keep their place.
+pub fn board_groups(tasks: Vec<Task>) -> Vec<TaskGroup> {
+    let mut groups: Vec<TaskGroup> = BOARD_COLUMNS
+        .iter()
+        .map(|status| TaskGroup {
+            title: status.label().to_string(),
+            tasks: Vec::new(),
+        })
+        .collect();
+    for mut task in tasks {
+        // Columns, not a tree
+        task.depth = 0;
+        let col = BOARD_COLUMNS.iter().position(|s| *s == task.status);
+        groups[col.unwrap_or(0)].tasks.push(task);
+    }
+    groups
+}
+
+/// Back to a single list, for widgets that select rows by index, with the
+/// index where each titled section starts.
+pub fn flatten_groups(groups: Vec<TaskGroup>) -> (Vec<Task>, Vec<(usize, String)>) {
+    let mut tasks = Vec::new();
+    let mut headers = Vec::new();
+    for group in groups {
+        if !group.title.is_empty() {
+            headers.push((tasks.len(), group.title));
+        }
+        tasks.extend(group.tasks);
+    }
+    (tasks, headers)
+}
+
+/// Numbers for the statistics view and `cfait stats`.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub total: usize,
+    pub open: usize,
+    // Open tasks due before today
+    pub overdue: usize,
+    // Status name (custom ones included) -> count
+    pub by_status: BTreeMap<String, usize>,
+    // Open tasks per tag
+    pub by_tag: BTreeMap<String, usize>,
+    // (calendar name, open, done), in the order of the calendar list
+    pub by_calendar: Vec<(String, usize, usize)>,
+    // Oldest first, the current week last
+    pub weeks: Vec<WeekStats>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekStats {
+    // The Monday
+    pub start: NaiveDate,
+    pub created: usize,
+    pub completed: usize,
+    // Still open at the end of the week (the burndown line)
+    pub open: usize,
+}
+
+impl WeekStats {
+    /// Share of the week's workload (finished or left open) that got finished.
+    pub fn completion_rate(&self) -> f64 {
+        let workload = self.completed + self.open;
+        if workload == 0 {
+            0.0
+        } else {
+            self.completed as f64 / workload as f64
+        }
+    }
 }
 
 pub struct FilterOptions<'a> {
@@ -19,11 +298,97 @@ pub struct FilterOptions<'a> {
     pub selected_categories: &'a HashSet<String>,
     pub match_all_categories: bool,
     pub search_term: &'a str,
+    // Known calendars, so search text also matches their names
+    pub calendars: &'a [CalendarListEntry],
     pub hide_completed_global: bool,
+    // Calendar href -> its own setting over `hide_completed_global` (config `[calendars]`)
+    pub hide_completed_in: &'a HashMap<String, bool>,
     pub cutoff_date: Option<DateTime<Utc>>,
     pub min_duration: Option<u32>,
     pub max_duration: Option<u32>,
     pub include_unset_duration: bool,
+    pub sort_order: SortOrder,
+    // Only tasks with this context (@@home); independent from the tag filter
+    pub selected_context: Option<&'a str>,
+    // A smart list (Today, Flagged, ...) across every visible calendar
+    pub smart_list: Option<SmartList>,
+}
+
+/// Computed lists over every visible calendar, shown above the real ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SmartList {
+    // Due today
+    Today,
+    // Due in the 7 days after today
+    Upcoming,
+    // Due before today
+    Overdue,
+    Flagged,
+}
+
+impl SmartList {
+    pub const ALL: [SmartList; 4] = [
+        SmartList::Today,
+        SmartList::Upcoming,
+        SmartList::Overdue,
+        SmartList::Flagged,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SmartList::Today => "Today",
+            SmartList::Upcoming => "Upcoming",
+            SmartList::Overdue => "Overdue",
+            SmartList::Flagged => "Flagged",
+        }
+    }
+
+    /// Whether `task` belongs in the list. Finished tasks only show up as
+    /// flagged (the date lists are about what is left to do).
+    pub fn contains(&self, task: &Task, today: NaiveDate) -> bool {
+        let due = task
+            .due
+            .filter(|_| !task.status.is_done())
+            .map(|d| d.with_timezone(&Local).date_naive());
+        match self {
+            SmartList::Today => due == Some(today),
+            SmartList::Upcoming => due.is_some_and(|d| d > today && d <= today + Duration::days(7)),
+            SmartList::Overdue => due.is_some_and(|d| d < today),
+            SmartList::Flagged => task.flagged,
+        }
+    }
+}
+
+impl FilterOptions<'_> {
+    /// Changes when the options would select or order tasks differently
+    /// (the cutoff only counts by the day).
+    pub fn fingerprint(&self) -> u64 {
+        let sorted = |set: &HashSet<String>| {
+            let mut items: Vec<&String> = set.iter().collect();
+            items.sort();
+            items
+        };
+        let mut h = DefaultHasher::new();
+        self.active_cal_href.hash(&mut h);
+        sorted(self.hidden_calendars).hash(&mut h);
+        sorted(self.selected_categories).hash(&mut h);
+        self.match_all_categories.hash(&mut h);
+        self.search_term.hash(&mut h);
+        for cal in self.calendars {
+            (&cal.href, &cal.name).hash(&mut h);
+        }
+        self.hide_completed_global.hash(&mut h);
+        let mut overrides: Vec<_> = self.hide_completed_in.iter().collect();
+        overrides.sort();
+        overrides.hash(&mut h);
+        self.cutoff_date.map(|d| d.date_naive()).hash(&mut h);
+        (self.min_duration, self.max_duration).hash(&mut h);
+        self.include_unset_duration.hash(&mut h);
+        self.sort_order.hash(&mut h);
+        self.selected_context.hash(&mut h);
+        self.smart_list.hash(&mut h);
+        h.finish()
+    }
 }
 
 impl TaskStore {
@@ -31,16 +396,57 @@ impl TaskStore {
         Self::default()
     }
 
+    /// Calls `listener` after every change; returns an id for `unsubscribe`.
+    pub fn subscribe(&mut self, listener: impl Fn(&StoreEvent) + Send + Sync + 'static) -> usize {
+        let id = self.listeners.next_id;
+        self.listeners.next_id += 1;
+        self.listeners.list.push((id, Arc::new(listener)));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: usize) {
+        self.listeners.list.retain(|(i, _)| *i != id);
+    }
+
+    fn emit(&self, event: StoreEvent) {
+        for (_, listener) in &self.listeners.list {
+            listener(&event);
+        }
+    }
+
+    // Runs `change` on a task, reporting it if it returns true
+    fn modify(&mut self, uid: &str, change: impl FnOnce(&mut Task) -> bool) -> Option<Task> {
+        let (task, _) = self.task_mut(uid)?;
+        let old = task.clone();
+        if !change(task) {
+            return None;
+        }
+        let new = task.clone();
+        self.emit(StoreEvent::Updated {
+            old,
+            new: new.clone(),
+        });
+        Some(new)
+    }
+
     pub fn insert(&mut self, calendar_href: String, tasks: Vec<Task>) {
+        if let Some(old) = self.calendars.get(&calendar_href) {
+            let old_uids: HashSet<String> = old.iter().map(|t| t.uid.clone()).collect();
+            self.unlink_blocks(&old_uids);
+        }
         for task in &tasks {
             self.index.insert(task.uid.clone(), calendar_href.clone());
+            self.link_blocks(task);
         }
-        self.calendars.insert(calendar_href, tasks);
+        self.calendars.insert(calendar_href.clone(), tasks);
+        self.emit(StoreEvent::Reloaded(Some(calendar_href)));
     }
 
     pub fn add_task(&mut self, task: Task) {
         let href = task.calendar_href.clone();
         self.index.insert(task.uid.clone(), href.clone());
+        self.link_blocks(&task);
+        self.emit(StoreEvent::Added(task.clone()));
         self.calendars.entry(href).or_default().push(task);
     }
 
@@ -48,31 +454,68 @@ impl TaskStore {
     /// Maintains index and persists to cache.
     pub fn update_or_add_task(&mut self, task: Task) {
         let href = task.calendar_href.clone();
+        let key = Cache::key(task.account.as_deref(), &href);
 
         // Ensure index is up to date
         self.index.insert(task.uid.clone(), href.clone());
+        self.unlink_blocks(&HashSet::from([task.uid.clone()]));
+        self.link_blocks(&task);
 
         let list = self.calendars.entry(href.clone()).or_default();
 
-        if let Some(idx) = list.iter().position(|t| t.uid == task.uid) {
-            list[idx] = task;
+        let event = if let Some(idx) = list.iter().position(|t| t.uid == task.uid) {
+            let old = std::mem::replace(&mut list[idx], task.clone());
+            StoreEvent::Updated { old, new: task }
         } else {
-            list.push(task);
-        }
+            list.push(task.clone());
+            StoreEvent::Added(task)
+        };
 
         // Persist
-        let (_, token) = Cache::load(&href).unwrap_or((vec![], None));
-        let _ = Cache::save(&href, list, token);
+        let _ = Cache::save_tasks(&key, list);
+        self.emit(event);
     }
 
     pub fn clear(&mut self) {
         self.calendars.clear();
         self.index.clear();
+        self.blocks_index.clear();
+        self.emit(StoreEvent::Reloaded(None));
+    }
+
+    fn link_blocks(&mut self, task: &Task) {
+        for dep in &task.dependencies {
+            let dependents = self.blocks_index.entry(dep.clone()).or_default();
+            if !dependents.contains(&task.uid) {
+                dependents.push(task.uid.clone());
+            }
+        }
+    }
+
+    fn unlink_blocks(&mut self, uids: &HashSet<String>) {
+        for dependents in self.blocks_index.values_mut() {
+            dependents.retain(|u| !uids.contains(u));
+        }
+        self.blocks_index
+            .retain(|_, dependents| !dependents.is_empty());
+    }
+
+    /// UIDs of the tasks that depend on `uid` (the ones it blocks).
+    pub fn blocked_tasks(&self, uid: &str) -> &[String] {
+        self.blocks_index.get(uid).map(Vec::as_slice).unwrap_or(&[])
     }
 
     // --- Core Logic Helpers ---
 
+    /// The task, to edit in place. Subscribers only learn that it was touched.
     pub fn get_task_mut(&mut self, uid: &str) -> Option<(&mut Task, String)> {
+        if self.index.contains_key(uid) {
+            self.emit(StoreEvent::Touched(uid.to_string()));
+        }
+        self.task_mut(uid)
+    }
+
+    fn task_mut(&mut self, uid: &str) -> Option<(&mut Task, String)> {
         let href = self.index.get(uid)?.clone();
 
         if let Some(tasks) = self.calendars.get_mut(&href)
@@ -85,32 +528,121 @@ impl TaskStore {
         None
     }
 
+    fn get_task(&self, uid: &str) -> Option<&Task> {
+        let href = self.index.get(uid)?;
+        self.calendars.get(href)?.iter().find(|t| t.uid == uid)
+    }
+
+    fn summary_of(&self, uid: &str) -> String {
+        self.get_summary(uid).unwrap_or_else(|| uid.to_string())
+    }
+
+    /// Err if making `child_uid` a child of `parent_uid` would create a loop.
+    pub fn check_parent(&self, child_uid: &str, parent_uid: &str) -> Result<(), String> {
+        if child_uid == parent_uid {
+            return Err("A task can't be its own parent".to_string());
+        }
+        // Walk up from the new parent: meeting the child means it's an ancestor
+        let mut seen = HashSet::new();
+        let mut current = Some(parent_uid.to_string());
+        while let Some(uid) = current {
+            if uid == child_uid {
+                return Err(format!(
+                    "Can't move '{}' under '{}': it is one of its subtasks",
+                    self.summary_of(child_uid),
+                    self.summary_of(parent_uid)
+                ));
+            }
+            if !seen.insert(uid.clone()) {
+                break;
+            }
+            current = self.get_task(&uid).and_then(|t| t.parent_uid.clone());
+        }
+        Ok(())
+    }
+
+    /// Err if making `task_uid` depend on `dep_uid` would create a loop.
+    pub fn check_dependency(&self, task_uid: &str, dep_uid: &str) -> Result<(), String> {
+        if task_uid == dep_uid {
+            return Err("A task can't depend on itself".to_string());
+        }
+        // Follow everything dep_uid (transitively) waits on
+        let mut seen = HashSet::new();
+        let mut stack = vec![dep_uid.to_string()];
+        while let Some(uid) = stack.pop() {
+            if uid == task_uid {
+                return Err(format!(
+                    "Can't make '{}' depend on '{}': '{}' already depends on '{}'",
+                    self.summary_of(task_uid),
+                    self.summary_of(dep_uid),
+                    self.summary_of(dep_uid),
+                    self.summary_of(task_uid)
+                ));
+            }
+            if seen.insert(uid.clone())
+                && let Some(task) = self.get_task(&uid)
+            {
+                stack.extend(task.dependencies.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
     pub fn toggle_task(&mut self, uid: &str) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(uid) {
-            task.status = if task.status == TaskStatus::Completed {
+        self.modify(uid, |task| {
+            task.set_status(if task.status == TaskStatus::Completed {
                 TaskStatus::NeedsAction
             } else {
                 TaskStatus::Completed
-            };
-            return Some(task.clone());
-        }
-        None
+            });
+            true
+        })
     }
 
     pub fn set_status(&mut self, uid: &str, status: TaskStatus) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(uid) {
+        self.modify(uid, |task| {
             if task.status == status {
-                task.status = TaskStatus::NeedsAction;
+                task.set_status(TaskStatus::NeedsAction);
             } else {
-                task.status = status;
+                task.set_status(status);
             }
-            return Some(task.clone());
-        }
-        None
+            true
+        })
+    }
+
+    pub fn toggle_checklist_item(&mut self, uid: &str, index: usize) -> Option<Task> {
+        self.modify(uid, |task| task.toggle_checklist_item(index))
+    }
+
+    pub fn toggle_flag(&mut self, uid: &str) -> Option<Task> {
+        self.modify(uid, |task| {
+            task.flagged = !task.flagged;
+            true
+        })
+    }
+
+    /// Starts or stops the time tracking session of a task.
+    pub fn toggle_tracking(&mut self, uid: &str) -> Option<Task> {
+        self.modify(uid, |task| {
+            task.toggle_tracking(Utc::now());
+            true
+        })
+    }
+
+    /// Moves the task to the next status of `cycle`. UIs send a "Done" step
+    /// through `toggle_task` instead, so recurring tasks respawn.
+    pub fn cycle_status(&mut self, uid: &str, cycle: &[CustomStatus]) -> Option<Task> {
+        self.modify(uid, |task| match task.next_in_cycle(cycle) {
+            Some(next) => {
+                task.apply_status(next);
+                true
+            }
+            None => false,
+        })
     }
 
     pub fn change_priority(&mut self, uid: &str, delta: i8) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(uid) {
+        self.modify(uid, |task| {
             task.priority = if delta > 0 {
                 match task.priority {
                     0 => 9,
@@ -128,9 +660,21 @@ impl TaskStore {
                     _ => 0,
                 }
             };
-            return Some(task.clone());
+            true
+        })
+    }
+
+    /// Moves a task one step up/down in the manual order (X-APPLE-SORT-ORDER).
+    /// `view` is the list as shown. Returns the tasks whose position changed.
+    pub fn move_in_manual_order(&mut self, view: &[Task], uid: &str, up: bool) -> Vec<Task> {
+        let mut updated = Vec::new();
+        for (uid, position) in manual_order_moves(view, uid, up) {
+            updated.extend(self.modify(&uid, |task| {
+                task.set_sort_order(Some(position));
+                true
+            }));
         }
-        None
+        updated
     }
 
     pub fn delete_task(&mut self, uid: &str) -> Option<Task> {
@@ -141,39 +685,53 @@ impl TaskStore {
         {
             let task = tasks.remove(idx);
             self.index.remove(uid);
-            let (_, token) = Cache::load(&href).unwrap_or((vec![], None));
-            let _ = Cache::save(&href, tasks, token);
+            self.unlink_blocks(&HashSet::from([uid.to_string()]));
+            let _ = Cache::save_tasks(&Cache::key(task.account.as_deref(), &href), tasks);
+            self.emit(StoreEvent::Removed(task.clone()));
             return Some(task);
         }
         None
     }
 
     pub fn set_parent(&mut self, child_uid: &str, parent_uid: Option<String>) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(child_uid) {
-            task.parent_uid = parent_uid;
-            return Some(task.clone());
+        if let Some(p) = &parent_uid
+            && self.check_parent(child_uid, p).is_err()
+        {
+            return None;
         }
-        None
+        self.modify(child_uid, |task| {
+            task.parent_uid = parent_uid;
+            true
+        })
     }
 
     pub fn add_dependency(&mut self, task_uid: &str, dep_uid: String) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(task_uid)
-            && !task.dependencies.contains(&dep_uid)
-        {
-            task.dependencies.push(dep_uid);
-            return Some(task.clone());
+        if self.check_dependency(task_uid, &dep_uid).is_err() {
+            return None;
         }
-        None
+        let updated = self.modify(task_uid, |task| {
+            if task.dependencies.contains(&dep_uid) {
+                return false;
+            }
+            task.dependencies.push(dep_uid);
+            true
+        })?;
+        self.link_blocks(&updated);
+        Some(updated)
     }
 
     pub fn remove_dependency(&mut self, task_uid: &str, dep_uid: &str) -> Option<Task> {
-        if let Some((task, _)) = self.get_task_mut(task_uid)
-            && let Some(pos) = task.dependencies.iter().position(|d| d == dep_uid)
-        {
+        let updated = self.modify(task_uid, |task| {
+            let Some(pos) = task.dependencies.iter().position(|d| d == dep_uid) else {
+                return false;
+            };
             task.dependencies.remove(pos);
-            return Some(task.clone());
+            true
+        })?;
+        if let Some(dependents) = self.blocks_index.get_mut(dep_uid) {
+            dependents.retain(|u| *u != updated.uid);
         }
-        None
+        Some(updated)
     }
 
     pub fn move_task(&mut self, uid: &str, target_href: String) -> Option<Task> {
@@ -189,8 +747,8 @@ impl TaskStore {
             self.add_task(task.clone());
 
             if let Some(target_list) = self.calendars.get(&target_href) {
-                let (_, token) = Cache::load(&target_href).unwrap_or((vec![], None));
-                let _ = Cache::save(&target_href, target_list, token);
+                let key = Cache::key(task.account.as_deref(), &target_href);
+                let _ = Cache::save_tasks(&key, target_list);
             }
 
             return Some(task);
@@ -198,6 +756,19 @@ impl TaskStore {
         None
     }
 
+    /// Mirrors a queued action (an undo, see `Journal::revert_last`) in the store.
+    pub fn apply_action(&mut self, action: &Action) {
+        match action {
+            Action::Create(t) | Action::Update(t) => self.update_or_add_task(t.clone()),
+            Action::Delete(t) => {
+                self.delete_task(&t.uid);
+            }
+            Action::Move(t, dest) => {
+                self.move_task(&t.uid, dest.clone());
+            }
+        }
+    }
+
     // --- NEW SHARED LOGIC ---
 
     /// Finds all tasks tagged with `alias_key` that are missing one or more of `target_tags`.
@@ -228,7 +799,7 @@ impl TaskStore {
         // 2. Modify (In Memory Only)
         let mut modified_tasks = Vec::new();
         for uid in uids_to_update {
-            if let Some((task, _)) = self.get_task_mut(&uid) {
+            let modified = self.modify(&uid, |task| {
                 for target_tag in target_tags {
                     if !task.categories.contains(target_tag) {
                         task.categories.push(target_tag.clone());
@@ -236,10 +807,10 @@ impl TaskStore {
                 }
                 task.categories.sort();
                 task.categories.dedup();
-
-                // Track for return
-                modified_tasks.push(task.clone());
-            }
+                true
+            });
+            // Track for return
+            modified_tasks.extend(modified);
         }
 
         // REMOVED: 3. Persist to Disk (Cache)
@@ -253,6 +824,27 @@ impl TaskStore {
 
     // --- Read/Filter Logic ---
 
+    /// Contexts of the visible calendars, with their number of open tasks.
+    pub fn get_all_contexts(&self, hidden_calendars: &HashSet<String>) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (href, tasks) in &self.calendars {
+            if hidden_calendars.contains(href) {
+                continue;
+            }
+            for task in tasks {
+                for ctx in &task.contexts {
+                    let count = counts.entry(ctx.clone()).or_insert(0);
+                    if !task.status.is_done() {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+        let mut contexts: Vec<_> = counts.into_iter().collect();
+        contexts.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+        contexts
+    }
+
     pub fn get_all_categories(
         &self,
         _hide_completed: bool,
@@ -349,10 +941,125 @@ impl TaskStore {
         count
     }
 
+    /// Open tasks of a smart list in the visible calendars, for its counter.
+    pub fn count_smart_list(&self, list: SmartList, hidden_calendars: &HashSet<String>) -> usize {
+        let today = Local::now().date_naive();
+        self.calendars
+            .iter()
+            .filter(|(href, _)| !hidden_calendars.contains(*href))
+            .flat_map(|(_, tasks)| tasks)
+            .filter(|t| !t.status.is_done() && list.contains(t, today))
+            .count()
+    }
+
+    /// Counts over the visible calendars, with the last `weeks` weeks of activity.
+    pub fn stats(
+        &self,
+        hidden_calendars: &HashSet<String>,
+        calendars: &[CalendarListEntry],
+        weeks: usize,
+    ) -> Stats {
+        let today = Local::now().date_naive();
+        let local_date = |dt: DateTime<Utc>| dt.with_timezone(&Local).date_naive();
+        let this_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let mut stats = Stats {
+            weeks: (0..weeks)
+                .rev()
+                .map(|i| WeekStats {
+                    start: this_monday - Duration::weeks(i as i64),
+                    created: 0,
+                    completed: 0,
+                    open: 0,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut hrefs: Vec<&String> = self
+            .calendars
+            .keys()
+            .filter(|href| !hidden_calendars.contains(*href))
+            .collect();
+        // Calendar list order, unknown ones last
+        hrefs.sort_by_key(|href| {
+            calendars
+                .iter()
+                .position(|c| &c.href == *href)
+                .unwrap_or(usize::MAX)
+        });
+
+        for href in hrefs {
+            let tasks = &self.calendars[href];
+            let name = calendars
+                .iter()
+                .find(|c| &c.href == href)
+                .map_or(href.as_str(), |c| c.name.as_str());
+            let done = tasks.iter().filter(|t| t.status.is_done()).count();
+            stats
+                .by_calendar
+                .push((name.to_string(), tasks.len() - done, done));
+
+            for t in tasks {
+                stats.total += 1;
+                *stats
+                    .by_status
+                    .entry(t.status_name().to_string())
+                    .or_default() += 1;
+                if !t.status.is_done() {
+                    stats.open += 1;
+                    if t.due.is_some_and(|d| local_date(d) < today) {
+                        stats.overdue += 1;
+                    }
+                    for cat in &t.categories {
+                        *stats.by_tag.entry(cat.clone()).or_default() += 1;
+                    }
+                }
+
+                let created = t.created.map(local_date);
+                // Done without a date (e.g. cancelled elsewhere): left out of the burndown
+                let finished = match (t.status.is_done(), t.completed_at) {
+                    (false, _) => None,
+                    (true, Some(at)) => Some(Some(local_date(at))),
+                    (true, None) => Some(None),
+                };
+                for week in &mut stats.weeks {
+                    let end = week.start + Duration::days(6);
+                    if created.is_some_and(|d| d >= week.start && d <= end) {
+                        week.created += 1;
+                    }
+                    if let Some(Some(d)) = finished
+                        && d >= week.start
+                        && d <= end
+                    {
+                        week.completed += 1;
+                    }
+                    let existed = created.is_none_or(|d| d <= end);
+                    let open_then = match finished {
+                        None => true,
+                        Some(Some(d)) => d > end,
+                        Some(None) => false,
+                    };
+                    if existed && open_then {
+                        week.open += 1;
+                    }
+                }
+            }
+        }
+        stats
+    }
+
     pub fn filter(&self, options: FilterOptions) -> Vec<Task> {
+        self.filter_counting_hidden(options).0
+    }
+
+    /// Same as `filter`, plus how many tasks were left out only for being done
+    /// (hide_completed), for a "N hidden" hint.
+    pub fn filter_counting_hidden(&self, options: FilterOptions) -> (Vec<Task>, usize) {
         let mut raw_tasks = Vec::new();
 
-        if let Some(href) = options.active_cal_href {
+        if let Some(href) = options.active_cal_href
+            && options.smart_list.is_none()
+        {
             if !options.hidden_calendars.contains(href)
                 && let Some(tasks) = self.calendars.get(href)
             {
@@ -366,18 +1073,30 @@ impl TaskStore {
             }
         }
 
+        let query = match options.search_term.strip_prefix(QUERY_PREFIX) {
+            Some(expr) => match Query::parse(expr) {
+                Ok(q) => Some(q),
+                // Nothing until it's fixed, see query_error
+                Err(_) => return (Vec::new(), 0),
+            },
+            None => None,
+        };
+
+        let search_lower = options.search_term.to_lowercase();
+        let has_status_filter = match &query {
+            Some(q) => q.mentions_status(),
+            None => {
+                search_lower.contains("is:done")
+                    || search_lower.contains("is:active")
+                    || search_lower.contains("is:ongoing")
+            }
+        };
+
+        let today = Local::now().date_naive();
+        let mut hidden_done = 0;
         let filtered: Vec<Task> = raw_tasks
             .into_iter()
             .filter(|t| {
-                let search_lower = options.search_term.to_lowercase();
-                let has_status_filter = search_lower.contains("is:done")
-                    || search_lower.contains("is:active")
-                    || search_lower.contains("is:ongoing");
-
-                if !has_status_filter && t.status.is_done() && options.hide_completed_global {
-                    return false;
-                }
-
                 match t.estimated_duration {
                     Some(mins) => {
                         if let Some(min) = options.min_duration
@@ -398,6 +1117,17 @@ impl TaskStore {
                     }
                 }
 
+                if let Some(ctx) = options.selected_context
+                    && !t.contexts.iter().any(|c| c == ctx)
+                {
+                    return false;
+                }
+                if let Some(list) = options.smart_list
+                    && !list.contains(t, today)
+                {
+                    return false;
+                }
+
                 if !options.selected_categories.is_empty() {
                     let filter_uncategorized =
                         options.selected_categories.contains(UNCATEGORIZED_ID);
@@ -457,13 +1187,36 @@ impl TaskStore {
                 }
 
                 if !options.search_term.is_empty() {
-                    return t.matches_search_term(options.search_term);
+                    let calendar_name = options
+                        .calendars
+                        .iter()
+                        .find(|c| c.href == t.calendar_href)
+                        .map(|c| c.name.as_str());
+                    let found = match &query {
+                        Some(q) => q.matches(t, calendar_name),
+                        None => t.matches_search_in(options.search_term, calendar_name),
+                    };
+                    if !found {
+                        return false;
+                    }
+                }
+
+                // Last, so only tasks that would be shown otherwise count as hidden
+                let hide_completed = options
+                    .hide_completed_in
+                    .get(&t.calendar_href)
+                    .copied()
+                    .unwrap_or(options.hide_completed_global);
+                if !has_status_filter && t.status.is_done() && hide_completed {
+                    hidden_done += 1;
+                    return false;
                 }
                 true
             })
             .collect();
 
-        Task::organize_hierarchy(filtered, options.cutoff_date)
+        let tasks = Task::organize_hierarchy(filtered, options.cutoff_date, options.sort_order);
+        (tasks, hidden_done)
     }
 
     pub fn is_task_done(&self, uid: &str) -> Option<bool> {
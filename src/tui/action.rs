@@ -1,3 +1,5 @@
+use crate::client::quota::Quota;
+use crate::journal::{Action as JournalAction, Conflict, Resolution};
 use crate::model::{CalendarListEntry, Task};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,11 +21,20 @@ pub enum Action {
     DeleteTask(Task),
     Refresh,
     Quit,
-    MoveTask(Task, String),   // Task, New Calendar Href
-    StartCreateChild(String), // Parent Task UID
-    MigrateLocal(String),     // target_href
+    MoveTask(Task, String),       // Task, New Calendar Href
+    StartCreateChild(String),     // Parent Task UID
+    MigrateLocal(String, String), // Local calendar href, target_href
     ToggleCalendarVisibility(String),
     IsolateCalendar(String),
+    // Tasks of all these calendars, for the "All" view
+    FetchAll(Vec<CalendarListEntry>),
+    // Changes to the marked tasks, sent together, and the message once done
+    Batch(Vec<JournalAction>, String),
+    Undo,
+    SyncNow,
+    ToggleOffline,
+    Undelete(String, String), // Task UID, calendar it was deleted from
+    ResolveConflict(String, String, Resolution), // Calendar href, Task UID, side to keep
 }
 
 #[derive(Debug)]
@@ -32,4 +43,12 @@ pub enum AppEvent {
     TasksLoaded(Vec<(String, Vec<Task>)>),
     Error(String),
     Status(String),
+    OfflineChanged(bool),
+    // After every action: whether the queued changes could reach the server
+    Reachable(bool),
+    QuotaLoaded(Quota),
+    // Conflicts waiting for the user (conflict_policy = "interactive")
+    Conflicts(Vec<Conflict>),
+    // Queued inverse of an undone change, to mirror in the task list
+    Undone(JournalAction),
 }
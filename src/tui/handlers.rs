@@ -1,20 +1,51 @@
 // File: src/tui/handlers.rs
-use crate::config::Config;
-use crate::model::{Task, TaskStatus, extract_inline_aliases};
-use crate::storage::LOCAL_CALENDAR_HREF;
+use crate::config::{CalendarPrefs, Config};
+use crate::journal::{Action as JournalAction, Journal, Resolution};
+use crate::model::recurrence::{ByDay, Until};
+use crate::model::{
+    CalendarListEntry, Freq, Recurrence, SortOrder, Task, TaskField, TaskStatus,
+    extract_inline_aliases, insert_alias,
+};
+use crate::storage::{LOCAL_CALENDAR_HREF, Trash, is_local_calendar};
+use crate::store::{BOARD_COLUMNS, SmartList};
 use crate::tui::action::{Action, AppEvent, SidebarMode};
-use crate::tui::state::{AppState, Focus, InputMode};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::tui::keymap;
+use crate::tui::state::{AppState, Focus, InputMode, RecurrenceStep};
+use chrono::{Days, Local, Months, NaiveDate};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
 use tokio::sync::mpsc::Sender;
 
 pub fn handle_app_event(state: &mut AppState, event: AppEvent, default_cal: &Option<String>) {
     match event {
-        AppEvent::Status(s) => state.message = s,
+        AppEvent::Status(s) => {
+            state.message = s;
+            state.refresh_journal_status();
+        }
+        AppEvent::OfflineChanged(offline) => state.offline = offline,
+        AppEvent::Reachable(reachable) => {
+            state.unreachable = !reachable;
+            state.refresh_journal_status();
+        }
+        AppEvent::QuotaLoaded(quota) => state.quota = Some(quota),
+        AppEvent::Undone(action) => {
+            state.store.apply_action(&action);
+            state.refresh_filtered_view();
+            state.refresh_journal_status();
+        }
+        AppEvent::Conflicts(conflicts) => {
+            // Don't ask again about conflicts put off with Esc
+            if conflicts != state.conflicts && state.mode == InputMode::Normal {
+                state.mode = InputMode::Conflict;
+            }
+            state.conflicts = conflicts;
+        }
         AppEvent::Error(s) => {
             state.message = format!("Error: {}", s);
             state.loading = false;
         }
-        AppEvent::CalendarsLoaded(cals) => {
+        AppEvent::CalendarsLoaded(mut cals) => {
+            CalendarPrefs::apply_colors(&state.calendar_prefs, &mut cals);
             state.calendars = cals;
 
             // Unhide default calendar on load
@@ -50,30 +81,56 @@ pub async fn handle_key_event(
     state: &mut AppState,
     action_tx: &Sender<Action>,
 ) -> Option<Action> {
+    // The help popup takes the keys until closed
+    if state.show_help {
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => state.help_scroll += 1,
+            KeyCode::Up | KeyCode::Char('k') => {
+                state.help_scroll = state.help_scroll.saturating_sub(1)
+            }
+            KeyCode::PageDown => state.help_scroll += 10,
+            KeyCode::PageUp => state.help_scroll = state.help_scroll.saturating_sub(10),
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => state.show_help = false,
+            _ => {}
+        }
+        return None;
+    }
+
+    // Keys that modify the selected task (see `keymap`) are refused on read-only
+    // calendars (bulk actions skip the read-only tasks instead)
+    if state.mode == InputMode::Normal
+        && state.marked.is_empty()
+        && let KeyCode::Char(c) = key.code
+        && keymap::edits_selected(c, state.board, state.active_focus == Focus::Sidebar)
+        && let Some(task) = state.get_selected_task()
+        && let Some(name) = state.read_only_calendar(&task.calendar_href)
+    {
+        state.message = format!("Calendar '{}' is read-only", name);
+        return None;
+    }
+
+    // The date picker takes the keys until a day is picked
+    if let Some(day) = state.date_picker {
+        handle_date_picker(state, key.code, day);
+        return None;
+    }
+    if key.code == KeyCode::Char('d')
+        && key.modifiers.contains(KeyModifiers::CONTROL)
+        && matches!(
+            state.mode,
+            InputMode::Creating | InputMode::Editing | InputMode::EditingFields
+        )
+    {
+        open_date_picker(state);
+        return None;
+    }
+
     match state.mode {
         InputMode::Creating => match key.code {
             KeyCode::Enter if !state.input_buffer.is_empty() => {
                 // --- 1. Extract Inline Aliases ---
                 let (clean_input, new_aliases) = extract_inline_aliases(&state.input_buffer);
-
-                if !new_aliases.is_empty() {
-                    for (key, tags) in new_aliases {
-                        state.tag_aliases.insert(key.clone(), tags.clone());
-
-                        // Shared Logic: Retroactive Update
-                        let modified = state.store.apply_alias_retroactively(&key, &tags);
-
-                        // Dispatch Updates
-                        for t in modified {
-                            let _ = action_tx.send(Action::UpdateTask(t)).await;
-                        }
-                    }
-                    // Persist Aliases
-                    if let Ok(mut cfg) = Config::load() {
-                        cfg.tag_aliases = state.tag_aliases.clone();
-                        let _ = cfg.save();
-                    }
-                }
+                let aliases_ok = register_aliases(state, new_aliases, action_tx).await;
 
                 // --- 2. Existing Logic with Clean Input ---
 
@@ -97,7 +154,9 @@ pub async fn handle_key_event(
                     } else {
                         state.mode = InputMode::Normal;
                         state.reset_input();
-                        state.message = "Alias updated.".to_string();
+                        if aliases_ok {
+                            state.message = "Alias updated.".to_string();
+                        }
                         return None;
                     }
                 }
@@ -107,18 +166,53 @@ pub async fn handle_key_event(
                     .clone()
                     .or_else(|| state.calendars.first().map(|c| c.href.clone()));
 
-                if let Some(href) = target_href {
-                    let mut task = Task::new(&clean_input, &state.tag_aliases);
-                    task.calendar_href = href.clone();
-                    task.parent_uid = state.creating_child_of.clone();
+                if let Some(name) = target_href
+                    .as_deref()
+                    .and_then(|h| state.read_only_calendar(h))
+                {
+                    state.mode = InputMode::Normal;
+                    state.reset_input();
+                    state.message = format!("Calendar '{}' is read-only", name);
+                    return None;
+                }
 
-                    state.store.add_task(task.clone());
+                if let Some(href) = target_href {
+                    // "Parent > child > child" creates the whole family at once
+                    let mut tasks = Task::new_with_subtasks(&clean_input, &state.tag_aliases);
+                    let warning = tasks
+                        .iter()
+                        .find_map(|t| t.smart_input_warning(&clean_input));
+                    if !confirm_warning(state, warning) {
+                        return None;
+                    }
+                    if let Some(first) = tasks.first_mut() {
+                        first.parent_uid = state.creating_child_of.clone();
+                    }
+                    let prefs = CalendarPrefs::find(&state.calendar_prefs, &state.calendars, &href);
+                    let account = state
+                        .calendars
+                        .iter()
+                        .find(|c| c.href == href)
+                        .and_then(|c| c.account.clone());
+                    for task in &mut tasks {
+                        task.calendar_href = href.clone();
+                        task.account = account.clone();
+                        if let Some(prefs) = prefs {
+                            prefs.tag_new_task(task, &state.tag_aliases);
+                        }
+                        state.store.add_task(task.clone());
+                    }
                     state.refresh_filtered_view();
 
                     state.mode = InputMode::Normal;
                     state.reset_input();
                     state.creating_child_of = None;
-                    return Some(Action::CreateTask(task));
+
+                    // Parent first, so it exists before its children point to it
+                    for task in tasks {
+                        let _ = action_tx.send(Action::CreateTask(task)).await;
+                    }
+                    return None;
                 }
                 state.mode = InputMode::Normal;
                 state.reset_input();
@@ -136,29 +230,22 @@ pub async fn handle_key_event(
         InputMode::Editing => match key.code {
             KeyCode::Enter => {
                 let (clean_input, new_aliases) = extract_inline_aliases(&state.input_buffer);
-                if !new_aliases.is_empty() {
-                    for (k, v) in new_aliases {
-                        state.tag_aliases.insert(k.clone(), v.clone());
-                        let modified = state.store.apply_alias_retroactively(&k, &v);
-                        for mod_t in modified {
-                            let _ = action_tx.send(Action::UpdateTask(mod_t)).await;
-                        }
-                    }
-                    if let Ok(mut cfg) = Config::load() {
-                        cfg.tag_aliases = state.tag_aliases.clone();
-                        let _ = cfg.save();
-                    }
-                }
+                register_aliases(state, new_aliases, action_tx).await;
 
                 let target_uid = state
                     .editing_index
                     .and_then(|idx| state.tasks.get(idx).map(|t| t.uid.clone()));
 
                 if let Some(uid) = target_uid
-                    && let Some((t, _)) = state.store.get_task_mut(&uid)
+                    && let Some(mut clone) = state.store.get_task_mut(&uid).map(|(t, _)| t.clone())
                 {
-                    t.apply_smart_input(&clean_input, &state.tag_aliases);
-                    let clone = t.clone();
+                    clone.apply_smart_input(&clean_input, &state.tag_aliases);
+                    if !confirm_warning(state, clone.smart_input_warning(&clean_input)) {
+                        return None;
+                    }
+                    if let Some((t, _)) = state.store.get_task_mut(&uid) {
+                        *t = clone.clone();
+                    }
                     state.refresh_filtered_view();
                     state.mode = InputMode::Normal;
                     state.reset_input();
@@ -178,10 +265,8 @@ pub async fn handle_key_event(
         },
         InputMode::EditingDescription => match key.code {
             KeyCode::Enter => {
-                if key.modifiers.contains(crossterm::event::KeyModifiers::ALT)
-                    || key
-                        .modifiers
-                        .contains(crossterm::event::KeyModifiers::SHIFT)
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    || key.modifiers.contains(KeyModifiers::SHIFT)
                 {
                     state.enter_char('\n');
                 } else {
@@ -214,12 +299,48 @@ pub async fn handle_key_event(
             _ => {}
         },
         InputMode::Normal => match key.code {
-            KeyCode::Char('?') => state.show_full_help = !state.show_full_help,
+            KeyCode::Char('?') => {
+                state.show_help = true;
+                state.help_scroll = 0;
+            }
             KeyCode::Char('q') => return Some(Action::Quit),
             KeyCode::Char('r') => return Some(Action::Refresh),
+            KeyCode::Char('u') => {
+                state.message = "Undoing...".to_string();
+                return Some(Action::Undo);
+            }
+            KeyCode::Char('S') => return Some(Action::SyncNow),
+            KeyCode::Char('O') => return Some(Action::ToggleOffline),
+
+            // Board: h/l move the task to the next column, j/k stay in the column
+            KeyCode::Char('h') | KeyCode::Char('l')
+                if state.board && state.active_focus == Focus::Main =>
+            {
+                return move_on_board(state, key.code == KeyCode::Char('l'));
+            }
+            KeyCode::Left | KeyCode::Right if state.board && state.active_focus == Focus::Main => {
+                state.board_jump(key.code == KeyCode::Right);
+            }
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Up | KeyCode::Char('k')
+                if state.board && state.active_focus == Focus::Main =>
+            {
+                state.board_step(matches!(key.code, KeyCode::Down | KeyCode::Char('j')));
+            }
+            KeyCode::Char('B') => {
+                state.board = !state.board;
+                state.agenda_days = None;
+                state.refresh_filtered_view();
+                state.message = if state.board {
+                    "Board: h/l move the task, Left/Right switch column".to_string()
+                } else {
+                    "Task list".to_string()
+                };
+            }
 
             KeyCode::Char(' ') => {
-                if state.active_focus == Focus::Main {
+                if state.active_focus == Focus::Main && !state.marked.is_empty() {
+                    return bulk_toggle(state);
+                } else if state.active_focus == Focus::Main {
                     if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
                         && let Some(updated) = state.store.toggle_task(&uid)
                     {
@@ -229,14 +350,7 @@ pub async fn handle_key_event(
                 } else if state.active_focus == Focus::Sidebar
                     && state.sidebar_mode == SidebarMode::Calendars
                 {
-                    let target_cal = if let Some(idx) = state.cal_state.selected() {
-                        let filtered = state.get_filtered_calendars();
-                        filtered.get(idx).map(|c| c.href.clone())
-                    } else {
-                        None
-                    };
-
-                    if let Some(href) = target_cal
+                    if let Some(href) = state.selected_sidebar_calendar()
                         && state.active_cal_href.as_ref() != Some(&href)
                     {
                         if state.hidden_calendars.contains(&href) {
@@ -265,6 +379,74 @@ pub async fn handle_key_event(
                     return Some(Action::MarkCancelled(updated));
                 }
             }
+            // Checklist: check the next open item / uncheck the last checked one
+            KeyCode::Char('l') | KeyCode::Char('L') => {
+                let check = key.code == KeyCode::Char('l');
+                let target = state.get_selected_task().and_then(|t| {
+                    let items = t.checklist();
+                    let idx = if check {
+                        items.iter().position(|i| !i.done)
+                    } else {
+                        items.iter().rposition(|i| i.done)
+                    };
+                    idx.map(|i| (t.uid.clone(), i))
+                });
+                if let Some((uid, idx)) = target
+                    && let Some(updated) = state.store.toggle_checklist_item(&uid, idx)
+                {
+                    state.refresh_filtered_view();
+                    return Some(Action::UpdateTask(updated));
+                }
+            }
+            KeyCode::Char('f') => {
+                if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
+                    && let Some(updated) = state.store.toggle_flag(&uid)
+                {
+                    state.refresh_filtered_view();
+                    return Some(Action::UpdateTask(updated));
+                }
+            }
+            KeyCode::Char('F') => {
+                state.smart_list = if state.smart_list == Some(SmartList::Flagged) {
+                    None
+                } else {
+                    Some(SmartList::Flagged)
+                };
+                state.refresh_filtered_view();
+                state.message = if state.smart_list.is_some() {
+                    "Flagged tasks (F to go back)".to_string()
+                } else {
+                    String::new()
+                };
+            }
+            KeyCode::Char('t') => {
+                if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
+                    && let Some(updated) = state.store.toggle_tracking(&uid)
+                {
+                    state.refresh_filtered_view();
+                    return Some(Action::UpdateTask(updated));
+                }
+            }
+            KeyCode::Char('w') => {
+                if let Some(task) = state.get_selected_task() {
+                    let uid = task.uid.clone();
+                    let completes = task
+                        .next_in_cycle(&state.status_cycle)
+                        .is_some_and(|s| s.is_standard() && s.base == TaskStatus::Completed);
+                    // Done the usual way, so recurring tasks respawn
+                    if completes {
+                        if let Some(updated) = state.store.toggle_task(&uid) {
+                            state.refresh_filtered_view();
+                            return Some(Action::ToggleTask(updated));
+                        }
+                    } else if let Some(updated) =
+                        state.store.cycle_status(&uid, &state.status_cycle)
+                    {
+                        state.refresh_filtered_view();
+                        return Some(Action::UpdateTask(updated));
+                    }
+                }
+            }
             KeyCode::Char('+') => {
                 if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
                     && let Some(updated) = state.store.change_priority(&uid, 1)
@@ -281,12 +463,42 @@ pub async fn handle_key_event(
                     return Some(Action::UpdateTask(updated));
                 }
             }
-            KeyCode::Char('d') => {
-                if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
-                    && let Some(deleted) = state.store.delete_task(&uid)
+            KeyCode::Char('K') | KeyCode::Char('J') => {
+                // Manual order (X-APPLE-SORT-ORDER), shared with Apple Reminders/Tasks.org
+                if state.active_focus == Focus::Main
+                    && let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
                 {
+                    let up = key.code == KeyCode::Char('K');
+                    let updated = state.store.move_in_manual_order(&state.tasks, &uid, up);
+                    if state.effective_sort_order() != SortOrder::Manual {
+                        set_sort_order(state, SortOrder::Manual);
+                    }
                     state.refresh_filtered_view();
-                    return Some(Action::DeleteTask(deleted));
+                    // Follow the moved task
+                    if let Some(idx) = state.tasks.iter().position(|t| t.uid == uid) {
+                        state.list_state.select(Some(idx));
+                    }
+                    for task in updated {
+                        let _ = action_tx.send(Action::UpdateTask(task)).await;
+                    }
+                }
+            }
+            KeyCode::Char('d') if !state.marked.is_empty() => {
+                if !state.confirm_delete {
+                    return bulk_delete(state);
+                }
+                state.deleting_uid = None;
+                state.mode = InputMode::ConfirmDelete;
+                state.message = "y: delete, n: keep".to_string();
+            }
+            KeyCode::Char('d') => {
+                if let Some(uid) = state.get_selected_task().map(|t| t.uid.clone()) {
+                    if !state.confirm_delete {
+                        return delete_task(state, &uid);
+                    }
+                    state.deleting_uid = Some(uid);
+                    state.mode = InputMode::ConfirmDelete;
+                    state.message = "y: delete, n: keep".to_string();
                 }
             }
             KeyCode::Char('c') => {
@@ -299,8 +511,8 @@ pub async fn handle_key_event(
                 };
 
                 if let Some((child_uid, parent_uid)) = data {
-                    if child_uid == parent_uid {
-                        state.message = "Cannot be child of self!".to_string();
+                    if let Err(e) = state.store.check_parent(&child_uid, &parent_uid) {
+                        state.message = e;
                     } else if let Some(updated) =
                         state.store.set_parent(&child_uid, Some(parent_uid))
                     {
@@ -349,8 +561,8 @@ pub async fn handle_key_event(
                 };
 
                 if let Some((curr_uid, yanked_uid)) = data {
-                    if curr_uid == yanked_uid {
-                        state.message = "Cannot depend on self!".to_string();
+                    if let Err(e) = state.store.check_dependency(&curr_uid, &yanked_uid) {
+                        state.message = e;
                     } else if let Some(updated) = state.store.add_dependency(&curr_uid, yanked_uid)
                     {
                         state.yanked_uid = None; // Auto-unlink after action
@@ -367,7 +579,11 @@ pub async fn handle_key_event(
                 {
                     let parent_uid = state.tasks[idx - 1].uid.clone();
                     let current_uid = state.tasks[idx].uid.clone();
-                    if let Some(updated) = state.store.set_parent(&current_uid, Some(parent_uid)) {
+                    if let Err(e) = state.store.check_parent(&current_uid, &parent_uid) {
+                        state.message = e;
+                    } else if let Some(updated) =
+                        state.store.set_parent(&current_uid, Some(parent_uid))
+                    {
                         state.refresh_filtered_view();
                         return Some(Action::UpdateTask(updated));
                     }
@@ -386,12 +602,17 @@ pub async fn handle_key_event(
                 }
             }
             KeyCode::Char('X') => {
-                if state.active_cal_href.as_deref() == Some(LOCAL_CALENDAR_HREF) {
+                if state
+                    .active_cal_href
+                    .as_deref()
+                    .is_some_and(is_local_calendar)
+                {
                     state.export_targets = state
                         .calendars
                         .iter()
                         .filter(|c| {
-                            c.href != LOCAL_CALENDAR_HREF
+                            !is_local_calendar(&c.href)
+                                && !c.read_only
                                 && !state.disabled_calendars.contains(&c.href)
                         })
                         .cloned()
@@ -404,12 +625,15 @@ pub async fn handle_key_event(
             }
             KeyCode::Char('M') => {
                 if let Some(task) = state.get_selected_task() {
-                    let current_href = task.calendar_href.clone();
+                    // Marked tasks can come from several calendars: all are offered
+                    let current_href = state.marked.is_empty().then(|| task.calendar_href.clone());
                     state.move_targets = state
                         .calendars
                         .iter()
                         .filter(|c| {
-                            c.href != current_href && !state.disabled_calendars.contains(&c.href)
+                            current_href.as_ref() != Some(&c.href)
+                                && !c.read_only
+                                && !state.disabled_calendars.contains(&c.href)
                         })
                         .cloned()
                         .collect();
@@ -420,6 +644,28 @@ pub async fn handle_key_event(
                     }
                 }
             }
+            KeyCode::Char('T') => {
+                state.trash_entries = Trash::load().unwrap_or_default();
+                if state.trash_entries.is_empty() {
+                    state.message = "Trash is empty.".to_string();
+                } else {
+                    state.trash_selection_state.select(Some(0));
+                    state.mode = InputMode::Trash;
+                    state.message = "Enter: restore the task, Esc: close.".to_string();
+                }
+            }
+            KeyCode::Char('P') => {
+                state.reload_pending();
+                if state.pending_len() == 0 {
+                    state.message = "No pending changes.".to_string();
+                } else {
+                    state.pending_selection_state.select(Some(0));
+                    state.mode = InputMode::Pending;
+                    state.message =
+                        "d: drop the change, K/J: move up/down, r: retry a failed one, Esc: close."
+                            .to_string();
+                }
+            }
             KeyCode::Down | KeyCode::Char('j') => state.next(),
             KeyCode::Up | KeyCode::Char('k') => state.previous(),
             KeyCode::PageDown => state.jump_forward(10),
@@ -437,9 +683,52 @@ pub async fn handle_key_event(
                 state.match_all_categories = !state.match_all_categories;
                 state.refresh_filtered_view();
             }
-            KeyCode::Char('H') => {
+            KeyCode::Char('h') | KeyCode::Char('H') => {
                 state.hide_completed = !state.hide_completed;
+                // Until the next start, the toggle wins over the calendars' own setting
+                for prefs in state.calendar_prefs.values_mut() {
+                    prefs.hide_completed = None;
+                }
                 state.refresh_filtered_view();
+                state.message = if state.hide_completed {
+                    format!("Completed tasks hidden ({})", state.hidden_done)
+                } else {
+                    "Completed tasks shown".to_string()
+                };
+            }
+            KeyCode::Char('o') => {
+                let order = state.effective_sort_order().next();
+                set_sort_order(state, order);
+                state.refresh_filtered_view();
+                state.message = format!("Sort: {}", order);
+            }
+            KeyCode::Char('g') => {
+                state.group_by = state.group_by.next();
+                state.refresh_filtered_view();
+                state.message = format!("Groups: {}", state.group_by);
+            }
+            KeyCode::Char('@') => {
+                // Cycle the context filter: none, then each context in turn
+                let contexts: Vec<String> = state
+                    .store
+                    .get_all_contexts(&state.hidden_calendars)
+                    .into_iter()
+                    .map(|(ctx, _)| ctx)
+                    .collect();
+                let next = match &state.selected_context {
+                    None => contexts.first().cloned(),
+                    Some(cur) => contexts
+                        .iter()
+                        .position(|c| c == cur)
+                        .and_then(|i| contexts.get(i + 1).cloned()),
+                };
+                state.selected_context = next;
+                state.refresh_filtered_view();
+                state.message = match &state.selected_context {
+                    Some(ctx) => format!("Context: @@{}", ctx),
+                    None if contexts.is_empty() => "No contexts (add one with @@name)".to_string(),
+                    None => "Context: any".to_string(),
+                };
             }
             KeyCode::Char('*') => {
                 if state.active_focus == Focus::Sidebar {
@@ -480,14 +769,9 @@ pub async fn handle_key_event(
                 if state.active_focus == Focus::Sidebar
                     && state.sidebar_mode == SidebarMode::Calendars
                 {
-                    let target_href = if let Some(idx) = state.cal_state.selected() {
-                        let filtered = state.get_filtered_calendars();
-                        filtered.get(idx).map(|c| c.href.clone())
-                    } else {
-                        None
-                    };
-
-                    if let Some(href) = target_href {
+                    if let Some(href) = state.selected_sidebar_calendar() {
+                        state.smart_list = None;
+                        state.show_all = false;
                         state.active_cal_href = Some(href.clone());
                         state.hidden_calendars.clear();
                         for c in &state.calendars {
@@ -496,7 +780,7 @@ pub async fn handle_key_event(
                             }
                         }
                         state.refresh_filtered_view();
-                        if href != LOCAL_CALENDAR_HREF {
+                        if !is_local_calendar(&href) {
                             return Some(Action::IsolateCalendar(href));
                         }
                     }
@@ -508,18 +792,32 @@ pub async fn handle_key_event(
                 if state.active_focus == Focus::Sidebar {
                     match state.sidebar_mode {
                         SidebarMode::Calendars => {
-                            let target_href = if let Some(idx) = state.cal_state.selected() {
-                                let filtered = state.get_filtered_calendars();
-                                filtered.get(idx).map(|c| c.href.clone())
-                            } else {
-                                None
-                            };
-
-                            if let Some(href) = target_href {
+                            if let Some(list) = state.selected_smart_list() {
+                                state.smart_list = (state.smart_list != Some(list)).then_some(list);
+                                state.show_all = false;
+                                state.refresh_filtered_view();
+                            } else if state.all_entry_selected() {
+                                // Every checked calendar, space unchecks some of them
+                                state.smart_list = None;
+                                state.show_all = true;
+                                state.refresh_filtered_view();
+                                let calendars: Vec<CalendarListEntry> = state
+                                    .get_filtered_calendars()
+                                    .into_iter()
+                                    .filter(|c| {
+                                        !is_local_calendar(&c.href)
+                                            && !state.hidden_calendars.contains(&c.href)
+                                    })
+                                    .cloned()
+                                    .collect();
+                                return Some(Action::FetchAll(calendars));
+                            } else if let Some(href) = state.selected_sidebar_calendar() {
+                                state.smart_list = None;
+                                state.show_all = false;
                                 state.active_cal_href = Some(href.clone());
                                 state.hidden_calendars.remove(&href);
                                 state.refresh_filtered_view();
-                                if href != LOCAL_CALENDAR_HREF {
+                                if !is_local_calendar(&href) {
                                     return Some(Action::SwitchCalendar(href));
                                 }
                             }
@@ -557,7 +855,7 @@ pub async fn handle_key_event(
             }
             KeyCode::Char('e') => {
                 if let Some(t) = state.get_selected_task() {
-                    state.input_buffer = t.to_smart_string();
+                    state.input_buffer = t.to_smart_string_with(state.named_priorities);
                     state.cursor_position = state.input_buffer.len();
                     state.editing_index = state.list_state.selected();
                     state.mode = InputMode::Editing;
@@ -573,6 +871,174 @@ pub async fn handle_key_event(
                     state.mode = InputMode::EditingDescription;
                 }
             }
+            KeyCode::Char('R') => {
+                if state.active_focus == Focus::Main
+                    && let Some((rrule, after_completion)) = state
+                        .get_selected_task()
+                        .map(|t| (t.rrule.clone(), t.repeat_after_completion))
+                {
+                    // Rules we can't model (e.g. FREQ=HOURLY) would be lost
+                    match rrule.as_deref().map(Recurrence::parse).transpose() {
+                        Ok(draft) => {
+                            state.recurrence_draft = draft;
+                            state.recurrence_after_completion = after_completion;
+                            state.editing_index = state.list_state.selected();
+                            set_recurrence_step(state, RecurrenceStep::Freq);
+                            state.mode = InputMode::Recurrence;
+                        }
+                        Err(e) => state.message = format!("Can't edit this recurrence: {}", e),
+                    }
+                }
+            }
+            KeyCode::Char('v') => {
+                state.board = false;
+                state.agenda_days = match state.agenda_days {
+                    None => Some(7),
+                    Some(7) => Some(31),
+                    Some(_) => None,
+                };
+                state.message = match state.agenda_days {
+                    Some(7) => "Agenda: this week".to_string(),
+                    Some(_) => "Agenda: next 31 days".to_string(),
+                    None => "Task list".to_string(),
+                };
+                state.refresh_filtered_view();
+            }
+            KeyCode::Char('V') => {
+                if state.active_focus == Focus::Main
+                    && let Some(uid) = state.get_selected_task().map(|t| t.uid.clone())
+                {
+                    if !state.marked.remove(&uid) {
+                        state.marked.insert(uid);
+                    }
+                    state.message = marked_message(state);
+                    state.next();
+                }
+            }
+            KeyCode::Esc if !state.marked.is_empty() => {
+                state.marked.clear();
+                state.message = "Marks cleared.".to_string();
+            }
+            KeyCode::Char('#') if !state.marked.is_empty() => {
+                state.mode = InputMode::Tagging;
+                state.reset_input();
+            }
+            KeyCode::Char('i') => {
+                if state.active_focus == Focus::Main
+                    && let Some(t) = state.get_selected_task()
+                {
+                    state.field_draft = Some(t.clone());
+                    state.editing_index = state.list_state.selected();
+                    set_editing_field(state, TaskField::Due);
+                    state.mode = InputMode::EditingFields;
+                }
+            }
+            KeyCode::Char(c) => state.message = keymap::unknown_key(c),
+            _ => {}
+        },
+        InputMode::Tagging => match key.code {
+            KeyCode::Enter => {
+                state.mode = InputMode::Normal;
+                let input = std::mem::take(&mut state.input_buffer);
+                state.reset_input();
+                return bulk_tag(state, &input);
+            }
+            KeyCode::Esc => {
+                state.mode = InputMode::Normal;
+                state.reset_input();
+            }
+            KeyCode::Char(c) => state.enter_char(c),
+            KeyCode::Backspace => state.delete_char(),
+            KeyCode::Left => state.move_cursor_left(),
+            KeyCode::Right => state.move_cursor_right(),
+            _ => {}
+        },
+        InputMode::EditingFields => match key.code {
+            KeyCode::Esc => {
+                state.mode = InputMode::Normal;
+                state.field_draft = None;
+                state.reset_input();
+                state.message = String::new();
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                if store_field(state) {
+                    set_editing_field(state, state.editing_field.next());
+                }
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                if store_field(state) {
+                    set_editing_field(state, state.editing_field.prev());
+                }
+            }
+            KeyCode::Enter => {
+                if !store_field(state) {
+                    return None;
+                }
+                let warning = state
+                    .field_draft
+                    .as_ref()
+                    .and_then(|t| t.recurrence_warning());
+                if !confirm_warning(state, warning) {
+                    return None;
+                }
+                state.mode = InputMode::Normal;
+                state.reset_input();
+                state.message = String::new();
+                let draft = state.field_draft.take()?;
+                let (t, _) = state.store.get_task_mut(&draft.uid)?;
+                *t = draft.clone();
+                state.refresh_filtered_view();
+                return Some(Action::UpdateTask(draft));
+            }
+            KeyCode::Char(c) => state.enter_char(c),
+            KeyCode::Backspace => state.delete_char(),
+            KeyCode::Left => state.move_cursor_left(),
+            KeyCode::Right => state.move_cursor_right(),
+            _ => {}
+        },
+        InputMode::Recurrence => match (state.recurrence_step, key.code) {
+            (_, KeyCode::Esc) => {
+                state.mode = InputMode::Normal;
+                state.recurrence_draft = None;
+                state.reset_input();
+                state.message = String::new();
+            }
+            (RecurrenceStep::Freq, KeyCode::Char('n')) => {
+                state.recurrence_draft = None;
+                return finish_recurrence(state);
+            }
+            (RecurrenceStep::Freq, KeyCode::Char(c)) => {
+                let freq = match c {
+                    'd' => Freq::Daily,
+                    'w' => Freq::Weekly,
+                    'm' => Freq::Monthly,
+                    'y' => Freq::Yearly,
+                    _ => return None,
+                };
+                state.recurrence_draft = Some(match state.recurrence_draft.take() {
+                    Some(r) => Recurrence { freq, ..r },
+                    None => Recurrence::new(freq),
+                });
+                set_recurrence_step(state, RecurrenceStep::Interval);
+            }
+            // Enter keeps the current frequency
+            (RecurrenceStep::Freq, KeyCode::Enter) => {
+                if state.recurrence_draft.is_some() {
+                    set_recurrence_step(state, RecurrenceStep::Interval);
+                }
+            }
+            (RecurrenceStep::Freq, _) => {}
+            (RecurrenceStep::Mode, KeyCode::Char(c @ ('f' | 'c'))) => {
+                state.recurrence_after_completion = c == 'c';
+                return finish_recurrence(state);
+            }
+            (RecurrenceStep::Mode, KeyCode::Enter) => return finish_recurrence(state),
+            (RecurrenceStep::Mode, _) => {}
+            (_, KeyCode::Enter) => return submit_recurrence_step(state),
+            (_, KeyCode::Char(c)) => state.enter_char(c),
+            (_, KeyCode::Backspace) => state.delete_char(),
+            (_, KeyCode::Left) => state.move_cursor_left(),
+            (_, KeyCode::Right) => state.move_cursor_right(),
             _ => {}
         },
         InputMode::Moving => match key.code {
@@ -582,6 +1048,15 @@ pub async fn handle_key_event(
             }
             KeyCode::Down | KeyCode::Char('j') => state.next_move_target(),
             KeyCode::Up | KeyCode::Char('k') => state.previous_move_target(),
+            KeyCode::Enter if !state.marked.is_empty() => {
+                state.mode = InputMode::Normal;
+                let target = state
+                    .move_selection_state
+                    .selected()
+                    .and_then(|idx| state.move_targets.get(idx))
+                    .map(|c| c.href.clone())?;
+                return bulk_move(state, &target);
+            }
             KeyCode::Enter => {
                 let data = if let Some(task) = state.get_selected_task()
                     && let Some(idx) = state.move_selection_state.selected()
@@ -614,15 +1089,523 @@ pub async fn handle_key_event(
             KeyCode::Enter => {
                 if let Some(idx) = state.export_selection_state.selected()
                     && let Some(target) = state.export_targets.get(idx)
+                    && let Some(source) = state.active_cal_href.clone()
                 {
                     let href = target.href.clone();
                     state.mode = InputMode::Normal;
-                    return Some(Action::MigrateLocal(href));
+                    return Some(Action::MigrateLocal(source, href));
                 }
             }
             _ => {}
         },
+        InputMode::Trash => match key.code {
+            KeyCode::Esc => {
+                state.mode = InputMode::Normal;
+                state.message = String::new();
+            }
+            KeyCode::Down | KeyCode::Char('j') => state.next_trash_entry(),
+            KeyCode::Up | KeyCode::Char('k') => state.previous_trash_entry(),
+            KeyCode::Enter => {
+                if let Some(idx) = state.trash_selection_state.selected()
+                    && idx < state.trash_entries.len()
+                {
+                    let entry = state.trash_entries.remove(idx);
+                    state.mode = InputMode::Normal;
+                    state.message = "Restoring task...".to_string();
+                    return Some(Action::Undelete(entry.task.uid, entry.calendar_href));
+                }
+            }
+            _ => {}
+        },
+        InputMode::Pending => match key.code {
+            KeyCode::Esc => {
+                state.mode = InputMode::Normal;
+                state.message = String::new();
+            }
+            KeyCode::Down | KeyCode::Char('j') => state.next_pending_entry(),
+            KeyCode::Up | KeyCode::Char('k') => state.previous_pending_entry(),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(idx) = state.pending_selection_state.selected()
+                    && let Some(action) = state.pending_entries.get(idx)
+                {
+                    state.message = match Journal::remove(action) {
+                        Ok(true) => format!("Dropped: {}", action),
+                        Ok(false) => "Already synced.".to_string(),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                } else if let Some(entry) = state.selected_failed_entry().cloned() {
+                    state.message = match Journal::drop_failed(&entry) {
+                        Ok(_) => format!("Dropped: {}", entry.action),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                }
+                state.reload_pending();
+                if state.pending_len() == 0 {
+                    state.mode = InputMode::Normal;
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(entry) = state.selected_failed_entry().cloned() {
+                    state.message = match Journal::retry_failed(&entry) {
+                        Ok(_) => format!("Queued again: {} (S to sync)", entry.action),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                    state.reload_pending();
+                }
+            }
+            KeyCode::Char('K') | KeyCode::Char('J') => {
+                let up = key.code == KeyCode::Char('K');
+                if let Some(idx) = state.pending_selection_state.selected()
+                    && let Some(action) = state.pending_entries.get(idx).cloned()
+                    && let Ok(true) = Journal::move_entry(&action, up)
+                {
+                    state.reload_pending();
+                    let pos = state.pending_entries.iter().position(|a| *a == action);
+                    state.pending_selection_state.select(pos);
+                }
+            }
+            _ => {}
+        },
+        InputMode::ConfirmDelete => match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                state.mode = InputMode::Normal;
+                state.message = String::new();
+                return match state.deleting_uid.take() {
+                    Some(uid) => delete_task(state, &uid),
+                    None => bulk_delete(state),
+                };
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                state.mode = InputMode::Normal;
+                state.deleting_uid = None;
+                state.message = "Nothing deleted.".to_string();
+            }
+            _ => {}
+        },
+        InputMode::Conflict => {
+            let keep = match key.code {
+                KeyCode::Char('l') => Resolution::Local,
+                KeyCode::Char('s') => Resolution::Server,
+                KeyCode::Char('b') => Resolution::Both,
+                KeyCode::Esc => {
+                    state.mode = InputMode::Normal;
+                    state.message = "Conflict left for later.".to_string();
+                    return None;
+                }
+                _ => return None,
+            };
+            if state.conflicts.is_empty() {
+                state.mode = InputMode::Normal;
+                return None;
+            }
+            let conflict = state.conflicts.remove(0);
+            if state.conflicts.is_empty() {
+                state.mode = InputMode::Normal;
+            }
+            state.message = "Resolving conflict...".to_string();
+            return Some(Action::ResolveConflict(
+                conflict.local.calendar_href,
+                conflict.local.uid,
+                keep,
+            ));
+        }
         _ => {}
     }
     None
 }
+
+/// Stores inline alias definitions, applies them to existing tasks and saves them.
+/// Returns false if one was rejected (the reason is in the status message).
+async fn register_aliases(
+    state: &mut AppState,
+    new_aliases: HashMap<String, Vec<String>>,
+    action_tx: &Sender<Action>,
+) -> bool {
+    if new_aliases.is_empty() {
+        return true;
+    }
+    let mut all_ok = true;
+    for (key, tags) in new_aliases {
+        match insert_alias(&mut state.tag_aliases, &key, &tags) {
+            Ok(key) => {
+                let tags = state.tag_aliases[&key].clone();
+                for t in state.store.apply_alias_retroactively(&key, &tags) {
+                    let _ = action_tx.send(Action::UpdateTask(t)).await;
+                }
+            }
+            Err(e) => {
+                state.message = e;
+                all_ok = false;
+            }
+        }
+    }
+    if let Ok(mut cfg) = Config::load() {
+        cfg.tag_aliases = state.tag_aliases.clone();
+        let _ = cfg.save();
+    }
+    all_ok
+}
+
+// Remembered for the active calendar (in the config), or for this session when
+// several calendars are shown
+fn set_sort_order(state: &mut AppState, order: SortOrder) {
+    let Some(href) = state.active_cal_href.clone().filter(|_| !state.show_all) else {
+        state.sort_order = order;
+        return;
+    };
+    CalendarPrefs::entry(&mut state.calendar_prefs, &state.calendars, &href).sort_order =
+        Some(order);
+    if let Ok(mut cfg) = Config::load() {
+        CalendarPrefs::entry(&mut cfg.calendars, &state.calendars, &href).sort_order = Some(order);
+        let _ = cfg.save();
+    }
+}
+
+// A warning blocks the first Enter only: the same input again is saved as is
+fn confirm_warning(state: &mut AppState, warning: Option<String>) -> bool {
+    let Some(warning) = warning else {
+        state.warned_input = None;
+        return true;
+    };
+    if state.warned_input.as_deref() == Some(state.input_buffer.as_str()) {
+        state.warned_input = None;
+        return true;
+    }
+    state.message = format!("{} (Enter again to save anyway)", warning);
+    state.warned_input = Some(state.input_buffer.clone());
+    false
+}
+
+fn marked_message(state: &AppState) -> String {
+    format!(
+        "{} marked (Space: done, d: delete, #: tag, M: move, Esc: clear)",
+        state.marked.len()
+    )
+}
+
+// Takes the marks, leaving out tasks of read-only calendars
+fn take_marked(state: &mut AppState) -> Vec<Task> {
+    let uids: Vec<String> = state.marked.drain().collect();
+    let mut tasks: Vec<Task> = uids
+        .iter()
+        .filter_map(|uid| state.store.get_task_mut(uid).map(|(t, _)| t.clone()))
+        .collect();
+    tasks.retain(|t| state.read_only_calendar(&t.calendar_href).is_none());
+    tasks
+}
+
+// Sends the changes as one batch (a single sync)
+fn send_batch(state: &mut AppState, actions: Vec<JournalAction>, done: String) -> Option<Action> {
+    state.refresh_filtered_view();
+    if actions.is_empty() {
+        state.message = "Nothing to change.".to_string();
+        return None;
+    }
+    state.message = "Saving...".to_string();
+    Some(Action::Batch(actions, done))
+}
+
+// Completes the marked tasks, or reopens them when they're all done already
+fn bulk_toggle(state: &mut AppState) -> Option<Action> {
+    let tasks = take_marked(state);
+    let reopen = tasks.iter().all(|t| t.status == TaskStatus::Completed);
+    let mut actions = Vec::new();
+    let mut count = 0;
+    for task in tasks {
+        if (task.status == TaskStatus::Completed) != reopen {
+            continue;
+        }
+        let Some(updated) = state.store.toggle_task(&task.uid) else {
+            continue;
+        };
+        // Same as completing one: the next occurrence of a recurring task
+        if updated.status == TaskStatus::Completed
+            && let Some(next) = updated.respawn()
+        {
+            state.store.add_task(next.clone());
+            actions.push(JournalAction::Create(next));
+        }
+        actions.push(JournalAction::Update(updated));
+        count += 1;
+    }
+    let verb = if reopen { "Reopened" } else { "Completed" };
+    send_batch(state, actions, format!("{} {} task(s).", verb, count))
+}
+
+fn delete_task(state: &mut AppState, uid: &str) -> Option<Action> {
+    let deleted = state.store.delete_task(uid)?;
+    state.refresh_filtered_view();
+    Some(Action::DeleteTask(deleted))
+}
+
+fn bulk_delete(state: &mut AppState) -> Option<Action> {
+    let actions: Vec<JournalAction> = take_marked(state)
+        .iter()
+        .filter_map(|t| state.store.delete_task(&t.uid))
+        .map(JournalAction::Delete)
+        .collect();
+    let done = format!("Deleted {} task(s).", actions.len());
+    send_batch(state, actions, done)
+}
+
+fn bulk_move(state: &mut AppState, target: &str) -> Option<Action> {
+    let mut actions = Vec::new();
+    for task in take_marked(state) {
+        if task.calendar_href != target
+            && state
+                .store
+                .move_task(&task.uid, target.to_string())
+                .is_some()
+        {
+            actions.push(JournalAction::Move(task, target.to_string()));
+        }
+    }
+    let done = format!("Moved {} task(s).", actions.len());
+    send_batch(state, actions, done)
+}
+
+// Moves the selected task one board column right (or left) by setting its status
+fn move_on_board(state: &mut AppState, right: bool) -> Option<Action> {
+    let uid = state.get_selected_task()?.uid.clone();
+    let col = state.board_column()?;
+    let target = if right { col + 1 } else { col.checked_sub(1)? };
+    let status = *BOARD_COLUMNS.get(target)?;
+    // Done the usual way, so recurring tasks respawn
+    let action = if status == TaskStatus::Completed {
+        Action::ToggleTask(state.store.toggle_task(&uid)?)
+    } else {
+        Action::UpdateTask(state.store.set_status(&uid, status)?)
+    };
+    state.refresh_filtered_view();
+    // Follow the task to its new column
+    if let Some(idx) = state.tasks.iter().position(|t| t.uid == uid) {
+        state.list_state.select(Some(idx));
+    }
+    state.message = format!("Moved to {}", status.label());
+    Some(action)
+}
+
+// "work #home -old": adds work and home, removes old
+fn bulk_tag(state: &mut AppState, input: &str) -> Option<Action> {
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    for word in input.split_whitespace() {
+        match word.strip_prefix('-') {
+            Some(tag) => remove.push(tag.trim_start_matches('#').to_string()),
+            None => add.push(word.trim_start_matches('#').to_string()),
+        }
+    }
+    add.retain(|t| !t.is_empty());
+    if add.is_empty() && remove.is_empty() {
+        return None;
+    }
+
+    let mut actions = Vec::new();
+    for mut task in take_marked(state) {
+        let before = task.categories.clone();
+        task.categories.retain(|c| !remove.contains(c));
+        for tag in &add {
+            task.add_tag(tag, &state.tag_aliases);
+        }
+        if task.categories == before {
+            continue;
+        }
+        if let Some((t, _)) = state.store.get_task_mut(&task.uid) {
+            *t = task.clone();
+        }
+        actions.push(JournalAction::Update(task));
+    }
+    let done = format!("Tagged {} task(s).", actions.len());
+    send_batch(state, actions, done)
+}
+
+// Starts on the date being edited, or today
+fn open_date_picker(state: &mut AppState) {
+    let current = match (state.mode, &state.field_draft) {
+        (InputMode::EditingFields, Some(t)) => match state.editing_field {
+            TaskField::Due => t.due,
+            TaskField::Start => t.dtstart,
+            // Nothing to pick a date for
+            _ => return,
+        },
+        _ => None,
+    };
+    state.date_picker = Some(current.map_or(Local::now().date_naive(), |d| {
+        d.with_timezone(&Local).date_naive()
+    }));
+}
+
+fn handle_date_picker(state: &mut AppState, code: KeyCode, day: NaiveDate) {
+    let month = Months::new(1);
+    let moved = match code {
+        KeyCode::Left | KeyCode::Char('h') => day.pred_opt(),
+        KeyCode::Right | KeyCode::Char('l') => day.succ_opt(),
+        KeyCode::Up | KeyCode::Char('k') => day.checked_sub_days(Days::new(7)),
+        KeyCode::Down | KeyCode::Char('j') => day.checked_add_days(Days::new(7)),
+        KeyCode::PageUp | KeyCode::Char('<') => day.checked_sub_months(month),
+        KeyCode::PageDown | KeyCode::Char('>') => day.checked_add_months(month),
+        KeyCode::Char('t') => Some(Local::now().date_naive()),
+        KeyCode::Esc => {
+            state.date_picker = None;
+            return;
+        }
+        KeyCode::Enter => {
+            state.date_picker = None;
+            insert_date(state, day);
+            return;
+        }
+        _ => None,
+    };
+    if let Some(moved) = moved {
+        state.date_picker = Some(moved);
+    }
+}
+
+// A field holds just the date, the smart input gets `@date` as its own word
+fn insert_date(state: &mut AppState, day: NaiveDate) {
+    let date = day.format("%Y-%m-%d").to_string();
+    if state.mode == InputMode::EditingFields {
+        state.input_buffer = date;
+        state.cursor_position = state.input_buffer.len();
+        return;
+    }
+    let before = state
+        .cursor_position
+        .checked_sub(1)
+        .and_then(|i| state.input_buffer.chars().nth(i));
+    let after = state.input_buffer.chars().nth(state.cursor_position);
+    let mut word = format!("@{}", date);
+    if before.is_some_and(|c| !c.is_whitespace()) {
+        word.insert(0, ' ');
+    }
+    if after.is_some_and(|c| !c.is_whitespace()) {
+        word.push(' ');
+    }
+    for c in word.chars() {
+        state.enter_char(c);
+    }
+}
+
+// Puts `field` of the draft in the input, in smart syntax
+fn set_editing_field(state: &mut AppState, field: TaskField) {
+    state.editing_field = field;
+    state.input_buffer = state
+        .field_draft
+        .as_ref()
+        .map(|t| t.field_smart_string(field, state.named_priorities))
+        .unwrap_or_default();
+    state.cursor_position = state.input_buffer.len();
+    state.message = field.hint().to_string();
+}
+
+// Parses the input into the draft's current field, false (with the reason shown) if it
+// doesn't make sense
+fn store_field(state: &mut AppState) -> bool {
+    let Some(draft) = state.field_draft.as_mut() else {
+        return false;
+    };
+    match draft.apply_field_input(state.editing_field, &state.input_buffer, &state.tag_aliases) {
+        Ok(()) => true,
+        Err(e) => {
+            state.message = e;
+            false
+        }
+    }
+}
+
+// Moves the recurrence prompt to `step`, pre-filled with the current value
+fn set_recurrence_step(state: &mut AppState, step: RecurrenceStep) {
+    let current = match (&state.recurrence_draft, step) {
+        (Some(r), RecurrenceStep::Interval) => r.interval.to_string(),
+        (Some(r), RecurrenceStep::Days) => r
+            .by_day
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+        (Some(r), RecurrenceStep::End) => match (r.until, r.count) {
+            (Some(until), _) => until.date().format("%Y-%m-%d").to_string(),
+            (None, Some(count)) => count.to_string(),
+            (None, None) => String::new(),
+        },
+        _ => String::new(),
+    };
+    state.recurrence_step = step;
+    state.input_buffer = current;
+    state.cursor_position = state.input_buffer.len();
+    state.message = String::new();
+}
+
+fn submit_recurrence_step(state: &mut AppState) -> Option<Action> {
+    let input = state.input_buffer.trim().to_string();
+    let rule = state.recurrence_draft.as_mut()?;
+    let result = match state.recurrence_step {
+        RecurrenceStep::Freq | RecurrenceStep::Mode => Ok(()),
+        RecurrenceStep::Interval => match input.as_str() {
+            "" => Ok(1),
+            s => s
+                .parse()
+                .ok()
+                .filter(|i| *i > 0)
+                .ok_or_else(|| format!("Invalid interval '{}'", s)),
+        }
+        .map(|i| rule.interval = i),
+        RecurrenceStep::Days => input
+            .split([',', ' '])
+            .filter(|d| !d.is_empty())
+            .map(ByDay::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map(|days| rule.by_day = days),
+        RecurrenceStep::End => parse_recurrence_end(&input).map(|(until, count)| {
+            rule.until = until;
+            rule.count = count;
+        }),
+    };
+    if let Err(e) = result {
+        state.message = e;
+        return None;
+    }
+
+    match state.recurrence_step {
+        RecurrenceStep::Freq => set_recurrence_step(state, RecurrenceStep::Interval),
+        RecurrenceStep::Interval => set_recurrence_step(state, RecurrenceStep::Days),
+        RecurrenceStep::Days => set_recurrence_step(state, RecurrenceStep::End),
+        RecurrenceStep::End => set_recurrence_step(state, RecurrenceStep::Mode),
+        RecurrenceStep::Mode => return finish_recurrence(state),
+    }
+    None
+}
+
+// "2025-12-31" ends on that day, "10" (or "10x") after ten occurrences, empty never
+fn parse_recurrence_end(input: &str) -> Result<(Option<Until>, Option<u32>), String> {
+    if input.is_empty() {
+        return Ok((None, None));
+    }
+    if let Ok(count) = input.trim_end_matches('x').parse::<u32>()
+        && count > 0
+    {
+        return Ok((None, Some(count)));
+    }
+    chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map(|d| (Some(Until::Date(d)), None))
+        .map_err(|_| "Enter a date (YYYY-MM-DD), a number of times, or nothing".to_string())
+}
+
+fn finish_recurrence(state: &mut AppState) -> Option<Action> {
+    state.mode = InputMode::Normal;
+    state.reset_input();
+    let rule = state.recurrence_draft.take();
+    let uid = state
+        .editing_index
+        .and_then(|idx| state.tasks.get(idx).map(|t| t.uid.clone()))?;
+    let (t, _) = state.store.get_task_mut(&uid)?;
+    t.repeat_after_completion = rule.is_some() && state.recurrence_after_completion;
+    t.set_recurrence(rule);
+    let clone = t.clone();
+    state.message = match clone.recurrence_summary() {
+        Some(summary) => format!("Repeats: {}", summary),
+        None => "No longer repeats.".to_string(),
+    };
+    state.refresh_filtered_view();
+    Some(Action::UpdateTask(clone))
+}
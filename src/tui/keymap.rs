@@ -0,0 +1,322 @@
+// File: ./src/tui/keymap.rs
+// Every key of the TUI by mode, for the help popup ('?') and the read-only guard.
+// The keys themselves are handled in `handlers`: add a line here along with a new
+// binding, with `edit` if it changes the selected task (a test presses every key in
+// normal mode and checks that those doing something are the ones listed here).
+
+pub struct KeySection {
+    pub mode: &'static str,
+    pub context: Context,
+    pub keys: &'static [Binding],
+}
+
+// Where the keys of a section apply
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Context {
+    // Task list and sidebar, no popup or input open
+    Normal,
+    // Normal mode, on the board (these come first)
+    Board,
+    // Normal mode, with the focus on the sidebar (these come first)
+    Sidebar,
+    // Popups and inputs
+    Other,
+}
+
+pub struct Binding {
+    // Key names separated by " / ", e.g. "e / E" or "Space"
+    pub keys: &'static str,
+    pub what: &'static str,
+    // Changes the selected task: refused on read-only calendars
+    pub edits: bool,
+}
+
+const fn key(keys: &'static str, what: &'static str) -> Binding {
+    Binding {
+        keys,
+        what,
+        edits: false,
+    }
+}
+
+const fn edit(keys: &'static str, what: &'static str) -> Binding {
+    Binding {
+        keys,
+        what,
+        edits: true,
+    }
+}
+
+impl Binding {
+    /// The printable keys of the binding ("Space" included, other named keys skipped).
+    pub fn chars(&self) -> impl Iterator<Item = char> {
+        self.keys.split(" / ").filter_map(|k| {
+            let mut chars = k.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => (k == "Space").then_some(' '),
+            }
+        })
+    }
+}
+
+/// Whether a normal-mode key changes the selected task. On the board and in the
+/// sidebar, their own meaning of a key wins.
+pub fn edits_selected(c: char, board: bool, sidebar: bool) -> bool {
+    let bindings = |context: Context| {
+        KEYMAP
+            .iter()
+            .filter(move |s| s.context == context)
+            .flat_map(|s| s.keys)
+            .filter(move |b| b.chars().any(|k| k == c))
+    };
+    let first = match (sidebar, board) {
+        (true, _) => Some(Context::Sidebar),
+        (false, true) => Some(Context::Board),
+        _ => None,
+    };
+    if let Some(context) = first
+        && bindings(context).next().is_some()
+    {
+        return bindings(context).any(|b| b.edits);
+    }
+    bindings(Context::Normal).any(|b| b.edits)
+}
+
+/// Status line for a normal-mode key that does nothing.
+pub fn unknown_key(c: char) -> String {
+    format!("No '{}' key, ? lists them", c)
+}
+
+pub const KEYMAP: &[KeySection] = &[
+    KeySection {
+        mode: "Global",
+        context: Context::Normal,
+        keys: &[
+            key("Tab", "Switch focus (tasks / sidebar)"),
+            key("?", "This help"),
+            key("q", "Quit"),
+            key("r", "Refresh"),
+            key("S", "Sync now (upload queued changes)"),
+            key("O", "Toggle offline mode"),
+            key("u", "Undo the last change"),
+            key("P", "Pending changes not synced yet"),
+            key("T", "Trash: restore deleted tasks"),
+        ],
+    },
+    KeySection {
+        mode: "Task list",
+        context: Context::Normal,
+        keys: &[
+            key("j / k", "Down / up"),
+            key("PgDn / PgUp", "Scroll by 10"),
+            edit("Space", "Toggle done"),
+            key("a", "Add a task"),
+            key("C", "Add a child of the task"),
+            edit("e / E", "Edit the title / the description"),
+            edit("i", "Edit the fields one by one"),
+            edit("d", "Delete (y to confirm)"),
+            edit("s", "Start / pause"),
+            edit("x", "Cancel"),
+            edit("w", "Next status of the status cycle"),
+            edit("f", "Flag / unflag"),
+            edit("t", "Start / stop time tracking"),
+            edit("l / L", "Check the next checklist item / uncheck the last"),
+            edit("R", "Repeat (guided recurrence)"),
+            edit("M", "Move to another calendar"),
+            key("X", "Export the local tasks to a calendar"),
+            key("y", "Yank (copy the task for linking)"),
+            edit("b", "Blocked by the yanked task"),
+            edit("c", "Child of the yanked task"),
+            edit("+ / -", "Raise / lower the priority"),
+            edit("> / <", "Indent / outdent"),
+            edit(". / ,", "Indent / outdent (no shift)"),
+            edit("K / J", "Move up / down in the manual order"),
+        ],
+    },
+    KeySection {
+        mode: "Marked tasks",
+        context: Context::Normal,
+        keys: &[
+            key("V", "Mark / unmark the task"),
+            key("Esc", "Clear the marks"),
+            key("Space", "Complete (or reopen) the marked tasks"),
+            key("d", "Delete the marked tasks"),
+            key("M", "Move the marked tasks"),
+            key("#", "Tag the marked tasks (-tag removes one)"),
+        ],
+    },
+    KeySection {
+        mode: "View & filter",
+        context: Context::Normal,
+        keys: &[
+            key("/", "Search"),
+            key("h / H", "Hide / show completed tasks"),
+            key("o", "Next sort order"),
+            key("g", "Next grouping"),
+            key("v", "Agenda: week, month, back to the list"),
+            key("B", "Kanban board / back to the list"),
+            key("F", "Flagged tasks / back"),
+            key("@", "Next context filter"),
+            key("1 / 2", "Calendars / tags in the sidebar"),
+            key("m", "Tags: match all / any"),
+        ],
+    },
+    KeySection {
+        mode: "Board",
+        context: Context::Board,
+        keys: &[
+            edit("h / l", "Move the task to the previous / next column"),
+            key("j / k", "Down / up in the column"),
+            key("Left / Right", "Previous / next column"),
+        ],
+    },
+    KeySection {
+        mode: "Sidebar",
+        context: Context::Sidebar,
+        keys: &[
+            key("Enter", "Select the calendar / toggle the tag"),
+            key("Space", "Show / hide the calendar"),
+            key("Right", "Only this calendar"),
+            key("*", "Show all / hide others, or clear the tags"),
+        ],
+    },
+    KeySection {
+        mode: "Typing (add, edit, search)",
+        context: Context::Other,
+        keys: &[
+            key("Enter", "Save"),
+            key("Esc", "Cancel"),
+            key("Left / Right", "Move the cursor"),
+            key("Ctrl+D", "Date picker"),
+            key("Alt+Enter", "New line (description)"),
+        ],
+    },
+    KeySection {
+        mode: "Edit fields",
+        context: Context::Other,
+        keys: &[
+            key("Tab / Down", "Next field"),
+            key("Shift+Tab / Up", "Previous field"),
+            key("Enter", "Save all fields"),
+            key("Esc", "Cancel"),
+        ],
+    },
+    KeySection {
+        mode: "Date picker",
+        context: Context::Other,
+        keys: &[
+            key("arrows / hjkl", "Previous / next day or week"),
+            key("PgUp / PgDn", "Previous / next month"),
+            key("t", "Today"),
+            key("Enter", "Insert the day"),
+            key("Esc", "Close"),
+        ],
+    },
+    KeySection {
+        mode: "Repeat",
+        context: Context::Other,
+        keys: &[
+            key("d / w / m / y", "Daily, weekly, monthly, yearly"),
+            key("n", "Don't repeat"),
+            key("Enter", "Next step"),
+            key("f / c", "Fixed dates / after completion"),
+            key("Esc", "Cancel"),
+        ],
+    },
+    KeySection {
+        mode: "Lists (move, export, trash, pending)",
+        context: Context::Other,
+        keys: &[
+            key("j / k", "Down / up"),
+            key("Enter", "Pick"),
+            key("Esc", "Close"),
+            key("d", "Pending: drop the change"),
+            key("K / J", "Pending: move the change up / down"),
+            key("r", "Pending: retry a failed change"),
+        ],
+    },
+    KeySection {
+        mode: "Delete confirmation",
+        context: Context::Other,
+        keys: &[key("y", "Delete"), key("n / Esc", "Keep")],
+    },
+    KeySection {
+        mode: "Conflicts",
+        context: Context::Other,
+        keys: &[
+            key("l", "Keep the local version"),
+            key("s", "Keep the server version"),
+            key("b", "Keep both"),
+            key("Esc", "Decide later"),
+        ],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_selected() {
+        assert!(edits_selected('e', false, false));
+        assert!(edits_selected(' ', false, false));
+        assert!(edits_selected('.', false, false));
+        assert!(!edits_selected('/', false, false));
+        // Space shows / hides a calendar in the sidebar
+        assert!(!edits_selected(' ', false, true));
+        // h hides the completed tasks, but moves the task on the board
+        assert!(!edits_selected('h', false, false));
+        assert!(edits_selected('h', true, false));
+        assert!(!edits_selected('j', true, false));
+    }
+
+    // Whether pressing `c` does anything in normal mode, on the list, the board or
+    // in the sidebar. Tasks are marked so that keys for marked tasks work too.
+    fn handled(c: char) -> bool {
+        use crate::tui::handlers::handle_key_event;
+        use crate::tui::state::{AppState, Focus};
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use std::collections::HashMap;
+
+        let views = [
+            (false, Focus::Main),
+            (true, Focus::Main),
+            (false, Focus::Sidebar),
+        ];
+        views.into_iter().any(|(board, focus)| {
+            let mut state = AppState::new();
+            for summary in ["One", "Two"] {
+                let mut task = crate::model::Task::new(summary, &HashMap::new());
+                task.calendar_href = "/cal/".to_string();
+                state.marked.insert(task.uid.clone());
+                state.store.add_task(task);
+            }
+            state.refresh_filtered_view();
+            state.board = board;
+            state.active_focus = focus;
+
+            // Nobody listens: actions are dropped
+            let (action_tx, _) = tokio::sync::mpsc::channel(1);
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            futures::executor::block_on(handle_key_event(key, &mut state, &action_tx));
+            state.message != unknown_key(c)
+        })
+    }
+
+    // A key handled in normal mode but missing here would be neither in the help
+    // nor checked by the read-only guard, and one listed but not handled is a lie
+    #[test]
+    fn test_normal_keys_are_in_the_keymap() {
+        let mut known: Vec<char> = KEYMAP
+            .iter()
+            .filter(|s| s.context != Context::Other)
+            .flat_map(|s| s.keys)
+            .flat_map(|b| b.chars())
+            .collect();
+        known.sort_unstable();
+        known.dedup();
+        let handled: Vec<char> = (' '..='~').filter(|c| handled(*c)).collect();
+        assert_eq!(handled, known);
+    }
+}
@@ -1,11 +1,23 @@
 // File: ./src/tui/mod.rs
 pub mod action;
 pub mod handlers;
+pub mod keymap;
 pub mod network;
+pub mod setup;
 pub mod state;
 pub mod view;
 
+use crate::backup::Backup;
+use crate::cache::Cache;
+use crate::client::{AccountManager, ClientError};
 use crate::config;
+use crate::journal::Journal;
+use crate::model::status::status_cycle;
+use crate::model::{CalendarListEntry, FileFormat, Task};
+use crate::storage::{
+    LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME, LocalStorage, Trash, is_local_calendar,
+};
+use crate::store::{FilterOptions, QUERY_PREFIX, TaskStore, query_error};
 use crate::tui::state::{AppState, InputMode};
 use crate::tui::view::draw;
 
@@ -16,14 +28,40 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
-use std::{env, io, time::Duration};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::{env, io, path::Path, time::Duration};
 use tokio::sync::mpsc;
 
 pub async fn run() -> Result<()> {
     // --- 1. PREAMBLE & CONFIG ---
     let args: Vec<String> = env::args().collect();
     if args.len() > 1 && (args[1] == "--help" || args[1] == "-h") {
-        println!("Usage: cfait [OPTIONS]");
+        println!("Usage: cfait [OPTIONS] [COMMAND]");
+        println!();
+        println!("Commands:");
+        println!("  sync                  Upload changes made offline and exit");
+        println!("  import <file> [cal]   Import an .ics file (default: default_calendar)");
+        println!("  export <file> [cal]   Write all tasks of a calendar to one .ics file");
+        println!("    --format <format>   ics, taskwarrior; export only: org, csv, markdown.");
+        println!("                        Default: from the extension (.json: Taskwarrior)");
+        println!("    --filter <query>    Only the cached tasks matching a filter expression,");
+        println!("                        of all calendars unless one is named");
+        println!("  list [query]          Print cached tasks matching a filter expression,");
+        println!("                        e.g. 'due<7d AND prio<=4 AND #work AND status!=done'");
+        println!("  search <words>        Print cached tasks containing all the words");
+        println!("  stats [weeks]         Counts by status/tag/calendar and weekly completion");
+        println!("  doctor                Explain what keeps offline changes from syncing");
+        println!("  cache [clear]         Show the size of the task caches, or clear them");
+        println!("  cache prune <days> [MB]");
+        println!("                        Remove caches not synced for that long, then the");
+        println!("                        least recently synced ones above MB in total");
+        println!("  local                 List the local (offline) calendars");
+        println!("  local add <name>      Add a local calendar");
+        println!("  local rename <name> <new name>");
+        println!("  local delete <name>   Delete a local calendar, its tasks go to the trash");
+        println!("  backup <file>         Save all tasks, pending changes and aliases to one file");
+        println!("  restore <file>        Merge a backup back in (same UID: the backup wins)");
         return Ok(());
     }
 
@@ -41,33 +79,17 @@ pub async fn run() -> Result<()> {
         default_hook(info);
     }));
 
-    let config_result = config::Config::load();
-    let (
-        url,
-        user,
-        pass,
-        default_cal,
-        hide_completed,
-        hide_fully_completed_tags,
-        tag_aliases,
-        sort_cutoff,
-        allow_insecure,
-        hidden_calendars,
-        disabled_calendars,
-    ) = match config_result {
-        Ok(cfg) => (
-            cfg.url,
-            cfg.username,
-            cfg.password,
-            cfg.default_calendar,
-            cfg.hide_completed,
-            cfg.hide_fully_completed_tags,
-            cfg.tag_aliases,
-            cfg.sort_cutoff_months,
-            cfg.allow_insecure_certs,
-            cfg.hidden_calendars,
-            cfg.disabled_calendars,
-        ),
+    let mut cfg = match config::Config::load() {
+        Ok(cfg) => cfg,
+        // First run: walk through the setup instead of bailing out
+        Err(_) if !config::Config::file_exists() && io::stdin().is_terminal() => {
+            setup::run().await?
+        }
+        // What's wrong with it, key by key
+        Err(e) if config::Config::file_exists() => {
+            eprintln!("{}", e);
+            return Ok(());
+        }
         Err(_) => {
             let path_str =
                 config::Config::get_path_string().unwrap_or("[path unknown]".to_string());
@@ -75,6 +97,55 @@ pub async fn run() -> Result<()> {
             return Ok(());
         }
     };
+    // On the main screen: still there after the TUI exits
+    for problem in &cfg.warnings {
+        eprintln!("Config warning: {}", problem);
+    }
+    let default_cal = cfg.default_calendar.clone();
+
+    // Offline, no password needed
+    if args.len() > 1 && args[1] == "list" {
+        return list_tasks(&cfg, &args[2..]);
+    }
+    if args.len() > 1 && args[1] == "search" {
+        return search_tasks(&cfg, &args[2..]);
+    }
+    if args.len() > 1 && args[1] == "stats" {
+        return print_stats(&cfg, args.get(2).map(String::as_str));
+    }
+    if args.len() > 1 && args[1] == "doctor" {
+        return doctor(&cfg);
+    }
+    if args.len() > 1 && args[1] == "cache" {
+        return cache_command(&args[2..]);
+    }
+    if args.len() > 1 && args[1] == "local" {
+        return local_command(&cfg, &args[2..]);
+    }
+    if args.len() > 1 && args[1] == "backup" {
+        return backup(&cfg, args.get(2));
+    }
+    if args.len() > 1 && args[1] == "restore" {
+        return restore(&mut cfg, args.get(2));
+    }
+
+    // Fallback prompt when the keyring has no secret for this account
+    if uses_keyring_password(&cfg) && cfg.password.is_empty() {
+        cfg.password = prompt_password(&format!("Password for {}@{}: ", cfg.username, cfg.url))?;
+        if let Err(e) = crate::credentials::set_password(&cfg.url, &cfg.username, &cfg.password) {
+            eprintln!("Could not store password in keyring: {}", e);
+        }
+    }
+
+    if args.len() > 1 && args[1] == "sync" {
+        return sync_now(&cfg).await;
+    }
+    if args.len() > 1 && args[1] == "import" {
+        return import_tasks(&cfg, &args[2..]).await;
+    }
+    if args.len() > 1 && args[1] == "export" {
+        return export_tasks(&cfg, &args[2..]).await;
+    }
 
     // --- 2. TERMINAL SETUP ---
     enable_raw_mode()?;
@@ -85,26 +156,35 @@ pub async fn run() -> Result<()> {
 
     // --- 3. STATE INIT ---
     let mut app_state = AppState::new();
-    app_state.hide_completed = hide_completed;
-    app_state.hide_fully_completed_tags = hide_fully_completed_tags;
-    app_state.tag_aliases = tag_aliases;
-    app_state.sort_cutoff_months = sort_cutoff;
-    app_state.hidden_calendars = hidden_calendars.into_iter().collect();
-    app_state.disabled_calendars = disabled_calendars.into_iter().collect();
+    app_state.hide_completed = cfg.hide_completed;
+    app_state.hide_fully_completed_tags = cfg.hide_fully_completed_tags;
+    app_state.tag_aliases = cfg.tag_aliases.clone();
+    app_state.calendar_prefs = cfg.calendars.clone();
+    app_state.theme = cfg.theme.clone();
+    if let Some(first) = cfg.warnings.first() {
+        app_state.message = match cfg.warnings.len() {
+            1 => format!("Config: {}", first),
+            n => format!("Config: {} (+{} more, shown after quitting)", first, n - 1),
+        };
+    }
+    app_state.sort_cutoff_months = cfg.sort_cutoff_months;
+    app_state.named_priorities = cfg.named_priorities;
+    app_state.confirm_delete = cfg.confirm_delete;
+    app_state.status_cycle = status_cycle(&cfg.custom_statuses, &cfg.status_cycle);
+    app_state.custom_statuses = cfg.custom_statuses.clone();
+    app_state.sort_order = cfg.sort_order;
+    app_state.group_by = cfg.group_by;
+    app_state.offline = cfg.offline;
+    app_state.journal_limits = (cfg.journal_max_entries, cfg.journal_max_days);
+    app_state.refresh_journal_status();
+    app_state.hidden_calendars = cfg.hidden_calendars.iter().cloned().collect();
+    app_state.disabled_calendars = cfg.disabled_calendars.iter().cloned().collect();
 
     let (action_tx, action_rx) = mpsc::channel(10);
     let (event_tx, mut event_rx) = mpsc::channel(10);
 
     // --- 4. NETWORK THREAD ---
-    tokio::spawn(network::run_network_actor(
-        url,
-        user,
-        pass,
-        allow_insecure,
-        default_cal.clone(), // Clone for the thread
-        action_rx,
-        event_tx,
-    ));
+    tokio::spawn(network::run_network_actor(cfg, action_rx, event_tx));
 
     // --- 5. UI LOOP ---
     loop {
@@ -159,3 +239,588 @@ pub async fn run() -> Result<()> {
     terminal.show_cursor()?;
     Ok(())
 }
+
+// The accounts of the CLI commands, warning about any that had to be left out
+async fn connect(cfg: &config::Config) -> Result<AccountManager> {
+    let manager = AccountManager::from_config(cfg)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    for warning in &manager.skipped {
+        eprintln!("Account skipped: {}", warning);
+    }
+    Ok(manager)
+}
+
+/// `cfait sync`: replays the offline journal once, without starting the UI.
+/// Works in offline mode too, that's what it's for.
+async fn sync_now(cfg: &config::Config) -> Result<()> {
+    let pending = Journal::load()?.queue.len();
+    if pending == 0 {
+        println!("Nothing to sync.");
+        return Ok(());
+    }
+    let manager = connect(cfg).await?;
+    let mut result = manager.sync_journal().await;
+    // The keyring password was rejected: ask for it again and retry once
+    if matches!(result, Err(ClientError::Unauthorized(_))) && uses_keyring_password(cfg) {
+        let mut cfg = cfg.clone();
+        cfg.password = prompt_password(&format!(
+            "Authentication failed. Password for {}@{}: ",
+            cfg.username, cfg.url
+        ))?;
+        let manager = connect(&cfg).await?;
+        result = manager.sync_journal().await;
+        if result.is_ok()
+            && let Err(e) = crate::credentials::set_password(&cfg.url, &cfg.username, &cfg.password)
+        {
+            eprintln!("Could not store password in keyring: {}", e);
+        }
+    }
+    let left = Journal::load()?.queue.len();
+    match result {
+        Ok(warnings) => {
+            for w in warnings {
+                eprintln!("{}", w);
+            }
+            println!("Synced {} change(s).", pending.saturating_sub(left));
+            Ok(())
+        }
+        Err(e) => Err(anyhow::anyhow!(
+            "Sync failed after {} of {} change(s): {}",
+            pending.saturating_sub(left),
+            pending,
+            e
+        )),
+    }
+}
+
+/// `cfait import <file> [calendar]`: the calendar is matched by name or href,
+/// falling back to `default_calendar`, then to Local.
+// Account and href of the calendar named on the command line (name or href), else
+// default_calendar, else Local
+async fn resolve_calendar(
+    cfg: &config::Config,
+    manager: &mut AccountManager,
+    wanted: Option<&String>,
+) -> Result<(Option<String>, String)> {
+    match wanted.or(cfg.default_calendar.as_ref()) {
+        None => Ok((None, LOCAL_CALENDAR_HREF.to_string())),
+        Some(w) if w == LOCAL_CALENDAR_NAME || w == LOCAL_CALENDAR_HREF => {
+            Ok((None, LOCAL_CALENDAR_HREF.to_string()))
+        }
+        Some(w) => {
+            let mut calendars = match manager.get_calendars().await {
+                Ok(cals) => cals,
+                Err(_) => Cache::load_calendars()?,
+            };
+            LocalStorage::add_calendars(&mut calendars);
+            calendars
+                .into_iter()
+                .find(|c| c.name == *w || c.href == *w)
+                .map(|c| (c.account, c.href))
+                .ok_or_else(|| anyhow::anyhow!("Unknown calendar: {}", w))
+        }
+    }
+}
+
+// Splits `<option> <value>` (or `<option>=<value>`) off the arguments
+fn take_option(args: &[String], option: &str) -> (Vec<String>, Option<String>) {
+    let mut rest = Vec::new();
+    let mut value = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == option {
+            value = Some(iter.next().cloned().unwrap_or_default());
+        } else if let Some(v) = arg.strip_prefix(&format!("{}=", option)) {
+            value = Some(v.to_string());
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    (rest, value)
+}
+
+// Splits `--format <name>` off the arguments. Without it, the file extension decides.
+fn file_format(args: &[String]) -> Result<(Vec<String>, Option<FileFormat>)> {
+    let (rest, name) = take_option(args, "--format");
+    let format = match name {
+        Some(name) => Some(FileFormat::from_name(&name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown format: {} (ics, taskwarrior, org, csv, markdown)",
+                name
+            )
+        })?),
+        None => None,
+    };
+    Ok((rest, format))
+}
+
+async fn import_tasks(cfg: &config::Config, args: &[String]) -> Result<()> {
+    let (args, format) = file_format(args)?;
+    let Some(file) = args.first() else {
+        anyhow::bail!("Usage: cfait import <file> [calendar] [--format ics|taskwarrior]");
+    };
+    let format = format.unwrap_or_else(|| FileFormat::from_path(Path::new(file)));
+    let mut manager = connect(cfg).await?;
+    let (account, href) = resolve_calendar(cfg, &mut manager, args.get(1)).await?;
+
+    let count = manager
+        .client_for(account.as_deref())
+        .import_file(Path::new(file), &href, format)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    println!("Imported {} task(s).", count);
+    if !Journal::load()?.is_empty() {
+        println!("Some changes are not uploaded yet, run `cfait sync` when online.");
+    }
+    Ok(())
+}
+
+async fn export_tasks(cfg: &config::Config, args: &[String]) -> Result<()> {
+    let (args, format) = file_format(args)?;
+    let (args, filter) = take_option(&args, "--filter");
+    let Some(file) = args.first() else {
+        anyhow::bail!(
+            "Usage: cfait export <file> [calendar] [--format <format>] [--filter <query>]"
+        );
+    };
+    let format = format.unwrap_or_else(|| FileFormat::from_path(Path::new(file)));
+    if let Some(query) = filter {
+        return export_filtered(cfg, file, args.get(1), &query, format);
+    }
+    let mut manager = connect(cfg).await?;
+    let (account, href) = resolve_calendar(cfg, &mut manager, args.get(1)).await?;
+
+    let count = manager
+        .client_for(account.as_deref())
+        .export_file(&href, Path::new(file), format)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    println!("Exported {} task(s) to {}.", count, file);
+    Ok(())
+}
+
+/// `cfait list [query]`: tasks of the offline cache (as of the last sync) matching
+/// a filter expression, completed ones only if the query asks for a status.
+fn list_tasks(cfg: &config::Config, args: &[String]) -> Result<()> {
+    let (store, calendars) = load_cached_store();
+    let tasks = query_tasks(cfg, &store, &calendars, &args.join(" "), None)?;
+    for t in &tasks {
+        let mut line = format!("{} {}", t.checkbox_symbol(), t.summary);
+        if t.priority > 0 {
+            line.push_str(&format!(" !{}", t.priority));
+        }
+        if let Some(due) = t.format_due("%Y-%m-%d") {
+            line.push_str(&format!(" @{}", due));
+        }
+        for cat in &t.categories {
+            line.push_str(&format!(" #{}", cat));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// `cfait export <file> [calendar] --filter <query>`: the tasks `cfait list` would print,
+/// written to a file (a CSV report, usually). Works from the cache, offline.
+fn export_filtered(
+    cfg: &config::Config,
+    file: &str,
+    calendar: Option<&String>,
+    query: &str,
+    format: FileFormat,
+) -> Result<()> {
+    let (store, calendars) = load_cached_store();
+    let cal = match calendar {
+        Some(w) => Some(
+            calendars
+                .iter()
+                .find(|c| c.name == *w || c.href == *w)
+                .ok_or_else(|| anyhow::anyhow!("Unknown calendar: {}", w))?,
+        ),
+        None => None,
+    };
+    let tasks = query_tasks(cfg, &store, &calendars, query, cal.map(|c| c.href.as_str()))?;
+    let title = cal.map_or(query, |c| c.name.as_str());
+    std::fs::write(file, format.write(&tasks, title, &calendars))
+        .map_err(|e| anyhow::anyhow!("{}: {}", file, e))?;
+    println!("Exported {} task(s) to {}.", tasks.len(), file);
+    Ok(())
+}
+
+// Cached tasks matching a filter expression, in the configured order
+fn query_tasks(
+    cfg: &config::Config,
+    store: &TaskStore,
+    calendars: &[CalendarListEntry],
+    query: &str,
+    calendar_href: Option<&str>,
+) -> Result<Vec<Task>> {
+    let query = format!("{}{}", QUERY_PREFIX, query);
+    if let Some(err) = query_error(&query) {
+        anyhow::bail!("Invalid query: {}", err);
+    }
+
+    let hidden: HashSet<String> = cfg.disabled_calendars.iter().cloned().collect();
+    let hide_completed_in =
+        config::CalendarPrefs::hide_completed_by_href(&cfg.calendars, calendars);
+    Ok(store.filter(FilterOptions {
+        active_cal_href: calendar_href,
+        hidden_calendars: &hidden,
+        selected_categories: &HashSet::new(),
+        match_all_categories: false,
+        search_term: &query,
+        calendars,
+        hide_completed_global: true,
+        hide_completed_in: &hide_completed_in,
+        cutoff_date: None,
+        min_duration: None,
+        max_duration: None,
+        include_unset_duration: true,
+        sort_order: cfg.sort_order,
+        selected_context: None,
+        smart_list: None,
+    }))
+}
+
+/// `cfait search <words>`: full-text search of the cached tasks (summary, description,
+/// tags) through the cache's search index, completed tasks included.
+fn search_tasks(cfg: &config::Config, args: &[String]) -> Result<()> {
+    if args.is_empty() {
+        anyhow::bail!("Usage: cfait search <words>");
+    }
+    let mut calendars = Cache::load_calendars().unwrap_or_default();
+    LocalStorage::add_calendars(&mut calendars);
+    for t in Cache::search(&args.join(" ")) {
+        if cfg.disabled_calendars.contains(&t.calendar_href) {
+            continue;
+        }
+        let cal = calendars
+            .iter()
+            .find(|c| c.href == t.calendar_href)
+            .map_or(t.calendar_href.as_str(), |c| c.name.as_str());
+        println!("{} {}  ({})", t.checkbox_symbol(), t.summary, cal);
+    }
+    Ok(())
+}
+
+/// `cfait stats [weeks]`: the same numbers as the GUI statistics view, from the cache.
+fn print_stats(cfg: &config::Config, weeks: Option<&str>) -> Result<()> {
+    let weeks = match weeks {
+        Some(w) => w
+            .parse::<usize>()
+            .map_err(|_| anyhow::anyhow!("Not a number of weeks: {}", w))?,
+        None => 8,
+    };
+    let (store, calendars) = load_cached_store();
+    let hidden: HashSet<String> = cfg.disabled_calendars.iter().cloned().collect();
+    let stats = store.stats(&hidden, &calendars, weeks);
+
+    println!(
+        "Tasks: {} ({} open, {} done), {} overdue",
+        stats.total,
+        stats.open,
+        stats.total - stats.open,
+        stats.overdue
+    );
+    println!("\nBy status:");
+    for (status, count) in &stats.by_status {
+        println!("  {:<20} {:>5}", status, count);
+    }
+    println!("\nBy calendar:");
+    for (name, open, done) in &stats.by_calendar {
+        println!("  {:<20} {:>5} open {:>5} done", name, open, done);
+    }
+    if !stats.by_tag.is_empty() {
+        let mut tags: Vec<_> = stats.by_tag.iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        println!("\nOpen tasks by tag:");
+        for (tag, count) in tags.into_iter().take(15) {
+            println!("  #{:<19} {:>5}", tag, count);
+        }
+    }
+    if !stats.weeks.is_empty() {
+        println!("\nWeek of       created  completed  open  done");
+        for week in &stats.weeks {
+            let rate = week.completion_rate();
+            println!(
+                "  {}  {:>7}  {:>9}  {:>4}  {:>3.0}% {}",
+                week.start.format("%Y-%m-%d"),
+                week.created,
+                week.completed,
+                week.open,
+                rate * 100.0,
+                "#".repeat((rate * 20.0).round() as usize)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn doctor(cfg: &config::Config) -> Result<()> {
+    let journal = Journal::load()?;
+    if journal.is_empty() && journal.failed.is_empty() {
+        println!("No offline changes: everything is synced.");
+        return Ok(());
+    }
+    let now = chrono::Utc::now();
+    print!("{} change(s) waiting to be synced", journal.queue.len());
+    match journal.oldest() {
+        Some(oldest) => println!(
+            ", the oldest since {} ({} days).",
+            local_time(oldest),
+            (now - oldest).num_days()
+        ),
+        None => println!("."),
+    }
+    if let Some(warning) =
+        journal.health_warning(cfg.journal_max_entries, cfg.journal_max_days, now)
+    {
+        println!("Warning: {}.", warning);
+    }
+
+    let calendars = Cache::load_calendars().unwrap_or_default();
+    let cal_name = |href: &str| {
+        calendars
+            .iter()
+            .find(|c| c.href == href)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| href.to_string())
+    };
+
+    // Each account replays its own changes in order: the first one holds the rest back
+    let mut accounts: Vec<Option<&str>> = vec![None];
+    accounts.extend(cfg.accounts.iter().map(|a| Some(a.label.as_str())));
+    for account in accounts {
+        let Some((action, failure)) = journal.blocking(account) else {
+            continue;
+        };
+        let waiting = journal.queue_for(account).count();
+        println!();
+        println!(
+            "{} ({} change(s)):",
+            account.unwrap_or("Main account"),
+            waiting
+        );
+        println!("  First in line: {}", action);
+        println!("  Calendar:      {}", cal_name(action.calendar_href()));
+        if let Some(at) = journal.queued_at.get(&action.task().uid) {
+            println!("  Queued:        {}", local_time(*at));
+        }
+        match (failure, journal.failure_of(account)) {
+            (Some(f), _) => println!("  Last error:    {} ({})", f.error, local_time(f.at)),
+            // Failed on a change further down the queue (e.g. during a parallel sync)
+            (None, Some(f)) => println!("  Other error:   {} ({})", f.error, local_time(f.at)),
+            (None, None) => println!("  No failed attempt recorded yet."),
+        }
+    }
+
+    if !journal.failed.is_empty() {
+        println!();
+        println!("Given up on (not sent until retried):");
+        for f in &journal.failed {
+            println!(
+                "  {} in {}: {} ({})",
+                f.action,
+                cal_name(f.action.calendar_href()),
+                f.error,
+                local_time(f.at)
+            );
+        }
+    }
+
+    println!();
+    println!("Run `cfait sync` to retry. To give up on a change, or retry a failed one, use the");
+    println!("pending changes list (P in the TUI, the Unsynced badge in the GUI).");
+    Ok(())
+}
+
+fn local_time(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// `cfait cache [clear | prune <days> [MB]]`. Never touches the offline changes.
+fn cache_command(args: &[String]) -> Result<()> {
+    use crate::client::quota::format_bytes;
+    let number = |arg: Option<&String>, what: &str| -> Result<Option<u64>> {
+        arg.map(|a| {
+            a.parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("Not a number of {}: {}", what, a))
+        })
+        .transpose()
+    };
+    match args.first().map(String::as_str) {
+        None | Some("stats") => {
+            let stats = Cache::stats()?;
+            println!(
+                "{} calendar(s), {} task(s), {}",
+                stats.calendars,
+                stats.tasks,
+                format_bytes(stats.bytes)
+            );
+            if let Some(oldest) = stats.oldest {
+                println!("Least recently synced: {}", local_time(oldest));
+            }
+        }
+        Some("clear") => {
+            let cleared = Cache::clear()?;
+            println!(
+                "Cleared {} calendar cache(s). The next sync downloads them again.",
+                cleared
+            );
+        }
+        Some("prune") => {
+            let days = number(args.get(1), "days")?
+                .ok_or_else(|| anyhow::anyhow!("Usage: cfait cache prune <days> [MB]"))?;
+            let max_size =
+                number(args.get(2), "MB")?.map_or(u64::MAX, |mb| mb.saturating_mul(1024 * 1024));
+            let removed = Cache::prune(Duration::from_secs(days.saturating_mul(86400)), max_size)?;
+            println!("Removed {} calendar cache(s).", removed);
+        }
+        Some(other) => return Err(anyhow::anyhow!("Unknown cache command: {}", other)),
+    }
+    Ok(())
+}
+
+/// `cfait backup <file>`: tasks of every calendar, the journal and the aliases.
+fn backup(cfg: &config::Config, file: Option<&String>) -> Result<()> {
+    let Some(file) = file else {
+        anyhow::bail!("Usage: cfait backup <file>");
+    };
+    let backup = Backup::collect(cfg)?;
+    backup.write(Path::new(file))?;
+    let count: usize = backup.tasks.values().map(Vec::len).sum();
+    println!(
+        "Backed up {} task(s) of {} calendar(s) to {}.",
+        count,
+        backup.tasks.len(),
+        file
+    );
+    if cfg.encrypt_data {
+        println!("It is encrypted: restoring it elsewhere needs the key from this keyring.");
+    }
+    Ok(())
+}
+
+/// `cfait restore <file>`: merges a backup into what's there.
+fn restore(cfg: &mut config::Config, file: Option<&String>) -> Result<()> {
+    let Some(file) = file else {
+        anyhow::bail!("Usage: cfait restore <file>");
+    };
+    let summary = Backup::read(Path::new(file))?.restore(cfg)?;
+    if summary.aliases > 0 {
+        cfg.save()?;
+    }
+    println!(
+        "Restored {} task(s) in {} calendar(s), {} pending change(s), {} alias(es).",
+        summary.tasks, summary.calendars, summary.pending, summary.aliases
+    );
+    if summary.pending > 0 {
+        println!("Run `cfait sync` to upload the pending changes.");
+    }
+    Ok(())
+}
+
+/// `cfait local [add <name> | rename <name> <new name> | delete <name>]`.
+fn local_command(cfg: &config::Config, args: &[String]) -> Result<()> {
+    let find = |name: Option<&String>, usage: &str| -> Result<CalendarListEntry> {
+        let name = name.ok_or_else(|| anyhow::anyhow!("Usage: cfait local {}", usage))?;
+        LocalStorage::calendars()
+            .into_iter()
+            .find(|c| c.name == *name || c.href == *name)
+            .ok_or_else(|| anyhow::anyhow!("No local calendar named {}", name))
+    };
+    match args.first().map(String::as_str) {
+        None | Some("list") => {
+            for cal in LocalStorage::calendars() {
+                let count = LocalStorage::load_calendar(&cal.href)?.len();
+                println!("{} ({} task(s))", cal.name, count);
+            }
+        }
+        Some("add") => {
+            let name = args[1..].join(" ");
+            let cal = LocalStorage::create_calendar(&name)?;
+            println!("Added local calendar {}", cal.name);
+        }
+        Some("rename") => {
+            let cal = find(args.get(1), "rename <name> <new name>")?;
+            let name = args.get(2..).map(|a| a.join(" ")).unwrap_or_default();
+            LocalStorage::rename_calendar(&cal.href, &name)?;
+            println!("Renamed {} to {}", cal.name, name.trim());
+        }
+        Some("delete") => {
+            let cal = find(args.get(1), "delete <name>")?;
+            let tasks = LocalStorage::delete_calendar(&cal.href)?;
+            for task in &tasks {
+                Trash::add(task, cfg.trash_days)?;
+            }
+            println!(
+                "Deleted {}, {} task(s) moved to the trash",
+                cal.name,
+                tasks.len()
+            );
+        }
+        Some(other) => return Err(anyhow::anyhow!("Unknown local command: {}", other)),
+    }
+    Ok(())
+}
+
+/// Tasks of the offline cache (as of the last sync) and the local calendars.
+fn load_cached_store() -> (TaskStore, Vec<CalendarListEntry>) {
+    let mut store = TaskStore::new();
+    for (href, tasks) in LocalStorage::load_all() {
+        store.insert(href, tasks);
+    }
+    let mut calendars = Cache::load_calendars().unwrap_or_default();
+    for cal in calendars.iter().filter(|c| !is_local_calendar(&c.href)) {
+        if let Ok((tasks, _)) = Cache::load_for(cal) {
+            store.insert(cal.href.clone(), tasks);
+        }
+    }
+    LocalStorage::add_calendars(&mut calendars);
+    (store, calendars)
+}
+
+// Basic auth with the secret in the OS keyring: the only case we can re-prompt for
+fn uses_keyring_password(cfg: &config::Config) -> bool {
+    cfg.credentials == config::CredentialStore::Keyring
+        && cfg.auth == config::AuthMode::Basic
+        && !cfg.url.is_empty()
+}
+
+/// Reads a password from the terminal without echoing it.
+fn prompt_password(prompt: &str) -> Result<String> {
+    use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+    use std::io::Write;
+
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    enable_raw_mode()?;
+    // A closure so a read error gets out of raw mode too
+    let read_password = || -> Result<String> {
+        let mut password = String::new();
+        loop {
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match key.code {
+                    KeyCode::Enter => return Ok(password),
+                    KeyCode::Backspace => {
+                        password.pop();
+                    }
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        anyhow::bail!("Password prompt cancelled");
+                    }
+                    KeyCode::Char(c) => password.push(c),
+                    _ => {}
+                }
+            }
+        }
+    };
+    let result = read_password();
+    disable_raw_mode()?;
+    println!();
+    result
+}
@@ -1,18 +1,137 @@
 // File: ./src/tui/network.rs
 // New file: Encapsulates the network actor logic
 use crate::cache::Cache;
-use crate::client::RustyClient;
-use crate::model::CalendarListEntry;
-use crate::storage::{LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME, LocalStorage};
+use crate::client::core::{ConnectionOptions, FetchProgress};
+use crate::client::push::{self, ChangeEvent};
+use crate::client::{AccountManager, ClientError};
+use crate::config::Config;
+use crate::journal::{Action as JournalAction, Journal};
+use crate::model::{CalendarListEntry, Task, TaskStatus};
+use crate::storage::{LocalStorage, is_local_calendar};
 use crate::tui::action::{Action, AppEvent};
-use tokio::sync::mpsc::{Receiver, Sender};
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+
+// Last known server calendars, used when we can't (or shouldn't) ask the server
+fn cached_calendars() -> Vec<CalendarListEntry> {
+    let mut cals = Cache::load_calendars().unwrap_or_default();
+    cals.retain(|c| !is_local_calendar(&c.href));
+    cals
+}
+
+// What to show for a failed request. Network trouble isn't fatal (the cache is
+// still there), bad credentials or certificates need the user to edit the config.
+fn error_event(config: &Config, context: &str, e: ClientError) -> AppEvent {
+    let config_advice = format!(
+        "\n\nTo fix this, please edit your config file:\n  {}",
+        Config::get_path_string().unwrap_or_else(|_| "path unknown".to_string())
+    );
+    match e {
+        ClientError::Unauthorized(_) => {
+            let mut msg = format!("{}: {}", context, e);
+            if config.credentials == crate::config::CredentialStore::Keyring {
+                msg.push_str(
+                    "\nThe password is kept in the OS keyring: remove the 'cfait' entry \
+                     to be asked for it on the next start.",
+                );
+            } else {
+                msg.push_str(&config_advice);
+            }
+            AppEvent::Error(msg)
+        }
+        ClientError::Tls(_) => {
+            let mut msg = "Connection failed: The server presented an invalid TLS/SSL certificate."
+                .to_string();
+            if !config.allow_insecure_certs {
+                msg.push_str(
+                    "\nIf this is a self-hosted server, set 'allow_insecure_certs = true'.",
+                );
+            }
+            msg.push_str(&config_advice);
+            AppEvent::Error(msg)
+        }
+        e if e.is_connectivity() => AppEvent::Status(format!("{}: {}", context, e)),
+        e => AppEvent::Error(format!("{}: {}", context, e)),
+    }
+}
+
+// A toggle or delete that already reached the server, so the journal can't revert it
+enum Undoable {
+    Toggled(Task),
+    Deleted(Task),
+}
+
+// Reverts the last queued change, or else the last toggle/delete. Returns what was done.
+async fn undo(
+    manager: &AccountManager,
+    last_change: Option<Undoable>,
+    event_tx: &Sender<AppEvent>,
+) -> Result<String, ClientError> {
+    if let Some(inverse) = manager.undo_last().await? {
+        let summary = inverse.task().summary.clone();
+        let msg = match &inverse {
+            JournalAction::Create(_) => format!("Undo: restored \"{}\"", summary),
+            JournalAction::Delete(_) => format!("Undo: removed new task \"{}\"", summary),
+            JournalAction::Update(_) => format!("Undo: reverted changes to \"{}\"", summary),
+            JournalAction::Move(..) => format!("Undo: moved \"{}\" back", summary),
+        };
+        let _ = event_tx.send(AppEvent::Undone(inverse)).await;
+        return Ok(msg);
+    }
+
+    let (client, href, msg) = match last_change {
+        None => return Ok("Nothing to undo.".to_string()),
+        Some(Undoable::Deleted(task)) => {
+            let client = manager.client_of(&task);
+            client.undelete(&task.uid).await?;
+            (
+                client,
+                task.calendar_href,
+                format!("Undo: restored \"{}\"", task.summary),
+            )
+        }
+        Some(Undoable::Toggled(task)) => {
+            // The server copy, its etag changed with the toggle
+            let client = manager.client_of(&task);
+            let mut current = client
+                .get_tasks(&task.calendar_href)
+                .await?
+                .into_iter()
+                .find(|t| t.uid == task.uid)
+                .ok_or_else(|| ClientError::NotFound(task.summary.clone()))?;
+            let (status, label) = if current.status == TaskStatus::Completed {
+                (TaskStatus::NeedsAction, "not done")
+            } else {
+                (TaskStatus::Completed, "done")
+            };
+            current.set_status(status);
+            client.update_task(&mut current).await?;
+            (
+                client,
+                task.calendar_href,
+                format!("Undo: \"{}\" is {} again", task.summary, label),
+            )
+        }
+    };
+    if let Ok(t) = client.get_tasks(&href).await {
+        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+    }
+    Ok(msg)
+}
+
+// Same, for a change that's already in the journal: if the server can't be
+// reached it simply goes out on the next sync.
+fn change_error_event(config: &Config, context: &str, e: ClientError) -> AppEvent {
+    if e.is_connectivity() {
+        AppEvent::Status("Offline: change queued, press S to sync later.".to_string())
+    } else {
+        error_event(config, context, e)
+    }
+}
 
 pub async fn run_network_actor(
-    url: String,
-    user: String,
-    pass: String,
-    allow_insecure: bool,
-    _default_cal: Option<String>,
+    config: Config,
     mut action_rx: Receiver<Action>,
     event_tx: Sender<AppEvent>,
 ) {
@@ -20,27 +139,16 @@ pub async fn run_network_actor(
     // 0. LOAD CACHE IMMEDIATELY
     // ------------------------------------------------------------------
     if let Ok(mut cached_cals) = Cache::load_calendars() {
-        let local_cal = CalendarListEntry {
-            name: LOCAL_CALENDAR_NAME.to_string(),
-            href: LOCAL_CALENDAR_HREF.to_string(),
-            color: None,
-        };
-        if !cached_cals.iter().any(|c| c.href == LOCAL_CALENDAR_HREF) {
-            cached_cals.push(local_cal);
-        }
+        LocalStorage::add_calendars(&mut cached_cals);
 
         let _ = event_tx
             .send(AppEvent::CalendarsLoaded(cached_cals.clone()))
             .await;
 
-        let mut cached_tasks = Vec::new();
-        if let Ok(local_t) = LocalStorage::load() {
-            cached_tasks.push((LOCAL_CALENDAR_HREF.to_string(), local_t));
-        }
-
+        let mut cached_tasks = LocalStorage::load_all();
         for cal in &cached_cals {
-            if cal.href != LOCAL_CALENDAR_HREF
-                && let Ok((tasks, _)) = Cache::load(&cal.href)
+            if !is_local_calendar(&cal.href)
+                && let Ok((tasks, _)) = Cache::load_for(cal)
             {
                 cached_tasks.push((cal.href.clone(), tasks));
             }
@@ -54,53 +162,48 @@ pub async fn run_network_actor(
     // ------------------------------------------------------------------
     // 1. CONNECT & SYNC
     // ------------------------------------------------------------------
-    let client = match RustyClient::new(&url, &user, &pass, allow_insecure) {
+    let mut manager = match AccountManager::from_config(&config).await {
         Ok(c) => c,
         Err(e) => {
-            let _ = event_tx.send(AppEvent::Error(e)).await;
+            let _ = event_tx
+                .send(error_event(&config, "Connection failed", e))
+                .await;
             return;
         }
     };
     let _ = event_tx
         .send(AppEvent::Status("Connecting...".to_string()))
         .await;
+    manager.bootstrap().await;
+
+    // Large calendars arrive in batches: show each one as soon as it's parsed. The
+    // callback isn't async: wait for room in the channel (off the runtime) rather
+    // than drop batches while the UI is busy.
+    let progress_tx = event_tx.clone();
+    manager = manager.with_progress(FetchProgress(Arc::new(move |href, tasks| {
+        let event = AppEvent::TasksLoaded(vec![(href.to_string(), tasks.to_vec())]);
+        // Only fails once the UI is gone
+        let _ = tokio::task::block_in_place(|| progress_tx.blocking_send(event));
+    })));
 
-    let mut calendars = match client.get_calendars().await {
+    let mut calendars = match manager.get_calendars().await {
         Ok(cals) => cals,
+        // Retrying won't help until the config is fixed
+        Err(e @ (ClientError::Unauthorized(_) | ClientError::Tls(_))) => {
+            let _ = event_tx
+                .send(error_event(&config, "Connection failed", e))
+                .await;
+            return;
+        }
         Err(e) => {
-            let err_str = e.to_string();
-            if err_str.contains("InvalidCertificate") {
-                let mut helpful_msg =
-                    "Connection failed: The server presented an invalid TLS/SSL certificate."
-                        .to_string();
-                let config_advice = format!(
-                    "\n\nTo fix this, please edit your config file:\n  {}",
-                    crate::config::Config::get_path_string()
-                        .unwrap_or_else(|_| "path unknown".to_string())
-                );
-                if !allow_insecure {
-                    helpful_msg.push_str(
-                        "\nIf this is a self-hosted server, set 'allow_insecure_certs = true'.",
-                    );
-                }
-                helpful_msg.push_str(&config_advice);
-                let _ = event_tx.send(AppEvent::Error(helpful_msg)).await;
-                return;
-            } else {
-                let _ = event_tx
-                    .send(AppEvent::Status(format!("Sync warning: {}", err_str)))
-                    .await;
-                vec![]
-            }
+            let _ = event_tx
+                .send(AppEvent::Status(format!("Sync warning: {}", e)))
+                .await;
+            cached_calendars()
         }
     };
 
-    let local_cal = CalendarListEntry {
-        name: LOCAL_CALENDAR_NAME.to_string(),
-        href: LOCAL_CALENDAR_HREF.to_string(),
-        color: None,
-    };
-    calendars.push(local_cal);
+    LocalStorage::add_calendars(&mut calendars);
 
     let _ = event_tx
         .send(AppEvent::CalendarsLoaded(calendars.clone()))
@@ -113,8 +216,8 @@ pub async fn run_network_actor(
     // Load tasks again with validated calendars list
     let mut cached_results = Vec::new();
     for cal in &calendars {
-        if cal.href != LOCAL_CALENDAR_HREF
-            && let Ok((tasks, _)) = Cache::load(&cal.href)
+        if !is_local_calendar(&cal.href)
+            && let Ok((tasks, _)) = Cache::load_for(cal)
         {
             cached_results.push((cal.href.clone(), tasks));
         }
@@ -123,7 +226,7 @@ pub async fn run_network_actor(
         let _ = event_tx.send(AppEvent::TasksLoaded(cached_results)).await;
     }
 
-    match client.get_all_tasks(&calendars).await {
+    match manager.get_all_tasks(&calendars).await {
         Ok(results) => {
             let _ = event_tx.send(AppEvent::TasksLoaded(results)).await;
             let _ = event_tx.send(AppEvent::Status("Ready.".to_string())).await;
@@ -134,41 +237,142 @@ pub async fn run_network_actor(
                 .await;
         }
     }
+    // Last, or "Ready." would hide it
+    if !manager.skipped.is_empty() {
+        let _ = event_tx
+            .send(AppEvent::Status(format!(
+                "Sync warning: {}",
+                manager.skipped.join("; ")
+            )))
+            .await;
+    }
+    send_quota(&manager, &event_tx).await;
+    send_conflicts(&event_tx).await;
+    send_reachable(&manager, &event_tx).await;
 
     // ------------------------------------------------------------------
-    // 2. ACTION LOOP
+    // 2. PUSH (optional): refresh calendars when the server says they changed
     // ------------------------------------------------------------------
-    while let Some(action) = action_rx.recv().await {
-        match action {
-            Action::Quit => break,
-            Action::SwitchCalendar(href) => match client.get_tasks(&href).await {
-                Ok(t) => {
-                    let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+    let (push_tx, mut push_rx) = mpsc::channel::<ChangeEvent>(16);
+    if let Some(endpoint) = config.push_endpoint.clone() {
+        let (topics, errors) = manager.subscribe_push(&calendars, &endpoint).await;
+        if !errors.is_empty() {
+            let _ = event_tx
+                .send(AppEvent::Status(format!("Push: {}", errors.join("; "))))
+                .await;
+        }
+        if !topics.is_empty() {
+            match push::listen(&endpoint, &ConnectionOptions::from_config(&config), topics) {
+                Ok(events) => {
+                    tokio::spawn(async move {
+                        let mut events = std::pin::pin!(events);
+                        while let Some(event) = events.next().await {
+                            if push_tx.send(event).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
                 }
                 Err(e) => {
-                    let _ = event_tx.send(AppEvent::Error(e)).await;
+                    let _ = event_tx
+                        .send(AppEvent::Status(format!("Push: {}", e)))
+                        .await;
                 }
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // 3. ACTION LOOP
+    // ------------------------------------------------------------------
+    let mut last_change: Option<Undoable> = None;
+    loop {
+        let action = tokio::select! {
+            action = action_rx.recv() => match action {
+                Some(a) => a,
+                None => break,
             },
-            Action::IsolateCalendar(href) => match client.get_tasks(&href).await {
-                Ok(t) => {
-                    let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+            Some(event) = push_rx.recv() => {
+                // Only the calendar that changed, unless the topic is unknown
+                let results = match event.calendar_href {
+                    Some(href) => manager
+                        .client_of_calendar(&href)
+                        .get_tasks(&href)
+                        .await
+                        .map(|t| vec![(href, t)]),
+                    None => manager.get_all_tasks(&calendars).await,
+                };
+                if let Ok(results) = results {
+                    let _ = event_tx.send(AppEvent::TasksLoaded(results)).await;
                 }
-                Err(e) => {
-                    let _ = event_tx.send(AppEvent::Error(e)).await;
+                continue;
+            }
+        };
+        // What 'u' falls back to once the change has left the journal
+        match &action {
+            Action::ToggleTask(t) => last_change = Some(Undoable::Toggled(t.clone())),
+            Action::DeleteTask(t) => last_change = Some(Undoable::Deleted(t.clone())),
+            Action::CreateTask(_)
+            | Action::UpdateTask(_)
+            | Action::MarkInProcess(_)
+            | Action::MarkCancelled(_)
+            | Action::MoveTask(..)
+            | Action::Batch(..)
+            | Action::MigrateLocal(..)
+            | Action::Undelete(..)
+            | Action::ResolveConflict(..) => last_change = None,
+            _ => {}
+        }
+        match action {
+            Action::Quit => break,
+            Action::Undo => {
+                let msg = match undo(&manager, last_change.take(), &event_tx).await {
+                    Ok(msg) => AppEvent::Status(msg),
+                    Err(e) => change_error_event(&config, "Undo failed", e),
+                };
+                let _ = event_tx.send(msg).await;
+            }
+            Action::SwitchCalendar(href) => {
+                match manager.client_of_calendar(&href).get_tasks(&href).await {
+                    Ok(t) => {
+                        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                    }
+                    Err(e) => {
+                        let _ = event_tx.send(error_event(&config, "Fetch failed", e)).await;
+                    }
                 }
-            },
-            Action::ToggleCalendarVisibility(href) => match client.get_tasks(&href).await {
-                Ok(t) => {
-                    let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+            }
+            Action::IsolateCalendar(href) => {
+                match manager.client_of_calendar(&href).get_tasks(&href).await {
+                    Ok(t) => {
+                        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                    }
+                    Err(e) => {
+                        let _ = event_tx.send(error_event(&config, "Fetch failed", e)).await;
+                    }
+                }
+            }
+            Action::FetchAll(calendars) => match manager.get_all_tasks(&calendars).await {
+                Ok(results) => {
+                    let _ = event_tx.send(AppEvent::TasksLoaded(results)).await;
                 }
                 Err(e) => {
-                    let _ = event_tx
-                        .send(AppEvent::Error(format!("Fetch failed: {}", e)))
-                        .await;
+                    let _ = event_tx.send(error_event(&config, "Fetch failed", e)).await;
                 }
             },
+            Action::ToggleCalendarVisibility(href) => {
+                match manager.client_of_calendar(&href).get_tasks(&href).await {
+                    Ok(t) => {
+                        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                    }
+                    Err(e) => {
+                        let _ = event_tx.send(error_event(&config, "Fetch failed", e)).await;
+                    }
+                }
+            }
             Action::CreateTask(mut new_task) => {
                 let href = new_task.calendar_href.clone();
+                let client = manager.client_of(&new_task);
                 match client.create_task(&mut new_task).await {
                     Ok(msgs) => {
                         if let Ok(t) = client.get_tasks(&href).await {
@@ -182,12 +386,15 @@ pub async fn run_network_actor(
                         let _ = event_tx.send(AppEvent::Status(s)).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Create failed", e))
+                            .await;
                     }
                 }
             }
             Action::UpdateTask(mut task) => {
                 let href = task.calendar_href.clone();
+                let client = manager.client_of(&task);
                 match client.update_task(&mut task).await {
                     Ok(msgs) => {
                         let s = if msgs.is_empty() {
@@ -198,7 +405,9 @@ pub async fn run_network_actor(
                         let _ = event_tx.send(AppEvent::Status(s)).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Save failed", e))
+                            .await;
                         // On error, reload to revert
                         if let Ok(t) = client.get_tasks(&href).await {
                             let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
@@ -209,11 +418,12 @@ pub async fn run_network_actor(
             Action::ToggleTask(mut task) => {
                 let href = task.calendar_href.clone();
                 if task.status == crate::model::TaskStatus::Completed {
-                    task.status = crate::model::TaskStatus::NeedsAction;
+                    task.set_status(crate::model::TaskStatus::NeedsAction);
                 } else {
-                    task.status = crate::model::TaskStatus::Completed;
+                    task.set_status(crate::model::TaskStatus::Completed);
                 }
 
+                let client = manager.client_of(&task);
                 match client.toggle_task(&mut task).await {
                     Ok((_, _, msgs)) => {
                         let s = if msgs.is_empty() {
@@ -227,7 +437,9 @@ pub async fn run_network_actor(
                         }
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Save failed", e))
+                            .await;
                         if let Ok(t) = client.get_tasks(&href).await {
                             let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
                         }
@@ -236,6 +448,7 @@ pub async fn run_network_actor(
             }
             Action::DeleteTask(task) => {
                 let href = task.calendar_href.clone();
+                let client = manager.client_of(&task);
                 match client.delete_task(&task).await {
                     Ok(msgs) => {
                         let s = if msgs.is_empty() {
@@ -246,7 +459,9 @@ pub async fn run_network_actor(
                         let _ = event_tx.send(AppEvent::Status(s)).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Delete failed", e))
+                            .await;
                         if let Ok(t) = client.get_tasks(&href).await {
                             let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
                         }
@@ -258,44 +473,45 @@ pub async fn run_network_actor(
                     .send(AppEvent::Status("Refreshing...".to_string()))
                     .await;
 
-                let mut calendars = match client.get_calendars().await {
+                let mut calendars = match manager.get_calendars().await {
                     Ok(c) => c,
+                    Err(_) if manager.is_offline() => cached_calendars(),
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
-                        vec![]
+                        let _ = event_tx
+                            .send(error_event(&config, "Refresh failed", e))
+                            .await;
+                        cached_calendars()
                     }
                 };
 
-                let local_cal = CalendarListEntry {
-                    name: LOCAL_CALENDAR_NAME.to_string(),
-                    href: LOCAL_CALENDAR_HREF.to_string(),
-                    color: None,
-                };
-                calendars.push(local_cal);
+                LocalStorage::add_calendars(&mut calendars);
 
                 let _ = event_tx
                     .send(AppEvent::CalendarsLoaded(calendars.clone()))
                     .await;
 
-                match client.get_all_tasks(&calendars).await {
+                match manager.get_all_tasks(&calendars).await {
                     Ok(results) => {
                         let _ = event_tx.send(AppEvent::TasksLoaded(results)).await;
                         let _ = event_tx
                             .send(AppEvent::Status("Refreshed.".to_string()))
                             .await;
+                        send_quota(&manager, &event_tx).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(error_event(&config, "Refresh failed", e))
+                            .await;
                     }
                 }
             }
             Action::MarkInProcess(mut task) => {
                 if task.status == crate::model::TaskStatus::InProcess {
-                    task.status = crate::model::TaskStatus::NeedsAction;
+                    task.set_status(crate::model::TaskStatus::NeedsAction);
                 } else {
-                    task.status = crate::model::TaskStatus::InProcess;
+                    task.set_status(crate::model::TaskStatus::InProcess);
                 }
-                match client.update_task(&mut task).await {
+                match manager.client_of(&task).update_task(&mut task).await {
                     Ok(msgs) => {
                         let s = if msgs.is_empty() {
                             "Saved.".to_string()
@@ -305,17 +521,19 @@ pub async fn run_network_actor(
                         let _ = event_tx.send(AppEvent::Status(s)).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Save failed", e))
+                            .await;
                     }
                 }
             }
             Action::MarkCancelled(mut task) => {
                 if task.status == crate::model::TaskStatus::Cancelled {
-                    task.status = crate::model::TaskStatus::NeedsAction;
+                    task.set_status(crate::model::TaskStatus::NeedsAction);
                 } else {
-                    task.status = crate::model::TaskStatus::Cancelled;
+                    task.set_status(crate::model::TaskStatus::Cancelled);
                 }
-                match client.update_task(&mut task).await {
+                match manager.client_of(&task).update_task(&mut task).await {
                     Ok(msgs) => {
                         let s = if msgs.is_empty() {
                             "Saved.".to_string()
@@ -325,26 +543,28 @@ pub async fn run_network_actor(
                         let _ = event_tx.send(AppEvent::Status(s)).await;
                     }
                     Err(e) => {
-                        let _ = event_tx.send(AppEvent::Error(e)).await;
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Save failed", e))
+                            .await;
                     }
                 }
             }
             Action::MoveTask(task, new_href) => {
                 let old_href = task.calendar_href.clone();
-                match client.move_task(&task, &new_href).await {
-                    Ok((_, msgs)) => {
+                match manager.move_task(&task, &new_href).await {
+                    Ok((moved, msgs)) => {
                         let s = if msgs.is_empty() {
                             "Moved.".to_string()
                         } else {
                             msgs.join("; ")
                         };
                         let _ = event_tx.send(AppEvent::Status(s)).await;
-                        if let Ok(t1) = client.get_tasks(&old_href).await {
+                        if let Ok(t1) = manager.client_of(&task).get_tasks(&old_href).await {
                             let _ = event_tx
                                 .send(AppEvent::TasksLoaded(vec![(old_href, t1)]))
                                 .await;
                         }
-                        if let Ok(t2) = client.get_tasks(&new_href).await {
+                        if let Ok(t2) = manager.client_of(&moved).get_tasks(&new_href).await {
                             let _ = event_tx
                                 .send(AppEvent::TasksLoaded(vec![(new_href, t2)]))
                                 .await;
@@ -352,33 +572,73 @@ pub async fn run_network_actor(
                     }
                     Err(e) => {
                         let _ = event_tx
-                            .send(AppEvent::Error(format!("Move failed: {}", e)))
+                            .send(change_error_event(&config, "Move failed", e))
                             .await;
                     }
                 }
             }
-            Action::MigrateLocal(target_href) => {
-                if let Ok(local_tasks) = LocalStorage::load() {
+            Action::Batch(actions, done) => {
+                // Reload every calendar involved afterwards, both ends of moves
+                let mut hrefs: Vec<String> = Vec::new();
+                for action in &actions {
+                    let mut involved = vec![action.calendar_href()];
+                    if let JournalAction::Move(_, dest) = action {
+                        involved.push(dest);
+                    }
+                    for href in involved {
+                        if !hrefs.iter().any(|h| h == href) {
+                            hrefs.push(href.to_string());
+                        }
+                    }
+                }
+                match manager.apply_batch(actions).await {
+                    Ok(msgs) => {
+                        let s = if msgs.is_empty() {
+                            done
+                        } else {
+                            msgs.join("; ")
+                        };
+                        let _ = event_tx.send(AppEvent::Status(s)).await;
+                    }
+                    Err(e) => {
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Bulk change failed", e))
+                            .await;
+                    }
+                }
+                for href in hrefs {
+                    if let Ok(t) = manager.client_of_calendar(&href).get_tasks(&href).await {
+                        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                    }
+                }
+            }
+            Action::MigrateLocal(source_href, target_href) => {
+                if let Ok(local_tasks) = LocalStorage::load_calendar(&source_href) {
                     let _ = event_tx
                         .send(AppEvent::Status(format!(
                             "Exporting {} tasks...",
                             local_tasks.len()
                         )))
                         .await;
-                    match client.migrate_tasks(local_tasks, &target_href).await {
+                    match manager
+                        .client_of_calendar(&target_href)
+                        .migrate_tasks(local_tasks, &target_href)
+                        .await
+                    {
                         Ok(count) => {
                             let _ = event_tx
                                 .send(AppEvent::Status(format!("Exported {} tasks.", count)))
                                 .await;
-                            if let Ok(t1) = client.get_tasks(LOCAL_CALENDAR_HREF).await {
+                            if let Ok(t1) = manager.primary().get_tasks(&source_href).await {
                                 let _ = event_tx
-                                    .send(AppEvent::TasksLoaded(vec![(
-                                        LOCAL_CALENDAR_HREF.to_string(),
-                                        t1,
-                                    )]))
+                                    .send(AppEvent::TasksLoaded(vec![(source_href, t1)]))
                                     .await;
                             }
-                            if let Ok(t2) = client.get_tasks(&target_href).await {
+                            if let Ok(t2) = manager
+                                .client_of_calendar(&target_href)
+                                .get_tasks(&target_href)
+                                .await
+                            {
                                 let _ = event_tx
                                     .send(AppEvent::TasksLoaded(vec![(target_href, t2)]))
                                     .await;
@@ -386,7 +646,7 @@ pub async fn run_network_actor(
                         }
                         Err(e) => {
                             let _ = event_tx
-                                .send(AppEvent::Error(format!("Export failed: {}", e)))
+                                .send(error_event(&config, "Export failed", e))
                                 .await;
                         }
                     }
@@ -395,6 +655,115 @@ pub async fn run_network_actor(
             Action::StartCreateChild(_parent_uid) => {
                 // UI logic only
             }
+            Action::Undelete(uid, href) => match manager.undelete(&uid).await {
+                Ok((task, msgs)) => {
+                    if let Ok(t) = manager.client_of(&task).get_tasks(&href).await {
+                        let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                    }
+                    let s = if msgs.is_empty() {
+                        "Restored.".to_string()
+                    } else {
+                        msgs.join("; ")
+                    };
+                    let _ = event_tx.send(AppEvent::Status(s)).await;
+                }
+                Err(e) => {
+                    let _ = event_tx
+                        .send(change_error_event(&config, "Restore failed", e))
+                        .await;
+                }
+            },
+            Action::ResolveConflict(href, uid, keep) => {
+                match manager.resolve_conflict(&uid, keep).await {
+                    Ok((_, msgs)) => {
+                        if let Ok(t) = manager.client_of_calendar(&href).get_tasks(&href).await {
+                            let _ = event_tx.send(AppEvent::TasksLoaded(vec![(href, t)])).await;
+                        }
+                        let s = if msgs.is_empty() {
+                            "Conflict resolved.".to_string()
+                        } else {
+                            msgs.join("; ")
+                        };
+                        let _ = event_tx.send(AppEvent::Status(s)).await;
+                    }
+                    Err(e) => {
+                        let _ = event_tx
+                            .send(change_error_event(&config, "Resolve failed", e))
+                            .await;
+                    }
+                }
+            }
+            Action::SyncNow => sync_now(&config, &manager, &calendars, &event_tx).await,
+            Action::ToggleOffline => {
+                let offline = !manager.is_offline();
+                manager.set_offline(offline);
+                let _ = event_tx.send(AppEvent::OfflineChanged(offline)).await;
+                if offline {
+                    let _ = event_tx
+                        .send(AppEvent::Status(
+                            "Offline mode: changes are queued until you sync (S).".to_string(),
+                        ))
+                        .await;
+                } else {
+                    // Back online: push what was queued right away
+                    sync_now(&config, &manager, &calendars, &event_tx).await;
+                }
+            }
+        }
+        // Any change may have been replayed and hit a conflict
+        send_conflicts(&event_tx).await;
+        send_reachable(&manager, &event_tx).await;
+    }
+}
+
+// For the status bar: every action tries to sync, the pending count changes too
+async fn send_reachable(manager: &AccountManager, event_tx: &Sender<AppEvent>) {
+    let _ = event_tx
+        .send(AppEvent::Reachable(!manager.is_unreachable()))
+        .await;
+}
+
+// Conflicts set aside by the interactive policy, for the UI to ask about
+async fn send_conflicts(event_tx: &Sender<AppEvent>) {
+    // An unreadable journal is reported by the status line already
+    let conflicts = Journal::load().map(|j| j.conflicts).unwrap_or_default();
+    if !conflicts.is_empty() {
+        let _ = event_tx.send(AppEvent::Conflicts(conflicts)).await;
+    }
+}
+
+// Storage usage of the primary account, when the server reports it
+async fn send_quota(manager: &AccountManager, event_tx: &Sender<AppEvent>) {
+    if let Ok(Some(quota)) = manager.primary().quota().await {
+        let _ = event_tx.send(AppEvent::QuotaLoaded(quota)).await;
+    }
+}
+
+// Flushes the journal on demand (works in offline mode too), then reloads tasks
+// so the new ETags/hrefs from the server are picked up.
+async fn sync_now(
+    config: &Config,
+    manager: &AccountManager,
+    calendars: &[CalendarListEntry],
+    event_tx: &Sender<AppEvent>,
+) {
+    let _ = event_tx
+        .send(AppEvent::Status("Syncing...".to_string()))
+        .await;
+    match manager.sync_journal().await {
+        Ok(warnings) => {
+            if let Ok(results) = manager.get_all_tasks(calendars).await {
+                let _ = event_tx.send(AppEvent::TasksLoaded(results)).await;
+            }
+            let s = if warnings.is_empty() {
+                "Synced.".to_string()
+            } else {
+                warnings.join("; ")
+            };
+            let _ = event_tx.send(AppEvent::Status(s)).await;
+        }
+        Err(e) => {
+            let _ = event_tx.send(error_event(config, "Sync failed", e)).await;
         }
     }
 }
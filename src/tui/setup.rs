@@ -0,0 +1,128 @@
+// File: ./src/tui/setup.rs
+// First run without a config file: asks for the server and the account, checks them
+// and writes config.toml, instead of sending people to the README.
+use crate::client::{ClientError, RustyClient};
+use crate::config::{Config, CredentialStore};
+use crate::model::CalendarListEntry;
+use anyhow::{Result, anyhow};
+use std::io::{self, Write};
+
+/// Runs the wizard on the terminal (line by line, before the TUI takes over) and
+/// returns the saved config.
+pub async fn run() -> Result<Config> {
+    println!("Welcome to cfait!");
+    println!(
+        "No config file yet, let's write {}",
+        Config::get_path_string()?
+    );
+    println!("Leave the URL empty to keep tasks on this device only. Ctrl+D cancels.");
+    println!();
+
+    let mut cfg = Config::default();
+    cfg.url = ask("CalDAV server URL (or just the domain)", "")?;
+    if cfg.url.is_empty() {
+        cfg.save()?;
+        println!("Offline mode: set `url` in the config file to sync later.");
+        return Config::load();
+    }
+    cfg.username = ask("Username", "")?;
+    cfg.password = super::prompt_password("Password: ")?;
+
+    loop {
+        println!("Connecting...");
+        match RustyClient::check_connection(&cfg).await {
+            Ok(calendars) => {
+                cfg.default_calendar = pick_calendar(&calendars)?;
+                break;
+            }
+            Err(ClientError::Unauthorized(_)) => {
+                println!("Login failed: please check your username and password.");
+                cfg.username = ask("Username", &cfg.username)?;
+                cfg.password = super::prompt_password("Password: ")?;
+            }
+            Err(ClientError::Tls(e)) if !cfg.allow_insecure_certs => {
+                println!("Certificate error: {}", e);
+                if !confirm("Allow insecure certificates (self-signed server)?", false)? {
+                    cfg.url = ask("CalDAV server URL", &cfg.url)?;
+                    continue;
+                }
+                cfg.allow_insecure_certs = true;
+            }
+            Err(e) => {
+                println!("Connection failed: {}", e);
+                if confirm("Save these settings anyway?", false)? {
+                    break;
+                }
+                cfg.url = ask("CalDAV server URL", &cfg.url)?;
+            }
+        }
+    }
+
+    if confirm(
+        "Keep the password in the system keyring instead of the file?",
+        true,
+    )? {
+        cfg.credentials = CredentialStore::Keyring;
+    }
+    if let Err(e) = cfg.save() {
+        if cfg.credentials != CredentialStore::Keyring {
+            return Err(e);
+        }
+        println!(
+            "Could not use the keyring ({}), the password goes in the file.",
+            e
+        );
+        cfg.credentials = CredentialStore::Plaintext;
+        cfg.save()?;
+    }
+    println!("Saved. Starting cfait...");
+    Config::load()
+}
+
+fn pick_calendar(calendars: &[CalendarListEntry]) -> Result<Option<String>> {
+    if calendars.is_empty() {
+        println!("No calendars on this account yet: tasks go to the local calendar.");
+        return Ok(None);
+    }
+    println!("Calendars:");
+    for (i, cal) in calendars.iter().enumerate() {
+        let note = if cal.read_only { " (read-only)" } else { "" };
+        println!("  {}. {}{}", i + 1, cal.name, note);
+    }
+    loop {
+        let choice = ask("Default calendar", "1")?;
+        match choice.parse::<usize>() {
+            Ok(n) if (1..=calendars.len()).contains(&n) => {
+                return Ok(Some(calendars[n - 1].name.clone()));
+            }
+            _ => println!("Pick a number from 1 to {}", calendars.len()),
+        }
+    }
+}
+
+// One line from stdin; an empty answer takes the default
+fn ask(prompt: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", prompt);
+    } else {
+        print!("{} [{}]: ", prompt, default);
+    }
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Err(anyhow!("Setup cancelled"));
+    }
+    let line = line.trim();
+    Ok(if line.is_empty() { default } else { line }.to_string())
+}
+
+fn confirm(prompt: &str, default: bool) -> Result<bool> {
+    let answer = ask(
+        &format!("{} ({})", prompt, if default { "Y/n" } else { "y/N" }),
+        "",
+    )?;
+    Ok(match answer.to_lowercase().as_str() {
+        "" => default,
+        a => a.starts_with('y'),
+    })
+}
@@ -1,7 +1,18 @@
 // File: ./src/tui/state.rs
-use crate::model::{CalendarListEntry, Task};
-use crate::store::{FilterOptions, TaskStore};
+use crate::client::quota::Quota;
+use crate::config::{CalendarPrefs, ThemeConfig};
+use crate::journal::{Action as JournalAction, Conflict, FailedAction, Journal};
+use crate::model::status::status_cycle;
+use crate::model::{
+    CalendarListEntry, CustomStatus, GroupBy, Recurrence, SortOrder, Task, TaskField,
+};
+use crate::storage::TrashEntry;
+use crate::store::{
+    EventQueue, FilterOptions, SmartList, TaskStore, agenda_groups, board_groups, flatten_groups,
+    group_tasks, patch_view,
+};
 use crate::tui::action::SidebarMode;
+use chrono::{Local, NaiveDate, Utc};
 use ratatui::widgets::ListState;
 use std::collections::{HashMap, HashSet};
 
@@ -20,11 +31,34 @@ pub enum InputMode {
     EditingDescription,
     Moving,
     Exporting,
+    Trash,
+    Pending,
+    Conflict,
+    Recurrence,
+    EditingFields,
+    // Tags for the marked tasks
+    Tagging,
+    // y/n before deleting (confirm_delete)
+    ConfirmDelete,
+}
+
+// Steps of the guided recurrence prompt
+#[derive(PartialEq, Clone, Copy)]
+pub enum RecurrenceStep {
+    Freq,
+    Interval,
+    Days,
+    End,
+    Mode,
 }
 
 pub struct AppState {
     // Data
     pub store: TaskStore,
+    // Changes to the store since the last refresh
+    pub store_events: EventQueue,
+    // Filter options and grouping `tasks` was built with
+    pub view_fingerprint: Option<(u64, GroupBy, Option<i64>, bool)>,
     pub tasks: Vec<Task>,
     pub calendars: Vec<CalendarListEntry>,
 
@@ -35,6 +69,8 @@ pub struct AppState {
     pub mode: InputMode,
     pub message: String,
     pub loading: bool,
+    // Input that got a warning: submitting it again unchanged saves it anyway
+    pub warned_input: Option<String>,
 
     // Filter State
     pub sidebar_mode: SidebarMode,
@@ -44,8 +80,30 @@ pub struct AppState {
     pub selected_categories: HashSet<String>,
     pub match_all_categories: bool,
     pub hide_completed: bool,
+    // Done tasks the current view leaves out because of hide_completed
+    pub hidden_done: usize,
+    // Agenda instead of the list ('v'): how many days it covers
+    pub agenda_days: Option<i64>,
+    // Kanban board ('B'): a column per status
+    pub board: bool,
     pub hide_fully_completed_tags: bool,
     pub sort_cutoff_months: Option<u32>,
+    pub sort_order: SortOrder,
+    pub group_by: GroupBy,
+    // Where each section of `tasks` starts, and its title
+    pub group_headers: Vec<(usize, String)>,
+    pub selected_context: Option<String>,
+    // Showing a smart list (Today, Flagged, ...) instead of the calendars
+    pub smart_list: Option<SmartList>,
+    // The "All" sidebar entry: tasks of every checked calendar, marked with their calendar
+    pub show_all: bool,
+    pub named_priorities: bool,
+    pub confirm_delete: bool,
+    // Task waiting for the y/n, the marked ones when None
+    pub deleting_uid: Option<String>,
+    pub custom_statuses: Vec<CustomStatus>,
+    // What 'w' cycles through
+    pub status_cycle: Vec<CustomStatus>,
 
     // Input Buffers
     pub input_buffer: String,
@@ -55,14 +113,48 @@ pub struct AppState {
     pub move_targets: Vec<CalendarListEntry>,
     pub export_selection_state: ListState,
     pub export_targets: Vec<CalendarListEntry>,
+    pub trash_selection_state: ListState,
+    pub trash_entries: Vec<TrashEntry>,
+    pub pending_selection_state: ListState,
+    pub pending_entries: Vec<JournalAction>,
+    // Given up on, listed after the pending entries
+    pub failed_entries: Vec<FailedAction>,
+    // Waiting for the user to pick a side, the first one is shown
+    pub conflicts: Vec<Conflict>,
+    pub recurrence_step: RecurrenceStep,
+    pub recurrence_draft: Option<Recurrence>,
+    pub recurrence_after_completion: bool,
+    // Field by field editing in the details pane: the copy being edited and the field
+    pub field_draft: Option<Task>,
+    pub editing_field: TaskField,
+    // Ctrl+D calendar over the input, on this day
+    pub date_picker: Option<NaiveDate>,
+    // Uids of the tasks marked with 'v', for bulk actions
+    pub marked: HashSet<String>,
 
     pub yanked_uid: Option<String>,
     pub creating_child_of: Option<String>,
-    pub show_full_help: bool,
+    // Help popup ('?') and how far it's scrolled
+    pub show_help: bool,
+    pub help_scroll: u16,
     pub tag_aliases: HashMap<String, Vec<String>>,
+    // `[calendars."..."]` of the config, by href or name
+    pub calendar_prefs: HashMap<String, CalendarPrefs>,
+    pub theme: ThemeConfig,
 
     // Track unsynced status
     pub unsynced_changes: bool,
+    // Changes not on the server yet (queued and failed), for the status bar badge
+    pub pending_count: usize,
+    // The last sync couldn't reach the server
+    pub unreachable: bool,
+    // Set when the journal is over its limits (entries, days), see `refresh_journal_status`
+    pub journal_warning: Option<String>,
+    pub journal_limits: (usize, u32),
+    // Offline mode: changes stay in the journal until a manual sync
+    pub offline: bool,
+    // Server storage usage, shown next to the status
+    pub quota: Option<Quota>,
 }
 
 impl Default for AppState {
@@ -77,9 +169,14 @@ impl AppState {
         l_state.select(Some(0));
         let mut c_state = ListState::default();
         c_state.select(Some(0));
+        let store_events = EventQueue::default();
+        let mut store = TaskStore::new();
+        store.subscribe(store_events.listener());
 
         Self {
-            store: TaskStore::new(),
+            store,
+            store_events,
+            view_fingerprint: None,
             tasks: vec![],
             calendars: vec![],
             list_state: l_state,
@@ -88,6 +185,7 @@ impl AppState {
             mode: InputMode::Normal,
             message: "Loading...".to_string(),
             loading: true,
+            warned_input: None,
 
             sidebar_mode: SidebarMode::Calendars,
             active_cal_href: None,
@@ -98,6 +196,20 @@ impl AppState {
             hide_completed: false,
             hide_fully_completed_tags: false,
             sort_cutoff_months: Some(6),
+            sort_order: SortOrder::Smart,
+            hidden_done: 0,
+            agenda_days: None,
+            board: false,
+            group_by: GroupBy::None,
+            group_headers: Vec::new(),
+            selected_context: None,
+            smart_list: None,
+            show_all: false,
+            named_priorities: false,
+            confirm_delete: true,
+            deleting_uid: None,
+            custom_statuses: Vec::new(),
+            status_cycle: status_cycle(&[], &[]),
 
             input_buffer: String::new(),
             cursor_position: 0,
@@ -106,13 +218,35 @@ impl AppState {
             move_targets: Vec::new(),
             yanked_uid: None,
             creating_child_of: None,
-            show_full_help: false,
+            show_help: false,
+            help_scroll: 0,
 
             tag_aliases: HashMap::new(),
+            calendar_prefs: HashMap::new(),
+            theme: ThemeConfig::default(),
             export_selection_state: ListState::default(),
             export_targets: Vec::new(),
+            trash_selection_state: ListState::default(),
+            trash_entries: Vec::new(),
+            pending_selection_state: ListState::default(),
+            pending_entries: Vec::new(),
+            failed_entries: Vec::new(),
+            conflicts: Vec::new(),
+            recurrence_step: RecurrenceStep::Freq,
+            recurrence_draft: None,
+            recurrence_after_completion: false,
+            field_draft: None,
+            editing_field: TaskField::Due,
+            date_picker: None,
+            marked: HashSet::new(),
 
             unsynced_changes: false, // Default false
+            pending_count: 0,
+            unreachable: false,
+            journal_warning: None,
+            journal_limits: (100, 7),
+            offline: false,
+            quota: None,
         }
     }
 
@@ -123,6 +257,36 @@ impl AppState {
             .collect()
     }
 
+    /// Smart list under the sidebar cursor (they come before the calendars).
+    pub fn selected_smart_list(&self) -> Option<SmartList> {
+        SmartList::ALL.get(self.cal_state.selected()?).copied()
+    }
+
+    /// Whether the sidebar cursor is on "All", between the smart lists and the calendars.
+    pub fn all_entry_selected(&self) -> bool {
+        self.sidebar_mode == SidebarMode::Calendars
+            && self.cal_state.selected() == Some(SmartList::ALL.len())
+    }
+
+    /// Calendar under the sidebar cursor, in the calendar view.
+    pub fn selected_sidebar_calendar(&self) -> Option<String> {
+        let idx = self
+            .cal_state
+            .selected()?
+            .checked_sub(SmartList::ALL.len() + 1)?;
+        self.get_filtered_calendars()
+            .get(idx)
+            .map(|c| c.href.clone())
+    }
+
+    /// Name of the calendar at `href` if the server reported it as read-only.
+    pub fn read_only_calendar(&self, href: &str) -> Option<String> {
+        self.calendars
+            .iter()
+            .find(|c| c.href == href && c.read_only)
+            .map(|c| c.name.clone())
+    }
+
     pub fn refresh_filtered_view(&mut self) {
         let cal_filter = None;
 
@@ -142,20 +306,56 @@ impl AppState {
 
         let mut effective_hidden = self.hidden_calendars.clone();
         effective_hidden.extend(self.disabled_calendars.clone());
+        // The board has columns for finished tasks, they'd stay empty
+        let hide_completed_in = if self.board {
+            HashMap::new()
+        } else {
+            CalendarPrefs::hide_completed_by_href(&self.calendar_prefs, &self.calendars)
+        };
+        let sort_order = self.effective_sort_order();
 
-        self.tasks = self.store.filter(FilterOptions {
+        let options = FilterOptions {
             active_cal_href: cal_filter,
             selected_categories: &self.selected_categories,
             match_all_categories: self.match_all_categories,
             hidden_calendars: &effective_hidden,
             search_term,
-            hide_completed_global: self.hide_completed,
+            calendars: &self.calendars,
+            hide_completed_global: self.hide_completed && !self.board,
+            hide_completed_in: &hide_completed_in,
             cutoff_date,
             // TUI currently doesn't implement duration filtering UI, so we disable it
             min_duration: None,
             max_duration: None,
             include_unset_duration: true,
-        });
+            sort_order,
+            selected_context: self.selected_context.as_deref(),
+            smart_list: self.smart_list,
+        };
+
+        // Edits that can't move a task only patch its row
+        let fingerprint = (
+            options.fingerprint(),
+            self.group_by,
+            self.agenda_days,
+            self.board,
+        );
+        let events = self.store_events.drain();
+        if self.view_fingerprint == Some(fingerprint)
+            && !events.is_empty()
+            && patch_view(&mut self.tasks, &events)
+        {
+            return;
+        }
+        self.view_fingerprint = Some(fingerprint);
+        let (tasks, hidden_done) = self.store.filter_counting_hidden(options);
+        self.hidden_done = hidden_done;
+        let groups = match self.agenda_days {
+            _ if self.board => board_groups(tasks),
+            Some(days) => agenda_groups(tasks, Local::now().date_naive(), days),
+            None => group_tasks(tasks, self.group_by, &self.calendars),
+        };
+        (self.tasks, self.group_headers) = flatten_groups(groups);
 
         let len = self.tasks.len();
         if len == 0 {
@@ -170,6 +370,16 @@ impl AppState {
         }
     }
 
+    /// The active calendar's own order if it has one, else the global one.
+    pub fn effective_sort_order(&self) -> SortOrder {
+        self.active_cal_href
+            .as_deref()
+            .filter(|_| !self.show_all)
+            .and_then(|href| CalendarPrefs::find(&self.calendar_prefs, &self.calendars, href))
+            .and_then(|p| p.sort_order)
+            .unwrap_or(self.sort_order)
+    }
+
     pub fn get_selected_task(&self) -> Option<&Task> {
         if let Some(idx) = self.list_state.selected() {
             self.tasks.get(idx)
@@ -211,11 +421,16 @@ impl AppState {
     // --- HELPER FOR SIDEBAR LENGTH ---
     fn get_sidebar_len(&self) -> usize {
         match self.sidebar_mode {
-            SidebarMode::Calendars => self
-                .calendars
-                .iter()
-                .filter(|c| !self.disabled_calendars.contains(&c.href))
-                .count(),
+            SidebarMode::Calendars => {
+                // Smart lists, "All", then the calendars
+                SmartList::ALL.len()
+                    + 1
+                    + self
+                        .calendars
+                        .iter()
+                        .filter(|c| !self.disabled_calendars.contains(&c.href))
+                        .count()
+            }
             SidebarMode::Categories => self
                 .store
                 .get_all_categories(
@@ -303,6 +518,61 @@ impl AppState {
             }
         }
     }
+    /// Board column of the selected task.
+    pub fn board_column(&self) -> Option<usize> {
+        let i = self.list_state.selected()?;
+        self.group_headers
+            .iter()
+            .rposition(|(start, _)| *start <= i)
+    }
+
+    /// Indexes in `tasks` of the board column `col`.
+    pub fn board_column_range(&self, col: usize) -> std::ops::Range<usize> {
+        let start = self.group_headers.get(col).map_or(0, |(s, _)| *s);
+        let end = self
+            .group_headers
+            .get(col + 1)
+            .map_or(self.tasks.len(), |(s, _)| *s);
+        start..end
+    }
+
+    /// Down (or up) within the board column, wrapping around.
+    pub fn board_step(&mut self, down: bool) {
+        let (Some(i), Some(col)) = (self.list_state.selected(), self.board_column()) else {
+            return;
+        };
+        let range = self.board_column_range(col);
+        let next = if down {
+            if i + 1 >= range.end {
+                range.start
+            } else {
+                i + 1
+            }
+        } else if i <= range.start {
+            range.end - 1
+        } else {
+            i - 1
+        };
+        self.list_state.select(Some(next));
+    }
+
+    /// To the first task of the next column with tasks, on the right or the left.
+    pub fn board_jump(&mut self, right: bool) {
+        let Some(col) = self.board_column() else {
+            return;
+        };
+        let has_tasks = |c: &usize| !self.board_column_range(*c).is_empty();
+        let target = if right {
+            (col + 1..self.group_headers.len()).find(has_tasks)
+        } else {
+            (0..col).rev().find(has_tasks)
+        };
+        if let Some(c) = target {
+            let start = self.board_column_range(c).start;
+            self.list_state.select(Some(start));
+        }
+    }
+
     pub fn jump_forward(&mut self, step: usize) {
         match self.active_focus {
             Focus::Main => {
@@ -410,6 +680,107 @@ impl AppState {
         };
         self.export_selection_state.select(Some(i));
     }
+
+    pub fn next_trash_entry(&mut self) {
+        if self.trash_entries.is_empty() {
+            return;
+        }
+        let i = match self.trash_selection_state.selected() {
+            Some(i) => {
+                if i >= self.trash_entries.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.trash_selection_state.select(Some(i));
+    }
+
+    pub fn previous_trash_entry(&mut self) {
+        if self.trash_entries.is_empty() {
+            return;
+        }
+        let i = match self.trash_selection_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.trash_entries.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.trash_selection_state.select(Some(i));
+    }
+
+    /// Reads the journal for the unsynced badge and the health warning.
+    pub fn refresh_journal_status(&mut self) {
+        let journal = match Journal::load() {
+            Ok(j) => j,
+            // Not "nothing pending": there may be changes in there
+            Err(e) => {
+                self.unsynced_changes = true;
+                self.journal_warning = Some(format!("Can't read the offline queue: {}", e));
+                return;
+            }
+        };
+        let (max_entries, max_days) = self.journal_limits;
+        self.unsynced_changes = !journal.is_empty() || !journal.failed.is_empty();
+        self.pending_count = journal.queue.len() + journal.failed.len();
+        self.journal_warning = journal.health_warning(max_entries, max_days, Utc::now());
+    }
+
+    /// Reloads the journal into the pending changes popup, keeping the selection in range.
+    pub fn reload_pending(&mut self) {
+        match Journal::load() {
+            Ok(journal) => {
+                self.pending_entries = journal.queue;
+                self.failed_entries = journal.failed;
+            }
+            Err(e) => self.message = format!("Can't read the offline queue: {}", e),
+        }
+        self.refresh_journal_status();
+        let last = self.pending_len().checked_sub(1);
+        let selected = self.pending_selection_state.selected().unwrap_or(0);
+        self.pending_selection_state
+            .select(last.map(|l| selected.min(l)));
+    }
+
+    // Entries of the popup: pending, then failed
+    pub fn pending_len(&self) -> usize {
+        self.pending_entries.len() + self.failed_entries.len()
+    }
+
+    /// Failed entry under the cursor of the pending changes popup.
+    pub fn selected_failed_entry(&self) -> Option<&FailedAction> {
+        let idx = self.pending_selection_state.selected()?;
+        self.failed_entries
+            .get(idx.checked_sub(self.pending_entries.len())?)
+    }
+
+    pub fn next_pending_entry(&mut self) {
+        if self.pending_len() == 0 {
+            return;
+        }
+        let i = match self.pending_selection_state.selected() {
+            Some(i) if i + 1 < self.pending_len() => i + 1,
+            _ => 0,
+        };
+        self.pending_selection_state.select(Some(i));
+    }
+
+    pub fn previous_pending_entry(&mut self) {
+        if self.pending_len() == 0 {
+            return;
+        }
+        let i = match self.pending_selection_state.selected() {
+            Some(i) if i > 0 => i - 1,
+            _ => self.pending_len() - 1,
+        };
+        self.pending_selection_state.select(Some(i));
+    }
 }
 
 #[cfg(test)]
@@ -1,87 +1,25 @@
 // File: src/tui/view.rs
 use crate::color_utils;
-use crate::store::UNCATEGORIZED_ID;
+use crate::model::matcher::{highlight_ranges, search_text_terms};
+use crate::model::tracking::format_minutes;
+use crate::model::{CustomStatus, TaskField};
+use crate::store::{AGENDA_OVERDUE, QUERY_PREFIX, SmartList, UNCATEGORIZED_ID, query_error};
 use crate::tui::action::SidebarMode;
-use crate::tui::state::{AppState, Focus, InputMode};
+use crate::tui::keymap::KEYMAP;
+use crate::tui::state::{AppState, Focus, InputMode, RecurrenceStep};
+use chrono::{Datelike, Months, NaiveDate, Weekday};
 
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
 
 pub fn draw(f: &mut Frame, state: &mut AppState) {
-    let full_help_text = vec![
-        Line::from(vec![
-            Span::styled(
-                " GLOBAL ",
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" Tab:Switch Focus  ?:Toggle Help  q:Quit"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                " NAVIGATION ",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" j/k:Up/Down  PgUp/PgDn:Scroll"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                " TASKS ",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" a:Add  e:Edit Title  E:Edit Desc  d:Delete  Space:Toggle Done"),
-        ]),
-        Line::from(vec![
-            Span::styled("       ", Style::default()), // Indent alignment
-            Span::raw("s:Start/Pause  x:Cancel  M:Move  r:Sync  X:Export(Local)"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                " ORGANIZATION ",
-                Style::default()
-                    .fg(Color::Magenta)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(
-                " +/-:Priority  </>:Indent  y:Yank  b:Block(w/Yank)  c:Child(w/Yank)  C:NewChild",
-            ),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                " VIEW & FILTER ",
-                Style::default()
-                    .fg(Color::Blue)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" /:Search  H:Hide Completed  1:Cal View  2:Tag View"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                " SIDEBAR ",
-                Style::default()
-                    .fg(Color::LightCyan)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(
-                " Enter:Select/Toggle  Space:Toggle Visibility  *:Show/Clear All  Right:Focus(Solo)",
-            ),
-        ]),
-    ];
-
     let footer_height = if state.mode == InputMode::EditingDescription {
         Constraint::Length(10)
-    } else if state.show_full_help {
-        Constraint::Length(full_help_text.len() as u16 + 2)
     } else {
         Constraint::Length(3)
     };
@@ -110,7 +48,53 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
 
     let (sidebar_title, sidebar_items) = match state.sidebar_mode {
         SidebarMode::Calendars => {
-            let items: Vec<ListItem> = state
+            // Smart lists first, across the visible calendars
+            let mut items: Vec<ListItem> = SmartList::ALL
+                .iter()
+                .map(|list| {
+                    let is_active = state.smart_list == Some(*list);
+                    let color = match list {
+                        SmartList::Today => Color::LightBlue,
+                        SmartList::Upcoming => Color::LightMagenta,
+                        SmartList::Overdue => Color::LightRed,
+                        SmartList::Flagged => Color::Rgb(255, 165, 0),
+                    };
+                    let style = if is_active {
+                        Style::default().fg(color).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(color)
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::raw(if is_active { ">" } else { " " }),
+                        Span::styled(
+                            format!(
+                                " * {} ({})",
+                                list.label(),
+                                state.store.count_smart_list(*list, &state.hidden_calendars)
+                            ),
+                            style,
+                        ),
+                    ]))
+                })
+                .collect();
+            // "All": the checked calendars together
+            let checked = state
+                .get_filtered_calendars()
+                .iter()
+                .filter(|c| !state.hidden_calendars.contains(&c.href))
+                .count();
+            let all_style = if state.show_all {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            items.push(ListItem::new(Line::from(vec![
+                Span::raw(if state.show_all { ">" } else { " " }),
+                Span::styled(format!(" = All ({} calendars)", checked), all_style),
+            ])));
+            let cal_items: Vec<ListItem> = state
                 .calendars
                 .iter()
                 .filter(|c| !state.disabled_calendars.contains(&c.href))
@@ -153,10 +137,21 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
                     };
 
                     spans.push(Span::styled(format!(" {}", c.name), text_style));
+                    if c.shared {
+                        let badge = match &c.owner {
+                            Some(owner) => format!(" (shared by {})", owner),
+                            None => " (shared)".to_string(),
+                        };
+                        spans.push(Span::styled(badge, Style::default().fg(Color::DarkGray)));
+                    }
+                    if c.read_only {
+                        spans.push(Span::styled(" (ro)", Style::default().fg(Color::DarkGray)));
+                    }
 
                     ListItem::new(Line::from(spans))
                 })
                 .collect();
+            items.extend(cal_items);
             (" Calendars [1] ".to_string(), items)
         }
         SidebarMode::Categories => {
@@ -216,37 +211,38 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
     f.render_stateful_widget(sidebar, h_chunks[0], &mut state.cal_state);
 
     // --- Task List ---
-    let list_inner_width = main_chunks[0].width.saturating_sub(2) as usize;
+    let list_inner_width = if state.board {
+        // A quarter of the list each, inside their own borders
+        (main_chunks[0].width.saturating_sub(2) / 4).saturating_sub(2) as usize
+    } else {
+        main_chunks[0].width.saturating_sub(2) as usize
+    };
+    let search_terms = if state.mode == InputMode::Searching {
+        search_text_terms(&state.input_buffer)
+    } else {
+        Vec::new()
+    };
+    let now = chrono::Utc::now();
 
-    let task_items: Vec<ListItem> = state
+    let mut task_items: Vec<ListItem> = state
         .tasks
         .iter()
         .map(|t| {
             let is_blocked = state.store.is_blocked(t);
+            // Priority gradient from the theme: red (1) -> yellow (5) -> purple (9)
             let base_style = if is_blocked {
                 Style::default().fg(Color::DarkGray)
             } else {
-                match t.priority {
-                    // 1: Critical -> Red
-                    1 => Style::default().fg(Color::Red),
-                    // 2: Urgent -> Orange-Red
-                    2 => Style::default().fg(Color::Rgb(255, 69, 0)),
-                    // 3: High -> Dark Orange
-                    3 => Style::default().fg(Color::Rgb(255, 140, 0)),
-                    // 4: Med-High -> Amber/Gold
-                    4 => Style::default().fg(Color::Rgb(255, 190, 0)),
-                    // 5: Normal -> Yellow
-                    5 => Style::default().fg(Color::Yellow),
-                    // 6: Med-Low -> Pale Goldenrod / Khaki
-                    6 => Style::default().fg(Color::Rgb(240, 230, 140)),
-                    // 7: Low -> Light Steel Blue
-                    7 => Style::default().fg(Color::Rgb(176, 196, 222)),
-                    // 8: Very Low -> Medium Purple / Slate
-                    8 => Style::default().fg(Color::Rgb(147, 112, 219)),
-                    // 9: Lowest -> Muted Lavender / Grey-Purple
-                    9 => Style::default().fg(Color::Rgb(170, 150, 180)),
-                    _ => Style::default(),
+                match state.theme.priority_rgb(t.priority) {
+                    Some((r, g, b)) => Style::default().fg(Color::Rgb(r, g, b)),
+                    None => Style::default(),
+                }
+            };
+            let due_style = match state.theme.due_soon_rgb() {
+                Some((r, g, b)) if !is_blocked && state.theme.is_due_soon(t, now) => {
+                    base_style.fg(Color::Rgb(r, g, b))
                 }
+                _ => base_style,
             };
 
             // Bracket Color logic
@@ -258,21 +254,38 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
                     bracket_style = Style::default().fg(Color::Rgb(r, g, b));
                 }
 
+            // "All" view: the initial of the task's calendar, in its color
+            let cal_initial = if state.show_all {
+                state
+                    .calendars
+                    .iter()
+                    .find(|c| c.href == t.calendar_href)
+                    .and_then(|c| c.name.chars().next())
+                    .map(|ch| format!("{} ", ch.to_uppercase()))
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+
             let full_symbol = t.checkbox_symbol(); // e.g. "[x]"
             let inner_char = &full_symbol[1..2]; // e.g. "x"
 
             let due_str = t
-                .due
-                .map(|d| format!(" ({})", d.format("%d/%m")))
+                .format_due("%d/%m")
+                .map(|d| format!(" ({})", d))
                 .unwrap_or_default();
             let dur_str = t.format_duration_short();
-            let show_indent = state.active_cal_href.is_some() && state.mode != InputMode::Searching;
+            let show_indent = state.active_cal_href.is_some()
+                && state.mode != InputMode::Searching
+                && state.smart_list.is_none();
             let indent = if show_indent {
                 "  ".repeat(t.depth)
             } else {
                 "".to_string()
             };
             let recur_str = if t.rrule.is_some() { " (R)" } else { "" };
+            let track_str = if t.is_tracking() { " [T]" } else { "" };
+            let flag_str = if t.flagged { " [F]" } else { "" };
 
             // Alias Hiding Logic
             let mut hidden_tags = std::collections::HashSet::new();
@@ -298,57 +311,122 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
                 .collect();
 
             // Layout Calculation
-            let tags_str_len: usize = visible_cats.iter().map(|c| c.len() + 2).sum();
+            let tags_str_len: usize = visible_cats.iter().map(|c| c.len() + 2).sum::<usize>()
+                + t.contexts.iter().map(|c| c.len() + 3).sum::<usize>()
+                + t.custom_status.as_ref().map_or(0, |s| s.len() + 3);
 
             // Manually calc length because we are building spans manually
             let raw_text = format!(
-                "[{}] {}{}{}{}{}",
+                "[{}] {}{}{}{}{}{}{}",
                 inner_char,
                 if is_blocked { "[B] " } else { " " },
                 t.summary,
                 dur_str,
                 due_str,
-                recur_str
+                recur_str,
+                track_str,
+                flag_str
             );
 
             // "  " indent + brackets + inner + etc
-            let total_len = indent.len() + raw_text.len() + tags_str_len;
+            let total_len =
+                cal_initial.chars().count() + indent.len() + raw_text.len() + tags_str_len;
             let padding_len = list_inner_width.saturating_sub(total_len);
             let padding = " ".repeat(padding_len);
 
             // Construct spans for colorful brackets
             let mut spans = vec![
+                Span::styled(cal_initial, bracket_style.add_modifier(Modifier::BOLD)),
                 Span::raw(indent),
                 Span::styled("[", bracket_style),
                 Span::styled(inner_char, base_style),
                 Span::styled("]", bracket_style),
                 Span::raw(if is_blocked { " [B] " } else { " " }),
-                Span::styled(
-                    format!("{}{}{}{}", t.summary, dur_str, due_str, recur_str),
-                    base_style,
-                ),
-                Span::raw(padding),
             ];
+            spans.extend(highlight_spans(&t.summary, &search_terms, base_style));
+            spans.push(Span::styled(dur_str, base_style));
+            spans.push(Span::styled(due_str, due_style));
+            spans.push(Span::styled(
+                format!("{}{}{}", recur_str, track_str, flag_str),
+                base_style,
+            ));
+            spans.push(Span::raw(padding));
 
+            if let Some(name) = &t.custom_status {
+                let color = CustomStatus::find(&state.custom_statuses, name)
+                    .and_then(|s| s.color.as_deref())
+                    .and_then(color_utils::parse_hex_to_u8)
+                    .map_or(Color::Cyan, |(r, g, b)| Color::Rgb(r, g, b));
+                spans.push(Span::styled(
+                    format!(" [{}]", name),
+                    Style::default().fg(color),
+                ));
+            }
             for cat in visible_cats {
                 let (r, g, b) = color_utils::generate_color(cat);
                 let color = Color::Rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8);
-                spans.push(Span::styled(
-                    format!(" #{}", cat),
+                spans.extend(highlight_spans(
+                    &format!(" #{}", cat),
+                    &search_terms,
                     Style::default().fg(color),
                 ));
             }
-            ListItem::new(Line::from(spans))
+            for ctx in &t.contexts {
+                spans.extend(highlight_spans(
+                    &format!(" @@{}", ctx),
+                    &search_terms,
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            let item = ListItem::new(Line::from(spans));
+            if state.marked.contains(&t.uid) {
+                item.style(Style::default().bg(Color::DarkGray))
+            } else {
+                item
+            }
         })
         .collect();
 
+    // Section headers (grouped views) sit between the tasks without being selectable
+    // (the board's go above the columns)
+    for (start, group) in state.group_headers.iter().rev().filter(|_| !state.board) {
+        // The agenda points out what's late and what's for today
+        let color = match group.as_str() {
+            AGENDA_OVERDUE if state.agenda_days.is_some() => Color::Red,
+            g if state.agenda_days.is_some() && g.starts_with("Today") => Color::Yellow,
+            _ => Color::Cyan,
+        };
+        task_items.insert(
+            *start,
+            ListItem::new(Line::from(Span::styled(
+                format!("── {} ──", group),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))),
+        );
+    }
+
     let mut title = if state.loading {
         " Tasks (Loading...) ".to_string()
     } else {
         format!(" Tasks ({}) ", state.tasks.len())
     };
-    if state.unsynced_changes {
-        title.push_str(" [UNSYNCED] ");
+    if state.hidden_done > 0 {
+        title.push_str(&format!(" +{} done hidden ", state.hidden_done));
+    }
+    if let Some(list) = state.smart_list {
+        title.push_str(&format!(" [{}] ", list.label().to_uppercase()));
+    }
+    match state.agenda_days {
+        _ if state.board => title.push_str(" Board "),
+        Some(7) => title.push_str(" Agenda: week "),
+        Some(_) => title.push_str(" Agenda: month "),
+        None => title.push_str(&format!(" Sort: {} ", state.effective_sort_order())),
+    }
+    if let Some(ctx) = &state.selected_context {
+        title.push_str(&format!(" @@{} ", ctx));
+    }
+    if !state.marked.is_empty() {
+        title.push_str(&format!(" [{} MARKED] ", state.marked.len()));
     }
 
     let main_style = if state.active_focus == Focus::Main {
@@ -359,28 +437,103 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
         Style::default()
     };
 
-    let task_list = List::new(task_items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(title)
-                .border_style(main_style),
-        )
-        .highlight_style(
-            Style::default()
-                .add_modifier(Modifier::BOLD)
-                .bg(Color::Green)
-                .fg(Color::Black),
-        );
-    f.render_stateful_widget(task_list, main_chunks[0], &mut state.list_state);
+    let selection_bg = state
+        .theme
+        .selection_rgb()
+        .map_or(Color::Green, |(r, g, b)| Color::Rgb(r, g, b));
+    let highlight = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .bg(selection_bg)
+        .fg(Color::Black);
+    let list_block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(main_style);
+    if state.board {
+        draw_board(f, state, task_items, list_block, main_chunks[0], highlight);
+    } else {
+        let task_list = List::new(task_items)
+            .block(list_block)
+            .highlight_style(highlight);
+        // The selection counts tasks only, the list also has the headers
+        let selected = state.list_state.selected().map(|i| {
+            let headers = state.group_headers.iter().filter(|(s, _)| *s <= i).count();
+            (
+                i + headers,
+                state.group_headers.iter().any(|(s, _)| *s == i),
+            )
+        });
+        let mut offset = state.list_state.offset();
+        if let Some((row, true)) = selected {
+            // Keep the header of the section visible when reaching its first task
+            offset = offset.min(row - 1);
+        }
+        let mut list_state = ListState::default()
+            .with_offset(offset)
+            .with_selected(selected.map(|(row, _)| row));
+        f.render_stateful_widget(task_list, main_chunks[0], &mut list_state);
+        *state.list_state.offset_mut() = list_state.offset();
+    }
 
     // Details
     let mut full_details = String::new();
+    if state.mode == InputMode::EditingFields
+        && let Some(draft) = &state.field_draft
+    {
+        full_details.push_str("[Fields] (Tab/Up/Down: next, Enter: save, Esc: cancel)\n");
+        for field in TaskField::ALL {
+            let mut value = draft.field_smart_string(field, state.named_priorities);
+            if value.is_empty() {
+                value = "none".to_string();
+            }
+            let marker = if field == state.editing_field {
+                '>'
+            } else {
+                '-'
+            };
+            full_details.push_str(&format!("{} {}: {}\n", marker, field.label(), value));
+        }
+        full_details.push('\n');
+    }
     if let Some(task) = state.get_selected_task() {
-        if !task.description.is_empty() {
-            full_details.push_str(&task.description);
+        let description = task.description_text();
+        if !description.is_empty() {
+            full_details.push_str(&description);
             full_details.push_str("\n\n");
         }
+        if let Some((done, total)) = task.checklist_progress() {
+            full_details.push_str(&format!(
+                "[Checklist {}/{}] (l/L: check/uncheck)\n",
+                done, total
+            ));
+            for item in task.checklist() {
+                let check = if item.done { "[x]" } else { "[ ]" };
+                full_details.push_str(&format!(" {} {}\n", check, item.text));
+            }
+            full_details.push('\n');
+        }
+        if let Some(name) = &task.custom_status {
+            full_details.push_str(&format!(
+                "[Status: {} ({})] (w: next)\n\n",
+                name,
+                task.status.label()
+            ));
+        }
+        if !task.time_sessions.is_empty() {
+            full_details.push_str(&format!(
+                "[Time spent: {}",
+                format_minutes(task.tracked_minutes(chrono::Utc::now()))
+            ));
+            if let Some(mins) = task.estimated_duration {
+                full_details.push_str(&format!(" of ~{}", format_minutes(mins)));
+            }
+            let running = if task.is_tracking() { ", running" } else { "" };
+            full_details.push_str(&format!(
+                "] ({} sessions{}, t: start/stop)\n\n",
+                task.time_sessions.len(),
+                running
+            ));
+        }
         if !task.dependencies.is_empty() {
             full_details.push_str("[Blocked By]:\n");
             for dep_uid in &task.dependencies {
@@ -393,6 +546,36 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
                 full_details.push_str(&format!(" {} {}\n", check, name));
             }
         }
+        let blocked = state.store.blocked_tasks(&task.uid);
+        if !blocked.is_empty() {
+            full_details.push_str(&format!("[Blocks: {}]:\n", blocked.len()));
+            for uid in blocked {
+                let name = state
+                    .store
+                    .get_summary(uid)
+                    .unwrap_or_else(|| "Unknown Task".to_string());
+                full_details.push_str(&format!(" - {}\n", name));
+            }
+        }
+        if let Some(done) = task.completed_at {
+            full_details.push_str(&format!(
+                "Completed: {}\n",
+                done.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ));
+        }
+        if let Some(rec) = task.recurrence_summary() {
+            full_details.push_str(&format!("Repeats: {}\n", rec));
+        }
+        if let Some(next) = task.occurrence_summary() {
+            full_details.push_str(&format!("{}\n", next));
+            for o in &task.overrides {
+                full_details.push_str(&format!(
+                    " {}: {}\n",
+                    task.format_occurrence(o.recurrence_id),
+                    o.status.label()
+                ));
+            }
+        }
     }
     if full_details.is_empty() {
         full_details = "No details.".to_string();
@@ -411,7 +594,10 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
         InputMode::Creating
         | InputMode::Editing
         | InputMode::Searching
-        | InputMode::EditingDescription => {
+        | InputMode::EditingDescription
+        | InputMode::Recurrence
+        | InputMode::EditingFields
+        | InputMode::Tagging => {
             // ... Input Mode Rendering logic ...
             let (mut title_str, prefix, color) = match state.mode {
                 InputMode::Searching => (" Search ".to_string(), "/ ", Color::Green),
@@ -419,6 +605,39 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
                 InputMode::EditingDescription => {
                     (" Edit Description ".to_string(), "📝 ", Color::Blue)
                 }
+                InputMode::Recurrence => {
+                    let prompt = match state.recurrence_step {
+                        RecurrenceStep::Freq => {
+                            "Repeat: (n)ever (d)aily (w)eekly (m)onthly (y)early, Enter: keep"
+                        }
+                        RecurrenceStep::Interval => "Every how many? (Enter for 1)",
+                        RecurrenceStep::Days => "On which days? e.g. mo,fr or -1fr (empty: any)",
+                        RecurrenceStep::End => "Ends: YYYY-MM-DD, N times, or empty for never",
+                        RecurrenceStep::Mode => {
+                            "Count from: (f)ixed dates or (c)ompletion, Enter: keep"
+                        }
+                    };
+                    let mut title = format!(" {} ", prompt);
+                    // Validation errors of the current step
+                    if !state.message.is_empty() {
+                        title.push_str(&format!("- {} ", state.message));
+                    }
+                    (title, "\u{21bb} ", Color::LightGreen)
+                }
+                InputMode::EditingFields => {
+                    // The field's hint, or why the input was refused
+                    let title =
+                        format!(" Edit {} - {} ", state.editing_field.label(), state.message);
+                    (title, "\u{270e} ", Color::Cyan)
+                }
+                InputMode::Tagging => (
+                    format!(
+                        " Tag {} marked task(s): tag adds, -tag removes ",
+                        state.marked.len()
+                    ),
+                    "# ",
+                    Color::LightMagenta,
+                ),
                 InputMode::Creating => {
                     if state.creating_child_of.is_some() {
                         (" Create Child Task ".to_string(), "> ", Color::LightYellow)
@@ -438,8 +657,33 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
             if show_tag_hint {
                 title_str.push_str(" [Enter to jump to tag] ");
             }
+            if state.mode == InputMode::Searching {
+                if let Some(err) = query_error(&state.input_buffer) {
+                    title_str.push_str(&format!("- {} ", err));
+                } else if state.input_buffer.starts_with(QUERY_PREFIX) {
+                    title_str.push_str("(filter: due<7d AND #work AND status!=done) ");
+                }
+            }
 
-            let input_text = format!("{}{}", prefix, state.input_buffer);
+            let input_text = match (&state.mode, &state.recurrence_draft) {
+                (InputMode::Recurrence, None) if state.recurrence_step == RecurrenceStep::Freq => {
+                    format!("{}Doesn't repeat", prefix)
+                }
+                (InputMode::Recurrence, Some(r))
+                    if matches!(
+                        state.recurrence_step,
+                        RecurrenceStep::Freq | RecurrenceStep::Mode
+                    ) =>
+                {
+                    let from = if state.recurrence_after_completion {
+                        ", after completion"
+                    } else {
+                        ""
+                    };
+                    format!("{}{}{}", prefix, r.summary(), from)
+                }
+                _ => format!("{}{}", prefix, state.input_buffer),
+            };
             let input = Paragraph::new(input_text)
                 .style(Style::default().fg(color))
                 .block(Block::default().borders(Borders::ALL).title(title_str))
@@ -455,36 +699,62 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
             ));
         }
         _ => {
-            if state.show_full_help {
-                let p = Paragraph::new(full_help_text)
-                    .block(Block::default().borders(Borders::ALL).title(" Help "))
-                    .wrap(Wrap { trim: false });
-                f.render_widget(p, footer_area);
+            let mut status_title = vec![Span::raw(match &state.quota {
+                Some(quota) => format!(" Status \u{2022} {} ", quota.summary()),
+                None => " Status ".to_string(),
+            })];
+            // Whether edits reach the server, and how many are still waiting
+            status_title.push(if state.offline {
+                Span::styled(" \u{25cb} offline ", Style::default().fg(Color::Yellow))
+            } else if state.unreachable {
+                Span::styled(
+                    " \u{25cb} unreachable ",
+                    Style::default().fg(Color::LightRed),
+                )
             } else {
-                let status = Paragraph::new(state.message.clone())
-                    .style(Style::default().fg(Color::Cyan))
-                    .block(
-                        Block::default()
-                            .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)
-                            .title(" Status "),
-                    );
-                let help_str = match state.active_focus {
-                    Focus::Sidebar => "Ret:Select Space:Vis *:All Tab:Tasks".to_string(),
-                    Focus::Main => "a:Add e:Edit Spc:Done d:Del /:Find".to_string(),
-                };
-                let help = Paragraph::new(help_str).alignment(Alignment::Right).block(
+                Span::styled(" \u{25cf} online ", Style::default().fg(Color::Green))
+            });
+            if state.pending_count > 0 {
+                status_title.push(Span::styled(
+                    format!(" \u{21c5} {} pending ", state.pending_count),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            // The journal looks stuck: `cfait doctor` tells why
+            if let Some(warning) = &state.journal_warning {
+                status_title.push(Span::styled(
+                    format!(" \u{26a0} {} (P, cfait doctor) ", warning),
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::LightRed)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            let status = Paragraph::new(state.message.clone())
+                .style(Style::default().fg(Color::Cyan))
+                .block(
                     Block::default()
-                        .borders(Borders::RIGHT | Borders::TOP | Borders::BOTTOM)
-                        .title(" Actions "),
+                        .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)
+                        .title(Line::from(status_title)),
                 );
+            let help_str = match state.active_focus {
+                Focus::Sidebar => "Ret:Select Space:Vis *:All Tab:Tasks".to_string(),
+                Focus::Main => "a:Add e:Edit Spc:Done d:Del /:Find ?:Keys".to_string(),
+            };
+            let help = Paragraph::new(help_str).alignment(Alignment::Right).block(
+                Block::default()
+                    .borders(Borders::RIGHT | Borders::TOP | Borders::BOTTOM)
+                    .title(" Actions "),
+            );
 
-                let chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                    .split(footer_area);
-                f.render_widget(status, chunks[0]);
-                f.render_widget(help, chunks[1]);
-            }
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(footer_area);
+            f.render_widget(status, chunks[0]);
+            f.render_widget(help, chunks[1]);
         }
     }
 
@@ -502,6 +772,312 @@ pub fn draw(f: &mut Frame, state: &mut AppState) {
         f.render_widget(Clear, area);
         f.render_stateful_widget(popup, area, &mut state.move_selection_state);
     }
+
+    if state.mode == InputMode::Trash {
+        let area = centered_rect(60, 50, f.area());
+        let items: Vec<ListItem> = state
+            .trash_entries
+            .iter()
+            .map(|e| {
+                let cal_name = state
+                    .calendars
+                    .iter()
+                    .find(|c| c.href == e.calendar_href)
+                    .map(|c| c.name.as_str())
+                    .unwrap_or(e.calendar_href.as_str());
+                ListItem::new(Line::from(vec![
+                    Span::raw(e.task.summary.clone()),
+                    Span::styled(
+                        format!(
+                            "  ({}, {})",
+                            cal_name,
+                            e.deleted_at
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M")
+                        ),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+        let popup = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Trash "))
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(popup, area, &mut state.trash_selection_state);
+    }
+
+    if state.mode == InputMode::Pending {
+        let area = centered_rect(60, 50, f.area());
+        let cal_name = |href: &str| {
+            state
+                .calendars
+                .iter()
+                .find(|c| c.href == href)
+                .map(|c| c.name.clone())
+                .unwrap_or_else(|| href.to_string())
+        };
+        let mut items: Vec<ListItem> = state
+            .pending_entries
+            .iter()
+            .map(|a| {
+                let mut place = cal_name(a.calendar_href());
+                if let crate::journal::Action::Move(_, dest) = a {
+                    place = format!("{} -> {}", place, cal_name(dest));
+                }
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<7}", a.kind()),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(a.task().summary.clone()),
+                    Span::styled(
+                        format!("  ({})", place),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+        // Given up on: not replayed until retried (r)
+        items.extend(state.failed_entries.iter().map(|f| {
+            ListItem::new(Line::from(vec![
+                Span::styled("Failed ", Style::default().fg(Color::Red)),
+                Span::raw(f.action.task().summary.clone()),
+                Span::styled(
+                    format!("  ({}: {})", cal_name(f.action.calendar_href()), f.error),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        }));
+        let popup = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Pending changes (oldest first) "),
+            )
+            .highlight_style(Style::default().bg(Color::Blue));
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(popup, area, &mut state.pending_selection_state);
+    }
+
+    if state.mode == InputMode::Conflict
+        && let Some(conflict) = state.conflicts.first()
+    {
+        let area = centered_rect(70, 50, f.area());
+        let mut lines = vec![
+            Line::from("Both you and the server changed this task:"),
+            Line::from(""),
+        ];
+        for change in conflict.server.diff(&conflict.local) {
+            let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}: ", change.field),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(show(&change.old), Style::default().fg(Color::Cyan)),
+                Span::raw(" (server)  "),
+                Span::styled(show(&change.new), Style::default().fg(Color::Yellow)),
+                Span::raw(" (mine)"),
+            ]));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "l: Keep mine  s: Keep server  b: Keep both  Esc: Later",
+            Style::default().fg(Color::DarkGray),
+        )));
+        let count = match state.conflicts.len() {
+            1 => String::new(),
+            n => format!(" 1/{}", n),
+        };
+        let title = format!(" Conflict{}: {} ", count, conflict.local.summary);
+        let popup = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+
+    if state.mode == InputMode::ConfirmDelete {
+        let uids: Vec<&String> = match &state.deleting_uid {
+            Some(uid) => vec![uid],
+            None => state.marked.iter().collect(),
+        };
+        let mut summaries: Vec<String> = uids
+            .iter()
+            .filter_map(|uid| state.store.get_summary(uid))
+            .collect();
+        summaries.sort_unstable();
+        let mut lines: Vec<Line> = summaries
+            .iter()
+            .take(10)
+            .map(|s| Line::from(format!("- {}", s)))
+            .collect();
+        if summaries.len() > 10 {
+            lines.push(Line::from(format!("...and {} more", summaries.len() - 10)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "y: Delete  n/Esc: Keep",
+            Style::default().fg(Color::DarkGray),
+        )));
+        let title = match summaries.len() {
+            1 => " Delete this task? ".to_string(),
+            n => format!(" Delete {} tasks? ", n),
+        };
+        let area = centered_rect(60, 40, f.area());
+        let popup = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::LightRed)),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+
+    if let Some(day) = state.date_picker {
+        let lines = month_grid(day);
+        let screen = f.area();
+        let (width, height) = (24, lines.len() as u16 + 2);
+        let area = Rect::new(
+            screen.x + screen.width.saturating_sub(width) / 2,
+            screen.y + screen.height.saturating_sub(height) / 2,
+            width.min(screen.width),
+            height.min(screen.height),
+        );
+        let title = day.format(" %B %Y ").to_string();
+        let popup = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+
+    if state.show_help {
+        let lines = help_lines();
+        let area = centered_rect(70, 80, f.area());
+        // Not past the last line
+        let max = (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+        state.help_scroll = state.help_scroll.min(max);
+        let popup = Paragraph::new(lines).scroll((state.help_scroll, 0)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Keys (j/k: scroll, Esc: close) ")
+                .border_style(Style::default().fg(Color::Cyan)),
+        );
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+}
+
+// The help popup: every section of the keymap
+fn help_lines() -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for section in KEYMAP {
+        lines.push(Line::from(Span::styled(
+            format!(" {} ", section.mode.to_uppercase()),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for binding in section.keys {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<16}", binding.keys),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(binding.what),
+            ]));
+        }
+        lines.push(Line::default());
+    }
+    lines.pop();
+    lines
+}
+
+// Monday-first month of `day`, with `day` highlighted and today underlined
+// A column per status, each with its own selection
+fn draw_board(
+    f: &mut Frame,
+    state: &AppState,
+    items: Vec<ListItem>,
+    block: Block,
+    area: Rect,
+    highlight: Style,
+) {
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 4); 4])
+        .split(inner);
+    let selected = state.list_state.selected();
+    let mut items = items.into_iter();
+    for (col, (_, title)) in state.group_headers.iter().enumerate().take(columns.len()) {
+        let range = state.board_column_range(col);
+        let column: Vec<ListItem> = items.by_ref().take(range.len()).collect();
+        let current = selected.filter(|i| range.contains(i));
+        let border = if current.is_some() {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let list = List::new(column)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} ({}) ", title, range.len()))
+                    .border_style(border),
+            )
+            .highlight_style(highlight);
+        let mut list_state = ListState::default().with_selected(current.map(|i| i - range.start));
+        f.render_stateful_widget(list, columns[col], &mut list_state);
+    }
+}
+
+fn month_grid(day: NaiveDate) -> Vec<Line<'static>> {
+    let today = chrono::Local::now().date_naive();
+    let first = day.with_day(1).unwrap_or(day);
+    let next_month = first.checked_add_months(Months::new(1)).unwrap_or(first);
+    let days_in_month = (next_month - first).num_days() as u32;
+
+    let mut lines = vec![Line::from(Span::styled(
+        " Mo Tu We Th Fr Sa Su",
+        Style::default().fg(Color::DarkGray),
+    ))];
+    let offset = first.weekday().num_days_from_monday() as usize;
+    let mut week = vec![Span::raw("   ".repeat(offset))];
+    for d in 1..=days_in_month {
+        let date = first.with_day(d).unwrap_or(first);
+        let mut style = Style::default();
+        if date == today {
+            style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+        }
+        if date == day {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        week.push(Span::raw(" "));
+        week.push(Span::styled(format!("{:>2}", d), style));
+        if date.weekday() == Weekday::Sun {
+            lines.push(Line::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Line::from(week));
+    }
+    lines.push(Line::from(Span::styled(
+        " Enter:Pick  t:Today",
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(Span::styled(
+        " PgUp/PgDn:Month",
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -522,3 +1098,23 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+// Search matches in `text` stand out
+fn highlight_spans(text: &str, terms: &[String], style: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for r in highlight_ranges(text, terms) {
+        if r.start > pos {
+            spans.push(Span::styled(text[pos..r.start].to_string(), style));
+        }
+        spans.push(Span::styled(
+            text[r.clone()].to_string(),
+            style.fg(Color::Black).bg(Color::Yellow),
+        ));
+        pos = r.end;
+    }
+    if pos < text.len() {
+        spans.push(Span::styled(text[pos..].to_string(), style));
+    }
+    spans
+}
@@ -0,0 +1,206 @@
+// File: src/vdir.rs
+// Local calendars kept as a vdir: one directory per calendar, one .ics file per task,
+// plus optional `displayname` and `color` files. That's the layout of vdirsyncer, khal
+// and todoman, so they can work on the same data. Used instead of the JSON files in
+// the data directory when `vdir_path` is set in the config.
+use crate::model::{CalendarListEntry, Task};
+use crate::storage::{
+    LOCAL_CALENDAR_HREF, LOCAL_CALENDAR_NAME, LOCAL_CALENDAR_PREFIX, LocalStorage,
+};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+static ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Keeps the local calendars under `root` from now on (from the config), or in the
+/// JSON files again with None.
+pub fn init(root: Option<&str>) {
+    let root = root
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(expand_home);
+    *ROOT.write().unwrap_or_else(|e| e.into_inner()) = root;
+}
+
+pub fn root() -> Option<PathBuf> {
+    ROOT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), directories::BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Directory of a local calendar: "local://<name>" is `<root>/<name>`, so the default
+/// calendar lives in `<root>/default`.
+pub fn collection_path(href: &str) -> Option<PathBuf> {
+    let name = href.strip_prefix(LOCAL_CALENDAR_PREFIX)?;
+    // Names come from directory listings, but hrefs can come from a hand-edited config
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return None;
+    }
+    Some(root()?.join(name))
+}
+
+// Held while a collection is read or written. Not an .ics file, so other tools skip it.
+fn lock_path(dir: &Path) -> PathBuf {
+    dir.join(".cfait")
+}
+
+/// Every collection under the root, the default one first.
+pub fn calendars() -> Vec<CalendarListEntry> {
+    let Some(root) = root() else {
+        return vec![];
+    };
+    let mut cals: Vec<CalendarListEntry> = fs::read_dir(&root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let href = format!("{}{}", LOCAL_CALENDAR_PREFIX, e.file_name().to_str()?);
+            collection_path(&href)?;
+            Some(entry(&href, &e.path()))
+        })
+        .collect();
+    cals.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    match cals.iter().position(|c| c.href == LOCAL_CALENDAR_HREF) {
+        Some(i) => {
+            let default = cals.remove(i);
+            cals.insert(0, default);
+        }
+        // Created on the first write
+        None => cals.insert(0, entry(LOCAL_CALENDAR_HREF, &root.join("default"))),
+    }
+    cals
+}
+
+fn entry(href: &str, dir: &Path) -> CalendarListEntry {
+    let read = |file: &str| {
+        fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let name = read("displayname").unwrap_or_else(|| {
+        if href == LOCAL_CALENDAR_HREF {
+            LOCAL_CALENDAR_NAME.to_string()
+        } else {
+            href.trim_start_matches(LOCAL_CALENDAR_PREFIX).to_string()
+        }
+    });
+    CalendarListEntry {
+        name,
+        href: href.to_string(),
+        color: read("color"),
+        read_only: false,
+        owner: None,
+        shared: false,
+        account: None,
+    }
+}
+
+pub fn load(href: &str) -> Result<Vec<Task>> {
+    let Some(dir) = collection_path(href).filter(|d| d.is_dir()) else {
+        return Ok(vec![]);
+    };
+    LocalStorage::with_lock(&lock_path(&dir), || {
+        Ok(read_items(&dir, href)?
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect())
+    })
+}
+
+// Tasks of a collection with their file names. Events (khal) and files that don't
+// parse are left alone.
+fn read_items(dir: &Path, href: &str) -> Result<Vec<(String, Task)>> {
+    let mut items = Vec::new();
+    for file in fs::read_dir(dir)? {
+        let path = file?.path();
+        if path.extension().is_none_or(|e| e != "ics") {
+            continue;
+        }
+        let (Some(name), Ok(raw)) = (path.file_name(), fs::read_to_string(&path)) else {
+            continue;
+        };
+        if let Ok(task) = Task::from_ics(&raw, String::new(), String::new(), href.to_string()) {
+            items.push((name.to_string_lossy().into_owned(), task));
+        }
+    }
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(items)
+}
+
+/// Makes the collection hold exactly `tasks`: changed tasks are rewritten, new ones get
+/// a file, and the files of tasks that are gone are removed.
+pub fn save(href: &str, tasks: &[Task]) -> Result<()> {
+    let dir = collection_path(href).ok_or_else(|| anyhow!("Not a vdir calendar: {}", href))?;
+    fs::create_dir_all(&dir)?;
+    LocalStorage::with_lock(&lock_path(&dir), || {
+        let mut on_disk: HashMap<String, (String, Task)> = read_items(&dir, href)?
+            .into_iter()
+            .map(|(file, task)| (task.uid.clone(), (file, task)))
+            .collect();
+        for task in tasks {
+            let file = match on_disk.remove(&task.uid) {
+                // Untouched files keep their mtime, so vdirsyncer has nothing to upload
+                Some((_, old)) if old == *task => continue,
+                Some((file, _)) => file,
+                None => file_name(&task.uid),
+            };
+            LocalStorage::atomic_write(dir.join(file), task.to_ics())?;
+        }
+        for (file, _) in on_disk.into_values() {
+            fs::remove_file(dir.join(file))?;
+        }
+        Ok(())
+    })
+}
+
+// vdirsyncer's rule: the UID, unless it has characters that aren't safe in a file name
+fn file_name(uid: &str) -> String {
+    let safe = uid
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.@".contains(c));
+    if safe && !uid.is_empty() && !uid.starts_with('.') {
+        format!("{}.ics", uid)
+    } else {
+        format!("{}.ics", Uuid::new_v4())
+    }
+}
+
+pub fn create(name: &str) -> Result<CalendarListEntry> {
+    let href = format!("{}{}", LOCAL_CALENDAR_PREFIX, Uuid::new_v4());
+    let dir = collection_path(&href).ok_or_else(|| anyhow!("vdir_path is not set"))?;
+    fs::create_dir_all(&dir)?;
+    LocalStorage::atomic_write(dir.join("displayname"), name)?;
+    Ok(entry(&href, &dir))
+}
+
+pub fn rename(href: &str, name: &str) -> Result<()> {
+    let dir = collection_path(href).ok_or_else(|| anyhow!("No local calendar {}", href))?;
+    fs::create_dir_all(&dir)?;
+    LocalStorage::atomic_write(dir.join("displayname"), name)
+}
+
+/// Removes the tasks of a collection, returning them. The directory itself only goes
+/// if nothing else (e.g. events) is left in it.
+pub fn delete(href: &str) -> Result<Vec<Task>> {
+    let dir = collection_path(href)
+        .filter(|d| d.is_dir())
+        .ok_or_else(|| anyhow!("No local calendar {}", href))?;
+    let tasks = load(href)?;
+    save(href, &[])?;
+    for file in ["displayname", "color", ".cfait.lock"] {
+        let _ = fs::remove_file(dir.join(file));
+    }
+    let _ = fs::remove_dir(&dir);
+    Ok(tasks)
+}
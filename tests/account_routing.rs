@@ -0,0 +1,101 @@
+// File: ./tests/account_routing.rs
+use cfait::cache::Cache;
+use cfait::client::AccountManager;
+use cfait::config::{AccountConfig, Config};
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use mockito::Server;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+fn queued_update(uid: &str, cal: &str) -> Task {
+    let mut task = Task::new(&format!("Task {}", uid), &HashMap::new());
+    task.uid = uid.to_string();
+    task.calendar_href = cal.to_string();
+    task.href = format!("{}{}.ics", cal, uid);
+    task.etag = format!("\"{}-1\"", uid);
+    task
+}
+
+#[tokio::test]
+async fn test_queued_changes_go_to_their_account() {
+    let temp_dir = env::temp_dir().join(format!("cfait_test_routing_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+
+    let mut home = Server::new_async().await;
+    let mut work = Server::new_async().await;
+    // Both servers use the same calendar path
+    let mock_home = home
+        .mock("PUT", "/cal/h.ics")
+        .with_status(204)
+        .with_header("ETag", "\"h-2\"")
+        .expect(1)
+        .create_async()
+        .await;
+    let mock_work = work
+        .mock("PUT", "/cal/w.ics")
+        .with_status(204)
+        .with_header("ETag", "\"w-2\"")
+        .expect(1)
+        .create_async()
+        .await;
+
+    Journal::push(Action::Update(queued_update("h", "/cal/"))).unwrap();
+    Journal::push_for(Some("Work"), Action::Update(queued_update("w", "/cal/"))).unwrap();
+    // A Move stays on its account, on both ends
+    Journal::push_for(
+        Some("Work"),
+        Action::Move(queued_update("m", "/cal/"), "/cal/archive/".to_string()),
+    )
+    .unwrap();
+
+    // 1. Each queued change remembers its account, not just the calendar path
+    let journal = Journal::load().unwrap();
+    let accounts: Vec<Option<&str>> = journal
+        .queue
+        .iter()
+        .map(|a| journal.account_of(a))
+        .collect();
+    assert_eq!(accounts, [None, Some("Work"), Some("Work")]);
+    Journal::modify_journal(|j| j.queue.retain(|a| a.task().uid != "m")).unwrap();
+
+    // 2. Their copies in the cache don't overwrite each other either
+    for action in &Journal::load().unwrap().queue {
+        Cache::apply(action).unwrap();
+    }
+    let cached = |account: Option<&str>| -> Vec<String> {
+        let (tasks, _) = Cache::load(&Cache::key(account, "/cal/")).unwrap();
+        tasks.into_iter().map(|t| t.uid).collect()
+    };
+    assert_eq!(cached(None), ["h"]);
+    assert_eq!(cached(Some("Work")), ["w"]);
+
+    // 3. Each account only replays its own changes, on its own server
+    let config = Config {
+        url: format!("{}/dav/", home.url()),
+        accounts: vec![AccountConfig {
+            label: "Work".to_string(),
+            url: format!("{}/dav/", work.url()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let manager = AccountManager::from_config(&config).await.unwrap();
+    let result = manager.sync_journal().await;
+    assert!(result.is_ok(), "Sync should succeed: {:?}", result.err());
+    mock_home.assert();
+    mock_work.assert();
+    assert!(Journal::load().unwrap().queue.is_empty());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
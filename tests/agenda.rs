@@ -0,0 +1,59 @@
+// File: ./tests/agenda.rs
+use cfait::model::{Task, TaskStatus};
+use cfait::store::{AGENDA_OVERDUE, agenda_groups};
+use chrono::{Days, Local, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+
+fn due_on(summary: &str, day: NaiveDate) -> Task {
+    let mut task = Task::new(summary, &HashMap::new());
+    let noon = day.and_hms_opt(12, 0, 0).unwrap();
+    task.due = Some(
+        Local
+            .from_local_datetime(&noon)
+            .unwrap()
+            .with_timezone(&Utc),
+    );
+    task
+}
+
+#[test]
+fn test_agenda_groups() {
+    let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+    let day = |n: u64| today + Days::new(n);
+    let mut done_late = due_on("Old chore", today - Days::new(2));
+    done_late.set_status(TaskStatus::Completed);
+    let tasks = vec![
+        due_on("Dentist", day(2)),
+        due_on("Pay rent", today - Days::new(1)),
+        done_late,
+        due_on("Call mum", today),
+        due_on("Holidays", day(20)),
+        Task::new("Someday", &HashMap::new()),
+    ];
+
+    // A week: open overdue tasks, then every day even when empty
+    let week = agenda_groups(tasks.clone(), today, 7);
+    let titles: Vec<&str> = week.iter().map(|g| g.title.as_str()).collect();
+    assert_eq!(titles.len(), 8);
+    assert_eq!(titles[0], AGENDA_OVERDUE);
+    assert_eq!(titles[1], "Today, Mon 10 Mar");
+    assert_eq!(titles[3], "Wed 12 Mar");
+    assert_eq!(week[0].tasks.len(), 1);
+    assert_eq!(week[0].tasks[0].summary, "Pay rent");
+    assert_eq!(week[1].tasks[0].summary, "Call mum");
+    assert_eq!(week[3].tasks[0].summary, "Dentist");
+    assert!(week[2].tasks.is_empty());
+
+    // A month only lists today and the busy days
+    let month = agenda_groups(tasks, today, 31);
+    let titles: Vec<&str> = month.iter().map(|g| g.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            AGENDA_OVERDUE,
+            "Today, Mon 10 Mar",
+            "Wed 12 Mar",
+            "Sun 30 Mar"
+        ]
+    );
+}
@@ -0,0 +1,85 @@
+// File: ./tests/backup_restore.rs
+use cfait::backup::Backup;
+use cfait::cache::Cache;
+use cfait::config::Config;
+use cfait::journal::{Action, Journal};
+use cfait::model::{CalendarListEntry, Task};
+use cfait::storage::LocalStorage;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+fn task(summary: &str, cal: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.calendar_href = cal.to_string();
+    t
+}
+
+#[test]
+fn test_backup_restores_on_a_new_machine() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_backup_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    let file = env::temp_dir().join(format!("cfait_test_backup_{}.json", std::process::id()));
+
+    // 1. A local calendar, a synced server calendar and a change not uploaded yet
+    let garden = LocalStorage::create_calendar("Garden").unwrap();
+    LocalStorage::save_calendar(&garden.href, &[task("Plant tomatoes", &garden.href)]).unwrap();
+    let work = CalendarListEntry {
+        name: "Work".to_string(),
+        href: "/work/".to_string(),
+        color: None,
+        read_only: false,
+        owner: None,
+        shared: false,
+        account: None,
+    };
+    Cache::save_calendars(std::slice::from_ref(&work)).unwrap();
+    let report = task("Send report", "/work/");
+    Cache::save("/work/", std::slice::from_ref(&report), None).unwrap();
+    Journal::push(Action::Update(report.clone())).unwrap();
+    let mut config = Config::default();
+    config
+        .tag_aliases
+        .insert("veg".to_string(), vec!["garden".to_string()]);
+
+    Backup::collect(&config).unwrap().write(&file).unwrap();
+
+    // 2. Everything is gone...
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    assert_eq!(LocalStorage::calendars().len(), 1);
+
+    // 3. ...and comes back, with the same hrefs
+    let mut fresh = Config::default();
+    let summary = Backup::read(&file).unwrap().restore(&mut fresh).unwrap();
+    assert_eq!(summary.tasks, 2);
+    assert_eq!(summary.pending, 1);
+    assert_eq!(summary.aliases, 1);
+    assert!(LocalStorage::calendar_exists(&garden.href));
+    assert_eq!(LocalStorage::load_calendar(&garden.href).unwrap().len(), 1);
+    assert_eq!(Cache::load("/work/").unwrap().0, vec![report]);
+    let cached: Vec<String> = Cache::load_calendars()
+        .unwrap()
+        .into_iter()
+        .map(|c| c.href)
+        .collect();
+    assert_eq!(cached, vec![work.href]);
+    assert_eq!(fresh.tag_aliases["veg"], vec!["garden".to_string()]);
+
+    // 4. Restoring twice doesn't duplicate anything
+    let again = Backup::read(&file).unwrap().restore(&mut fresh).unwrap();
+    assert_eq!((again.pending, again.aliases), (0, 0));
+    assert_eq!(Journal::load().unwrap().queue.len(), 1);
+    assert_eq!(LocalStorage::load_calendar(&garden.href).unwrap().len(), 1);
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::remove_file(&file);
+}
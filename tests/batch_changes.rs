@@ -0,0 +1,86 @@
+// File: ./tests/batch_changes.rs
+use cfait::cache::Cache;
+use cfait::client::RustyClient;
+use cfait::journal::{Action, Journal};
+use cfait::model::{CalendarListEntry, Task};
+use cfait::storage::Trash;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+fn calendar(href: &str, read_only: bool) -> CalendarListEntry {
+    CalendarListEntry {
+        name: href.trim_matches('/').to_string(),
+        href: href.to_string(),
+        color: None,
+        read_only,
+        owner: None,
+        shared: false,
+        account: None,
+    }
+}
+
+fn task(summary: &str, href: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.calendar_href = href.to_string();
+    t.href = format!("{}{}.ics", href, t.uid);
+    t
+}
+
+#[tokio::test]
+async fn test_batch_is_queued_whole_or_not_at_all() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_batch_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    Cache::save_calendars(&[calendar("/home/", false), calendar("/shared/", true)]).unwrap();
+    let client = RustyClient::new("", "", "", false).unwrap();
+    client.set_offline(true);
+
+    // 1. The read-only calendar is last in the batch, still nothing is queued
+    let new = task("Water plants", "/home/");
+    let old = task("Old chore", "/home/");
+    let locked = task("Someone else's", "/shared/");
+    let result = client
+        .apply_batch(vec![
+            Action::Create(new.clone()),
+            Action::Delete(old.clone()),
+            Action::Delete(locked),
+        ])
+        .await;
+    assert!(result.is_err());
+    assert!(Journal::load().unwrap().queue.is_empty());
+    assert!(Trash::load().unwrap_or_default().is_empty());
+    assert!(
+        Cache::load("/home/")
+            .map(|c| c.0)
+            .unwrap_or_default()
+            .is_empty()
+    );
+
+    // 2. Without it, all of it goes in at once
+    client
+        .apply_batch(vec![
+            Action::Create(new.clone()),
+            Action::Delete(old.clone()),
+        ])
+        .await
+        .unwrap();
+    let queue = Journal::load().unwrap().queue;
+    assert_eq!(queue.len(), 2);
+    assert!(matches!(&queue[0], Action::Create(t) if t.uid == new.uid));
+    assert!(matches!(&queue[1], Action::Delete(t) if t.uid == old.uid));
+    assert_eq!(Trash::load().unwrap()[0].task.uid, old.uid);
+    let (cached, _) = Cache::load("/home/").unwrap();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(cached[0].uid, new.uid);
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
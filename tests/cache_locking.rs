@@ -0,0 +1,70 @@
+// File: ./tests/cache_locking.rs
+use cfait::cache::Cache;
+use cfait::journal::Action;
+use cfait::model::Task;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+#[test]
+fn test_concurrent_cache_writers() {
+    // 1. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_cache_lock_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    Cache::save_with_ctag("/cal/", &[], Some("token".to_string()), None).unwrap();
+
+    // 2. Writers standing for the TUI, the GUI and a sync, all at the same time
+    let thread_count = 10;
+    let barrier = Arc::new(Barrier::new(thread_count));
+    let handles: Vec<_> = (0..thread_count)
+        .map(|i| {
+            let b = barrier.clone();
+            thread::spawn(move || {
+                let mut task = Task::new(&format!("Task {}", i), &HashMap::new());
+                task.uid = format!("uid-{}", i);
+                task.calendar_href = "/cal/".to_string();
+                b.wait();
+
+                // Each one reads the file, adds its task and writes it back
+                if i % 2 == 0 {
+                    Cache::apply(&Action::Create(task)).unwrap();
+                } else {
+                    Cache::modify("/cal/", |tasks| {
+                        tasks.push(task);
+                        true
+                    })
+                    .unwrap();
+                }
+                // Readers never see a half-written file
+                assert!(Cache::load("/cal/").is_ok());
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    // 3. No write got lost, and the sync token survived
+    let (tasks, token) = Cache::load("/cal/").unwrap();
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+
+    assert_eq!(tasks.len(), thread_count);
+    for i in 0..thread_count {
+        assert!(
+            tasks.iter().any(|t| t.uid == format!("uid-{}", i)),
+            "Cache missing uid-{}",
+            i
+        );
+    }
+    assert_eq!(token.as_deref(), Some("token"));
+}
@@ -0,0 +1,65 @@
+// File: ./tests/cache_maintenance.rs
+use cfait::cache::Cache;
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::time::Duration;
+
+fn task(summary: &str, cal: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.calendar_href = cal.to_string();
+    t.href = format!("{}{}.ics", cal, t.uid);
+    t
+}
+
+#[test]
+fn test_clear_and_prune_keep_pending_changes() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_cache_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    let home = vec![task("Fix the sink", "/home/"), task("Call mum", "/home/")];
+    let work = vec![task("Send report", "/work/")];
+    Cache::save_with_ctag("/home/", &home, Some("token-1".into()), Some("1".into())).unwrap();
+    Cache::save_with_ctag("/work/", &work, Some("token-2".into()), Some("2".into())).unwrap();
+
+    let stats = Cache::stats().unwrap();
+    assert_eq!(stats.calendars, 2);
+    assert_eq!(stats.tasks, 3);
+    assert!(stats.bytes > 0 && stats.oldest.is_some());
+
+    // 1. Nothing is old or big enough to prune
+    assert_eq!(
+        Cache::prune(Duration::from_secs(3600), u64::MAX).unwrap(),
+        0
+    );
+
+    // 2. An unsynced edit keeps its calendar cache, only its sync state is reset
+    let mut edited = home[0].clone();
+    edited.summary = "Fix the sink today".to_string();
+    Journal::push(Action::Update(edited)).unwrap();
+    assert_eq!(Cache::prune(Duration::ZERO, 0).unwrap(), 1);
+    assert_eq!(Cache::load("/work/").unwrap().0.len(), 0);
+
+    Cache::save_with_ctag("/work/", &work, None, None).unwrap();
+    assert_eq!(Cache::clear().unwrap(), 2);
+    let (tasks, token) = Cache::load("/home/").unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(token, None);
+    assert_eq!(Cache::load_ctag("/home/"), None);
+    assert_eq!(Cache::stats().unwrap().calendars, 1);
+
+    // 3. The journal itself is untouched
+    assert_eq!(Journal::load().unwrap().queue.len(), 1);
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
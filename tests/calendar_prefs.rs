@@ -0,0 +1,94 @@
+// File: ./tests/calendar_prefs.rs
+use cfait::config::{CalendarPrefs, Config};
+use cfait::model::{CalendarListEntry, SortOrder, Task, TaskStatus};
+use cfait::store::{FilterOptions, TaskStore};
+use std::collections::{HashMap, HashSet};
+
+const CONFIG: &str = r##"
+url = ""
+username = ""
+password = ""
+hide_completed = false
+
+[tag_aliases]
+meeting = ["work", "calls"]
+
+[calendars.Work]
+default_tags = ["#meeting"]
+hide_completed = true
+sort_order = "priority"
+
+[calendars."/cal/home/"]
+color = "#00aa00"
+"##;
+
+fn calendar(name: &str, href: &str) -> CalendarListEntry {
+    CalendarListEntry {
+        name: name.to_string(),
+        href: href.to_string(),
+        color: Some("#ffffff".to_string()),
+        read_only: false,
+        owner: None,
+        shared: false,
+        account: None,
+    }
+}
+
+fn done(summary: &str, href: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.calendar_href = href.to_string();
+    t.set_status(TaskStatus::Completed);
+    t
+}
+
+#[test]
+fn test_calendar_sections_by_name_or_href() {
+    let cfg: Config = toml::from_str(CONFIG).unwrap();
+    let mut calendars = vec![
+        calendar("Work", "/cal/work/"),
+        calendar("Home", "/cal/home/"),
+    ];
+
+    // 1. Sections match by name or by href
+    let work = CalendarPrefs::find(&cfg.calendars, &calendars, "/cal/work/").unwrap();
+    assert_eq!(work.sort_order, Some(SortOrder::Priority));
+    CalendarPrefs::apply_colors(&cfg.calendars, &mut calendars);
+    assert_eq!(calendars[0].color.as_deref(), Some("#ffffff"));
+    assert_eq!(calendars[1].color.as_deref(), Some("#00aa00"));
+
+    // 2. New tasks get the default tags, aliases included
+    let mut task = Task::new("Call the bank #money", &cfg.tag_aliases);
+    work.tag_new_task(&mut task, &cfg.tag_aliases);
+    assert_eq!(task.categories, vec!["money", "meeting", "work", "calls"]);
+
+    // 3. Completed tasks are hidden in Work only
+    let mut store = TaskStore::new();
+    store.insert(
+        "/cal/work/".to_string(),
+        vec![done("Old report", "/cal/work/")],
+    );
+    store.insert(
+        "/cal/home/".to_string(),
+        vec![done("Old chore", "/cal/home/")],
+    );
+    let hide_completed_in = CalendarPrefs::hide_completed_by_href(&cfg.calendars, &calendars);
+    let shown = store.filter(FilterOptions {
+        active_cal_href: None,
+        hidden_calendars: &HashSet::new(),
+        selected_categories: &HashSet::new(),
+        match_all_categories: false,
+        search_term: "",
+        calendars: &calendars,
+        hide_completed_global: cfg.hide_completed,
+        hide_completed_in: &hide_completed_in,
+        cutoff_date: None,
+        min_duration: None,
+        max_duration: None,
+        include_unset_duration: true,
+        sort_order: SortOrder::Smart,
+        selected_context: None,
+        smart_list: None,
+    });
+    let summaries: Vec<&str> = shown.iter().map(|t| t.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["Old chore"]);
+}
@@ -0,0 +1,78 @@
+// File: ./tests/config_check.rs
+use cfait::config_check::check;
+
+fn keys(contents: &str) -> Vec<(String, bool)> {
+    check(contents)
+        .into_iter()
+        .map(|p| (p.key, p.warning))
+        .collect()
+}
+
+#[test]
+fn test_valid_config_has_no_problems() {
+    let contents = r##"
+url = "dav.example.com"
+username = "me"
+password = "secret"
+hide_completed = true
+
+[retry]
+max_attempts = 5
+
+[calendars.Work]
+color = "#ff0000"
+
+[[accounts]]
+label = "Work"
+url = "https://work.example.com/dav/"
+"##;
+    assert_eq!(check(contents), vec![]);
+}
+
+#[test]
+fn test_problems_are_all_listed() {
+    let contents = r##"
+url = "ftp://dav.example.com/"
+username = "me"
+password = "secret"
+auth = "bearer"
+hide_complete = true
+client_cert = "/etc/cfait/me.pem"
+
+[theme]
+selction = "#3465A4"
+
+[[accounts]]
+url = "work.example.com/dav/"
+"##;
+    let problems = check(contents);
+    assert_eq!(
+        keys(contents),
+        vec![
+            ("hide_complete".to_string(), true),
+            ("theme.selction".to_string(), true),
+            ("url".to_string(), false),
+            ("client_key".to_string(), false),
+            ("bearer_token".to_string(), false),
+            ("password".to_string(), true),
+            ("accounts[0].label".to_string(), false),
+            ("accounts[0].url".to_string(), false),
+        ]
+    );
+    // Typos point to the key that was meant
+    assert_eq!(problems[0].hint, "did you mean `hide_completed`?");
+    assert_eq!(problems[1].hint, "did you mean `selection`?");
+    assert!(problems[7].hint.contains("https://work.example.com/dav/"));
+}
+
+#[test]
+fn test_syntax_and_type_errors_give_the_line() {
+    let problems = check("url = \"\"\nusername = \"\"\n\nsync_concurrency = \"lots\"\n");
+    assert_eq!(problems.len(), 1);
+    assert!(!problems[0].warning);
+    assert_eq!(problems[0].hint, "line 4");
+
+    let problems = check("url = \"\"\nusername = \n");
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].hint, "line 2");
+}
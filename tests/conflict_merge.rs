@@ -0,0 +1,94 @@
+// File: ./tests/conflict_merge.rs
+use cfait::cache::Cache;
+use cfait::client::RustyClient;
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use mockito::{Matcher, Server};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[tokio::test]
+async fn test_412_merges_non_overlapping_fields() {
+    let temp_dir = env::temp_dir().join(format!("cfait_test_merge_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    // Base: what we last fetched from the server
+    let mut base = Task::new("Base Title", &HashMap::new());
+    base.uid = "merge-uid".to_string();
+    base.calendar_href = "/cal/".to_string();
+    base.href = "/cal/merge-uid.ics".to_string();
+    base.description = "Base Description".to_string();
+    base.etag = "old-etag".to_string();
+    Cache::save_with_ctag("/cal/", std::slice::from_ref(&base), None, None).unwrap();
+
+    // Local edit: title only (cached optimistically, like the UIs do)
+    let mut local = base.clone();
+    local.summary = "Local Title".to_string();
+    Cache::save("/cal/", std::slice::from_ref(&local), None).unwrap();
+    Journal::push(Action::Update(local)).unwrap();
+
+    // Remote edit: description only
+    let mut remote = base.clone();
+    remote.description = "Server Description".to_string();
+    let multiget = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/cal/merge-uid.ics</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:getetag>new-etag</d:getetag>
+        <c:calendar-data>{}</c:calendar-data>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#,
+        remote.to_ics()
+    );
+
+    let mock_412 = server
+        .mock("PUT", "/cal/merge-uid.ics")
+        .match_header("If-Match", "old-etag")
+        .with_status(412)
+        .create_async()
+        .await;
+    let mock_fetch = server
+        .mock("REPORT", "/cal/")
+        .with_status(207)
+        .with_header("Content-Type", "application/xml; charset=utf-8")
+        .with_body(multiget)
+        .create_async()
+        .await;
+    let mock_merged = server
+        .mock("PUT", "/cal/merge-uid.ics")
+        .match_header("If-Match", "new-etag")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex("Local Title".to_string()),
+            Matcher::Regex("Server Description".to_string()),
+        ]))
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = RustyClient::new(&url, "user", "pass", true).unwrap();
+    let warnings = client.sync_journal().await.expect("Sync should succeed");
+
+    mock_412.assert();
+    mock_fetch.assert();
+    mock_merged.assert();
+    assert!(warnings.iter().any(|w| w.contains("3-way merge")));
+    assert!(Journal::load().unwrap().is_empty());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
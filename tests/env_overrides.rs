@@ -0,0 +1,74 @@
+// File: ./tests/env_overrides.rs
+use cfait::config::Config;
+use cfait::paths::AppPaths;
+use std::env;
+use std::fs;
+
+#[test]
+fn test_environment_overrides_the_config_file() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_env_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    let path = AppPaths::get_config_file_path().unwrap();
+
+    // 1. No file, no variables: not configured
+    assert!(Config::load().is_err());
+
+    // 2. The environment alone is enough
+    unsafe {
+        env::set_var("CFAIT_URL", "https://dav.example.com/");
+        env::set_var("CFAIT_PASSWORD", "s3cret");
+        env::set_var("CFAIT_HIDE_COMPLETED", "true");
+        env::set_var("CFAIT_RETRY__MAX_ATTEMPTS", "7");
+        // Looks like a number, taken as a string since the field is one
+        env::set_var("CFAIT_DEFAULT_CALENDAR", "2024");
+    }
+    let cfg = Config::load().unwrap();
+    assert_eq!(cfg.url, "https://dav.example.com/");
+    assert_eq!(cfg.password, "s3cret");
+    assert!(cfg.hide_completed);
+    assert_eq!(cfg.retry.max_attempts, 7);
+    assert_eq!(cfg.default_calendar.as_deref(), Some("2024"));
+
+    // 3. Variables win over the file, and saving doesn't write them into it
+    fs::write(
+        &path,
+        "url = \"https://old.example.com/\"\nusername = \"me\"\npassword = \"\"\n",
+    )
+    .unwrap();
+    let mut cfg = Config::load().unwrap();
+    assert_eq!(cfg.url, "https://dav.example.com/");
+    assert_eq!(cfg.username, "me");
+    cfg.username = "someone".to_string();
+    cfg.save().unwrap();
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(written.contains("someone"));
+    assert!(written.contains("https://old.example.com/"));
+    assert!(!written.contains("s3cret"));
+
+    // 4. A value the field can't take names the variable
+    unsafe {
+        env::set_var("CFAIT_SYNC_CONCURRENCY", "lots");
+    }
+    let err = Config::load().unwrap_err().to_string();
+    assert!(err.contains("CFAIT_SYNC_CONCURRENCY"));
+
+    unsafe {
+        for var in [
+            "CFAIT_URL",
+            "CFAIT_PASSWORD",
+            "CFAIT_HIDE_COMPLETED",
+            "CFAIT_RETRY__MAX_ATTEMPTS",
+            "CFAIT_DEFAULT_CALENDAR",
+            "CFAIT_SYNC_CONCURRENCY",
+            "CFAIT_TEST_DIR",
+        ] {
+            env::remove_var(var);
+        }
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,60 @@
+// File: ./tests/journal_accounts.rs
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+fn task(summary: &str, cal: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.calendar_href = cal.to_string();
+    t.href = format!("{}{}.ics", cal, t.uid);
+    t
+}
+
+#[test]
+fn test_accounts_replay_independently() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_accounts_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+
+    let home = task("Fix the bike", "/home/");
+    let work = task("Send report", "/work/");
+    Journal::push(Action::Create(home.clone())).unwrap();
+    Journal::push_for(Some("Work"), Action::Create(work)).unwrap();
+    Journal::push(Action::Update(home.clone())).unwrap();
+
+    // 1. Each account sees its own queue
+    let journal = Journal::load().unwrap();
+    assert_eq!(journal.queue_for(None).count(), 2);
+    assert_eq!(journal.queue_for(Some("Work")).count(), 1);
+
+    // 2. A failure on one server is only held against that account
+    let failing = journal.queue_for(Some("Work")).next().unwrap().clone();
+    assert_eq!(failing.task().account.as_deref(), Some("Work"));
+    Journal::record_failure(Some("Work"), &failing, "503 Service Unavailable", true).unwrap();
+    let journal = Journal::load().unwrap();
+    let (first, failure) = journal.blocking(Some("Work")).unwrap();
+    assert_eq!(first, &failing);
+    assert_eq!(failure.unwrap().error, "503 Service Unavailable");
+    let (first, failure) = journal.blocking(None).unwrap();
+    assert_eq!(first.task().uid, home.uid);
+    assert!(failure.is_none());
+
+    // 3. Dropping the change forgets its failure
+    assert!(Journal::remove(&failing).unwrap());
+    let journal = Journal::load().unwrap();
+    assert!(journal.blocking(Some("Work")).is_none());
+    assert!(journal.failure_of(Some("Work")).is_none());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
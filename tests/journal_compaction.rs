@@ -0,0 +1,59 @@
+// File: ./tests/journal_compaction.rs
+use cfait::journal::{Action, compact};
+use cfait::model::Task;
+use std::collections::HashMap;
+
+fn task(summary: &str, uid: &str) -> Task {
+    let mut t = Task::new(summary, &HashMap::new());
+    t.uid = uid.to_string();
+    t.calendar_href = "/cal/".to_string();
+    t.href = format!("/cal/{}.ics", uid);
+    t
+}
+
+#[test]
+fn test_compact_merges_redundant_actions() {
+    let mut a = task("Water plants", "a");
+    a.etag = "\"1\"".to_string();
+    let b = task("Pay rent", "b");
+
+    // Toggled three times offline: one request
+    let mut queue = vec![
+        Action::Update(a.clone()),
+        Action::Create(b.clone()),
+        Action::Update(task("Water plants (done)", "a")),
+        Action::Update(task("Water plants (again)", "a")),
+    ];
+    compact(&mut queue);
+    assert_eq!(queue.len(), 2);
+    match &queue[0] {
+        Action::Update(t) => {
+            assert_eq!(t.summary, "Water plants (again)");
+            // Still aimed at the version the server has
+            assert_eq!(t.etag, "\"1\"");
+        }
+        other => panic!("Expected an update, got {:?}", other),
+    }
+
+    // Created then edited: the create carries the edit
+    queue.push(Action::Update(task("Pay rent today", "b")));
+    compact(&mut queue);
+    assert!(matches!(&queue[1], Action::Create(t) if t.summary == "Pay rent today"));
+
+    // Created then deleted: never sent. Edited then deleted: only the delete
+    queue.push(Action::Delete(b));
+    queue.push(Action::Delete(a));
+    compact(&mut queue);
+    assert_eq!(queue.len(), 1);
+    assert!(matches!(&queue[0], Action::Delete(t) if t.uid == "a"));
+
+    // A move keeps the edits on both sides apart
+    let c = task("Book flights", "c");
+    let mut queue = vec![
+        Action::Update(c.clone()),
+        Action::Move(c.clone(), "/other/".to_string()),
+        Action::Update(c),
+    ];
+    compact(&mut queue);
+    assert_eq!(queue.len(), 3);
+}
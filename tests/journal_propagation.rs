@@ -78,7 +78,7 @@ async fn test_move_propagates_href_to_pending_update() {
     mock_move.assert();
     mock_update_at_new_loc.assert();
 
-    let j = Journal::load();
+    let j = Journal::load().unwrap();
     assert!(j.is_empty(), "Journal should be empty");
 
     // CLEANUP
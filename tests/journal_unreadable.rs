@@ -0,0 +1,38 @@
+// File: ./tests/journal_unreadable.rs
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[test]
+fn test_unreadable_journal_is_an_error() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_unreadable_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    // 1. No journal yet: nothing pending
+    assert!(Journal::load().unwrap().is_empty());
+
+    // 2. A damaged one isn't read as an empty queue...
+    let path = Journal::get_path().unwrap();
+    fs::write(&path, "{ \"queue\": [ truncated").unwrap();
+    assert!(Journal::load().is_err());
+
+    // 3. ...nor overwritten by the next change
+    let task = Task::new("Water plants", &HashMap::new());
+    assert!(Journal::push(Action::Create(task)).is_err());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{ \"queue\": [ truncated"
+    );
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,75 @@
+// File: ./tests/local_calendars.rs
+use cfait::client::RustyClient;
+use cfait::model::Task;
+use cfait::storage::{LOCAL_CALENDAR_HREF, LocalStorage, is_local_calendar};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[tokio::test]
+async fn test_named_local_calendars() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_local_cals_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    // 1. The default calendar is always there, new ones come after it
+    let groceries = LocalStorage::create_calendar("Groceries").unwrap();
+    assert!(is_local_calendar(&groceries.href));
+    assert!(LocalStorage::create_calendar("groceries").is_err());
+    assert!(LocalStorage::create_calendar("  ").is_err());
+    let hrefs: Vec<String> = LocalStorage::calendars()
+        .into_iter()
+        .map(|c| c.href)
+        .collect();
+    assert_eq!(
+        hrefs,
+        vec![LOCAL_CALENDAR_HREF.to_string(), groceries.href.clone()]
+    );
+
+    // 2. Each calendar keeps its tasks in its own file
+    let client = RustyClient::new("", "", "", false).unwrap();
+    let mut task = Task::new("Buy bread", &HashMap::new());
+    task.calendar_href = groceries.href.clone();
+    client.create_task(&mut task).await.unwrap();
+    assert_eq!(
+        LocalStorage::load_calendar(&groceries.href).unwrap().len(),
+        1
+    );
+    assert!(LocalStorage::load().unwrap().is_empty());
+
+    // 3. Renaming keeps the href, and the tasks
+    LocalStorage::rename_calendar(&groceries.href, "Shopping").unwrap();
+    let renamed = LocalStorage::calendars().pop().unwrap();
+    assert_eq!(
+        (renamed.name.as_str(), renamed.href.as_str()),
+        ("Shopping", groceries.href.as_str())
+    );
+
+    // 4. Tasks move between local calendars
+    let (moved, _) = client.move_task(&task, LOCAL_CALENDAR_HREF).await.unwrap();
+    assert_eq!(moved.calendar_href, LOCAL_CALENDAR_HREF);
+    assert!(
+        LocalStorage::load_calendar(&groceries.href)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(LocalStorage::load().unwrap().len(), 1);
+
+    // 5. Deleting hands back what was left; the default one stays
+    let mut milk = Task::new("Buy milk", &HashMap::new());
+    milk.calendar_href = groceries.href.clone();
+    client.create_task(&mut milk).await.unwrap();
+    let left = LocalStorage::delete_calendar(&groceries.href).unwrap();
+    assert_eq!(left.len(), 1);
+    assert!(!LocalStorage::calendar_exists(&groceries.href));
+    assert!(LocalStorage::delete_calendar(LOCAL_CALENDAR_HREF).is_err());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -45,7 +45,7 @@ fn test_concurrent_journal_writes() {
     }
 
     // 4. Verify Data Integrity
-    let journal = Journal::load();
+    let journal = Journal::load().unwrap();
 
     // Clean up before asserting, so we don't leave trash on failure
     unsafe {
@@ -0,0 +1,70 @@
+// File: ./tests/parallel_sync.rs
+use cfait::client::RustyClient;
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use mockito::Server;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+fn queued_update(uid: &str, cal: &str) -> Task {
+    let mut task = Task::new(&format!("Task {}", uid), &HashMap::new());
+    task.uid = uid.to_string();
+    task.calendar_href = cal.to_string();
+    task.href = format!("{}{}.ics", cal, uid);
+    task.etag = format!("\"{}-1\"", uid);
+    task
+}
+
+#[tokio::test]
+async fn test_parallel_sync_keeps_other_changes_when_action_is_gone() {
+    let temp_dir = env::temp_dir().join(format!("cfait_test_parallel_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+
+    let mut server = Server::new_async().await;
+    // While "a" is uploaded, another sync takes it out of the queue (merged it away)
+    let mock_a = server
+        .mock("PUT", "/cal/a.ics")
+        .with_status(204)
+        .with_header("ETag", "\"a-2\"")
+        .with_body_from_request(|_| {
+            let _ = Journal::modify_journal(|j| j.queue.retain(|a| a.task().uid != "a"));
+            Vec::new()
+        })
+        .create_async()
+        .await;
+    let mock_b = server
+        .mock("PUT", "/cal/b.ics")
+        .with_status(204)
+        .with_header("ETag", "\"b-2\"")
+        .create_async()
+        .await;
+
+    Journal::push(Action::Update(queued_update("a", "/cal/"))).unwrap();
+    Journal::push(Action::Update(queued_update("b", "/cal/"))).unwrap();
+    // Another account's change, after them in the queue: this sync doesn't touch it
+    Journal::push_for(Some("Work"), Action::Update(queued_update("w", "/work/"))).unwrap();
+
+    let client = RustyClient::new(&server.url(), "user", "pass", true).unwrap();
+    let result = client.sync_journal_parallel(2).await;
+    assert!(result.is_ok(), "Sync should succeed: {:?}", result.err());
+    mock_a.assert();
+    mock_b.assert();
+
+    // Only the other account's change is left, not lost to a stale index
+    let j = Journal::load().unwrap();
+    let left: Vec<&str> = j.queue.iter().map(|a| a.task().uid.as_str()).collect();
+    assert_eq!(left, ["w"]);
+    assert_eq!(j.account_of(&j.queue[0]), Some("Work"));
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,68 @@
+// File: ./tests/replay_merge.rs
+use cfait::client::RustyClient;
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use mockito::{Matcher, Server};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[tokio::test]
+async fn test_merged_create_is_sent_as_an_update() {
+    let temp_dir = env::temp_dir().join(format!("cfait_test_replay_merge_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+
+    let mut task = Task::new("New task", &HashMap::new());
+    task.uid = "n".to_string();
+    task.calendar_href = "/cal/".to_string();
+
+    let mut server = Server::new_async().await;
+    // While the task is created, an edit of it gets merged into the queued Create
+    let mock_create = server
+        .mock("PUT", "/cal/n.ics")
+        .match_header("If-Match", Matcher::Missing)
+        .with_status(201)
+        .with_header("ETag", "\"n-1\"")
+        .with_body_from_request(|_| {
+            let _ = Journal::modify_journal(|j| {
+                for a in j.queue.iter_mut() {
+                    if let Action::Create(t) = a {
+                        t.summary = "New task, edited".to_string();
+                    }
+                }
+            });
+            Vec::new()
+        })
+        .expect(1)
+        .create_async()
+        .await;
+    // The edit goes out against the version just uploaded
+    let mock_update = server
+        .mock("PUT", "/cal/n.ics")
+        .match_header("If-Match", Matcher::Regex("n-1".to_string()))
+        .match_body(Matcher::Regex("New task, edited".to_string()))
+        .with_status(204)
+        .with_header("ETag", "\"n-2\"")
+        .create_async()
+        .await;
+
+    Journal::push(Action::Create(task)).unwrap();
+
+    let client = RustyClient::new(&server.url(), "user", "pass", true).unwrap();
+    let result = client.sync_journal().await;
+    assert!(result.is_ok(), "Sync should succeed: {:?}", result.err());
+    mock_create.assert();
+    mock_update.assert();
+    assert!(Journal::load().unwrap().queue.is_empty());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -1,5 +1,7 @@
 // File: ./tests/sync_edge_cases.rs
+use cfait::cache::Cache;
 use cfait::client::RustyClient;
+use cfait::config::RetryPolicy;
 use cfait::journal::{Action, Journal};
 use cfait::model::Task;
 use mockito::Server;
@@ -72,7 +74,7 @@ async fn test_sync_delete_404_is_success() {
     mock.assert();
 
     // Item should be removed from journal
-    let j = Journal::load();
+    let j = Journal::load().unwrap();
     assert!(j.is_empty(), "Journal should be empty after 404 delete");
 
     teardown(temp_dir);
@@ -91,6 +93,8 @@ async fn test_sync_500_keeps_item_in_queue() {
     let mock = server
         .mock("PUT", "/cal/task.ics")
         .with_status(500)
+        // 5xx is transient: the client retries before giving up
+        .expect(RetryPolicy::default().max_attempts as usize)
         .create_async()
         .await;
 
@@ -112,7 +116,7 @@ async fn test_sync_500_keeps_item_in_queue() {
     mock.assert();
 
     // Item should REMAIN in journal because it failed
-    let j = Journal::load();
+    let j = Journal::load().unwrap();
     assert!(
         !j.is_empty(),
         "Journal should still contain the failed item"
@@ -121,3 +125,221 @@ async fn test_sync_500_keeps_item_in_queue() {
 
     teardown(temp_dir);
 }
+
+#[tokio::test]
+async fn test_sync_waits_for_retry_after() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let temp_dir = setup_env("retry_after");
+
+    // 1. Server is overloaded once, then accepts the PUT.
+    // Mockito uses the first mock that still expects hits.
+    let mut server = Server::new_async().await;
+    let url = server.url();
+    let busy = server
+        .mock("PUT", "/cal/task.ics")
+        .with_status(503)
+        .with_header("Retry-After", "1")
+        .expect(1)
+        .create_async()
+        .await;
+    let ok = server
+        .mock("PUT", "/cal/task.ics")
+        .with_status(201)
+        .with_header("ETag", "\"new\"")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // 2. Setup Client
+    let client = RustyClient::new(&url, "u", "p", true).unwrap();
+
+    let mut task = Task::new("T", &HashMap::new());
+    task.uid = "task".to_string();
+    task.calendar_href = "/cal/".to_string();
+    Journal::push(Action::Create(task)).unwrap();
+
+    // 3. Sync pauses for the requested second (regular backoff would be shorter)
+    let started = std::time::Instant::now();
+    let res = client.sync_journal().await;
+    assert!(res.is_ok(), "Sync failed: {:?}", res.err());
+    assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+    busy.assert();
+    ok.assert();
+    assert!(Journal::load().unwrap().is_empty());
+
+    teardown(temp_dir);
+}
+
+#[tokio::test]
+async fn test_offline_mode_queues_until_sync() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let temp_dir = setup_env("offline");
+
+    let mut server = Server::new_async().await;
+    let url = server.url();
+    let mock = server
+        .mock("PUT", "/cal/task.ics")
+        .with_status(201)
+        .with_header("ETag", "\"new\"")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = RustyClient::new(&url, "u", "p", true).unwrap();
+    client.set_offline(true);
+
+    // 1. In offline mode the change only lands in the journal
+    let mut task = Task::new("T", &HashMap::new());
+    task.uid = "task".to_string();
+    task.calendar_href = "/cal/".to_string();
+    let res = client.create_task(&mut task).await;
+    assert!(res.is_ok(), "Create failed: {:?}", res.err());
+    assert_eq!(Journal::load().unwrap().queue.len(), 1);
+
+    // 2. A manual sync still flushes it
+    let res = client.sync_journal().await;
+    assert!(res.is_ok(), "Sync failed: {:?}", res.err());
+    mock.assert();
+    assert!(Journal::load().unwrap().is_empty());
+
+    teardown(temp_dir);
+}
+
+#[tokio::test]
+async fn test_put_etag_is_cached() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let temp_dir = setup_env("put_etag");
+
+    let mut server = Server::new_async().await;
+    let url = server.url();
+    let mock = server
+        .mock("PUT", "/cal/task.ics")
+        .with_status(201)
+        .with_header("ETag", "\"new\"")
+        .create_async()
+        .await;
+
+    let client = RustyClient::new(&url, "u", "p", true).unwrap();
+
+    // 1. The UI caches the new task before it reaches the server
+    let mut task = Task::new("T", &HashMap::new());
+    task.uid = "task".to_string();
+    task.calendar_href = "/cal/".to_string();
+    Cache::save("/cal/", std::slice::from_ref(&task), None).unwrap();
+    Journal::push(Action::Create(task)).unwrap();
+
+    // 2. After the upload, the cached copy carries the server's href and ETag
+    let res = client.sync_journal().await;
+    assert!(res.is_ok(), "Sync failed: {:?}", res.err());
+    mock.assert();
+    let (cached, _) = Cache::load("/cal/").unwrap();
+    assert_eq!(cached[0].href, "/cal/task.ics");
+    assert_eq!(cached[0].etag, "\"new\"");
+
+    teardown(temp_dir);
+}
+
+#[tokio::test]
+async fn test_move_falls_back_to_put_and_delete() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let temp_dir = setup_env("move_fallback");
+
+    let mut server = Server::new_async().await;
+    let url = server.url();
+
+    // 1. The server doesn't implement MOVE
+    let mock_move = server
+        .mock("MOVE", "/cal1/task.ics")
+        .with_status(405)
+        .create_async()
+        .await;
+    let mock_put = server
+        .mock("PUT", "/cal2/task.ics")
+        .with_status(201)
+        .with_header("ETag", "\"copied\"")
+        .create_async()
+        .await;
+    let mock_delete = server
+        .mock("DELETE", "/cal1/task.ics")
+        .match_header("If-Match", "\"orig\"")
+        .with_status(204)
+        .create_async()
+        .await;
+
+    let client = RustyClient::new(&url, "u", "p", true).unwrap();
+
+    let mut task = Task::new("T", &HashMap::new());
+    task.uid = "task".to_string();
+    task.calendar_href = "/cal1/".to_string();
+    task.href = "/cal1/task.ics".to_string();
+    task.etag = "\"orig\"".to_string();
+    Journal::push(Action::Move(task, "/cal2/".to_string())).unwrap();
+
+    // 2. The move still goes through, with a copy and a conditional delete
+    let res = client.sync_journal().await;
+    assert!(res.is_ok(), "Sync failed: {:?}", res.err());
+    mock_move.assert();
+    mock_put.assert();
+    mock_delete.assert();
+    assert!(Journal::load().unwrap().is_empty());
+
+    teardown(temp_dir);
+}
+
+#[tokio::test]
+async fn test_sync_403_parks_action_and_continues() {
+    let _guard = TEST_MUTEX.lock().unwrap();
+
+    let temp_dir = setup_env("403");
+
+    // 1. Read-only calendar rejects the first task, the second one goes through
+    let mut server = Server::new_async().await;
+    let url = server.url();
+    let rejected = server
+        .mock("PUT", "/ro/a.ics")
+        .with_status(403)
+        .create_async()
+        .await;
+    let accepted = server
+        .mock("PUT", "/cal/b.ics")
+        .with_status(201)
+        .with_header("ETag", "\"b1\"")
+        .create_async()
+        .await;
+
+    let client = RustyClient::new(&url, "u", "p", true).unwrap();
+
+    let mut a = Task::new("A", &HashMap::new());
+    a.uid = "a".to_string();
+    a.calendar_href = "/ro/".to_string();
+    let mut b = Task::new("B", &HashMap::new());
+    b.uid = "b".to_string();
+    b.calendar_href = "/cal/".to_string();
+    Journal::push(Action::Create(a)).unwrap();
+    Journal::push(Action::Create(b)).unwrap();
+
+    // 2. The 403 is permanent: parked, and the queue keeps going
+    let res = client.sync_journal().await;
+    assert!(res.is_ok(), "Sync failed: {:?}", res.err());
+    rejected.assert();
+    accepted.assert();
+
+    let j = Journal::load().unwrap();
+    assert!(j.is_empty());
+    assert_eq!(j.failed.len(), 1);
+    assert_eq!(j.failed[0].action.task().uid, "a");
+    assert!(j.health_warning(0, 0, chrono::Utc::now()).is_some());
+
+    // 3. Retrying puts it back in the queue
+    let parked = j.failed[0].clone();
+    assert!(Journal::retry_failed(&parked).unwrap());
+    let j = Journal::load().unwrap();
+    assert_eq!(j.queue.len(), 1);
+    assert!(j.failed.is_empty());
+
+    teardown(temp_dir);
+}
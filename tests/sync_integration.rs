@@ -74,7 +74,7 @@ async fn test_sync_recovers_from_412() {
     mock_conflict_copy.assert();
 
     // Ensure Journal is empty
-    let j = cfait::journal::Journal::load();
+    let j = cfait::journal::Journal::load().unwrap();
     assert!(
         j.is_empty(),
         "Journal should be empty after successful sync"
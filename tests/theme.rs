@@ -0,0 +1,46 @@
+// File: ./tests/theme.rs
+use cfait::config::{Config, ThemeBase};
+use cfait::model::{Task, TaskStatus};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
+
+#[test]
+fn test_theme_section() {
+    let cfg: Config = toml::from_str(
+        r##"
+url = ""
+username = ""
+password = ""
+
+[theme]
+base = "light"
+priority = ["#000080", "not a color"]
+due_soon_hours = 48
+"##,
+    )
+    .unwrap();
+    let theme = &cfg.theme;
+    assert_eq!(theme.base, ThemeBase::Light);
+
+    // 1. Set, broken and missing priorities: the last two keep the defaults
+    assert_eq!(theme.priority_rgb(1), Some((0, 0, 128)));
+    assert_eq!(theme.priority_rgb(2), Some((255, 102, 51)));
+    assert_eq!(theme.priority_rgb(9), Some((153, 140, 166)));
+    assert_eq!(theme.priority_rgb(0), None);
+    assert_eq!(theme.selection_rgb(), None);
+
+    // 2. Due soon: open tasks due within due_soon_hours, or overdue
+    let now = Utc::now();
+    let mut task = Task::new("Renew passport", &HashMap::new());
+    assert!(!theme.is_due_soon(&task, now));
+    task.due = Some(now + Duration::hours(30));
+    assert!(theme.is_due_soon(&task, now));
+    task.due = Some(now - Duration::days(3));
+    assert!(theme.is_due_soon(&task, now));
+    task.set_status(TaskStatus::Completed);
+    assert!(!theme.is_due_soon(&task, now));
+
+    // 3. An untouched theme isn't written out
+    let written = toml::to_string(&Config::default()).unwrap();
+    assert!(!written.contains("[theme]"));
+}
@@ -0,0 +1,47 @@
+// File: ./tests/trash.rs
+use cfait::client::RustyClient;
+use cfait::model::Task;
+use cfait::storage::{LOCAL_CALENDAR_HREF, LocalStorage, Trash};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[tokio::test]
+async fn test_delete_and_undelete_local_task() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_trash_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    // No server: only the Local calendar is used
+    let client = RustyClient::new("", "", "", false).unwrap();
+
+    let mut task = Task::new("Buy milk", &HashMap::new());
+    task.calendar_href = LOCAL_CALENDAR_HREF.to_string();
+    client.create_task(&mut task).await.unwrap();
+
+    // 1. Deleting keeps a copy in the trash
+    client.delete_task(&task).await.unwrap();
+    assert!(LocalStorage::load().unwrap().is_empty());
+    let trash = Trash::load().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].calendar_href, LOCAL_CALENDAR_HREF);
+
+    // 2. Undelete puts it back where it was
+    let (restored, _) = client.undelete(&task.uid).await.unwrap();
+    assert_eq!(restored.summary, "Buy milk");
+    let local = LocalStorage::load().unwrap();
+    assert_eq!(local.len(), 1);
+    assert_eq!(local[0].uid, task.uid);
+    assert!(Trash::load().unwrap().is_empty());
+
+    // 3. Unknown tasks can't be restored
+    assert!(client.undelete(&task.uid).await.is_err());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,91 @@
+// File: ./tests/undo.rs
+use cfait::journal::{Action, Journal};
+use cfait::model::Task;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[test]
+fn test_revert_last_queues_inverse() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_undo_{}", std::process::id()));
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+    assert_eq!(Journal::revert_last().unwrap(), None);
+
+    let mut task = Task::new("Call the bank", &HashMap::new());
+    task.calendar_href = "/cal/".to_string();
+    task.href = format!("/cal/{}.ics", task.uid);
+
+    // 1. An update goes back to the version queued before it
+    Journal::push(Action::Create(task.clone())).unwrap();
+    let mut edited = task.clone();
+    edited.summary = "Call the bank again".to_string();
+    Journal::push(Action::Update(edited.clone())).unwrap();
+
+    match Journal::revert_last().unwrap() {
+        Some(Action::Update(restored)) => {
+            assert_eq!(restored.summary, "Call the bank");
+            assert_eq!(restored.sequence, edited.sequence + 1);
+        }
+        other => panic!("Expected an update, got {:?}", other),
+    }
+    assert_eq!(Journal::load().unwrap().queue.len(), 3);
+
+    // 2. A delete comes back as a create, and undoing a create deletes
+    Journal::push(Action::Delete(task.clone())).unwrap();
+    assert!(matches!(
+        Journal::revert_last().unwrap(),
+        Some(Action::Create(t)) if t.uid == task.uid
+    ));
+    assert!(matches!(
+        Action::Create(task.clone()).inverse(None),
+        Some(Action::Delete(_))
+    ));
+
+    // 3. A move goes back to the source calendar
+    let back = Action::Move(task.clone(), "/other/".to_string()).inverse(None);
+    match back {
+        Some(Action::Move(t, dest)) => {
+            assert_eq!(dest, "/cal/");
+            assert_eq!(t.href, format!("/other/{}.ics", task.uid));
+        }
+        other => panic!("Expected a move, got {:?}", other),
+    }
+
+    // 4. Undoing again walks back through the queue instead of redoing
+    if let Some(p) = Journal::get_path() {
+        let _ = fs::remove_file(p);
+    }
+    let mut first = task.clone();
+    first.summary = "Call the bank at 9".to_string();
+    let mut second = task.clone();
+    second.summary = "Call the bank at 10".to_string();
+    Journal::push(Action::Create(task.clone())).unwrap();
+    Journal::push(Action::Update(first)).unwrap();
+    Journal::push(Action::Update(second)).unwrap();
+
+    let summaries: Vec<String> = (0..2)
+        .filter_map(|_| match Journal::revert_last().unwrap() {
+            Some(Action::Update(t)) => Some(t.summary),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(summaries, ["Call the bank at 9", "Call the bank"]);
+    assert!(matches!(
+        Journal::revert_last().unwrap(),
+        Some(Action::Delete(t)) if t.uid == task.uid
+    ));
+    assert_eq!(Journal::revert_last().unwrap(), None);
+    assert_eq!(Journal::load().unwrap().queue.len(), 6);
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,70 @@
+// File: ./tests/vdir_storage.rs
+use cfait::client::RustyClient;
+use cfait::model::Task;
+use cfait::storage::{LOCAL_CALENDAR_HREF, LocalStorage};
+use cfait::vdir;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+const EVENT: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:meeting\r\n\
+DTSTAMP:20250101T000000Z\r\nDTSTART:20250102T100000Z\r\nSUMMARY:Meeting\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+#[tokio::test]
+async fn test_local_calendars_in_a_vdir() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_vdir_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+    let root = temp_dir.join("tasks");
+    vdir::init(root.to_str());
+    let default_dir = root.join("default");
+
+    // 1. A new task is one .ics file named after its UID
+    let client = RustyClient::new("", "", "", false).unwrap();
+    let mut task = Task::new("Water plants", &HashMap::new());
+    task.calendar_href = LOCAL_CALENDAR_HREF.to_string();
+    client.create_task(&mut task).await.unwrap();
+    let file = default_dir.join(format!("{}.ics", task.uid));
+    assert!(
+        fs::read_to_string(&file)
+            .unwrap()
+            .contains("SUMMARY:Water plants")
+    );
+
+    // 2. Files written by other tools show up; their events are left alone
+    let other = Task::new("Call the plumber", &HashMap::new());
+    fs::write(default_dir.join("from-todoman.ics"), other.to_ics()).unwrap();
+    fs::write(default_dir.join("meeting.ics"), EVENT).unwrap();
+    let tasks = LocalStorage::load().unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert!(tasks.iter().any(|t| t.uid == other.uid));
+
+    // 3. Deleting removes the task's file only
+    client.delete_task(&task).await.unwrap();
+    assert!(!file.exists());
+    assert!(default_dir.join("from-todoman.ics").exists());
+    assert!(default_dir.join("meeting.ics").exists());
+
+    // 4. Other collections are calendars, named by their displayname file
+    fs::create_dir_all(root.join("work")).unwrap();
+    fs::write(root.join("work").join("displayname"), "Work\n").unwrap();
+    let cal = LocalStorage::create_calendar("Groceries").unwrap();
+    let names: Vec<String> = LocalStorage::calendars()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, vec!["Local", "Groceries", "Work"]);
+    LocalStorage::delete_calendar(&cal.href).unwrap();
+    assert_eq!(LocalStorage::calendars().len(), 2);
+
+    vdir::init(None);
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}
@@ -0,0 +1,53 @@
+// File: ./tests/write_through_cache.rs
+use cfait::cache::Cache;
+use cfait::client::RustyClient;
+use cfait::journal::Journal;
+use cfait::model::{Task, TaskStatus};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+#[tokio::test]
+async fn test_offline_changes_reach_the_cache() {
+    // 0. Setup Isolation
+    let temp_dir = env::temp_dir().join(format!("cfait_test_write_through_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&temp_dir);
+    let _ = fs::create_dir_all(&temp_dir);
+    unsafe {
+        env::set_var("CFAIT_TEST_DIR", &temp_dir);
+    }
+
+    let client = RustyClient::new("", "", "", false).unwrap();
+    client.set_offline(true);
+
+    // 1. A new task shows up in the cache before any sync
+    let mut task = Task::new("Water plants", &HashMap::new());
+    task.calendar_href = "/home/".to_string();
+    client.create_task(&mut task).await.unwrap();
+    let (cached, _) = Cache::load("/home/").unwrap();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(cached[0].href, format!("/home/{}.ics", task.uid));
+
+    // 2. Edits too
+    task.status = TaskStatus::Completed;
+    client.update_task(&mut task).await.unwrap();
+    let (cached, _) = Cache::load("/home/").unwrap();
+    assert_eq!(cached[0].status, TaskStatus::Completed);
+
+    // 3. A move leaves the source calendar for the destination
+    let (moved, _) = client.move_task(&task, "/garden/").await.unwrap();
+    assert!(Cache::load("/home/").unwrap().0.is_empty());
+    let (cached, _) = Cache::load("/garden/").unwrap();
+    assert_eq!(cached.len(), 1);
+    assert_eq!(cached[0].calendar_href, "/garden/");
+
+    // 4. Deleting drops it, while the journal still holds everything for the server
+    client.delete_task(&moved).await.unwrap();
+    assert!(Cache::load("/garden/").unwrap().0.is_empty());
+    assert!(!Journal::load().unwrap().queue.is_empty());
+
+    unsafe {
+        env::remove_var("CFAIT_TEST_DIR");
+    }
+    let _ = fs::remove_dir_all(&temp_dir);
+}